@@ -1,7 +1,9 @@
 use core::mem::take;
 use std::sync::Arc;
 
-use super::{hookify, BoxedHook, HookEnv, HookRegistry, SubproofVerifier};
+use super::{
+    hookify, BoxedHook, HookEnv, HookRegistry, SharedHookCache, SubproofVerifier, DEFAULT_HOOK_FDS,
+};
 
 /// Context to run a program inside SP1.
 #[derive(Clone, Default)]
@@ -16,6 +18,11 @@ pub struct SP1Context<'a> {
 
     /// The maximum number of cpu cycles to use for execution.
     pub max_cycles: Option<u64>,
+
+    /// The cache of hook responses, shared with the other executions given the same cache.
+    ///
+    /// Note: `None` denotes a cache for this execution alone, with the capacity of the options.
+    pub hook_cache: Option<SharedHookCache>,
 }
 
 #[derive(Clone, Default)]
@@ -24,6 +31,7 @@ pub struct SP1ContextBuilder<'a> {
     hook_registry_entries: Vec<(u32, BoxedHook<'a>)>,
     subproof_verifier: Option<Arc<dyn SubproofVerifier + 'a>>,
     max_cycles: Option<u64>,
+    hook_cache: Option<SharedHookCache>,
 }
 
 impl<'a> SP1Context<'a> {
@@ -44,7 +52,21 @@ impl<'a> SP1ContextBuilder<'a> {
     /// Build and return the [SP1Context].
     ///
     /// Clears and resets the builder, allowing it to be reused.
+    ///
+    /// Panics if a default hook memoized by the shared cache of [`Self::hook_cache`] is
+    /// overridden, as the cache would answer its requests with the responses of the default hook.
     pub fn build(&mut self) -> SP1Context<'a> {
+        if let Some(hook_cache) = &self.hook_cache {
+            let hook_cache = hook_cache.lock().unwrap();
+            if let Some((fd, _)) = self
+                .hook_registry_entries
+                .iter()
+                .find(|(fd, _)| DEFAULT_HOOK_FDS.contains(fd) && hook_cache.memoizes(*fd))
+            {
+                panic!("the hook at fd {fd} overrides a default hook memoized by the shared cache");
+            }
+        }
+
         // If hook_registry_entries is nonempty or no_default_hooks true,
         // indicating a non-default value of hook_registry.
         let hook_registry =
@@ -60,10 +82,12 @@ impl<'a> SP1ContextBuilder<'a> {
             });
         let subproof_verifier = take(&mut self.subproof_verifier);
         let cycle_limit = take(&mut self.max_cycles);
+        let hook_cache = take(&mut self.hook_cache);
         SP1Context {
             hook_registry,
             subproof_verifier,
             max_cycles: cycle_limit,
+            hook_cache,
         }
     }
 
@@ -106,13 +130,24 @@ impl<'a> SP1ContextBuilder<'a> {
         self.max_cycles = Some(max_cycles);
         self
     }
+
+    /// Memoize hook responses in a cache shared across executions, such as one made with
+    /// [`HookCache::shared`](super::HookCache::shared), instead of in a cache of their own.
+    ///
+    /// Requests answered in one execution are then answered from the cache in the next ones. As
+    /// the responses are keyed on the file descriptor of the hook, the default hooks memoized by
+    /// the cache cannot be overridden with [`Self::hook`].
+    pub fn hook_cache(&mut self, hook_cache: SharedHookCache) -> &mut Self {
+        self.hook_cache = Some(hook_cache);
+        self
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
 
-    use crate::runtime::{DefaultSubproofVerifier, SP1Context};
+    use crate::runtime::{DefaultSubproofVerifier, HookCache, SP1Context, FD_ECRECOVER_HOOK};
 
     #[test]
     fn defaults() {
@@ -120,10 +155,12 @@ mod tests {
             hook_registry,
             subproof_verifier,
             max_cycles: cycle_limit,
+            hook_cache,
         } = SP1Context::builder().build();
         assert!(hook_registry.is_none());
         assert!(subproof_verifier.is_none());
         assert!(cycle_limit.is_none());
+        assert!(hook_cache.is_none());
     }
 
     #[test]
@@ -161,4 +198,31 @@ mod tests {
             .build();
         assert!(subproof_verifier.is_some());
     }
+
+    #[test]
+    fn hook_cache() {
+        let cache = HookCache::shared(16);
+        let SP1Context { hook_cache, .. } = SP1Context::builder().hook_cache(cache.clone()).build();
+        assert!(Arc::ptr_eq(&hook_cache.unwrap(), &cache));
+
+        // Hooks that are not memoized may be overridden, or added.
+        cache.lock().unwrap().memoize_fd(30);
+        SP1Context::builder()
+            .hook_cache(HookCache::shared(0))
+            .hook(FD_ECRECOVER_HOOK, |_, _| vec![])
+            .build();
+        SP1Context::builder()
+            .hook_cache(cache)
+            .hook(30, |_, _| vec![])
+            .build();
+    }
+
+    #[test]
+    #[should_panic(expected = "overrides a default hook memoized by the shared cache")]
+    fn hook_cache_with_overridden_hook() {
+        SP1Context::builder()
+            .hook(FD_ECRECOVER_HOOK, |_, _| vec![])
+            .hook_cache(HookCache::shared(16))
+            .build();
+    }
 }
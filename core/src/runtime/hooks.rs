@@ -1,10 +1,12 @@
 use core::fmt::Debug;
 
-use std::collections::HashMap;
-use std::sync::{Arc, RwLock, RwLockWriteGuard};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex, RwLock, RwLockWriteGuard};
 
 use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
 use k256::elliptic_curve::ops::Invert;
+use num::BigUint;
+use p256::ecdsa::{Signature as P256Signature, VerifyingKey as P256VerifyingKey};
 
 use super::Runtime;
 
@@ -14,6 +16,49 @@ pub type BoxedHook<'a> = Arc<RwLock<dyn Hook + Send + Sync + 'a>>;
 /// The file descriptor through which to access `hook_ecrecover`.
 pub const FD_ECRECOVER_HOOK: u32 = 5;
 
+/// The file descriptor through which to access `hook_ecrecover_r1`.
+pub const FD_ECRECOVER_R1_HOOK: u32 = 6;
+
+/// The file descriptor through which to access `hook_modinv`.
+pub const FD_MODINV_HOOK: u32 = 7;
+
+/// The file descriptor through which to access `hook_sqrt`.
+pub const FD_SQRT_HOOK: u32 = 8;
+
+/// The file descriptor through which to access `hook_bigmod`.
+pub const FD_BIGMOD_HOOK: u32 = 9;
+
+/// The file descriptors of the hooks of the default registry, whose responses are memoized by
+/// default.
+pub const DEFAULT_HOOK_FDS: [u32; 5] = [
+    FD_ECRECOVER_HOOK,
+    FD_ECRECOVER_R1_HOOK,
+    FD_MODINV_HOOK,
+    FD_SQRT_HOOK,
+    FD_BIGMOD_HOOK,
+];
+
+/// The `hook_bigmod` operation that divides a value by the modulus.
+pub const BIGMOD_OP_DIVREM: u8 = 0;
+
+/// The `hook_bigmod` operation that inverts a value modulo the modulus.
+pub const BIGMOD_OP_INVERSE: u8 = 1;
+
+/// The status byte of an `ecrecover` hook response to a request of the wrong length.
+///
+/// Status bytes are never `0x02` or `0x03`, so that an error response can be distinguished from the
+/// compressed public key of a successful one.
+pub const HOOK_STATUS_MALFORMED_INPUT: u8 = 0x10;
+
+/// The status byte of an `ecrecover` hook response to a signature with out of range scalars.
+pub const HOOK_STATUS_INVALID_SIGNATURE: u8 = 0x11;
+
+/// The status byte of an `ecrecover` hook response to an invalid recovery id.
+pub const HOOK_STATUS_INVALID_RECOVERY_ID: u8 = 0x12;
+
+/// The status byte of an `ecrecover` hook response to a signature no public key recovers to.
+pub const HOOK_STATUS_UNRECOVERABLE: u8 = 0x13;
+
 /// A runtime hook. May be called during execution by writing to a specified file descriptor,
 /// accepting and returning arbitrary data.
 pub trait Hook {
@@ -74,6 +119,10 @@ impl<'a> Default for HookRegistry<'a> {
             // Note: To ensure any `fd` value is synced with `zkvm/precompiles/src/io.rs`,
             // add an assertion to the test `hook_fds_match` below.
             (FD_ECRECOVER_HOOK, hookify(hook_ecrecover)),
+            (FD_ECRECOVER_R1_HOOK, hookify(hook_ecrecover_r1)),
+            (FD_MODINV_HOOK, hookify(hook_modinv)),
+            (FD_SQRT_HOOK, hookify(hook_sqrt)),
+            (FD_BIGMOD_HOOK, hookify(hook_bigmod)),
         ]);
 
         Self { table }
@@ -93,45 +142,366 @@ impl<'a> Debug for HookRegistry<'a> {
     }
 }
 
+/// A memoization cache of hook responses, shared by the runtimes that hold it.
+pub type SharedHookCache = Arc<Mutex<HookCache>>;
+
+/// A bounded least recently used cache of the responses of hooks, keyed on their requests.
+///
+/// Only the hooks whose responses depend on nothing but their request are memoized, which by
+/// default are the hooks of the default registry. A cached response is exactly the one the hook
+/// would return, so caching changes nothing about what the guest reads or constrains.
+///
+/// Responses are keyed on the file descriptor of the hook, not on the hook itself. Overriding a
+/// default hook with one whose responses depend on more than the request requires disabling the
+/// cache, by setting [`SP1CoreOpts::hook_cache_capacity`] to 0. A shared cache may hold the
+/// responses of the default hooks of other executions, so [`SP1ContextBuilder::build`] rejects
+/// overriding a memoized default hook in a context with a shared cache. Hooks memoized with
+/// [`HookCache::memoize_fd`] must be the same in all the executions sharing a cache.
+///
+/// [`SP1CoreOpts::hook_cache_capacity`]: crate::utils::SP1CoreOpts::hook_cache_capacity
+/// [`SP1ContextBuilder::build`]: super::SP1ContextBuilder::build
+#[derive(Debug)]
+pub struct HookCache {
+    capacity: usize,
+    fds: HashSet<u32>,
+    entries: HashMap<(u32, Vec<u8>), (Vec<Vec<u8>>, u64)>,
+    /// The keys of the entries, by the tick of their last use.
+    recency: BTreeMap<u64, (u32, Vec<u8>)>,
+    tick: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl HookCache {
+    /// Creates a cache of at most `capacity` responses of the default hooks.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            fds: HashSet::from(DEFAULT_HOOK_FDS),
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Creates a cache to be shared by several runtimes, through [`SP1ContextBuilder::hook_cache`].
+    ///
+    /// [`SP1ContextBuilder::hook_cache`]: super::SP1ContextBuilder::hook_cache
+    pub fn shared(capacity: usize) -> SharedHookCache {
+        Arc::new(Mutex::new(Self::new(capacity)))
+    }
+
+    /// Also memoizes the hook at `fd`, which must return the same responses for the same request.
+    pub fn memoize_fd(&mut self, fd: u32) -> &mut Self {
+        self.fds.insert(fd);
+        self
+    }
+
+    /// Returns whether the responses of the hook at `fd` are memoized.
+    pub fn memoizes(&self, fd: u32) -> bool {
+        self.capacity > 0 && self.fds.contains(&fd)
+    }
+
+    /// Returns the cached response to a request, marking it as the most recently used.
+    pub fn get(&mut self, fd: u32, request: &[u8]) -> Option<Vec<Vec<u8>>> {
+        let key = (fd, request.to_vec());
+        let Some((responses, last_use)) = self.entries.get_mut(&key) else {
+            self.misses += 1;
+            return None;
+        };
+        self.hits += 1;
+        self.tick += 1;
+        let key = self.recency.remove(last_use).unwrap();
+        *last_use = self.tick;
+        self.recency.insert(self.tick, key);
+        Some(responses.clone())
+    }
+
+    /// Caches the response to a request, evicting the least recently used one if the cache is
+    /// full.
+    pub fn insert(&mut self, fd: u32, request: &[u8], responses: Vec<Vec<u8>>) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.tick += 1;
+        let key = (fd, request.to_vec());
+        if let Some((_, last_use)) = self.entries.insert(key.clone(), (responses, self.tick)) {
+            self.recency.remove(&last_use);
+        } else if self.entries.len() > self.capacity {
+            let (_, evicted) = self.recency.pop_first().unwrap();
+            self.entries.remove(&evicted);
+        }
+        self.recency.insert(self.tick, key);
+    }
+
+    /// The number of cached entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no response is cached.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The number of requests answered from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// The number of requests of memoized hooks that were not in the cache.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+}
+
 /// Environment that a hook may read from.
 pub struct HookEnv<'a, 'b: 'a> {
     pub runtime: &'a Runtime<'b>,
 }
 
+/// Recovers a secp256k1 public key from the 65-byte signature, with the recovery id as its last
+/// byte, followed by the 32-byte message hash.
+///
+/// On success, returns the compressed public key and the inverse of the (normalized) `s`.
+/// Otherwise, returns a single response made of a status byte, such as
+/// [`HOOK_STATUS_INVALID_SIGNATURE`], followed by a message describing the error.
 pub fn hook_ecrecover(_env: HookEnv, buf: &[u8]) -> Vec<Vec<u8>> {
-    assert_eq!(
-        buf.len(),
-        65 + 32,
-        "ecrecover input should have length 65 + 32"
-    );
+    ecrecover_response(ecrecover_k1(buf))
+}
+
+/// Recovers a secp256r1 (P-256) public key, with the same framing as `hook_ecrecover`.
+pub fn hook_ecrecover_r1(_env: HookEnv, buf: &[u8]) -> Vec<Vec<u8>> {
+    ecrecover_response(ecrecover_r1(buf))
+}
+
+/// Computes a modular inverse. The request is a big endian modulus followed by a big endian value
+/// of the same length.
+///
+/// Returns the big endian inverse of the value, with the length of the modulus, or an empty
+/// response if the value is not invertible, the modulus is zero or the request has an odd length.
+pub fn hook_modinv(_env: HookEnv, buf: &[u8]) -> Vec<Vec<u8>> {
+    use num::Zero;
+
+    if buf.is_empty() || buf.len() % 2 != 0 {
+        return vec![vec![]];
+    }
+    let (modulus, value) = buf.split_at(buf.len() / 2);
+    let modulus = BigUint::from_bytes_be(modulus);
+    let value = BigUint::from_bytes_be(value);
+    if modulus.is_zero() {
+        return vec![vec![]];
+    }
+
+    match value.modinv(&modulus) {
+        Some(inverse) => {
+            let bytes = inverse.to_bytes_be();
+            let mut response = vec![0u8; buf.len() / 2];
+            response[buf.len() / 2 - bytes.len()..].copy_from_slice(&bytes);
+            vec![response]
+        }
+        None => vec![vec![]],
+    }
+}
+
+/// Computes a modular square root, for a prime modulus congruent to 3 modulo 4. The request is a
+/// big endian modulus followed by a big endian value of the same length.
+///
+/// Returns the big endian `value^((p + 1) / 4)`, with the length of the modulus, which is a square
+/// root of the value if it is a quadratic residue, and a square root of its negation otherwise.
+/// Returns an empty response if the modulus is not congruent to 3 modulo 4 or the request has an
+/// odd length.
+pub fn hook_sqrt(_env: HookEnv, buf: &[u8]) -> Vec<Vec<u8>> {
+    if buf.is_empty() || buf.len() % 2 != 0 {
+        return vec![vec![]];
+    }
+    let (modulus, value) = buf.split_at(buf.len() / 2);
+    let modulus = BigUint::from_bytes_be(modulus);
+    let value = BigUint::from_bytes_be(value);
+    if &modulus % 4u32 != BigUint::from(3u32) {
+        return vec![vec![]];
+    }
+
+    let bytes = value
+        .modpow(&((&modulus + 1u32) >> 2), &modulus)
+        .to_bytes_be();
+    let mut response = vec![0u8; buf.len() / 2];
+    response[buf.len() / 2 - bytes.len()..].copy_from_slice(&bytes);
+    vec![response]
+}
+
+/// Computes the hints of the modular arithmetic of `sp1_lib::bigmod`. The request is an operation
+/// byte, the length of the modulus as a little endian `u32`, the big endian modulus, and a big
+/// endian value.
+///
+/// Always returns two responses. For [`BIGMOD_OP_DIVREM`], the quotient of the value by the
+/// modulus, with the length of the value, and the remainder, with the length of the modulus. For
+/// [`BIGMOD_OP_INVERSE`], the inverse of the value and an empty gcd if the value is invertible,
+/// and otherwise an empty inverse and the gcd of the value and the modulus, each with the length
+/// of the modulus. Returns two empty responses if the request is malformed or the modulus is zero.
+pub fn hook_bigmod(_env: HookEnv, buf: &[u8]) -> Vec<Vec<u8>> {
+    use num::{Integer, Zero};
+
+    let malformed = vec![vec![], vec![]];
+    let Some((&op, rest)) = buf.split_first() else {
+        return malformed;
+    };
+    if rest.len() < 4 {
+        return malformed;
+    }
+    let (len, rest) = rest.split_at(4);
+    let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+    if rest.len() < len {
+        return malformed;
+    }
+    let (modulus, value) = rest.split_at(len);
+    let modulus = BigUint::from_bytes_be(modulus);
+    let x = BigUint::from_bytes_be(value);
+    if modulus.is_zero() {
+        return malformed;
+    }
+
+    match op {
+        BIGMOD_OP_DIVREM => {
+            let (q, r) = x.div_rem(&modulus);
+            vec![to_be_padded(&q, value.len()), to_be_padded(&r, len)]
+        }
+        BIGMOD_OP_INVERSE => match x.modinv(&modulus) {
+            Some(inverse) => vec![to_be_padded(&inverse, len), vec![]],
+            None => vec![vec![], to_be_padded(&x.gcd(&modulus), len)],
+        },
+        _ => malformed,
+    }
+}
+
+/// Encodes a value as big endian bytes, padded to `len` bytes, which must be enough.
+fn to_be_padded(x: &BigUint, len: usize) -> Vec<u8> {
+    let bytes = x.to_bytes_be();
+    let bytes = &bytes[bytes.iter().take_while(|&&byte| byte == 0).count()..];
+    let mut padded = vec![0u8; len];
+    padded[len - bytes.len()..].copy_from_slice(bytes);
+    padded
+}
+
+/// An error returned by the `ecrecover` hooks, with its status byte.
+struct EcrecoverHookError {
+    status: u8,
+    message: String,
+}
+
+impl EcrecoverHookError {
+    fn new(status: u8, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+/// Splits an `ecrecover` request into the signature, the recovery id and the message hash.
+fn split_ecrecover_request(buf: &[u8]) -> Result<(&[u8], u8, &[u8]), EcrecoverHookError> {
+    if buf.len() != 65 + 32 {
+        return Err(EcrecoverHookError::new(
+            HOOK_STATUS_MALFORMED_INPUT,
+            format!(
+                "ecrecover input should have length 65 + 32, got {}",
+                buf.len()
+            ),
+        ));
+    }
     let (sig, msg_hash) = buf.split_at(65);
-    let sig: &[u8; 65] = sig.try_into().unwrap();
-    let msg_hash: &[u8; 32] = msg_hash.try_into().unwrap();
+    Ok((&sig[..64], sig[64], msg_hash))
+}
 
-    let mut recovery_id = sig[64];
-    let mut sig = Signature::from_slice(&sig[..64]).unwrap();
+fn ecrecover_k1(buf: &[u8]) -> Result<(Vec<u8>, Vec<u8>), EcrecoverHookError> {
+    let (sig, recovery_id, msg_hash) = split_ecrecover_request(buf)?;
+    let mut sig = Signature::from_slice(sig).map_err(|_| {
+        EcrecoverHookError::new(
+            HOOK_STATUS_INVALID_SIGNATURE,
+            "r or s is zero or not below the curve order",
+        )
+    })?;
 
+    // The id is parsed before it is flipped, so that errors report the id of the request.
+    let mut recid = RecoveryId::from_byte(recovery_id).ok_or_else(|| {
+        EcrecoverHookError::new(
+            HOOK_STATUS_INVALID_RECOVERY_ID,
+            format!("invalid recovery id {recovery_id}"),
+        )
+    })?;
     if let Some(sig_normalized) = sig.normalize_s() {
         sig = sig_normalized;
-        recovery_id ^= 1
+        recid = RecoveryId::new(!recid.is_y_odd(), recid.is_x_reduced());
     };
-    let recid = RecoveryId::from_byte(recovery_id).expect("Recovery ID is valid");
 
-    let recovered_key = VerifyingKey::recover_from_prehash(&msg_hash[..], &sig, recid).unwrap();
+    let recovered_key = VerifyingKey::recover_from_prehash(msg_hash, &sig, recid)
+        .map_err(|err| EcrecoverHookError::new(HOOK_STATUS_UNRECOVERABLE, err.to_string()))?;
     let bytes = recovered_key.to_sec1_bytes();
 
     let (_, s) = sig.split_scalars();
     let s_inverse = s.invert();
 
-    vec![bytes.to_vec(), s_inverse.to_bytes().to_vec()]
+    Ok((bytes.to_vec(), s_inverse.to_bytes().to_vec()))
+}
+
+fn ecrecover_r1(buf: &[u8]) -> Result<(Vec<u8>, Vec<u8>), EcrecoverHookError> {
+    let (sig, recovery_id, msg_hash) = split_ecrecover_request(buf)?;
+    let mut sig = P256Signature::from_slice(sig).map_err(|_| {
+        EcrecoverHookError::new(
+            HOOK_STATUS_INVALID_SIGNATURE,
+            "r or s is zero or not below the curve order",
+        )
+    })?;
+
+    // The id is parsed before it is flipped, so that errors report the id of the request. p256
+    // does not re-export the recovery id, which is the same `ecdsa` type as the one of k256.
+    let mut recid = RecoveryId::from_byte(recovery_id).ok_or_else(|| {
+        EcrecoverHookError::new(
+            HOOK_STATUS_INVALID_RECOVERY_ID,
+            format!("invalid recovery id {recovery_id}"),
+        )
+    })?;
+    if let Some(sig_normalized) = sig.normalize_s() {
+        sig = sig_normalized;
+        recid = RecoveryId::new(!recid.is_y_odd(), recid.is_x_reduced());
+    };
+
+    let recovered_key = P256VerifyingKey::recover_from_prehash(msg_hash, &sig, recid)
+        .map_err(|err| EcrecoverHookError::new(HOOK_STATUS_UNRECOVERABLE, err.to_string()))?;
+    // Unlike those of k256, the SEC1 bytes of p256 keys are uncompressed by default.
+    let bytes = recovered_key.to_encoded_point(true);
+
+    let (_, s) = sig.split_scalars();
+    let s_inverse = s.invert();
+
+    Ok((bytes.as_bytes().to_vec(), s_inverse.to_bytes().to_vec()))
+}
+
+/// Frames the result of an `ecrecover` hook as its responses.
+fn ecrecover_response(result: Result<(Vec<u8>, Vec<u8>), EcrecoverHookError>) -> Vec<Vec<u8>> {
+    match result {
+        Ok((pubkey, s_inverse)) => vec![pubkey, s_inverse],
+        Err(err) => {
+            log::debug!("ecrecover hook failed: {}", err.message);
+            let mut response = vec![err.status];
+            response.extend_from_slice(err.message.as_bytes());
+            vec![response]
+        }
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
+    use std::collections::BTreeMap;
+
+    use p256::elliptic_curve::PrimeField;
+
     use crate::{
-        runtime::Program,
+        runtime::{ExecutionError, HintTag, Instruction, Opcode, Program, SP1Context, SyscallCode},
         stark::DefaultProver,
-        utils::{self, tests::ECRECOVER_ELF},
+        utils::{self, tests::ECRECOVER_ELF, SP1CoreOpts},
     };
 
     use super::*;
@@ -139,7 +509,897 @@ pub mod tests {
     #[test]
     pub fn hook_fds_match() {
         use sp1_zkvm::lib::io;
-        assert_eq!(FD_ECRECOVER_HOOK, io::FD_ECRECOVER_HOOK)
+        assert_eq!(FD_ECRECOVER_HOOK, io::FD_ECRECOVER_HOOK);
+        assert_eq!(FD_ECRECOVER_R1_HOOK, io::FD_ECRECOVER_R1_HOOK);
+        assert_eq!(FD_MODINV_HOOK, io::FD_MODINV_HOOK);
+        assert_eq!(FD_SQRT_HOOK, io::FD_SQRT_HOOK);
+        assert_eq!(FD_BIGMOD_HOOK, io::FD_BIGMOD_HOOK);
+        assert_eq!(BIGMOD_OP_DIVREM, sp1_zkvm::lib::bigmod::OP_DIVREM);
+        assert_eq!(BIGMOD_OP_INVERSE, sp1_zkvm::lib::bigmod::OP_INVERSE);
+    }
+
+    #[test]
+    pub fn hook_statuses_match() {
+        use sp1_zkvm::lib::io;
+        assert_eq!(HOOK_STATUS_MALFORMED_INPUT, io::HOOK_STATUS_MALFORMED_INPUT);
+        assert_eq!(
+            HOOK_STATUS_INVALID_SIGNATURE,
+            io::HOOK_STATUS_INVALID_SIGNATURE
+        );
+        assert_eq!(
+            HOOK_STATUS_INVALID_RECOVERY_ID,
+            io::HOOK_STATUS_INVALID_RECOVERY_ID
+        );
+        assert_eq!(HOOK_STATUS_UNRECOVERABLE, io::HOOK_STATUS_UNRECOVERABLE);
+    }
+
+    #[test]
+    fn hook_ecrecover_rejects_malformed_input() {
+        let runtime = Runtime::new(Program::from(ECRECOVER_ELF), SP1CoreOpts::default());
+        let msg_hash = [0xab; 32];
+
+        let mut out_of_range = vec![0xff; 64];
+        out_of_range.push(0);
+        out_of_range.extend_from_slice(&msg_hash);
+
+        let mut bad_recovery_id = vec![1; 64];
+        bad_recovery_id.push(7);
+        bad_recovery_id.extend_from_slice(&msg_hash);
+
+        let cases = [
+            (vec![], HOOK_STATUS_MALFORMED_INPUT),
+            (vec![0; 96], HOOK_STATUS_MALFORMED_INPUT),
+            (vec![0; 98], HOOK_STATUS_MALFORMED_INPUT),
+            (out_of_range, HOOK_STATUS_INVALID_SIGNATURE),
+            (bad_recovery_id, HOOK_STATUS_INVALID_RECOVERY_ID),
+        ];
+        for (fd, name) in [
+            (FD_ECRECOVER_HOOK, "ecrecover"),
+            (FD_ECRECOVER_R1_HOOK, "ecrecover_r1"),
+        ] {
+            for (buf, status) in &cases {
+                let response = runtime.hook(fd, buf);
+                assert_eq!(response.len(), 1, "{name}: {buf:?}");
+                assert_eq!(response[0][0], *status, "{name}: {buf:?}");
+                assert!(response[0].len() > 1, "{name}: missing error message");
+            }
+        }
+    }
+
+    #[test]
+    fn hook_modinv_inverts() {
+        let runtime = Runtime::new(Program::from(ECRECOVER_ELF), SP1CoreOpts::default());
+
+        // 3 * 5 = 15 = 1 mod 7.
+        assert_eq!(
+            runtime.hook(FD_MODINV_HOOK, &[0, 7, 0, 3]),
+            vec![vec![0, 5]]
+        );
+        // 2 has no inverse modulo 4.
+        assert_eq!(
+            runtime.hook(FD_MODINV_HOOK, &[4, 2]),
+            vec![Vec::<u8>::new()]
+        );
+        assert_eq!(
+            runtime.hook(FD_MODINV_HOOK, &[7, 0, 3]),
+            vec![Vec::<u8>::new()]
+        );
+        // A zero modulus, which a guest can send, has no inverses.
+        assert_eq!(
+            runtime.hook(FD_MODINV_HOOK, &[0, 0, 0, 3]),
+            vec![Vec::<u8>::new()]
+        );
+        assert_eq!(
+            runtime.hook(FD_MODINV_HOOK, &[0, 0]),
+            vec![Vec::<u8>::new()]
+        );
+    }
+
+    #[test]
+    fn hook_sqrt_roots() {
+        let runtime = Runtime::new(Program::from(ECRECOVER_ELF), SP1CoreOpts::default());
+
+        // 2^2 = 4 mod 7.
+        assert_eq!(runtime.hook(FD_SQRT_HOOK, &[0, 7, 0, 4]), vec![vec![0, 2]]);
+        // 3 is not a square modulo 7, and 2^2 = 4 = -3 mod 7.
+        assert_eq!(runtime.hook(FD_SQRT_HOOK, &[0, 7, 0, 3]), vec![vec![0, 2]]);
+        // 13 is not congruent to 3 modulo 4.
+        assert_eq!(runtime.hook(FD_SQRT_HOOK, &[13, 4]), vec![Vec::<u8>::new()]);
+        assert_eq!(
+            runtime.hook(FD_SQRT_HOOK, &[7, 0, 4]),
+            vec![Vec::<u8>::new()]
+        );
+    }
+
+    #[test]
+    fn hook_ecrecover_r1_recovers_key() {
+        use p256::ecdsa::SigningKey;
+        use p256::Scalar;
+
+        let signing_key = SigningKey::from_slice(&[0x42; 32]).unwrap();
+        let msg_hash = [0xab; 32];
+        let (sig, recid) = signing_key.sign_prehash_recoverable(&msg_hash).unwrap();
+
+        let mut buf = sig.to_bytes().to_vec();
+        buf.push(recid.to_byte());
+        buf.extend_from_slice(&msg_hash);
+
+        let runtime = Runtime::new(Program::from(ECRECOVER_ELF), SP1CoreOpts::default());
+        let response = runtime.hook(FD_ECRECOVER_R1_HOOK, &buf);
+
+        assert_eq!(
+            response[0],
+            signing_key
+                .verifying_key()
+                .to_encoded_point(true)
+                .as_bytes()
+        );
+        let s_inverse = Scalar::from_repr(*p256::FieldBytes::from_slice(&response[1])).unwrap();
+        let (_, s) = sig.normalize_s().unwrap_or(sig).split_scalars();
+        assert_eq!(s_inverse * s.as_ref(), Scalar::ONE);
+    }
+
+    /// Requests to the `ecrecover` hook for `count` signatures by `signers` distinct keys, in
+    /// turn, followed by a malformed request.
+    fn ecrecover_requests(signers: u8, count: usize) -> Vec<Vec<u8>> {
+        use k256::ecdsa::SigningKey;
+
+        let requests = (1..=signers)
+            .map(|seed| {
+                let signing_key = SigningKey::from_slice(&[seed; 32]).unwrap();
+                let msg_hash = [seed.wrapping_mul(3); 32];
+                let (sig, recid) = signing_key.sign_prehash_recoverable(&msg_hash).unwrap();
+                let mut buf = sig.to_bytes().to_vec();
+                buf.push(recid.to_byte());
+                buf.extend_from_slice(&msg_hash);
+                buf
+            })
+            .collect::<Vec<_>>();
+        let mut batch = requests
+            .iter()
+            .cycle()
+            .take(count)
+            .cloned()
+            .collect::<Vec<_>>();
+        batch.push(vec![0; 96]);
+        batch
+    }
+
+    fn memoizing_opts(hook_cache_capacity: usize) -> SP1CoreOpts {
+        SP1CoreOpts {
+            hook_cache_capacity,
+            ..SP1CoreOpts::default()
+        }
+    }
+
+    #[test]
+    fn hook_cache_matches_uncached() {
+        let batch = ecrecover_requests(4, 32);
+        let uncached = Runtime::new(Program::from(ECRECOVER_ELF), memoizing_opts(0));
+        let cached = Runtime::new(Program::from(ECRECOVER_ELF), memoizing_opts(64));
+        assert!(uncached.hook_cache.is_none());
+
+        for buf in &batch {
+            assert_eq!(
+                cached.hook_memoized(FD_ECRECOVER_HOOK, buf),
+                uncached.hook_memoized(FD_ECRECOVER_HOOK, buf)
+            );
+        }
+        let cache = cached.hook_cache.as_ref().unwrap().lock().unwrap();
+        assert_eq!(cache.len(), 5);
+        assert_eq!(cache.misses(), 5);
+        assert_eq!(cache.hits(), 28);
+    }
+
+    #[test]
+    fn hook_cache_evicts_least_recently_used() {
+        let response = |byte| vec![vec![byte]];
+        let mut cache = HookCache::new(2);
+        cache.insert(FD_MODINV_HOOK, b"a", response(1));
+        cache.insert(FD_MODINV_HOOK, b"b", response(2));
+        assert_eq!(cache.get(FD_MODINV_HOOK, b"a"), Some(response(1)));
+
+        cache.insert(FD_MODINV_HOOK, b"c", response(3));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(FD_MODINV_HOOK, b"b"), None);
+        assert_eq!(cache.get(FD_MODINV_HOOK, b"a"), Some(response(1)));
+        assert_eq!(cache.get(FD_MODINV_HOOK, b"c"), Some(response(3)));
+
+        // Requests are keyed by their hook as well.
+        assert_eq!(cache.get(FD_SQRT_HOOK, b"a"), None);
+        assert!(!HookCache::new(0).memoizes(FD_MODINV_HOOK));
+        assert!(!cache.memoizes(30));
+        assert!(cache.memoize_fd(30).memoizes(30));
+    }
+
+    #[test]
+    fn hook_cache_shared_across_runtimes() {
+        let batch = ecrecover_requests(4, 4);
+        let cache = HookCache::shared(64);
+        for _ in 0..2 {
+            let context = SP1Context::builder().hook_cache(cache.clone()).build();
+            let runtime = Runtime::with_context(
+                Program::from(ECRECOVER_ELF),
+                SP1CoreOpts::default(),
+                context,
+            );
+            for buf in &batch {
+                runtime.hook_memoized(FD_ECRECOVER_HOOK, buf);
+            }
+        }
+        let cache = cache.lock().unwrap();
+        assert_eq!(cache.misses(), 5);
+        assert_eq!(cache.hits(), 5);
+    }
+
+    /// The address of the `i`-th request of [`ecrecover_batch_program`].
+    fn ecrecover_request_ptr(i: usize) -> u32 {
+        0x10000 + 0x100 * i as u32
+    }
+
+    /// The address of the compressed key read by [`ecrecover_batch_program`] from the responses
+    /// to its `i`-th request, which is followed by the inverse of s.
+    fn ecrecover_response_ptr(i: usize) -> u32 {
+        0x20000 + 0x100 * i as u32
+    }
+
+    /// A guest that sends a request to the `ecrecover` hook at `fd` for each of `requests` and reads
+    /// the key and the inverse of s of each response. With `read_each`, the responses to a request
+    /// are read before the next request is sent, as `secp256k1::ecrecover_batch` does. Otherwise
+    /// all the requests are sent before any response is read.
+    fn ecrecover_batch_program(fd: u32, requests: &[Vec<u8>], read_each: bool) -> Program {
+        let syscall = |code: SyscallCode, a0: u32, a1: u32, a2: u32| {
+            [
+                Instruction::new(Opcode::ADD, 5, 0, code as u32, false, true),
+                Instruction::new(Opcode::ADD, 10, 0, a0, false, true),
+                Instruction::new(Opcode::ADD, 11, 0, a1, false, true),
+                Instruction::new(Opcode::ADD, 12, 0, a2, false, true),
+                Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            ]
+        };
+
+        let mut memory_image = BTreeMap::new();
+        for (i, request) in requests.iter().enumerate() {
+            for (j, word) in request.chunks(4).enumerate() {
+                let mut bytes = [0; 4];
+                bytes[..word.len()].copy_from_slice(word);
+                let addr = ecrecover_request_ptr(i) + 4 * j as u32;
+                memory_image.insert(addr, u32::from_le_bytes(bytes));
+            }
+        }
+
+        let write = |i: usize| {
+            let len = requests[i].len() as u32;
+            syscall(SyscallCode::WRITE, fd, ecrecover_request_ptr(i), len)
+        };
+        let read = |i: usize| {
+            [(0, 33), (36, 32)]
+                .into_iter()
+                .flat_map(move |(offset, len)| {
+                    let ptr = ecrecover_response_ptr(i) + offset;
+                    syscall(SyscallCode::HINT_LEN, 0, 0, 0)
+                        .into_iter()
+                        .chain(syscall(SyscallCode::HINT_READ, ptr, len, 0))
+                })
+        };
+
+        let mut instructions = Vec::new();
+        if read_each {
+            for i in 0..requests.len() {
+                instructions.extend(write(i));
+                instructions.extend(read(i));
+            }
+        } else {
+            (0..requests.len()).for_each(|i| instructions.extend(write(i)));
+            (0..requests.len()).for_each(|i| instructions.extend(read(i)));
+        }
+        let mut program = Program::new(instructions, 0, 0);
+        program.memory_image = memory_image;
+        program
+    }
+
+    /// A request to the `ecrecover` hook for a signature by the key with bytes `seed` over the
+    /// hash with bytes `msg`, with the compressed key and the inverse of s that it recovers.
+    fn signed_ecrecover_request(seed: u8, msg: u8) -> (Vec<u8>, Vec<u8>) {
+        use k256::ecdsa::SigningKey;
+
+        let signing_key = SigningKey::from_slice(&[seed; 32]).unwrap();
+        let msg_hash = [msg; 32];
+        let (sig, recid) = signing_key.sign_prehash_recoverable(&msg_hash).unwrap();
+        let mut request = sig.to_bytes().to_vec();
+        request.push(recid.to_byte());
+        request.extend_from_slice(&msg_hash);
+
+        let mut response = signing_key
+            .verifying_key()
+            .to_encoded_point(true)
+            .as_bytes()
+            .to_vec();
+        response.extend_from_slice(&sig.s().invert().to_bytes());
+        (request, response)
+    }
+
+    /// Requests to the `ecrecover` hook for signatures by `count` distinct keys, with the
+    /// compressed key and the inverse of s that each one recovers.
+    fn ecrecover_requests_with_responses(count: u8) -> Vec<(Vec<u8>, Vec<u8>)> {
+        (1..=count)
+            .map(|seed| signed_ecrecover_request(seed, seed.wrapping_mul(7)))
+            .collect()
+    }
+
+    /// The `len` bytes at `addr` that a guest hinted but never loaded, so that they are still in
+    /// uninitialized memory.
+    fn read_hinted(runtime: &Runtime, addr: u32, len: usize) -> Vec<u8> {
+        let memory = &runtime.state.uninitialized_memory;
+        (addr..addr + len as u32)
+            .map(|addr| (memory[&(addr & !3)] >> (8 * (addr & 3))) as u8)
+            .collect()
+    }
+
+    #[test]
+    fn ecrecover_batch_reads_each_response_in_turn() {
+        let (requests, responses): (Vec<_>, Vec<_>) =
+            ecrecover_requests_with_responses(4).into_iter().unzip();
+        let program = ecrecover_batch_program(FD_ECRECOVER_HOOK, &requests, true);
+        let mut runtime = Runtime::new(program, SP1CoreOpts::default());
+        runtime.run().unwrap();
+        assert!(runtime.state.unread_hook_responses.is_empty());
+
+        for (i, response) in responses.iter().enumerate() {
+            let ptr = ecrecover_response_ptr(i);
+            assert_eq!(read_hinted(&runtime, ptr, 33), response[..33], "key {i}");
+            assert_eq!(
+                read_hinted(&runtime, ptr + 36, 32),
+                response[33..],
+                "s inverse {i}"
+            );
+        }
+    }
+
+    #[test]
+    fn ecrecover_batch_recovers_signers_merkle_root() {
+        use sp1_zkvm::lib::secp256k1::{
+            commit_signers_merkle_root, decompress_pubkey, pubkey_to_address, signers_merkle_proof,
+            signers_merkle_root, verify_signer_merkle_proof,
+        };
+
+        // Five signatures by three keys, requested as `commit_signers_merkle_root` does in a guest.
+        let requests = [(1, 1), (2, 2), (1, 3), (3, 4), (2, 5)]
+            .map(|(seed, msg)| signed_ecrecover_request(seed, msg).0)
+            .to_vec();
+        let program = ecrecover_batch_program(FD_ECRECOVER_HOOK, &requests, true);
+        let mut runtime = Runtime::new(program, SP1CoreOpts::default());
+        runtime.run().unwrap();
+
+        let mut signers = (0..requests.len())
+            .map(|i| {
+                let key = read_hinted(&runtime, ecrecover_response_ptr(i), 33);
+                pubkey_to_address(&decompress_pubkey(&key.try_into().unwrap()).unwrap())
+            })
+            .collect::<Vec<_>>();
+        signers.sort_unstable();
+        signers.dedup();
+        assert_eq!(signers.len(), 3);
+
+        let msg_hashes = requests
+            .iter()
+            .map(|request| request[65..].try_into().unwrap())
+            .collect::<Vec<[u8; 32]>>();
+        let sigs = requests
+            .iter()
+            .map(|request| request[..65].try_into().unwrap())
+            .collect::<Vec<[u8; 65]>>();
+        let root = commit_signers_merkle_root(&msg_hashes, &sigs).unwrap();
+        assert_eq!(signers_merkle_root(&signers), root);
+        for signer in &signers {
+            let proof = signers_merkle_proof(&signers, signer).unwrap();
+            assert!(verify_signer_merkle_proof(&root, signer, &proof));
+        }
+    }
+
+    #[test]
+    fn ecrecover_batch_sent_at_once_is_read_out_of_order() {
+        // The responses of the last request come first, before those of the first one.
+        let (requests, _): (Vec<_>, Vec<_>) =
+            ecrecover_requests_with_responses(3).into_iter().unzip();
+        let program = ecrecover_batch_program(FD_ECRECOVER_HOOK, &requests, false);
+        let mut runtime = Runtime::new(program, SP1CoreOpts::default());
+        let err = runtime.run().unwrap_err();
+        assert!(matches!(
+            err,
+            ExecutionError::HintOutOfOrder {
+                consumed: HintTag {
+                    fd: FD_ECRECOVER_HOOK,
+                    seq: 2,
+                },
+                skipped: HintTag {
+                    fd: FD_ECRECOVER_HOOK,
+                    seq: 0,
+                },
+            }
+        ));
+    }
+
+    #[test]
+    fn ecrecover_r1_recovers_webauthn_key() {
+        use p256::ecdsa::SigningKey;
+        use sp1_zkvm::lib::secp256r1::{ecrecover, verify_recovered, webauthn_message_hash};
+        use sp1_zkvm::lib::sha256::sha256;
+
+        // An assertion by a passkey over the hash of the relying party id, the flags and sign count
+        // of the authenticator, and the hash of the client data.
+        let signing_key = SigningKey::from_slice(&[0x42; 32]).unwrap();
+        let mut authenticator_data = sha256(b"example.com").to_vec();
+        authenticator_data.extend_from_slice(&[0x05, 0, 0, 0, 1]);
+        let client_data_json =
+            br#"{"type":"webauthn.get","challenge":"c3AxLXdlYmF1dGhu","origin":"https://example.com"}"#;
+        let msg_hash = webauthn_message_hash(&authenticator_data, client_data_json);
+        let (sig, recid) = signing_key.sign_prehash_recoverable(&msg_hash).unwrap();
+
+        // Authenticators do not normalize s, so the guest also sends the high s signature, whose
+        // nonce point has the other parity.
+        let (r, s) = sig.split_scalars();
+        let signatures = [(*s, recid.is_y_odd()), (-*s, !recid.is_y_odd())].map(|(s, y_odd)| {
+            let mut request = r.to_bytes().to_vec();
+            request.extend_from_slice(&s.to_bytes());
+            request.push(y_odd as u8);
+            request
+        });
+        let requests = signatures
+            .iter()
+            .map(|sig| [&sig[..], &msg_hash].concat())
+            .collect::<Vec<_>>();
+        let program = ecrecover_batch_program(FD_ECRECOVER_R1_HOOK, &requests, true);
+        let mut runtime = Runtime::new(program, SP1CoreOpts::default());
+        runtime.run().unwrap();
+        assert!(runtime.state.unread_hook_responses.is_empty());
+
+        // The key and the inverse of s the guest read pass the checks of `secp256r1::ecrecover`.
+        let expected = signing_key.verifying_key().to_encoded_point(false);
+        for (i, sig) in signatures.iter().enumerate() {
+            let ptr = ecrecover_response_ptr(i);
+            let compressed: [u8; 33] = read_hinted(&runtime, ptr, 33).try_into().unwrap();
+            let s_inv = read_hinted(&runtime, ptr + 36, 32);
+            let s_inv = p256::Scalar::from_repr(*p256::FieldBytes::from_slice(&s_inv)).unwrap();
+            let signature = P256Signature::from_slice(&sig[..64]).unwrap();
+            let pubkey = verify_recovered(&compressed, &s_inv, &signature, sig[64] == 1, &msg_hash);
+            assert_eq!(pubkey.unwrap(), expected.as_bytes(), "signature {i}");
+            assert_eq!(
+                ecrecover(&sig[..].try_into().unwrap(), &msg_hash).unwrap(),
+                expected.as_bytes()
+            );
+        }
+    }
+
+    #[test]
+    fn hook_cache_runs_hook_once_per_distinct_request() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // 2048 requests by 8 signers, and a malformed one.
+        let batch = ecrecover_requests(8, 2048);
+        let hook_calls = |hook_cache_capacity| {
+            let calls = AtomicUsize::new(0);
+            let context = SP1Context::builder()
+                .hook(FD_ECRECOVER_HOOK, |env, buf| {
+                    calls.fetch_add(1, Ordering::Relaxed);
+                    hook_ecrecover(env, buf)
+                })
+                .build();
+            let runtime = Runtime::with_context(
+                Program::from(ECRECOVER_ELF),
+                memoizing_opts(hook_cache_capacity),
+                context,
+            );
+            for buf in &batch {
+                runtime.hook_memoized(FD_ECRECOVER_HOOK, buf);
+            }
+            let counters = runtime.hook_cache.as_ref().map(|cache| {
+                let cache = cache.lock().unwrap();
+                (cache.hits(), cache.misses())
+            });
+            drop(runtime);
+            (calls.into_inner(), counters)
+        };
+        assert_eq!(hook_calls(0), (2049, None));
+        assert_eq!(hook_calls(64), (9, Some((2040, 9))));
+    }
+
+    /// Differential tests of the `ecrecover` hook and of the host build of sp1-lib against k256.
+    mod differential {
+        use k256::ecdsa::signature::hazmat::PrehashVerifier;
+        use k256::ecdsa::SigningKey;
+        use k256::Scalar;
+        use proptest::prelude::*;
+        use sp1_zkvm::lib::secp256k1 as lib;
+        use sp1_zkvm::lib::secp256k1::recoverable::RecoverableSignature;
+
+        use super::*;
+
+        /// The order of secp256k1.
+        const N: &str = "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141";
+        /// The order of secp256k1, minus one.
+        const N_MINUS_ONE: &str =
+            "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364140";
+        /// The largest low s value, `(n - 1) / 2`.
+        const HALF_N: &str = "7fffffffffffffffffffffffffffffff5d576e7357a4501ddfe92f46681b20a0";
+        const HALF_N_PLUS_ONE: &str =
+            "7fffffffffffffffffffffffffffffff5d576e7357a4501ddfe92f46681b20a1";
+        /// The order of the base field of secp256k1.
+        const P: &str = "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f";
+
+        fn bytes32(hex_str: &str) -> [u8; 32] {
+            hex::decode(hex_str).unwrap().try_into().unwrap()
+        }
+
+        /// Scalars and digests at the edges of the ranges that the signature checks depend on.
+        fn boundary_value() -> impl Strategy<Value = [u8; 32]> {
+            let mut one = [0u8; 32];
+            one[31] = 1;
+            prop::sample::select(vec![
+                [0u8; 32],
+                one,
+                bytes32(N_MINUS_ONE),
+                bytes32(N),
+                bytes32(HALF_N),
+                bytes32(HALF_N_PLUS_ONE),
+                bytes32(P),
+                [0xff; 32],
+            ])
+        }
+
+        /// A change made to a valid signature request of the form `r || s || v || msg_hash`.
+        #[derive(Clone, Debug)]
+        enum Mutation {
+            None,
+            /// Replaces s with `n - s` and flips the parity, which recovers the same key.
+            Malleate,
+            FlipBit(usize),
+            FlipParity,
+            SetR([u8; 32]),
+            SetS([u8; 32]),
+            SetRecoveryId(u8),
+        }
+
+        fn mutation() -> impl Strategy<Value = Mutation> {
+            prop_oneof![
+                Just(Mutation::None),
+                Just(Mutation::Malleate),
+                (0..(64 + 32) * 8usize).prop_map(Mutation::FlipBit),
+                Just(Mutation::FlipParity),
+                boundary_value().prop_map(Mutation::SetR),
+                boundary_value().prop_map(Mutation::SetS),
+                (2..=u8::MAX).prop_map(Mutation::SetRecoveryId),
+            ]
+        }
+
+        fn signing_key() -> impl Strategy<Value = SigningKey> {
+            any::<[u8; 32]>().prop_filter_map("not a valid secret key", |bytes| {
+                SigningKey::from_slice(&bytes).ok()
+            })
+        }
+
+        fn msg_hash() -> impl Strategy<Value = [u8; 32]> {
+            prop_oneof![any::<[u8; 32]>(), boundary_value()]
+        }
+
+        /// Signs `msg_hash` and returns the mutated request `r || s || v`, without the hash.
+        fn signature_request(
+            key: &SigningKey,
+            msg_hash: &[u8; 32],
+            mutation: &Mutation,
+        ) -> [u8; 65] {
+            let (sig, recid) = key.sign_prehash_recoverable(msg_hash).unwrap();
+            let mut request = [0u8; 65];
+            request[..64].copy_from_slice(&sig.to_bytes());
+            request[64] = recid.to_byte();
+            match mutation {
+                Mutation::None => {}
+                Mutation::Malleate => {
+                    let (_, s) = sig.split_scalars();
+                    request[32..64].copy_from_slice(&(-*s.as_ref()).to_bytes());
+                    request[64] ^= 1;
+                }
+                // Bits past the signature are flipped in the message hash by the caller.
+                Mutation::FlipBit(bit) if *bit < 64 * 8 => request[bit / 8] ^= 1 << (bit % 8),
+                Mutation::FlipBit(_) => {}
+                Mutation::FlipParity => request[64] ^= 1,
+                Mutation::SetR(r) => request[..32].copy_from_slice(r),
+                Mutation::SetS(s) => request[32..64].copy_from_slice(s),
+                Mutation::SetRecoveryId(id) => request[64] = *id,
+            }
+            request
+        }
+
+        fn mutated_msg_hash(msg_hash: &[u8; 32], mutation: &Mutation) -> [u8; 32] {
+            let mut msg_hash = *msg_hash;
+            if let Mutation::FlipBit(bit) = mutation {
+                if let Some(bit) = bit.checked_sub(64 * 8) {
+                    msg_hash[bit / 8] ^= 1 << (bit % 8);
+                }
+            }
+            msg_hash
+        }
+
+        /// Recovers the key of a request with k256, which only accepts low s values. Like
+        /// Ethereum's `ecrecover`, a high s is accepted as the low `n - s`, which negates the nonce
+        /// point and so flips the parity of its y-coordinate.
+        fn k256_recover(request: &[u8; 65], msg_hash: &[u8; 32]) -> Option<VerifyingKey> {
+            let sig = Signature::from_slice(&request[..64]).ok()?;
+            let (sig, flip) = match sig.normalize_s() {
+                Some(normalized) => (normalized, 1),
+                None => (sig, 0),
+            };
+            let recid = RecoveryId::from_byte(request[64] ^ flip)?;
+            VerifyingKey::recover_from_prehash(msg_hash, &sig, recid).ok()
+        }
+
+        fn uncompressed_pubkey(key: &SigningKey) -> [u8; 65] {
+            key.verifying_key()
+                .to_encoded_point(false)
+                .as_bytes()
+                .try_into()
+                .unwrap()
+        }
+
+        /// The message hash of the guest of `ECRECOVER_ELF`.
+        const GUEST_MSG_HASH: &str =
+            "5ae8317d34d1e595e3fa7247db80c0af4320cce1116de187f8f7e2e099c0d8d0";
+        /// The signature that the guest of `ECRECOVER_ELF` recovers the signer of.
+        const GUEST_SIG: &str = concat!(
+            "45c0b7f8c09a9e1f1cea0c25785594427b6bf8f9f878a8af0b1abbb48e16d092",
+            "0d8becd0c220f67c51217eecfd7184ef0732481c843857e6bc7fc095c4f6b78801"
+        );
+
+        /// Executes `ECRECOVER_ELF` with its signature replaced by `sig`, and returns the public
+        /// key committed by the guest, or `None` if the execution fails.
+        fn guest_ecrecover(sig: &[u8; 65]) -> Option<Vec<u8>> {
+            let guest_sig = hex::decode(GUEST_SIG).unwrap();
+            let mut elf = ECRECOVER_ELF.to_vec();
+            let offset = elf
+                .windows(65)
+                .position(|window| window == guest_sig)
+                .unwrap();
+            elf[offset..offset + 65].copy_from_slice(sig);
+
+            let mut runtime = Runtime::new(Program::from(&elf), SP1CoreOpts::default());
+            runtime.run().ok()?;
+            Some(runtime.state.public_values_stream)
+        }
+
+        fn hook_request(request: &[u8; 65], msg_hash: &[u8; 32]) -> Vec<u8> {
+            let mut buf = request.to_vec();
+            buf.extend_from_slice(msg_hash);
+            buf
+        }
+
+        proptest! {
+            #[test]
+            fn hook_ecrecover_matches_k256(
+                key in signing_key(),
+                msg_hash in msg_hash(),
+                mutation in mutation(),
+            ) {
+                let request = signature_request(&key, &msg_hash, &mutation);
+                let msg_hash = mutated_msg_hash(&msg_hash, &mutation);
+                let expected = k256_recover(&request, &msg_hash);
+                let response = ecrecover_response(ecrecover_k1(&hook_request(&request, &msg_hash)));
+
+                match expected {
+                    Some(expected) => {
+                        prop_assert_eq!(response.len(), 2);
+                        prop_assert_eq!(&response[0], &expected.to_sec1_bytes().to_vec());
+                        // The inverse is the one of the normalized s.
+                        let sig = Signature::from_slice(&request[..64]).unwrap();
+                        let (_, s) = sig.normalize_s().unwrap_or(sig).split_scalars();
+                        let s_inverse =
+                            Scalar::from_repr(*k256::FieldBytes::from_slice(&response[1]));
+                        prop_assert_eq!(s_inverse.unwrap() * s.as_ref(), Scalar::ONE);
+                    }
+                    None => {
+                        prop_assert_eq!(response.len(), 1);
+                        let status = if Signature::from_slice(&request[..64]).is_err() {
+                            HOOK_STATUS_INVALID_SIGNATURE
+                        } else if request[64] > 3 {
+                            HOOK_STATUS_INVALID_RECOVERY_ID
+                        } else {
+                            HOOK_STATUS_UNRECOVERABLE
+                        };
+                        prop_assert_eq!(response[0][0], status);
+                    }
+                }
+                if matches!(mutation, Mutation::None | Mutation::Malleate) {
+                    prop_assert_eq!(expected.as_ref(), Some(key.verifying_key()));
+                }
+            }
+
+            #[test]
+            fn lib_ecrecover_matches_k256(
+                key in signing_key(),
+                msg_hash in msg_hash(),
+                mutation in mutation(),
+                v_offset in prop::sample::select(vec![0u8, 27]),
+            ) {
+                let mut request = signature_request(&key, &msg_hash, &mutation);
+                let msg_hash = mutated_msg_hash(&msg_hash, &mutation);
+                if request[64] < 2 {
+                    request[64] += v_offset;
+                }
+
+                // The parity of a yParity, pre-EIP-155 or EIP-155 v value.
+                let parity = match request[64] {
+                    v @ 0..=1 => Some(v),
+                    v @ 27..=28 => Some(v - 27),
+                    v @ 35.. => Some((v - 35) % 2),
+                    _ => None,
+                };
+                let expected = parity.and_then(|parity| {
+                    let mut parity_request = request;
+                    parity_request[64] = parity;
+                    k256_recover(&parity_request, &msg_hash)
+                });
+                let expected = expected.map(|key| key.to_encoded_point(false).as_bytes().to_vec());
+                let recovered = lib::ecrecover(&request, &msg_hash).ok().map(|key| key.to_vec());
+                prop_assert_eq!(recovered, expected);
+            }
+
+            #[test]
+            fn lib_verify_signature_matches_k256(
+                key in signing_key(),
+                msg_hash in msg_hash(),
+                mutation in mutation(),
+            ) {
+                let request = signature_request(&key, &msg_hash, &mutation);
+                let msg_hash = mutated_msg_hash(&msg_hash, &mutation);
+                let Ok(sig) = Signature::from_slice(&request[..64]) else {
+                    return Ok(());
+                };
+                let mut pubkey = [0u8; 65];
+                pubkey.copy_from_slice(key.verifying_key().to_encoded_point(false).as_bytes());
+
+                let expected = key.verifying_key().verify_prehash(&msg_hash, &sig).is_ok();
+                prop_assert_eq!(lib::verify_signature(&pubkey, &msg_hash, &sig, None), expected);
+            }
+
+            #[test]
+            fn lib_verify_with_known_signer_matches_ecrecover(
+                key in signing_key(),
+                other_key in signing_key(),
+                msg_hash in msg_hash(),
+                mutation in mutation(),
+                v_offset in prop::sample::select(vec![0u8, 27, 37]),
+            ) {
+                let mut sig = signature_request(&key, &msg_hash, &mutation);
+                let msg_hash = mutated_msg_hash(&msg_hash, &mutation);
+                if sig[64] < 2 {
+                    sig[64] += v_offset;
+                }
+
+                let pubkey = uncompressed_pubkey(&key);
+                let other_pubkey = uncompressed_pubkey(&other_key);
+                let recovered = lib::ecrecover(&sig, &msg_hash);
+                for expected in [pubkey, other_pubkey] {
+                    match lib::verify_with_known_signer(&expected, &sig, &msg_hash) {
+                        Ok(verified) => prop_assert_eq!(verified, recovered == Ok(expected)),
+                        Err(err) => prop_assert_eq!(&Err(err), &recovered),
+                    }
+                }
+                // A valid signature is only accepted for its signer.
+                let valid = matches!(mutation, Mutation::None | Mutation::Malleate);
+                if valid && other_pubkey != pubkey {
+                    let verified = lib::verify_with_known_signer(&other_pubkey, &sig, &msg_hash);
+                    prop_assert_eq!(verified, Ok(false));
+                }
+            }
+
+            #[test]
+            fn lib_normalize_signature_keeps_signer(
+                key in signing_key(),
+                msg_hash in msg_hash(),
+                malleate in any::<bool>(),
+                v_encoding in prop_oneof![
+                    Just(0u8),
+                    Just(27),
+                    (0..=109u8).prop_map(|chain_id| chain_id * 2 + 35),
+                ],
+            ) {
+                let mutation = if malleate { Mutation::Malleate } else { Mutation::None };
+                let mut sig = signature_request(&key, &msg_hash, &mutation);
+                sig[64] += v_encoding;
+                let before = lib::ecrecover(&sig, &msg_hash).unwrap();
+
+                let mut normalized = sig;
+                prop_assert_eq!(lib::normalize_signature(&mut normalized), Ok(malleate));
+                prop_assert_eq!(lib::normalized_signature(&sig), Ok(normalized));
+                prop_assert_eq!(lib::ecrecover(&normalized, &msg_hash).unwrap(), before);
+                let expected = key.verifying_key().to_encoded_point(false);
+                prop_assert_eq!(&before[..], expected.as_bytes());
+                // The encoding of v is kept, and normalizing is idempotent.
+                let parity = (sig[64] - v_encoding) ^ malleate as u8;
+                prop_assert_eq!(normalized[64], parity + v_encoding);
+                let mut renormalized = normalized;
+                prop_assert_eq!(lib::normalize_signature(&mut renormalized), Ok(false));
+            }
+
+            #[test]
+            fn lib_recoverable_signature_round_trips(
+                key in signing_key(),
+                msg_hash in msg_hash(),
+                malleate in any::<bool>(),
+                v_encoding in prop_oneof![
+                    Just(0u8),
+                    Just(27),
+                    (0..=109u8).prop_map(|chain_id| chain_id * 2 + 35),
+                ],
+            ) {
+                let mutation = if malleate { Mutation::Malleate } else { Mutation::None };
+                let mut bytes = signature_request(&key, &msg_hash, &mutation);
+                bytes[64] += v_encoding;
+                let sig = RecoverableSignature::try_from(bytes).unwrap();
+                let expected = key.verifying_key().to_encoded_point(false);
+                prop_assert_eq!(&sig.recover(&msg_hash).unwrap()[..], expected.as_bytes());
+
+                // The canonical encoding only differs for yParity values, which become 27 or 28.
+                let canonical = <[u8; 65]>::try_from(sig).unwrap();
+                prop_assert_eq!(RecoverableSignature::try_from(canonical), Ok(sig));
+                if v_encoding != 0 {
+                    prop_assert_eq!(canonical, bytes);
+                }
+
+                let serialized = bincode::serialize(&sig).unwrap();
+                prop_assert_eq!(&serialized, &canonical.to_vec());
+                let deserialized: RecoverableSignature = bincode::deserialize(&serialized).unwrap();
+                prop_assert_eq!(deserialized, sig);
+
+                let alloy_sig = alloy_primitives::Signature::from(sig);
+                prop_assert_eq!(RecoverableSignature::try_from(alloy_sig), Ok(sig));
+
+                // The k256 and compact forms have no chain id.
+                let without_chain_id = lib::RecoveryMeta::new(sig.meta().parity(), None).unwrap();
+                let without_chain_id =
+                    RecoverableSignature::new(*sig.r(), *sig.s(), without_chain_id).unwrap();
+                let k256_sig = <(Signature, RecoveryId)>::from(sig);
+                prop_assert_eq!(RecoverableSignature::try_from(k256_sig), Ok(without_chain_id));
+                match <[u8; 64]>::try_from(sig) {
+                    Ok(compact) => {
+                        prop_assert!(!malleate);
+                        let from_compact = RecoverableSignature::try_from(compact);
+                        prop_assert_eq!(from_compact, Ok(without_chain_id));
+                    }
+                    Err(_) => prop_assert!(malleate),
+                }
+
+                // Chain ids whose v values do not fit in a byte have no canonical encoding.
+                let wide_meta = lib::RecoveryMeta::new(sig.meta().parity(), Some(1 << 40)).unwrap();
+                let wide = RecoverableSignature::new(*sig.r(), *sig.s(), wide_meta).unwrap();
+                prop_assert!(<[u8; 65]>::try_from(wide).is_err());
+                let alloy_wide = alloy_primitives::Signature::from(wide);
+                prop_assert_eq!(RecoverableSignature::try_from(alloy_wide), Ok(wide));
+            }
+        }
+
+        proptest! {
+            // Each case executes the guest, so fewer signatures are sampled.
+            #![proptest_config(ProptestConfig::with_cases(16))]
+
+            #[test]
+            fn guest_ecrecover_matches_k256(
+                key in signing_key(),
+                mutation in mutation().prop_filter("the message hash is built into the guest", |m| {
+                    !matches!(m, Mutation::FlipBit(bit) if *bit >= 64 * 8)
+                }),
+            ) {
+                let msg_hash = bytes32(GUEST_MSG_HASH);
+                let sig = signature_request(&key, &msg_hash, &mutation);
+                // The prebuilt guest predates the support of high s values, and checks the inverse
+                // from the hook, which is that of the normalized s, against its own s.
+                let high_s = Signature::from_slice(&sig[..64])
+                    .is_ok_and(|sig| sig.normalize_s().is_some());
+                prop_assume!(!high_s);
+                let expected = k256_recover(&sig, &msg_hash)
+                    .map(|key| key.to_encoded_point(false).as_bytes().to_vec());
+                prop_assert_eq!(guest_ecrecover(&sig), expected);
+            }
+        }
     }
 
     #[test]
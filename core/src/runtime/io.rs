@@ -8,6 +8,44 @@ use serde::Serialize;
 
 use super::Runtime;
 
+/// The largest entry of the input stream. Longer inputs and hook responses are split into chunks
+/// of at most this length, which guests reassemble in `sp1_zkvm::io::read_vec`.
+pub const MAX_HINT_CHUNK_LEN: usize = 1 << 20;
+
+/// The first bytes of the header entry of a chunked input.
+pub const CHUNKED_HINT_MAGIC: [u8; 8] = *b"SP1CHUNK";
+
+/// The length of the header entry of a chunked input: the magic bytes, followed by the total
+/// length as a little endian `u64` and the number of chunks as a little endian `u32`.
+pub const CHUNKED_HINT_HEADER_LEN: usize = 20;
+
+/// Splits an input into the entries of the input stream that carry it.
+///
+/// Inputs longer than [`MAX_HINT_CHUNK_LEN`] are sent as a header entry followed by chunks, each
+/// made of its index as a little endian `u32` and the next bytes of the input. So that headers are
+/// unambiguous, inputs that look like a header are chunked as well. Other inputs are sent as is.
+pub fn chunk_input(input: Vec<u8>) -> Vec<Vec<u8>> {
+    let looks_like_header =
+        input.len() == CHUNKED_HINT_HEADER_LEN && input.starts_with(&CHUNKED_HINT_MAGIC);
+    if input.len() <= MAX_HINT_CHUNK_LEN && !looks_like_header {
+        return vec![input];
+    }
+
+    let chunks = input.chunks(MAX_HINT_CHUNK_LEN - 4);
+    let mut header = CHUNKED_HINT_MAGIC.to_vec();
+    header.extend_from_slice(&(input.len() as u64).to_le_bytes());
+    header.extend_from_slice(&(chunks.len() as u32).to_le_bytes());
+
+    let mut entries = vec![header];
+    entries.extend(chunks.enumerate().map(|(index, data)| {
+        let mut chunk = Vec::with_capacity(4 + data.len());
+        chunk.extend_from_slice(&(index as u32).to_le_bytes());
+        chunk.extend_from_slice(data);
+        chunk
+    }));
+    entries
+}
+
 impl<'a> Read for Runtime<'a> {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
         self.read_public_values_slice(buf);
@@ -19,16 +57,16 @@ impl<'a> Runtime<'a> {
     pub fn write_stdin<T: Serialize>(&mut self, input: &T) {
         let mut buf = Vec::new();
         bincode::serialize_into(&mut buf, input).expect("serialization failed");
-        self.state.input_stream.push(buf);
+        self.state.input_stream.extend(chunk_input(buf));
     }
 
     pub fn write_stdin_slice(&mut self, input: &[u8]) {
-        self.state.input_stream.push(input.to_vec());
+        self.state.input_stream.extend(chunk_input(input.to_vec()));
     }
 
     pub fn write_vecs(&mut self, inputs: &[Vec<u8>]) {
         for input in inputs {
-            self.state.input_stream.push(input.clone());
+            self.state.input_stream.extend(chunk_input(input.clone()));
         }
     }
 
@@ -58,7 +96,7 @@ impl<'a> Runtime<'a> {
 #[cfg(test)]
 pub mod tests {
     use super::*;
-    use crate::runtime::Program;
+    use crate::runtime::{Instruction, Opcode, Program, SyscallCode};
     use crate::stark::DefaultProver;
     use crate::utils::tests::IO_ELF;
     use crate::utils::{self, prove_simple, BabyBearBlake3, SP1CoreOpts};
@@ -118,4 +156,129 @@ pub mod tests {
         let config = BabyBearBlake3::new();
         prove_simple::<_, DefaultProver<_, _>>(config, runtime).unwrap();
     }
+
+    #[test]
+    fn test_chunked_hints() {
+        use sp1_zkvm::lib::error::Error;
+        use sp1_zkvm::lib::io;
+
+        assert_eq!(MAX_HINT_CHUNK_LEN, io::MAX_HINT_CHUNK_LEN);
+        assert_eq!(CHUNKED_HINT_MAGIC, io::CHUNKED_HINT_MAGIC);
+        assert_eq!(CHUNKED_HINT_HEADER_LEN, io::CHUNKED_HINT_HEADER_LEN);
+
+        let reassemble = |entries: &[Vec<u8>]| {
+            let mut rest = entries[1..].iter().cloned();
+            io::reassemble_chunks(&entries[0], || rest.next().unwrap_or_default())
+        };
+
+        // A 16 MiB hint takes 17 chunks, as each one starts with its index.
+        let hint = (0..16 << 20).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        let entries = chunk_input(hint.clone());
+        assert_eq!(entries.len(), 18);
+        assert_eq!(entries[0].len(), CHUNKED_HINT_HEADER_LEN);
+        assert!(entries
+            .iter()
+            .all(|entry| entry.len() <= MAX_HINT_CHUNK_LEN));
+        assert_eq!(reassemble(&entries), Ok(hint));
+
+        let mut missing = entries.clone();
+        missing.remove(5);
+        assert_eq!(
+            reassemble(&missing),
+            Err(Error::HintChunkMissing { index: 4 })
+        );
+        let mut truncated = entries.clone();
+        truncated[17].pop();
+        assert_eq!(
+            reassemble(&truncated),
+            Err(Error::HintLengthMismatch {
+                expected: 16 << 20,
+                actual: (16 << 20) - 1,
+            })
+        );
+
+        // Inputs up to the limit are sent as is, unless they look like a header.
+        let small = vec![7u8; MAX_HINT_CHUNK_LEN];
+        assert_eq!(chunk_input(small.clone()), vec![small]);
+        let mut lookalike = CHUNKED_HINT_MAGIC.to_vec();
+        lookalike.resize(CHUNKED_HINT_HEADER_LEN, 0);
+        let entries = chunk_input(lookalike.clone());
+        assert_eq!(entries.len(), 2);
+        assert_eq!(reassemble(&entries), Ok(lookalike));
+
+        // Entries that are not headers are rejected rather than indexed out of bounds.
+        assert_eq!(
+            io::reassemble_chunks(&CHUNKED_HINT_MAGIC, Vec::new),
+            Err(Error::HintHeaderMalformed { len: 8 })
+        );
+        assert_eq!(
+            io::reassemble_chunks(&[0; CHUNKED_HINT_HEADER_LEN], Vec::new),
+            Err(Error::HintHeaderMalformed {
+                len: CHUNKED_HINT_HEADER_LEN
+            })
+        );
+
+        let mut runtime = Runtime::new(Program::from(IO_ELF), SP1CoreOpts::default());
+        runtime.write_stdin_slice(&[1, 2, 3]);
+        runtime.write_vecs(&[vec![0; 3 << 20]]);
+        assert_eq!(runtime.state.input_stream.len(), 1 + 5);
+    }
+
+    /// The address a guest of [`read_entries_program`] reads the entry with the given index to.
+    fn entry_ptr(index: usize) -> u32 {
+        0x100000 + (index as u32) * (2 * MAX_HINT_CHUNK_LEN as u32)
+    }
+
+    /// A guest that reads the next `count` entries of the input stream, each to its own address,
+    /// as `sp1_zkvm::io::read_vec` does for a chunked buffer.
+    fn read_entries_program(count: usize) -> Program {
+        let mut instructions = Vec::new();
+        for index in 0..count {
+            instructions.extend([
+                Instruction::new(Opcode::ADD, 5, 0, SyscallCode::HINT_LEN as u32, false, true),
+                Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+                Instruction::new(Opcode::ADD, 11, 5, 0, false, true),
+                Instruction::new(Opcode::ADD, 10, 0, entry_ptr(index), false, true),
+                Instruction::new(
+                    Opcode::ADD,
+                    5,
+                    0,
+                    SyscallCode::HINT_READ as u32,
+                    false,
+                    true,
+                ),
+                Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            ]);
+        }
+        Program::new(instructions, 0, 0)
+    }
+
+    #[test]
+    fn test_chunked_hint_read_by_guest() {
+        use sp1_zkvm::lib::io;
+
+        // A 2.5 MiB input is sent as a header and three chunks.
+        let input = (0..5 << 19).map(|i| (i % 253) as u8).collect::<Vec<_>>();
+        let entries = chunk_input(input.clone());
+        assert_eq!(entries.len(), 4);
+
+        let mut runtime = Runtime::new(read_entries_program(entries.len()), SP1CoreOpts::default());
+        runtime.write_vecs(&[input.clone()]);
+        runtime.run().unwrap();
+        assert_eq!(runtime.state.input_stream_ptr, entries.len());
+
+        // The guest never loads the entries, so they are still in uninitialized memory.
+        let memory = &runtime.state.uninitialized_memory;
+        let mut read = entries.iter().enumerate().map(|(index, entry)| {
+            let ptr = entry_ptr(index);
+            (ptr..ptr + entry.len() as u32)
+                .map(|addr| (memory[&(addr & !3)] >> (8 * (addr & 3))) as u8)
+                .collect::<Vec<_>>()
+        });
+        let header = read.next().unwrap();
+        assert_eq!(
+            io::reassemble_chunks(&header, || read.next().unwrap_or_default()),
+            Ok(input)
+        );
+    }
 }
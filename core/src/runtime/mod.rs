@@ -17,6 +17,7 @@ mod subproof;
 pub use context::*;
 pub use hooks::*;
 pub use instruction::*;
+pub use io::*;
 pub use memory::*;
 pub use opcode::*;
 pub use program::*;
@@ -116,6 +117,9 @@ pub struct Runtime<'a> {
 
     /// The maximum number of cpu cycles to use for execution.
     pub max_cycles: Option<u64>,
+
+    /// The cache of hook responses, if they are memoized.
+    pub hook_cache: Option<SharedHookCache>,
 }
 
 #[derive(Error, Debug)]
@@ -132,6 +136,8 @@ pub enum ExecutionError {
     ExceededCycleLimit(u64),
     #[error("got unimplemented as opcode")]
     Unimplemented(),
+    #[error("read a response of the {consumed} before the unread ones of the {skipped}")]
+    HintOutOfOrder { consumed: HintTag, skipped: HintTag },
 }
 
 impl<'a> Runtime<'a> {
@@ -171,6 +177,9 @@ impl<'a> Runtime<'a> {
             .subproof_verifier
             .unwrap_or_else(|| Arc::new(DefaultSubproofVerifier::new()));
         let hook_registry = context.hook_registry.unwrap_or_default();
+        let hook_cache = context.hook_cache.or_else(|| {
+            (opts.hook_cache_capacity > 0).then(|| HookCache::shared(opts.hook_cache_capacity))
+        });
 
         Self {
             record,
@@ -194,6 +203,7 @@ impl<'a> Runtime<'a> {
             hook_registry,
             opts,
             max_cycles: context.max_cycles,
+            hook_cache,
         }
     }
 
@@ -206,6 +216,99 @@ impl<'a> Runtime<'a> {
             .invoke_hook(self.hook_env(), buf)
     }
 
+    /// Invokes the hook at `fd` like [`Self::hook`], answering repeated requests from the hook
+    /// cache if the hook is memoized.
+    ///
+    /// The cache is not locked while the hook runs, so that executions sharing it do not wait on
+    /// each other.
+    pub fn hook_memoized(&self, fd: u32, buf: &[u8]) -> Vec<Vec<u8>> {
+        let Some(cache) = &self.hook_cache else {
+            return self.hook(fd, buf);
+        };
+        {
+            let mut cache = cache.lock().unwrap();
+            if !cache.memoizes(fd) {
+                drop(cache);
+                return self.hook(fd, buf);
+            }
+            if let Some(res) = cache.get(fd, buf) {
+                return res;
+            }
+        }
+        let res = self.hook(fd, buf);
+        cache.lock().unwrap().insert(fd, buf, res.clone());
+        res
+    }
+
+    /// Inserts the responses of the hook at `fd` at the current position of the input stream, so
+    /// that they are read next, and tags them with the hook invocation.
+    ///
+    /// Responses longer than [`MAX_HINT_CHUNK_LEN`] are split into chunks with [`chunk_input`].
+    pub fn insert_hook_responses(&mut self, fd: u32, responses: Vec<Vec<u8>>) {
+        let responses = responses
+            .into_iter()
+            .flat_map(chunk_input)
+            .collect::<Vec<_>>();
+        let ptr = self.state.input_stream_ptr;
+        let tag = HintTag {
+            fd,
+            seq: self.state.hook_invocations,
+        };
+        self.state.hook_invocations += 1;
+
+        let tags = &mut self.state.input_stream_tags;
+        if tags.len() < ptr {
+            tags.resize(ptr, None);
+        }
+        tags.splice(ptr..ptr, std::iter::repeat(Some(tag)).take(responses.len()));
+        if !responses.is_empty() {
+            self.state
+                .unread_hook_responses
+                .push_back((tag, responses.len()));
+        }
+        self.state.input_stream.splice(ptr..ptr, responses);
+    }
+
+    /// Checks that the next entry of the input stream is not a response of a hook invoked after
+    /// another one whose responses are still unread.
+    ///
+    /// Responses are inserted before all the unread entries, so the responses of a second hook
+    /// invoked before the responses of the first one are read would be read first. This is an
+    /// error, since guests read the responses in the order in which they invoked the hooks.
+    fn check_hint_order(&self) -> Result<(), ExecutionError> {
+        let Some(consumed) = self.next_hint_tag() else {
+            return Ok(());
+        };
+        // The entry is unread, so the oldest invocation with unread responses is at least as old.
+        match self.state.unread_hook_responses.front() {
+            Some(&(oldest, _)) if oldest != consumed => Err(ExecutionError::HintOutOfOrder {
+                consumed,
+                skipped: oldest,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the tag of the next entry of the input stream, if it is a hook response.
+    fn next_hint_tag(&self) -> Option<HintTag> {
+        let tags = &self.state.input_stream_tags;
+        tags.get(self.state.input_stream_ptr).copied().flatten()
+    }
+
+    /// Records that the next entry of the input stream is read, once [`Self::check_hint_order`]
+    /// accepted it.
+    fn mark_hint_read(&mut self) {
+        if self.next_hint_tag().is_some() {
+            let unread = &mut self.state.unread_hook_responses;
+            if let Some((_, count)) = unread.front_mut() {
+                *count -= 1;
+                if *count == 0 {
+                    unread.pop_front();
+                }
+            }
+        }
+    }
+
     /// Prepare a `HookEnv` for use by hooks.
     pub fn hook_env<'b>(&'b self) -> HookEnv<'b, 'a> {
         HookEnv { runtime: self }
@@ -841,6 +944,15 @@ impl<'a> Runtime<'a> {
                         .or_insert(1);
                 }
 
+                match syscall {
+                    SyscallCode::HINT_LEN => self.check_hint_order()?,
+                    SyscallCode::HINT_READ => {
+                        self.check_hint_order()?;
+                        self.mark_hint_read();
+                    }
+                    _ => {}
+                }
+
                 let syscall_impl = self.get_syscall(syscall).cloned();
                 let mut precompile_rt = SyscallContext::new(self);
                 precompile_rt.syscall_lookup_id = syscall_lookup_id;
@@ -1266,7 +1378,9 @@ pub mod tests {
         },
     };
 
-    use super::{Instruction, Opcode, Program, Runtime};
+    use super::{
+        ExecutionError, HintTag, Instruction, Opcode, Program, Runtime, SP1Context, SyscallCode,
+    };
 
     pub fn simple_program() -> Program {
         let instructions = vec![
@@ -1289,6 +1403,35 @@ pub mod tests {
         Program::from(PANIC_ELF)
     }
 
+    /// Invokes the hooks at `fds` with empty requests, then calls `syscall` to query a 1-byte hint,
+    /// either `HINT_LEN` or `HINT_READ`.
+    fn hooks_then_hint_program(fds: &[u32], syscall: SyscallCode) -> Program {
+        let mut instructions = Vec::new();
+        for &fd in fds {
+            instructions.extend([
+                Instruction::new(Opcode::ADD, 5, 0, SyscallCode::WRITE as u32, false, true),
+                Instruction::new(Opcode::ADD, 10, 0, fd, false, true),
+                Instruction::new(Opcode::ADD, 11, 0, 0x100, false, true),
+                Instruction::new(Opcode::ADD, 12, 0, 0, false, true),
+                Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+            ]);
+        }
+        instructions.extend([
+            Instruction::new(Opcode::ADD, 5, 0, syscall as u32, false, true),
+            Instruction::new(Opcode::ADD, 10, 0, 0x1000, false, true),
+            Instruction::new(Opcode::ADD, 11, 0, 1, false, true),
+            Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+        ]);
+        Program::new(instructions, 0, 0)
+    }
+
+    fn two_hooks_context<'a>() -> SP1Context<'a> {
+        SP1Context::builder()
+            .hook(30, |_, _| vec![vec![1], vec![2]])
+            .hook(31, |_, _| vec![vec![3]])
+            .build()
+    }
+
     fn _assert_send<T: Send>() {}
 
     /// Runtime needs to be Send so we can use it across async calls.
@@ -1914,4 +2057,81 @@ pub mod tests {
         assert_eq!(runtime.register(Register::X12), 0x12346525);
         assert_eq!(runtime.register(Register::X11), 0x65256525);
     }
+
+    #[test]
+    fn test_hint_read_in_hook_order() {
+        let program = hooks_then_hint_program(&[30], SyscallCode::HINT_READ);
+        let mut runtime =
+            Runtime::with_context(program, SP1CoreOpts::default(), two_hooks_context());
+        runtime.run().unwrap();
+        assert_eq!(runtime.state.input_stream_ptr, 1);
+        assert_eq!(
+            runtime.state.unread_hook_responses,
+            [(HintTag { fd: 30, seq: 0 }, 1)]
+        );
+    }
+
+    #[test]
+    fn test_hint_read_out_of_hook_order() {
+        // The responses of the second hook are inserted before the unread ones of the first.
+        let program = hooks_then_hint_program(&[30, 31], SyscallCode::HINT_READ);
+        let mut runtime =
+            Runtime::with_context(program, SP1CoreOpts::default(), two_hooks_context());
+        let err = runtime.run().unwrap_err();
+        assert!(matches!(
+            err,
+            ExecutionError::HintOutOfOrder {
+                consumed: HintTag { fd: 31, seq: 1 },
+                skipped: HintTag { fd: 30, seq: 0 },
+            }
+        ));
+    }
+
+    #[test]
+    fn test_hint_len_out_of_hook_order() {
+        let program = hooks_then_hint_program(&[30, 31], SyscallCode::HINT_LEN);
+        let mut runtime =
+            Runtime::with_context(program, SP1CoreOpts::default(), two_hooks_context());
+        let err = runtime.run().unwrap_err();
+        assert!(matches!(
+            err,
+            ExecutionError::HintOutOfOrder {
+                consumed: HintTag { fd: 31, seq: 1 },
+                skipped: HintTag { fd: 30, seq: 0 },
+            }
+        ));
+    }
+
+    #[test]
+    fn test_hint_reads_across_many_hooks() {
+        // Invokes the hook and reads both of its responses, before invoking it again.
+        let invocation = hooks_then_hint_program(&[30], SyscallCode::HINT_READ).instructions;
+        let mut instructions = Vec::new();
+        for i in 0..100 {
+            instructions.extend_from_slice(&invocation[..5]);
+            for j in 0..2 {
+                // Each response is read into fresh memory.
+                let ptr = 0x1000 + 8 * i + 4 * j;
+                instructions.extend([
+                    Instruction::new(
+                        Opcode::ADD,
+                        5,
+                        0,
+                        SyscallCode::HINT_READ as u32,
+                        false,
+                        true,
+                    ),
+                    Instruction::new(Opcode::ADD, 10, 0, ptr, false, true),
+                    Instruction::new(Opcode::ADD, 11, 0, 1, false, true),
+                    Instruction::new(Opcode::ECALL, 5, 10, 11, false, false),
+                ]);
+            }
+        }
+        let program = Program::new(instructions, 0, 0);
+        let mut runtime =
+            Runtime::with_context(program, SP1CoreOpts::default(), two_hooks_context());
+        runtime.run().unwrap();
+        assert_eq!(runtime.state.input_stream_ptr, 200);
+        assert!(runtime.state.unread_hook_responses.is_empty());
+    }
 }
@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     fs::File,
     io::{Seek, Write},
 };
@@ -58,6 +58,17 @@ pub struct ExecutionState {
     /// A ptr to the current position in the input stream incremented by HINT_READ opcode.
     pub input_stream_ptr: usize,
 
+    /// The hook invocation that produced each entry of the input stream, or `None` for the other
+    /// entries. The entries past the end of this vector are not from hooks.
+    pub input_stream_tags: Vec<Option<HintTag>>,
+
+    /// The number of hooks invoked so far.
+    pub hook_invocations: u64,
+
+    /// The hook invocations with unread responses in the input stream, oldest first, with the
+    /// number of their responses left unread.
+    pub unread_hook_responses: VecDeque<(HintTag, usize)>,
+
     /// A stream of proofs inputted to the program.
     pub proof_stream: Vec<(
         ShardProof<BabyBearPoseidon2>,
@@ -90,6 +101,9 @@ impl ExecutionState {
             uninitialized_memory: HashMap::default(),
             input_stream: Vec::new(),
             input_stream_ptr: 0,
+            input_stream_tags: Vec::new(),
+            hook_invocations: 0,
+            unread_hook_responses: VecDeque::new(),
             public_values_stream: Vec::new(),
             public_values_stream_ptr: 0,
             proof_stream: Vec::new(),
@@ -99,6 +113,23 @@ impl ExecutionState {
     }
 }
 
+/// Identifies the hook invocation that produced an entry of the input stream.
+///
+/// Tags are only kept by the executor, the guest reads the same bytes as without them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HintTag {
+    /// The file descriptor of the hook.
+    pub fd: u32,
+    /// The number of hooks invoked before this one.
+    pub seq: u64,
+}
+
+impl std::fmt::Display for HintTag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "hook at fd {} (invocation {})", self.fd, self.seq)
+    }
+}
+
 /// Holds data to track changes made to the runtime since a fork point.
 #[derive(Debug, Clone, Default)]
 pub(crate) struct ForkState {
@@ -29,4 +29,140 @@ mod tests {
     fn test_uint256_modulus() {
         assert_eq!(biguint_from_limbs(U256Field::MODULUS), U256Field::modulus());
     }
+
+    /// Differential and adversarial tests of `sp1_lib::bigmod` against `num-bigint`.
+    mod bigmod {
+        use num::bigint::RandBigInt;
+        use num::{BigUint, One, Zero};
+        use rand::{thread_rng, Rng};
+        use sp1_zkvm::lib::bigmod::BigMod;
+
+        use crate::runtime::{hook_bigmod, Program, Runtime, SP1Context, FD_BIGMOD_HOOK};
+        use crate::utils::{tests::UINT256_MUL_ELF, SP1CoreOpts};
+
+        fn to_bytes(x: &BigUint, len: usize) -> Vec<u8> {
+            let bytes = x.to_bytes_be();
+            let mut padded = vec![0u8; len.saturating_sub(bytes.len())];
+            padded.extend_from_slice(&bytes);
+            padded
+        }
+
+        /// Checks every operation modulo `m` against `num-bigint`.
+        fn check_operations(arith: &BigMod, m: &BigUint, exp_bits: u64) {
+            let mut rng = thread_rng();
+            let len = (m.bits() as usize + 7) / 8;
+            let a = rng.gen_biguint_below(m);
+            // Operands may be wider than the modulus, and are reduced first.
+            let b = rng.gen_biguint(2 * m.bits());
+            let exp = rng.gen_biguint(exp_bits);
+            let (a_bytes, b_bytes) = (to_bytes(&a, len), to_bytes(&b, 0));
+
+            assert_eq!(arith.mul(&a_bytes, &b_bytes), to_bytes(&(&a * &b % m), len));
+            assert_eq!(
+                arith.add(&a_bytes, &b_bytes),
+                to_bytes(&((&a + &b) % m), len)
+            );
+            assert_eq!(
+                arith.sub(&a_bytes, &b_bytes),
+                to_bytes(&((&a + m - &b % m) % m), len)
+            );
+            assert_eq!(
+                arith.pow(&a_bytes, &to_bytes(&exp, 0)),
+                to_bytes(&a.modpow(&exp, m), len)
+            );
+            assert_eq!(
+                arith.inv(&b_bytes),
+                (&b % m).modinv(m).map(|inverse| to_bytes(&inverse, len))
+            );
+        }
+
+        fn random_modulus(bits: u64) -> BigUint {
+            let mut rng = thread_rng();
+            rng.gen_biguint(bits) | (BigUint::one() << (bits - 1))
+        }
+
+        #[test]
+        fn test_bigmod_matches_num_bigint() {
+            let runtime = Runtime::new(Program::from(UINT256_MUL_ELF), SP1CoreOpts::default());
+            let mut rng = thread_rng();
+            let widths = [1, 8, 255, 256, 257, 384, 1024, 2048, 3072, 4096]
+                .into_iter()
+                .chain((0..8).map(|_| rng.gen_range(1..=4096)));
+            for bits in widths {
+                let m = random_modulus(bits);
+                let hinted = BigMod::with_hints(&m.to_bytes_be(), |request| {
+                    runtime.hook(FD_BIGMOD_HOOK, request)
+                })
+                .unwrap();
+                check_operations(&hinted, &m, 64);
+                if bits <= 256 {
+                    check_operations(&BigMod::new(&m.to_bytes_be()).unwrap(), &m, bits);
+                }
+            }
+        }
+
+        #[test]
+        fn test_bigmod_rejects_bad_hints() {
+            type Tamper = fn(&[u8], Vec<Vec<u8>>) -> Vec<Vec<u8>>;
+            let tampers: [Tamper; 7] = [
+                // A remainder that does not satisfy the identity.
+                |_, mut res| {
+                    res[1] = {
+                        let x = BigUint::from_bytes_be(&res[1]) + 1u32;
+                        to_bytes(&x, res[1].len())
+                    };
+                    res
+                },
+                // A remainder of at least the modulus, with a smaller quotient.
+                |request, res| {
+                    let len = u32::from_le_bytes(request[1..5].try_into().unwrap()) as usize;
+                    let m = BigUint::from_bytes_be(&request[5..5 + len]);
+                    let q = BigUint::from_bytes_be(&res[0]);
+                    if q.is_zero() {
+                        return res;
+                    }
+                    let r = BigUint::from_bytes_be(&res[1]) + &m;
+                    vec![to_bytes(&(q - 1u32), 0), to_bytes(&r, 0)]
+                },
+                // Swapped, missing, and extra responses.
+                |_, res| res.into_iter().rev().collect(),
+                |_, res| res.into_iter().take(1).collect(),
+                |_, mut res| {
+                    res.push(vec![1]);
+                    res
+                },
+                // A wrong inverse, or an inverse claimed not to exist.
+                |request, res| match request[0] {
+                    sp1_zkvm::lib::bigmod::OP_INVERSE => vec![vec![1], vec![]],
+                    _ => res,
+                },
+                |request, res| match request[0] {
+                    sp1_zkvm::lib::bigmod::OP_INVERSE => vec![vec![], vec![1]],
+                    _ => res,
+                },
+            ];
+            // Rejected hints fall back to the division in the VM, which is slow on wide moduli.
+            let m = random_modulus(384);
+            let even = random_modulus(255) * 2u32;
+            for tamper in tampers {
+                let context = SP1Context::builder()
+                    .hook(FD_BIGMOD_HOOK, move |env, buf| {
+                        tamper(buf, hook_bigmod(env, buf))
+                    })
+                    .build();
+                let runtime = Runtime::with_context(
+                    Program::from(UINT256_MUL_ELF),
+                    SP1CoreOpts::default(),
+                    context,
+                );
+                for m in [&m, &even] {
+                    let arith = BigMod::with_hints(&m.to_bytes_be(), |request| {
+                        runtime.hook(FD_BIGMOD_HOOK, request)
+                    })
+                    .unwrap();
+                    check_operations(&arith, m, 16);
+                }
+            }
+        }
+    }
 }
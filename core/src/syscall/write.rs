@@ -1,5 +1,5 @@
 use crate::{
-    runtime::{Register, Syscall, SyscallContext},
+    runtime::{chunk_input, Register, Syscall, SyscallContext},
     utils::num_to_comma_separated,
 };
 
@@ -71,12 +71,17 @@ impl Syscall for SyscallWrite {
         } else if fd == 3 {
             rt.state.public_values_stream.extend_from_slice(slice);
         } else if fd == 4 {
-            rt.state.input_stream.push(slice.to_vec());
-        } else if let Some(mut hook) = rt.hook_registry.get(&fd) {
-            let res = hook.invoke_hook(rt.hook_env(), slice);
+            rt.state.input_stream.extend(chunk_input(slice.to_vec()));
+        } else if rt.hook_registry.table.contains_key(&fd) {
+            log::debug!(
+                "invoking hook at fd {}: {} byte request, starting with {}",
+                fd,
+                slice.len(),
+                hex::encode(&slice[..slice.len().min(32)])
+            );
+            let res = rt.hook_memoized(fd, slice);
             // Add result vectors to the beginning of the stream.
-            let ptr = rt.state.input_stream_ptr;
-            rt.state.input_stream.splice(ptr..ptr, res);
+            rt.insert_hook_responses(fd, res);
         } else {
             log::warn!("tried to write to unknown file descriptor {fd}");
         }
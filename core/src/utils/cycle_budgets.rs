@@ -0,0 +1,112 @@
+//! Cycle budgets for the guest-side crypto utilities.
+//!
+//! Each budget is the maximum number of cycles a test program may take when executed, before
+//! slack. Intentional changes to the cost of a utility should update the corresponding constant.
+//! Run `cargo test -p sp1-core --features expensive-tests print_cycle_counts -- --ignored
+//! --nocapture` to print the current numbers.
+
+use crate::runtime::{Program, Runtime};
+use crate::utils::{tests::*, SP1CoreOpts};
+
+/// The relative slack allowed over each budget, in percent.
+pub const CYCLE_BUDGET_SLACK_PERCENT: u64 = 10;
+
+/// Budget for `secp256k1::ecrecover`, which also covers `decompress_pubkey` and
+/// `verify_signature`.
+pub const ECRECOVER_CYCLE_BUDGET: u64 = 200_000;
+
+/// Budget for four calls to the secp256k1 decompress precompile.
+pub const SECP256K1_DECOMPRESS_CYCLE_BUDGET: u64 = 50_000;
+
+/// Budget for Keccak-256 of a 1 KiB input.
+pub const KECCAK256_CYCLE_BUDGET: u64 = 100_000;
+
+/// Budget for SHA-256 of a short input.
+pub const SHA2_CYCLE_BUDGET: u64 = 50_000;
+
+/// A test program, its inputs, and its cycle budget.
+struct BudgetCase {
+    name: &'static str,
+    elf: &'static [u8],
+    stdin: fn(&mut Runtime),
+    budget: u64,
+}
+
+fn cases() -> [BudgetCase; 4] {
+    [
+        BudgetCase {
+            name: "ecrecover",
+            elf: ECRECOVER_ELF,
+            stdin: |_| {},
+            budget: ECRECOVER_CYCLE_BUDGET,
+        },
+        BudgetCase {
+            name: "secp256k1-decompress",
+            elf: SECP256K1_DECOMPRESS_ELF,
+            stdin: |runtime| {
+                // The generator point, compressed.
+                let mut compressed = [0u8; 33];
+                compressed[0] = 2;
+                hex::decode_to_slice(
+                    "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+                    &mut compressed[1..],
+                )
+                .unwrap();
+                runtime.write_stdin_slice(&compressed);
+            },
+            budget: SECP256K1_DECOMPRESS_CYCLE_BUDGET,
+        },
+        BudgetCase {
+            name: "keccak256",
+            elf: KECCAK256_ELF,
+            stdin: |runtime| {
+                runtime.write_stdin(&1usize);
+                runtime.write_stdin(&vec![0xa5u8; 1024]);
+            },
+            budget: KECCAK256_CYCLE_BUDGET,
+        },
+        BudgetCase {
+            name: "sha2",
+            elf: SHA2_ELF,
+            stdin: |_| {},
+            budget: SHA2_CYCLE_BUDGET,
+        },
+    ]
+}
+
+/// Executes the test program of `case` and returns the number of cycles it took.
+fn measure_cycles(case: &BudgetCase) -> u64 {
+    let mut runtime = Runtime::new(Program::from(case.elf), SP1CoreOpts::default());
+    (case.stdin)(&mut runtime);
+    runtime.run_untraced().unwrap();
+    runtime.state.global_clk
+}
+
+#[test]
+fn test_cycle_budgets() {
+    for case in cases() {
+        let cycles = measure_cycles(&case);
+        let limit = case.budget + case.budget * CYCLE_BUDGET_SLACK_PERCENT / 100;
+        assert!(
+            cycles <= limit,
+            "{} took {} cycles, over its budget of {} (+{}%)",
+            case.name,
+            cycles,
+            case.budget,
+            CYCLE_BUDGET_SLACK_PERCENT
+        );
+    }
+}
+
+#[test]
+#[ignore]
+fn print_cycle_counts() {
+    for case in cases() {
+        println!(
+            "{}: {} cycles (budget {})",
+            case.name,
+            measure_cycles(&case),
+            case.budget
+        );
+    }
+}
@@ -1,5 +1,7 @@
 mod buffer;
 mod config;
+#[cfg(all(test, feature = "expensive-tests"))]
+mod cycle_budgets;
 pub mod ec;
 mod logger;
 mod options;
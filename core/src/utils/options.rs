@@ -6,6 +6,7 @@ const DEFAULT_SHARD_SIZE: usize = 1 << 22;
 const DEFAULT_SHARD_BATCH_SIZE: usize = 16;
 const DEFAULT_COMMIT_STREAM_CAPACITY: usize = 1;
 const DEFAULT_PROVE_STREAM_CAPACITY: usize = 1;
+const DEFAULT_HOOK_CACHE_CAPACITY: usize = 0;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct SP1ProverOpts {
@@ -30,6 +31,8 @@ pub struct SP1CoreOpts {
     pub prove_stream_capacity: usize,
     pub split_opts: SplitOpts,
     pub reconstruct_commitments: bool,
+    /// The number of hook responses memoized during an execution, or 0 not to memoize them.
+    pub hook_cache_capacity: usize,
 }
 
 impl Default for SP1CoreOpts {
@@ -56,6 +59,10 @@ impl Default for SP1CoreOpts {
             ),
             split_opts: SplitOpts::new(split_threshold),
             reconstruct_commitments: true,
+            hook_cache_capacity: env::var("HOOK_CACHE_CAPACITY").map_or_else(
+                |_| DEFAULT_HOOK_CACHE_CAPACITY,
+                |s| s.parse::<usize>().unwrap_or(DEFAULT_HOOK_CACHE_CAPACITY),
+            ),
         }
     }
 }
@@ -0,0 +1,15 @@
+//! Recovers the signer of a 65-byte `r || s || v` signature over a 32-byte message hash, and
+//! commits it ABI-encoded, so that a contract can `abi.decode` the public values of the proof.
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use sp1_zkvm::lib::abi::{commit_abi, RecoveredSigner};
+use sp1_zkvm::lib::secp256k1::ecrecover_address;
+
+pub fn main() {
+    let sig: [u8; 65] = sp1_zkvm::io::read_array();
+    let msg_hash: [u8; 32] = sp1_zkvm::io::read_array();
+
+    let signer = ecrecover_address(&sig, &msg_hash).expect("invalid signature");
+    commit_abi(&RecoveredSigner::new(signer, msg_hash));
+}
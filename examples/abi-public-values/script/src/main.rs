@@ -0,0 +1,49 @@
+//! Executes the program and decodes its public values as a contract would, with
+//! `abi.decode(publicValues, (RecoveredSigner))`.
+
+use alloy_sol_types::{sol, SolValue};
+use k256::ecdsa::SigningKey;
+use sp1_sdk::{utils, ProverClient, SP1Stdin};
+use tiny_keccak::{Hasher, Keccak};
+
+/// The ELF we want to execute inside the zkVM.
+const ELF: &[u8] = include_bytes!("../../program/elf/riscv32im-succinct-zkvm-elf");
+
+sol! {
+    /// The struct as declared by the contract that verifies the proof.
+    struct RecoveredSigner {
+        address signer;
+        bytes32 msgHash;
+    }
+}
+
+fn main() {
+    // Setup a tracer for logging.
+    utils::setup_logger();
+
+    let signing_key = SigningKey::from_slice(&[0x42; 32]).unwrap();
+    let msg_hash = [0xab; 32];
+    let (signature, recovery_id) = signing_key.sign_prehash_recoverable(&msg_hash).unwrap();
+    let mut sig = signature.to_bytes().to_vec();
+    sig.push(recovery_id.to_byte());
+
+    // The address is the last 20 bytes of the keccak256 hash of the uncompressed key.
+    let pubkey = signing_key.verifying_key().to_encoded_point(false);
+    let mut hash = [0u8; 32];
+    let mut keccak = Keccak::v256();
+    keccak.update(&pubkey.as_bytes()[1..]);
+    keccak.finalize(&mut hash);
+
+    let mut stdin = SP1Stdin::new();
+    stdin.write_vec(sig);
+    stdin.write_vec(msg_hash.to_vec());
+
+    let client = ProverClient::new();
+    let (public_values, _) = client.execute(ELF, stdin).run().expect("execution failed");
+
+    let decoded = RecoveredSigner::abi_decode(public_values.as_slice(), true)
+        .expect("public values are not an ABI-encoded RecoveredSigner");
+    assert_eq!(decoded.signer.as_slice(), &hash[12..]);
+    assert_eq!(decoded.msgHash.0, msg_hash);
+    println!("recovered signer {} of {}", decoded.signer, decoded.msgHash);
+}
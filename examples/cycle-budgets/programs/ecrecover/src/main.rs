@@ -0,0 +1,14 @@
+//! Recovers the public key of a 65-byte `r || s || v` signature over a 32-byte message hash, and
+//! commits the uncompressed public key.
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use sp1_zkvm::lib::secp256k1::ecrecover;
+
+pub fn main() {
+    let sig: [u8; 65] = sp1_zkvm::io::read_array();
+    let msg_hash: [u8; 32] = sp1_zkvm::io::read_array();
+
+    let pubkey = ecrecover(&sig, &msg_hash).expect("failed to recover the public key");
+    sp1_zkvm::io::commit_slice(&pubkey);
+}
@@ -0,0 +1,10 @@
+//! Hashes the input buffer with Keccak-256 and commits the digest.
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use sp1_zkvm::lib::keccak::keccak256;
+
+pub fn main() {
+    let data = sp1_zkvm::io::read_vec();
+    sp1_zkvm::io::commit_slice(&keccak256(&data));
+}
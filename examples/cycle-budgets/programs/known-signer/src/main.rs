@@ -0,0 +1,15 @@
+//! Checks that a 65-byte `r || s || v` signature over a 32-byte message hash was made by an
+//! uncompressed public key, without recovering the key, and commits the result.
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use sp1_zkvm::lib::secp256k1::verify_with_known_signer;
+
+pub fn main() {
+    let pubkey: [u8; 65] = sp1_zkvm::io::read_array();
+    let sig: [u8; 65] = sp1_zkvm::io::read_array();
+    let msg_hash: [u8; 32] = sp1_zkvm::io::read_array();
+
+    let verified = verify_with_known_signer(&pubkey, &sig, &msg_hash).expect("invalid input");
+    sp1_zkvm::io::commit(&verified);
+}
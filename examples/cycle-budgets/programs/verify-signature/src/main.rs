@@ -0,0 +1,17 @@
+//! Verifies a 64-byte `r || s` signature over a 32-byte message hash against an uncompressed
+//! public key, and commits the result.
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use k256::ecdsa::Signature;
+use sp1_zkvm::lib::secp256k1::verify_signature;
+
+pub fn main() {
+    let pubkey: [u8; 65] = sp1_zkvm::io::read_array();
+    let msg_hash: [u8; 32] = sp1_zkvm::io::read_array();
+    let sig: [u8; 64] = sp1_zkvm::io::read_array();
+
+    let signature = Signature::from_slice(&sig).expect("invalid signature");
+    let verified = verify_signature(&pubkey, &msg_hash, &signature, None);
+    sp1_zkvm::io::commit(&verified);
+}
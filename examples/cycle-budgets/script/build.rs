@@ -0,0 +1,8 @@
+fn main() {
+    for program in ["ecrecover", "verify-signature", "known-signer", "keccak256"] {
+        sp1_helper::build_program(&format!(
+            "{}/../programs/{program}",
+            env!("CARGO_MANIFEST_DIR")
+        ));
+    }
+}
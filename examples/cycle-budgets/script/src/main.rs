@@ -0,0 +1,103 @@
+//! Executes the example programs for the utilities in `sp1-lib` and prints their cycle counts.
+//!
+//! With `--check`, exits with an error if a program exceeds its budget in [`BUDGETS`].
+
+use k256::ecdsa::SigningKey;
+use sp1_sdk::{utils, ProverClient, SP1Stdin};
+
+const ECRECOVER_ELF: &[u8] =
+    include_bytes!("../../programs/ecrecover/elf/riscv32im-succinct-zkvm-elf");
+const VERIFY_SIGNATURE_ELF: &[u8] =
+    include_bytes!("../../programs/verify-signature/elf/riscv32im-succinct-zkvm-elf");
+const KNOWN_SIGNER_ELF: &[u8] =
+    include_bytes!("../../programs/known-signer/elf/riscv32im-succinct-zkvm-elf");
+const KECCAK256_ELF: &[u8] =
+    include_bytes!("../../programs/keccak256/elf/riscv32im-succinct-zkvm-elf");
+
+/// The maximum number of cycles each program may take, checked with `--check`.
+///
+/// These are ceilings rather than exact counts, so that unrelated changes to the executor or the
+/// toolchain don't break the check. Lower them when an optimization lands.
+const BUDGETS: &[(&str, u64)] = &[
+    ("ecrecover", 200_000),
+    ("verify_signature", 150_000),
+    ("known_signer", 150_000),
+    ("keccak256 (1 KiB)", 100_000),
+];
+
+fn main() {
+    utils::setup_logger();
+    let check = std::env::args().any(|arg| arg == "--check");
+
+    let signing_key = SigningKey::from_slice(&[0x42; 32]).unwrap();
+    let msg_hash = [0xab; 32];
+    let (signature, recovery_id) = signing_key.sign_prehash_recoverable(&msg_hash).unwrap();
+    let pubkey = signing_key.verifying_key().to_encoded_point(false);
+
+    let mut sig = signature.to_bytes().to_vec();
+    sig.push(recovery_id.to_byte());
+
+    let mut ecrecover_stdin = SP1Stdin::new();
+    ecrecover_stdin.write_vec(sig.clone());
+    ecrecover_stdin.write_vec(msg_hash.to_vec());
+
+    let mut verify_stdin = SP1Stdin::new();
+    verify_stdin.write_vec(pubkey.as_bytes().to_vec());
+    verify_stdin.write_vec(msg_hash.to_vec());
+    verify_stdin.write_vec(signature.to_bytes().to_vec());
+
+    let mut known_signer_stdin = SP1Stdin::new();
+    known_signer_stdin.write_vec(pubkey.as_bytes().to_vec());
+    known_signer_stdin.write_vec(sig);
+    known_signer_stdin.write_vec(msg_hash.to_vec());
+
+    let mut keccak_stdin = SP1Stdin::new();
+    keccak_stdin.write_vec((0..1024).map(|i| i as u8).collect());
+
+    let programs = [
+        ("ecrecover", ECRECOVER_ELF, ecrecover_stdin),
+        ("verify_signature", VERIFY_SIGNATURE_ELF, verify_stdin),
+        ("known_signer", KNOWN_SIGNER_ELF, known_signer_stdin),
+        ("keccak256 (1 KiB)", KECCAK256_ELF, keccak_stdin),
+    ];
+
+    let client = ProverClient::new();
+    let mut over_budget = Vec::new();
+    let mut cycle_counts = Vec::new();
+    println!("{:<20} {:>12} {:>12}", "program", "cycles", "budget");
+    for (name, elf, stdin) in programs {
+        let (_, report) = client.execute(elf, stdin).run().expect("execution failed");
+        let cycles = report.total_instruction_count();
+        let budget = BUDGETS
+            .iter()
+            .find(|(budget_name, _)| *budget_name == name)
+            .map(|(_, budget)| *budget)
+            .unwrap();
+
+        println!("{name:<20} {cycles:>12} {budget:>12}");
+        cycle_counts.push((name, cycles));
+        if cycles > budget {
+            over_budget.push(name);
+        }
+    }
+
+    // `verify_with_known_signer` replaces recovering the key and comparing it with the expected one.
+    let cycles_of = |name| {
+        cycle_counts
+            .iter()
+            .find(|(program, _)| *program == name)
+            .map(|(_, cycles)| *cycles)
+            .unwrap()
+    };
+    let (recover, known) = (cycles_of("ecrecover"), cycles_of("known_signer"));
+    println!(
+        "known_signer saves {} cycles ({}%) over ecrecover",
+        recover.saturating_sub(known),
+        recover.saturating_sub(known) * 100 / recover
+    );
+
+    if check && !over_budget.is_empty() {
+        eprintln!("over budget: {}", over_budget.join(", "));
+        std::process::exit(1);
+    }
+}
@@ -164,6 +164,7 @@ fn warn_if_not_default(opts: &SP1ProverOpts, context: &SP1Context) {
     let SP1Context {
         hook_registry,
         subproof_verifier,
+        hook_cache,
         ..
     } = context;
     if hook_registry.is_some() {
@@ -178,6 +179,9 @@ fn warn_if_not_default(opts: &SP1ProverOpts, context: &SP1Context) {
         tracing::warn!("non-default context.subproof_verifier will be ignored");
         tracing::warn!("custom subproof verifiers are currently unsupported by the network prover");
     }
+    if hook_cache.is_some() {
+        tracing::warn!("non-default context.hook_cache will be ignored");
+    }
 }
 
 impl From<SP1ProofKind> for ProofMode {
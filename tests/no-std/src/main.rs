@@ -0,0 +1,29 @@
+//! Links against `sp1-lib` without its `std` and `alloc` features, to check that the fixed-size
+//! APIs build for the zkVM target.
+#![no_std]
+#![no_main]
+
+use sp1_lib::io;
+use sp1_lib::keccak::keccak256;
+use sp1_lib::secp256k1::{decompress_pubkey, ecrecover, pubkey_to_address};
+
+sp1_zkvm::entrypoint!(main);
+
+/// The compressed encoding of the secp256k1 generator.
+const GENERATOR: [u8; 33] = [
+    2, 121, 190, 102, 126, 249, 220, 187, 172, 85, 160, 98, 149, 206, 135, 11, 7, 2, 155, 252, 219,
+    45, 206, 40, 217, 89, 242, 129, 91, 22, 248, 23, 152,
+];
+
+pub fn main() {
+    let msg_hash = keccak256(b"hello world");
+    io::commit_slice(&msg_hash);
+
+    let pubkey = decompress_pubkey(&GENERATOR).unwrap();
+    io::commit_slice(&pubkey);
+    io::commit_slice(&pubkey_to_address(&pubkey));
+
+    // A zero signature is out of range, so recovery must fail without touching the hook.
+    let recovered = ecrecover(&[0u8; 65], &msg_hash);
+    io::commit_slice(&[recovered.is_err() as u8]);
+}
@@ -0,0 +1,73 @@
+//! The verification of many signatures at once.
+
+use curve25519_dalek::edwards::CompressedEdwardsY;
+#[cfg(any(not(target_os = "zkvm"), test))]
+use curve25519_dalek::edwards::EdwardsPoint;
+
+#[cfg(any(not(target_os = "zkvm"), test))]
+use crate::verifying::challenge;
+use crate::verifying::check_scalar;
+use crate::{Signature, SignatureError, VerifyingKey};
+
+/// Verifies a batch of signatures, as `verify_batch` in ed25519-dalek: every `S` must be below the
+/// order of the base point, and the equations are checked with the cofactor, so non-canonical
+/// encodings and points of small order are accepted. An error is returned if the slices have
+/// different lengths.
+///
+/// In the zkVM, the batch is checked at once by [`sp1_lib::ed25519::verify_batch`], after each
+/// `R` is decompressed with curve25519-dalek, as the precompiles cannot reject an encoding of a
+/// point off the curve.
+pub fn verify_batch(
+    messages: &[&[u8]],
+    signatures: &[Signature],
+    verifying_keys: &[VerifyingKey],
+) -> Result<(), SignatureError> {
+    if messages.len() != signatures.len() || signatures.len() != verifying_keys.len() {
+        return Err(SignatureError::new());
+    }
+    for signature in signatures {
+        check_scalar(signature)?;
+        CompressedEdwardsY(*signature.r_bytes())
+            .decompress()
+            .ok_or_else(SignatureError::new)?;
+    }
+
+    #[cfg(any(target_os = "zkvm", test))]
+    let valid = {
+        extern crate alloc;
+        use alloc::vec::Vec;
+
+        let signatures: Vec<[u8; 64]> = signatures.iter().map(Signature::to_bytes).collect();
+        let public_keys: Vec<[u8; 32]> =
+            verifying_keys.iter().map(VerifyingKey::to_bytes).collect();
+        sp1_lib::ed25519::verify_batch(messages, &signatures, &public_keys)
+    };
+    #[cfg(not(any(target_os = "zkvm", test)))]
+    let valid = messages
+        .iter()
+        .zip(signatures)
+        .zip(verifying_keys)
+        .all(|((message, signature), key)| cofactored_equation_holds(key, signature, message));
+    if valid {
+        Ok(())
+    } else {
+        Err(SignatureError::new())
+    }
+}
+
+/// Returns whether `[8]([S]B - [k]A - R)` is the identity, for a signature whose `S` and `R` are
+/// known to be valid.
+#[cfg(any(not(target_os = "zkvm"), test))]
+pub(crate) fn cofactored_equation_holds(
+    key: &VerifyingKey,
+    signature: &Signature,
+    message: &[u8],
+) -> bool {
+    let s = check_scalar(signature).expect("a reduced S");
+    let r = CompressedEdwardsY(*signature.r_bytes())
+        .decompress()
+        .expect("an R on the curve");
+    let k = challenge(signature, key.as_bytes(), message);
+    let difference = EdwardsPoint::vartime_double_scalar_mul_basepoint(&k, &-key.point, &s) - r;
+    difference.is_small_order()
+}
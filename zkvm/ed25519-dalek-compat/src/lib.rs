@@ -0,0 +1,368 @@
+//! A subset of the [`ed25519-dalek`](https://docs.rs/ed25519-dalek/2.1) crate API, backed by the
+//! precompiles of `sp1-lib`.
+//!
+//! Code written against `ed25519-dalek` can use this crate instead with a `[patch]` entry:
+//!
+//! ```toml
+//! [patch.crates-io]
+//! ed25519-dalek = { git = "https://github.com/succinctlabs/sp1", package = "ed25519-dalek" }
+//! ```
+//!
+//! Only verification is provided: [`Verifier::verify`], [`VerifyingKey::verify_strict`] and,
+//! with the `batch` feature, [`verify_batch`]. They accept the same signatures as in
+//! ed25519-dalek. In the zkVM, the scalar multiplications run on the ed_add precompile through
+//! [`sp1_lib::ed25519`]; elsewhere, curve25519-dalek computes them. Signing keys, the prehashed
+//! and contextualized variants of Ed25519, and the serde and pkcs8 encodings are not provided.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "batch")]
+mod batch;
+mod verifying;
+
+use core::fmt;
+
+#[cfg(feature = "batch")]
+pub use batch::verify_batch;
+pub use signature::{self, Verifier};
+pub use verifying::VerifyingKey;
+
+/// The size of a public key, in bytes.
+pub const PUBLIC_KEY_LENGTH: usize = 32;
+
+/// The size of a signature, in bytes.
+pub const SIGNATURE_LENGTH: usize = 64;
+
+/// An error returned by the verification functions. No detail is given on why a signature or a
+/// key was rejected.
+pub type SignatureError = signature::Error;
+
+/// An Ed25519 signature: the encoding of the point `R` followed by the scalar `S`, as
+/// `ed25519::Signature`.
+///
+/// Neither component is checked when a signature is parsed, but only when it is verified.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Signature {
+    r: [u8; 32],
+    s: [u8; 32],
+}
+
+impl Signature {
+    /// The size of a signature, in bytes.
+    pub const BYTE_SIZE: usize = SIGNATURE_LENGTH;
+
+    /// Parses the 64-byte `R || S` encoding of a signature.
+    pub fn from_bytes(bytes: &[u8; SIGNATURE_LENGTH]) -> Self {
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+        r.copy_from_slice(&bytes[..32]);
+        s.copy_from_slice(&bytes[32..]);
+        Self { r, s }
+    }
+
+    /// Creates a signature from the encodings of `R` and `S`.
+    pub fn from_components(r: [u8; 32], s: [u8; 32]) -> Self {
+        Self { r, s }
+    }
+
+    /// Parses the `R || S` encoding of a signature, which must be 64 bytes long.
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, SignatureError> {
+        bytes
+            .try_into()
+            .map(Self::from_bytes)
+            .map_err(|_| SignatureError::new())
+    }
+
+    /// Returns the encoding of `R`.
+    pub fn r_bytes(&self) -> &[u8; 32] {
+        &self.r
+    }
+
+    /// Returns the encoding of `S`.
+    pub fn s_bytes(&self) -> &[u8; 32] {
+        &self.s
+    }
+
+    /// Returns the 64-byte `R || S` encoding of the signature.
+    pub fn to_bytes(&self) -> [u8; SIGNATURE_LENGTH] {
+        let mut bytes = [0u8; SIGNATURE_LENGTH];
+        bytes[..32].copy_from_slice(&self.r);
+        bytes[32..].copy_from_slice(&self.s);
+        bytes
+    }
+}
+
+impl fmt::Debug for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ed25519::Signature")
+            .field("R", &self.r)
+            .field("s", &self.s)
+            .finish()
+    }
+}
+
+impl From<[u8; SIGNATURE_LENGTH]> for Signature {
+    fn from(bytes: [u8; SIGNATURE_LENGTH]) -> Self {
+        Self::from_bytes(&bytes)
+    }
+}
+
+impl From<&[u8; SIGNATURE_LENGTH]> for Signature {
+    fn from(bytes: &[u8; SIGNATURE_LENGTH]) -> Self {
+        Self::from_bytes(bytes)
+    }
+}
+
+impl TryFrom<&[u8]> for Signature {
+    type Error = SignatureError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, SignatureError> {
+        Self::from_slice(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use curve25519_dalek::scalar::Scalar;
+    use num_bigint::BigUint;
+
+    /// The ed_add and ed_decompress precompiles, which sp1-lib only provides in the zkVM.
+    mod precompiles {
+        use num_bigint::BigUint;
+        use std::sync::OnceLock;
+
+        fn modulus() -> BigUint {
+            (BigUint::from(1u32) << 255u32) - 19u32
+        }
+
+        fn inv(x: &BigUint) -> BigUint {
+            x.modinv(&modulus()).expect("no inverse of zero")
+        }
+
+        /// The curve constant `d = -121665 / 121666`.
+        fn d() -> &'static BigUint {
+            static D: OnceLock<BigUint> = OnceLock::new();
+            D.get_or_init(|| {
+                let p = modulus();
+                (&p - 121665u32) * inv(&BigUint::from(121666u32)) % &p
+            })
+        }
+
+        fn to_le_bytes(x: &BigUint) -> [u8; 32] {
+            let mut bytes = [0u8; 32];
+            let le = x.to_bytes_le();
+            bytes[..le.len()].copy_from_slice(&le);
+            bytes
+        }
+
+        #[no_mangle]
+        extern "C" fn syscall_ed_add(p: *mut u32, q: *mut u32) {
+            let (p_words, q_words) = unsafe {
+                (
+                    core::slice::from_raw_parts_mut(p, 16),
+                    core::slice::from_raw_parts(q, 16),
+                )
+            };
+            let m = modulus();
+            let (x1, y1) = (
+                BigUint::from_slice(&p_words[..8]),
+                BigUint::from_slice(&p_words[8..]),
+            );
+            let (x2, y2) = (
+                BigUint::from_slice(&q_words[..8]),
+                BigUint::from_slice(&q_words[8..]),
+            );
+            let dxy = d() * &x1 * &x2 * &y1 * &y2 % &m;
+            let x3 = (&x1 * &y2 + &y1 * &x2) * inv(&(1u32 + &dxy)) % &m;
+            let y3 = (&y1 * &y2 + &x1 * &x2) * inv(&(&m + 1u32 - &dxy)) % &m;
+            for (words, value) in p_words.chunks_exact_mut(8).zip([x3, y3]) {
+                for (word, chunk) in words.iter_mut().zip(to_le_bytes(&value).chunks_exact(4)) {
+                    *word = u32::from_le_bytes(chunk.try_into().unwrap());
+                }
+            }
+        }
+
+        /// Panics if the encoding is not of a point on the curve, as the precompile does.
+        #[no_mangle]
+        extern "C" fn syscall_ed_decompress(point: &mut [u8; 64]) {
+            let m = modulus();
+            let sign = point[63] >> 7;
+            point[63] &= 0x7f;
+            let y = BigUint::from_bytes_le(&point[32..]);
+            assert!(y < m, "non-canonical y-coordinate");
+            let y2 = &y * &y % &m;
+            let x2 = (&y2 + &m - 1u32) * inv(&(d() * &y2 + 1u32)) % &m;
+            let mut x = x2.modpow(&((&m + 3u32) >> 3u32), &m);
+            if &x * &x % &m != x2 {
+                x = x * BigUint::from(2u32).modpow(&((&m - 1u32) >> 2u32), &m) % &m;
+            }
+            assert!(&x * &x % &m == x2, "not a point on the curve");
+            if x.bit(0) != (sign == 1) {
+                x = (&m - x) % &m;
+            }
+            point[..32].copy_from_slice(&to_le_bytes(&x));
+        }
+    }
+
+    fn bytes<const N: usize>(hex_str: &str) -> [u8; N] {
+        hex::decode(hex_str).unwrap().try_into().unwrap()
+    }
+
+    /// The public key, message and signature of the tests 1 to 3 of RFC 8032, section 7.1.
+    fn rfc8032_vectors() -> Vec<(VerifyingKey, Vec<u8>, Signature)> {
+        [
+            (
+                "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+                "",
+                "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e06522490155\
+                 5fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+            ),
+            (
+                "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+                "72",
+                "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da\
+                 085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+            ),
+            (
+                "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+                "af82",
+                "6291d657deec24024827e69c3abe01a30ce548a284743a445e3680d7db5ac3ac\
+                 18ff9b538d16f290ae67f760984dc6594a7c15e9716ed28dc027beceea1ec40a",
+            ),
+        ]
+        .into_iter()
+        .map(|(public_key, message, signature)| {
+            (
+                VerifyingKey::from_bytes(&bytes(public_key)).unwrap(),
+                hex::decode(message).unwrap(),
+                Signature::from_bytes(&bytes(&signature.replace(' ', ""))),
+            )
+        })
+        .collect()
+    }
+
+    /// Returns whether curve25519-dalek accepts a signature without the cofactor, as the
+    /// `verify` of ed25519-dalek does outside of the zkVM.
+    fn software_verify(key: &VerifyingKey, message: &[u8], signature: &Signature) -> bool {
+        verifying::check_scalar(signature)
+            .is_ok_and(|s| key.recompute_r(&s, signature, message) == *signature.r_bytes())
+    }
+
+    #[test]
+    fn test_rfc8032_vectors() {
+        for (key, message, signature) in rfc8032_vectors() {
+            assert!(software_verify(&key, &message, &signature));
+            assert!(key.verify(&message, &signature).is_ok());
+            assert!(key.verify_strict(&message, &signature).is_ok());
+            assert!(!key.is_weak());
+
+            let mut other_message = message.clone();
+            other_message.push(0);
+            assert!(key.verify(&other_message, &signature).is_err());
+            assert!(key.verify_strict(&other_message, &signature).is_err());
+
+            // S + L satisfies the equation, but is not reduced.
+            let l = BigUint::from_bytes_le((-Scalar::ONE).as_bytes()) + 1u32;
+            let mut s = [0u8; 32];
+            let unreduced = BigUint::from_bytes_le(signature.s_bytes()) + l;
+            s.copy_from_slice(&unreduced.to_bytes_le());
+            let unreduced = Signature::from_components(*signature.r_bytes(), s);
+            assert!(key.verify(&message, &unreduced).is_err());
+            assert!(key.verify_strict(&message, &unreduced).is_err());
+        }
+    }
+
+    /// The encodings of the points of small order, as in the test vectors of ed25519-zebra: the
+    /// eight canonical ones, then six whose y-coordinate is not reduced or which set the sign of
+    /// x = 0.
+    const SMALL_ORDER_ENCODINGS: [&str; 14] = [
+        "0100000000000000000000000000000000000000000000000000000000000000",
+        "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "0000000000000000000000000000000000000000000000000000000000000080",
+        "26e8958fc2b227b045c3f489f2ef98f0d5dfac05d3c63339b13802886d53fc05",
+        "26e8958fc2b227b045c3f489f2ef98f0d5dfac05d3c63339b13802886d53fc85",
+        "c7176a703d4dd84fba3c0b760d10670f2a2053fa2c39ccc64ec7fd7792ac037a",
+        "c7176a703d4dd84fba3c0b760d10670f2a2053fa2c39ccc64ec7fd7792ac03fa",
+        "0100000000000000000000000000000000000000000000000000000000000080",
+        "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        "edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        "edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        "eeffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        "eeffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+    ];
+
+    /// Every pair of small order `A` and `R` with `S = 0`: the precompiles, or curve25519-dalek
+    /// for the keys they cannot decompress, accept the same signatures as curve25519-dalek alone.
+    #[test]
+    fn test_small_order_vectors() {
+        let message = b"Zcash";
+        let mut accepted = 0;
+        for public_key in SMALL_ORDER_ENCODINGS {
+            let key = VerifyingKey::from_bytes(&bytes(public_key)).unwrap();
+            assert!(key.is_weak());
+            for r in SMALL_ORDER_ENCODINGS {
+                let signature = Signature::from_components(bytes(r), [0; 32]);
+                let expected = software_verify(&key, message, &signature);
+                assert_eq!(
+                    key.verify(message, &signature).is_ok(),
+                    expected,
+                    "A = {public_key}, R = {r}"
+                );
+                assert!(key.verify_strict(message, &signature).is_err());
+                accepted += expected as usize;
+            }
+        }
+        assert!(0 < accepted && accepted < 14 * 14);
+    }
+
+    /// Encodings of points off the curve are rejected instead of reaching the precompiles.
+    #[test]
+    fn test_invalid_points() {
+        let off_curve = (2u8..)
+            .map(|y| {
+                let mut encoded = [0u8; 32];
+                encoded[0] = y;
+                encoded
+            })
+            .find(|encoded| VerifyingKey::from_bytes(encoded).is_err())
+            .unwrap();
+        assert!(VerifyingKey::try_from(&off_curve[..31]).is_err());
+
+        let (key, message, signature) = rfc8032_vectors().remove(0);
+        let signature = Signature::from_components(off_curve, *signature.s_bytes());
+        assert!(key.verify(&message, &signature).is_err());
+        assert!(key.verify_strict(&message, &signature).is_err());
+        #[cfg(feature = "batch")]
+        assert!(verify_batch(&[&message], &[signature], &[key]).is_err());
+    }
+
+    #[cfg(feature = "batch")]
+    #[test]
+    fn test_verify_batch() {
+        let vectors = rfc8032_vectors();
+        let mut messages: Vec<&[u8]> = vectors.iter().map(|(_, m, _)| m.as_slice()).collect();
+        let mut signatures: Vec<Signature> = vectors.iter().map(|(_, _, s)| *s).collect();
+        let mut keys: Vec<VerifyingKey> = vectors.iter().map(|(k, _, _)| *k).collect();
+        assert!(verify_batch(&messages, &signatures, &keys).is_ok());
+        assert!(verify_batch(&messages[1..], &signatures, &keys).is_err());
+
+        // A non-canonical `A = 0` of small order and `R = 0` with `S = 0`, which only the
+        // cofactored equation accepts.
+        let key = VerifyingKey::from_bytes(&bytes(SMALL_ORDER_ENCODINGS[10])).unwrap();
+        let signature = Signature::from_components(bytes(SMALL_ORDER_ENCODINGS[2]), [0; 32]);
+        assert!(batch::cofactored_equation_holds(&key, &signature, b"Zcash"));
+        assert!(key.verify(b"Zcash", &signature).is_err());
+        messages.push(b"Zcash");
+        signatures.push(signature);
+        keys.push(key);
+        assert!(verify_batch(&messages, &signatures, &keys).is_ok());
+
+        let mut swapped = keys.clone();
+        swapped.swap(0, 3);
+        assert!(verify_batch(&messages, &signatures, &swapped).is_err());
+        signatures[1] = vectors[2].2;
+        assert!(verify_batch(&messages, &signatures, &keys).is_err());
+    }
+}
@@ -0,0 +1,173 @@
+//! Ed25519 public keys and the verification of single signatures.
+
+use core::fmt;
+use core::hash::{Hash, Hasher};
+
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::Scalar;
+use sha2::{Digest, Sha512};
+
+use crate::{Signature, SignatureError, Verifier, PUBLIC_KEY_LENGTH};
+
+/// An Ed25519 public key.
+///
+/// The key is decompressed with curve25519-dalek when it is parsed, so that an encoding of a
+/// point off the curve is rejected instead of making the decompression precompile fail.
+#[derive(Copy, Clone)]
+pub struct VerifyingKey {
+    compressed: CompressedEdwardsY,
+    pub(crate) point: EdwardsPoint,
+}
+
+impl VerifyingKey {
+    /// Parses a 32-byte public key, which must be the encoding of a point on the curve.
+    ///
+    /// As in ed25519-dalek, encodings whose y-coordinate is not reduced are accepted, and so are
+    /// points of small order, which [`VerifyingKey::is_weak`] detects.
+    pub fn from_bytes(bytes: &[u8; PUBLIC_KEY_LENGTH]) -> Result<Self, SignatureError> {
+        let compressed = CompressedEdwardsY(*bytes);
+        let point = compressed.decompress().ok_or_else(SignatureError::new)?;
+        Ok(Self { compressed, point })
+    }
+
+    /// Returns the encoding of the key, as it was parsed.
+    pub fn as_bytes(&self) -> &[u8; PUBLIC_KEY_LENGTH] {
+        self.compressed.as_bytes()
+    }
+
+    /// Returns the encoding of the key, as it was parsed.
+    pub fn to_bytes(&self) -> [u8; PUBLIC_KEY_LENGTH] {
+        self.compressed.to_bytes()
+    }
+
+    /// Returns `true` if the key is a point of small order, for which signatures of any message
+    /// can be forged.
+    pub fn is_weak(&self) -> bool {
+        self.point.is_small_order()
+    }
+
+    /// Verifies a signature of `message` with the stricter rules of ed25519-dalek: `R` must be a
+    /// canonical encoding, and neither `R` nor the key may be of small order.
+    ///
+    /// `R` is decompressed with curve25519-dalek before the precompiles are used, as they cannot
+    /// reject an encoding of a point off the curve.
+    pub fn verify_strict(
+        &self,
+        message: &[u8],
+        signature: &Signature,
+    ) -> Result<(), SignatureError> {
+        let s = check_scalar(signature)?;
+        let r = CompressedEdwardsY(*signature.r_bytes())
+            .decompress()
+            .ok_or_else(SignatureError::new)?;
+        if r.is_small_order() || self.is_weak() {
+            return Err(SignatureError::new());
+        }
+
+        #[cfg(any(target_os = "zkvm", test))]
+        if sp1_lib::ed25519::is_canonical(self.as_bytes()) {
+            return to_result(sp1_lib::ed25519::verify_with_mode(
+                self.as_bytes(),
+                message,
+                &signature.to_bytes(),
+                sp1_lib::ed25519::VerificationMode::Strict,
+            ));
+        }
+        to_result(self.recompute_r(&s, signature, message) == *signature.r_bytes())
+    }
+
+    /// Returns the encoding of `[S]B - [k]A`, which is `R` for a valid signature.
+    pub(crate) fn recompute_r(
+        &self,
+        s: &Scalar,
+        signature: &Signature,
+        message: &[u8],
+    ) -> [u8; 32] {
+        let k = challenge(signature, self.as_bytes(), message);
+        EdwardsPoint::vartime_double_scalar_mul_basepoint(&k, &-self.point, s)
+            .compress()
+            .to_bytes()
+    }
+}
+
+impl Verifier<Signature> for VerifyingKey {
+    /// Verifies a signature of `message` as in ed25519-dalek: the equation is checked without the
+    /// cofactor, and `R` is compared in its encoded form, so a non-canonical `R` is rejected.
+    /// Keys and `R` of small order are accepted.
+    fn verify(&self, message: &[u8], signature: &Signature) -> Result<(), SignatureError> {
+        let s = check_scalar(signature)?;
+
+        // The precompile only decompresses canonical encodings, and the rare keys which are not
+        // are left to curve25519-dalek.
+        #[cfg(any(target_os = "zkvm", test))]
+        if sp1_lib::ed25519::is_canonical(self.as_bytes()) {
+            return to_result(sp1_lib::ed25519::verify(
+                self.as_bytes(),
+                message,
+                &signature.to_bytes(),
+            ));
+        }
+        to_result(self.recompute_r(&s, signature, message) == *signature.r_bytes())
+    }
+}
+
+impl fmt::Debug for VerifyingKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("VerifyingKey")
+            .field(&self.compressed)
+            .finish()
+    }
+}
+
+// As in ed25519-dalek, keys are compared by their encodings.
+impl PartialEq for VerifyingKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl Eq for VerifyingKey {}
+
+impl Hash for VerifyingKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state);
+    }
+}
+
+impl AsRef<[u8]> for VerifyingKey {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl TryFrom<&[u8]> for VerifyingKey {
+    type Error = SignatureError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, SignatureError> {
+        let bytes: &[u8; PUBLIC_KEY_LENGTH] =
+            bytes.try_into().map_err(|_| SignatureError::new())?;
+        Self::from_bytes(bytes)
+    }
+}
+
+/// Returns `S`, which must be below the order of the base point.
+pub(crate) fn check_scalar(signature: &Signature) -> Result<Scalar, SignatureError> {
+    Option::from(Scalar::from_canonical_bytes(*signature.s_bytes())).ok_or_else(SignatureError::new)
+}
+
+/// Returns `k = SHA-512(R || A || message)` reduced modulo the order of the base point.
+pub(crate) fn challenge(signature: &Signature, public_key: &[u8; 32], message: &[u8]) -> Scalar {
+    let mut hasher = Sha512::new();
+    hasher.update(signature.r_bytes());
+    hasher.update(public_key);
+    hasher.update(message);
+    Scalar::from_bytes_mod_order_wide(&hasher.finalize().into())
+}
+
+fn to_result(valid: bool) -> Result<(), SignatureError> {
+    if valid {
+        Ok(())
+    } else {
+        Err(SignatureError::new())
+    }
+}
@@ -0,0 +1,149 @@
+//! Solidity ABI encoding of public values, for proofs verified on-chain.
+//!
+//! A value committed with [`commit_abi`] is encoded as by `abi.encode(value)`, so that a contract
+//! recovers it from the public values of a verified proof with `abi.decode(publicValues, (T))`.
+//! The structs of this module are the outputs of the utilities of this crate, and are declared in
+//! Solidity as:
+//!
+//! ```solidity
+//! struct RecoveredSigner { address signer; bytes32 msgHash; }
+//! struct SignerSetRoot { bytes32 root; uint64 count; }
+//! struct VerificationResult { bool ok; bytes32 pubkeyHash; }
+//! ```
+//!
+//! The address of a [`RecoveredSigner`] is named `signer`, as `address` is a type name in
+//! Solidity.
+
+use crate::io::commit_slice;
+use crate::keccak::keccak256;
+use alloc::vec::Vec;
+use alloy_primitives::{Address, B256};
+use alloy_sol_types::{sol, SolValue};
+
+sol! {
+    /// The signer of a message hash, as recovered by `ecrecover`.
+    #[derive(Debug, PartialEq, Eq)]
+    struct RecoveredSigner {
+        address signer;
+        bytes32 msgHash;
+    }
+
+    /// The Merkle root of a set of signers, as computed by `signers_merkle_root`, and their number.
+    #[derive(Debug, PartialEq, Eq)]
+    struct SignerSetRoot {
+        bytes32 root;
+        uint64 count;
+    }
+
+    /// Whether a signature verified, and the keccak256 hash of the public key it was checked with.
+    #[derive(Debug, PartialEq, Eq)]
+    struct VerificationResult {
+        bool ok;
+        bytes32 pubkeyHash;
+    }
+}
+
+impl RecoveredSigner {
+    pub fn new(signer: [u8; 20], msg_hash: [u8; 32]) -> Self {
+        Self {
+            signer: Address::from(signer),
+            msgHash: B256::from(msg_hash),
+        }
+    }
+}
+
+impl SignerSetRoot {
+    pub fn new(root: [u8; 32], count: u64) -> Self {
+        Self {
+            root: B256::from(root),
+            count,
+        }
+    }
+
+    /// Returns the root of the signers, in the order of their messages, like
+    /// [`signers_merkle_root`](crate::secp256k1::signers_merkle_root).
+    #[cfg(feature = "secp256k1")]
+    pub fn from_signers(signers: &[[u8; 20]]) -> Self {
+        Self::new(
+            crate::secp256k1::signers_merkle_root(signers),
+            signers.len() as u64,
+        )
+    }
+}
+
+impl VerificationResult {
+    /// Returns the result of a verification with `pubkey`, in whichever encoding it was given.
+    pub fn new(ok: bool, pubkey: &[u8]) -> Self {
+        Self {
+            ok,
+            pubkeyHash: B256::from(keccak256(pubkey)),
+        }
+    }
+}
+
+/// Returns the encoding of `value` by `abi.encode(value)`.
+pub fn abi_encode<T: SolValue>(value: &T) -> Vec<u8> {
+    value.abi_encode()
+}
+
+/// Commits `value` to the public values stream, encoded as by `abi.encode(value)`.
+///
+/// ### Examples
+/// ```ignore
+/// use sp1_zkvm::lib::abi::{commit_abi, RecoveredSigner};
+///
+/// let address = sp1_zkvm::lib::secp256k1::ecrecover_address(&sig, &msg_hash)?;
+/// commit_abi(&RecoveredSigner::new(address, msg_hash));
+/// ```
+pub fn commit_abi<T: SolValue>(value: &T) {
+    commit_slice(&abi_encode(value));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_abi_public_values() {
+        // The structs as declared by a contract decoding the public values.
+        sol! {
+            struct ContractRecoveredSigner { address signer; bytes32 msgHash; }
+            struct ContractSignerSetRoot { bytes32 root; uint64 count; }
+            struct ContractVerificationResult { bool ok; bytes32 pubkeyHash; }
+        }
+
+        let signer = [0x11; 20];
+        let msg_hash = [0x22; 32];
+        let encoded = abi_encode(&RecoveredSigner::new(signer, msg_hash));
+        assert_eq!(&encoded[..12], &[0; 12]);
+        assert_eq!(&encoded[12..32], &signer);
+        assert_eq!(&encoded[32..], &msg_hash);
+        let decoded = ContractRecoveredSigner::abi_decode(&encoded, true).unwrap();
+        assert_eq!(decoded.signer.0 .0, signer);
+        assert_eq!(decoded.msgHash.0, msg_hash);
+        assert_eq!(
+            RecoveredSigner::abi_decode(&encoded, true).unwrap(),
+            RecoveredSigner::new(signer, msg_hash)
+        );
+
+        #[cfg(feature = "secp256k1")]
+        {
+            let signers = [[1; 20], [2; 20], [3; 20]];
+            let root = SignerSetRoot::from_signers(&signers);
+            let decoded = ContractSignerSetRoot::abi_decode(&abi_encode(&root), true).unwrap();
+            assert_eq!(
+                decoded.root.0,
+                crate::secp256k1::signers_merkle_root(&signers)
+            );
+            assert_eq!(decoded.count, 3);
+        }
+
+        let pubkey = [0x04; 65];
+        let result = VerificationResult::new(true, &pubkey);
+        let encoded = abi_encode(&result);
+        assert_eq!(encoded.len(), 64);
+        let decoded = ContractVerificationResult::abi_decode(&encoded, true).unwrap();
+        assert!(decoded.ok);
+        assert_eq!(decoded.pubkeyHash.0, keccak256(&pubkey));
+    }
+}
@@ -0,0 +1,359 @@
+//! ChaCha20-Poly1305 authenticated encryption, as defined in RFC 8439.
+//!
+//! Decrypting an input inside the zkVM binds the proof to the ciphertext, the nonce and the
+//! associated data, so that a prover can be given private data encrypted. There is no ChaCha20
+//! precompile, so the block function runs in software. [`chacha20_block`] is the only function
+//! that a precompile would replace.
+
+use crate::error::Error;
+use alloc::vec::Vec;
+use core::convert::TryInto;
+
+/// The length of a Poly1305 tag.
+pub const TAG_LEN: usize = 16;
+
+/// The words "expand 32-byte k" that the ChaCha20 state starts with.
+const CONSTANTS: [u32; 4] = [0x61707865, 0x3320646e, 0x79622d32, 0x6b206574];
+
+/// Decrypts and authenticates `ciphertext_and_tag`, the ciphertext followed by its tag, with the
+/// associated data `aad`.
+///
+/// Returns [`Error::DecryptionFailed`] if the input is shorter than a tag or the tag does not
+/// match. The tag is checked before anything is decrypted, so no plaintext is produced for a
+/// forged input.
+pub fn chacha20poly1305_open(
+    key: &[u8; 32],
+    nonce: &[u8; 12],
+    aad: &[u8],
+    ciphertext_and_tag: &[u8],
+) -> Result<Vec<u8>, Error> {
+    if ciphertext_and_tag.len() < TAG_LEN {
+        return Err(Error::DecryptionFailed);
+    }
+    let (ciphertext, tag) = ciphertext_and_tag.split_at(ciphertext_and_tag.len() - TAG_LEN);
+
+    let key = key_words(key);
+    let nonce = nonce_words(nonce);
+    let expected = compute_tag(&key, &nonce, aad, ciphertext);
+    // The tags are compared in constant time.
+    let difference = expected
+        .iter()
+        .zip(tag)
+        .fold(0u8, |acc, (a, b)| acc | (a ^ b));
+    if difference != 0 {
+        return Err(Error::DecryptionFailed);
+    }
+
+    let mut plaintext = ciphertext.to_vec();
+    apply_keystream(&key, &nonce, &mut plaintext);
+    Ok(plaintext)
+}
+
+/// Encrypts `plaintext` with the associated data `aad`, returning the ciphertext followed by its
+/// tag.
+///
+/// Each nonce must be used only once with a given key.
+pub fn chacha20poly1305_seal(
+    key: &[u8; 32],
+    nonce: &[u8; 12],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Vec<u8> {
+    let key = key_words(key);
+    let nonce = nonce_words(nonce);
+    let mut output = Vec::with_capacity(plaintext.len() + TAG_LEN);
+    output.extend_from_slice(plaintext);
+    apply_keystream(&key, &nonce, &mut output);
+    let tag = compute_tag(&key, &nonce, aad, &output);
+    output.extend_from_slice(&tag);
+    output
+}
+
+/// Computes the ChaCha20 block of a key, a block counter and a nonce: 20 rounds of quarter rounds
+/// over the state, added to the initial state.
+pub fn chacha20_block(key: &[u32; 8], counter: u32, nonce: &[u32; 3]) -> [u8; 64] {
+    let mut initial = [0u32; 16];
+    initial[..4].copy_from_slice(&CONSTANTS);
+    initial[4..12].copy_from_slice(key);
+    initial[12] = counter;
+    initial[13..].copy_from_slice(nonce);
+
+    let mut state = initial;
+    for _ in 0..10 {
+        // The column rounds, then the diagonal rounds.
+        quarter_round(&mut state, 0, 4, 8, 12);
+        quarter_round(&mut state, 1, 5, 9, 13);
+        quarter_round(&mut state, 2, 6, 10, 14);
+        quarter_round(&mut state, 3, 7, 11, 15);
+        quarter_round(&mut state, 0, 5, 10, 15);
+        quarter_round(&mut state, 1, 6, 11, 12);
+        quarter_round(&mut state, 2, 7, 8, 13);
+        quarter_round(&mut state, 3, 4, 9, 14);
+    }
+
+    let mut block = [0u8; 64];
+    for (i, chunk) in block.chunks_exact_mut(4).enumerate() {
+        chunk.copy_from_slice(&state[i].wrapping_add(initial[i]).to_le_bytes());
+    }
+    block
+}
+
+fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
+
+/// XORs `data` with the keystream, whose blocks are counted from 1, as block 0 derives the
+/// Poly1305 key.
+///
+/// The 32-bit counter limits messages to 256 GiB, more than the memory of the zkVM.
+fn apply_keystream(key: &[u32; 8], nonce: &[u32; 3], data: &mut [u8]) {
+    for (counter, chunk) in (1..).zip(data.chunks_mut(64)) {
+        let block = chacha20_block(key, counter, nonce);
+        for (byte, key_byte) in chunk.iter_mut().zip(block) {
+            *byte ^= key_byte;
+        }
+    }
+}
+
+/// Computes the tag of the ciphertext: the Poly1305 MAC, with the one-time key of block 0, of the
+/// associated data and the ciphertext, each padded with zeros to 16 bytes, followed by their
+/// lengths as little endian `u64`s.
+fn compute_tag(key: &[u32; 8], nonce: &[u32; 3], aad: &[u8], ciphertext: &[u8]) -> [u8; 16] {
+    let block = chacha20_block(key, 0, nonce);
+    let mut poly = Poly1305::new(block[..32].try_into().unwrap());
+    poly.update_padded(aad);
+    poly.update_padded(ciphertext);
+    let mut lengths = [0u8; 16];
+    lengths[..8].copy_from_slice(&(aad.len() as u64).to_le_bytes());
+    lengths[8..].copy_from_slice(&(ciphertext.len() as u64).to_le_bytes());
+    poly.update_padded(&lengths);
+    poly.finalize()
+}
+
+fn key_words(key: &[u8; 32]) -> [u32; 8] {
+    let mut words = [0u32; 8];
+    for (word, chunk) in words.iter_mut().zip(key.chunks_exact(4)) {
+        *word = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+    words
+}
+
+fn nonce_words(nonce: &[u8; 12]) -> [u32; 3] {
+    let mut words = [0u32; 3];
+    for (word, chunk) in words.iter_mut().zip(nonce.chunks_exact(4)) {
+        *word = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+    words
+}
+
+/// The Poly1305 MAC, evaluated modulo `2^130 - 5` on 26-bit limbs.
+struct Poly1305 {
+    /// The clamped multiplier r.
+    r: [u32; 5],
+    /// The accumulator h, partially reduced.
+    h: [u32; 5],
+    /// The second half of the key, added to the accumulator at the end.
+    s: [u32; 4],
+}
+
+impl Poly1305 {
+    fn new(key: &[u8; 32]) -> Self {
+        let word = |i: usize| u32::from_le_bytes(key[i..i + 4].try_into().unwrap());
+        // r is clamped as the RFC requires, and split into limbs.
+        let r = [
+            word(0) & 0x3ffffff,
+            (word(3) >> 2) & 0x3ffff03,
+            (word(6) >> 4) & 0x3ffc0ff,
+            (word(9) >> 6) & 0x3f03fff,
+            (word(12) >> 8) & 0x00fffff,
+        ];
+        Self {
+            r,
+            h: [0; 5],
+            s: [word(16), word(20), word(24), word(28)],
+        }
+    }
+
+    /// Absorbs `data`, padded with zeros to a whole number of 16-byte blocks.
+    fn update_padded(&mut self, data: &[u8]) {
+        for chunk in data.chunks(16) {
+            let mut block = [0u8; 16];
+            block[..chunk.len()].copy_from_slice(chunk);
+            self.block(&block);
+        }
+    }
+
+    /// Absorbs a full block, with its high bit 2^128 set: `h = (h + block) * r`.
+    fn block(&mut self, block: &[u8; 16]) {
+        let word = |i: usize| u32::from_le_bytes(block[i..i + 4].try_into().unwrap());
+        let h = &mut self.h;
+        h[0] += word(0) & 0x3ffffff;
+        h[1] += (word(3) >> 2) & 0x3ffffff;
+        h[2] += (word(6) >> 4) & 0x3ffffff;
+        h[3] += (word(9) >> 6) & 0x3ffffff;
+        h[4] += (word(12) >> 8) | (1 << 24);
+
+        // 2^130 = 5 modulo the prime, so the limbs above 2^130 wrap around multiplied by 5.
+        let [r0, r1, r2, r3, r4] = self.r.map(u64::from);
+        let [s1, s2, s3, s4] = [r1 * 5, r2 * 5, r3 * 5, r4 * 5];
+        let [h0, h1, h2, h3, h4] = (*h).map(u64::from);
+        let mut d = [
+            h0 * r0 + h1 * s4 + h2 * s3 + h3 * s2 + h4 * s1,
+            h0 * r1 + h1 * r0 + h2 * s4 + h3 * s3 + h4 * s2,
+            h0 * r2 + h1 * r1 + h2 * r0 + h3 * s4 + h4 * s3,
+            h0 * r3 + h1 * r2 + h2 * r1 + h3 * r0 + h4 * s4,
+            h0 * r4 + h1 * r3 + h2 * r2 + h3 * r1 + h4 * r0,
+        ];
+
+        // Carry the products back into 26-bit limbs.
+        for i in 0..4 {
+            d[i + 1] += d[i] >> 26;
+            d[i] &= 0x3ffffff;
+        }
+        let carry = d[4] >> 26;
+        d[4] &= 0x3ffffff;
+        d[0] += carry * 5;
+        d[1] += d[0] >> 26;
+        d[0] &= 0x3ffffff;
+        *h = d.map(|limb| limb as u32);
+    }
+
+    /// Returns the tag `(h mod (2^130 - 5)) + s mod 2^128`.
+    fn finalize(self) -> [u8; 16] {
+        // Carry h fully, so that h[0] to h[3] have 26 bits and h < 2^130 + 2^26.
+        let mut h = self.h;
+        for i in 0..4 {
+            h[i + 1] += h[i] >> 26;
+            h[i] &= 0x3ffffff;
+        }
+        h[0] += (h[4] >> 26) * 5;
+        h[4] &= 0x3ffffff;
+        for i in 0..4 {
+            h[i + 1] += h[i] >> 26;
+            h[i] &= 0x3ffffff;
+        }
+
+        // g = h + 5 - 2^130 replaces h if it is not negative, that is if h >= 2^130 - 5. The
+        // selection is done with a mask, in constant time.
+        let mut g = h;
+        let mut carry = 5;
+        for limb in g.iter_mut() {
+            *limb += carry;
+            carry = *limb >> 26;
+            *limb &= 0x3ffffff;
+        }
+        g[4] = g[4].wrapping_add(carry << 26).wrapping_sub(1 << 26);
+        let mask = (g[4] >> 31).wrapping_sub(1);
+        for (h_limb, g_limb) in h.iter_mut().zip(g) {
+            *h_limb = (*h_limb & !mask) | (g_limb & mask);
+        }
+
+        // Pack h into 32-bit words, and add s.
+        let words = [
+            h[0] | (h[1] << 26),
+            (h[1] >> 6) | (h[2] << 20),
+            (h[2] >> 12) | (h[3] << 14),
+            (h[3] >> 18) | (h[4] << 8),
+        ];
+        let mut tag = [0u8; 16];
+        let mut carry = 0u64;
+        for ((chunk, word), s) in tag.chunks_exact_mut(4).zip(words).zip(self.s) {
+            let sum = word as u64 + s as u64 + carry;
+            chunk.copy_from_slice(&(sum as u32).to_le_bytes());
+            carry = sum >> 32;
+        }
+        tag
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+    use chacha20poly1305::ChaCha20Poly1305;
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn test_chacha20poly1305() {
+        // The AEAD vector of RFC 8439, section 2.8.2.
+        let key: [u8; 32] = core::array::from_fn(|i| 0x80 + i as u8);
+        let nonce: [u8; 12] = hex::decode("070000004041424344454647")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let aad = hex::decode("50515253c0c1c2c3c4c5c6c7").unwrap();
+        let plaintext = b"Ladies and Gentlemen of the class of '99: If I could offer you only one \
+            tip for the future, sunscreen would be it.";
+        let sealed = chacha20poly1305_seal(&key, &nonce, &aad, plaintext);
+        assert_eq!(
+            hex::encode(&sealed),
+            concat!(
+                "d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d6",
+                "3dbea45e8ca9671282fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b36",
+                "92ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc",
+                "3ff4def08e4b7a9de576d26586cec64b6116",
+                "1ae10b594f09e26a7e902ecbd0600691"
+            )
+        );
+        assert_eq!(
+            chacha20poly1305_open(&key, &nonce, &aad, &sealed).unwrap(),
+            plaintext
+        );
+
+        // Any change to the ciphertext, the tag, the nonce or the associated data is rejected.
+        for i in [0, plaintext.len() - 1, plaintext.len(), sealed.len() - 1] {
+            let mut tampered = sealed.clone();
+            tampered[i] ^= 1;
+            assert_eq!(
+                chacha20poly1305_open(&key, &nonce, &aad, &tampered),
+                Err(Error::DecryptionFailed)
+            );
+        }
+        let mut other_nonce = nonce;
+        other_nonce[11] ^= 1;
+        assert_eq!(
+            chacha20poly1305_open(&key, &other_nonce, &aad, &sealed),
+            Err(Error::DecryptionFailed)
+        );
+        assert_eq!(
+            chacha20poly1305_open(&key, &nonce, &aad[1..], &sealed),
+            Err(Error::DecryptionFailed)
+        );
+        assert_eq!(
+            chacha20poly1305_open(&key, &nonce, &aad, &sealed[..TAG_LEN - 1]),
+            Err(Error::DecryptionFailed)
+        );
+
+        // Lengths around the block sizes of ChaCha20 and Poly1305 match the `chacha20poly1305`
+        // crate.
+        let mut rng = thread_rng();
+        for len in [0, 1, 15, 16, 17, 63, 64, 65, 128, 1000] {
+            let key: [u8; 32] = rng.gen();
+            let nonce: [u8; 12] = rng.gen();
+            let aad: Vec<u8> = (0..rng.gen_range(0..40)).map(|_| rng.gen()).collect();
+            let plaintext: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+            let expected = ChaCha20Poly1305::new(&key.into())
+                .encrypt(
+                    &nonce.into(),
+                    Payload {
+                        msg: &plaintext,
+                        aad: &aad,
+                    },
+                )
+                .unwrap();
+            let sealed = chacha20poly1305_seal(&key, &nonce, &aad, &plaintext);
+            assert_eq!(sealed, expected);
+            assert_eq!(
+                chacha20poly1305_open(&key, &nonce, &aad, &sealed).unwrap(),
+                plaintext
+            );
+        }
+    }
+}
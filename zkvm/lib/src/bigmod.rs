@@ -0,0 +1,661 @@
+//! Modular arithmetic for moduli of up to 4096 bits, such as RSA moduli.
+//!
+//! Numbers are big endian byte strings, and results are padded to the length of the modulus.
+//! Products are computed in full, 128 bits at a time with the uint256 precompile inside the
+//! zkVM, and are reduced with a quotient and a remainder requested from the bigmod hook. The hint
+//! is checked with the division identity `x = q * m + r` and `r < m`, computed exactly on 32-bit
+//! limbs, so that every witness is range checked by its width. A hint that fails the check is
+//! ignored, and the division is computed in the VM one bit at a time instead.
+//!
+//! Outside of the zkVM, and with the `no-hints` feature, the divisions are always computed
+//! directly, unless a hint source is given with [`BigMod::with_hints`].
+//!
+//! The `rsa` and `p384` verifiers and the field arithmetic of the `kzg` and `bls12381` modules are
+//! built on this one. The RSA example still verifies with the `rsa` crate.
+
+use crate::error::Error;
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::convert::TryInto;
+
+/// The largest supported modulus, in bytes.
+pub const MAX_MODULUS_LEN: usize = 512;
+
+/// The bigmod hook operation that divides `x` by the modulus, answered with the quotient and the
+/// remainder.
+pub const OP_DIVREM: u8 = 0;
+
+/// The bigmod hook operation that inverts `x` modulo the modulus, answered with the inverse and
+/// an empty gcd, or with an empty inverse and the gcd of `x` and the modulus.
+pub const OP_INVERSE: u8 = 1;
+
+/// A source of hints, given the request to the bigmod hook.
+type Hints<'a> = Box<dyn Fn(&[u8]) -> Vec<Vec<u8>> + 'a>;
+
+/// Arithmetic modulo a fixed modulus.
+pub struct BigMod<'a> {
+    /// The little endian limbs of the modulus, without leading zero limbs.
+    modulus: Vec<u32>,
+    /// The length of the modulus in bytes, without leading zero bytes.
+    len: usize,
+    hints: Option<Hints<'a>>,
+}
+
+impl BigMod<'static> {
+    /// Creates the arithmetic modulo the big endian `modulus_be`, whose leading zero bytes are
+    /// ignored.
+    ///
+    /// Returns [`Error::InvalidModulus`] if the modulus is zero or longer than
+    /// [`MAX_MODULUS_LEN`] bytes.
+    pub fn new(modulus_be: &[u8]) -> Result<Self, Error> {
+        cfg_if::cfg_if! {
+            if #[cfg(all(target_os = "zkvm", target_vendor = "succinct", not(feature = "no-hints")))] {
+                Self::with_hints(modulus_be, hook_hints)
+            } else {
+                Self::build(modulus_be, None)
+            }
+        }
+    }
+}
+
+impl<'a> BigMod<'a> {
+    /// Creates the arithmetic modulo `modulus_be` like [`BigMod::new`], taking the hints from
+    /// `hints` instead of the bigmod hook.
+    ///
+    /// `hints` is given the requests to the hook, `op || len(m) || m || x` with the length as a
+    /// little endian `u32`, and returns the responses the hook would.
+    pub fn with_hints(
+        modulus_be: &[u8],
+        hints: impl Fn(&[u8]) -> Vec<Vec<u8>> + 'a,
+    ) -> Result<Self, Error> {
+        Self::build(modulus_be, Some(Box::new(hints)))
+    }
+
+    fn build(modulus_be: &[u8], hints: Option<Hints<'a>>) -> Result<Self, Error> {
+        let modulus_be = strip_leading_zeros(modulus_be);
+        if modulus_be.is_empty() || modulus_be.len() > MAX_MODULUS_LEN {
+            return Err(Error::InvalidModulus);
+        }
+        Ok(Self {
+            modulus: from_be(modulus_be),
+            len: modulus_be.len(),
+            hints,
+        })
+    }
+
+    /// Returns `a * b` modulo the modulus.
+    pub fn mul(&self, a: &[u8], b: &[u8]) -> Vec<u8> {
+        let product = mul_wide(&from_be(a), &from_be(b));
+        self.to_be(&self.reduce(&product))
+    }
+
+    /// Returns `a + b` modulo the modulus.
+    pub fn add(&self, a: &[u8], b: &[u8]) -> Vec<u8> {
+        let sum = add(&self.residue(a), &self.residue(b));
+        self.to_be(&self.reduce_once(sum))
+    }
+
+    /// Returns `a - b` modulo the modulus.
+    pub fn sub(&self, a: &[u8], b: &[u8]) -> Vec<u8> {
+        let a = self.residue(a);
+        let b = self.residue(b);
+        let difference = if compare(&a, &b) == Ordering::Less {
+            sub(&add(&a, &self.modulus), &b)
+        } else {
+            sub(&a, &b)
+        };
+        self.to_be(&difference)
+    }
+
+    /// Returns `base ^ exp` modulo the modulus, where `exp` is a big endian integer.
+    pub fn pow(&self, base: &[u8], exp: &[u8]) -> Vec<u8> {
+        let base = self.residue(base);
+        let mut res = self.residue(&[1]);
+        for bit in strip_leading_zeros(exp)
+            .iter()
+            .flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1))
+        {
+            res = self.reduce(&mul_wide(&res, &res));
+            if bit == 1 {
+                res = self.reduce(&mul_wide(&res, &base));
+            }
+        }
+        self.to_be(&res)
+    }
+
+    /// Returns the inverse of `a` modulo the modulus, or `None` if `a` is not coprime to it.
+    ///
+    /// Both outcomes are checked: an inverse `x` with `a * x = 1`, and a missing inverse with a
+    /// common divisor `g > 1` of `a` and the modulus, each found with the bigmod hook.
+    pub fn inv(&self, a: &[u8]) -> Option<Vec<u8>> {
+        let a = self.residue(a);
+        let one = self.residue(&[1]);
+        if let Some([inverse, gcd]) = self.hint(OP_INVERSE, &self.modulus, &a) {
+            let inverse = from_be(&inverse);
+            let gcd = from_be(strip_leading_zeros(&gcd));
+            if compare(&inverse, &self.modulus) == Ordering::Less
+                && gcd.is_empty()
+                && self.reduce(&mul_wide(&a, &inverse)) == one
+            {
+                return Some(self.to_be(&inverse));
+            }
+            if compare(&gcd, &[1]) == Ordering::Greater
+                && is_zero(&self.divrem(&self.modulus, &gcd).1)
+                && is_zero(&self.divrem(&a, &gcd).1)
+            {
+                return None;
+            }
+        }
+
+        let (inverse, gcd) = invert(&a, &self.modulus);
+        (gcd == [1]).then(|| self.to_be(&inverse))
+    }
+
+    /// Returns `x` modulo the modulus, as many limbs as the modulus.
+    fn residue(&self, x: &[u8]) -> Vec<u32> {
+        self.reduce(&from_be(x))
+    }
+
+    /// Reduces `x`, checking the hinted quotient and remainder when `x` is not already reduced.
+    fn reduce(&self, x: &[u32]) -> Vec<u32> {
+        if compare(x, &self.modulus) == Ordering::Less {
+            return pad(x.to_vec(), self.modulus.len());
+        }
+        pad(self.divrem(x, &self.modulus).1, self.modulus.len())
+    }
+
+    /// Divides `x` by `d`, checking the hinted quotient and remainder.
+    fn divrem(&self, x: &[u32], d: &[u32]) -> (Vec<u32>, Vec<u32>) {
+        divrem(x, d, self.hint(OP_DIVREM, d, x))
+    }
+
+    /// Reduces a number below twice the modulus.
+    fn reduce_once(&self, x: Vec<u32>) -> Vec<u32> {
+        let x = if compare(&x, &self.modulus) == Ordering::Less {
+            x
+        } else {
+            sub(&x, &self.modulus)
+        };
+        pad(x, self.modulus.len())
+    }
+
+    /// Requests a hint for `x` modulo `m`, returning the two responses of the hook if there is a
+    /// hint source.
+    fn hint(&self, op: u8, m: &[u32], x: &[u32]) -> Option<[Vec<u8>; 2]> {
+        let hints = self.hints.as_ref()?;
+        let modulus = limbs_to_be(m);
+        let mut request = Vec::with_capacity(5 + modulus.len() + 4 * x.len());
+        request.push(op);
+        request.extend_from_slice(&(modulus.len() as u32).to_le_bytes());
+        request.extend_from_slice(&modulus);
+        request.extend_from_slice(&limbs_to_be(x));
+        hints(&request).try_into().ok()
+    }
+
+    /// Returns a reduced number as as many bytes as the modulus.
+    fn to_be(&self, limbs: &[u32]) -> Vec<u8> {
+        let bytes = limbs_to_be(&pad(limbs.to_vec(), self.modulus.len()));
+        bytes[bytes.len() - self.len..].to_vec()
+    }
+}
+
+/// Requests a hint from the bigmod hook, which answers every request with two responses.
+///
+/// The tests pass it to [`BigMod::with_hints`] to answer the requests with `io::mock`.
+#[cfg(all(
+    any(all(target_os = "zkvm", target_vendor = "succinct"), test),
+    not(feature = "no-hints")
+))]
+pub(crate) fn hook_hints(request: &[u8]) -> Vec<Vec<u8>> {
+    use crate::io;
+    use crate::unconstrained;
+
+    unconstrained! {
+        io::write(io::FD_BIGMOD_HOOK, request);
+    }
+    vec![io::read_vec(), io::read_vec()]
+}
+
+/// Returns the quotient and the remainder of `x` divided by `d`, using the hinted quotient and
+/// remainder if they satisfy the division identity.
+fn divrem(x: &[u32], d: &[u32], hint: Option<[Vec<u8>; 2]>) -> (Vec<u32>, Vec<u32>) {
+    if let Some([q, r]) = hint {
+        let (q, r) = (from_be(&q), from_be(&r));
+        if compare(&r, d) == Ordering::Less
+            && compare(&add(&mul_wide(&q, d), &r), x) == Ordering::Equal
+        {
+            return (q, r);
+        }
+    }
+
+    // Shift and subtract, one bit of the quotient at a time.
+    let mut q = vec![0u32; x.len()];
+    let mut r: Vec<u32> = Vec::with_capacity(d.len() + 1);
+    for i in (0..32 * x.len()).rev() {
+        // r = 2 * r + the next bit of x.
+        let mut carry = (x[i / 32] >> (i % 32)) & 1;
+        for limb in r.iter_mut() {
+            let next = *limb >> 31;
+            *limb = (*limb << 1) | carry;
+            carry = next;
+        }
+        if carry != 0 {
+            r.push(carry);
+        }
+        if compare(&r, d) != Ordering::Less {
+            r = sub(&r, d);
+            q[i / 32] |= 1 << (i % 32);
+        }
+    }
+    (q, r)
+}
+
+/// Returns the inverse of `a` modulo `m` if they are coprime, and their gcd, with the extended
+/// Euclidean algorithm. The coefficients are kept modulo `m`, so that they are never negative.
+fn invert(a: &[u32], m: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    let (mut r0, mut r1) = (m.to_vec(), a.to_vec());
+    let (mut t0, mut t1) = (vec![], vec![1]);
+    while !is_zero(&r1) {
+        let (q, r) = divrem(&r0, &r1, None);
+        // t0 - q * t1 modulo m.
+        let qt = divrem(&mul_wide(&q, &t1), m, None).1;
+        let t = if compare(&t0, &qt) == Ordering::Less {
+            sub(&add(&t0, m), &qt)
+        } else {
+            sub(&t0, &qt)
+        };
+        (r0, r1) = (r1, r);
+        (t0, t1) = (t1, t);
+    }
+    // Modulo 1, the inverse is 0 rather than the initial coefficient 1.
+    let inverse = divrem(&t0, m, None).1;
+    (trim(inverse), trim(r0))
+}
+
+/// Returns the full product of two numbers, with as many limbs as both.
+fn mul_wide(a: &[u32], b: &[u32]) -> Vec<u32> {
+    cfg_if::cfg_if! {
+        if #[cfg(all(target_os = "zkvm", target_vendor = "succinct"))] {
+            use crate::syscall_uint256_mulmod;
+
+            // Multiplies 128-bit chunks into 256-bit products, with a zero modulus for 2^256.
+            let a = pad(a.to_vec(), (a.len() + 3) / 4 * 4);
+            let b = pad(b.to_vec(), (b.len() + 3) / 4 * 4);
+            let mut res = vec![0u32; a.len() + b.len()];
+            for (i, a_chunk) in a.chunks_exact(4).enumerate() {
+                for (j, b_chunk) in b.chunks_exact(4).enumerate() {
+                    let mut x = [0u32; 8];
+                    x[..4].copy_from_slice(a_chunk);
+                    let mut y_and_modulus = [0u32; 16];
+                    y_and_modulus[..4].copy_from_slice(b_chunk);
+                    unsafe {
+                        syscall_uint256_mulmod(x.as_mut_ptr(), y_and_modulus.as_ptr());
+                    }
+                    add_at(&mut res, &x, 4 * (i + j));
+                }
+            }
+            res
+        } else {
+            let mut res = vec![0u32; a.len() + b.len()];
+            for (i, &a_limb) in a.iter().enumerate() {
+                let mut carry = 0u64;
+                for (j, &b_limb) in b.iter().enumerate() {
+                    let t = a_limb as u64 * b_limb as u64 + res[i + j] as u64 + carry;
+                    res[i + j] = t as u32;
+                    carry = t >> 32;
+                }
+                res[i + b.len()] = carry as u32;
+            }
+            res
+        }
+    }
+}
+
+/// Adds `x` to `res` at the limb `offset`, propagating the carry. `res` must be wide enough.
+#[cfg(all(target_os = "zkvm", target_vendor = "succinct"))]
+fn add_at(res: &mut [u32], x: &[u32], offset: usize) {
+    let mut carry = 0u64;
+    for (i, limb) in res[offset..].iter_mut().enumerate() {
+        if i >= x.len() && carry == 0 {
+            break;
+        }
+        let t = *limb as u64 + x.get(i).copied().unwrap_or(0) as u64 + carry;
+        *limb = t as u32;
+        carry = t >> 32;
+    }
+}
+
+fn add(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut res = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, &limb) in long.iter().enumerate() {
+        let t = limb as u64 + short.get(i).copied().unwrap_or(0) as u64 + carry;
+        res.push(t as u32);
+        carry = t >> 32;
+    }
+    res.push(carry as u32);
+    res
+}
+
+/// Returns `a - b`, for `a >= b`, with as many limbs as `a`.
+fn sub(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut res = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, &limb) in a.iter().enumerate() {
+        let t = limb as i64 - b.get(i).copied().unwrap_or(0) as i64 - borrow;
+        res.push(t as u32);
+        borrow = (t < 0) as i64;
+    }
+    debug_assert!(borrow == 0 && b.iter().skip(a.len()).all(|&limb| limb == 0));
+    res
+}
+
+/// Compares numbers of any number of limbs.
+fn compare(a: &[u32], b: &[u32]) -> Ordering {
+    let len = a.len().max(b.len());
+    (0..len)
+        .rev()
+        .map(|i| {
+            let a = a.get(i).copied().unwrap_or(0);
+            let b = b.get(i).copied().unwrap_or(0);
+            a.cmp(&b)
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+fn is_zero(x: &[u32]) -> bool {
+    x.iter().all(|&limb| limb == 0)
+}
+
+/// Removes the leading zero limbs.
+fn trim(mut x: Vec<u32>) -> Vec<u32> {
+    while x.last() == Some(&0) {
+        x.pop();
+    }
+    x
+}
+
+/// Pads or trims `x` to `len` limbs. The removed limbs must be zero.
+fn pad(mut x: Vec<u32>, len: usize) -> Vec<u32> {
+    debug_assert!(x.iter().skip(len).all(|&limb| limb == 0));
+    x.resize(len, 0);
+    x
+}
+
+fn strip_leading_zeros(bytes: &[u8]) -> &[u8] {
+    let start = bytes
+        .iter()
+        .position(|&byte| byte != 0)
+        .unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+/// Converts a big endian number to little endian limbs.
+fn from_be(bytes: &[u8]) -> Vec<u32> {
+    bytes
+        .rchunks(4)
+        .map(|chunk| {
+            let mut limb = [0u8; 4];
+            limb[4 - chunk.len()..].copy_from_slice(chunk);
+            u32::from_be_bytes(limb)
+        })
+        .collect()
+}
+
+/// Converts little endian limbs to a big endian number of four bytes per limb.
+fn limbs_to_be(limbs: &[u32]) -> Vec<u8> {
+    limbs
+        .iter()
+        .rev()
+        .flat_map(|limb| limb.to_be_bytes())
+        .collect()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    fn num(bytes: &[u8]) -> u128 {
+        bytes.iter().fold(0, |acc, &byte| acc << 8 | byte as u128)
+    }
+
+    fn bytes(x: u128) -> Vec<u8> {
+        strip_leading_zeros(&x.to_be_bytes()).to_vec()
+    }
+
+    /// Returns the inverse of `a` modulo `m`, or their gcd if it is not 1.
+    fn inverse(a: u128, m: u128) -> Result<u128, u128> {
+        let (mut r0, mut r1) = (m as i128, (a % m) as i128);
+        let (mut t0, mut t1) = (0i128, 1i128);
+        while r1 != 0 {
+            let q = r0 / r1;
+            (r0, r1) = (r1, r0 - q * r1);
+            (t0, t1) = (t1, t0 - q * t1);
+        }
+        match r0 {
+            1 => Ok(t0.rem_euclid(m as i128) as u128),
+            gcd => Err(gcd as u128),
+        }
+    }
+
+    /// The answers of the bigmod hook, for numbers of up to 128 bits.
+    fn hints(request: &[u8]) -> Vec<Vec<u8>> {
+        let len = u32::from_le_bytes(request[1..5].try_into().unwrap()) as usize;
+        let m = num(&request[5..5 + len]);
+        let x = num(&request[5 + len..]);
+        match request[0] {
+            OP_DIVREM => vec![bytes(x / m), bytes(x % m)],
+            _ => match inverse(x, m) {
+                Ok(inverse) => vec![bytes(inverse), vec![]],
+                Err(gcd) => vec![vec![], bytes(gcd)],
+            },
+        }
+    }
+
+    /// The answers of the bigmod hook of the executor, with as many bytes.
+    #[cfg(not(feature = "no-hints"))]
+    pub(crate) fn hook_responses(request: &[u8]) -> Vec<Vec<u8>> {
+        use num_bigint::BigUint;
+
+        let padded = |x: &BigUint, len: usize| {
+            let bytes = x.to_bytes_be();
+            let mut padded = vec![0u8; len.max(bytes.len())];
+            let start = padded.len() - bytes.len();
+            padded[start..].copy_from_slice(&bytes);
+            padded[padded.len() - len..].to_vec()
+        };
+        let len = u32::from_le_bytes(request[1..5].try_into().unwrap()) as usize;
+        let value = &request[5 + len..];
+        let m = BigUint::from_bytes_be(&request[5..5 + len]);
+        let x = BigUint::from_bytes_be(value);
+        match request[0] {
+            OP_DIVREM => vec![padded(&(&x / &m), value.len()), padded(&(&x % &m), len)],
+            _ => match x.modinv(&m) {
+                Some(inverse) => vec![padded(&inverse, len), vec![]],
+                None => {
+                    let (mut a, mut b) = (m, x);
+                    while b != BigUint::ZERO {
+                        (a, b) = (b.clone(), a % b);
+                    }
+                    vec![vec![], padded(&a, len)]
+                }
+            },
+        }
+    }
+
+    /// Checks every operation modulo `m` against `u128` arithmetic.
+    fn check_operations(arith: &BigMod, m: u64) {
+        let m = m as u128;
+        let len = bytes(m).len();
+        let padded = |x: u128| x.to_be_bytes()[16 - len..].to_vec();
+        for (a, b) in [
+            (0, 0),
+            (1, m - 1),
+            (m - 1, m),
+            (0xdead_beef_cafe_f00d, 0x0123_4567_89ab_cdef),
+            (u64::MAX as u128, u128::MAX),
+        ] {
+            let (a_bytes, b_bytes) = (bytes(a), bytes(b));
+            let (a, b) = (a % m, b % m);
+            assert_eq!(arith.mul(&a_bytes, &b_bytes), padded(a * b % m));
+            assert_eq!(arith.add(&a_bytes, &b_bytes), padded((a + b) % m));
+            assert_eq!(arith.sub(&a_bytes, &b_bytes), padded((a + m - b) % m));
+            let pow = (0..0x1234).fold(1 % m, |acc, _| acc * a % m);
+            assert_eq!(arith.pow(&a_bytes, &[0x12, 0x34]), padded(pow));
+            assert_eq!(
+                arith.inv(&b_bytes),
+                inverse(b, m).ok().map(|inverse| padded(inverse % m))
+            );
+        }
+    }
+
+    #[test]
+    fn test_bigmod_small_moduli() {
+        for m in [
+            1,
+            2,
+            3,
+            4,
+            6,
+            12,
+            1 << 16,
+            0xffff_ffff,
+            (1 << 61) - 1,
+            u64::MAX,
+        ] {
+            let modulus = bytes(m as u128);
+            check_operations(&BigMod::new(&modulus).unwrap(), m);
+            check_operations(&BigMod::with_hints(&modulus, hints).unwrap(), m);
+        }
+
+        let arith = BigMod::new(&[0, 0, 12]).unwrap();
+        assert_eq!(arith.inv(&[5]), Some(vec![5]));
+        assert_eq!(arith.inv(&[8]), None);
+        assert_eq!(arith.inv(&[0]), None);
+        assert_eq!(arith.pow(&[7], &[]), vec![1]);
+        assert_eq!(arith.sub(&[3], &[5]), vec![10]);
+
+        assert_eq!(BigMod::new(&[0, 0]).err(), Some(Error::InvalidModulus));
+        assert_eq!(BigMod::new(&[1; 513]).err(), Some(Error::InvalidModulus));
+        assert!(BigMod::new(&[0xff; 512]).is_ok());
+    }
+
+    #[test]
+    fn test_bigmod_rejects_bad_hints() {
+        type Tamper = fn(&[u8], Vec<Vec<u8>>) -> Vec<Vec<u8>>;
+        let tampers: [Tamper; 7] = [
+            // A remainder that does not satisfy the identity.
+            |_, mut res| {
+                res[1] = bytes(num(&res[1]) + 1);
+                res
+            },
+            // A remainder of at least the modulus, with a smaller quotient.
+            |request, res| {
+                let len = u32::from_le_bytes(request[1..5].try_into().unwrap()) as usize;
+                let m = num(&request[5..5 + len]);
+                match num(&res[0]) {
+                    0 => res,
+                    q => vec![bytes(q - 1), bytes(num(&res[1]) + m)],
+                }
+            },
+            // Swapped, missing, and extra responses.
+            |_, res| res.into_iter().rev().collect(),
+            |_, res| res.into_iter().take(1).collect(),
+            |_, mut res| {
+                res.push(vec![1]);
+                res
+            },
+            // A wrong inverse, or an inverse claimed not to exist.
+            |request, res| match request[0] {
+                OP_INVERSE => vec![vec![1], vec![]],
+                _ => res,
+            },
+            |request, res| match request[0] {
+                OP_INVERSE => vec![vec![], vec![1]],
+                _ => res,
+            },
+        ];
+        for tamper in tampers {
+            for m in [(1 << 61) - 1, 0xdead_beef_cafe_f00e] {
+                let arith = BigMod::with_hints(&bytes(m as u128), |request| {
+                    tamper(request, hints(request))
+                })
+                .unwrap();
+                check_operations(&arith, m);
+            }
+        }
+
+        // A divisor of the modulus that does not divide the value does not show that the value
+        // has no inverse.
+        let arith = BigMod::with_hints(&[15], |request| match request[0] {
+            OP_INVERSE => vec![vec![], vec![3]],
+            _ => vec![vec![], vec![]],
+        })
+        .unwrap();
+        assert_eq!(arith.inv(&[2]), Some(vec![8]));
+    }
+    #[test]
+    #[cfg(not(feature = "no-hints"))]
+    fn test_bigmod_mock_hook() {
+        use crate::io::{mock, FD_BIGMOD_HOOK};
+        use core::cell::Cell;
+        use std::rc::Rc;
+
+        let requests = Rc::new(Cell::new(0));
+        let hook = {
+            let requests = requests.clone();
+            move |fd, request: &[u8]| {
+                assert_eq!(fd, FD_BIGMOD_HOOK);
+                requests.set(requests.get() + 1);
+                hook_responses(request)
+            }
+        };
+        let ((), unread) = mock::with_hook(hook, || {
+            for m in [(1 << 61) - 1, 0xdead_beef_cafe_f00e] {
+                check_operations(
+                    &BigMod::with_hints(&bytes(m as u128), hook_hints).unwrap(),
+                    m,
+                );
+            }
+        });
+        assert_eq!(unread, 0);
+        assert!(requests.get() > 0);
+
+        // The responses for another value, a remainder off by one, and empty responses are all
+        // ignored.
+        type Tamper = fn(&[u8]) -> Vec<Vec<u8>>;
+        let tampers: [Tamper; 3] = [
+            |request| {
+                let mut other = request.to_vec();
+                *other.last_mut().unwrap() ^= 1;
+                hook_responses(&other)
+            },
+            |request| {
+                let mut res = hook_responses(request);
+                if let Some(last) = res[1].last_mut() {
+                    *last ^= 1;
+                }
+                res
+            },
+            |_| vec![vec![], vec![]],
+        ];
+        for tamper in tampers {
+            let ((), unread) = mock::with_hook(
+                move |_, request| tamper(request),
+                || {
+                    for m in [(1 << 61) - 1, 0xdead_beef_cafe_f00e] {
+                        check_operations(
+                            &BigMod::with_hints(&bytes(m as u128), hook_hints).unwrap(),
+                            m,
+                        );
+                    }
+                },
+            );
+            assert_eq!(unread, 0);
+        }
+    }
+}
@@ -0,0 +1,950 @@
+//! Bitcoin utilities: double SHA-256, proof-of-work and header chain checks, and BIP-322 signed
+//! messages.
+//!
+//! The difficulty rules are the ones of mainnet. The testnet rule that allows minimum difficulty
+//! blocks is not implemented, and timestamps are not checked against the median time past.
+
+use crate::error::Error;
+use crate::secp256k1::{schnorr, verify_signature};
+use crate::sha256::sha256;
+use alloc::vec::Vec;
+use core::convert::TryInto;
+use core::fmt;
+use k256::ecdsa::Signature;
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::PublicKey;
+use ripemd::{Digest, Ripemd160};
+
+/// The number of blocks between difficulty adjustments.
+pub const RETARGET_INTERVAL: u32 = 2016;
+
+/// The expected duration of a difficulty period, two weeks, in seconds.
+const TARGET_TIMESPAN: u32 = 14 * 24 * 60 * 60;
+
+/// The largest target of mainnet, `0x00000000ffff << 208`, in compact form.
+const POW_LIMIT_BITS: u32 = 0x1d00ffff;
+
+/// A block hash, in the byte order in which hashes are conventionally displayed, that is the
+/// reverse of the order of the SHA-256 output.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BlockHash(pub [u8; 32]);
+
+impl BlockHash {
+    /// Creates a block hash from the raw double SHA-256 output, as found in headers.
+    pub fn from_internal(mut bytes: [u8; 32]) -> Self {
+        bytes.reverse();
+        Self(bytes)
+    }
+
+    /// Returns the raw double SHA-256 output, as found in headers.
+    pub fn to_internal(&self) -> [u8; 32] {
+        let mut bytes = self.0;
+        bytes.reverse();
+        bytes
+    }
+}
+
+impl fmt::Display for BlockHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in &self.0 {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The state of the chain before the first header passed to [`verify_header_chain`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// The hash of the last block before the headers.
+    pub hash: BlockHash,
+    /// The height of that block.
+    pub height: u32,
+    /// The compact target of that block.
+    pub bits: u32,
+    /// The timestamp of that block.
+    pub time: u32,
+    /// The timestamp of the first block of the difficulty period of that block, at the height
+    /// `height - height % RETARGET_INTERVAL`.
+    pub period_start_time: u32,
+}
+
+/// Computes `SHA-256(SHA-256(data))` with the sha256 precompile.
+pub fn sha256d(data: &[u8]) -> [u8; 32] {
+    sha256(&sha256(data))
+}
+
+/// Computes the hash of a header and checks that it is at most the header's target.
+///
+/// Returns [`Error::InvalidCompactTarget`] if the target is negative, overflows or is zero, and
+/// [`Error::InsufficientProofOfWork`] if the hash is above it. The target is not checked against
+/// the difficulty rules, use [`verify_header_chain`] for that.
+pub fn check_header_pow(header80: &[u8; 80]) -> Result<BlockHash, Error> {
+    let target = expand_compact(header_bits(header80)).ok_or(Error::InvalidCompactTarget)?;
+    let hash = BlockHash::from_internal(sha256d(header80));
+    // Both are big endian, so they compare as integers.
+    if hash.0 > target {
+        return Err(Error::InsufficientProofOfWork);
+    }
+    Ok(hash)
+}
+
+/// Verifies a chain of headers following a checkpoint: the proof of work of each header, that
+/// each header commits to the hash of the previous block, and that the targets follow the
+/// difficulty adjustments. Returns the hash of the last header, or of the checkpoint if there
+/// are no headers.
+///
+/// Returns [`Error::HeaderChainBroken`] and [`Error::UnexpectedDifficulty`] with the index of the
+/// offending header.
+pub fn verify_header_chain(
+    headers: &[[u8; 80]],
+    checkpoint: &Checkpoint,
+) -> Result<BlockHash, Error> {
+    let mut prev = *checkpoint;
+    for (index, header) in headers.iter().enumerate() {
+        if header[4..36] != prev.hash.to_internal() {
+            return Err(Error::HeaderChainBroken { index });
+        }
+
+        let height = prev.height + 1;
+        let bits = header_bits(header);
+        let time = u32::from_le_bytes(header[68..72].try_into().unwrap());
+        let expected_bits = if height % RETARGET_INTERVAL == 0 {
+            next_bits(
+                prev.bits,
+                prev.time.wrapping_sub(prev.period_start_time) as i32,
+            )
+            .ok_or(Error::InvalidCompactTarget)?
+        } else {
+            prev.bits
+        };
+        if bits != expected_bits {
+            return Err(Error::UnexpectedDifficulty { index });
+        }
+
+        let hash = check_header_pow(header)?;
+        let period_start_time = if height % RETARGET_INTERVAL == 0 {
+            time
+        } else {
+            prev.period_start_time
+        };
+        prev = Checkpoint {
+            hash,
+            height,
+            bits,
+            time,
+            period_start_time,
+        };
+    }
+    Ok(prev.hash)
+}
+
+fn header_bits(header: &[u8; 80]) -> u32 {
+    u32::from_le_bytes(header[72..76].try_into().unwrap())
+}
+
+/// Computes the compact target of the first block of a difficulty period, from the compact target
+/// of the previous period and the time it took, as `GetNextWorkRequired` in Bitcoin Core.
+fn next_bits(bits: u32, timespan: i32) -> Option<u32> {
+    let timespan = timespan.clamp((TARGET_TIMESPAN / 4) as i32, (TARGET_TIMESPAN * 4) as i32);
+    let target = expand_compact(bits)?;
+
+    // target * timespan / TARGET_TIMESPAN, on little endian limbs with room for the carry.
+    let mut limbs = [0u64; 9];
+    for (limb, chunk) in limbs.iter_mut().zip(target.rchunks_exact(4)) {
+        *limb = u32::from_be_bytes(chunk.try_into().unwrap()) as u64;
+    }
+    let mut carry = 0;
+    for limb in limbs.iter_mut() {
+        let product = *limb * timespan as u64 + carry;
+        *limb = product & 0xffff_ffff;
+        carry = product >> 32;
+    }
+    let mut remainder = 0;
+    for limb in limbs.iter_mut().rev() {
+        let dividend = (remainder << 32) | *limb;
+        *limb = dividend / TARGET_TIMESPAN as u64;
+        remainder = dividend % TARGET_TIMESPAN as u64;
+    }
+
+    let pow_limit = expand_compact(POW_LIMIT_BITS).unwrap();
+    if limbs[8] != 0 {
+        return Some(to_compact(&pow_limit));
+    }
+    let mut new_target = [0u8; 32];
+    for (chunk, limb) in new_target.rchunks_exact_mut(4).zip(&limbs) {
+        chunk.copy_from_slice(&(*limb as u32).to_be_bytes());
+    }
+    Some(to_compact(&new_target.min(pow_limit)))
+}
+
+/// Expands a compact target into a big endian integer, rejecting the negative, overflowing and
+/// zero encodings that Bitcoin Core rejects.
+fn expand_compact(bits: u32) -> Option<[u8; 32]> {
+    let exponent = (bits >> 24) as usize;
+    let mantissa = bits & 0x007f_ffff;
+    if mantissa == 0 || bits & 0x0080_0000 != 0 {
+        return None;
+    }
+    if exponent > 34 || (mantissa > 0xff && exponent > 33) || (mantissa > 0xffff && exponent > 32) {
+        return None;
+    }
+
+    // The mantissa is the integer's 3 most significant bytes of `exponent` bytes.
+    let mut target = [0u8; 32];
+    for (i, byte) in mantissa.to_be_bytes()[1..].iter().enumerate() {
+        let pos = 32 + i;
+        // Bytes past the end are shifted out, and the ones before the start are zero.
+        if pos >= exponent && pos - exponent < 32 {
+            target[pos - exponent] = *byte;
+        }
+    }
+    if target == [0; 32] {
+        return None;
+    }
+    Some(target)
+}
+
+/// Encodes a nonzero big endian integer as a compact target, as `GetCompact` in Bitcoin Core.
+fn to_compact(target: &[u8; 32]) -> u32 {
+    let start = target.iter().position(|&byte| byte != 0).unwrap_or(32);
+    let mut size = 32 - start;
+    let byte_at = |i: usize| target.get(start + i).copied().unwrap_or(0) as u32;
+    let mut mantissa = (byte_at(0) << 16) | (byte_at(1) << 8) | byte_at(2);
+    // The sign bit must be clear.
+    if mantissa & 0x0080_0000 != 0 {
+        mantissa >>= 8;
+        size += 1;
+    }
+    ((size as u32) << 24) | mantissa
+}
+
+/// The sighash type of Taproot signatures without a sighash byte, which commits like
+/// `SIGHASH_ALL`.
+const SIGHASH_DEFAULT: u8 = 0x00;
+const SIGHASH_ALL: u8 = 0x01;
+/// The other standard sighash types, which are not supported.
+const SIGHASH_OTHERS: [u8; 5] = [0x02, 0x03, 0x81, 0x82, 0x83];
+
+/// The `OP_RETURN` script of the output of the `to_sign` transaction.
+const OP_RETURN_SCRIPT: [u8; 1] = [0x6a];
+
+/// The characters of the bech32 alphabet, in the order of their values.
+const BECH32_CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+/// The checksum constants of BIP-173 for witness version 0 and of BIP-350 for later versions.
+const BECH32_CONST: u32 = 1;
+const BECH32M_CONST: u32 = 0x2bc8_30a3;
+
+/// The address types for which BIP-322 signatures can be verified.
+enum WitnessProgram {
+    P2wpkh([u8; 20]),
+    P2tr([u8; 32]),
+}
+
+impl WitnessProgram {
+    fn script_pubkey(&self) -> Vec<u8> {
+        // OP_0 or OP_1, then a push of the program.
+        let (version, program) = match self {
+            WitnessProgram::P2wpkh(hash) => (0x00, &hash[..]),
+            WitnessProgram::P2tr(key) => (0x51, &key[..]),
+        };
+        let mut script = Vec::from([version, program.len() as u8]);
+        script.extend_from_slice(program);
+        script
+    }
+}
+
+/// Verifies a BIP-322 signature of `message` by `address`, in the simple format: the consensus
+/// encoding of the witness stack of the `to_sign` transaction, as decoded from base64.
+///
+/// P2WPKH signatures are checked with their BIP-143 sighash, and P2TR key path signatures with
+/// their BIP-341 sighash. Both must use `SIGHASH_ALL`, or the default sighash type for P2TR, and
+/// ECDSA signatures must be strictly DER encoded with a low s, as the standardness rules of
+/// Bitcoin Core require.
+///
+/// Returns `Ok(false)` if the signature does not verify. Returns [`Error::InvalidAddress`] if the
+/// address is not a valid bech32 or bech32m address, [`Error::WitnessMalformed`] if the witness
+/// cannot be decoded, and [`Error::Bip322Unsupported`] for the legacy and full formats, for other
+/// address types, for Taproot script path spends and annexes, and for other sighash types.
+pub fn verify_bip322(address: &str, message: &[u8], witness: &[u8]) -> Result<bool, Error> {
+    let program = parse_address(address)?;
+    // A full signature is a transaction, which starts with its 4-byte version, while a simple
+    // signature starting with 4 zero bytes would be an empty witness with trailing bytes.
+    if witness.starts_with(&[0; 4]) {
+        return Err(Error::Bip322Unsupported);
+    }
+    let witness = parse_witness(witness).ok_or(Error::WitnessMalformed)?;
+
+    let script_pubkey = program.script_pubkey();
+    let message_hash = schnorr::tagged_hash("BIP0322-signed-message", &[message]);
+    let mut outpoint = [0u8; 36];
+    outpoint[..32].copy_from_slice(&to_spend_txid(&script_pubkey, &message_hash));
+
+    // The amount, script and sequence of the single input and output of `to_sign`, whose
+    // version and lock time are 0 as well.
+    let mut outputs = Vec::from([0u8; 8]);
+    outputs.push(OP_RETURN_SCRIPT.len() as u8);
+    outputs.extend_from_slice(&OP_RETURN_SCRIPT);
+
+    match program {
+        WitnessProgram::P2wpkh(hash) => {
+            let [signature, pubkey] = &witness[..] else {
+                return Ok(false);
+            };
+            let Some((&hash_type, signature)) = signature.split_last() else {
+                return Ok(false);
+            };
+            if !check_hash_type(hash_type)? {
+                return Ok(false);
+            }
+            // Uncompressed keys are not standard in segwit scripts.
+            let Ok(pubkey) = <&[u8; 33]>::try_from(&pubkey[..]) else {
+                return Ok(false);
+            };
+            if hash160(pubkey) != hash {
+                return Ok(false);
+            }
+            let Some(signature) = parse_der_signature(signature) else {
+                return Ok(false);
+            };
+            if signature.normalize_s().is_some() {
+                return Ok(false);
+            }
+
+            // The BIP-143 preimage, with the P2PKH script code of the key hash.
+            let mut preimage = Vec::new();
+            preimage.extend_from_slice(&0u32.to_le_bytes());
+            preimage.extend_from_slice(&sha256d(&outpoint));
+            preimage.extend_from_slice(&sha256d(&0u32.to_le_bytes()));
+            preimage.extend_from_slice(&outpoint);
+            preimage.extend_from_slice(&[0x19, 0x76, 0xa9, 0x14]);
+            preimage.extend_from_slice(&hash);
+            preimage.extend_from_slice(&[0x88, 0xac]);
+            preimage.extend_from_slice(&0u64.to_le_bytes());
+            preimage.extend_from_slice(&0u32.to_le_bytes());
+            preimage.extend_from_slice(&sha256d(&outputs));
+            preimage.extend_from_slice(&0u32.to_le_bytes());
+            preimage.extend_from_slice(&(hash_type as u32).to_le_bytes());
+
+            // The key is decompressed in software, as it may not be on the curve.
+            let Ok(pubkey) = PublicKey::from_sec1_bytes(pubkey) else {
+                return Ok(false);
+            };
+            let mut uncompressed = [0u8; 65];
+            uncompressed.copy_from_slice(pubkey.to_encoded_point(false).as_bytes());
+            Ok(verify_signature(
+                &uncompressed,
+                &sha256d(&preimage),
+                &signature,
+                None,
+            ))
+        }
+        WitnessProgram::P2tr(output_key) => {
+            // Other stacks are script path spends, spends with an annex, or empty.
+            let [signature] = &witness[..] else {
+                return Err(Error::Bip322Unsupported);
+            };
+            let (signature, hash_type) = match signature.len() {
+                64 => (&signature[..], SIGHASH_DEFAULT),
+                // The default type must be implicit.
+                65 if signature[64] != SIGHASH_DEFAULT => (&signature[..64], signature[64]),
+                _ => return Ok(false),
+            };
+            if hash_type != SIGHASH_DEFAULT && !check_hash_type(hash_type)? {
+                return Ok(false);
+            }
+
+            // The BIP-341 common signature message, for the key path of input 0 without annex.
+            let mut script_pubkeys = Vec::from([script_pubkey.len() as u8]);
+            script_pubkeys.extend_from_slice(&script_pubkey);
+            let sighash = schnorr::tagged_hash(
+                "TapSighash",
+                &[
+                    &[0x00, hash_type],
+                    &0u32.to_le_bytes(),
+                    &0u32.to_le_bytes(),
+                    &sha256(&outpoint),
+                    &sha256(&0u64.to_le_bytes()),
+                    &sha256(&script_pubkeys),
+                    &sha256(&0u32.to_le_bytes()),
+                    &sha256(&outputs),
+                    &[0x00],
+                    &0u32.to_le_bytes(),
+                ],
+            );
+
+            // The output key may not be on the curve, which `lift_x` reports as an error.
+            schnorr::verify(&output_key, &sighash, signature.try_into().unwrap())
+        }
+    }
+}
+
+/// Checks that an explicit sighash type is `SIGHASH_ALL`, returning an error for the other
+/// standard types and `false` for undefined ones.
+fn check_hash_type(hash_type: u8) -> Result<bool, Error> {
+    if SIGHASH_OTHERS.contains(&hash_type) {
+        return Err(Error::Bip322Unsupported);
+    }
+    Ok(hash_type == SIGHASH_ALL)
+}
+
+/// Parses a segwit address of mainnet, testnet or regtest into a supported witness program.
+fn parse_address(address: &str) -> Result<WitnessProgram, Error> {
+    let lowercase = address.to_ascii_lowercase();
+    // Base58 addresses are P2PKH or P2SH, which need the legacy format.
+    if !["bc1", "tb1", "bcrt1"]
+        .iter()
+        .any(|prefix| lowercase.starts_with(prefix))
+    {
+        return Err(Error::Bip322Unsupported);
+    }
+    let (version, program) = decode_segwit_address(address).ok_or(Error::InvalidAddress)?;
+    match (version, program.len()) {
+        (0, 20) => Ok(WitnessProgram::P2wpkh(program.try_into().unwrap())),
+        (1, 32) => Ok(WitnessProgram::P2tr(program.try_into().unwrap())),
+        // P2WSH and future versions.
+        _ => Err(Error::Bip322Unsupported),
+    }
+}
+
+/// Decodes a segwit address into its witness version and program, checking the bech32 checksum
+/// for version 0 and the bech32m checksum for later versions.
+fn decode_segwit_address(address: &str) -> Option<(u8, Vec<u8>)> {
+    if address.len() > 90
+        || (address.bytes().any(|c| c.is_ascii_lowercase())
+            && address.bytes().any(|c| c.is_ascii_uppercase()))
+    {
+        return None;
+    }
+    let address = address.to_ascii_lowercase();
+    let (hrp, data) = address.rsplit_once('1')?;
+    if !matches!(hrp, "bc" | "tb" | "bcrt") {
+        return None;
+    }
+    let values = data
+        .bytes()
+        .map(|c| BECH32_CHARSET.iter().position(|&d| d == c).map(|v| v as u8))
+        .collect::<Option<Vec<u8>>>()?;
+    let (&version, program) = values.get(..values.len().checked_sub(6)?)?.split_first()?;
+
+    let mut checksum_input: Vec<u8> = hrp.bytes().map(|c| c >> 5).collect();
+    checksum_input.push(0);
+    checksum_input.extend(hrp.bytes().map(|c| c & 0x1f));
+    checksum_input.extend_from_slice(&values);
+    let expected = if version == 0 {
+        BECH32_CONST
+    } else {
+        BECH32M_CONST
+    };
+    if bech32_polymod(&checksum_input) != expected || version > 16 {
+        return None;
+    }
+
+    // The program is regrouped from 5-bit to 8-bit values, with at most 4 zero padding bits.
+    let mut bytes = Vec::with_capacity(program.len() * 5 / 8);
+    let (mut acc, mut bits) = (0u32, 0);
+    for &value in program {
+        acc = ((acc << 5) | value as u32) & 0xfff;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((acc >> bits) as u8);
+        }
+    }
+    if bits >= 5 || acc & ((1 << bits) - 1) != 0 {
+        return None;
+    }
+    if !(2..=40).contains(&bytes.len()) || (version == 0 && bytes.len() != 20 && bytes.len() != 32)
+    {
+        return None;
+    }
+    Some((version, bytes))
+}
+
+/// Computes the BCH checksum of BIP-173 over 5-bit values.
+fn bech32_polymod(values: &[u8]) -> u32 {
+    const GENERATOR: [u32; 5] = [
+        0x3b6a_57b2,
+        0x2650_8e6d,
+        0x1ea1_19fa,
+        0x3d42_33dd,
+        0x2a14_62b3,
+    ];
+    let mut checksum = 1u32;
+    for &value in values {
+        let top = checksum >> 25;
+        checksum = ((checksum & 0x1ff_ffff) << 5) ^ value as u32;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
+
+/// Parses the consensus encoding of a witness stack, a count of items followed by each item with
+/// its length.
+fn parse_witness(mut bytes: &[u8]) -> Option<Vec<&[u8]>> {
+    let count = read_compact_size(&mut bytes)?;
+    // Each item takes at least one byte.
+    if count > bytes.len() as u64 {
+        return None;
+    }
+    let mut items = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let len = read_compact_size(&mut bytes)?;
+        if len > bytes.len() as u64 {
+            return None;
+        }
+        let (item, rest) = bytes.split_at(len as usize);
+        items.push(item);
+        bytes = rest;
+    }
+    bytes.is_empty().then_some(items)
+}
+
+/// Reads a canonically encoded CompactSize integer.
+fn read_compact_size(bytes: &mut &[u8]) -> Option<u64> {
+    let (&first, rest) = bytes.split_first()?;
+    let (len, min) = match first {
+        0xfd => (2, 0xfd),
+        0xfe => (4, 0x1_0000),
+        0xff => (8, 0x1_0000_0000),
+        _ => {
+            *bytes = rest;
+            return Some(first as u64);
+        }
+    };
+    let mut buf = [0u8; 8];
+    buf[..len].copy_from_slice(rest.get(..len)?);
+    let value = u64::from_le_bytes(buf);
+    if value < min {
+        return None;
+    }
+    *bytes = &rest[len..];
+    Some(value)
+}
+
+/// Computes the txid of the `to_spend` transaction of BIP-322, in internal byte order.
+fn to_spend_txid(script_pubkey: &[u8], message_hash: &[u8; 32]) -> [u8; 32] {
+    let mut tx = Vec::new();
+    tx.extend_from_slice(&0u32.to_le_bytes());
+    // A single input, spending the null outpoint with `OP_0 PUSH32 message_hash`.
+    tx.push(1);
+    tx.extend_from_slice(&[0; 32]);
+    tx.extend_from_slice(&0xffff_ffffu32.to_le_bytes());
+    tx.extend_from_slice(&[34, 0x00, 32]);
+    tx.extend_from_slice(message_hash);
+    tx.extend_from_slice(&0u32.to_le_bytes());
+    // A single output of 0 to the signer's script.
+    tx.push(1);
+    tx.extend_from_slice(&0u64.to_le_bytes());
+    tx.push(script_pubkey.len() as u8);
+    tx.extend_from_slice(script_pubkey);
+    tx.extend_from_slice(&0u32.to_le_bytes());
+    sha256d(&tx)
+}
+
+/// Computes `RIPEMD-160(SHA-256(data))`.
+fn hash160(data: &[u8]) -> [u8; 20] {
+    Ripemd160::digest(sha256(data)).into()
+}
+
+/// Parses an ECDSA signature in the strict DER encoding of BIP-66.
+fn parse_der_signature(signature: &[u8]) -> Option<Signature> {
+    let [0x30, len, rest @ ..] = signature else {
+        return None;
+    };
+    if *len as usize != rest.len() {
+        return None;
+    }
+    let (r, rest) = parse_der_integer(rest)?;
+    let (s, rest) = parse_der_integer(rest)?;
+    if !rest.is_empty() {
+        return None;
+    }
+    Signature::from_scalars(r, s).ok()
+}
+
+/// Parses a DER INTEGER that is positive and minimally encoded into a 32-byte big endian scalar.
+fn parse_der_integer(bytes: &[u8]) -> Option<([u8; 32], &[u8])> {
+    let [0x02, len, rest @ ..] = bytes else {
+        return None;
+    };
+    let len = *len as usize;
+    if len == 0 || len > rest.len() {
+        return None;
+    }
+    let (integer, rest) = rest.split_at(len);
+    let integer = match integer {
+        [byte, ..] if byte & 0x80 != 0 => return None,
+        // The leading zero byte is only allowed to keep the sign bit of the next one clear.
+        [0, next, ..] if next & 0x80 != 0 => &integer[1..],
+        [0, _, ..] => return None,
+        _ => integer,
+    };
+    if integer.len() > 32 {
+        return None;
+    }
+    let mut scalar = [0u8; 32];
+    scalar[32 - integer.len()..].copy_from_slice(integer);
+    Some((scalar, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::string::ToString;
+
+    fn header(hex_str: &str) -> [u8; 80] {
+        hex::decode(hex_str).unwrap().try_into().unwrap()
+    }
+
+    fn block_hash(hex_str: &str) -> BlockHash {
+        BlockHash(hex::decode(hex_str).unwrap().try_into().unwrap())
+    }
+
+    const GENESIS: &str = "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd\
+                           7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c";
+    const BLOCK_1: &str = "010000006fe28c0ab6f1b372c1a6a246ae63f74f931e8365e15a089c68d6190000000000982051fd\
+                           1e4ba744bbbe680e1fee14677ba1a3c3540bf7b1cdb606e857233e0e61bc6649ffff001d01e36299";
+    const BLOCK_2: &str = "010000004860eb18bf1b1620e37e9490fc8a427514416fd75159ab86688e9a8300000000d5fdcc54\
+                           1e25de1c7a5addedf24858b8bb665c9f36ef744ee42c316022c90f9bb0bc6649ffff001d08d2bd61";
+
+    fn genesis_checkpoint() -> Checkpoint {
+        Checkpoint {
+            hash: block_hash("000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f"),
+            height: 0,
+            bits: 0x1d00ffff,
+            time: 1231006505,
+            period_start_time: 1231006505,
+        }
+    }
+
+    #[test]
+    fn test_check_header_pow() {
+        let hash = check_header_pow(&header(GENESIS)).unwrap();
+        assert_eq!(hash, genesis_checkpoint().hash);
+        assert_eq!(
+            hash.to_string(),
+            "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f"
+        );
+        assert_eq!(BlockHash::from_internal(hash.to_internal()), hash);
+        assert_eq!(hash.to_internal(), sha256d(&header(GENESIS)));
+
+        let mut other_nonce = header(GENESIS);
+        other_nonce[76] ^= 1;
+        assert_eq!(
+            check_header_pow(&other_nonce),
+            Err(Error::InsufficientProofOfWork)
+        );
+        // A negative target, and a zero one.
+        for bits in [0x1d80ffffu32, 0x1d000000] {
+            let mut invalid = header(GENESIS);
+            invalid[72..76].copy_from_slice(&bits.to_le_bytes());
+            assert_eq!(check_header_pow(&invalid), Err(Error::InvalidCompactTarget));
+        }
+    }
+
+    #[test]
+    fn test_verify_header_chain() {
+        let checkpoint = genesis_checkpoint();
+        let headers = [header(BLOCK_1), header(BLOCK_2)];
+        assert_eq!(
+            verify_header_chain(&headers, &checkpoint),
+            Ok(block_hash(
+                "000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd"
+            ))
+        );
+        assert_eq!(verify_header_chain(&[], &checkpoint), Ok(checkpoint.hash));
+
+        assert_eq!(
+            verify_header_chain(&[headers[0], headers[0]], &checkpoint),
+            Err(Error::HeaderChainBroken { index: 1 })
+        );
+        assert_eq!(
+            verify_header_chain(&headers[1..], &checkpoint),
+            Err(Error::HeaderChainBroken { index: 0 })
+        );
+        // The difficulty only changes at a retarget.
+        let easier = Checkpoint {
+            bits: 0x1d01ffff,
+            ..checkpoint
+        };
+        assert_eq!(
+            verify_header_chain(&headers, &easier),
+            Err(Error::UnexpectedDifficulty { index: 0 })
+        );
+        let mut other_block_2 = headers[1];
+        other_block_2[76] ^= 1;
+        assert_eq!(
+            verify_header_chain(&[headers[0], other_block_2], &checkpoint),
+            Err(Error::InsufficientProofOfWork)
+        );
+    }
+
+    /// Block 1 as if it were at the height 2016, the first of a difficulty period.
+    #[test]
+    fn test_verify_header_chain_retarget() {
+        let block_1 = header(BLOCK_1);
+        let block_1_hash =
+            block_hash("00000000839a8e6886ab5951d76f411475428afc90947ee320161bbf18eb6048");
+        // A period of exactly two weeks keeps the target.
+        let on_time = Checkpoint {
+            height: RETARGET_INTERVAL - 1,
+            time: 1231006505 + TARGET_TIMESPAN,
+            ..genesis_checkpoint()
+        };
+        assert_eq!(verify_header_chain(&[block_1], &on_time), Ok(block_1_hash));
+        // A faster period makes the target lower, and a slower one cannot raise it above the
+        // proof of work limit.
+        let faster = Checkpoint {
+            time: 1231006505 + TARGET_TIMESPAN / 2,
+            ..on_time
+        };
+        assert_eq!(
+            verify_header_chain(&[block_1], &faster),
+            Err(Error::UnexpectedDifficulty { index: 0 })
+        );
+        let slower = Checkpoint {
+            time: 1231006505 + TARGET_TIMESPAN * 2,
+            ..faster
+        };
+        assert_eq!(verify_header_chain(&[block_1], &slower), Ok(block_1_hash));
+    }
+
+    /// The cases of `pow_tests.cpp` in Bitcoin Core.
+    #[test]
+    fn test_next_bits() {
+        // get_next_work: the retarget of block 32256.
+        assert_eq!(
+            next_bits(0x1d00ffff, 1262152739 - 1261130161),
+            Some(0x1d00d86a)
+        );
+        // get_next_work_pow_limit: the target cannot go above the limit.
+        assert_eq!(
+            next_bits(0x1d00ffff, 1233061996 - 1231006505),
+            Some(0x1d00ffff)
+        );
+        // get_next_work_lower_limit_actual: a period under half a week counts as half a week.
+        assert_eq!(
+            next_bits(0x1c05a3f4, 1279297671 - 1279008237),
+            Some(0x1c0168fd)
+        );
+        // get_next_work_upper_limit_actual: a period over eight weeks counts as eight weeks.
+        assert_eq!(
+            next_bits(0x1c387f6f, 1269211443 - 1263163443),
+            Some(0x1d00e1fd)
+        );
+    }
+
+    /// The cases of `SetCompact` in the `arith_uint256` tests of Bitcoin Core.
+    #[test]
+    fn test_compact_targets() {
+        let expand = |bits: u32| expand_compact(bits).map(|target| to_compact(&target));
+        for (bits, normalized) in [
+            (0x01123456, Some(0x01120000)),
+            (0x02123456, Some(0x02123400)),
+            (0x03123456, Some(0x03123456)),
+            (0x04123456, Some(0x04123456)),
+            (0x05009234, Some(0x05009234)),
+            (0x20123456, Some(0x20123456)),
+            (0x1d00ffff, Some(0x1d00ffff)),
+            // Zero, negative and overflowing targets.
+            (0x00123456, None),
+            (0x01003456, None),
+            (0x04923456, None),
+            (0x01fedcba, None),
+            (0xff123456, None),
+        ] {
+            assert_eq!(expand(bits), normalized, "{bits:#010x}");
+        }
+        let target = expand_compact(0x20123456).unwrap();
+        assert_eq!(target[..3], [0x12, 0x34, 0x56]);
+    }
+
+    /// The P2WPKH address of the test vectors of BIP-322.
+    const P2WPKH_ADDRESS: &str = "bc1q9vza2e8x573nczrlzms0wvx3gsqjx7vavgkx0l";
+
+    fn witness(base64: &str) -> Vec<u8> {
+        crate::utils::decode_base64(base64).unwrap()
+    }
+
+    fn p2wpkh_signatures() -> [(&'static [u8], Vec<u8>); 2] {
+        [
+            (
+                b"",
+                witness(
+                    "AkcwRAIgM2gBAQqvZX15ZiysmKmQpDrG83avLIT492QBzLnQIxYCIBaTpOaD20qRlEylyxFSeEA2ba9YOi\
+                     xpX8z46TSDtS40ASECx/EgAxlkQpQ9hYjgGu6EBCPMVPwVIVJqO4XCsMvViHI=",
+                ),
+            ),
+            (
+                b"Hello World",
+                witness(
+                    "AkcwRAIgZRfIY3p7/DoVTty6YZbWS71bc5Vct9p9Fia83eRmw2QCICK/ENGfwLtptFluMGs2KsqoNSk89p\
+                     O7F29zJLUx9a/sASECx/EgAxlkQpQ9hYjgGu6EBCPMVPwVIVJqO4XCsMvViHI=",
+                ),
+            ),
+        ]
+    }
+
+    /// The message hashes of the test vectors of BIP-322.
+    #[test]
+    fn test_bip322_message_hash() {
+        for (message, hash) in [
+            (
+                &b""[..],
+                "c90c269c4f8fcbe6880f72a721ddfbf1914268a794cbb21cfafee13770ae19f1",
+            ),
+            (
+                b"Hello World",
+                "f0eb03b1a75ac6d9847f55c624a99169b5dccba2a31f5b23bea77ba270de0a7a",
+            ),
+        ] {
+            let message_hash = schnorr::tagged_hash("BIP0322-signed-message", &[message]);
+            assert_eq!(hex::encode(message_hash), hash);
+        }
+    }
+
+    #[test]
+    fn test_verify_bip322_p2wpkh() {
+        let [(empty, empty_witness), (hello, hello_witness)] = p2wpkh_signatures();
+        assert_eq!(
+            verify_bip322(P2WPKH_ADDRESS, empty, &empty_witness),
+            Ok(true)
+        );
+        assert_eq!(
+            verify_bip322(P2WPKH_ADDRESS, hello, &hello_witness),
+            Ok(true)
+        );
+        // Addresses may be in uppercase.
+        assert_eq!(
+            verify_bip322(&P2WPKH_ADDRESS.to_uppercase(), hello, &hello_witness),
+            Ok(true)
+        );
+
+        assert_eq!(
+            verify_bip322(P2WPKH_ADDRESS, hello, &empty_witness),
+            Ok(false)
+        );
+        assert_eq!(
+            verify_bip322(P2WPKH_ADDRESS, empty, &hello_witness),
+            Ok(false)
+        );
+
+        // Byte 40 is in s, and the sighash type is at 72.
+        let mut tampered = hello_witness.clone();
+        tampered[40] ^= 1;
+        assert_eq!(verify_bip322(P2WPKH_ADDRESS, hello, &tampered), Ok(false));
+        let mut other_key = hello_witness.clone();
+        *other_key.last_mut().unwrap() ^= 1;
+        assert_eq!(verify_bip322(P2WPKH_ADDRESS, hello, &other_key), Ok(false));
+        let mut undefined_type = hello_witness.clone();
+        undefined_type[72] = 0x04;
+        assert_eq!(
+            verify_bip322(P2WPKH_ADDRESS, hello, &undefined_type),
+            Ok(false)
+        );
+        let mut single = hello_witness.clone();
+        single[72] = 0x03;
+        assert_eq!(
+            verify_bip322(P2WPKH_ADDRESS, hello, &single),
+            Err(Error::Bip322Unsupported)
+        );
+    }
+
+    #[test]
+    fn test_verify_bip322_rejects_high_s() {
+        let [_, (hello, witness)] = p2wpkh_signatures();
+        // Negate s, which keeps the signature valid for ECDSA but not for the standardness
+        // rules. The witness starts with the number of items, the length of the signature, the
+        // DER header, and r and s of 32 bytes each, then the sighash type at 72.
+        assert_eq!(witness[..6], [0x02, 0x47, 0x30, 0x44, 0x02, 0x20]);
+        assert_eq!(witness[38..40], [0x02, 0x20]);
+        let s: [u8; 32] = witness[40..72].try_into().unwrap();
+        let s = Signature::from_scalars([1; 32], s).unwrap().s();
+        let high_s: [u8; 32] = (-*s).to_bytes().into();
+        // The high s needs a leading zero byte, which makes the lengths one byte longer.
+        let mut high = Vec::from([0x02, 0x48, 0x30, 0x45]);
+        high.extend_from_slice(&witness[4..38]);
+        high.extend_from_slice(&[0x02, 0x21, 0x00]);
+        high.extend_from_slice(&high_s);
+        high.extend_from_slice(&witness[72..]);
+        assert_eq!(parse_witness(&high).map(|items| items.len()), Some(2));
+        assert_eq!(verify_bip322(P2WPKH_ADDRESS, hello, &high), Ok(false));
+    }
+
+    #[test]
+    fn test_verify_bip322_p2tr() {
+        let address = "bc1ppv609nr0vr25u07u95waq5lucwfm6tde4nydujnu8npg4q75mr5sxq8lt3";
+        let witness = witness(
+            "AUHd69PrJQEv+oKTfZ8l+WROBHuy9HKrbFCJu7U1iK2iiEy1vMU5EfMtjc+VSHM7aU0SDbak5IUZRVno2P5mjSafAQ==",
+        );
+        assert_eq!(verify_bip322(address, b"Hello World", &witness), Ok(true));
+        assert_eq!(verify_bip322(address, b"", &witness), Ok(false));
+
+        // The signature has the explicit SIGHASH_ALL type. An explicit default type is invalid.
+        let mut explicit_default = witness.clone();
+        *explicit_default.last_mut().unwrap() = SIGHASH_DEFAULT;
+        assert_eq!(
+            verify_bip322(address, b"Hello World", &explicit_default),
+            Ok(false)
+        );
+        // An annex, as a second item.
+        let mut with_annex = witness.clone();
+        with_annex[0] = 2;
+        with_annex.extend_from_slice(&[1, 0x50]);
+        assert_eq!(
+            verify_bip322(address, b"Hello World", &with_annex),
+            Err(Error::Bip322Unsupported)
+        );
+    }
+
+    #[test]
+    fn test_verify_bip322_rejects_unsupported_formats() {
+        let [_, (hello, witness)] = p2wpkh_signatures();
+        // Legacy and P2SH addresses, a P2WSH address, and an invalid checksum.
+        for (address, error) in [
+            (
+                "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa",
+                Error::Bip322Unsupported,
+            ),
+            (
+                "3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy",
+                Error::Bip322Unsupported,
+            ),
+            (
+                "bc1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3qccfmv3",
+                Error::Bip322Unsupported,
+            ),
+            (
+                "bc1q9vza2e8x573nczrlzms0wvx3gsqjx7vavgkx0m",
+                Error::InvalidAddress,
+            ),
+            (
+                "bc1Q9vza2e8x573nczrlzms0wvx3gsqjx7vavgkx0l",
+                Error::InvalidAddress,
+            ),
+        ] {
+            assert_eq!(
+                verify_bip322(address, hello, &witness),
+                Err(error),
+                "{address}"
+            );
+        }
+
+        // A full signature, which is a transaction.
+        let mut full = Vec::from([0u8; 4]);
+        full.extend_from_slice(&witness);
+        assert_eq!(
+            verify_bip322(P2WPKH_ADDRESS, hello, &full),
+            Err(Error::Bip322Unsupported)
+        );
+        assert_eq!(
+            verify_bip322(P2WPKH_ADDRESS, hello, &witness[..witness.len() - 1]),
+            Err(Error::WitnessMalformed)
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! BLAKE2b-512 hashing, as defined in RFC 7693.
+//!
+//! There is no BLAKE2b precompile, so the compression function runs in software.
+
+use core::convert::TryInto;
+
+/// The block size of BLAKE2b in bytes.
+const BLOCK_SIZE: usize = 128;
+
+/// The initialization vector, which is the one of SHA-512.
+const IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+/// The message word permutations of each round. The last two rounds reuse the first two.
+const SIGMA: [[usize; 16]; 12] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+];
+
+/// An incremental BLAKE2b hasher with a 64-byte digest and no key.
+#[derive(Clone)]
+pub struct Blake2b512 {
+    state: [u64; 8],
+    buf: [u8; BLOCK_SIZE],
+    buf_len: usize,
+    len: u128,
+}
+
+impl Blake2b512 {
+    /// Creates a new hasher with the initial state.
+    pub const fn new() -> Self {
+        let mut state = IV;
+        // The parameter block: a digest length of 64 bytes, no key, and sequential mode.
+        state[0] ^= 0x01010040;
+        Self {
+            state,
+            buf: [0; BLOCK_SIZE],
+            buf_len: 0,
+            len: 0,
+        }
+    }
+
+    /// Absorbs `data` into the hasher.
+    pub fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            // The last block is compressed differently, so a full block is only compressed once
+            // more data follows it.
+            if self.buf_len == BLOCK_SIZE {
+                self.len += BLOCK_SIZE as u128;
+                compress(&mut self.state, &self.buf, self.len, false);
+                self.buf_len = 0;
+            }
+            let take = (BLOCK_SIZE - self.buf_len).min(data.len());
+            self.buf[self.buf_len..self.buf_len + take].copy_from_slice(&data[..take]);
+            self.buf_len += take;
+            data = &data[take..];
+        }
+    }
+
+    /// Pads the remaining input and returns the digest.
+    pub fn finalize(mut self) -> [u8; 64] {
+        self.len += self.buf_len as u128;
+        self.buf[self.buf_len..].fill(0);
+        compress(&mut self.state, &self.buf, self.len, true);
+
+        let mut out = [0u8; 64];
+        for (chunk, word) in out.chunks_exact_mut(8).zip(self.state.iter()) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        out
+    }
+}
+
+impl Default for Blake2b512 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Computes the BLAKE2b-512 digest of `data`.
+pub fn blake2b512(data: &[u8]) -> [u8; 64] {
+    let mut hasher = Blake2b512::new();
+    hasher.update(data);
+    hasher.finalize()
+}
+
+/// The compression function `F`, where `len` is the number of bytes hashed so far including the
+/// block.
+fn compress(state: &mut [u64; 8], block: &[u8; BLOCK_SIZE], len: u128, last: bool) {
+    let mut m = [0u64; 16];
+    for (word, chunk) in m.iter_mut().zip(block.chunks_exact(8)) {
+        *word = u64::from_le_bytes(chunk.try_into().unwrap());
+    }
+
+    let mut v = [0u64; 16];
+    v[..8].copy_from_slice(state);
+    v[8..].copy_from_slice(&IV);
+    v[12] ^= len as u64;
+    v[13] ^= (len >> 64) as u64;
+    if last {
+        v[14] = !v[14];
+    }
+
+    for s in SIGMA.iter() {
+        mix(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+        mix(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+        mix(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+        mix(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+        mix(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+        mix(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+        mix(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+        mix(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+    }
+
+    for (i, word) in state.iter_mut().enumerate() {
+        *word ^= v[i] ^ v[i + 8];
+    }
+}
+
+/// The mixing function `G`.
+fn mix(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
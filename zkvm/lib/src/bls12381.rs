@@ -2,8 +2,14 @@
 use crate::utils::CurveOperations;
 use crate::{syscall_bls12381_add, syscall_bls12381_decompress, syscall_bls12381_double};
 
-use amcl::bls381::bls381::utils::deserialize_g1;
-use anyhow::Result;
+use amcl::bls381::bls381::proof_of_possession::verify_g2;
+use amcl::bls381::bls381::utils::{deserialize_g1, subgroup_check_g1};
+
+use crate::error::Error;
+use crate::sha256::{sha256, Sha256};
+
+pub mod compression;
+mod hash_to_curve;
 
 #[derive(Copy, Clone)]
 pub struct Bls12381;
@@ -35,7 +41,7 @@ impl CurveOperations<NUM_WORDS> for Bls12381 {
 }
 
 /// Decompresses a compressed public key using bls12381_decompress precompile.
-pub fn decompress_pubkey(compressed_key: &[u8; 48]) -> Result<[u8; 96]> {
+pub fn decompress_pubkey(compressed_key: &[u8; 48]) -> Result<[u8; 96], Error> {
     cfg_if::cfg_if! {
         if #[cfg(all(target_os = "zkvm", target_vendor = "succinct"))] {
             let mut decompressed_key = [0u8; 96];
@@ -49,11 +55,14 @@ pub fn decompress_pubkey(compressed_key: &[u8; 48]) -> Result<[u8; 96]> {
 
             Ok(decompressed_key)
         } else {
-            let point = deserialize_g1(compressed_key.as_slice()).unwrap();
+            use alloc::string::ToString;
+
+            let point =
+                deserialize_g1(compressed_key.as_slice()).map_err(|_| Error::InvalidPublicKey)?;
             let x = point.getx().to_string();
             let y = point.gety().to_string();
 
-            let decompressed_key = hex::decode(format!("{x}{y}")).unwrap();
+            let decompressed_key = hex::decode(alloc::format!("{x}{y}")).unwrap();
             let mut result = [0u8; 96];
             result.copy_from_slice(&decompressed_key);
 
@@ -61,3 +70,276 @@ pub fn decompress_pubkey(compressed_key: &[u8; 48]) -> Result<[u8; 96]> {
         }
     }
 }
+
+/// Hashes a message to a point of G1 with the `BLS12381G1_XMD:SHA-256_SSWU_RO_` suite of
+/// RFC 9380, returning the compressed point.
+///
+/// The `hash_to_curve` of the suite expands the message with `expand_message_xmd` over SHA-256,
+/// maps two field elements with the simplified SWU map and its isogeny, and clears the cofactor.
+/// SHA-256 runs on its precompiles, and the field arithmetic on [`crate::bigmod`], with hinted
+/// reductions, inversions and square roots. Returns [`Error::InvalidDomainSeparationTag`] if the
+/// tag is empty or longer than 255 bytes, as RFC 9380 requires longer tags to be hashed first.
+pub fn hash_to_g1(msg: &[u8], dst: &[u8]) -> Result<[u8; 48], Error> {
+    check_dst(dst)?;
+    Ok(compression::compress_g1(&hash_to_curve::g1(msg, dst)))
+}
+
+/// Hashes a message to a point of G2 with the `BLS12381G2_XMD:SHA-256_SSWU_RO_` suite of
+/// RFC 9380, the one of BLS signatures in Ethereum, returning the compressed point.
+///
+/// As with [`hash_to_g1`], the tag must be between 1 and 255 bytes long.
+pub fn hash_to_g2(msg: &[u8], dst: &[u8]) -> Result<[u8; 96], Error> {
+    check_dst(dst)?;
+    Ok(compression::compress_g2(&hash_to_curve::g2(msg, dst)))
+}
+
+fn check_dst(dst: &[u8]) -> Result<(), Error> {
+    if dst.is_empty() || dst.len() > 255 {
+        return Err(Error::InvalidDomainSeparationTag);
+    }
+    Ok(())
+}
+
+/// The `DOMAIN_DEPOSIT` domain type from the consensus specs.
+const DOMAIN_DEPOSIT: [u8; 4] = [3, 0, 0, 0];
+
+/// Verifies the BLS signature of a beacon-chain deposit.
+///
+/// The signing root is computed as in the consensus specs: the SSZ root of the `DepositMessage`
+/// mixed with the `DOMAIN_DEPOSIT` domain for the given genesis fork version. Deposits are signed
+/// with a zero genesis validators root, so that the signature is valid across forks.
+///
+/// Returns [`Error::InvalidPublicKey`] if the public key is not a valid G1 point (including the
+/// point at infinity), and `Ok(false)` if the signature does not verify.
+pub fn verify_deposit_signature(
+    pubkey: &[u8; 48],
+    withdrawal_credentials: &[u8; 32],
+    amount_gwei: u64,
+    signature: &[u8; 96],
+    genesis_fork_version: [u8; 4],
+) -> Result<bool, Error> {
+    // Reject invalid public keys before doing any pairing work.
+    let point = deserialize_g1(pubkey).map_err(|_| Error::InvalidPublicKey)?;
+    if point.is_infinity() || !subgroup_check_g1(&point) {
+        return Err(Error::InvalidPublicKey);
+    }
+
+    let message_root = deposit_message_root(pubkey, withdrawal_credentials, amount_gwei);
+    let domain = compute_domain(DOMAIN_DEPOSIT, genesis_fork_version, &[0u8; 32]);
+    let signing_root = hash_pair(&message_root, &domain);
+
+    Ok(verify_g2(pubkey, &signing_root, signature))
+}
+
+/// Computes the SSZ root of a `DepositMessage { pubkey, withdrawal_credentials, amount }`.
+fn deposit_message_root(
+    pubkey: &[u8; 48],
+    withdrawal_credentials: &[u8; 32],
+    amount_gwei: u64,
+) -> [u8; 32] {
+    // The 48-byte pubkey is packed into two chunks, the second one zero-padded.
+    let mut pubkey_chunks = [0u8; 64];
+    pubkey_chunks[..48].copy_from_slice(pubkey);
+    let pubkey_root = sha256(&pubkey_chunks);
+
+    let mut amount_chunk = [0u8; 32];
+    amount_chunk[..8].copy_from_slice(&amount_gwei.to_le_bytes());
+
+    // Three fields are merkleized as four leaves, the last one being zero.
+    hash_pair(
+        &hash_pair(&pubkey_root, withdrawal_credentials),
+        &hash_pair(&amount_chunk, &[0u8; 32]),
+    )
+}
+
+/// Computes the signature domain from the domain type, the fork version and the genesis
+/// validators root, as in `compute_domain` from the consensus specs.
+fn compute_domain(
+    domain_type: [u8; 4],
+    fork_version: [u8; 4],
+    genesis_validators_root: &[u8; 32],
+) -> [u8; 32] {
+    // The SSZ root of `ForkData { current_version, genesis_validators_root }`.
+    let mut version_chunk = [0u8; 32];
+    version_chunk[..4].copy_from_slice(&fork_version);
+    let fork_data_root = hash_pair(&version_chunk, genesis_validators_root);
+
+    let mut domain = [0u8; 32];
+    domain[..4].copy_from_slice(&domain_type);
+    domain[4..].copy_from_slice(&fork_data_root[..28]);
+    domain
+}
+
+/// Hashes two 32-byte chunks together into their parent node.
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use amcl::bls381::bls381::proof_of_possession::{
+        key_generate, secret_key_to_public_key_g2, sign_g2,
+    };
+    use amcl::bls381::bls381::utils::{
+        hash_to_curve_g1, hash_to_curve_g2, serialize_g1, serialize_g2,
+    };
+
+    #[test]
+    fn test_hash_to_curve() {
+        // The vectors of the empty message from RFC 9380, appendices J.9.1 and J.10.1, as
+        // compressed points.
+        assert_eq!(
+            hex::encode(
+                hash_to_g1(b"", b"QUUX-V01-CS02-with-BLS12381G1_XMD:SHA-256_SSWU_RO_").unwrap()
+            ),
+            concat!(
+                "852926add2207b76ca4fa57a8734416c8dc95e24501772c8",
+                "14278700eed6d1e4e8cf62d9c09db0fac349612b759e79a1"
+            )
+        );
+        assert_eq!(
+            hex::encode(
+                hash_to_g2(b"", b"QUUX-V01-CS02-with-BLS12381G2_XMD:SHA-256_SSWU_RO_").unwrap()
+            ),
+            concat!(
+                "a5cb8437535e20ecffaef7752baddf98034139c38452458b",
+                "aeefab379ba13dff5bf5dd71b72418717047f5b0f37da03d",
+                "0141ebfbdca40eb85b87142e130ab689c673cf60f1a3e98d",
+                "69335266f30d9b8d4ac44c1038e9dcdd5393faf5c41fb78a"
+            )
+        );
+
+        let dst = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+        assert_ne!(hash_to_g2(b"abc", dst), hash_to_g2(b"abd", dst));
+        assert_ne!(
+            hash_to_g2(b"abc", dst),
+            hash_to_g2(b"abc", b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_")
+        );
+        assert_eq!(
+            hash_to_g1(b"abc", b""),
+            Err(Error::InvalidDomainSeparationTag)
+        );
+        assert_eq!(
+            hash_to_g2(b"abc", &[b'A'; 256]),
+            Err(Error::InvalidDomainSeparationTag)
+        );
+        assert!(hash_to_g2(b"abc", &[b'A'; 255]).is_ok());
+    }
+
+    #[test]
+    fn test_hash_to_curve_matches_amcl() {
+        let dst = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+        for len in [1u8, 32, 100] {
+            let msg: Vec<u8> = (0..len).collect();
+            assert_eq!(
+                hash_to_g1(&msg, dst).unwrap(),
+                serialize_g1(&hash_to_curve_g1(&msg, dst))
+            );
+            assert_eq!(
+                hash_to_g2(&msg, dst).unwrap(),
+                serialize_g2(&hash_to_curve_g2(&msg, dst))
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_deposit_signature() {
+        // The deposit domain of mainnet, whose genesis fork version is zero, as published in the
+        // consensus specs and used by the deposit CLI.
+        let mainnet_domain = compute_domain(DOMAIN_DEPOSIT, [0; 4], &[0; 32]);
+        assert_eq!(
+            hex::encode(mainnet_domain),
+            "03000000f5a5fd42d16a20302798ef6ed309979b43003d2320d9f0e8ea9831a9"
+        );
+
+        // The roots of a `DepositMessage` and of its signing data, computed independently with
+        // the SHA-256 of Python's hashlib.
+        let mut credentials = [0x22; 32];
+        credentials[0] = 0x01;
+        credentials[1..12].fill(0);
+        let message_root = deposit_message_root(&[0xaa; 48], &credentials, 32_000_000_000);
+        assert_eq!(
+            hex::encode(message_root),
+            "d0bdb4eb5e1fc12410c3a9fa12b7b270a286fc7c3b323ff034f0cc4113d34dd7"
+        );
+        assert_eq!(
+            hex::encode(hash_pair(&message_root, &mainnet_domain)),
+            "f033d1207e0ce026994c56591b48990b06efad6e802b1261700728b264c4ea83"
+        );
+        let holesky_domain = compute_domain(DOMAIN_DEPOSIT, [0x01, 0x01, 0x70, 0x00], &[0; 32]);
+        assert_eq!(
+            hex::encode(hash_pair(&message_root, &holesky_domain)),
+            "20779fc64a5ec3a073470393d6cc8232f6e82c31d1a886820e7954c8cd800623"
+        );
+
+        let secret_key = key_generate(&[0x42; 32], b"");
+        let pubkey: [u8; 48] = secret_key_to_public_key_g2(&secret_key).unwrap();
+        let mut withdrawal_credentials = [0x11; 32];
+        withdrawal_credentials[0] = 0x01;
+        let amount_gwei = 32_000_000_000;
+        let signing_root = hash_pair(
+            &deposit_message_root(&pubkey, &withdrawal_credentials, amount_gwei),
+            &mainnet_domain,
+        );
+        let signature = sign_g2(&secret_key, &signing_root).unwrap();
+
+        let verify = |pubkey: &[u8; 48], amount_gwei, signature: &[u8; 96], fork_version| {
+            verify_deposit_signature(
+                pubkey,
+                &withdrawal_credentials,
+                amount_gwei,
+                signature,
+                fork_version,
+            )
+        };
+        assert_eq!(verify(&pubkey, amount_gwei, &signature, [0; 4]), Ok(true));
+
+        // The signature is over the fork version of mainnet, not of Holesky, and over the amount.
+        let holesky = [0x01, 0x01, 0x70, 0x00];
+        assert_eq!(verify(&pubkey, amount_gwei, &signature, holesky), Ok(false));
+        assert_eq!(
+            verify(&pubkey, 1_000_000_000, &signature, [0; 4]),
+            Ok(false)
+        );
+        let other_signature = sign_g2(&secret_key, &[0; 32]).unwrap();
+        assert_eq!(
+            verify(&pubkey, amount_gwei, &other_signature, [0; 4]),
+            Ok(false)
+        );
+
+        // The point at infinity, whose signatures are trivial, is rejected before any pairing.
+        let mut infinity = [0u8; 48];
+        infinity[0] = 0xc0;
+        assert_eq!(
+            verify(&infinity, amount_gwei, &signature, [0; 4]),
+            Err(Error::InvalidPublicKey)
+        );
+
+        // So are points of the curve outside of G1, and x-coordinates of no point.
+        let mut not_a_point = None;
+        let mut not_in_g1 = None;
+        for x in 1u8..=255 {
+            let mut candidate = [0u8; 48];
+            candidate[0] = 0x80;
+            candidate[47] = x;
+            match deserialize_g1(&candidate) {
+                Err(_) => not_a_point = not_a_point.or(Some(candidate)),
+                Ok(point) if !subgroup_check_g1(&point) => {
+                    not_in_g1 = not_in_g1.or(Some(candidate))
+                }
+                Ok(_) => {}
+            }
+        }
+        for pubkey in [not_a_point.unwrap(), not_in_g1.unwrap()] {
+            assert_eq!(
+                verify(&pubkey, amount_gwei, &signature, [0; 4]),
+                Err(Error::InvalidPublicKey)
+            );
+        }
+    }
+}
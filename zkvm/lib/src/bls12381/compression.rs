@@ -0,0 +1,499 @@
+//! Compression of G1 and G2 points with the flag bits of the ZCash serialization format, which
+//! the consensus specs, KZG commitments and BLS signatures use.
+//!
+//! The three most significant bits of the first byte are flags: compression, infinity, and the
+//! sign of `y`, set if `y` is the lexicographically largest of `y` and `-y`. The rest of a G1
+//! encoding is the big endian `x`, and the rest of a G2 encoding `x = x0 + x1 * u` is `x1`
+//! followed by `x0`. The point at infinity is encoded with the compression and infinity flags
+//! only, and zero bytes.
+//!
+//! Inside the zkVM, the square roots of the base field are requested from the square root hook and
+//! checked by squaring them. As `p = 3 mod 4`, -1 is not a quadratic residue, so a hint whose
+//! square is `-value` proves that `value` has no root. A hint that fails both checks is ignored,
+//! and the root is computed in the VM. The square roots of G2 coordinates are computed from those
+//! of the base field, with the complex method.
+
+use crate::error::Error;
+use crate::utils::hex;
+use amcl::bls381::big::Big;
+use amcl::bls381::bls381::utils::{subgroup_check_g1, subgroup_check_g2};
+use amcl::bls381::ecp::ECP;
+use amcl::bls381::ecp2::ECP2;
+use amcl::bls381::fp2::FP2;
+use core::convert::TryInto;
+
+/// The flag of compressed encodings.
+const COMPRESSION_FLAG: u8 = 0b1000_0000;
+
+/// The flag of the encoding of the point at infinity.
+const INFINITY_FLAG: u8 = 0b0100_0000;
+
+/// The flag set if `y` is the lexicographically largest of `y` and `-y`.
+const SIGN_FLAG: u8 = 0b0010_0000;
+
+/// The modulus p of the base field.
+const MODULUS: [u8; 48] = hex(concat!(
+    "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf",
+    "6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaab",
+));
+
+/// `(p + 1) / 4`, the exponent of square roots.
+const SQRT_EXPONENT: [u8; 48] = hex(concat!(
+    "0680447a8e5ff9a692c6e9ed90d2eb35d91dd2e13ce144af",
+    "d9cc34a83dac3d8907aaffffac54ffffee7fbfffffffeaab",
+));
+
+/// `p - 2`, the exponent of inversions.
+const MODULUS_MINUS_TWO: [u8; 48] = hex(concat!(
+    "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf",
+    "6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaa9",
+));
+
+/// `(p + 1) / 2`, the inverse of 2.
+const HALF: [u8; 48] = hex(concat!(
+    "0d0088f51cbff34d258dd3db21a5d66bb23ba5c279c2895f",
+    "b39869507b587b120f55ffff58a9ffffdcff7fffffffd556",
+));
+
+/// A point of the G1 subgroup, with big endian affine coordinates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum G1Point {
+    Infinity,
+    Affine { x: [u8; 48], y: [u8; 48] },
+}
+
+/// A point of the G2 subgroup, with affine coordinates `c0 + c1 * u` given as `[c0, c1]`, each
+/// big endian.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum G2Point {
+    Infinity,
+    Affine { x: [[u8; 48]; 2], y: [[u8; 48]; 2] },
+}
+
+/// Decompresses a G1 point.
+///
+/// Returns [`Error::InvalidPointEncoding`] if the compression flag is clear, if the encoding of
+/// the point at infinity has the sign flag or a nonzero byte, if `x` is not below the modulus, or
+/// if no point of the curve has this `x`. Returns [`Error::PointNotInSubgroup`] if the point is
+/// on the curve but not in G1.
+pub fn decompress_g1(bytes: &[u8; 48]) -> Result<G1Point, Error> {
+    let (flags, x) = split_flags(bytes);
+    if parse_infinity(flags, &x)? {
+        return Ok(G1Point::Infinity);
+    }
+
+    let x = parse_field_element(&x)?;
+    // y^2 = x^3 + 4.
+    let rhs = add(&mul(&sqr(&x), &x), &Big::new_int(4));
+    let mut y = sqrt(&rhs).ok_or(Error::InvalidPointEncoding)?;
+    if is_largest(&y) != (flags & SIGN_FLAG != 0) {
+        y = neg(&y);
+    }
+
+    if !subgroup_check_g1(&ECP::new_bigs(&x, &y)) {
+        return Err(Error::PointNotInSubgroup);
+    }
+    Ok(G1Point::Affine {
+        x: to_bytes(&x),
+        y: to_bytes(&y),
+    })
+}
+
+/// Decompresses a G2 point, rejecting invalid encodings like [`decompress_g1`].
+pub fn decompress_g2(bytes: &[u8; 96]) -> Result<G2Point, Error> {
+    let (flags, x) = split_flags(bytes);
+    if parse_infinity(flags, &x)? {
+        return Ok(G2Point::Infinity);
+    }
+
+    let x1 = parse_field_element(x[..48].try_into().unwrap())?;
+    let x0 = parse_field_element(x[48..].try_into().unwrap())?;
+    let x = (x0, x1);
+    // y^2 = x^3 + 4 * (1 + u).
+    let (c0, c1) = mul2(&mul2(&x, &x), &x);
+    let rhs = (add(&c0, &Big::new_int(4)), add(&c1, &Big::new_int(4)));
+    let (mut y0, mut y1) = sqrt2(&rhs).ok_or(Error::InvalidPointEncoding)?;
+    if is_largest2(&y0, &y1) != (flags & SIGN_FLAG != 0) {
+        (y0, y1) = (neg(&y0), neg(&y1));
+    }
+
+    let affine = G2Point::Affine {
+        x: [to_bytes(&x.0), to_bytes(&x.1)],
+        y: [to_bytes(&y0), to_bytes(&y1)],
+    };
+    let point = ECP2::new_fp2s(FP2::new_bigs(x.0, x.1), FP2::new_bigs(y0, y1));
+    if !subgroup_check_g2(&point) {
+        return Err(Error::PointNotInSubgroup);
+    }
+    Ok(affine)
+}
+
+/// Compresses a G1 point, the inverse of [`decompress_g1`].
+pub fn compress_g1(point: &G1Point) -> [u8; 48] {
+    match point {
+        G1Point::Infinity => infinity(),
+        G1Point::Affine { x, y } => {
+            let mut bytes = *x;
+            bytes[0] |= COMPRESSION_FLAG;
+            if is_largest(&Big::from_bytes(y)) {
+                bytes[0] |= SIGN_FLAG;
+            }
+            bytes
+        }
+    }
+}
+
+/// Compresses a G2 point, the inverse of [`decompress_g2`].
+pub fn compress_g2(point: &G2Point) -> [u8; 96] {
+    match point {
+        G2Point::Infinity => infinity(),
+        G2Point::Affine { x, y } => {
+            let mut bytes = [0u8; 96];
+            bytes[..48].copy_from_slice(&x[1]);
+            bytes[48..].copy_from_slice(&x[0]);
+            bytes[0] |= COMPRESSION_FLAG;
+            if is_largest2(&Big::from_bytes(&y[0]), &Big::from_bytes(&y[1])) {
+                bytes[0] |= SIGN_FLAG;
+            }
+            bytes
+        }
+    }
+}
+
+fn infinity<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    bytes[0] = COMPRESSION_FLAG | INFINITY_FLAG;
+    bytes
+}
+
+/// Splits an encoding into its flags and the encoding of `x`.
+fn split_flags<const N: usize>(bytes: &[u8; N]) -> (u8, [u8; N]) {
+    let mut x = *bytes;
+    x[0] &= !(COMPRESSION_FLAG | INFINITY_FLAG | SIGN_FLAG);
+    (bytes[0] & (COMPRESSION_FLAG | INFINITY_FLAG | SIGN_FLAG), x)
+}
+
+/// Returns whether the flags are the ones of the point at infinity, whose other bits must be zero.
+fn parse_infinity(flags: u8, x: &[u8]) -> Result<bool, Error> {
+    if flags & COMPRESSION_FLAG == 0 {
+        return Err(Error::InvalidPointEncoding);
+    }
+    if flags & INFINITY_FLAG == 0 {
+        return Ok(false);
+    }
+    if flags & SIGN_FLAG != 0 || x.iter().any(|&byte| byte != 0) {
+        return Err(Error::InvalidPointEncoding);
+    }
+    Ok(true)
+}
+
+/// Parses a big endian element of the base field, which must be below the modulus.
+fn parse_field_element(bytes: &[u8; 48]) -> Result<Big, Error> {
+    let element = Big::from_bytes(bytes);
+    if Big::comp(&element, &modulus()) >= 0 {
+        return Err(Error::InvalidPointEncoding);
+    }
+    Ok(element)
+}
+
+/// Returns whether `y` is larger than `-y`.
+fn is_largest(y: &Big) -> bool {
+    Big::comp(y, &neg(y)) > 0
+}
+
+/// Returns whether `y0 + y1 * u` is larger than its negation, comparing `y1` first.
+fn is_largest2(y0: &Big, y1: &Big) -> bool {
+    if is_zero(y1) {
+        is_largest(y0)
+    } else {
+        is_largest(y1)
+    }
+}
+
+/// Returns a square root of `a0 + a1 * u`, or `None` if it is not a square.
+///
+/// The element is a square if and only if its norm `a0^2 + a1^2` is a square of the base field.
+/// The root is then `x0 + x1 * u`, where `x0^2 = (a0 +- sqrt(a0^2 + a1^2)) / 2`, whichever of
+/// them has a root, and `x1 = a1 / (2 * x0)`.
+fn sqrt2(a: &(Big, Big)) -> Option<(Big, Big)> {
+    let (a0, a1) = a;
+    if is_zero(a1) {
+        // Either a0 or -a0 is a square, and (x1 * u)^2 = -x1^2.
+        return match sqrt(a0) {
+            Some(x0) => Some((x0, Big::new())),
+            None => sqrt(&neg(a0)).map(|x1| (Big::new(), x1)),
+        };
+    }
+
+    let norm = sqrt(&add(&sqr(a0), &sqr(a1)))?;
+    let half = Big::from_bytes(&HALF);
+    // x0 is nonzero, as x0 = 0 would make a1 zero.
+    let x0 =
+        sqrt(&mul(&add(a0, &norm), &half)).or_else(|| sqrt(&mul(&add(a0, &neg(&norm)), &half)))?;
+    let x1 = mul(a1, &invert(&add(&x0, &x0)));
+    Some((x0, x1))
+}
+
+/// Returns a square root of `value`, or `None` if it is not a quadratic residue.
+fn sqrt(value: &Big) -> Option<Big> {
+    #[cfg(all(
+        target_os = "zkvm",
+        target_vendor = "succinct",
+        not(feature = "no-hints")
+    ))]
+    {
+        use crate::io;
+        use crate::unconstrained;
+
+        let value_bytes = to_bytes(value);
+        unconstrained! {
+            let mut buf = [0u8; 96];
+            buf[..48].copy_from_slice(&MODULUS);
+            buf[48..].copy_from_slice(&value_bytes);
+            io::write(io::FD_SQRT_HOOK, &buf);
+        }
+
+        let hint = io::read_vec();
+        if let Ok(hint) = <[u8; 48]>::try_from(hint.as_slice()) {
+            if let Ok(root) = parse_field_element(&hint) {
+                let square = sqr(&root);
+                if Big::comp(&square, value) == 0 {
+                    return Some(root);
+                }
+                if Big::comp(&square, &neg(value)) == 0 {
+                    return None;
+                }
+            }
+        }
+    }
+
+    let root = pow(value, &SQRT_EXPONENT);
+    (Big::comp(&sqr(&root), value) == 0).then_some(root)
+}
+
+/// Inverts a nonzero element, as `a^(p - 2)`.
+fn invert(a: &Big) -> Big {
+    pow(a, &MODULUS_MINUS_TWO)
+}
+
+fn pow(a: &Big, exp: &[u8; 48]) -> Big {
+    let mut result = Big::new_int(1);
+    for byte in exp {
+        for bit in (0..8).rev() {
+            result = sqr(&result);
+            if (byte >> bit) & 1 == 1 {
+                result = mul(&result, a);
+            }
+        }
+    }
+    result
+}
+
+/// Multiplies elements `a0 + a1 * u` of the quadratic extension, where `u^2 = -1`.
+fn mul2(a: &(Big, Big), b: &(Big, Big)) -> (Big, Big) {
+    (
+        add(&mul(&a.0, &b.0), &neg(&mul(&a.1, &b.1))),
+        add(&mul(&a.0, &b.1), &mul(&a.1, &b.0)),
+    )
+}
+
+fn mul(a: &Big, b: &Big) -> Big {
+    Big::modmul(a, b, &modulus())
+}
+
+fn sqr(a: &Big) -> Big {
+    Big::modsqr(a, &modulus())
+}
+
+fn add(a: &Big, b: &Big) -> Big {
+    let mut sum = a.clone();
+    sum.add(b);
+    sum.rmod(&modulus());
+    sum
+}
+
+fn neg(a: &Big) -> Big {
+    let mut negation = Big::modneg(a, &modulus());
+    negation.rmod(&modulus());
+    negation
+}
+
+fn is_zero(a: &Big) -> bool {
+    Big::comp(a, &Big::new()) == 0
+}
+
+fn modulus() -> Big {
+    Big::from_bytes(&MODULUS)
+}
+
+/// Converts an element to big endian bytes, as amcl formats it in hex.
+fn to_bytes(a: &Big) -> [u8; 48] {
+    ::hex::decode(a.to_string()).unwrap().try_into().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amcl::bls381::bls381::utils::{serialize_g1, serialize_g2};
+
+    #[test]
+    fn test_bls12381_point_compression() {
+        let negate = |y: &[u8; 48]| to_bytes(&neg(&Big::from_bytes(y)));
+        let decode = |hex_str: &str| hex::decode(hex_str).unwrap();
+        let coordinate = |hex_str: &str| -> [u8; 48] { decode(hex_str).try_into().unwrap() };
+
+        // The generators, compressed as in the serialization tests of the consensus specs.
+        let g1: [u8; 48] = decode(concat!(
+            "97f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905",
+            "a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb"
+        ))
+        .try_into()
+        .unwrap();
+        let g2: [u8; 96] = decode(concat!(
+            "93e02b6052719f607dacd3a088274f65596bd0d09920b61a",
+            "b5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e",
+            "024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02",
+            "b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8"
+        ))
+        .try_into()
+        .unwrap();
+        let g1_point = G1Point::Affine {
+            x: coordinate(concat!(
+                "17f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905",
+                "a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb"
+            )),
+            y: coordinate(concat!(
+                "08b3f481e3aaa0f1a09e30ed741d8ae4fcf5e095d5d00af6",
+                "00db18cb2c04b3edd03cc744a2888ae40caa232946c5e7e1"
+            )),
+        };
+        assert_eq!(decompress_g1(&g1), Ok(g1_point));
+        let g2_point = G2Point::Affine {
+            x: [
+                coordinate(concat!(
+                    "024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02",
+                    "b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8"
+                )),
+                coordinate(concat!(
+                    "13e02b6052719f607dacd3a088274f65596bd0d09920b61a",
+                    "b5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e"
+                )),
+            ],
+            y: [
+                coordinate(concat!(
+                    "0ce5d527727d6e118cc9cdc6da2e351aadfd9baa8cbdd3a7",
+                    "6d429a695160d12c923ac9cc3baca289e193548608b82801"
+                )),
+                coordinate(concat!(
+                    "0606c4a02ea734cc32acd2b02bc28b99cb3e287e85a763af",
+                    "267492ab572e99ab3f370d275cec1da1aaa9075ff05f79be"
+                )),
+            ],
+        };
+        assert_eq!(decompress_g2(&g2), Ok(g2_point));
+
+        // Multiples of the generators round trip, and the sign flag selects the negation.
+        for k in 1..=8 {
+            let point = ECP::generator().mul(&Big::new_int(k));
+            let bytes = serialize_g1(&point);
+            let decompressed = decompress_g1(&bytes).unwrap();
+            let G1Point::Affine { x, y } = decompressed else {
+                panic!("decompressed the point at infinity");
+            };
+            assert_eq!(hex::encode(x), point.getx().to_string().to_lowercase());
+            assert_eq!(hex::encode(y), point.gety().to_string().to_lowercase());
+            assert_eq!(compress_g1(&decompressed), bytes);
+            let mut negated = bytes;
+            negated[0] ^= 0b0010_0000;
+            assert_eq!(
+                decompress_g1(&negated),
+                Ok(G1Point::Affine { x, y: negate(&y) })
+            );
+
+            let bytes = serialize_g2(&ECP2::generator().mul(&Big::new_int(k)));
+            let decompressed = decompress_g2(&bytes).unwrap();
+            assert_eq!(compress_g2(&decompressed), bytes);
+            let G2Point::Affine { x, y } = decompressed else {
+                panic!("decompressed the point at infinity");
+            };
+            let mut negated = bytes;
+            negated[0] ^= 0b0010_0000;
+            assert_eq!(
+                decompress_g2(&negated),
+                Ok(G2Point::Affine {
+                    x,
+                    y: [negate(&y[0]), negate(&y[1])]
+                })
+            );
+        }
+
+        // The point at infinity, and encodings with invalid flags.
+        let mut infinity = [0u8; 96];
+        infinity[0] = 0xc0;
+        assert_eq!(
+            decompress_g1(infinity[..48].try_into().unwrap()),
+            Ok(G1Point::Infinity)
+        );
+        assert_eq!(decompress_g2(&infinity), Ok(G2Point::Infinity));
+        assert_eq!(compress_g1(&G1Point::Infinity)[..], infinity[..48]);
+        assert_eq!(compress_g2(&G2Point::Infinity), infinity);
+        let mut g1_uncompressed_flag = g1;
+        g1_uncompressed_flag[0] &= 0x7f;
+        let mut infinity_with_sign = infinity;
+        infinity_with_sign[0] |= 0x20;
+        let mut infinity_with_x = infinity;
+        infinity_with_x[47] = 1;
+        infinity_with_x[95] = 1;
+        for bytes in [g1_uncompressed_flag, [0u8; 48]] {
+            assert_eq!(decompress_g1(&bytes), Err(Error::InvalidPointEncoding));
+        }
+        for bytes in [infinity_with_sign, infinity_with_x] {
+            assert_eq!(
+                decompress_g1(bytes[..48].try_into().unwrap()),
+                Err(Error::InvalidPointEncoding)
+            );
+            assert_eq!(decompress_g2(&bytes), Err(Error::InvalidPointEncoding));
+        }
+        let mut g2_uncompressed_flag = g2;
+        g2_uncompressed_flag[0] &= 0x7f;
+        assert_eq!(
+            decompress_g2(&g2_uncompressed_flag),
+            Err(Error::InvalidPointEncoding)
+        );
+
+        // Coordinates that are not below the modulus.
+        let mut x_is_modulus = MODULUS;
+        x_is_modulus[0] |= 0x80;
+        assert_eq!(
+            decompress_g1(&x_is_modulus),
+            Err(Error::InvalidPointEncoding)
+        );
+        let mut x1_is_modulus = g2;
+        x1_is_modulus[..48].copy_from_slice(&x_is_modulus);
+        let mut x0_is_modulus = g2;
+        x0_is_modulus[48..].copy_from_slice(&MODULUS);
+        for bytes in [x1_is_modulus, x0_is_modulus] {
+            assert_eq!(decompress_g2(&bytes), Err(Error::InvalidPointEncoding));
+        }
+
+        // No point has x = 1, and the points with x = 4 in G1, and x = 2 in G2, are not in the
+        // subgroups.
+        let mut x = [0u8; 96];
+        x[0] = 0x80;
+        x[47] = 1;
+        assert_eq!(
+            decompress_g1(x[..48].try_into().unwrap()),
+            Err(Error::InvalidPointEncoding)
+        );
+        x[47] = 4;
+        assert_eq!(
+            decompress_g1(x[..48].try_into().unwrap()),
+            Err(Error::PointNotInSubgroup)
+        );
+        x[47] = 0;
+        x[95] = 1;
+        assert_eq!(decompress_g2(&x), Err(Error::InvalidPointEncoding));
+        x[95] = 2;
+        assert_eq!(decompress_g2(&x), Err(Error::PointNotInSubgroup));
+    }
+}
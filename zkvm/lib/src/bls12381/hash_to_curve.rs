@@ -0,0 +1,1361 @@
+//! Hashing to G1 and G2 with the `BLS12381G1_XMD:SHA-256_SSWU_RO_` and
+//! `BLS12381G2_XMD:SHA-256_SSWU_RO_` suites of RFC 9380.
+//!
+//! The message is expanded with `expand_message_xmd` over [`crate::sha256`], which runs on the
+//! sha256 precompiles inside the zkVM. The field arithmetic runs on [`BigMod`], whose products use
+//! the uint256 precompile and whose reductions and inversions are hinted and checked. Inside the
+//! zkVM, square roots are requested from the square root hook and checked by squaring them, as in
+//! [`super::compression`].
+//!
+//! Each field element is mapped with the simplified SWU map to a curve isogenous to the target
+//! curve, and sent to the target curve with the isogeny of the suite. Points are added and
+//! multiplied in Jacobian coordinates, so that the only inversions are those of the map and the
+//! final one.
+
+use super::compression::{G1Point, G2Point};
+use crate::bigmod::BigMod;
+use crate::sha256::Sha256;
+use crate::utils::hex;
+use alloc::vec::Vec;
+use core::convert::TryInto;
+
+/// A big endian element of the base field.
+type Fp = [u8; 48];
+
+/// An element `c0 + c1 * u` of the quadratic extension, where `u^2 = -1`.
+type Fp2 = [Fp; 2];
+
+/// The modulus p of the base field.
+const MODULUS: Fp = hex(concat!(
+    "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf",
+    "6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaab"
+));
+
+/// `(p + 1) / 4`, the exponent of square roots.
+const SQRT_EXPONENT: Fp = hex(concat!(
+    "0680447a8e5ff9a692c6e9ed90d2eb35d91dd2e13ce144af",
+    "d9cc34a83dac3d8907aaffffac54ffffee7fbfffffffeaab"
+));
+
+/// `(p + 1) / 2`, the inverse of 2.
+const HALF: Fp = hex(concat!(
+    "0d0088f51cbff34d258dd3db21a5d66bb23ba5c279c2895f",
+    "b39869507b587b120f55ffff58a9ffffdcff7fffffffd556"
+));
+
+/// The absolute value of the parameter `x` of BLS12-381, which is negative.
+const X: u64 = 0xd201000000010000;
+
+/// The effective cofactor of G1, `1 - x`.
+const H_EFF_G1: u64 = 0xd201000000010001;
+
+/// The suite of G1, on the curve `y^2 = x^3 + A' * x + B'` that is 11-isogenous to E1.
+const G1: Suite<Base> = Suite {
+    a: hex(concat!(
+        "00144698a3b8e9433d693a02c96d4982b0ea985383ee66a8",
+        "d8e8981aefd881ac98936f8da0e0f97f5cf428082d584c1d"
+    )),
+    b: hex(concat!(
+        "12e2908d11688030018b12e8753eee3b2016c1f0f24f4070",
+        "a0b9c14fcef35ef55a23215a316ceaa5d1cc48e98e172be0"
+    )),
+    z: small(11),
+    x_num: &G1_XNUM,
+    x_den: &G1_XDEN,
+    y_num: &G1_YNUM,
+    y_den: &G1_YDEN,
+};
+
+/// The suite of G2, on the curve `y^2 = x^3 + 240 * u * x + 1012 * (1 + u)` that is 3-isogenous
+/// to E2.
+const G2: Suite<Quadratic> = Suite {
+    a: [small(0), small(240)],
+    b: [small(1012), small(1012)],
+    // Z = -(2 + u).
+    z: [
+        hex(concat!(
+            "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf",
+            "6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaa9"
+        )),
+        hex(concat!(
+            "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf",
+            "6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaaa"
+        )),
+    ],
+    x_num: &G2_XNUM,
+    x_den: &G2_XDEN,
+    y_num: &G2_YNUM,
+    y_den: &G2_YDEN,
+};
+
+/// `1 / (1 + u)^((p - 1) / 3)`, which multiplies the conjugated x-coordinate in the endomorphism
+/// psi of E2.
+const PSI_X: Fp2 = [
+    small(0),
+    hex(concat!(
+        "1a0111ea397fe699ec02408663d4de85aa0d857d89759ad4",
+        "897d29650fb85f9b409427eb4f49fffd8bfd00000000aaad"
+    )),
+];
+
+/// `1 / (1 + u)^((p - 1) / 2)`, which multiplies the conjugated y-coordinate in psi.
+const PSI_Y: Fp2 = [
+    hex(concat!(
+        "135203e60180a68ee2e9c448d77a2cd91c3dedd930b1cf60",
+        "ef396489f61eb45e304466cf3e67fa0af1ee7b04121bdea2"
+    )),
+    hex(concat!(
+        "06af0e0437ff400b6831e36d6bd17ffe48395dabc2d3435e",
+        "77f76e17009241c5ee67992f72ec05f4c81084fbede3cc09"
+    )),
+];
+
+/// Hashes a message to a point of G1, once the domain separation tag has been checked.
+pub(super) fn g1(msg: &[u8], dst: &[u8]) -> G1Point {
+    let field = Base::new();
+    let u = hash_to_field(&field, msg, dst, 2);
+    let q0 = map_to_curve(&field, &G1, &u[0]);
+    let q1 = map_to_curve(&field, &G1, &u[1]);
+    match q0.add(&field, &q1).mul(&field, H_EFF_G1).to_affine(&field) {
+        Some((x, y)) => G1Point::Affine { x, y },
+        None => G1Point::Infinity,
+    }
+}
+
+/// Hashes a message to a point of G2, once the domain separation tag has been checked.
+pub(super) fn g2(msg: &[u8], dst: &[u8]) -> G2Point {
+    let field = Quadratic(Base::new());
+    let u = hash_to_field(&field.0, msg, dst, 4);
+    let q0 = map_to_curve(&field, &G2, &[u[0], u[1]]);
+    let q1 = map_to_curve(&field, &G2, &[u[2], u[3]]);
+    match clear_cofactor_g2(&field, &q0.add(&field, &q1)).to_affine(&field) {
+        Some((x, y)) => G2Point::Affine { x, y },
+        None => G2Point::Infinity,
+    }
+}
+
+/// Hashes a message to `count` elements of the base field, reducing 64 bytes of
+/// `expand_message_xmd` for each of them.
+fn hash_to_field(field: &Base, msg: &[u8], dst: &[u8], count: usize) -> Vec<Fp> {
+    expand_message_xmd(msg, dst, 64 * count)
+        .chunks_exact(64)
+        .map(|chunk| element(field.0.add(chunk, &[0])))
+        .collect()
+}
+
+/// Expands a message to `len` bytes with SHA-256, for tags of at most 255 bytes and lengths of at
+/// most 255 blocks.
+fn expand_message_xmd(msg: &[u8], dst: &[u8], len: usize) -> Vec<u8> {
+    let hash = |parts: &[&[u8]]| {
+        let mut hasher = Sha256::new();
+        for part in parts {
+            hasher.update(part);
+        }
+        // DST' is the tag followed by its length.
+        hasher.update(dst);
+        hasher.update(&[dst.len() as u8]);
+        hasher.finalize()
+    };
+
+    // The message is prefixed with a zero block, and followed by the length and a zero byte.
+    let b0 = hash(&[&[0; 64], msg, &(len as u16).to_be_bytes(), &[0]]);
+    let mut out = Vec::with_capacity(len + 31);
+    let mut b = [0u8; 32];
+    for i in 1..=(len + 31) / 32 {
+        // b_1 = H(b_0 || 1 || DST'), and b_i = H((b_0 xor b_(i - 1)) || i || DST').
+        for (byte, b0_byte) in b.iter_mut().zip(b0) {
+            *byte ^= b0_byte;
+        }
+        b = hash(&[&b, &[i as u8]]);
+        out.extend_from_slice(&b);
+    }
+    out.truncate(len);
+    out
+}
+
+/// Maps a field element to the target curve, with the simplified SWU map to the isogenous curve
+/// followed by the isogeny.
+fn map_to_curve<F: Field>(field: &F, suite: &Suite<F>, u: &F::Element) -> Point<F> {
+    let curve = |x: &F::Element| {
+        let x3 = field.mul(&field.sqr(x), x);
+        field.add(&field.add(&x3, &field.mul(&suite.a, x)), &suite.b)
+    };
+
+    let zu2 = field.mul(&suite.z, &field.sqr(u));
+    let tv1 = field.add(&field.sqr(&zu2), &zu2);
+    // x1 = -B / A * (1 + 1 / tv1), or B / (Z * A) in the exceptional case tv1 = 0.
+    let x1 = if tv1 == F::ZERO {
+        field.mul(&suite.b, &field.inv0(&field.mul(&suite.z, &suite.a)))
+    } else {
+        let minus_b_over_a = field.neg(&field.mul(&suite.b, &field.inv0(&suite.a)));
+        field.mul(&minus_b_over_a, &field.add(&F::ONE, &field.inv0(&tv1)))
+    };
+    let (x, y) = match field.sqrt(&curve(&x1)) {
+        Some(y) => (x1, y),
+        None => {
+            // g(x2) = Z^3 * u^6 * g(x1) is a square, as Z is not.
+            let x2 = field.mul(&zu2, &x1);
+            (x2, field.sqrt(&curve(&x2)).unwrap())
+        }
+    };
+    let y = if field.sgn0(u) == field.sgn0(&y) {
+        y
+    } else {
+        field.neg(&y)
+    };
+
+    // The isogeny maps the points whose denominators vanish to the point at infinity.
+    let x_den = horner(field, suite.x_den, &x);
+    let y_den = horner(field, suite.y_den, &x);
+    if x_den == F::ZERO || y_den == F::ZERO {
+        return Point::INFINITY;
+    }
+    Point {
+        x: field.mul(&horner(field, suite.x_num, &x), &field.inv0(&x_den)),
+        y: field.mul(
+            &field.mul(&y, &horner(field, suite.y_num, &x)),
+            &field.inv0(&y_den),
+        ),
+        z: F::ONE,
+    }
+}
+
+/// Evaluates a polynomial given by its coefficients from the constant term.
+fn horner<F: Field>(field: &F, coefficients: &[F::Element], x: &F::Element) -> F::Element {
+    coefficients
+        .iter()
+        .rev()
+        .fold(F::ZERO, |acc, c| field.add(&field.mul(&acc, x), c))
+}
+
+/// Multiplies a point of E2 by the effective cofactor of G2, following appendix G.3 of RFC 9380:
+/// `h_eff * P = (x^2 - x - 1) * P + (x - 1) * psi(P) + psi^2(2 * P)`.
+fn clear_cofactor_g2(field: &Quadratic, p: &Point<Quadratic>) -> Point<Quadratic> {
+    // Multiplies by the negative x.
+    let mul_by_x = |p: &Point<Quadratic>| p.mul(field, X).neg(field);
+
+    let t1 = mul_by_x(p);
+    let t2 = psi(field, p);
+    let t3 = psi(field, &psi(field, &p.double(field))).add(field, &t2.neg(field));
+    let t2 = mul_by_x(&t1.add(field, &t2));
+    let t3 = t3.add(field, &t2).add(field, &t1.neg(field));
+    t3.add(field, &p.neg(field))
+}
+
+/// The endomorphism `psi(x, y) = (conj(x) * PSI_X, conj(y) * PSI_Y)` of E2, where `conj` is the
+/// Frobenius map of the quadratic extension. As `conj` is a field automorphism, it is applied to
+/// Jacobian coordinates directly.
+fn psi(field: &Quadratic, p: &Point<Quadratic>) -> Point<Quadratic> {
+    Point {
+        x: field.mul(&field.conjugate(&p.x), &PSI_X),
+        y: field.mul(&field.conjugate(&p.y), &PSI_Y),
+        z: field.conjugate(&p.z),
+    }
+}
+
+/// The parameters of a suite: the curve `y^2 = x^3 + a * x + b` of the simplified SWU map, its
+/// non-square `z`, and the rational maps of the isogeny to the target curve.
+struct Suite<F: Field> {
+    a: F::Element,
+    b: F::Element,
+    z: F::Element,
+    x_num: &'static [F::Element],
+    x_den: &'static [F::Element],
+    y_num: &'static [F::Element],
+    y_den: &'static [F::Element],
+}
+
+/// A point of a curve `y^2 = x^3 + b` in Jacobian coordinates, `(x / z^2, y / z^3)`, or the
+/// point at infinity if `z` is zero.
+struct Point<F: Field> {
+    x: F::Element,
+    y: F::Element,
+    z: F::Element,
+}
+
+impl<F: Field> Clone for Point<F> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<F: Field> Copy for Point<F> {}
+
+impl<F: Field> Point<F> {
+    const INFINITY: Self = Point {
+        x: F::ONE,
+        y: F::ONE,
+        z: F::ZERO,
+    };
+
+    fn is_infinity(&self) -> bool {
+        self.z == F::ZERO
+    }
+
+    fn neg(&self, field: &F) -> Self {
+        Point {
+            x: self.x,
+            y: field.neg(&self.y),
+            z: self.z,
+        }
+    }
+
+    /// Doubles the point, with the `dbl-2009-l` formulas for curves with `a = 0`.
+    fn double(&self, field: &F) -> Self {
+        if self.is_infinity() {
+            return Self::INFINITY;
+        }
+        let a = field.sqr(&self.x);
+        let b = field.sqr(&self.y);
+        let c = field.sqr(&b);
+        let d = field.sub(&field.sub(&field.sqr(&field.add(&self.x, &b)), &a), &c);
+        let d = field.add(&d, &d);
+        let e = field.add(&field.add(&a, &a), &a);
+        let x = field.sub(&field.sqr(&e), &field.add(&d, &d));
+        let c8 = field.add(&c, &c);
+        let c8 = field.add(&c8, &c8);
+        let c8 = field.add(&c8, &c8);
+        Point {
+            x,
+            y: field.sub(&field.mul(&e, &field.sub(&d, &x)), &c8),
+            z: field.mul(&field.add(&self.y, &self.y), &self.z),
+        }
+    }
+
+    /// Adds two points, with the `add-2007-bl` formulas.
+    fn add(&self, field: &F, other: &Self) -> Self {
+        if self.is_infinity() {
+            return *other;
+        }
+        if other.is_infinity() {
+            return *self;
+        }
+        let z1z1 = field.sqr(&self.z);
+        let z2z2 = field.sqr(&other.z);
+        let u1 = field.mul(&self.x, &z2z2);
+        let u2 = field.mul(&other.x, &z1z1);
+        let s1 = field.mul(&field.mul(&self.y, &other.z), &z2z2);
+        let s2 = field.mul(&field.mul(&other.y, &self.z), &z1z1);
+        if u1 == u2 {
+            return if s1 == s2 {
+                self.double(field)
+            } else {
+                Self::INFINITY
+            };
+        }
+        let h = field.sub(&u2, &u1);
+        let i = field.sqr(&field.add(&h, &h));
+        let j = field.mul(&h, &i);
+        let r = field.sub(&s2, &s1);
+        let r = field.add(&r, &r);
+        let v = field.mul(&u1, &i);
+        let x = field.sub(&field.sub(&field.sqr(&r), &j), &field.add(&v, &v));
+        let s1j = field.mul(&s1, &j);
+        let z = field.sub(
+            &field.sub(&field.sqr(&field.add(&self.z, &other.z)), &z1z1),
+            &z2z2,
+        );
+        Point {
+            x,
+            y: field.sub(&field.mul(&r, &field.sub(&v, &x)), &field.add(&s1j, &s1j)),
+            z: field.mul(&z, &h),
+        }
+    }
+
+    /// Multiplies the point by a scalar, with double and add.
+    fn mul(&self, field: &F, k: u64) -> Self {
+        (0..64 - k.leading_zeros())
+            .rev()
+            .fold(Self::INFINITY, |acc, i| {
+                let acc = acc.double(field);
+                if (k >> i) & 1 == 1 {
+                    acc.add(field, self)
+                } else {
+                    acc
+                }
+            })
+    }
+
+    /// Returns the affine coordinates of the point, or `None` for the point at infinity.
+    fn to_affine(self, field: &F) -> Option<(F::Element, F::Element)> {
+        if self.is_infinity() {
+            return None;
+        }
+        let z_inv = field.inv0(&self.z);
+        let z_inv2 = field.sqr(&z_inv);
+        Some((
+            field.mul(&self.x, &z_inv2),
+            field.mul(&self.y, &field.mul(&z_inv2, &z_inv)),
+        ))
+    }
+}
+
+/// The operations of the base field and of its quadratic extension that the map and the point
+/// arithmetic use.
+trait Field {
+    type Element: Copy + PartialEq + 'static;
+
+    const ZERO: Self::Element;
+    const ONE: Self::Element;
+
+    fn add(&self, a: &Self::Element, b: &Self::Element) -> Self::Element;
+
+    fn sub(&self, a: &Self::Element, b: &Self::Element) -> Self::Element;
+
+    fn mul(&self, a: &Self::Element, b: &Self::Element) -> Self::Element;
+
+    /// Returns the inverse of `a`, or zero if `a` is zero.
+    fn inv0(&self, a: &Self::Element) -> Self::Element;
+
+    /// Returns a square root of `a`, or `None` if it is not a square.
+    fn sqrt(&self, a: &Self::Element) -> Option<Self::Element>;
+
+    /// Returns the sign of `a`, as defined by RFC 9380.
+    fn sgn0(&self, a: &Self::Element) -> bool;
+
+    fn sqr(&self, a: &Self::Element) -> Self::Element {
+        self.mul(a, a)
+    }
+
+    fn neg(&self, a: &Self::Element) -> Self::Element {
+        self.sub(&Self::ZERO, a)
+    }
+}
+
+/// The base field, on top of [`BigMod`].
+struct Base(BigMod<'static>);
+
+impl Base {
+    fn new() -> Self {
+        Self(BigMod::new(&MODULUS).unwrap())
+    }
+}
+
+impl Field for Base {
+    type Element = Fp;
+
+    const ZERO: Fp = small(0);
+    const ONE: Fp = small(1);
+
+    fn add(&self, a: &Fp, b: &Fp) -> Fp {
+        element(self.0.add(a, b))
+    }
+
+    fn sub(&self, a: &Fp, b: &Fp) -> Fp {
+        element(self.0.sub(a, b))
+    }
+
+    fn mul(&self, a: &Fp, b: &Fp) -> Fp {
+        element(self.0.mul(a, b))
+    }
+
+    fn inv0(&self, a: &Fp) -> Fp {
+        // The modulus is prime, so only zero has no inverse.
+        self.0.inv(a).map_or(Self::ZERO, element)
+    }
+
+    fn sqrt(&self, a: &Fp) -> Option<Fp> {
+        #[cfg(all(
+            target_os = "zkvm",
+            target_vendor = "succinct",
+            not(feature = "no-hints")
+        ))]
+        {
+            use crate::io;
+            use crate::unconstrained;
+
+            unconstrained! {
+                let mut buf = [0u8; 96];
+                buf[..48].copy_from_slice(&MODULUS);
+                buf[48..].copy_from_slice(a);
+                io::write(io::FD_SQRT_HOOK, &buf);
+            }
+
+            // As -1 is not a square, a hint whose square is -a proves that a has no root.
+            if let Ok(root) = Fp::try_from(io::read_vec().as_slice()) {
+                if root < MODULUS {
+                    let square = self.sqr(&root);
+                    if square == *a {
+                        return Some(root);
+                    }
+                    if square == self.neg(a) {
+                        return None;
+                    }
+                }
+            }
+        }
+
+        let root = element(self.0.pow(a, &SQRT_EXPONENT));
+        (self.sqr(&root) == *a).then_some(root)
+    }
+
+    fn sgn0(&self, a: &Fp) -> bool {
+        a[47] & 1 == 1
+    }
+}
+
+/// The quadratic extension of the base field by `u^2 = -1`.
+struct Quadratic(Base);
+
+impl Quadratic {
+    fn conjugate(&self, a: &Fp2) -> Fp2 {
+        [a[0], self.0.neg(&a[1])]
+    }
+}
+
+impl Field for Quadratic {
+    type Element = Fp2;
+
+    const ZERO: Fp2 = [small(0), small(0)];
+    const ONE: Fp2 = [small(1), small(0)];
+
+    fn add(&self, a: &Fp2, b: &Fp2) -> Fp2 {
+        [self.0.add(&a[0], &b[0]), self.0.add(&a[1], &b[1])]
+    }
+
+    fn sub(&self, a: &Fp2, b: &Fp2) -> Fp2 {
+        [self.0.sub(&a[0], &b[0]), self.0.sub(&a[1], &b[1])]
+    }
+
+    fn mul(&self, a: &Fp2, b: &Fp2) -> Fp2 {
+        // Karatsuba: (a0 + a1 * u) * (b0 + b1 * u) = a0 * b0 - a1 * b1
+        // + ((a0 + a1) * (b0 + b1) - a0 * b0 - a1 * b1) * u.
+        let fp = &self.0;
+        let v0 = fp.mul(&a[0], &b[0]);
+        let v1 = fp.mul(&a[1], &b[1]);
+        let sum = fp.mul(&fp.add(&a[0], &a[1]), &fp.add(&b[0], &b[1]));
+        [fp.sub(&v0, &v1), fp.sub(&fp.sub(&sum, &v0), &v1)]
+    }
+
+    fn inv0(&self, a: &Fp2) -> Fp2 {
+        // 1 / a = conj(a) / (a0^2 + a1^2).
+        let fp = &self.0;
+        let norm_inv = fp.inv0(&fp.add(&fp.sqr(&a[0]), &fp.sqr(&a[1])));
+        let conjugate = self.conjugate(a);
+        [
+            fp.mul(&conjugate[0], &norm_inv),
+            fp.mul(&conjugate[1], &norm_inv),
+        ]
+    }
+
+    /// Returns a square root with the complex method, as `decompress_g2` does.
+    ///
+    /// The element is a square if and only if its norm `a0^2 + a1^2` is a square of the base
+    /// field. The root is then `x0 + x1 * u`, where `x0^2 = (a0 +- sqrt(a0^2 + a1^2)) / 2`,
+    /// whichever of them has a root, and `x1 = a1 / (2 * x0)`.
+    fn sqrt(&self, a: &Fp2) -> Option<Fp2> {
+        let fp = &self.0;
+        let [a0, a1] = a;
+        if *a1 == Base::ZERO {
+            // Either a0 or -a0 is a square, and (x1 * u)^2 = -x1^2.
+            return match fp.sqrt(a0) {
+                Some(x0) => Some([x0, Base::ZERO]),
+                None => fp.sqrt(&fp.neg(a0)).map(|x1| [Base::ZERO, x1]),
+            };
+        }
+
+        let norm = fp.sqrt(&fp.add(&fp.sqr(a0), &fp.sqr(a1)))?;
+        // x0 is nonzero, as x0 = 0 would make a1 zero.
+        let x0 = fp
+            .sqrt(&fp.mul(&fp.add(a0, &norm), &HALF))
+            .or_else(|| fp.sqrt(&fp.mul(&fp.sub(a0, &norm), &HALF)))?;
+        let x1 = fp.mul(a1, &fp.inv0(&fp.add(&x0, &x0)));
+        Some([x0, x1])
+    }
+
+    fn sgn0(&self, a: &Fp2) -> bool {
+        self.0.sgn0(&a[0]) || (a[0] == Base::ZERO && self.0.sgn0(&a[1]))
+    }
+}
+
+fn element(bytes: Vec<u8>) -> Fp {
+    bytes.try_into().unwrap()
+}
+
+/// Returns a small element of the base field.
+const fn small(value: u16) -> Fp {
+    let mut element = [0u8; 48];
+    element[46] = (value >> 8) as u8;
+    element[47] = value as u8;
+    element
+}
+
+/// The coefficients of `x_num` of the 11-isogeny, from the constant term.
+const G1_XNUM: [Fp; 12] = [
+    hex(concat!(
+        "11a05f2b1e833340b809101dd99815856b303e88a2d7005f",
+        "f2627b56cdb4e2c85610c2d5f2e62d6eaeac1662734649b7"
+    )),
+    hex(concat!(
+        "17294ed3e943ab2f0588bab22147a81c7c17e75b2f6a8417",
+        "f565e33c70d1e86b4838f2a6f318c356e834eef1b3cb83bb"
+    )),
+    hex(concat!(
+        "0d54005db97678ec1d1048c5d10a9a1bce032473295983e5",
+        "6878e501ec68e25c958c3e3d2a09729fe0179f9dac9edcb0"
+    )),
+    hex(concat!(
+        "1778e7166fcc6db74e0609d307e55412d7f5e4656a8dbf25",
+        "f1b33289f1b330835336e25ce3107193c5b388641d9b6861"
+    )),
+    hex(concat!(
+        "0e99726a3199f4436642b4b3e4118e5499db995a1257fb3f",
+        "086eeb65982fac18985a286f301e77c451154ce9ac8895d9"
+    )),
+    hex(concat!(
+        "1630c3250d7313ff01d1201bf7a74ab5db3cb17dd952799b",
+        "9ed3ab9097e68f90a0870d2dcae73d19cd13c1c66f652983"
+    )),
+    hex(concat!(
+        "0d6ed6553fe44d296a3726c38ae652bfb11586264f0f8ce1",
+        "9008e218f9c86b2a8da25128c1052ecaddd7f225a139ed84"
+    )),
+    hex(concat!(
+        "17b81e7701abdbe2e8743884d1117e53356de5ab275b4db1",
+        "a682c62ef0f2753339b7c8f8c8f475af9ccb5618e3f0c88e"
+    )),
+    hex(concat!(
+        "080d3cf1f9a78fc47b90b33563be990dc43b756ce79f5574",
+        "a2c596c928c5d1de4fa295f296b74e956d71986a8497e317"
+    )),
+    hex(concat!(
+        "169b1f8e1bcfa7c42e0c37515d138f22dd2ecb803a0c5c99",
+        "676314baf4bb1b7fa3190b2edc0327797f241067be390c9e"
+    )),
+    hex(concat!(
+        "10321da079ce07e272d8ec09d2565b0dfa7dccdde6787f96",
+        "d50af36003b14866f69b771f8c285decca67df3f1605fb7b"
+    )),
+    hex(concat!(
+        "06e08c248e260e70bd1e962381edee3d31d79d7e22c837bc",
+        "23c0bf1bc24c6b68c24b1b80b64d391fa9c8ba2e8ba2d229"
+    )),
+];
+/// The coefficients of `x_den` of the 11-isogeny, from the constant term.
+const G1_XDEN: [Fp; 11] = [
+    hex(concat!(
+        "08ca8d548cff19ae18b2e62f4bd3fa6f01d5ef4ba35b48ba",
+        "9c9588617fc8ac62b558d681be343df8993cf9fa40d21b1c"
+    )),
+    hex(concat!(
+        "12561a5deb559c4348b4711298e536367041e8ca0cf0800c",
+        "0126c2588c48bf5713daa8846cb026e9e5c8276ec82b3bff"
+    )),
+    hex(concat!(
+        "0b2962fe57a3225e8137e629bff2991f6f89416f5a718cd1",
+        "fca64e00b11aceacd6a3d0967c94fedcfcc239ba5cb83e19"
+    )),
+    hex(concat!(
+        "03425581a58ae2fec83aafef7c40eb545b08243f16b16551",
+        "54cca8abc28d6fd04976d5243eecf5c4130de8938dc62cd8"
+    )),
+    hex(concat!(
+        "13a8e162022914a80a6f1d5f43e7a07dffdfc759a12062bb",
+        "8d6b44e833b306da9bd29ba81f35781d539d395b3532a21e"
+    )),
+    hex(concat!(
+        "0e7355f8e4e667b955390f7f0506c6e9395735e9ce9cad4d",
+        "0a43bcef24b8982f7400d24bc4228f11c02df9a29f6304a5"
+    )),
+    hex(concat!(
+        "0772caacf16936190f3e0c63e0596721570f5799af53a189",
+        "4e2e073062aede9cea73b3538f0de06cec2574496ee84a3a"
+    )),
+    hex(concat!(
+        "14a7ac2a9d64a8b230b3f5b074cf01996e7f63c21bca68a8",
+        "1996e1cdf9822c580fa5b9489d11e2d311f7d99bbdcc5a5e"
+    )),
+    hex(concat!(
+        "0a10ecf6ada54f825e920b3dafc7a3cce07f8d1d7161366b",
+        "74100da67f39883503826692abba43704776ec3a79a1d641"
+    )),
+    hex(concat!(
+        "095fc13ab9e92ad4476d6e3eb3a56680f682b4ee96f7d037",
+        "76df533978f31c1593174e4b4b7865002d6384d168ecdd0a"
+    )),
+    hex(concat!(
+        "000000000000000000000000000000000000000000000000",
+        "000000000000000000000000000000000000000000000001"
+    )),
+];
+/// The coefficients of `y_num` of the 11-isogeny, from the constant term.
+const G1_YNUM: [Fp; 16] = [
+    hex(concat!(
+        "090d97c81ba24ee0259d1f094980dcfa11ad138e48a86952",
+        "2b52af6c956543d3cd0c7aee9b3ba3c2be9845719707bb33"
+    )),
+    hex(concat!(
+        "134996a104ee5811d51036d776fb46831223e96c254f383d",
+        "0f906343eb67ad34d6c56711962fa8bfe097e75a2e41c696"
+    )),
+    hex(concat!(
+        "00cc786baa966e66f4a384c86a3b49942552e2d658a31ce2",
+        "c344be4b91400da7d26d521628b00523b8dfe240c72de1f6"
+    )),
+    hex(concat!(
+        "01f86376e8981c217898751ad8746757d42aa7b90eeb791c",
+        "09e4a3ec03251cf9de405aba9ec61deca6355c77b0e5f4cb"
+    )),
+    hex(concat!(
+        "08cc03fdefe0ff135caf4fe2a21529c4195536fbe3ce50b8",
+        "79833fd221351adc2ee7f8dc099040a841b6daecf2e8fedb"
+    )),
+    hex(concat!(
+        "16603fca40634b6a2211e11db8f0a6a074a7d0d4afadb7bd",
+        "76505c3d3ad5544e203f6326c95a807299b23ab13633a5f0"
+    )),
+    hex(concat!(
+        "04ab0b9bcfac1bbcb2c977d027796b3ce75bb8ca2be184cb",
+        "5231413c4d634f3747a87ac2460f415ec961f8855fe9d6f2"
+    )),
+    hex(concat!(
+        "0987c8d5333ab86fde9926bd2ca6c674170a05bfe3bdd81f",
+        "fd038da6c26c842642f64550fedfe935a15e4ca31870fb29"
+    )),
+    hex(concat!(
+        "09fc4018bd96684be88c9e221e4da1bb8f3abd16679dc26c",
+        "1e8b6e6a1f20cabe69d65201c78607a360370e577bdba587"
+    )),
+    hex(concat!(
+        "0e1bba7a1186bdb5223abde7ada14a23c42a0ca7915af6fe",
+        "06985e7ed1e4d43b9b3f7055dd4eba6f2bafaaebca731c30"
+    )),
+    hex(concat!(
+        "19713e47937cd1be0dfd0b8f1d43fb93cd2fcbcb6caf493f",
+        "d1183e416389e61031bf3a5cce3fbafce813711ad011c132"
+    )),
+    hex(concat!(
+        "18b46a908f36f6deb918c143fed2edcc523559b8aaf0c246",
+        "2e6bfe7f911f643249d9cdf41b44d606ce07c8a4d0074d8e"
+    )),
+    hex(concat!(
+        "0b182cac101b9399d155096004f53f447aa7b12a3426b08e",
+        "c02710e807b4633f06c851c1919211f20d4c04f00b971ef8"
+    )),
+    hex(concat!(
+        "0245a394ad1eca9b72fc00ae7be315dc757b3b080d4c1580",
+        "13e6632d3c40659cc6cf90ad1c232a6442d9d3f5db980133"
+    )),
+    hex(concat!(
+        "05c129645e44cf1102a159f748c4a3fc5e673d81d7e86568",
+        "d9ab0f5d396a7ce46ba1049b6579afb7866b1e715475224b"
+    )),
+    hex(concat!(
+        "15e6be4e990f03ce4ea50b3b42df2eb5cb181d8f84965a39",
+        "57add4fa95af01b2b665027efec01c7704b456be69c8b604"
+    )),
+];
+/// The coefficients of `y_den` of the 11-isogeny, from the constant term.
+const G1_YDEN: [Fp; 16] = [
+    hex(concat!(
+        "16112c4c3a9c98b252181140fad0eae9601a6de578980be6",
+        "eec3232b5be72e7a07f3688ef60c206d01479253b03663c1"
+    )),
+    hex(concat!(
+        "1962d75c2381201e1a0cbd6c43c348b885c84ff731c4d59c",
+        "a4a10356f453e01f78a4260763529e3532f6102c2e49a03d"
+    )),
+    hex(concat!(
+        "058df3306640da276faaae7d6e8eb15778c4855551ae7f31",
+        "0c35a5dd279cd2eca6757cd636f96f891e2538b53dbf67f2"
+    )),
+    hex(concat!(
+        "16b7d288798e5395f20d23bf89edb4d1d115c5dbddbcd30e",
+        "123da489e726af41727364f2c28297ada8d26d98445f5416"
+    )),
+    hex(concat!(
+        "0be0e079545f43e4b00cc912f8228ddcc6d19c9f0f69bbb0",
+        "542eda0fc9dec916a20b15dc0fd2ededda39142311a5001d"
+    )),
+    hex(concat!(
+        "08d9e5297186db2d9fb266eaac783182b70152c65550d881",
+        "c5ecd87b6f0f5a6449f38db9dfa9cce202c6477faaf9b7ac"
+    )),
+    hex(concat!(
+        "166007c08a99db2fc3ba8734ace9824b5eecfdfa8d0cf8ef",
+        "5dd365bc400a0051d5fa9c01a58b1fb93d1a1399126a775c"
+    )),
+    hex(concat!(
+        "16a3ef08be3ea7ea03bcddfabba6ff6ee5a4375efa1f4fd7",
+        "feb34fd206357132b920f5b00801dee460ee415a15812ed9"
+    )),
+    hex(concat!(
+        "1866c8ed336c61231a1be54fd1d74cc4f9fb0ce4c6af5920",
+        "abc5750c4bf39b4852cfe2f7bb9248836b233d9d55535d4a"
+    )),
+    hex(concat!(
+        "167a55cda70a6e1cea820597d94a84903216f763e13d87bb",
+        "5308592e7ea7d4fbc7385ea3d529b35e346ef48bb8913f55"
+    )),
+    hex(concat!(
+        "04d2f259eea405bd48f010a01ad2911d9c6dd039bb61a629",
+        "0e591b36e636a5c871a5c29f4f83060400f8b49cba8f6aa8"
+    )),
+    hex(concat!(
+        "0accbb67481d033ff5852c1e48c50c477f94ff8aefce42d2",
+        "8c0f9a88cea7913516f968986f7ebbea9684b529e2561092"
+    )),
+    hex(concat!(
+        "0ad6b9514c767fe3c3613144b45f1496543346d98adf0226",
+        "7d5ceef9a00d9b8693000763e3b90ac11e99b138573345cc"
+    )),
+    hex(concat!(
+        "02660400eb2e4f3b628bdd0d53cd76f2bf565b94e72927c1",
+        "cb748df27942480e420517bd8714cc80d1fadc1326ed06f7"
+    )),
+    hex(concat!(
+        "0e0fa1d816ddc03e6b24255e0d7819c171c40f65e273b853",
+        "324efcd6356caa205ca2f570f13497804415473a1d634b8f"
+    )),
+    hex(concat!(
+        "000000000000000000000000000000000000000000000000",
+        "000000000000000000000000000000000000000000000001"
+    )),
+];
+/// The coefficients of `x_num` of the 3-isogeny, from the constant term.
+const G2_XNUM: [Fp2; 4] = [
+    [
+        hex(concat!(
+            "05c759507e8e333ebb5b7a9a47d7ed8532c52d39fd3a042a",
+            "88b58423c50ae15d5c2638e343d9c71c6238aaaaaaaa97d6"
+        )),
+        hex(concat!(
+            "05c759507e8e333ebb5b7a9a47d7ed8532c52d39fd3a042a",
+            "88b58423c50ae15d5c2638e343d9c71c6238aaaaaaaa97d6"
+        )),
+    ],
+    [
+        hex(concat!(
+            "000000000000000000000000000000000000000000000000",
+            "000000000000000000000000000000000000000000000000"
+        )),
+        hex(concat!(
+            "11560bf17baa99bc32126fced787c88f984f87adf7ae0c7f",
+            "9a208c6b4f20a4181472aaa9cb8d555526a9ffffffffc71a"
+        )),
+    ],
+    [
+        hex(concat!(
+            "11560bf17baa99bc32126fced787c88f984f87adf7ae0c7f",
+            "9a208c6b4f20a4181472aaa9cb8d555526a9ffffffffc71e"
+        )),
+        hex(concat!(
+            "08ab05f8bdd54cde190937e76bc3e447cc27c3d6fbd7063f",
+            "cd104635a790520c0a395554e5c6aaaa9354ffffffffe38d"
+        )),
+    ],
+    [
+        hex(concat!(
+            "171d6541fa38ccfaed6dea691f5fb614cb14b4e7f4e810aa",
+            "22d6108f142b85757098e38d0f671c7188e2aaaaaaaa5ed1"
+        )),
+        hex(concat!(
+            "000000000000000000000000000000000000000000000000",
+            "000000000000000000000000000000000000000000000000"
+        )),
+    ],
+];
+/// The coefficients of `x_den` of the 3-isogeny, from the constant term.
+const G2_XDEN: [Fp2; 3] = [
+    [
+        hex(concat!(
+            "000000000000000000000000000000000000000000000000",
+            "000000000000000000000000000000000000000000000000"
+        )),
+        hex(concat!(
+            "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf",
+            "6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaa63"
+        )),
+    ],
+    [
+        hex(concat!(
+            "000000000000000000000000000000000000000000000000",
+            "00000000000000000000000000000000000000000000000c"
+        )),
+        hex(concat!(
+            "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf",
+            "6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaa9f"
+        )),
+    ],
+    [
+        hex(concat!(
+            "000000000000000000000000000000000000000000000000",
+            "000000000000000000000000000000000000000000000001"
+        )),
+        hex(concat!(
+            "000000000000000000000000000000000000000000000000",
+            "000000000000000000000000000000000000000000000000"
+        )),
+    ],
+];
+/// The coefficients of `y_num` of the 3-isogeny, from the constant term.
+const G2_YNUM: [Fp2; 4] = [
+    [
+        hex(concat!(
+            "1530477c7ab4113b59a4c18b076d11930f7da5d4a07f649b",
+            "f54439d87d27e500fc8c25ebf8c92f6812cfc71c71c6d706"
+        )),
+        hex(concat!(
+            "1530477c7ab4113b59a4c18b076d11930f7da5d4a07f649b",
+            "f54439d87d27e500fc8c25ebf8c92f6812cfc71c71c6d706"
+        )),
+    ],
+    [
+        hex(concat!(
+            "000000000000000000000000000000000000000000000000",
+            "000000000000000000000000000000000000000000000000"
+        )),
+        hex(concat!(
+            "05c759507e8e333ebb5b7a9a47d7ed8532c52d39fd3a042a",
+            "88b58423c50ae15d5c2638e343d9c71c6238aaaaaaaa97be"
+        )),
+    ],
+    [
+        hex(concat!(
+            "11560bf17baa99bc32126fced787c88f984f87adf7ae0c7f",
+            "9a208c6b4f20a4181472aaa9cb8d555526a9ffffffffc71c"
+        )),
+        hex(concat!(
+            "08ab05f8bdd54cde190937e76bc3e447cc27c3d6fbd7063f",
+            "cd104635a790520c0a395554e5c6aaaa9354ffffffffe38f"
+        )),
+    ],
+    [
+        hex(concat!(
+            "124c9ad43b6cf79bfbf7043de3811ad0761b0f37a1e26286",
+            "b0e977c69aa274524e79097a56dc4bd9e1b371c71c718b10"
+        )),
+        hex(concat!(
+            "000000000000000000000000000000000000000000000000",
+            "000000000000000000000000000000000000000000000000"
+        )),
+    ],
+];
+/// The coefficients of `y_den` of the 3-isogeny, from the constant term.
+const G2_YDEN: [Fp2; 4] = [
+    [
+        hex(concat!(
+            "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf",
+            "6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffa8fb"
+        )),
+        hex(concat!(
+            "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf",
+            "6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffa8fb"
+        )),
+    ],
+    [
+        hex(concat!(
+            "000000000000000000000000000000000000000000000000",
+            "000000000000000000000000000000000000000000000000"
+        )),
+        hex(concat!(
+            "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf",
+            "6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffa9d3"
+        )),
+    ],
+    [
+        hex(concat!(
+            "000000000000000000000000000000000000000000000000",
+            "000000000000000000000000000000000000000000000012"
+        )),
+        hex(concat!(
+            "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf",
+            "6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaa99"
+        )),
+    ],
+    [
+        hex(concat!(
+            "000000000000000000000000000000000000000000000000",
+            "000000000000000000000000000000000000000000000001"
+        )),
+        hex(concat!(
+            "000000000000000000000000000000000000000000000000",
+            "000000000000000000000000000000000000000000000000"
+        )),
+    ],
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const G1_DST: &[u8] = b"QUUX-V01-CS02-with-BLS12381G1_XMD:SHA-256_SSWU_RO_";
+    const G2_DST: &[u8] = b"QUUX-V01-CS02-with-BLS12381G2_XMD:SHA-256_SSWU_RO_";
+
+    /// The field elements and the affine points of the vectors of RFC 9380, appendix J.9.1.
+    const G1_VECTORS: [([Fp; 2], [Fp; 2]); 5] = [
+        (
+            [
+                hex(concat!(
+                    "0ba14bd907ad64a016293ee7c2d276b8eae71f25a4b941ee",
+                    "ce7b0d89f17f75cb3ae5438a614fb61d6835ad59f29c564f"
+                )),
+                hex(concat!(
+                    "019b9bd7979f12657976de2884c7cce192b82c177c80e0ec",
+                    "604436a7f538d231552f0d96d9f7babe5fa3b19b3ff25ac9"
+                )),
+            ],
+            [
+                hex(concat!(
+                    "052926add2207b76ca4fa57a8734416c8dc95e24501772c8",
+                    "14278700eed6d1e4e8cf62d9c09db0fac349612b759e79a1"
+                )),
+                hex(concat!(
+                    "08ba738453bfed09cb546dbb0783dbb3a5f1f566ed67bb6b",
+                    "e0e8c67e2e81a4cc68ee29813bb7994998f3eae0c9c6a265"
+                )),
+            ],
+        ),
+        (
+            [
+                hex(concat!(
+                    "0d921c33f2bad966478a03ca35d05719bdf92d347557ea16",
+                    "6e5bba579eea9b83e9afa5c088573c2281410369fbd32951"
+                )),
+                hex(concat!(
+                    "003574a00b109ada2f26a37a91f9d1e740dffd8d69ec0c35",
+                    "e1e9f4652c7dba61123e9dd2e76c655d956e2b3462611139"
+                )),
+            ],
+            [
+                hex(concat!(
+                    "03567bc5ef9c690c2ab2ecdf6a96ef1c139cc0b2f284dca0",
+                    "a9a7943388a49a3aee664ba5379a7655d3c68900be2f6903"
+                )),
+                hex(concat!(
+                    "0b9c15f3fe6e5cf4211f346271d7b01c8f3b28be689c8429",
+                    "c85b67af215533311f0b8dfaaa154fa6b88176c229f2885d"
+                )),
+            ],
+        ),
+        (
+            [
+                hex(concat!(
+                    "062d1865eb80ebfa73dcfc45db1ad4266b9f3a93219976a3",
+                    "790ab8d52d3e5f1e62f3b01795e36834b17b70e7b76246d4"
+                )),
+                hex(concat!(
+                    "0cdc3e2f271f29c4ff75020857ce6c5d36008c9b48385ea2",
+                    "f2bf6f96f428a3deb798aa033cd482d1cdc8b30178b08e3a"
+                )),
+            ],
+            [
+                hex(concat!(
+                    "11e0b079dea29a68f0383ee94fed1b940995272407e3bb91",
+                    "6bbf268c263ddd57a6a27200a784cbc248e84f357ce82d98"
+                )),
+                hex(concat!(
+                    "03a87ae2caf14e8ee52e51fa2ed8eefe80f02457004ba4d4",
+                    "86d6aa1f517c0889501dc7413753f9599b099ebcbbd2d709"
+                )),
+            ],
+        ),
+        (
+            [
+                hex(concat!(
+                    "010476f6a060453c0b1ad0b628f3e57c23039ee16eea5e71",
+                    "bb87c3b5419b1255dc0e5883322e563b84a29543823c0e86"
+                )),
+                hex(concat!(
+                    "0b1a912064fb0554b180e07af7e787f1f883a0470759c03c",
+                    "1b6509eb8ce980d1670305ae7b928226bb58fdc0a419f46e"
+                )),
+            ],
+            [
+                hex(concat!(
+                    "15f68eaa693b95ccb85215dc65fa81038d69629f70aeee0d",
+                    "0f677cf22285e7bf58d7cb86eefe8f2e9bc3f8cb84fac488"
+                )),
+                hex(concat!(
+                    "1807a1d50c29f430b8cafc4f8638dfeeadf51211e1602a5f",
+                    "184443076715f91bb90a48ba1e370edce6ae1062f5e6dd38"
+                )),
+            ],
+        ),
+        (
+            [
+                hex(concat!(
+                    "0a8ffa7447f6be1c5a2ea4b959c9454b431e29ccc0802bc0",
+                    "52413a9c5b4f9aac67a93431bd480d15be1e057c8a08e8c6"
+                )),
+                hex(concat!(
+                    "05d487032f602c90fa7625dbafe0f4a49ef4a6b0b33d7bb3",
+                    "49ff4cf5410d297fd6241876e3e77b651cfc8191e40a68b7"
+                )),
+            ],
+            [
+                hex(concat!(
+                    "082aabae8b7dedb0e78aeb619ad3bfd9277a2f77ba7fad20",
+                    "ef6aabdc6c31d19ba5a6d12283553294c1825c4b3ca2dcfe"
+                )),
+                hex(concat!(
+                    "05b84ae5a942248eea39e1d91030458c40153f3b654ab787",
+                    "2d779ad1e942856a20c438e8d99bc8abfbf74729ce1f7ac8"
+                )),
+            ],
+        ),
+    ];
+
+    /// The field elements and the affine points of the vectors of RFC 9380, appendix J.10.1.
+    const G2_VECTORS: [([Fp2; 2], [Fp2; 2]); 5] = [
+        (
+            [
+                [
+                    hex(concat!(
+                        "03dbc2cce174e91ba93cbb08f26b917f98194a2ea08d1cce",
+                        "75b2b9cc9f21689d80bd79b594a613d0a68eb807dfdc1cf8"
+                    )),
+                    hex(concat!(
+                        "05a2acec64114845711a54199ea339abd125ba38253b70a9",
+                        "2c876df10598bd1986b739cad67961eb94f7076511b3b39a"
+                    )),
+                ],
+                [
+                    hex(concat!(
+                        "02f99798e8a5acdeed60d7e18e9120521ba1f47ec0909846",
+                        "62846bc825de191b5b7641148c0dbc237726a334473eee94"
+                    )),
+                    hex(concat!(
+                        "145a81e418d4010cc027a68f14391b30074e89e60ee7a22f",
+                        "87217b2f6eb0c4b94c9115b436e6fa4607e95a98de30a435"
+                    )),
+                ],
+            ],
+            [
+                [
+                    hex(concat!(
+                        "0141ebfbdca40eb85b87142e130ab689c673cf60f1a3e98d",
+                        "69335266f30d9b8d4ac44c1038e9dcdd5393faf5c41fb78a"
+                    )),
+                    hex(concat!(
+                        "05cb8437535e20ecffaef7752baddf98034139c38452458b",
+                        "aeefab379ba13dff5bf5dd71b72418717047f5b0f37da03d"
+                    )),
+                ],
+                [
+                    hex(concat!(
+                        "0503921d7f6a12805e72940b963c0cf3471c7b2a524950ca",
+                        "195d11062ee75ec076daf2d4bc358c4b190c0c98064fdd92"
+                    )),
+                    hex(concat!(
+                        "12424ac32561493f3fe3c260708a12b7c620e7be00099a97",
+                        "4e259ddc7d1f6395c3c811cdd19f1e8dbf3e9ecfdcbab8d6"
+                    )),
+                ],
+            ],
+        ),
+        (
+            [
+                [
+                    hex(concat!(
+                        "15f7c0aa8f6b296ab5ff9c2c7581ade64f4ee6f1bf18f551",
+                        "79ff44a2cf355fa53dd2a2158c5ecb17d7c52f63e7195771"
+                    )),
+                    hex(concat!(
+                        "01c8067bf4c0ba709aa8b9abc3d1cef589a4758e09ef5373",
+                        "2d670fd8739a7274e111ba2fcaa71b3d33df2a3a0c8529dd"
+                    )),
+                ],
+                [
+                    hex(concat!(
+                        "187111d5e088b6b9acfdfad078c4dacf72dcd17ca17c82be",
+                        "35e79f8c372a693f60a033b461d81b025864a0ad051a06e4"
+                    )),
+                    hex(concat!(
+                        "08b852331c96ed983e497ebc6dee9b75e373d923b729194a",
+                        "f8e72a051ea586f3538a6ebb1e80881a082fa2b24df9f566"
+                    )),
+                ],
+            ],
+            [
+                [
+                    hex(concat!(
+                        "02c2d18e033b960562aae3cab37a27ce00d80ccd5ba4b7fe",
+                        "0e7a210245129dbec7780ccc7954725f4168aff2787776e6"
+                    )),
+                    hex(concat!(
+                        "139cddbccdc5e91b9623efd38c49f81a6f83f175e80b06fc",
+                        "374de9eb4b41dfe4ca3a230ed250fbe3a2acf73a41177fd8"
+                    )),
+                ],
+                [
+                    hex(concat!(
+                        "1787327b68159716a37440985269cf584bcb1e621d3a7202",
+                        "be6ea05c4cfe244aeb197642555a0645fb87bf7466b2ba48"
+                    )),
+                    hex(concat!(
+                        "00aa65dae3c8d732d10ecd2c50f8a1baf3001578f71c694e",
+                        "03866e9f3d49ac1e1ce70dd94a733534f106d4cec0eddd16"
+                    )),
+                ],
+            ],
+        ),
+        (
+            [
+                [
+                    hex(concat!(
+                        "0313d9325081b415bfd4e5364efaef392ecf69b087496973",
+                        "b229303e1816d2080971470f7da112c4eb43053130b785e1"
+                    )),
+                    hex(concat!(
+                        "062f84cb21ed89406890c051a0e8b9cf6c575cf6e8e18ecf",
+                        "63ba86826b0ae02548d83b483b79e48512b82a6c0686df8f"
+                    )),
+                ],
+                [
+                    hex(concat!(
+                        "1739123845406baa7be5c5dc74492051b6d42504de008c63",
+                        "5f3535bb831d478a341420e67dcc7b46b2e8cba5379cca97"
+                    )),
+                    hex(concat!(
+                        "01897665d9cb5db16a27657760bbea7951f67ad68f8d55f7",
+                        "113f24ba6ddd82caef240a9bfa627972279974894701d975"
+                    )),
+                ],
+            ],
+            [
+                [
+                    hex(concat!(
+                        "121982811d2491fde9ba7ed31ef9ca474f0e1501297f68c2",
+                        "98e9f4c0028add35aea8bb83d53c08cfc007c1e005723cd0"
+                    )),
+                    hex(concat!(
+                        "190d119345b94fbd15497bcba94ecf7db2cbfd1e1fe7da03",
+                        "4d26cbba169fb3968288b3fafb265f9ebd380512a71c3f2c"
+                    )),
+                ],
+                [
+                    hex(concat!(
+                        "05571a0f8d3c08d094576981f4a3b8eda0a8e771fcdcc8ec",
+                        "ceaf1356a6acf17574518acb506e435b639353c2e14827c8"
+                    )),
+                    hex(concat!(
+                        "0bb5e7572275c567462d91807de765611490205a941a5a6a",
+                        "f3b1691bfe596c31225d3aabdf15faff860cb4ef17c7c3be"
+                    )),
+                ],
+            ],
+        ),
+        (
+            [
+                [
+                    hex(concat!(
+                        "025820cefc7d06fd38de7d8e370e0da8a52498be9b53cba9",
+                        "927b2ef5c6de1e12e12f188bbc7bc923864883c57e49e253"
+                    )),
+                    hex(concat!(
+                        "034147b77ce337a52e5948f66db0bab47a8d038e712123bb",
+                        "381899b6ab5ad20f02805601e6104c29df18c254b8618c7b"
+                    )),
+                ],
+                [
+                    hex(concat!(
+                        "0930315cae1f9a6017c3f0c8f2314baa130e1cf13f6532bf",
+                        "f0a8a1790cd70af918088c3db94bda214e896e1543629795"
+                    )),
+                    hex(concat!(
+                        "10c4df2cacf67ea3cb3108b00d4cbd0b3968031ebc8eac4b",
+                        "1ebcefe84d6b715fde66bef0219951ece29d1facc8a520ef"
+                    )),
+                ],
+            ],
+            [
+                [
+                    hex(concat!(
+                        "19a84dd7248a1066f737cc34502ee5555bd3c19f2ecdb3c7",
+                        "d9e24dc65d4e25e50d83f0f77105e955d78f4762d33c17da"
+                    )),
+                    hex(concat!(
+                        "0934aba516a52d8ae479939a91998299c76d39cc0c035cd1",
+                        "8813bec433f587e2d7a4fef038260eef0cef4d02aae3eb91"
+                    )),
+                ],
+                [
+                    hex(concat!(
+                        "14f81cd421617428bc3b9fe25afbb751d934a00493524bc4",
+                        "e065635b0555084dd54679df1536101b2c979c0152d09192"
+                    )),
+                    hex(concat!(
+                        "09bcccfa036b4847c9950780733633f13619994394c23ff0",
+                        "b32fa6b795844f4a0673e20282d07bc69641cee04f5e5662"
+                    )),
+                ],
+            ],
+        ),
+        (
+            [
+                [
+                    hex(concat!(
+                        "190b513da3e66fc9a3587b78c76d1d132b1152174d0b83e3",
+                        "c1114066392579a45824c5fa17649ab89299ddd4bda54935"
+                    )),
+                    hex(concat!(
+                        "12ab625b0fe0ebd1367fe9fac57bb1168891846039b4216b",
+                        "9d94007b674de2d79126870e88aeef54b2ec717a887dcf39"
+                    )),
+                ],
+                [
+                    hex(concat!(
+                        "0e6a42010cf435fb5bacc156a585e1ea3294cc81d0ceb819",
+                        "24d95040298380b164f702275892cedd81b62de3aba3f6b5"
+                    )),
+                    hex(concat!(
+                        "117d9a0defc57a33ed208428cb84e54c85a6840e7648480a",
+                        "e428838989d25d97a0af8e3255be62b25c2a85630d2dddd8"
+                    )),
+                ],
+            ],
+            [
+                [
+                    hex(concat!(
+                        "01a6ba2f9a11fa5598b2d8ace0fbe0a0eacb65deceb476fb",
+                        "bcb64fd24557c2f4b18ecfc5663e54ae16a84f5ab7f62534"
+                    )),
+                    hex(concat!(
+                        "11fca2ff525572795a801eed17eb12785887c7b63fb77a42",
+                        "be46ce4a34131d71f7a73e95fee3f812aea3de78b4d01569"
+                    )),
+                ],
+                [
+                    hex(concat!(
+                        "0b6798718c8aed24bc19cb27f866f1c9effcdbf92397ad64",
+                        "48b5c9db90d2b9da6cbabf48adc1adf59a1a28344e79d57e"
+                    )),
+                    hex(concat!(
+                        "03a47f8e6d1763ba0cad63d6114c0accbef65707825a511b",
+                        "251a660a9b3994249ae4e63fac38b23da0c398689ee2ab52"
+                    )),
+                ],
+            ],
+        ),
+    ];
+
+    /// The messages of the vectors.
+    fn messages() -> [Vec<u8>; 5] {
+        [
+            b"".to_vec(),
+            b"abc".to_vec(),
+            b"abcdef0123456789".to_vec(),
+            [&b"q128_"[..], &[b'q'; 128]].concat(),
+            [&b"a512_"[..], &[b'a'; 512]].concat(),
+        ]
+    }
+
+    #[test]
+    fn test_hash_to_g1_vectors() {
+        let field = Base::new();
+        for (msg, (u, p)) in messages().iter().zip(G1_VECTORS) {
+            assert_eq!(hash_to_field(&field, msg, G1_DST, 2), u);
+            assert_eq!(g1(msg, G1_DST), G1Point::Affine { x: p[0], y: p[1] });
+        }
+    }
+
+    #[test]
+    fn test_hash_to_g2_vectors() {
+        let field = Base::new();
+        for (msg, (u, p)) in messages().iter().zip(G2_VECTORS) {
+            assert_eq!(hash_to_field(&field, msg, G2_DST, 4), u.concat());
+            assert_eq!(g2(msg, G2_DST), G2Point::Affine { x: p[0], y: p[1] });
+        }
+    }
+}
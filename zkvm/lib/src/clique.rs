@@ -0,0 +1,257 @@
+//! Recovery of the signers of clique (proof of authority) blocks, as sealed by geth.
+//!
+//! The extra data of a clique header is 32 bytes of vanity data, followed on checkpoint blocks
+//! by the 20-byte addresses of the signers, and by the 65-byte seal signature. The seal signs the
+//! keccak256 hash of the header encoded without the seal, and has a `v` value of 0 or 1.
+
+use crate::error::Error;
+use crate::keccak::keccak256;
+use crate::rlp::{self, Item};
+use crate::secp256k1::ecrecover_address;
+use alloc::vec::Vec;
+use core::convert::TryInto;
+
+/// The length of the vanity data at the start of the extra data.
+pub const EXTRA_VANITY: usize = 32;
+
+/// The length of the seal signature at the end of the extra data.
+pub const EXTRA_SEAL: usize = 65;
+
+/// The number of fields of a header before London, without the base fee.
+const LEGACY_FIELDS: usize = 15;
+
+/// The index of the extra data among the fields of a header.
+const EXTRA_INDEX: usize = 12;
+
+/// Returns the address of the signer that sealed a block, given the RLP encoding of its header.
+///
+/// The header must be a canonical encoding of the 15 fields of a pre-London header, or of the 16
+/// fields of a London header with its base fee. Headers with later fields, such as the
+/// withdrawals root, are not clique headers. Whether the block is a checkpoint, whose extra
+/// data lists the signers, is not checked, as it depends on the epoch length of the chain.
+///
+/// Returns [`Error::CliqueMalformed`] if the header cannot be decoded, or if the extra data is
+/// too short or its signer list is not made of whole addresses, and the errors of
+/// [`ecrecover_address`] if the seal does not recover.
+pub fn recover_clique_signer(header_rlp: &[u8]) -> Result<[u8; 20], Error> {
+    let (seal_hash, seal) = split_seal(header_rlp)?;
+    ecrecover_address(&seal, &seal_hash)
+}
+
+/// Returns the hash signed by the seal of a header, the `SealHash` of geth, with the same
+/// requirements on the header as [`recover_clique_signer`].
+pub fn seal_hash(header_rlp: &[u8]) -> Result<[u8; 32], Error> {
+    Ok(split_seal(header_rlp)?.0)
+}
+
+/// Decodes a header, returning its seal hash and its seal.
+fn split_seal(header_rlp: &[u8]) -> Result<([u8; 32], [u8; 65]), Error> {
+    let fields = decode_header(header_rlp).ok_or(Error::CliqueMalformed)?;
+    let extra = fields[EXTRA_INDEX].payload;
+    if extra.len() < EXTRA_VANITY + EXTRA_SEAL
+        || (extra.len() - EXTRA_VANITY - EXTRA_SEAL) % 20 != 0
+    {
+        return Err(Error::CliqueMalformed);
+    }
+    let (unsealed_extra, seal) = extra.split_at(extra.len() - EXTRA_SEAL);
+
+    // The fields are canonical, so their encodings are copied rather than encoded again.
+    let mut payload = Vec::with_capacity(header_rlp.len());
+    for (i, field) in fields.iter().enumerate() {
+        if i == EXTRA_INDEX {
+            // The vanity data makes the extra data longer than a single byte.
+            rlp::put_header(&mut payload, 0x80, unsealed_extra.len());
+            payload.extend_from_slice(unsealed_extra);
+        } else {
+            payload.extend_from_slice(field.encoding);
+        }
+    }
+    let mut encoded = Vec::with_capacity(payload.len() + 9);
+    rlp::put_header(&mut encoded, 0xc0, payload.len());
+    encoded.extend_from_slice(&payload);
+
+    Ok((keccak256(&encoded), seal.try_into().unwrap()))
+}
+
+/// Decodes the fields of a header, checking that they are canonical and have the lengths of
+/// their types, as the decoder of geth does.
+fn decode_header(header_rlp: &[u8]) -> Option<Vec<Item<'_>>> {
+    let fields = rlp::decode_list(header_rlp)?;
+    if fields.len() != LEGACY_FIELDS && fields.len() != LEGACY_FIELDS + 1 {
+        return None;
+    }
+
+    for (i, field) in fields.iter().enumerate() {
+        let len = field.payload.len();
+        let valid = !field.is_list
+            && match i {
+                // The parent, uncle, state, transactions and receipts hashes, and the mix digest.
+                0 | 1 | 3 | 4 | 5 | 13 => len == 32,
+                // The coinbase.
+                2 => len == 20,
+                // The logs bloom.
+                6 => len == 256,
+                // The gas limit, the gas used and the timestamp are 64-bit integers.
+                9..=11 => rlp::decode_u64(field.payload).is_some(),
+                // The difficulty, the number and the base fee are big integers.
+                7 | 8 | 15 => field.payload.first() != Some(&0),
+                EXTRA_INDEX => true,
+                // The nonce.
+                _ => len == 8,
+            };
+        if !valid {
+            return None;
+        }
+    }
+    Some(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_recover_clique_signer() {
+        use crate::clique::{recover_clique_signer, seal_hash};
+        use crate::error::Error;
+        use crate::keccak::keccak256;
+        use crate::secp256k1::pubkey_to_address;
+        use alloc::vec::Vec;
+        use k256::ecdsa::SigningKey;
+
+        fn put_len(out: &mut Vec<u8>, offset: u8, len: usize) {
+            if len < 56 {
+                out.push(offset + len as u8);
+            } else {
+                let len_bytes = (len as u64).to_be_bytes();
+                let len_bytes = &len_bytes[len_bytes.iter().position(|&b| b != 0).unwrap()..];
+                out.push(offset + 55 + len_bytes.len() as u8);
+                out.extend_from_slice(len_bytes);
+            }
+        }
+        fn encode_header(fields: &[Vec<u8>]) -> Vec<u8> {
+            let mut payload = Vec::new();
+            for field in fields {
+                match field[..] {
+                    [byte] if byte < 0x80 => payload.push(byte),
+                    _ => {
+                        put_len(&mut payload, 0x80, field.len());
+                        payload.extend_from_slice(field);
+                    }
+                }
+            }
+            let mut header = Vec::new();
+            put_len(&mut header, 0xc0, payload.len());
+            header.extend_from_slice(&payload);
+            header
+        }
+        let int = |value: u64| -> Vec<u8> {
+            value
+                .to_be_bytes()
+                .into_iter()
+                .skip_while(|&b| b == 0)
+                .collect()
+        };
+        let unsealed_fields = |extra: Vec<u8>, base_fee: Option<u64>| {
+            let mut fields = vec![
+                vec![0x11; 32],
+                hex::decode("1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347")
+                    .unwrap(),
+                vec![0; 20],
+                vec![0x22; 32],
+                vec![0x33; 32],
+                vec![0x44; 32],
+                vec![0; 256],
+                int(2),
+                int(4_000_000),
+                int(30_000_000),
+                int(21_000),
+                int(1_600_000_000),
+                extra,
+                vec![0; 32],
+                vec![0; 8],
+            ];
+            fields.extend(base_fee.map(int));
+            fields
+        };
+
+        let signing_key = SigningKey::from_slice(&[0x42; 32]).unwrap();
+        let signer = pubkey_to_address(
+            &signing_key
+                .verifying_key()
+                .to_encoded_point(false)
+                .as_bytes()
+                .try_into()
+                .unwrap(),
+        );
+        let seal = |fields: &[Vec<u8>]| {
+            let hash = keccak256(&encode_header(fields));
+            let (sig, recid) = signing_key.sign_prehash_recoverable(&hash).unwrap();
+            let mut sealed = fields.to_vec();
+            sealed[12].extend_from_slice(&sig.to_bytes());
+            sealed[12].push(recid.to_byte());
+            (hash, sealed)
+        };
+
+        let vanity = vec![0xd8; 32];
+        let mut checkpoint = vanity.clone();
+        for signer in [[0xaa; 20], [0xbb; 20], [0xcc; 20]] {
+            checkpoint.extend_from_slice(&signer);
+        }
+        // A base fee of 7 is encoded as a single byte.
+        for (extra, base_fee) in [
+            (vanity.clone(), None),
+            (vanity.clone(), Some(7)),
+            (checkpoint.clone(), None),
+            (checkpoint, Some(1_000_000_000)),
+        ] {
+            let (hash, sealed) = seal(&unsealed_fields(extra, base_fee));
+            let header = encode_header(&sealed);
+            assert_eq!(seal_hash(&header), Ok(hash));
+            assert_eq!(recover_clique_signer(&header), Ok(signer));
+        }
+
+        let (_, sealed) = seal(&unsealed_fields(vanity.clone(), None));
+        let mut invalid_v = sealed.clone();
+        *invalid_v[12].last_mut().unwrap() = 2;
+        assert_eq!(
+            recover_clique_signer(&encode_header(&invalid_v)),
+            Err(Error::InvalidRecoveryId(2))
+        );
+
+        let mut short_extra = sealed.clone();
+        short_extra[12].remove(0);
+        let mut partial_signer = sealed.clone();
+        partial_signer[12] = [vec![0xaa; 10], sealed[12].clone()].concat();
+        let mut withdrawals_root = sealed.clone();
+        withdrawals_root.extend([int(7), vec![0x55; 32]]);
+        let mut padded_gas_limit = sealed.clone();
+        padded_gas_limit[9].insert(0, 0);
+        let mut long_timestamp = sealed.clone();
+        long_timestamp[11] = vec![1; 9];
+        let mut short_coinbase = sealed.clone();
+        short_coinbase[2].pop();
+        let mut trailing_byte = encode_header(&sealed);
+        trailing_byte.push(0);
+        for header in [
+            encode_header(&short_extra),
+            encode_header(&partial_signer),
+            encode_header(&withdrawals_root),
+            encode_header(&padded_gas_limit),
+            encode_header(&long_timestamp),
+            encode_header(&short_coinbase),
+            encode_header(&sealed[..14]),
+            trailing_byte,
+            Vec::new(),
+        ] {
+            assert_eq!(recover_clique_signer(&header), Err(Error::CliqueMalformed));
+        }
+
+        // A base fee of 7 encoded as a string of length one is not canonical.
+        let (_, sealed) = seal(&unsealed_fields(vanity, Some(7)));
+        let mut header = encode_header(&sealed);
+        assert_eq!(header.pop(), Some(7));
+        header.extend_from_slice(&[0x81, 7]);
+        let payload_len = u16::from_be_bytes([header[2], header[3]]) + 1;
+        header[2..4].copy_from_slice(&payload_len.to_be_bytes());
+        assert_eq!(recover_clique_signer(&header), Err(Error::CliqueMalformed));
+    }
+}
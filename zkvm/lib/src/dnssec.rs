@@ -0,0 +1,458 @@
+//! Verification of DNSSEC RRSIG records signed with ECDSAP256SHA256, algorithm 13 of RFC 6605.
+//!
+//! Domain names are in the DNS wire format: a sequence of length-prefixed labels, ending with the
+//! empty root label.
+
+use crate::error::Error;
+use crate::sha256::sha256;
+use alloc::vec::Vec;
+use p256::ecdsa::signature::hazmat::PrehashVerifier;
+
+/// The algorithm number of ECDSA over P-256 with SHA-256.
+pub const ALGORITHM_ECDSAP256SHA256: u8 = 13;
+
+/// The DNSKEY flag of zone keys, the only ones allowed to sign zone data.
+const FLAG_ZONE_KEY: u16 = 0x0100;
+/// The protocol field of all DNSKEY records.
+const DNSKEY_PROTOCOL: u8 = 3;
+
+const TYPE_NS: u16 = 2;
+const TYPE_CNAME: u16 = 5;
+const TYPE_SOA: u16 = 6;
+const TYPE_PTR: u16 = 12;
+const TYPE_MX: u16 = 15;
+const TYPE_SRV: u16 = 33;
+const TYPE_DNAME: u16 = 39;
+
+/// A resource record of the RRset covered by a signature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResourceRecord {
+    /// The owner name, in wire format.
+    pub name: Vec<u8>,
+    pub rtype: u16,
+    pub class: u16,
+    /// The TTL as served, which is replaced by the original TTL of the signature.
+    pub ttl: u32,
+    /// The RDATA in wire format, without name compression.
+    pub rdata: Vec<u8>,
+}
+
+/// The RDATA of an RRSIG record.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rrsig {
+    pub type_covered: u16,
+    pub algorithm: u8,
+    /// The number of labels of the owner name, without the root and any wildcard label.
+    pub labels: u8,
+    pub original_ttl: u32,
+    /// The end of the validity period, in seconds since the Unix epoch modulo 2^32.
+    pub expiration: u32,
+    /// The start of the validity period, in seconds since the Unix epoch modulo 2^32.
+    pub inception: u32,
+    pub key_tag: u16,
+    /// The name of the zone that signed the records, in wire format.
+    pub signer_name: Vec<u8>,
+    /// The raw `r || s` signature.
+    pub signature: Vec<u8>,
+}
+
+/// The RDATA of a DNSKEY record.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Dnskey {
+    pub flags: u16,
+    pub protocol: u8,
+    pub algorithm: u8,
+    /// The public key, which is `x || y` for algorithm 13.
+    pub public_key: Vec<u8>,
+}
+
+impl Dnskey {
+    /// Computes the key tag of the key, as in RFC 4034 Appendix B.
+    pub fn key_tag(&self) -> u16 {
+        let mut acc: u32 = 0;
+        for (i, &byte) in self.rdata().iter().enumerate() {
+            acc += if i % 2 == 0 {
+                (byte as u32) << 8
+            } else {
+                byte as u32
+            };
+        }
+        acc += (acc >> 16) & 0xffff;
+        acc as u16
+    }
+
+    fn rdata(&self) -> Vec<u8> {
+        let mut rdata = Vec::with_capacity(4 + self.public_key.len());
+        rdata.extend_from_slice(&self.flags.to_be_bytes());
+        rdata.push(self.protocol);
+        rdata.push(self.algorithm);
+        rdata.extend_from_slice(&self.public_key);
+        rdata
+    }
+}
+
+/// Verifies an RRSIG over an RRset with the DNSKEY of the signer, building the signed data in
+/// the canonical form of RFC 4034: lowercased names, the original TTL in place of the served one,
+/// records sorted by RDATA without duplicates, and the wildcard owner name if the signature
+/// covers a wildcard expansion.
+///
+/// Names embedded in the RDATA of NS, CNAME, SOA, PTR, MX, SRV and DNAME records are lowercased;
+/// the RDATA of other types is used as is. The validity period of the signature is not checked,
+/// and the caller must check that the DNSKEY is published at the signer name.
+///
+/// Returns `Ok(false)` if the signature does not verify. Returns
+/// [`Error::DnssecUnsupportedAlgorithm`] for algorithms other than 13,
+/// [`Error::DnssecKeyMismatch`] if the key is not a zone key with the signature's key tag, and
+/// [`Error::DnssecMalformed`] if the records are not a single RRset covered by the signature.
+pub fn verify_rrsig(
+    rrset: &[ResourceRecord],
+    rrsig: &Rrsig,
+    dnskey: &Dnskey,
+) -> Result<bool, Error> {
+    if rrsig.algorithm != ALGORITHM_ECDSAP256SHA256 {
+        return Err(Error::DnssecUnsupportedAlgorithm(rrsig.algorithm));
+    }
+    if dnskey.algorithm != ALGORITHM_ECDSAP256SHA256 {
+        return Err(Error::DnssecUnsupportedAlgorithm(dnskey.algorithm));
+    }
+    if dnskey.protocol != DNSKEY_PROTOCOL
+        || dnskey.flags & FLAG_ZONE_KEY == 0
+        || dnskey.key_tag() != rrsig.key_tag
+    {
+        return Err(Error::DnssecKeyMismatch);
+    }
+
+    let (first, _) = rrset.split_first().ok_or(Error::DnssecMalformed)?;
+    let name = canonical_name(&first.name).ok_or(Error::DnssecMalformed)?;
+    let owner = signed_owner_name(&name, rrsig.labels).ok_or(Error::DnssecMalformed)?;
+
+    let mut rdatas = Vec::with_capacity(rrset.len());
+    for record in rrset {
+        if record.rtype != rrsig.type_covered
+            || record.class != first.class
+            || canonical_name(&record.name).as_ref() != Some(&name)
+        {
+            return Err(Error::DnssecMalformed);
+        }
+        rdatas.push(canonical_rdata(record.rtype, &record.rdata).ok_or(Error::DnssecMalformed)?);
+    }
+    // RRs are sorted as left-justified octet sequences, which is how slices compare.
+    rdatas.sort();
+    rdatas.dedup();
+
+    // The RRSIG RDATA without the signature, then each record.
+    let signer_name = canonical_name(&rrsig.signer_name).ok_or(Error::DnssecMalformed)?;
+    let mut signed_data = Vec::new();
+    signed_data.extend_from_slice(&rrsig.type_covered.to_be_bytes());
+    signed_data.push(rrsig.algorithm);
+    signed_data.push(rrsig.labels);
+    signed_data.extend_from_slice(&rrsig.original_ttl.to_be_bytes());
+    signed_data.extend_from_slice(&rrsig.expiration.to_be_bytes());
+    signed_data.extend_from_slice(&rrsig.inception.to_be_bytes());
+    signed_data.extend_from_slice(&rrsig.key_tag.to_be_bytes());
+    signed_data.extend_from_slice(&signer_name);
+    for rdata in &rdatas {
+        signed_data.extend_from_slice(&owner);
+        signed_data.extend_from_slice(&rrsig.type_covered.to_be_bytes());
+        signed_data.extend_from_slice(&first.class.to_be_bytes());
+        signed_data.extend_from_slice(&rrsig.original_ttl.to_be_bytes());
+        let rdlength = u16::try_from(rdata.len()).map_err(|_| Error::DnssecMalformed)?;
+        signed_data.extend_from_slice(&rdlength.to_be_bytes());
+        signed_data.extend_from_slice(rdata);
+    }
+
+    if dnskey.public_key.len() != 64 || rrsig.signature.len() != 64 {
+        return Err(Error::DnssecMalformed);
+    }
+    let mut sec1 = [4u8; 65];
+    sec1[1..].copy_from_slice(&dnskey.public_key);
+    let key =
+        p256::ecdsa::VerifyingKey::from_sec1_bytes(&sec1).map_err(|_| Error::InvalidPublicKey)?;
+    let Ok(signature) = p256::ecdsa::Signature::from_slice(&rrsig.signature) else {
+        return Ok(false);
+    };
+    Ok(key
+        .verify_prehash(&sha256(&signed_data), &signature)
+        .is_ok())
+}
+
+/// Checks that a name is in wire format and lowercases its ASCII letters.
+fn canonical_name(name: &[u8]) -> Option<Vec<u8>> {
+    if name.len() > 255 {
+        return None;
+    }
+    let mut pos = 0;
+    loop {
+        let len = *name.get(pos)? as usize;
+        // Compression pointers and extended label types are not allowed.
+        if len > 63 {
+            return None;
+        }
+        pos += 1 + len;
+        if len == 0 {
+            break;
+        }
+    }
+    if pos != name.len() {
+        return None;
+    }
+    // The length bytes are below 64, so only the letters of the labels are changed.
+    Some(name.to_ascii_lowercase())
+}
+
+/// Returns the owner name that is signed for a name with `labels` labels in the signature: the
+/// name itself, or the wildcard that it was expanded from if it has more labels.
+fn signed_owner_name(name: &[u8], labels: u8) -> Option<Vec<u8>> {
+    let mut starts = Vec::new();
+    let mut pos = 0;
+    while name[pos] != 0 {
+        starts.push(pos);
+        pos += 1 + name[pos] as usize;
+    }
+    // A wildcard label is not counted.
+    let count = match name {
+        [1, b'*', ..] => starts.len() - 1,
+        _ => starts.len(),
+    };
+
+    let labels = labels as usize;
+    if labels > count {
+        return None;
+    }
+    if labels == count {
+        return Some(name.to_vec());
+    }
+    let mut wildcard = Vec::from([1, b'*']);
+    wildcard.extend_from_slice(&name[starts[starts.len() - labels]..]);
+    Some(wildcard)
+}
+
+/// Lowercases the names embedded in the RDATA of the types listed in RFC 4034 section 6.2 that
+/// are still in use.
+fn canonical_rdata(rtype: u16, rdata: &[u8]) -> Option<Vec<u8>> {
+    let (prefix_len, num_names) = match rtype {
+        TYPE_NS | TYPE_CNAME | TYPE_PTR | TYPE_DNAME => (0, 1),
+        TYPE_MX => (2, 1),
+        TYPE_SRV => (6, 1),
+        TYPE_SOA => (0, 2),
+        _ => return Some(rdata.to_vec()),
+    };
+
+    let mut canonical = rdata.get(..prefix_len)?.to_vec();
+    let mut rest = &rdata[prefix_len..];
+    for _ in 0..num_names {
+        let len = name_len(rest)?;
+        canonical.extend_from_slice(&canonical_name(&rest[..len])?);
+        rest = &rest[len..];
+    }
+    canonical.extend_from_slice(rest);
+    Some(canonical)
+}
+
+/// Returns the length of the wire format name at the start of `bytes`.
+fn name_len(bytes: &[u8]) -> Option<usize> {
+    let mut pos = 0;
+    loop {
+        let len = *bytes.get(pos)? as usize;
+        pos += 1 + len;
+        if len == 0 {
+            return Some(pos);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::decode_base64;
+    use p256::ecdsa::signature::hazmat::PrehashSigner;
+    use std::vec;
+
+    const TYPE_A: u16 = 1;
+    const CLASS_IN: u16 = 1;
+
+    fn name(labels: &[&str]) -> Vec<u8> {
+        let mut name = Vec::new();
+        for label in labels {
+            name.push(label.len() as u8);
+            name.extend_from_slice(label.as_bytes());
+        }
+        name.push(0);
+        name
+    }
+
+    fn a_record(owner: &[&str], address: [u8; 4]) -> ResourceRecord {
+        ResourceRecord {
+            name: name(owner),
+            rtype: TYPE_A,
+            class: CLASS_IN,
+            ttl: 3600,
+            rdata: address.to_vec(),
+        }
+    }
+
+    /// The example of RFC 6605 section 6.1: the A record of www.example.net, signed by the
+    /// key-signing key of example.net.
+    fn rfc6605_example() -> (ResourceRecord, Rrsig, Dnskey) {
+        let dnskey = Dnskey {
+            flags: 257,
+            protocol: 3,
+            algorithm: 13,
+            public_key: decode_base64(
+                "GojIhhXUN/u4v54ZQqGSnyhWJwaubCvTmeexv7bR6edbkrSqQpF64cYbcB7wNcP+e+MAnLr+Wi9xMWyQLc8NAA==",
+            )
+            .unwrap(),
+        };
+        let rrsig = Rrsig {
+            type_covered: TYPE_A,
+            algorithm: 13,
+            labels: 3,
+            original_ttl: 3600,
+            // 20100909100439 and 20100812100439.
+            expiration: 1284026679,
+            inception: 1281607479,
+            key_tag: 55648,
+            signer_name: name(&["example", "net"]),
+            signature: decode_base64(
+                "qx6wLYqmh+l9oCKTN6qIc+bw6ya+KJ8oMz0YP107epXAyGmt+3SNruPFKG7tZoLBLlUzGGus7ZwmwWep666VCw==",
+            )
+            .unwrap(),
+        };
+        (
+            a_record(&["www", "example", "net"], [192, 0, 2, 1]),
+            rrsig,
+            dnskey,
+        )
+    }
+
+    #[test]
+    fn test_verify_rrsig_rfc6605_example() {
+        let (record, rrsig, dnskey) = rfc6605_example();
+        assert_eq!(dnskey.key_tag(), 55648);
+        assert_eq!(verify_rrsig(&[record.clone()], &rrsig, &dnskey), Ok(true));
+
+        // The signed data is canonical: names are lowercased, the served TTL is replaced, and
+        // duplicate records are dropped.
+        let mut served = a_record(&["WWW", "Example", "NET"], [192, 0, 2, 1]);
+        served.ttl = 1234;
+        assert_eq!(
+            verify_rrsig(&[served.clone(), served], &rrsig, &dnskey),
+            Ok(true)
+        );
+
+        let other = a_record(&["www", "example", "net"], [192, 0, 2, 2]);
+        assert_eq!(verify_rrsig(&[other.clone()], &rrsig, &dnskey), Ok(false));
+        assert_eq!(
+            verify_rrsig(&[record.clone(), other], &rrsig, &dnskey),
+            Ok(false)
+        );
+        let mut tampered = rrsig.clone();
+        tampered.expiration += 1;
+        assert_eq!(
+            verify_rrsig(&[record.clone()], &tampered, &dnskey),
+            Ok(false)
+        );
+        let mut tampered = rrsig;
+        tampered.signature[63] ^= 1;
+        assert_eq!(verify_rrsig(&[record], &tampered, &dnskey), Ok(false));
+    }
+
+    #[test]
+    fn test_verify_rrsig_wildcard() {
+        let key = p256::ecdsa::SigningKey::from_slice(&[0x5a; 32]).unwrap();
+        let point = key.verifying_key().to_encoded_point(false);
+        let dnskey = Dnskey {
+            flags: 256,
+            protocol: 3,
+            algorithm: 13,
+            public_key: point.as_bytes()[1..].to_vec(),
+        };
+        let mut rrsig = Rrsig {
+            type_covered: TYPE_A,
+            algorithm: 13,
+            labels: 2,
+            original_ttl: 300,
+            expiration: 1735689600,
+            inception: 1733011200,
+            key_tag: dnskey.key_tag(),
+            signer_name: name(&["example", "net"]),
+            signature: Vec::new(),
+        };
+
+        // The signature over *.example.net, made as a signer would.
+        let mut signed_data = vec![0, 1, 13, 2];
+        for field in [300, 1735689600, 1733011200] {
+            signed_data.extend_from_slice(&u32::to_be_bytes(field));
+        }
+        signed_data.extend_from_slice(&rrsig.key_tag.to_be_bytes());
+        signed_data.extend_from_slice(&name(&["example", "net"]));
+        signed_data.extend_from_slice(&name(&["*", "example", "net"]));
+        signed_data.extend_from_slice(&[0, 1, 0, 1, 0, 0, 1, 44, 0, 4, 198, 51, 100, 7]);
+        let signature: p256::ecdsa::Signature = key.sign_prehash(&sha256(&signed_data)).unwrap();
+        rrsig.signature = signature.to_bytes().to_vec();
+
+        let expanded = a_record(&["a", "b", "example", "net"], [198, 51, 100, 7]);
+        assert_eq!(verify_rrsig(&[expanded], &rrsig, &dnskey), Ok(true));
+        let wildcard = a_record(&["*", "example", "net"], [198, 51, 100, 7]);
+        assert_eq!(verify_rrsig(&[wildcard], &rrsig, &dnskey), Ok(true));
+        // The signature covers more labels than the name has.
+        let apex = a_record(&["example", "net"], [198, 51, 100, 7]);
+        rrsig.labels = 3;
+        assert_eq!(
+            verify_rrsig(&[apex], &rrsig, &dnskey),
+            Err(Error::DnssecMalformed)
+        );
+    }
+
+    #[test]
+    fn test_verify_rrsig_rejects_mismatched_inputs() {
+        let (record, rrsig, dnskey) = rfc6605_example();
+
+        let mut rsa = rrsig.clone();
+        rsa.algorithm = 8;
+        assert_eq!(
+            verify_rrsig(&[record.clone()], &rsa, &dnskey),
+            Err(Error::DnssecUnsupportedAlgorithm(8))
+        );
+
+        let mut other_tag = rrsig.clone();
+        other_tag.key_tag += 1;
+        assert_eq!(
+            verify_rrsig(&[record.clone()], &other_tag, &dnskey),
+            Err(Error::DnssecKeyMismatch)
+        );
+        let mut not_zone_key = dnskey.clone();
+        not_zone_key.flags = 1;
+        let mut matching_tag = rrsig.clone();
+        matching_tag.key_tag = not_zone_key.key_tag();
+        assert_eq!(
+            verify_rrsig(&[record.clone()], &matching_tag, &not_zone_key),
+            Err(Error::DnssecKeyMismatch)
+        );
+
+        let mut txt = record.clone();
+        txt.rtype = 16;
+        let mut other_owner = record.clone();
+        other_owner.name = name(&["mail", "example", "net"]);
+        let mut compressed = record.clone();
+        compressed.name = vec![3, b'w', b'w', b'w', 0xc0, 12];
+        for rrset in [
+            vec![],
+            vec![record.clone(), txt],
+            vec![record.clone(), other_owner],
+            vec![compressed],
+        ] {
+            assert_eq!(
+                verify_rrsig(&rrset, &rrsig, &dnskey),
+                Err(Error::DnssecMalformed)
+            );
+        }
+
+        let mut short_signature = rrsig.clone();
+        short_signature.signature.pop();
+        assert_eq!(
+            verify_rrsig(&[record], &short_signature, &dnskey),
+            Err(Error::DnssecMalformed)
+        );
+    }
+}
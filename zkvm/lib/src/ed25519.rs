@@ -0,0 +1,755 @@
+//! Edwards25519 point arithmetic on top of the ed_add and ed_decompress precompiles.
+//!
+//! Points are kept in affine coordinates, as the little endian limbs `x || y`. The Edwards
+//! addition law is complete, so the identity and doublings need no special cases.
+//!
+//! Ed25519 signatures can be checked with [`verify`], or with [`verify_with_mode`] to match the
+//! acceptance rules of a given ecosystem. With the `alloc` feature, [`verify_batch`] checks many
+//! signatures at once.
+
+use crate::utils::{AffinePoint, CurveOperations};
+use crate::{syscall_ed_add, syscall_ed_decompress};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::convert::TryInto;
+use sha2::{Digest, Sha512};
+
+const NUM_WORDS: usize = 16;
+
+/// The order of the base point, `2^252 + 27742317777372353535851937790883648493`, as little endian
+/// 64-bit limbs.
+const L: [u64; 4] = [
+    0x5812631a5cf5d3ed,
+    0x14def9dea2f79cd6,
+    0,
+    0x1000000000000000,
+];
+
+/// The field modulus `2^255 - 19`, as little endian 64-bit limbs.
+const P: [u64; 4] = [
+    0xffffffffffffffed,
+    0xffffffffffffffff,
+    0xffffffffffffffff,
+    0x7fffffffffffffff,
+];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Ed25519Operations;
+
+impl CurveOperations<NUM_WORDS> for Ed25519Operations {
+    /// The base point from RFC 8032, with y = 4/5.
+    const GENERATOR: [u32; NUM_WORDS] = [
+        2401621274, 3377868128, 2502272946, 1764542304, 4258716764, 3232031281, 3446559742,
+        560543443, 1717986904, 1717986918, 1717986918, 1717986918, 1717986918, 1717986918,
+        1717986918, 1717986918,
+    ];
+
+    fn add_assign(limbs: &mut [u32; NUM_WORDS], other: &[u32; NUM_WORDS]) {
+        let mut other = *other;
+        unsafe {
+            syscall_ed_add(limbs.as_mut_ptr(), other.as_mut_ptr());
+        }
+    }
+
+    fn double(limbs: &mut [u32; NUM_WORDS]) {
+        let other = *limbs;
+        Self::add_assign(limbs, &other);
+    }
+}
+
+/// An affine point on the Edwards25519 curve.
+pub type Ed25519AffinePoint = AffinePoint<Ed25519Operations, NUM_WORDS>;
+
+impl Ed25519AffinePoint {
+    /// The identity point `(0, 1)`.
+    pub const IDENTITY: Self = Self::new([0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0]);
+
+    /// Decompresses the 32-byte encoding of a point: the little endian y-coordinate, with the
+    /// parity of x in its top bit.
+    ///
+    /// The precompile only accepts encodings of points on the curve, so the encoding must be
+    /// known to be valid.
+    pub fn decompress(compressed: &[u8; 32]) -> Self {
+        let mut point = [0u8; 64];
+        point[32..].copy_from_slice(compressed);
+        unsafe {
+            syscall_ed_decompress(&mut point);
+        }
+        Self::from_le_bytes(&point)
+    }
+
+    /// Returns the 32-byte encoding of the point.
+    pub fn compress(&self) -> [u8; 32] {
+        let mut compressed = [0u8; 32];
+        for (chunk, limb) in compressed
+            .chunks_exact_mut(4)
+            .zip(&self.limbs[NUM_WORDS / 2..])
+        {
+            chunk.copy_from_slice(&limb.to_le_bytes());
+        }
+        compressed[31] |= ((self.limbs[0] & 1) as u8) << 7;
+        compressed
+    }
+
+    /// Returns `true` if the point is the identity.
+    pub fn is_identity(&self) -> bool {
+        *self == Self::IDENTITY
+    }
+}
+
+/// Computes `sum(scalars[i] * points[i])`, with the scalars as 32-byte little endian integers, as
+/// `EdwardsPoint::vartime_multiscalar_mul` in curve25519-dalek.
+///
+/// Scalars need not be reduced. The computation is not constant time.
+pub fn vartime_multiscalar_mul<'a, I, J>(scalars: I, points: J) -> Ed25519AffinePoint
+where
+    I: IntoIterator<Item = &'a [u8; 32]>,
+    J: IntoIterator<Item = &'a Ed25519AffinePoint>,
+{
+    let mut res = Ed25519AffinePoint::IDENTITY;
+    for (scalar, point) in scalars.into_iter().zip(points) {
+        // The multiples of the point above the most significant byte of the scalar are unused.
+        let Some(last) = scalar.iter().rposition(|&byte| byte != 0) else {
+            continue;
+        };
+        let mut temp = *point;
+        for &byte in &scalar[..=last] {
+            for i in 0..8 {
+                if (byte >> i) & 1 == 1 {
+                    res.add_assign(&temp);
+                }
+                temp.double();
+            }
+        }
+    }
+    res
+}
+
+/// Verifies an Ed25519 signature of `message` as defined in RFC 8032, by checking that
+/// `[S]B = R + [k]A` with `k = SHA-512(R || A || message)`. The equation is checked without
+/// multiplying by the cofactor, and `R` is compared in its encoded form.
+///
+/// Returns `false` if `S` is not below the order of the base point. The public key is
+/// decompressed with [`Ed25519AffinePoint::decompress`], so it must be a valid encoding.
+pub fn verify(public_key: &[u8; 32], message: &[u8], signature: &[u8; 64]) -> bool {
+    let (r, s) = signature.split_at(32);
+    let s: [u8; 32] = s.try_into().unwrap();
+    if !lt(&le_bytes_to_limbs(&s), &L) {
+        return false;
+    }
+
+    let k = challenge(r, public_key, message);
+
+    // [S]B + [k](-A). Negating the scalar instead would give another point when A has a
+    // component of small order, as L is not a multiple of 8.
+    let points = [
+        Ed25519AffinePoint::generator_in_affine(),
+        negate(&Ed25519AffinePoint::decompress(public_key)),
+    ];
+    let scalars = [s, limbs_to_le_bytes(&k)];
+    vartime_multiscalar_mul(&scalars, &points).compress()[..] == *r
+}
+
+/// The rules that decide which edge cases an Ed25519 verification accepts. In every mode, `S`
+/// must be below the order of the base point, and `A` and `R` must be encodings of points on the
+/// curve, as points are decompressed with the precompile.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VerificationMode {
+    /// Rejects non-canonical encodings of `A` and `R`, whose y-coordinate is not below the field
+    /// modulus or which encode x = 0 with the sign bit set, and components of small order, like
+    /// `verify_strict` in ed25519-dalek. The equation is checked without the cofactor.
+    Strict,
+    /// The rules of ZIP-215, used by Zcash, Solana and CometBFT: non-canonical encodings and
+    /// components of small order are accepted, and the cofactored equation
+    /// `[8][S]B = [8]R + [8][k]A` is checked.
+    Zip215,
+    /// The rules of [`verify`]: the equation is checked without the cofactor, and `R` is compared
+    /// in its encoded form, so a non-canonical `R` is rejected. Components of small order are
+    /// accepted.
+    Legacy,
+}
+
+/// Verifies an Ed25519 signature of `message` with the acceptance rules of `mode`.
+///
+/// The challenge `k = SHA-512(R || A || message)` always hashes the encodings as given, even
+/// when they are not canonical.
+pub fn verify_with_mode(
+    public_key: &[u8; 32],
+    message: &[u8],
+    signature: &[u8; 64],
+    mode: VerificationMode,
+) -> bool {
+    let r: &[u8; 32] = signature[..32].try_into().unwrap();
+    match mode {
+        VerificationMode::Legacy => return verify(public_key, message, signature),
+        VerificationMode::Strict if !is_canonical(public_key) || !is_canonical(r) => return false,
+        _ => {}
+    }
+    let s: [u8; 32] = signature[32..].try_into().unwrap();
+    if !lt(&le_bytes_to_limbs(&s), &L) {
+        return false;
+    }
+
+    let a = decompress_reduced(public_key);
+    let r_point = decompress_reduced(r);
+    if mode == VerificationMode::Strict && (has_small_order(&a) || has_small_order(&r_point)) {
+        return false;
+    }
+
+    // [S]B + [k](-A) + (-R), with the points negated as in `verify`.
+    let k = challenge(r, public_key, message);
+    let mut one = [0u8; 32];
+    one[0] = 1;
+    let points = [
+        Ed25519AffinePoint::generator_in_affine(),
+        negate(&a),
+        negate(&r_point),
+    ];
+    let scalars = [s, limbs_to_le_bytes(&k), one];
+    let mut difference = vartime_multiscalar_mul(&scalars, &points);
+    if mode == VerificationMode::Zip215 {
+        for _ in 0..3 {
+            difference.double();
+        }
+    }
+    difference.is_identity()
+}
+
+/// Verifies a batch of Ed25519 signatures with the rules of [`VerificationMode::Zip215`], as
+/// `verify_batch` in ed25519-dalek. Returns `true` if every signature is valid, and `false` if
+/// the slices have different lengths.
+///
+/// The cofactored equations are summed with 128-bit coefficients derived from a hash of the
+/// whole batch, so that a batch with an invalid signature is accepted with probability about
+/// `2^-128`. The sum computes `[S]B` once for the batch, and multiplies each `R` by a 128-bit
+/// coefficient only, which saves about a quarter of the point operations of separate checks.
+#[cfg(feature = "alloc")]
+pub fn verify_batch(messages: &[&[u8]], signatures: &[[u8; 64]], public_keys: &[[u8; 32]]) -> bool {
+    if messages.len() != signatures.len() || signatures.len() != public_keys.len() {
+        return false;
+    }
+
+    let mut transcript = Sha512::new();
+    let mut challenges = Vec::with_capacity(signatures.len());
+    for ((message, signature), public_key) in messages.iter().zip(signatures).zip(public_keys) {
+        let s: [u8; 32] = signature[32..].try_into().unwrap();
+        if !lt(&le_bytes_to_limbs(&s), &L) {
+            return false;
+        }
+        let k = challenge(&signature[..32], public_key, message);
+        transcript.update(signature);
+        transcript.update(public_key);
+        transcript.update(limbs_to_le_bytes(&k));
+        challenges.push(k);
+    }
+    let seed = transcript.finalize();
+
+    // sum([z S]B + [z k](-A) + [z](-R)), with the points negated as in `verify`.
+    let mut base_scalar = [0u64; 4];
+    let mut sum = Ed25519AffinePoint::IDENTITY;
+    for (i, ((signature, public_key), k)) in signatures
+        .iter()
+        .zip(public_keys)
+        .zip(challenges)
+        .enumerate()
+    {
+        let mut hasher = Sha512::new();
+        hasher.update(seed);
+        hasher.update((i as u64).to_le_bytes());
+        let hash = hasher.finalize();
+        let z = [
+            u64::from_le_bytes(hash[..8].try_into().unwrap()),
+            u64::from_le_bytes(hash[8..16].try_into().unwrap()),
+            0,
+            0,
+        ];
+
+        let s: [u8; 32] = signature[32..].try_into().unwrap();
+        base_scalar = add_mod_l(&base_scalar, &mul_mod_l(&z, &le_bytes_to_limbs(&s)));
+        let r: &[u8; 32] = signature[..32].try_into().unwrap();
+        let points = [
+            negate(&decompress_reduced(public_key)),
+            negate(&decompress_reduced(r)),
+        ];
+        let scalars = [limbs_to_le_bytes(&mul_mod_l(&z, &k)), limbs_to_le_bytes(&z)];
+        sum.add_assign(&vartime_multiscalar_mul(&scalars, &points));
+    }
+    sum.add_assign(&vartime_multiscalar_mul(
+        &[limbs_to_le_bytes(&base_scalar)],
+        &[Ed25519AffinePoint::generator_in_affine()],
+    ));
+    for _ in 0..3 {
+        sum.double();
+    }
+    sum.is_identity()
+}
+
+/// Returns `k = SHA-512(R || A || message)` reduced modulo `L`.
+fn challenge(r: &[u8], public_key: &[u8; 32], message: &[u8]) -> [u64; 4] {
+    let mut hasher = Sha512::new();
+    hasher.update(r);
+    hasher.update(public_key);
+    hasher.update(message);
+    reduce(&hasher.finalize())
+}
+
+/// Returns whether an encoding is canonical: its y-coordinate is below the field modulus, and
+/// it does not set the sign bit of x = 0, the x-coordinate of the points with y = 1 and y = -1.
+pub fn is_canonical(encoded: &[u8; 32]) -> bool {
+    let mut y = le_bytes_to_limbs(encoded);
+    let sign = y[3] >> 63 == 1;
+    y[3] &= !(1 << 63);
+    let minus_one = [P[0] - 1, P[1], P[2], P[3]];
+    lt(&y, &P) && !(sign && (y == [1, 0, 0, 0] || y == minus_one))
+}
+
+/// Decompresses an encoding whose y-coordinate may not be below the field modulus, or which may
+/// set the sign bit of x = 0, as curve25519-dalek does.
+fn decompress_reduced(encoded: &[u8; 32]) -> Ed25519AffinePoint {
+    let mut y = le_bytes_to_limbs(encoded);
+    let sign = y[3] >> 63;
+    y[3] &= !(1 << 63);
+    if !lt(&y, &P) {
+        sub_assign(&mut y, &P);
+    }
+    let minus_one = [P[0] - 1, P[1], P[2], P[3]];
+    let mut canonical = limbs_to_le_bytes(&y);
+    if y != [1, 0, 0, 0] && y != minus_one {
+        canonical[31] |= (sign as u8) << 7;
+    }
+    Ed25519AffinePoint::decompress(&canonical)
+}
+
+/// Returns `-point`, which has the opposite x-coordinate.
+fn negate(point: &Ed25519AffinePoint) -> Ed25519AffinePoint {
+    let mut x = [0u8; 32];
+    for (chunk, limb) in x.chunks_exact_mut(4).zip(&point.limbs[..NUM_WORDS / 2]) {
+        chunk.copy_from_slice(&limb.to_le_bytes());
+    }
+    let x = le_bytes_to_limbs(&x);
+    let mut minus_x = [0u64; 4];
+    if x != [0; 4] {
+        minus_x = P;
+        sub_assign(&mut minus_x, &x);
+    }
+    let mut limbs = point.limbs;
+    for (words, limb) in limbs[..NUM_WORDS / 2].chunks_exact_mut(2).zip(minus_x) {
+        words[0] = limb as u32;
+        words[1] = (limb >> 32) as u32;
+    }
+    Ed25519AffinePoint::new(limbs)
+}
+
+/// Returns whether a point is in the torsion subgroup of order 8.
+fn has_small_order(point: &Ed25519AffinePoint) -> bool {
+    let mut point = *point;
+    for _ in 0..3 {
+        point.double();
+    }
+    point.is_identity()
+}
+
+/// Reduces a little endian integer modulo `L`, one bit at a time from the most significant one.
+fn reduce(bytes: &[u8]) -> [u64; 4] {
+    let mut res = [0u64; 4];
+    for &byte in bytes.iter().rev() {
+        for i in (0..8).rev() {
+            // The result is below L < 2^253, so shifting a bit in cannot overflow.
+            let mut carry = ((byte >> i) & 1) as u64;
+            for limb in res.iter_mut() {
+                let next = *limb >> 63;
+                *limb = (*limb << 1) | carry;
+                carry = next;
+            }
+            if !lt(&res, &L) {
+                sub_assign(&mut res, &L);
+            }
+        }
+    }
+    res
+}
+
+/// Returns `a * b` reduced modulo `L`.
+#[cfg(feature = "alloc")]
+fn mul_mod_l(a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
+    let mut product = [0u64; 8];
+    for (i, &a) in a.iter().enumerate() {
+        let mut carry = 0u128;
+        for (j, &b) in b.iter().enumerate() {
+            let limb = product[i + j] as u128 + a as u128 * b as u128 + carry;
+            product[i + j] = limb as u64;
+            carry = limb >> 64;
+        }
+        product[i + 4] = carry as u64;
+    }
+    let mut bytes = [0u8; 64];
+    for (chunk, limb) in bytes.chunks_exact_mut(8).zip(product) {
+        chunk.copy_from_slice(&limb.to_le_bytes());
+    }
+    reduce(&bytes)
+}
+
+/// Returns `a + b` reduced modulo `L`, for `a` and `b` below `L`.
+#[cfg(feature = "alloc")]
+fn add_mod_l(a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
+    // Both are below L < 2^253, so the sum cannot overflow.
+    let mut sum = [0u64; 4];
+    let mut carry = false;
+    for ((sum, a), b) in sum.iter_mut().zip(a).zip(b) {
+        let (limb, carry_a) = a.overflowing_add(*b);
+        let (limb, carry_b) = limb.overflowing_add(carry as u64);
+        *sum = limb;
+        carry = carry_a || carry_b;
+    }
+    if !lt(&sum, &L) {
+        sub_assign(&mut sum, &L);
+    }
+    sum
+}
+
+fn lt(a: &[u64; 4], b: &[u64; 4]) -> bool {
+    for (a, b) in a.iter().zip(b).rev() {
+        if a != b {
+            return a < b;
+        }
+    }
+    false
+}
+
+fn sub_assign(a: &mut [u64; 4], b: &[u64; 4]) {
+    let mut borrow = false;
+    for (a, b) in a.iter_mut().zip(b) {
+        let (diff, borrow_a) = a.overflowing_sub(*b);
+        let (diff, borrow_b) = diff.overflowing_sub(borrow as u64);
+        *a = diff;
+        borrow = borrow_a || borrow_b;
+    }
+}
+
+fn le_bytes_to_limbs(bytes: &[u8; 32]) -> [u64; 4] {
+    let mut limbs = [0u64; 4];
+    for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks_exact(8)) {
+        *limb = u64::from_le_bytes(chunk.try_into().unwrap());
+    }
+    limbs
+}
+
+fn limbs_to_le_bytes(limbs: &[u64; 4]) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    for (chunk, limb) in bytes.chunks_exact_mut(8).zip(limbs) {
+        chunk.copy_from_slice(&limb.to_le_bytes());
+    }
+    bytes
+}
+
+/// The ed_add and ed_decompress precompiles, for the tests of this crate on the host, where only
+/// the other curves have a fallback, and an RFC 8032 signer.
+#[cfg(all(test, not(target_os = "zkvm")))]
+pub(crate) mod host {
+    use super::{vartime_multiscalar_mul, Ed25519AffinePoint, L};
+    use num_bigint::BigUint;
+    use sha2::{Digest, Sha512};
+
+    fn modulus() -> BigUint {
+        (BigUint::from(1u32) << 255u32) - 19u32
+    }
+
+    fn inv(x: &BigUint) -> BigUint {
+        x.modinv(&modulus()).expect("no inverse of zero")
+    }
+
+    /// The curve constant `d = -121665 / 121666`.
+    fn d() -> &'static BigUint {
+        static D: std::sync::OnceLock<BigUint> = std::sync::OnceLock::new();
+        D.get_or_init(|| {
+            let p = modulus();
+            (&p - 121665u32) * inv(&BigUint::from(121666u32)) % &p
+        })
+    }
+
+    fn from_words(words: &[u32]) -> BigUint {
+        BigUint::from_slice(words)
+    }
+
+    fn to_le_bytes(x: &BigUint) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        let le = x.to_bytes_le();
+        bytes[..le.len()].copy_from_slice(&le);
+        bytes
+    }
+
+    #[no_mangle]
+    extern "C" fn syscall_ed_add(p: *mut u32, q: *mut u32) {
+        let (p_words, q_words) = unsafe {
+            (
+                core::slice::from_raw_parts_mut(p, 16),
+                core::slice::from_raw_parts(q, 16),
+            )
+        };
+        let m = modulus();
+        let (x1, y1) = (from_words(&p_words[..8]), from_words(&p_words[8..]));
+        let (x2, y2) = (from_words(&q_words[..8]), from_words(&q_words[8..]));
+        let dxy = d() * &x1 * &x2 * &y1 * &y2 % &m;
+        // Both denominators are inverted at once, as the inversions dominate the tests.
+        let (x_den, y_den) = (1u32 + &dxy, &m + 1u32 - &dxy);
+        let den_inv = inv(&(&x_den * &y_den % &m));
+        let x3 = (&x1 * &y2 + &y1 * &x2) * &y_den % &m * &den_inv % &m;
+        let y3 = (&y1 * &y2 + &x1 * &x2) * &x_den % &m * &den_inv % &m;
+        for (words, value) in p_words.chunks_exact_mut(8).zip([x3, y3]) {
+            for (word, chunk) in words.iter_mut().zip(to_le_bytes(&value).chunks_exact(4)) {
+                *word = u32::from_le_bytes(chunk.try_into().unwrap());
+            }
+        }
+    }
+
+    /// Panics if the encoding is not of a point on the curve, as the precompile does.
+    #[no_mangle]
+    extern "C" fn syscall_ed_decompress(point: &mut [u8; 64]) {
+        let m = modulus();
+        let sign = point[63] >> 7;
+        point[63] &= 0x7f;
+        let y = BigUint::from_bytes_le(&point[32..]);
+        assert!(y < m, "non-canonical y-coordinate");
+        let y2 = &y * &y % &m;
+        let x2 = (&y2 + &m - 1u32) * inv(&(d() * &y2 + 1u32)) % &m;
+        let mut x = x2.modpow(&((&m + 3u32) >> 3u32), &m);
+        if &x * &x % &m != x2 {
+            x = x * BigUint::from(2u32).modpow(&((&m - 1u32) >> 2u32), &m) % &m;
+        }
+        assert!(&x * &x % &m == x2, "not a point on the curve");
+        if x.bit(0) != (sign == 1) {
+            x = (&m - x) % &m;
+        }
+        point[..32].copy_from_slice(&to_le_bytes(&x));
+    }
+
+    /// Returns `SHA-512(data)` reduced modulo `L`, as a little endian integer.
+    fn hash_to_scalar(data: &[&[u8]]) -> BigUint {
+        let mut hasher = Sha512::new();
+        for chunk in data {
+            hasher.update(chunk);
+        }
+        BigUint::from_bytes_le(&hasher.finalize()) % order()
+    }
+
+    fn order() -> BigUint {
+        let words: std::vec::Vec<u32> = L
+            .iter()
+            .flat_map(|&limb| [limb as u32, (limb >> 32) as u32])
+            .collect();
+        from_words(&words)
+    }
+
+    /// Signs `message` with the 32-byte secret key `seed` as defined in RFC 8032, returning the
+    /// public key and the signature.
+    pub(crate) fn sign(seed: &[u8; 32], message: &[u8]) -> ([u8; 32], [u8; 64]) {
+        let h = Sha512::digest(seed);
+        let mut a = [0u8; 32];
+        a.copy_from_slice(&h[..32]);
+        a[0] &= 248;
+        a[31] &= 127;
+        a[31] |= 64;
+        let base = Ed25519AffinePoint::generator_in_affine();
+        let public_key = vartime_multiscalar_mul(&[a], &[base]).compress();
+
+        let r = hash_to_scalar(&[&h[32..], message]);
+        let r_encoded = vartime_multiscalar_mul(&[to_le_bytes(&r)], &[base]).compress();
+        let k = hash_to_scalar(&[&r_encoded, &public_key, message]);
+        let s = (r + k * BigUint::from_bytes_le(&a)) % order();
+
+        let mut signature = [0u8; 64];
+        signature[..32].copy_from_slice(&r_encoded);
+        signature[32..].copy_from_slice(&to_le_bytes(&s));
+        (public_key, signature)
+    }
+}
+
+#[cfg(all(test, not(target_os = "zkvm")))]
+mod tests {
+    use super::*;
+    use num_bigint::BigUint;
+    use std::vec::Vec;
+    use VerificationMode::{Legacy, Strict, Zip215};
+
+    const MODES: [VerificationMode; 3] = [Strict, Zip215, Legacy];
+
+    fn bytes<const N: usize>(hex_str: &str) -> [u8; N] {
+        hex::decode(hex_str).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_rfc8032_vectors() {
+        // The secret key, public key, message and signature of the tests 1 to 3 of RFC 8032,
+        // section 7.1.
+        let vectors = [
+            (
+                "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+                "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+                "",
+                "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e06522490155\
+                 5fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+            ),
+            (
+                "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+                "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+                "72",
+                "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da\
+                 085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+            ),
+            (
+                "c5aa8df43f9f837bedb7442f31dcb7b166d38535076f094b85ce3a2e0b4458f7",
+                "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+                "af82",
+                "6291d657deec24024827e69c3abe01a30ce548a284743a445e3680d7db5ac3ac\
+                 18ff9b538d16f290ae67f760984dc6594a7c15e9716ed28dc027beceea1ec40a",
+            ),
+        ];
+        for (secret, public, message, signature) in vectors {
+            let public_key = bytes::<32>(public);
+            let message = hex::decode(message).unwrap();
+            let signature = bytes::<64>(&signature.replace(' ', ""));
+            assert_eq!(
+                host::sign(&bytes(secret), &message),
+                (public_key, signature)
+            );
+            for mode in MODES {
+                assert!(verify_with_mode(&public_key, &message, &signature, mode));
+
+                let mut other_message = message.clone();
+                other_message.push(0);
+                assert!(!verify_with_mode(
+                    &public_key,
+                    &other_message,
+                    &signature,
+                    mode
+                ));
+
+                let mut other_signature = signature;
+                other_signature[32] ^= 1;
+                assert!(!verify_with_mode(
+                    &public_key,
+                    &message,
+                    &other_signature,
+                    mode
+                ));
+
+                // S + L satisfies the equation, but is not reduced.
+                let s = BigUint::from_bytes_le(&signature[32..]);
+                let l = BigUint::from_bytes_le(&limbs_to_le_bytes(&L));
+                let mut unreduced = signature;
+                unreduced[32..].copy_from_slice(&(s + l).to_bytes_le());
+                assert!(!verify_with_mode(&public_key, &message, &unreduced, mode));
+            }
+        }
+    }
+
+    /// The encodings of the points of small order, as in the test vectors of ed25519-zebra: the
+    /// eight canonical ones, then six whose y-coordinate is not reduced or which set the sign of
+    /// x = 0.
+    const SMALL_ORDER_ENCODINGS: [&str; 14] = [
+        "0100000000000000000000000000000000000000000000000000000000000000",
+        "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "0000000000000000000000000000000000000000000000000000000000000080",
+        "26e8958fc2b227b045c3f489f2ef98f0d5dfac05d3c63339b13802886d53fc05",
+        "26e8958fc2b227b045c3f489f2ef98f0d5dfac05d3c63339b13802886d53fc85",
+        "c7176a703d4dd84fba3c0b760d10670f2a2053fa2c39ccc64ec7fd7792ac037a",
+        "c7176a703d4dd84fba3c0b760d10670f2a2053fa2c39ccc64ec7fd7792ac03fa",
+        "0100000000000000000000000000000000000000000000000000000000000080",
+        "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        "edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        "edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        "eeffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        "eeffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+    ];
+
+    /// Every pair of small order `A` and `R` with `S = 0`, which ZIP-215 accepts for any message,
+    /// as the cofactor clears both.
+    #[test]
+    fn test_small_order_vectors() {
+        let message = b"Zcash";
+        let encodings: Vec<[u8; 32]> = SMALL_ORDER_ENCODINGS.iter().map(|e| bytes(e)).collect();
+        let mut legacy_accepted = 0;
+        let mut legacy_checked = 0;
+        for public_key in &encodings {
+            for r in &encodings {
+                let mut signature = [0u8; 64];
+                signature[..32].copy_from_slice(r);
+                assert!(verify_with_mode(public_key, message, &signature, Zip215));
+                assert!(!verify_with_mode(public_key, message, &signature, Strict));
+
+                // Without the cofactor, `[0]B = R + [k]A` holds when `R` is the canonical
+                // encoding of `-[k]A`. The legacy rules decompress `A` with the precompile,
+                // which requires a reduced y-coordinate.
+                let mut y = *public_key;
+                y[31] &= 0x7f;
+                if !lt(&le_bytes_to_limbs(&y), &P) {
+                    continue;
+                }
+                let a = Ed25519AffinePoint::decompress(public_key);
+                let k = limbs_to_le_bytes(&challenge(r, public_key, message));
+                // `A` has an order dividing 8, so `-[k]A = [8 - k mod 8]A`.
+                let mut minus_k_a = Ed25519AffinePoint::IDENTITY;
+                for _ in 0..(8 - k[0] % 8) % 8 {
+                    minus_k_a.add_assign(&a);
+                }
+                let expected = minus_k_a.compress() == *r;
+                assert_eq!(
+                    verify_with_mode(public_key, message, &signature, Legacy),
+                    expected,
+                    "A = {}, R = {}",
+                    hex::encode(public_key),
+                    hex::encode(r)
+                );
+                legacy_checked += 1;
+                legacy_accepted += expected as usize;
+            }
+        }
+        assert_eq!(legacy_checked, 10 * 14);
+        assert!(0 < legacy_accepted && legacy_accepted < legacy_checked);
+    }
+
+    /// Batches of the RFC 8032 vectors and of signatures of small order, which are accepted
+    /// together and rejected as soon as one of them is invalid.
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_verify_batch() {
+        let messages: Vec<Vec<u8>> = (0..4u8).map(|i| vec![i; i as usize * 40]).collect();
+        let mut signatures = Vec::new();
+        let mut public_keys = Vec::new();
+        for (i, message) in messages.iter().enumerate() {
+            let (public_key, signature) = host::sign(&[i as u8 + 1; 32], message);
+            public_keys.push(public_key);
+            signatures.push(signature);
+        }
+        let mut messages: Vec<&[u8]> = messages.iter().map(Vec::as_slice).collect();
+        assert!(verify_batch(&messages, &signatures, &public_keys));
+        assert!(verify_batch(&[], &[], &[]));
+        assert!(!verify_batch(&messages[1..], &signatures, &public_keys));
+
+        // A non-canonical `A` of small order and `R = 0` with `S = 0`, which only ZIP-215 accepts.
+        let small_order: [u8; 32] = bytes(SMALL_ORDER_ENCODINGS[10]);
+        let mut small_order_signature = [0u8; 64];
+        small_order_signature[..32].copy_from_slice(&bytes::<32>(SMALL_ORDER_ENCODINGS[2]));
+        messages.push(b"Zcash");
+        signatures.push(small_order_signature);
+        public_keys.push(small_order);
+        assert!(verify_batch(&messages, &signatures, &public_keys));
+
+        for i in 0..signatures.len() {
+            let mut tampered = signatures.clone();
+            tampered[i][32] ^= 1;
+            assert!(!verify_batch(&messages, &tampered, &public_keys));
+
+            let mut swapped = public_keys.clone();
+            swapped.swap(i, (i + 1) % public_keys.len());
+            assert!(!verify_batch(&messages, &signatures, &swapped));
+        }
+
+        // S + L satisfies the equation, but is not reduced.
+        let s = BigUint::from_bytes_le(&signatures[0][32..]);
+        let l = BigUint::from_bytes_le(&limbs_to_le_bytes(&L));
+        signatures[0][32..].copy_from_slice(&(s + l).to_bytes_le());
+        assert!(!verify_batch(&messages, &signatures, &public_keys));
+    }
+}
@@ -0,0 +1,259 @@
+//! Recovery of the authorities of EIP-7702 authorization tuples.
+//!
+//! An authorization delegates the code of its authority to `address`, and is signed over
+//! `keccak256(0x05 || rlp([chain_id, address, nonce]))`. Like transaction signatures since EIP-2,
+//! its s value must be at most half the curve order.
+
+use crate::error::Error;
+use crate::keccak::keccak256;
+use crate::rlp;
+use crate::secp256k1::{ecrecover_address, ecrecover_batch, pubkey_to_address};
+use alloc::vec::Vec;
+use k256::ecdsa::Signature;
+
+/// The byte prepended to the RLP encoding of an authorization before hashing it.
+pub const MAGIC: u8 = 0x05;
+
+/// A signed authorization tuple, as in the authorization list of a set code transaction.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SignedAuthorization {
+    /// The chain the authorization is valid on, or 0 for every chain.
+    pub chain_id: u64,
+    /// The address whose code is delegated to.
+    pub address: [u8; 20],
+    pub nonce: u64,
+    pub y_parity: bool,
+    pub r: [u8; 32],
+    pub s: [u8; 32],
+}
+
+impl SignedAuthorization {
+    /// Returns the address of the authority, like [`recover_authorization_signer`].
+    pub fn recover_signer(&self) -> Result<[u8; 20], Error> {
+        recover_authorization_signer(
+            self.chain_id,
+            self.address,
+            self.nonce,
+            self.y_parity,
+            &self.r,
+            &self.s,
+        )
+    }
+}
+
+/// Returns the hash signed by an authorization, `keccak256(0x05 || rlp([chain_id, address,
+/// nonce]))`.
+pub fn authorization_hash(chain_id: u64, delegate: [u8; 20], nonce: u64) -> [u8; 32] {
+    // The list is at most 9 + 21 + 9 bytes long, so its length fits in the prefix byte.
+    let mut payload = Vec::with_capacity(39);
+    rlp::put_u64(&mut payload, chain_id);
+    rlp::put_header(&mut payload, 0x80, delegate.len());
+    payload.extend_from_slice(&delegate);
+    rlp::put_u64(&mut payload, nonce);
+
+    let mut preimage = Vec::with_capacity(2 + payload.len());
+    preimage.push(MAGIC);
+    rlp::put_header(&mut preimage, 0xc0, payload.len());
+    preimage.extend_from_slice(&payload);
+    keccak256(&preimage)
+}
+
+/// Returns the address of the authority that signed the authorization of `delegate` with the
+/// given chain id and nonce.
+///
+/// The chain id and the nonce are only hashed: whether the authorization applies to the current
+/// chain, and whether its nonce is the one of the authority, are left to the caller.
+///
+/// Returns [`Error::HighS`] if s is above half the curve order, and the errors of
+/// [`ecrecover_address`] if the signature does not recover.
+pub fn recover_authorization_signer(
+    chain_id: u64,
+    delegate: [u8; 20],
+    nonce: u64,
+    y_parity: bool,
+    r: &[u8; 32],
+    s: &[u8; 32],
+) -> Result<[u8; 20], Error> {
+    let sig = authorization_signature(y_parity, r, s)?;
+    ecrecover_address(&sig, &authorization_hash(chain_id, delegate, nonce))
+}
+
+/// Returns the 65-byte signature of an authorization, or [`Error::HighS`] if s is above half the
+/// curve order.
+fn authorization_signature(y_parity: bool, r: &[u8; 32], s: &[u8; 32]) -> Result<[u8; 65], Error> {
+    let mut sig = [0u8; 65];
+    sig[..32].copy_from_slice(r);
+    sig[32..64].copy_from_slice(s);
+    sig[64] = y_parity as u8;
+    let signature = Signature::from_slice(&sig[..64]).map_err(|_| Error::InvalidSignature)?;
+    if signature.normalize_s().is_some() {
+        return Err(Error::HighS);
+    }
+    Ok(sig)
+}
+
+/// Returns the authority of each authorization of a list, for a transaction on the chain with
+/// id `chain_id`.
+///
+/// Authorizations with a chain id of 0 apply to every chain. Those for another chain return
+/// [`Error::AuthorizationChainMismatch`], and those whose signature does not recover return the
+/// errors of [`recover_authorization_signer`]. As invalid authorizations are skipped rather than
+/// invalidating the transaction, the results are returned one by one. The signatures of the
+/// authorizations for this chain are recovered together with [`ecrecover_batch`].
+pub fn recover_authorization_signers(
+    chain_id: u64,
+    authorizations: &[SignedAuthorization],
+) -> Vec<Result<[u8; 20], Error>> {
+    let checked = authorizations
+        .iter()
+        .map(|authorization| {
+            if authorization.chain_id != 0 && authorization.chain_id != chain_id {
+                return Err(Error::AuthorizationChainMismatch(authorization.chain_id));
+            }
+            let sig = authorization_signature(
+                authorization.y_parity,
+                &authorization.r,
+                &authorization.s,
+            )?;
+            let msg_hash = authorization_hash(
+                authorization.chain_id,
+                authorization.address,
+                authorization.nonce,
+            );
+            Ok((msg_hash, sig))
+        })
+        .collect::<Vec<_>>();
+
+    let (msg_hashes, sigs): (Vec<_>, Vec<_>) = checked.iter().flatten().copied().unzip();
+    let mut recovered = ecrecover_batch(&msg_hashes, &sigs)
+        .expect("as many messages as signatures")
+        .into_iter();
+    checked
+        .into_iter()
+        .map(|checked| {
+            checked?;
+            let pubkey = recovered
+                .next()
+                .expect("a recovery for each checked authorization")?;
+            Ok(pubkey_to_address(&pubkey))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_recover_authorization_signer() {
+        use crate::eip7702::{
+            authorization_hash, recover_authorization_signer, recover_authorization_signers,
+            SignedAuthorization,
+        };
+        use crate::error::Error;
+        use crate::secp256k1::pubkey_to_address;
+        use k256::ecdsa::SigningKey;
+        use k256::elliptic_curve::scalar::IsHigh;
+        use k256::elliptic_curve::PrimeField;
+
+        // The hashes of `keccak256(0x05 || rlp([chain_id, address, nonce]))`, computed
+        // independently, with a nonce of 300 encoded on two bytes and one of 127 on a single byte.
+        let delegate: [u8; 20] = hex::decode("70997970c51812dc3a010c7d01b50e0d17dc79c8")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        for (chain_id, address, nonce, hash) in [
+            (
+                1,
+                delegate,
+                0,
+                "36bceb509287c368d50628d5c59b9f01ad0e3bf788f2b713a89af690d1316d63",
+            ),
+            (
+                0,
+                [0x11; 20],
+                300,
+                "fa46e5d58857d617105267150cbbc5f00d394bc783fa8a81282be46ce433c628",
+            ),
+            (
+                17000,
+                [0x11; 20],
+                127,
+                "2b2df0944c1800cb1ea3ced735db37054828aa5e4b18f6fd0e19b3c1f33f772b",
+            ),
+        ] {
+            assert_eq!(
+                hex::encode(authorization_hash(chain_id, address, nonce)),
+                hash
+            );
+        }
+
+        let signing_key = SigningKey::from_slice(&[0x42; 32]).unwrap();
+        let authority = pubkey_to_address(
+            &signing_key
+                .verifying_key()
+                .to_encoded_point(false)
+                .as_bytes()
+                .try_into()
+                .unwrap(),
+        );
+        let sign = |chain_id, nonce| {
+            let hash = authorization_hash(chain_id, delegate, nonce);
+            let (sig, recid) = signing_key.sign_prehash_recoverable(&hash).unwrap();
+            assert!(!bool::from(sig.s().is_high()));
+            SignedAuthorization {
+                chain_id,
+                address: delegate,
+                nonce,
+                y_parity: recid.is_y_odd(),
+                r: sig.r().to_bytes().into(),
+                s: sig.s().to_bytes().into(),
+            }
+        };
+
+        let devnet = sign(7_088_110_746, 0);
+        let wildcard = sign(0, 5);
+        let mainnet = sign(1, 1);
+        assert_eq!(devnet.recover_signer(), Ok(authority));
+        assert_eq!(
+            recover_authorization_signer(
+                wildcard.chain_id,
+                wildcard.address,
+                wildcard.nonce,
+                wildcard.y_parity,
+                &wildcard.r,
+                &wildcard.s,
+            ),
+            Ok(authority)
+        );
+
+        // The authorization signed for nonce 5 recovers another address for nonce 6.
+        let mut wrong_nonce = wildcard;
+        wrong_nonce.nonce = 6;
+        assert_ne!(wrong_nonce.recover_signer(), Ok(authority));
+
+        // Negating s and flipping the parity recovers the same key, but is rejected.
+        let mut high_s = devnet;
+        let s = k256::Scalar::from_repr(devnet.s.into()).unwrap();
+        high_s.s = (-s).to_bytes().into();
+        high_s.y_parity = !devnet.y_parity;
+        assert_eq!(high_s.recover_signer(), Err(Error::HighS));
+
+        let mut zero_r = devnet;
+        zero_r.r = [0; 32];
+        assert_eq!(zero_r.recover_signer(), Err(Error::InvalidSignature));
+
+        assert_eq!(
+            recover_authorization_signers(
+                7_088_110_746,
+                &[devnet, zero_r, wildcard, mainnet, high_s]
+            ),
+            vec![
+                Ok(authority),
+                Err(Error::InvalidSignature),
+                Ok(authority),
+                Err(Error::AuthorizationChainMismatch(1)),
+                Err(Error::HighS),
+            ]
+        );
+        assert_eq!(recover_authorization_signers(1, &[]), vec![]);
+    }
+}
@@ -0,0 +1,345 @@
+//! Verification of Ethereum Node Records, as defined in EIP-778, with the `v4` identity scheme.
+//!
+//! A record is the RLP list `[signature, seq, k1, v1, k2, v2, ...]` of at most 300 bytes, whose
+//! keys are unique and sorted. With the `v4` scheme, the signature is the 64-byte `r || s`
+//! secp256k1 signature of the keccak256 hash of the list without the signature, by the compressed
+//! key of the `secp256k1` pair, and the node id is the keccak256 hash of the 64-byte uncompressed
+//! key, without its prefix.
+
+use crate::error::Error;
+use crate::keccak::keccak256;
+use crate::rlp::{self, Item};
+use crate::secp256k1::{parse_sec1_pubkey, try_verify_signature};
+use crate::utils::decode_base64url;
+use alloc::vec::Vec;
+use core::convert::TryInto;
+use k256::ecdsa::Signature;
+
+/// The largest encoding of a record.
+pub const MAX_RECORD_LEN: usize = 300;
+
+/// A node record whose signature has been verified.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifiedEnr {
+    /// The keccak256 hash of the uncompressed public key of the node.
+    pub node_id: [u8; 32],
+    /// The sequence number, which the node increases when it changes its record.
+    pub seq: u64,
+    /// The compressed public key of the node.
+    pub secp256k1: [u8; 33],
+    pub ip: Option<[u8; 4]>,
+    pub tcp: Option<u16>,
+    pub udp: Option<u16>,
+    pub ip6: Option<[u8; 16]>,
+    pub tcp6: Option<u16>,
+    pub udp6: Option<u16>,
+    /// Every pair of the record, in the order of the keys, with the RLP encoding of the values.
+    pub pairs: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+/// Verifies a record given in its textual form, `enr:` followed by the unpadded base64url
+/// encoding of the record, or as the hex encoding of the record, with an optional `0x` prefix.
+///
+/// Returns [`Error::EnrMalformed`] if the text cannot be decoded, and the errors of
+/// [`verify_enr_rlp`].
+pub fn verify_enr(enr_rlp_or_base64: &str) -> Result<VerifiedEnr, Error> {
+    let record = match enr_rlp_or_base64.strip_prefix("enr:") {
+        Some(base64) => decode_base64url(base64),
+        None => {
+            let hex = enr_rlp_or_base64.strip_prefix("0x");
+            decode_hex(hex.unwrap_or(enr_rlp_or_base64))
+        }
+    };
+    verify_enr_rlp(&record.ok_or(Error::EnrMalformed)?)
+}
+
+/// Verifies the RLP encoding of a record, returning its node id and its pairs.
+///
+/// Returns [`Error::EnrMalformed`] if the record is longer than [`MAX_RECORD_LEN`] or is not a
+/// canonical RLP list, if its keys are not unique and sorted, if it has no `id`, or if the value
+/// of a known key is malformed. Returns [`Error::UnsupportedIdentityScheme`] if its identity
+/// scheme is not `v4`, [`Error::HighS`] if s is above half the curve order, as geth rejects such
+/// signatures, and [`Error::VerificationFailed`] if the signature does not verify.
+pub fn verify_enr_rlp(record: &[u8]) -> Result<VerifiedEnr, Error> {
+    if record.len() > MAX_RECORD_LEN {
+        return Err(Error::EnrMalformed);
+    }
+    let items = rlp::decode_list(record).ok_or(Error::EnrMalformed)?;
+    if items.len() < 2 || items.len() % 2 != 0 {
+        return Err(Error::EnrMalformed);
+    }
+    let (signature, content) = items.split_first().unwrap();
+    let (seq, pairs) = content.split_first().unwrap();
+    let seq = string(seq).and_then(rlp::decode_u64);
+    let pairs = pairs
+        .chunks_exact(2)
+        .map(|pair| Some((string(&pair[0])?, pair[1])))
+        .collect::<Option<Vec<_>>>();
+    let (Some(seq), Some(pairs)) = (seq, pairs) else {
+        return Err(Error::EnrMalformed);
+    };
+    if pairs.windows(2).any(|pairs| pairs[0].0 >= pairs[1].0) {
+        return Err(Error::EnrMalformed);
+    }
+    let value = |key: &[u8]| {
+        pairs
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| string(value).ok_or(Error::EnrMalformed))
+            .transpose()
+    };
+
+    // The identity scheme is checked before anything else is interpreted.
+    match value(b"id")? {
+        Some(b"v4") => {}
+        Some(_) => return Err(Error::UnsupportedIdentityScheme),
+        None => return Err(Error::EnrMalformed),
+    }
+    let secp256k1: [u8; 33] = fixed(value(b"secp256k1")?)?.ok_or(Error::EnrMalformed)?;
+    let signature: [u8; 64] = fixed(string(signature))?.ok_or(Error::EnrMalformed)?;
+
+    // The signed content is the list of the items after the signature.
+    let content_len = content.iter().map(|item| item.encoding.len()).sum();
+    let mut encoded = Vec::with_capacity(9 + content_len);
+    rlp::put_header(&mut encoded, 0xc0, content_len);
+    for item in content {
+        encoded.extend_from_slice(item.encoding);
+    }
+
+    let signature = Signature::from_slice(&signature).map_err(|_| Error::InvalidSignature)?;
+    if signature.normalize_s().is_some() {
+        return Err(Error::HighS);
+    }
+    let pubkey = parse_sec1_pubkey(&secp256k1)?;
+    if !try_verify_signature(&pubkey, &keccak256(&encoded), &signature, None)? {
+        return Err(Error::VerificationFailed);
+    }
+
+    Ok(VerifiedEnr {
+        node_id: keccak256(&pubkey[1..]),
+        seq,
+        secp256k1,
+        ip: fixed(value(b"ip")?)?,
+        tcp: port(value(b"tcp")?)?,
+        udp: port(value(b"udp")?)?,
+        ip6: fixed(value(b"ip6")?)?,
+        tcp6: port(value(b"tcp6")?)?,
+        udp6: port(value(b"udp6")?)?,
+        pairs: pairs
+            .iter()
+            .map(|(key, value)| (key.to_vec(), value.encoding.to_vec()))
+            .collect(),
+    })
+}
+
+/// Returns the payload of an item that is a string.
+fn string<'a>(item: &Item<'a>) -> Option<&'a [u8]> {
+    (!item.is_list).then_some(item.payload)
+}
+
+/// Converts an optional value to an array, which must have exactly `N` bytes.
+fn fixed<const N: usize>(value: Option<&[u8]>) -> Result<Option<[u8; N]>, Error> {
+    value
+        .map(|value| value.try_into().map_err(|_| Error::EnrMalformed))
+        .transpose()
+}
+
+/// Decodes an optional port, a canonical integer of 16 bits.
+fn port(value: Option<&[u8]>) -> Result<Option<u16>, Error> {
+    value
+        .map(|value| {
+            rlp::decode_u64(value)
+                .and_then(|port| port.try_into().ok())
+                .ok_or(Error::EnrMalformed)
+        })
+        .transpose()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 || !hex.bytes().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_verify_enr() {
+        use crate::enr::{verify_enr, verify_enr_rlp};
+        use crate::error::Error;
+        use crate::keccak::keccak256;
+        use crate::utils::decode_base64url;
+        use alloc::vec::Vec;
+        use k256::ecdsa::SigningKey;
+        use k256::elliptic_curve::PrimeField;
+
+        // The example record of EIP-778, and mainnet bootnodes of Lighthouse, the Ethereum
+        // Foundation and Teku.
+        let example = "enr:-IS4QHCYrYZbAKWCBRlAy5zzaDZXJBGkcnh4MHcBFZntXNFrdvJjX04jRzjzCBOonrkTfj499SZuOh8R33Ls8RRcy5wBgmlkgnY0gmlwhH8AAAGJc2VjcDI1NmsxoQPKY0yuDUmstAHYpMa2_oxVtw0RW_QAdpzBQA8yWM0xOIN1ZHCCdl8";
+        let enr = verify_enr(example).unwrap();
+        assert_eq!(
+            hex::encode(enr.node_id),
+            "a448f24c6d18e575453db13171562b71999873db5b286df957af199ec94617f7"
+        );
+        assert_eq!(enr.seq, 1);
+        assert_eq!(
+            hex::encode(enr.secp256k1),
+            "03ca634cae0d49acb401d8a4c6b6fe8c55b70d115bf400769cc1400f3258cd3138"
+        );
+        assert_eq!(enr.ip, Some([127, 0, 0, 1]));
+        assert_eq!((enr.tcp, enr.udp), (None, Some(30303)));
+        assert_eq!((enr.ip6, enr.tcp6, enr.udp6), (None, None, None));
+        assert_eq!(
+            enr.pairs,
+            vec![
+                (b"id".to_vec(), b"\x82v4".to_vec()),
+                (b"ip".to_vec(), hex::decode("847f000001").unwrap()),
+                (
+                    b"secp256k1".to_vec(),
+                    [&[0xa1], &enr.secp256k1[..]].concat()
+                ),
+                (b"udp".to_vec(), hex::decode("82765f").unwrap()),
+            ]
+        );
+
+        let lighthouse = verify_enr("enr:-Le4QPUXJS2BTORXxyx2Ia-9ae4YqA_JWX3ssj4E_J-3z1A-HmFGrU8BpvpqhNabayXeOZ2Nq_sbeDgtzMJpLLnXFgAChGV0aDKQtTA_KgEAAAAAIgEAAAAAAIJpZIJ2NIJpcISsaa0Zg2lwNpAkAIkHAAAAAPA8kv_-awoTiXNlY3AyNTZrMaEDHAD2JKYevx89W0CcFJFiskdcEzkH_Wdv9iW42qLK79ODdWRwgiMohHVkcDaCI4I").unwrap();
+        assert_eq!(
+            hex::encode(lighthouse.node_id),
+            "97209eae44c2d45dce2f9d949f33105891c0694a7d1f5f1783c43adce3a3f82e"
+        );
+        assert_eq!(lighthouse.seq, 2);
+        assert_eq!(lighthouse.ip, Some([172, 105, 173, 25]));
+        assert_eq!(
+            lighthouse.ip6.map(hex::encode).as_deref(),
+            Some("2400890700000000f03c92fffe6b0a13")
+        );
+        assert_eq!((lighthouse.tcp, lighthouse.udp), (None, Some(9000)));
+        assert_eq!((lighthouse.tcp6, lighthouse.udp6), (None, Some(9090)));
+        assert_eq!(lighthouse.pairs[0].0, b"eth2");
+
+        for (enr, node_id, ip, tcp) in [
+            (
+                "enr:-Ku4QHqVeJ8PPICcWk1vSn_XcSkjOkNiTg6Fmii5j6vUQgvzMc9L1goFnLKgXqBJspJjIsB91LTOleFmyWWrFVATGngBh2F0dG5ldHOIAAAAAAAAAACEZXRoMpC1MD8qAAAAAP__________gmlkgnY0gmlwhAMRHkWJc2VjcDI1NmsxoQKLVXFOhp2uX6jeT0DvvDpPcU8FWMjQdR4wMuORMhpX24N1ZHCCIyg",
+                "ef4cf7caa876063f4b8a8d1dad0f58fe9cd0ce945abba6b85dbf31c5fac98269",
+                [3, 17, 30, 69],
+                None,
+            ),
+            (
+                "enr:-Ku4QG-2_Md3sZIAUebGYT6g0SMskIml77l6yR-M_JXc-UdNHCmHQeOiMLbylPejyJsdAPsTHJyjJB2sYGDLe0dn8uYBh2F0dG5ldHOIAAAAAAAAAACEZXRoMpC1MD8qAAAAAP__________gmlkgnY0gmlwhBLY-NyJc2VjcDI1NmsxoQORcM6e19T1T9gi7jxEZjk_sjVLGFscUNqAY9obgZaxbIN1ZHCCIyg",
+                "e6e8bf5a8226432f492ae7484a2a324392dcac3b4eeaa219384708d8653ba36b",
+                [18, 216, 248, 220],
+                None,
+            ),
+            (
+                "enr:-KG4QOtcP9X1FbIMOe17QNMKqDxCpm14jcX5tiOE4_TyMrFqbmhPZHK_ZPG2Gxb1GE2xdtodOfx9-cgvNtxnRyHEmC0ghGV0aDKQ9aX9QgAAAAD__________4JpZIJ2NIJpcIQDE8KdiXNlY3AyNTZrMaEDhpehBDbZjM_L9ek699Y7vhUJ-eAdMyQW_Fil522Y0fODdGNwgiMog3VkcIIjKA",
+                "f92b82f11af5ed0959135cde8e64b626cac4f16d05e43087224deed25d1dbd72",
+                [3, 19, 194, 157],
+                Some(9000),
+            ),
+        ] {
+            let enr = verify_enr(enr).unwrap();
+            assert_eq!(hex::encode(enr.node_id), node_id);
+            assert_eq!(enr.ip, Some(ip));
+            assert_eq!((enr.tcp, enr.udp), (tcp, Some(9000)));
+        }
+
+        // The RLP encoding is accepted directly, or as hex.
+        let pubkey = enr.secp256k1;
+        let record = decode_base64url(&example[4..]).unwrap();
+        assert_eq!(record.len(), 134);
+        assert_eq!(verify_enr_rlp(&record), Ok(enr.clone()));
+        assert_eq!(verify_enr(&hex::encode(&record)), Ok(enr.clone()));
+        assert_eq!(verify_enr(&format!("0x{}", hex::encode(&record))), Ok(enr));
+        assert_eq!(verify_enr(&format!("{example}=")), Err(Error::EnrMalformed));
+        assert_eq!(verify_enr("0xc0f"), Err(Error::EnrMalformed));
+
+        // Changing the UDP port invalidates the signature, and so does negating s, which geth
+        // rejects even though the signature would verify.
+        let mut tampered = record.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert_eq!(verify_enr_rlp(&tampered), Err(Error::VerificationFailed));
+        let mut high_s = record.clone();
+        let s = k256::Scalar::from_repr(<[u8; 32]>::try_from(&record[36..68]).unwrap().into());
+        high_s[36..68].copy_from_slice(&(-s.unwrap()).to_bytes());
+        assert_eq!(verify_enr_rlp(&high_s), Err(Error::HighS));
+
+        // Records built and signed with the key of the EIP-778 example.
+        let signing_key = SigningKey::from_slice(
+            &hex::decode("b71c71a67e1177ad4e901695e1b4b9ee17ae16c6668d313eac2f96dbcda3f291")
+                .unwrap(),
+        )
+        .unwrap();
+        let encode_string = |bytes: &[u8]| match bytes.len() {
+            1 if bytes[0] < 0x80 => bytes.to_vec(),
+            len if len < 56 => [&[0x80 + len as u8], bytes].concat(),
+            len => [&[0xb8, len as u8], bytes].concat(),
+        };
+        let encode_list = |items: &[Vec<u8>]| {
+            let payload = items.concat();
+            let header = match payload.len() {
+                len if len < 56 => vec![0xc0 + len as u8],
+                len if len < 256 => vec![0xf8, len as u8],
+                len => vec![0xf9, (len >> 8) as u8, len as u8],
+            };
+            [header, payload].concat()
+        };
+        let sign = |seq: &[u8], pairs: &[(&[u8], &[u8])]| {
+            let mut content = vec![encode_string(seq)];
+            for (key, value) in pairs {
+                content.push(encode_string(key));
+                content.push(encode_string(value));
+            }
+            let hash = keccak256(&encode_list(&content));
+            let (sig, _) = signing_key.sign_prehash_recoverable(&hash).unwrap();
+            content.insert(0, encode_string(&sig.to_bytes()));
+            encode_list(&content)
+        };
+        let id: (&[u8], &[u8]) = (b"id", b"v4");
+        let secp256k1: (&[u8], &[u8]) = (b"secp256k1", &pubkey);
+
+        let signed = sign(&[0x01, 0x00], &[id, secp256k1, (b"tcp6", &[0x76, 0x5f])]);
+        let enr = verify_enr_rlp(&signed).unwrap();
+        assert_eq!(
+            (enr.seq, enr.tcp6, enr.ip),
+            (256, Some(30303), None::<[u8; 4]>)
+        );
+        assert_eq!(
+            hex::encode(enr.node_id),
+            "a448f24c6d18e575453db13171562b71999873db5b286df957af199ec94617f7"
+        );
+
+        // A record with another identity scheme fails distinctly, before its signature is read.
+        assert_eq!(
+            verify_enr_rlp(&sign(&[1], &[(b"id", b"v5"), secp256k1])),
+            Err(Error::UnsupportedIdentityScheme)
+        );
+        for malformed in [
+            // No identity scheme, and no public key.
+            sign(&[1], &[secp256k1]),
+            sign(&[1], &[id]),
+            // Unsorted and repeated keys.
+            sign(&[1], &[secp256k1, id]),
+            sign(&[1], &[id, id, secp256k1]),
+            // A sequence number with a leading zero, a port above 65535, and a short address.
+            sign(&[0, 1], &[id, secp256k1]),
+            sign(&[1], &[id, secp256k1, (b"udp", &[1, 0, 0])]),
+            sign(&[1], &[id, (b"ip", &[127, 0, 0]), secp256k1]),
+            // A signed record that is longer than 300 bytes.
+            sign(&[1], &[id, secp256k1, (b"z", &[0; 178])]),
+        ] {
+            assert_eq!(verify_enr_rlp(&malformed), Err(Error::EnrMalformed));
+        }
+        let long = sign(&[1], &[id, secp256k1, (b"z", &[0; 177])]);
+        assert_eq!(long.len(), 300);
+        assert!(verify_enr_rlp(&long).is_ok());
+        assert_eq!(verify_enr_rlp(&[0xc0]), Err(Error::EnrMalformed));
+        assert_eq!(
+            verify_enr_rlp(&[&record[..], &[0]].concat()),
+            Err(Error::EnrMalformed)
+        );
+    }
+}
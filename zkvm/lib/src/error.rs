@@ -0,0 +1,263 @@
+//! Errors returned by the guest-side utilities.
+
+use core::fmt;
+
+/// An error returned by the guest-side utilities.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The `r` or `s` scalar of a signature is zero or not below the curve order.
+    InvalidSignature,
+    /// The `v` value of a signature could not be interpreted.
+    InvalidRecoveryId(u64),
+    /// The chain id is too large to be encoded in a `v` value.
+    ChainIdOverflow(u64),
+    /// The public key is not a valid encoding of a point on the curve.
+    InvalidPublicKey,
+    /// The point is the point at infinity, which affine points cannot represent.
+    IdentityPoint,
+    /// The private key is zero or not below the curve order.
+    InvalidPrivateKey,
+    /// A tweak derived from a hash is not below the curve order, or cancels the tweaked key.
+    InvalidTweak,
+    /// The message hash cannot be converted to a scalar.
+    InvalidMessageHash,
+    /// The provided s_inverse value is not the inverse of the signature's s.
+    InvalidSInverse,
+    /// A hook returned a response of the wrong length.
+    HookResponseMalformed {
+        fd: u32,
+        expected: usize,
+        actual: usize,
+    },
+    /// A hook returned a hint that does not satisfy the constraints checked on it.
+    BadHint { fd: u32 },
+    /// An entry of the input stream is not the chunk with the given index of a chunked buffer.
+    HintChunkMissing { index: u32 },
+    /// The chunks of a chunked buffer do not add up to the length announced by its header.
+    HintLengthMismatch { expected: u64, actual: u64 },
+    /// An entry of the input stream passed as the header of a chunked buffer is not one, with its
+    /// length.
+    HintHeaderMalformed { len: usize },
+    /// A hook reported that it could not process the request, with the given status byte.
+    HookFailed { fd: u32, status: u8 },
+    /// The signature does not verify against the public key.
+    VerificationFailed,
+    /// The signature at the given index is not from an authorized signer.
+    UnauthorizedSigner { index: usize },
+    /// The certificate chain has no certificates.
+    EmptyCertificateChain,
+    /// The certificate at the given index of a chain is not valid DER or is missing fields.
+    CertificateMalformed { index: usize },
+    /// The certificate at the given index of a chain is signed with an unsupported algorithm, or
+    /// by an unsupported key type.
+    UnsupportedAlgorithm { index: usize },
+    /// The signature of the certificate at the given index of a chain does not verify.
+    CertificateSignatureInvalid { index: usize },
+    /// The issuer of the certificate at the given index of a chain is not the subject of the
+    /// next one.
+    IssuerMismatch { index: usize },
+    /// The certificate at the given index of a chain has expired.
+    CertificateExpired { index: usize },
+    /// The certificate at the given index of a chain is not valid yet.
+    CertificateNotYetValid { index: usize },
+    /// An SSH public key or signature cannot be decoded.
+    SshSignatureMalformed,
+    /// An SSH public key or signature uses an unsupported key type or hash algorithm.
+    UnsupportedSshAlgorithm,
+    /// A minisign public key or signature file cannot be parsed.
+    MinisignMalformed,
+    /// A minisign signature is by another key than the given one.
+    MinisignKeyIdMismatch,
+    /// A Nostr event is not valid JSON, or lacks one of the fields of NIP-01.
+    NostrEventMalformed,
+    /// The id of a Nostr event is not the hash of its canonical serialization.
+    NostrIdMismatch,
+    /// A DNSSEC record uses an algorithm other than ECDSAP256SHA256.
+    DnssecUnsupportedAlgorithm(u8),
+    /// A DNSKEY is not a zone key, or does not have the key tag of the signature.
+    DnssecKeyMismatch,
+    /// DNSSEC records have malformed names or fields, or do not form the covered RRset.
+    DnssecMalformed,
+    /// The TLS signature scheme with the given code point is not supported.
+    UnsupportedSignatureScheme(u16),
+    /// A compact target is negative, overflows 256 bits or is zero.
+    InvalidCompactTarget,
+    /// The hash of a block header is above its target.
+    InsufficientProofOfWork,
+    /// A block header does not commit to the hash of the previous one.
+    HeaderChainBroken { index: usize },
+    /// The target of a block header does not follow the difficulty adjustment.
+    UnexpectedDifficulty { index: usize },
+    /// A string is not a valid segwit address.
+    InvalidAddress,
+    /// A BIP-322 witness is not a valid encoding of a witness stack.
+    WitnessMalformed,
+    /// A BIP-322 signature uses a format, an address type or a sighash type that is not supported.
+    Bip322Unsupported,
+    /// The signatures of a Tendermint commit do not match the validator set, or the voting powers
+    /// are invalid.
+    TendermintMalformed,
+    /// A clique header cannot be decoded, or its extra data does not hold a seal.
+    CliqueMalformed,
+    /// A KZG commitment or proof is not a valid encoding of a point of the G1 subgroup.
+    InvalidKzgPoint,
+    /// A field element of a KZG proof or blob is not below the modulus of the scalar field.
+    InvalidFieldElement,
+    /// A hash-to-curve domain separation tag is empty or longer than 255 bytes.
+    InvalidDomainSeparationTag,
+    /// A Merkle Patricia trie proof has a node that is malformed or does not match its hash.
+    MptProofInvalid,
+    /// A Merkle Patricia trie proof shows that the key is not in the trie.
+    MptKeyNotFound,
+    /// A receipt cannot be decoded, or has a transaction type that is not supported.
+    ReceiptMalformed,
+    /// The s value of a signature is above half the curve order, which EIP-2 rejects.
+    HighS,
+    /// An EIP-7702 authorization is for the chain with the given id, not the current one.
+    AuthorizationChainMismatch(u64),
+    /// The numbers of messages and of signatures given to a batch recovery differ.
+    SignatureCountMismatch { messages: usize, signatures: usize },
+    /// A modulus is zero, or too long for the modular arithmetic.
+    InvalidModulus,
+    /// A Pedersen commitment blinding factor is not below the curve order.
+    InvalidBlinding,
+    /// A compressed point has invalid flags, a coordinate not below the modulus, or no point of
+    /// the curve has its x-coordinate.
+    InvalidPointEncoding,
+    /// A point is on the curve but not in the prime order subgroup.
+    PointNotInSubgroup,
+    /// An AEAD ciphertext is shorter than its tag, or the tag does not match.
+    DecryptionFailed,
+    /// A node record is too long, is not a canonical RLP list with sorted keys, or has a malformed
+    /// identity or well-known value.
+    EnrMalformed,
+    /// A node record uses an identity scheme other than `v4`.
+    UnsupportedIdentityScheme,
+    /// A signature was not completed from the adaptor pre-signature, as their nonces differ.
+    AdaptorSignatureMismatch,
+    /// The public key and the signature are for different algorithms.
+    SignatureKeyMismatch,
+    /// The algorithm of the signature only signs raw messages, and a digest was given.
+    UnsupportedMessageKind,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidSignature => write!(f, "invalid signature scalars"),
+            Error::InvalidRecoveryId(v) => write!(f, "invalid v value: {v}"),
+            Error::ChainIdOverflow(chain_id) => {
+                write!(f, "chain id {chain_id} overflows the v value")
+            }
+            Error::InvalidPublicKey => write!(f, "invalid public key"),
+            Error::IdentityPoint => write!(f, "the point at infinity has no affine coordinates"),
+            Error::InvalidPrivateKey => write!(f, "invalid private key"),
+            Error::InvalidTweak => write!(f, "invalid tweak"),
+            Error::InvalidMessageHash => write!(f, "invalid message hash"),
+            Error::InvalidSInverse => write!(f, "s_inverse is not the inverse of s"),
+            Error::HookResponseMalformed {
+                fd,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "malformed response from the hook at fd {fd}: expected {expected} bytes, got {actual}"
+            ),
+            Error::BadHint { fd } => write!(f, "invalid hint from the hook at fd {fd}"),
+            Error::HintChunkMissing { index } => write!(f, "chunk {index} of a hint is missing"),
+            Error::HintLengthMismatch { expected, actual } => {
+                write!(f, "chunked hint has {actual} bytes instead of {expected}")
+            }
+            Error::HintHeaderMalformed { len } => {
+                write!(f, "the {len}-byte entry is not the header of a chunked hint")
+            }
+            Error::HookFailed { fd, status } => {
+                write!(f, "the hook at fd {fd} failed with status {status:#04x}")
+            }
+            Error::VerificationFailed => write!(f, "failed to verify signature"),
+            Error::UnauthorizedSigner { index } => {
+                write!(f, "signature {index} is not from an authorized address")
+            }
+            Error::EmptyCertificateChain => write!(f, "empty certificate chain"),
+            Error::CertificateMalformed { index } => write!(f, "certificate {index} is malformed"),
+            Error::UnsupportedAlgorithm { index } => {
+                write!(f, "certificate {index} uses an unsupported algorithm")
+            }
+            Error::CertificateSignatureInvalid { index } => {
+                write!(f, "the signature of certificate {index} does not verify")
+            }
+            Error::IssuerMismatch { index } => write!(
+                f,
+                "the issuer of certificate {index} is not the subject of the next certificate"
+            ),
+            Error::CertificateExpired { index } => write!(f, "certificate {index} has expired"),
+            Error::CertificateNotYetValid { index } => {
+                write!(f, "certificate {index} is not valid yet")
+            }
+            Error::SshSignatureMalformed => write!(f, "malformed SSH key or signature"),
+            Error::UnsupportedSshAlgorithm => write!(f, "unsupported algorithm"),
+            Error::MinisignMalformed => write!(f, "malformed minisign key or signature"),
+            Error::MinisignKeyIdMismatch => {
+                write!(f, "the signature key id does not match the public key")
+            }
+            Error::NostrEventMalformed => write!(f, "malformed Nostr event"),
+            Error::NostrIdMismatch => write!(f, "the Nostr event id does not match its contents"),
+            Error::DnssecUnsupportedAlgorithm(algorithm) => {
+                write!(f, "unsupported DNSSEC algorithm {algorithm}")
+            }
+            Error::DnssecKeyMismatch => write!(f, "the DNSKEY does not match the signature"),
+            Error::DnssecMalformed => write!(f, "malformed DNSSEC records"),
+            Error::UnsupportedSignatureScheme(scheme) => {
+                write!(f, "unsupported signature scheme {scheme:#06x}")
+            }
+            Error::InvalidCompactTarget => write!(f, "invalid compact target"),
+            Error::InsufficientProofOfWork => write!(f, "block hash is above the target"),
+            Error::HeaderChainBroken { index } => {
+                write!(f, "header {index} does not follow the previous block")
+            }
+            Error::UnexpectedDifficulty { index } => {
+                write!(f, "header {index} has an unexpected target")
+            }
+            Error::InvalidAddress => write!(f, "invalid address"),
+            Error::WitnessMalformed => write!(f, "malformed witness"),
+            Error::Bip322Unsupported => write!(f, "unsupported BIP-322 signature"),
+            Error::TendermintMalformed => write!(f, "commit does not match the validator set"),
+            Error::CliqueMalformed => write!(f, "malformed clique header"),
+            Error::InvalidKzgPoint => write!(f, "invalid KZG commitment or proof"),
+            Error::InvalidFieldElement => write!(f, "field element is not below the modulus"),
+            Error::InvalidDomainSeparationTag => {
+                write!(f, "domain separation tag must be 1 to 255 bytes long")
+            }
+            Error::MptProofInvalid => write!(f, "invalid trie proof"),
+            Error::MptKeyNotFound => write!(f, "key is not in the trie"),
+            Error::ReceiptMalformed => write!(f, "malformed receipt"),
+            Error::HighS => write!(f, "signature s value is above half the curve order"),
+            Error::AuthorizationChainMismatch(chain_id) => {
+                write!(f, "authorization is for chain {chain_id}")
+            }
+            Error::SignatureCountMismatch {
+                messages,
+                signatures,
+            } => write!(f, "{messages} messages but {signatures} signatures"),
+            Error::InvalidModulus => write!(f, "modulus is zero or too long"),
+            Error::InvalidBlinding => write!(f, "blinding factor is not below the curve order"),
+            Error::InvalidPointEncoding => write!(f, "invalid point encoding"),
+            Error::PointNotInSubgroup => write!(f, "point is not in the prime order subgroup"),
+            Error::DecryptionFailed => write!(f, "failed to authenticate the ciphertext"),
+            Error::EnrMalformed => write!(f, "malformed node record"),
+            Error::UnsupportedIdentityScheme => write!(f, "unsupported identity scheme"),
+            Error::AdaptorSignatureMismatch => {
+                write!(f, "the signature was not completed from the pre-signature")
+            }
+            Error::SignatureKeyMismatch => {
+                write!(f, "the public key and the signature use different algorithms")
+            }
+            Error::UnsupportedMessageKind => {
+                write!(f, "the signature algorithm cannot verify a prehashed message")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
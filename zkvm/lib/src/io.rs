@@ -1,11 +1,13 @@
 #![allow(unused_unsafe)]
 use crate::syscall_write;
-use crate::{syscall_hint_len, syscall_hint_read};
-use serde::de::DeserializeOwned;
-use serde::Serialize;
-use std::alloc::Layout;
-use std::io::Result;
-use std::io::Write;
+#[cfg(feature = "alloc")]
+use crate::{error::Error, syscall_hint_len, syscall_hint_read};
+#[cfg(feature = "alloc")]
+use alloc::{alloc::Layout, vec::Vec};
+#[cfg(feature = "std")]
+use serde::{de::DeserializeOwned, Serialize};
+#[cfg(feature = "std")]
+use std::io::{Result, Write};
 
 /// The file descriptor for public values.
 pub const FD_PUBLIC_VALUES: u32 = 3;
@@ -16,19 +18,52 @@ pub const FD_HINT: u32 = 4;
 /// The file descriptor for the `ecreover` hook.
 pub const FD_ECRECOVER_HOOK: u32 = 5;
 
+/// The file descriptor for the secp256r1 `ecrecover` hook.
+pub const FD_ECRECOVER_R1_HOOK: u32 = 6;
+
+/// The file descriptor for the modular inverse hook.
+pub const FD_MODINV_HOOK: u32 = 7;
+
+/// The file descriptor for the modular square root hook.
+pub const FD_SQRT_HOOK: u32 = 8;
+
+/// The file descriptor for the hook of the modular arithmetic of [`crate::bigmod`].
+pub const FD_BIGMOD_HOOK: u32 = 9;
+
+/// The largest entry of the input stream. The executor splits longer inputs and hook responses
+/// into chunks, which [`try_read_vec`] reassembles.
+pub const MAX_HINT_CHUNK_LEN: usize = 1 << 20;
+
+/// The first bytes of the header entry of a chunked input.
+pub const CHUNKED_HINT_MAGIC: [u8; 8] = *b"SP1CHUNK";
+
+/// The length of the header entry of a chunked input: the magic bytes, followed by the total
+/// length as a little endian `u64` and the number of chunks as a little endian `u32`.
+pub const CHUNKED_HINT_HEADER_LEN: usize = 20;
+
+/// The status byte of an `ecrecover` hook response to a request of the wrong length.
+pub const HOOK_STATUS_MALFORMED_INPUT: u8 = 0x10;
+
+/// The status byte of an `ecrecover` hook response to a signature with out of range scalars.
+pub const HOOK_STATUS_INVALID_SIGNATURE: u8 = 0x11;
+
+/// The status byte of an `ecrecover` hook response to an invalid recovery id.
+pub const HOOK_STATUS_INVALID_RECOVERY_ID: u8 = 0x12;
+
+/// The status byte of an `ecrecover` hook response to a signature no public key recovers to.
+pub const HOOK_STATUS_UNRECOVERABLE: u8 = 0x13;
+
 /// A writer that writes to a file descriptor inside the zkVM.
+#[cfg(feature = "std")]
 struct SyscallWriter {
     fd: u32,
 }
 
+#[cfg(feature = "std")]
 impl Write for SyscallWriter {
     fn write(&mut self, buf: &[u8]) -> Result<usize> {
-        let nbytes = buf.len();
-        let write_buf = buf.as_ptr();
-        unsafe {
-            syscall_write(self.fd, write_buf, nbytes);
-        }
-        Ok(nbytes)
+        write(self.fd, buf);
+        Ok(buf.len())
     }
 
     fn flush(&mut self) -> Result<()> {
@@ -38,18 +73,98 @@ impl Write for SyscallWriter {
 
 /// Read a buffer from the input stream.
 ///
+/// The responses of a hook are read before the rest of the input stream, and must be read in the
+/// order in which the hooks were invoked: the executor fails with `HintOutOfOrder` if a response
+/// is read while responses of an earlier hook are still unread.
+///
+/// Buffers longer than [`MAX_HINT_CHUNK_LEN`] are reassembled from their chunks, like with
+/// [`try_read_vec`]. Panics if the chunks are not the ones announced by their header.
+///
 /// ### Examples
 /// ```ignore
 /// let data: Vec<u8> = sp1_zkvm::io::read_vec();
 /// ```
+#[cfg(feature = "alloc")]
 pub fn read_vec() -> Vec<u8> {
+    try_read_vec().unwrap_or_else(|err| {
+        panic!("failed to reassemble a chunked buffer of the input stream: {err}")
+    })
+}
+
+/// Read a buffer from the input stream, reassembling it if the executor split it into chunks.
+///
+/// The chunks are copied into the buffer, and the allocator of the zkVM does not free memory, so a
+/// chunked buffer takes twice its length.
+///
+/// Returns [`Error::HintChunkMissing`] if an entry is not the next chunk of the buffer, and
+/// [`Error::HintLengthMismatch`] if the chunks do not add up to the announced length.
+///
+/// ### Examples
+/// ```ignore
+/// let witness: Vec<u8> = sp1_zkvm::io::try_read_vec()?;
+/// ```
+#[cfg(feature = "alloc")]
+pub fn try_read_vec() -> core::result::Result<Vec<u8>, Error> {
+    let entry = read_entry();
+    if entry.len() == CHUNKED_HINT_HEADER_LEN && entry.starts_with(&CHUNKED_HINT_MAGIC) {
+        reassemble_chunks(&entry, read_entry)
+    } else {
+        Ok(entry)
+    }
+}
+
+/// Reassembles a chunked buffer from its header entry and a source of the next entries of the
+/// input stream, as [`try_read_vec`] does. Exposed so that host code can decode chunked
+/// streams.
+///
+/// Returns [`Error::HintHeaderMalformed`] if `header` is not a header entry, and the errors of
+/// [`try_read_vec`] if the chunks are not the ones it announces.
+#[cfg(feature = "alloc")]
+pub fn reassemble_chunks(
+    header: &[u8],
+    mut next_entry: impl FnMut() -> Vec<u8>,
+) -> core::result::Result<Vec<u8>, Error> {
+    if header.len() != CHUNKED_HINT_HEADER_LEN || !header.starts_with(&CHUNKED_HINT_MAGIC) {
+        return Err(Error::HintHeaderMalformed { len: header.len() });
+    }
+    let total = u64::from_le_bytes(header[8..16].try_into().unwrap());
+    let count = u32::from_le_bytes(header[16..20].try_into().unwrap());
+
+    // The announced length is not trusted to size the buffer.
+    let capacity = total.min(count as u64 * MAX_HINT_CHUNK_LEN as u64) as usize;
+    let mut buf = Vec::with_capacity(capacity);
+    for index in 0..count {
+        let chunk = next_entry();
+        if chunk.len() < 4 || chunk[..4] != index.to_le_bytes() {
+            return Err(Error::HintChunkMissing { index });
+        }
+        buf.extend_from_slice(&chunk[4..]);
+    }
+    if buf.len() as u64 != total {
+        return Err(Error::HintLengthMismatch {
+            expected: total,
+            actual: buf.len() as u64,
+        });
+    }
+    Ok(buf)
+}
+
+/// Read the next entry of the input stream as is.
+#[cfg(feature = "alloc")]
+fn read_entry() -> Vec<u8> {
     // Round up to the nearest multiple of 4 so that the memory allocated is in whole words
     let len = unsafe { syscall_hint_len() };
+    if len == 0 {
+        // Nothing can be allocated for an empty entry, which is read into an aligned dangling
+        // pointer instead.
+        unsafe { syscall_hint_read(core::ptr::NonNull::<u32>::dangling().as_ptr().cast(), 0) };
+        return Vec::new();
+    }
     let capacity = (len + 3) / 4 * 4;
 
     // Allocate a buffer of the required length that is 4 byte aligned
     let layout = Layout::from_size_align(capacity, 4).expect("vec is too large");
-    let ptr = unsafe { std::alloc::alloc(layout) };
+    let ptr = unsafe { alloc::alloc::alloc(layout) };
 
     // SAFETY:
     // 1. `ptr` was allocated using alloc
@@ -68,6 +183,100 @@ pub fn read_vec() -> Vec<u8> {
     vec
 }
 
+/// Read a buffer of exactly `N` bytes from the input stream.
+///
+/// Panics if the buffer does not have the expected length.
+///
+/// ### Examples
+/// ```ignore
+/// let data: [u8; 32] = sp1_zkvm::io::read_array();
+/// ```
+#[cfg(feature = "alloc")]
+pub fn read_array<const N: usize>() -> [u8; N] {
+    let vec = read_vec();
+    let len = vec.len();
+    vec.try_into()
+        .unwrap_or_else(|_| panic!("expected {N} bytes in the input stream, got {len}"))
+}
+
+/// Read the response of the hook at `fd` from the input stream, which must be exactly `N` bytes.
+///
+/// Returns [`Error::HookResponseMalformed`] if the response does not have the expected length.
+///
+/// ### Examples
+/// ```ignore
+/// let s_inverse: [u8; 32] = sp1_zkvm::io::read_hook_array(FD_ECRECOVER_HOOK)?;
+/// ```
+#[cfg(feature = "alloc")]
+pub fn read_hook_array<const N: usize>(fd: u32) -> core::result::Result<[u8; N], Error> {
+    let vec = try_read_vec()?;
+    let actual = vec.len();
+    vec.try_into().map_err(|_| Error::HookResponseMalformed {
+        fd,
+        expected: N,
+        actual,
+    })
+}
+
+/// Sends a request to the `ecrecover` hook at `fd`: the 65-byte signature, with the recovery id as
+/// its last byte, followed by the 32-byte message hash. Must be called in an
+/// [`unconstrained!`](crate::unconstrained) block.
+///
+/// This is public for the forks of curve crates, which cannot depend on the curve features of
+/// this crate, as those depend on the crates they replace.
+#[cfg(all(feature = "alloc", not(feature = "no-hints")))]
+pub fn write_ecrecover_request(fd: u32, sig: &[u8; 65], msg_hash: &[u8; 32]) {
+    let mut buf = [0; 65 + 32];
+    let (buf_sig, buf_msg_hash) = buf.split_at_mut(sig.len());
+    buf_sig.copy_from_slice(sig);
+    buf_msg_hash.copy_from_slice(msg_hash);
+    write(fd, &buf);
+}
+
+/// Reads the responses of the `ecrecover` hook at `fd` to the request of `sig`: the compressed
+/// public key and the big endian inverse of s, neither of which is checked.
+///
+/// Returns the error matching the status byte of a failed request, and
+/// [`Error::HookResponseMalformed`] if a response does not have the expected length.
+#[cfg(all(feature = "alloc", not(feature = "no-hints")))]
+pub fn read_ecrecover_response(
+    fd: u32,
+    sig: &[u8; 65],
+) -> core::result::Result<([u8; 33], [u8; 32]), Error> {
+    // A failed request is answered with a single response starting with a status byte, which is
+    // never a compressed key prefix.
+    let recovered_bytes = read_vec();
+    match recovered_bytes.first() {
+        Some(2 | 3) => {}
+        Some(&HOOK_STATUS_INVALID_SIGNATURE) => return Err(Error::InvalidSignature),
+        Some(&HOOK_STATUS_INVALID_RECOVERY_ID) => {
+            return Err(Error::InvalidRecoveryId(sig[64] as u64))
+        }
+        Some(&HOOK_STATUS_UNRECOVERABLE) => return Err(Error::VerificationFailed),
+        Some(&status) => return Err(Error::HookFailed { fd, status }),
+        None => {
+            return Err(Error::HookResponseMalformed {
+                fd,
+                expected: 33,
+                actual: 0,
+            })
+        }
+    }
+    // A key is followed by the inverse of s, which is read before the key is checked, so that a
+    // malformed key does not leave it to be read in place of the response to the next request.
+    let s_inv_bytes = read_hook_array(fd);
+    let recovered_bytes =
+        recovered_bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| Error::HookResponseMalformed {
+                fd,
+                expected: 33,
+                actual: recovered_bytes.len(),
+            })?;
+    Ok((recovered_bytes, s_inv_bytes?))
+}
+
 /// Read a deserializable object from the input stream.
 ///
 /// ### Examples
@@ -82,6 +291,7 @@ pub fn read_vec() -> Vec<u8> {
 ///
 /// let data: MyStruct = sp1_zkvm::io::read();
 /// ```
+#[cfg(feature = "std")]
 pub fn read<T: DeserializeOwned>() -> T {
     let vec = read_vec();
     bincode::deserialize(&vec).expect("deserialization failed")
@@ -105,6 +315,7 @@ pub fn read<T: DeserializeOwned>() -> T {
 /// };
 /// sp1_zkvm::io::commit(&data);
 /// ```
+#[cfg(feature = "std")]
 pub fn commit<T: Serialize>(value: &T) {
     let writer = SyscallWriter {
         fd: FD_PUBLIC_VALUES,
@@ -120,10 +331,7 @@ pub fn commit<T: Serialize>(value: &T) {
 /// sp1_zkvm::io::commit_slice(&data);
 /// ```
 pub fn commit_slice(buf: &[u8]) {
-    let mut my_writer = SyscallWriter {
-        fd: FD_PUBLIC_VALUES,
-    };
-    my_writer.write_all(buf).unwrap();
+    write(FD_PUBLIC_VALUES, buf);
 }
 
 /// Hint a serializable object to the hint stream.
@@ -144,6 +352,7 @@ pub fn commit_slice(buf: &[u8]) {
 /// };
 /// sp1_zkvm::io::hint(&data);
 /// ```
+#[cfg(feature = "std")]
 pub fn hint<T: Serialize>(value: &T) {
     let writer = SyscallWriter { fd: FD_HINT };
     bincode::serialize_into(writer, value).expect("serialization failed");
@@ -157,8 +366,7 @@ pub fn hint<T: Serialize>(value: &T) {
 /// sp1_zkvm::io::hint_slice(&data);
 /// ```
 pub fn hint_slice(buf: &[u8]) {
-    let mut my_reader = SyscallWriter { fd: FD_HINT };
-    my_reader.write_all(buf).unwrap();
+    write(FD_HINT, buf);
 }
 
 /// Write the data `buf` to the file descriptor `fd`.
@@ -169,5 +377,101 @@ pub fn hint_slice(buf: &[u8]) {
 /// sp1_zkvm::io::write(3, &data);
 /// ```
 pub fn write(fd: u32, buf: &[u8]) {
-    SyscallWriter { fd }.write_all(buf).unwrap();
+    unsafe {
+        syscall_write(fd, buf.as_ptr(), buf.len());
+    }
+}
+
+/// The io syscalls, for the tests of this crate on the host, where the requests written to a hook
+/// are answered by a mock of the hook. Hints are only read from the responses of the mock.
+///
+/// Like the executor, the mock inserts the responses of a hook in front of the unread ones, and
+/// panics if a response is read while responses to an earlier request are still unread.
+#[cfg(all(test, not(target_os = "zkvm")))]
+pub(crate) mod mock {
+    use std::boxed::Box;
+    use std::cell::{Cell, RefCell};
+    use std::collections::VecDeque;
+    use std::vec::Vec;
+
+    type MockHook = Box<dyn FnMut(u32, &[u8]) -> Vec<Vec<u8>>>;
+
+    std::thread_local! {
+        static HOOK: RefCell<Option<MockHook>> = const { RefCell::new(None) };
+        /// The unread responses, with the number of hook invocations before the one of each.
+        static HINTS: RefCell<VecDeque<(u64, Vec<u8>)>> = const { RefCell::new(VecDeque::new()) };
+        static INVOCATIONS: Cell<u64> = const { Cell::new(0) };
+    }
+
+    /// Runs `f` with the requests written to any hook answered by `hook`, and returns its result
+    /// with the number of responses left unread.
+    ///
+    /// Only the tests of the hinted paths use it, which `no-hints` disables.
+    #[allow(dead_code)]
+    pub(crate) fn with_hook<T>(
+        hook: impl FnMut(u32, &[u8]) -> Vec<Vec<u8>> + 'static,
+        f: impl FnOnce() -> T,
+    ) -> (T, usize) {
+        HOOK.with(|mock| *mock.borrow_mut() = Some(Box::new(hook)));
+        INVOCATIONS.with(|invocations| invocations.set(0));
+        let result = f();
+        HOOK.with(|mock| *mock.borrow_mut() = None);
+        let unread = HINTS.with(|hints| hints.borrow_mut().drain(..).count());
+        (result, unread)
+    }
+
+    /// Returns the next response, after checking that no response to an earlier request is unread.
+    fn next_hint(hints: &VecDeque<(u64, Vec<u8>)>) -> Option<&Vec<u8>> {
+        let (seq, hint) = hints.front()?;
+        let skipped = hints
+            .iter()
+            .map(|(other, _)| other)
+            .filter(|other| *other < seq)
+            .min();
+        if let Some(skipped) = skipped {
+            panic!("read a response to hook invocation {seq} before the unread ones of {skipped}");
+        }
+        Some(hint)
+    }
+
+    #[no_mangle]
+    extern "C" fn syscall_write(fd: u32, write_buf: *const u8, nbytes: usize) {
+        let buf = unsafe { core::slice::from_raw_parts(write_buf, nbytes) };
+        let responses =
+            HOOK.with(|mock| mock.borrow_mut().as_mut().expect("no mock hook")(fd, buf));
+        let seq = INVOCATIONS.with(|invocations| invocations.replace(invocations.get() + 1));
+        HINTS.with(|hints| {
+            let mut hints = hints.borrow_mut();
+            for response in responses.into_iter().rev() {
+                hints.push_front((seq, response));
+            }
+        });
+    }
+
+    #[no_mangle]
+    extern "C" fn syscall_hint_len() -> usize {
+        HINTS.with(|hints| next_hint(&hints.borrow()).map_or(0, Vec::len))
+    }
+
+    #[no_mangle]
+    extern "C" fn syscall_hint_read(ptr: *mut u8, len: usize) {
+        let Some(hint) = HINTS.with(|hints| {
+            let mut hints = hints.borrow_mut();
+            next_hint(&hints)?;
+            hints.pop_front().map(|(_, hint)| hint)
+        }) else {
+            assert_eq!(len, 0, "no hint to read");
+            return;
+        };
+        assert_eq!(hint.len(), len);
+        unsafe { core::ptr::copy_nonoverlapping(hint.as_ptr(), ptr, len) };
+    }
+
+    #[no_mangle]
+    extern "C" fn syscall_enter_unconstrained() -> bool {
+        true
+    }
+
+    #[no_mangle]
+    extern "C" fn syscall_exit_unconstrained() {}
 }
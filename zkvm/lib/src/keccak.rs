@@ -0,0 +1,88 @@
+//! Keccak-256 hashing on top of the keccak_permute precompile.
+
+#[cfg(all(target_os = "zkvm", target_vendor = "succinct"))]
+use crate::syscall_keccak_permute;
+
+/// The rate of the Keccak-256 sponge in bytes.
+const RATE: usize = 136;
+
+/// An incremental Keccak-256 hasher.
+///
+/// Inside the zkVM, each absorbed block is permuted with the `keccak_permute` precompile.
+#[derive(Clone)]
+pub struct Keccak256 {
+    state: [u64; 25],
+    buf: [u8; RATE],
+    buf_len: usize,
+}
+
+impl Keccak256 {
+    /// Creates a new hasher with an empty state.
+    pub const fn new() -> Self {
+        Self {
+            state: [0; 25],
+            buf: [0; RATE],
+            buf_len: 0,
+        }
+    }
+
+    /// Absorbs `data` into the hasher.
+    pub fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            let take = (RATE - self.buf_len).min(data.len());
+            self.buf[self.buf_len..self.buf_len + take].copy_from_slice(&data[..take]);
+            self.buf_len += take;
+            data = &data[take..];
+            if self.buf_len == RATE {
+                self.absorb_block();
+            }
+        }
+    }
+
+    /// Pads the remaining input and returns the digest.
+    pub fn finalize(mut self) -> [u8; 32] {
+        self.buf[self.buf_len..].fill(0);
+        self.buf[self.buf_len] ^= 0x01;
+        self.buf[RATE - 1] ^= 0x80;
+        self.absorb_block();
+
+        let mut out = [0u8; 32];
+        for (chunk, lane) in out.chunks_exact_mut(8).zip(self.state.iter()) {
+            chunk.copy_from_slice(&lane.to_le_bytes());
+        }
+        out
+    }
+
+    fn absorb_block(&mut self) {
+        for (lane, chunk) in self.state.iter_mut().zip(self.buf.chunks_exact(8)) {
+            *lane ^= u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+        permute(&mut self.state);
+        self.buf_len = 0;
+    }
+}
+
+impl Default for Keccak256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Computes the Keccak-256 digest of `data`.
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(data);
+    hasher.finalize()
+}
+
+fn permute(state: &mut [u64; 25]) {
+    cfg_if::cfg_if! {
+        if #[cfg(all(target_os = "zkvm", target_vendor = "succinct"))] {
+            unsafe {
+                syscall_keccak_permute(state.as_mut_ptr());
+            }
+        } else {
+            tiny_keccak::keccakf(state);
+        }
+    }
+}
@@ -0,0 +1,460 @@
+//! Verification of the KZG proofs of EIP-4844 blobs, as specified in the Deneb consensus specs.
+//!
+//! Commitments and proofs are compressed G1 points, and field elements are big endian integers
+//! below the modulus of the BLS12-381 scalar field. The scalar field arithmetic of the evaluation
+//! of blobs is done with [`BigMod`], on the uint256 precompile with hinted reductions and
+//! inversions inside the zkVM. The pairings and the group operations are computed in software
+//! with amcl, as there is no pairing or G2 precompile.
+
+use crate::bigmod::BigMod;
+use crate::error::Error;
+use crate::sha256::{sha256, Sha256};
+use crate::utils::hex;
+use alloc::vec::Vec;
+use amcl::bls381::big::Big;
+use amcl::bls381::bls381::utils::{deserialize_g1, deserialize_g2, subgroup_check_g1};
+use amcl::bls381::ecp::ECP;
+use amcl::bls381::ecp2::ECP2;
+use amcl::bls381::pair;
+use core::convert::TryInto;
+
+/// The number of field elements of a blob.
+pub const FIELD_ELEMENTS_PER_BLOB: usize = 4096;
+
+/// The length of a blob.
+pub const BYTES_PER_BLOB: usize = FIELD_ELEMENTS_PER_BLOB * 32;
+
+/// The version byte of the versioned hashes of KZG commitments.
+pub const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+
+/// The domain separator of the Fiat-Shamir challenge of blob proofs.
+const FIAT_SHAMIR_PROTOCOL_DOMAIN: &[u8; 16] = b"FSBLOBVERIFY_V1_";
+
+/// `[s]G2`, the second G2 point of the trusted setup of the KZG ceremony.
+const TRUSTED_SETUP_G2: [u8; 96] = hex(concat!(
+    "b5bfd7dd8cdeb128843bc287230af38926187075cbfbefa81009a2ce615ac53d",
+    "2914e5870cb452d2afaaab24f3499f72185cbfee53492714734429b7b38608e2",
+    "3926c911cceceac9a36851477ba4c60b087041de621000edc98edada20c1def2",
+));
+
+/// An element of the scalar field, as a big endian integer below the modulus.
+type Scalar = [u8; 32];
+
+/// The modulus r of the scalar field.
+const MODULUS: [u8; 32] = hex("73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001");
+
+/// `7^((r - 1) / 4096)`, the primitive root of unity of the evaluation domain of blobs.
+const ROOT_OF_UNITY: [u8; 32] =
+    hex("564c0a11a0f704f4fc3e8acfe0f8245f0ad1347b378fbf96e206da11a5d36306");
+
+/// Verifies a proof that the polynomial of `commitment` evaluates to `y` at `z`, the
+/// `verify_kzg_proof` function of the specs.
+///
+/// Returns `Ok(false)` if the proof does not verify. Returns [`Error::InvalidKzgPoint`] if the
+/// commitment or the proof is not a valid encoding of a point of the G1 subgroup, and
+/// [`Error::InvalidFieldElement`] if `z` or `y` is not below the modulus.
+pub fn verify_kzg_proof(
+    commitment: &[u8; 48],
+    z: &[u8; 32],
+    y: &[u8; 32],
+    proof: &[u8; 48],
+) -> Result<bool, Error> {
+    let commitment = parse_g1(commitment)?;
+    let z = parse_field_element(z)?;
+    let y = parse_field_element(y)?;
+    let proof = parse_g1(proof)?;
+    Ok(verify_kzg_proof_impl(&commitment, &z, &y, &proof))
+}
+
+/// Verifies a proof that `commitment` is the commitment of a blob, the `verify_blob_kzg_proof`
+/// function of the specs.
+///
+/// The proof is checked at a Fiat-Shamir challenge derived from the blob and the commitment,
+/// where the polynomial of the blob is evaluated in evaluation form. Returns the errors of
+/// [`verify_kzg_proof`], and [`Error::InvalidFieldElement`] if an element of the blob is not
+/// below the modulus.
+pub fn verify_blob_kzg_proof(
+    blob: &[u8; BYTES_PER_BLOB],
+    commitment: &[u8; 48],
+    proof: &[u8; 48],
+) -> Result<bool, Error> {
+    let commitment_point = parse_g1(commitment)?;
+    let polynomial = blob
+        .chunks_exact(32)
+        .map(|element| parse_field_element(element.try_into().unwrap()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let proof = parse_g1(proof)?;
+
+    // The challenge hashes the degree of the polynomial as a 16-byte integer.
+    let mut hasher = Sha256::new();
+    hasher.update(FIAT_SHAMIR_PROTOCOL_DOMAIN);
+    hasher.update(&(FIELD_ELEMENTS_PER_BLOB as u128).to_be_bytes());
+    hasher.update(blob);
+    hasher.update(commitment);
+    let field = scalar_field();
+    let z = scalar(field.add(&hasher.finalize(), &[0]));
+
+    let y = evaluate_polynomial(&field, &polynomial, &z);
+    Ok(verify_kzg_proof_impl(&commitment_point, &z, &y, &proof))
+}
+
+/// Returns the versioned hash of a commitment, as in blob transactions: its SHA-256 hash with
+/// the first byte replaced by [`VERSIONED_HASH_VERSION_KZG`].
+pub fn kzg_to_versioned_hash(commitment: &[u8; 48]) -> [u8; 32] {
+    let mut hash = sha256(commitment);
+    hash[0] = VERSIONED_HASH_VERSION_KZG;
+    hash
+}
+
+/// Verifies the input of the point evaluation precompile, `versioned_hash || z || y ||
+/// commitment || proof`, checking that the versioned hash is the one of the commitment.
+///
+/// Returns `Ok(false)` if the versioned hash does not match or the proof does not verify, and
+/// the errors of [`verify_kzg_proof`].
+pub fn verify_point_evaluation(input: &[u8; 192]) -> Result<bool, Error> {
+    let commitment = input[96..144].try_into().unwrap();
+    if kzg_to_versioned_hash(commitment) != input[..32] {
+        return Ok(false);
+    }
+    verify_kzg_proof(
+        commitment,
+        input[32..64].try_into().unwrap(),
+        input[64..96].try_into().unwrap(),
+        input[144..].try_into().unwrap(),
+    )
+}
+
+/// Checks that `e(commitment - [y]G1, -G2) * e(proof, [s]G2 - [z]G2) = 1`.
+fn verify_kzg_proof_impl(commitment: &ECP, z: &Scalar, y: &Scalar, proof: &ECP) -> bool {
+    // The setup point is a valid constant.
+    let mut x_minus_z = deserialize_g2(&TRUSTED_SETUP_G2).unwrap();
+    x_minus_z.sub(&ECP2::generator().mul(&from_be_bytes(z)));
+    let mut p_minus_y = commitment.clone();
+    p_minus_y.sub(&ECP::generator().mul(&from_be_bytes(y)));
+    let mut minus_g2 = ECP2::generator();
+    minus_g2.neg();
+
+    // Pairings with the point at infinity are one, and are left out rather than given to amcl.
+    let pairs = [(&minus_g2, &p_minus_y), (&x_minus_z, proof)];
+    let mut pairs = pairs
+        .iter()
+        .filter(|(g2, g1)| !g2.is_infinity() && !g1.is_infinity());
+    let product = match (pairs.next(), pairs.next()) {
+        (None, _) => return true,
+        (Some((g2, g1)), None) => pair::ate(g2, g1),
+        (Some((g2, g1)), Some((h2, h1))) => pair::ate2(g2, g1, h2, h1),
+    };
+    pair::fexp(&product).is_unity()
+}
+
+/// Evaluates at `z` the polynomial whose values at the roots of unity, in bit-reversed order,
+/// are `polynomial`, with the barycentric formula
+/// `(z^N - 1) / N * sum(p_i * w_i / (z - w_i))`.
+fn evaluate_polynomial(field: &BigMod, polynomial: &[Scalar], z: &Scalar) -> Scalar {
+    let bits = FIELD_ELEMENTS_PER_BLOB.trailing_zeros();
+
+    let mut roots = Vec::with_capacity(FIELD_ELEMENTS_PER_BLOB);
+    let mut power = scalar(field.add(&[1], &[0]));
+    for _ in 0..FIELD_ELEMENTS_PER_BLOB {
+        roots.push(power);
+        power = scalar(field.mul(&power, &ROOT_OF_UNITY));
+    }
+    let root_at = |i: usize| &roots[i.reverse_bits() >> (usize::BITS - bits)];
+
+    // The formula divides by zero at the roots themselves, where the value is known.
+    if let Some(i) = (0..FIELD_ELEMENTS_PER_BLOB).find(|&i| root_at(i) == z) {
+        return polynomial[i];
+    }
+
+    // The inverses of `z - w_i` are computed with a single inversion, from the prefix products.
+    let mut differences = Vec::with_capacity(FIELD_ELEMENTS_PER_BLOB);
+    let mut prefix_products = Vec::with_capacity(FIELD_ELEMENTS_PER_BLOB);
+    let mut product = scalar(field.add(&[1], &[0]));
+    for i in 0..FIELD_ELEMENTS_PER_BLOB {
+        let difference = scalar(field.sub(z, root_at(i)));
+        prefix_products.push(product);
+        product = scalar(field.mul(&product, &difference));
+        differences.push(difference);
+    }
+    // The product of nonzero elements of a field is not zero.
+    let mut inverse = scalar(field.inv(&product).unwrap());
+
+    let mut sum = [0; 32];
+    for i in (0..FIELD_ELEMENTS_PER_BLOB).rev() {
+        let difference_inverse = field.mul(&inverse, &prefix_products[i]);
+        inverse = scalar(field.mul(&inverse, &differences[i]));
+        let term = field.mul(&field.mul(&polynomial[i], root_at(i)), &difference_inverse);
+        sum = scalar(field.add(&sum, &term));
+    }
+
+    let mut z_pow = *z;
+    for _ in 0..bits {
+        z_pow = scalar(field.mul(&z_pow, &z_pow));
+    }
+    let vanishing = field.sub(&z_pow, &[1]);
+    let width_inverse = field
+        .inv(&(FIELD_ELEMENTS_PER_BLOB as u32).to_be_bytes())
+        .unwrap();
+    scalar(field.mul(&field.mul(&sum, &vanishing), &width_inverse))
+}
+
+/// Parses a compressed G1 point, checking that it is in the subgroup.
+fn parse_g1(bytes: &[u8; 48]) -> Result<ECP, Error> {
+    let point = deserialize_g1(bytes).map_err(|_| Error::InvalidKzgPoint)?;
+    if !point.is_infinity() && !subgroup_check_g1(&point) {
+        return Err(Error::InvalidKzgPoint);
+    }
+    Ok(point)
+}
+
+/// Parses a field element, which must be below the modulus.
+fn parse_field_element(bytes: &[u8; 32]) -> Result<Scalar, Error> {
+    // Big endian integers of the same length compare as byte strings.
+    if *bytes >= MODULUS {
+        return Err(Error::InvalidFieldElement);
+    }
+    Ok(*bytes)
+}
+
+fn scalar_field() -> BigMod<'static> {
+    // The modulus is a valid constant.
+    BigMod::new(&MODULUS).unwrap()
+}
+
+/// Converts a result of the scalar field, which has as many bytes as the modulus.
+fn scalar(bytes: Vec<u8>) -> Scalar {
+    bytes.try_into().unwrap()
+}
+
+/// Converts a 32-byte big endian integer, as amcl reads integers of the size of the base field.
+fn from_be_bytes(bytes: &[u8; 32]) -> Big {
+    let mut padded = [0u8; 48];
+    padded[16..].copy_from_slice(bytes);
+    Big::from_bytes(&padded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::boxed::Box;
+    use alloc::format;
+
+    fn g1(hex_str: &str) -> [u8; 48] {
+        hex::decode(hex_str).unwrap().try_into().unwrap()
+    }
+
+    fn field_element(hex_str: &str) -> Scalar {
+        hex::decode(hex_str).unwrap().try_into().unwrap()
+    }
+
+    fn small(value: u8) -> Scalar {
+        let mut bytes = [0u8; 32];
+        bytes[31] = value;
+        bytes
+    }
+
+    fn constant_blob(element: Scalar) -> Box<[u8; BYTES_PER_BLOB]> {
+        element
+            .repeat(FIELD_ELEMENTS_PER_BLOB)
+            .into_boxed_slice()
+            .try_into()
+            .unwrap()
+    }
+
+    fn infinity() -> [u8; 48] {
+        g1(&format!("c0{}", "00".repeat(47)))
+    }
+
+    /// The point with x = 4 is on the curve but not in the subgroup.
+    fn not_in_subgroup() -> [u8; 48] {
+        g1(&format!("80{}04", "00".repeat(46)))
+    }
+
+    /// No point has x = 1.
+    fn not_on_curve() -> [u8; 48] {
+        g1(&format!("80{}01", "00".repeat(46)))
+    }
+
+    /// The first test of `pointEvaluation.json` in go-ethereum, a proof from the trusted setup
+    /// at the first root of unity.
+    const POINT_EVALUATION_INPUT: &str = concat!(
+        "01e798154708fe7789429634053cbf9f99b619f9f084048927333fce637f549b",
+        "564c0a11a0f704f4fc3e8acfe0f8245f0ad1347b378fbf96e206da11a5d36306",
+        "24d25032e67a7e6a4910df5834b8fe70e6bcfeeac0352434196bdf4b2485d5a1",
+        "8f59a8d2a1a625a17f3fea0fe5eb8c896db3764f3185481bc22f91b4aaffcca2",
+        "5f26936857bc3a7c2539ea8ec3a952b7873033e038326e87ed3e1276fd140253",
+        "fa08e9fc25fb2d9a98527fc22a2c9612fbeafdad446cbc7bcdbdcd780af2c16a",
+    );
+
+    fn point_evaluation_input() -> [u8; 192] {
+        hex::decode(POINT_EVALUATION_INPUT)
+            .unwrap()
+            .try_into()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_verify_kzg_proof() {
+        let input = point_evaluation_input();
+        let commitment: [u8; 48] = input[96..144].try_into().unwrap();
+        let z: Scalar = input[32..64].try_into().unwrap();
+        let y: Scalar = input[64..96].try_into().unwrap();
+        let proof: [u8; 48] = input[144..].try_into().unwrap();
+        assert_eq!(z, ROOT_OF_UNITY);
+        assert_eq!(verify_kzg_proof(&commitment, &z, &y, &proof), Ok(true));
+
+        let mut other_y = y;
+        other_y[31] ^= 1;
+        let mut other_z = z;
+        other_z[31] ^= 1;
+        let generator = g1(concat!(
+            "97f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905",
+            "a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb"
+        ));
+        for (commitment, z, y, proof) in [
+            (commitment, z, other_y, proof),
+            (commitment, other_z, y, proof),
+            (commitment, z, y, generator),
+            (commitment, z, y, infinity()),
+            (generator, z, y, proof),
+            (proof, z, y, commitment),
+        ] {
+            assert_eq!(verify_kzg_proof(&commitment, &z, &y, &proof), Ok(false));
+        }
+
+        // The zero polynomial commits to the point at infinity, as do its proofs, at any point.
+        for z in [small(0), small(1), ROOT_OF_UNITY, [0x42; 32]] {
+            assert_eq!(
+                verify_kzg_proof(&infinity(), &z, &small(0), &infinity()),
+                Ok(true)
+            );
+            assert_eq!(
+                verify_kzg_proof(&infinity(), &z, &small(1), &infinity()),
+                Ok(false)
+            );
+        }
+
+        for point in [not_in_subgroup(), not_on_curve()] {
+            assert_eq!(
+                verify_kzg_proof(&point, &z, &y, &proof),
+                Err(Error::InvalidKzgPoint)
+            );
+            assert_eq!(
+                verify_kzg_proof(&commitment, &z, &y, &point),
+                Err(Error::InvalidKzgPoint)
+            );
+        }
+        assert_eq!(
+            verify_kzg_proof(&commitment, &MODULUS, &y, &proof),
+            Err(Error::InvalidFieldElement)
+        );
+        assert_eq!(
+            verify_kzg_proof(&commitment, &z, &MODULUS, &proof),
+            Err(Error::InvalidFieldElement)
+        );
+    }
+
+    #[test]
+    fn test_verify_point_evaluation() {
+        let mut input = point_evaluation_input();
+        assert_eq!(
+            kzg_to_versioned_hash(input[96..144].try_into().unwrap()),
+            input[..32]
+        );
+        assert_eq!(verify_point_evaluation(&input), Ok(true));
+        input[95] ^= 1;
+        assert_eq!(verify_point_evaluation(&input), Ok(false));
+        input[95] ^= 1;
+        input[0] = 0x02;
+        assert_eq!(verify_point_evaluation(&input), Ok(false));
+
+        assert_eq!(
+            hex::encode(kzg_to_versioned_hash(&infinity())),
+            "010657f37554c781402a22917dee2f75def7ab966d7b770905398eba3c444014"
+        );
+    }
+
+    #[test]
+    fn test_verify_blob_kzg_proof() {
+        let double = g1(concat!(
+            "a572cbea904d67468808c8eb50a9450c9721db3091280125",
+            "43902d0ac358a62ae28f75bb8f1c7c42c39a8c5529bf0f4e"
+        ));
+        let negated = g1(concat!(
+            "b7f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905",
+            "a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb"
+        ));
+        let mut minus_one = MODULUS;
+        minus_one[31] = 0;
+
+        // The polynomial with the constant value c commits to [c]G1, and its proofs are the
+        // commitment to the zero quotient.
+        for (blob, commitment, proof, valid) in [
+            (constant_blob(small(0)), infinity(), infinity(), true),
+            (constant_blob(small(2)), double, infinity(), true),
+            (constant_blob(minus_one), negated, infinity(), true),
+            (constant_blob(small(2)), negated, infinity(), false),
+            (constant_blob(small(2)), double, double, false),
+        ] {
+            assert_eq!(verify_blob_kzg_proof(&blob, &commitment, &proof), Ok(valid));
+        }
+
+        let mut invalid_blob = constant_blob(small(0));
+        invalid_blob[7 * 32..8 * 32].copy_from_slice(&MODULUS);
+        assert_eq!(
+            verify_blob_kzg_proof(&invalid_blob, &infinity(), &infinity()),
+            Err(Error::InvalidFieldElement)
+        );
+        for point in [not_in_subgroup(), not_on_curve()] {
+            assert_eq!(
+                verify_blob_kzg_proof(&constant_blob(small(0)), &point, &infinity()),
+                Err(Error::InvalidKzgPoint)
+            );
+            assert_eq!(
+                verify_blob_kzg_proof(&constant_blob(small(0)), &infinity(), &point),
+                Err(Error::InvalidKzgPoint)
+            );
+        }
+    }
+
+    #[test]
+    fn test_evaluate_polynomial() {
+        let field = scalar_field();
+        // `3 + 5x + 7x^2 + (r - 1)x^4095`, as its nonzero terms.
+        let terms = [
+            (0u32, small(3)),
+            (1, small(5)),
+            (2, small(7)),
+            (
+                FIELD_ELEMENTS_PER_BLOB as u32 - 1,
+                scalar(field.sub(&[0], &[1])),
+            ),
+        ];
+        let evaluate = |x: &Scalar| {
+            terms.iter().fold([0; 32], |acc, (exponent, c)| {
+                let term = field.mul(c, &field.pow(x, &exponent.to_be_bytes()));
+                scalar(field.add(&acc, &term))
+            })
+        };
+
+        // Its values at the roots of unity, in bit-reversed order.
+        let bits = FIELD_ELEMENTS_PER_BLOB.trailing_zeros();
+        let polynomial = (0..FIELD_ELEMENTS_PER_BLOB)
+            .map(|i| {
+                let exponent = (i.reverse_bits() >> (usize::BITS - bits)) as u32;
+                evaluate(&scalar(field.pow(&ROOT_OF_UNITY, &exponent.to_be_bytes())))
+            })
+            .collect::<Vec<_>>();
+
+        let root_cubed = scalar(field.pow(&ROOT_OF_UNITY, &[3]));
+        for z in [
+            small(0),
+            small(2),
+            field_element(&"42".repeat(32)),
+            ROOT_OF_UNITY,
+            root_cubed,
+        ] {
+            assert_eq!(evaluate_polynomial(&field, &polynomial, &z), evaluate(&z));
+        }
+    }
+}
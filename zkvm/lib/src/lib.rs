@@ -1,16 +1,97 @@
 //! System calls for the SP1 zkVM.
-
+//!
+//! # Hints
+//!
+//! By default, [`secp256k1::ecrecover`] asks a host hook for the public key and the inverse of
+//! `s`, and constrains both in the VM. With the `no-hints` feature, no hook data is consumed:
+//! the public key is computed in the VM as `r^-1 * (s * R - z * G)`. The outputs are identical,
+//! but the constrained path pays for lifting `r` to the nonce point and a software inversion of
+//! `r`, which takes a few hundred scalar multiplications, while the hinted path checks the
+//! provided inverse with a single multiplication. APIs that only exist to query hooks, such as
+//! [`secp256k1::unconstrained_ecrecover`], are not available with this feature. Outside of the
+//! zkVM, where no hook answers, the public key is always recovered directly.
+//!
+//! # `no_std`
+//!
+//! The crate is `no_std` when the default `std` feature is disabled. The `alloc` feature brings
+//! back the conveniences that return a `Vec`, such as [`io::read_vec`]; the serialization helpers
+//! of [`io`] require `std`. Hook responses can only be read with `alloc`, so without it
+//! [`secp256k1::ecrecover`] behaves as with the `no-hints` feature.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+// The tests need `std` and its macros, whichever features the crate is built with.
+#[cfg(all(test, not(feature = "std")))]
+#[macro_use]
+extern crate std;
+
+#[cfg(feature = "abi")]
+pub mod abi;
+#[cfg(feature = "aead")]
+pub mod aead;
+#[cfg(feature = "bigmod")]
+pub mod bigmod;
+#[cfg(feature = "bitcoin")]
+pub mod bitcoin;
+pub mod blake2b;
 #[cfg(feature = "bls12381")]
 pub mod bls12381;
 #[cfg(feature = "bn254")]
 pub mod bn254;
+#[cfg(feature = "clique")]
+pub mod clique;
+#[cfg(feature = "dnssec")]
+pub mod dnssec;
+#[cfg(feature = "ed25519")]
+pub mod ed25519;
+#[cfg(feature = "eip7702")]
+pub mod eip7702;
+#[cfg(feature = "enr")]
+pub mod enr;
+pub mod error;
 pub mod io;
+pub mod keccak;
+#[cfg(feature = "kzg")]
+pub mod kzg;
+#[cfg(feature = "minisign")]
+pub mod minisign;
+#[cfg(feature = "mpt")]
+pub mod mpt;
+#[cfg(feature = "nostr")]
+pub mod nostr;
+#[cfg(feature = "p384")]
+pub mod p384;
+#[cfg(any(
+    feature = "clique",
+    feature = "eip7702",
+    feature = "enr",
+    feature = "mpt"
+))]
+mod rlp;
+#[cfg(feature = "rsa")]
+pub mod rsa;
 #[cfg(feature = "secp256k1")]
 pub mod secp256k1;
+#[cfg(feature = "secp256r1")]
+pub mod secp256r1;
+pub mod sha256;
+#[cfg(feature = "signature")]
+pub mod signature;
+#[cfg(feature = "ssh")]
+pub mod ssh;
+#[cfg(feature = "tendermint")]
+pub mod tendermint;
+#[cfg(feature = "tls")]
+pub mod tls;
 pub mod unconstrained;
 pub mod utils;
 #[cfg(feature = "verify")]
 pub mod verify;
+#[cfg(feature = "x509")]
+pub mod x509;
 
 extern "C" {
     /// Halts the program with the given exit code.
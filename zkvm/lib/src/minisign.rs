@@ -0,0 +1,196 @@
+//! Verification of minisign signatures, and of the signify signatures compatible with them.
+
+use crate::blake2b::blake2b512;
+use crate::ed25519;
+use crate::error::Error;
+use crate::utils::decode_base64;
+use core::convert::TryInto;
+
+/// The algorithm of keys, and of signatures over the message itself.
+const ALG_ED: &[u8] = b"Ed";
+/// The algorithm of signatures over the BLAKE2b-512 digest of the message.
+const ALG_ED_PREHASHED: &[u8] = b"ED";
+const UNTRUSTED_COMMENT: &str = "untrusted comment: ";
+const TRUSTED_COMMENT: &str = "trusted comment: ";
+
+/// Verifies a minisign signature file of `message` with a base64 public key, i.e. the second line
+/// of a minisign `.pub` file.
+///
+/// Both the legacy `Ed` and the prehashed `ED` signature algorithms are supported. The untrusted
+/// comment is ignored, while the trusted comment must be authenticated by the global signature.
+/// signify signature files, which have no trusted comment, are accepted for `Ed` signatures.
+///
+/// Returns `Ok(false)` if the signature or the global signature does not verify, and
+/// [`Error::MinisignKeyIdMismatch`] if the signature is by another key.
+pub fn verify_minisign(
+    public_key_b64: &str,
+    signature_file: &str,
+    message: &[u8],
+) -> Result<bool, Error> {
+    let public_key = decode_base64(public_key_b64.trim())
+        .filter(|key| key.len() == 42 && &key[..2] == ALG_ED)
+        .ok_or(Error::MinisignMalformed)?;
+    let key_id = &public_key[2..10];
+    let public_key: &[u8; 32] = public_key[10..].try_into().unwrap();
+
+    let mut lines = signature_file.lines();
+    lines
+        .next()
+        .filter(|line| line.starts_with(UNTRUSTED_COMMENT))
+        .ok_or(Error::MinisignMalformed)?;
+    let signature = lines
+        .next()
+        .and_then(|line| decode_base64(line.trim()))
+        .filter(|signature| signature.len() == 74)
+        .ok_or(Error::MinisignMalformed)?;
+    let trusted = match lines.next() {
+        Some(line) => {
+            let comment = line
+                .strip_prefix(TRUSTED_COMMENT)
+                .ok_or(Error::MinisignMalformed)?;
+            let global_signature = lines
+                .next()
+                .and_then(|line| decode_base64(line.trim()))
+                .filter(|signature| signature.len() == 64)
+                .ok_or(Error::MinisignMalformed)?;
+            Some((comment, global_signature))
+        }
+        None => None,
+    };
+    if lines.any(|line| !line.trim().is_empty()) {
+        return Err(Error::MinisignMalformed);
+    }
+
+    let (algorithm, rest) = signature.split_at(2);
+    let (signature_key_id, signature) = rest.split_at(8);
+    let signature: &[u8; 64] = signature.try_into().unwrap();
+    if signature_key_id != key_id {
+        return Err(Error::MinisignKeyIdMismatch);
+    }
+
+    let verified = match algorithm {
+        ALG_ED => ed25519::verify(public_key, message, signature),
+        // Prehashed signatures are only made by minisign, which always adds a trusted comment.
+        ALG_ED_PREHASHED if trusted.is_some() => {
+            ed25519::verify(public_key, &blake2b512(message), signature)
+        }
+        _ => return Err(Error::MinisignMalformed),
+    };
+    if !verified {
+        return Ok(false);
+    }
+
+    // The global signature is over the signature and the trusted comment.
+    if let Some((comment, global_signature)) = trusted {
+        let mut signed = signature.to_vec();
+        signed.extend_from_slice(comment.as_bytes());
+        let global_signature: &[u8; 64] = global_signature[..].try_into().unwrap();
+        return Ok(ed25519::verify(public_key, &signed, global_signature));
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ed25519::host;
+    use crate::utils::encode_base64;
+    use std::string::String;
+    use std::vec::Vec;
+
+    const SEED: [u8; 32] = [3; 32];
+    const KEY_ID: [u8; 8] = [0x8a, 0x3c, 0x1f, 0x72, 0x05, 0xe4, 0x9b, 0x60];
+    const MESSAGE: &[u8] = b"minisign test message\n";
+    const COMMENT: &str = "timestamp:1722470400\tfile:message.txt\thashed";
+
+    fn public_key_b64() -> String {
+        let (public_key, _) = host::sign(&SEED, b"");
+        encode_base64(&[ALG_ED, &KEY_ID, &public_key].concat())
+    }
+
+    /// Returns a signature file as written by minisign, or by signify without a trusted comment.
+    fn signature_file(algorithm: &[u8], key_id: &[u8; 8], comment: Option<&str>) -> String {
+        let signed = match algorithm {
+            ALG_ED_PREHASHED => blake2b512(MESSAGE).to_vec(),
+            _ => MESSAGE.to_vec(),
+        };
+        let (_, signature) = host::sign(&SEED, &signed);
+        let mut file = format!(
+            "{UNTRUSTED_COMMENT}signature from minisign secret key\n{}\n",
+            encode_base64(&[algorithm, key_id, &signature].concat())
+        );
+        if let Some(comment) = comment {
+            let (_, global_signature) =
+                host::sign(&SEED, &[&signature[..], comment.as_bytes()].concat());
+            file += &format!(
+                "{TRUSTED_COMMENT}{comment}\n{}\n",
+                encode_base64(&global_signature)
+            );
+        }
+        file
+    }
+
+    #[test]
+    fn test_verify_minisign() {
+        let public_key = public_key_b64();
+        for (algorithm, comment) in [
+            (ALG_ED_PREHASHED, Some(COMMENT)),
+            (ALG_ED, Some(COMMENT)),
+            (ALG_ED, None),
+        ] {
+            let file = signature_file(algorithm, &KEY_ID, comment);
+            assert_eq!(verify_minisign(&public_key, &file, MESSAGE), Ok(true));
+            assert_eq!(
+                verify_minisign(&public_key, &file, b"another message"),
+                Ok(false)
+            );
+        }
+
+        // The trusted comment is authenticated by the global signature.
+        let file = signature_file(ALG_ED_PREHASHED, &KEY_ID, Some(COMMENT));
+        let forged = file.replace("timestamp:1722470400", "timestamp:1722470401");
+        assert_eq!(verify_minisign(&public_key, &forged, MESSAGE), Ok(false));
+
+        let mut other_key_id = KEY_ID;
+        other_key_id[0] ^= 1;
+        let file = signature_file(ALG_ED_PREHASHED, &other_key_id, Some(COMMENT));
+        assert_eq!(
+            verify_minisign(&public_key, &file, MESSAGE),
+            Err(Error::MinisignKeyIdMismatch)
+        );
+    }
+
+    #[test]
+    fn test_verify_minisign_rejects_malformed_files() {
+        let public_key = public_key_b64();
+        let file = signature_file(ALG_ED_PREHASHED, &KEY_ID, Some(COMMENT));
+        let malformed = [
+            // A prehashed signature without a trusted comment.
+            signature_file(ALG_ED_PREHASHED, &KEY_ID, None),
+            signature_file(b"EE", &KEY_ID, Some(COMMENT)),
+            file.replacen(UNTRUSTED_COMMENT, "comment: ", 1),
+            file.replacen("\ntrusted comment: ", "\ncomment: ", 1),
+            file.clone() + "trailing line\n",
+            file.lines().take(3).collect::<Vec<_>>().join("\n"),
+        ];
+        for file in &malformed {
+            assert_eq!(
+                verify_minisign(&public_key, file, MESSAGE),
+                Err(Error::MinisignMalformed),
+                "{file}"
+            );
+        }
+
+        let (public_key_bytes, _) = host::sign(&SEED, b"");
+        for key in [
+            encode_base64(&[b"ED", &KEY_ID[..], &public_key_bytes].concat()),
+            encode_base64(&[ALG_ED, &KEY_ID[..]].concat()),
+            String::from("not base64"),
+        ] {
+            assert_eq!(
+                verify_minisign(&key, &file, MESSAGE),
+                Err(Error::MinisignMalformed)
+            );
+        }
+    }
+}
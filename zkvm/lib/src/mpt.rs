@@ -0,0 +1,473 @@
+//! Verification of Merkle Patricia trie proofs, and of the receipts and transactions of blocks.
+//!
+//! Proofs are the nodes on the path from the root to the key, ordered from the root, as returned
+//! by `eth_getProof` and the proof APIs of clients. Nodes whose encoding is shorter than 32 bytes
+//! are embedded in their parent instead of being referenced by their hash, and are not part of
+//! the proof. The receipts and transactions tries of a block are keyed by the RLP encoding of the
+//! index of the transaction.
+
+use crate::error::Error;
+use crate::keccak::keccak256;
+use crate::rlp::{self, Item};
+use alloc::vec::Vec;
+use core::convert::TryInto;
+
+/// The transaction types whose receipts are encoded as their type followed by the RLP list of
+/// the receipt: EIP-2930, EIP-1559 and EIP-4844 transactions.
+const TYPED_RECEIPTS: [u8; 3] = [0x01, 0x02, 0x03];
+
+/// A log emitted during the execution of a transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Log {
+    pub address: [u8; 20],
+    pub topics: Vec<[u8; 32]>,
+    pub data: Vec<u8>,
+}
+
+/// The outcome of a transaction recorded in its receipt.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ReceiptOutcome {
+    /// The state root after the transaction, in receipts before Byzantium.
+    PostState([u8; 32]),
+    /// Whether the transaction succeeded, in receipts since Byzantium.
+    Status(bool),
+}
+
+/// The receipt of a transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Receipt {
+    /// The type of the transaction, 0 for legacy transactions.
+    pub tx_type: u8,
+    pub outcome: ReceiptOutcome,
+    pub cumulative_gas_used: u64,
+    pub logs_bloom: [u8; 256],
+    pub logs: Vec<Log>,
+}
+
+/// Verifies a proof of the value of `key` in the trie with the given root.
+///
+/// Returns `Ok(None)` if the proof shows that the key is not in the trie. Returns
+/// [`Error::MptProofInvalid`] if a node does not match the hash referencing it, if a node is
+/// malformed, or if the proof has nodes past the end of the path.
+pub fn verify_proof<'a>(
+    root: &[u8; 32],
+    key: &[u8],
+    proof_nodes: &[&'a [u8]],
+) -> Result<Option<&'a [u8]>, Error> {
+    let nibbles = key
+        .iter()
+        .flat_map(|byte| [byte >> 4, byte & 0xf])
+        .collect::<Vec<_>>();
+    let mut nibbles = &nibbles[..];
+    let mut proof_nodes = proof_nodes.iter().copied();
+
+    // The root is referenced by its hash even if its encoding is short.
+    let mut node = proof_nodes.next().ok_or(Error::MptProofInvalid)?;
+    if keccak256(node) != *root {
+        return Err(Error::MptProofInvalid);
+    }
+
+    let value = loop {
+        let items = rlp::decode_list(node).ok_or(Error::MptProofInvalid)?;
+        let child = match items.len() {
+            17 => match nibbles.split_first() {
+                Some((&nibble, rest)) => {
+                    nibbles = rest;
+                    items[nibble as usize]
+                }
+                None => break value_of(&items[16])?,
+            },
+            2 => {
+                let (is_leaf, path) = decode_path(&items[0]).ok_or(Error::MptProofInvalid)?;
+                if is_leaf {
+                    break if path == nibbles {
+                        value_of(&items[1])?
+                    } else {
+                        None
+                    };
+                }
+                match nibbles.strip_prefix(&path[..]) {
+                    Some(rest) if !path.is_empty() => {
+                        nibbles = rest;
+                        items[1]
+                    }
+                    Some(_) => return Err(Error::MptProofInvalid),
+                    None => break None,
+                }
+            }
+            _ => return Err(Error::MptProofInvalid),
+        };
+
+        // Children are referenced by their hash, embedded, or absent.
+        node = match (child.is_list, child.payload.len()) {
+            (true, _) if child.encoding.len() < 32 => child.encoding,
+            (false, 32) => {
+                let next = proof_nodes.next().ok_or(Error::MptProofInvalid)?;
+                if keccak256(next) != child.payload {
+                    return Err(Error::MptProofInvalid);
+                }
+                next
+            }
+            (false, 0) => break None,
+            _ => return Err(Error::MptProofInvalid),
+        };
+    };
+
+    if proof_nodes.next().is_some() {
+        return Err(Error::MptProofInvalid);
+    }
+    Ok(value)
+}
+
+/// Verifies a proof of the receipt of the transaction at `tx_index` against the receipts root of
+/// a block, and decodes the receipt.
+///
+/// Returns the errors of [`verify_proof`], [`Error::MptKeyNotFound`] if the proof shows that the
+/// block has no such transaction, and [`Error::ReceiptMalformed`] if the receipt cannot be
+/// decoded.
+pub fn verify_receipt_proof(
+    receipts_root: &[u8; 32],
+    tx_index: u64,
+    proof_nodes: &[&[u8]],
+) -> Result<Receipt, Error> {
+    let encoded = verify_proof(receipts_root, &index_key(tx_index), proof_nodes)?
+        .ok_or(Error::MptKeyNotFound)?;
+    decode_receipt(encoded).ok_or(Error::ReceiptMalformed)
+}
+
+/// Verifies a proof of the transaction at `tx_index` against the transactions root of a block,
+/// returning its encoding: the RLP list of a legacy transaction, or the type of a typed
+/// transaction followed by its payload.
+///
+/// Returns the errors of [`verify_proof`], and [`Error::MptKeyNotFound`] if the proof shows that
+/// the block has no such transaction.
+pub fn verify_transaction_proof<'a>(
+    transactions_root: &[u8; 32],
+    tx_index: u64,
+    proof_nodes: &[&'a [u8]],
+) -> Result<&'a [u8], Error> {
+    verify_proof(transactions_root, &index_key(tx_index), proof_nodes)?.ok_or(Error::MptKeyNotFound)
+}
+
+/// Returns the logs of a receipt that were emitted by `address`, and whose first topic is
+/// `topic0`, when they are given.
+pub fn extract_logs(
+    receipt: &Receipt,
+    address: Option<[u8; 20]>,
+    topic0: Option<[u8; 32]>,
+) -> Vec<Log> {
+    receipt
+        .logs
+        .iter()
+        .filter(|log| address.map_or(true, |address| log.address == address))
+        .filter(|log| topic0.map_or(true, |topic0| log.topics.first() == Some(&topic0)))
+        .cloned()
+        .collect()
+}
+
+/// The key of a transaction in the receipts and transactions tries.
+fn index_key(tx_index: u64) -> Vec<u8> {
+    let mut key = Vec::with_capacity(9);
+    rlp::put_u64(&mut key, tx_index);
+    key
+}
+
+/// Returns the value stored in a leaf or a branch, or `None` if a branch has no value.
+fn value_of<'a>(item: &Item<'a>) -> Result<Option<&'a [u8]>, Error> {
+    match (item.is_list, item.payload.is_empty()) {
+        (false, true) => Ok(None),
+        (false, false) => Ok(Some(item.payload)),
+        (true, _) => Err(Error::MptProofInvalid),
+    }
+}
+
+/// Decodes the hex-prefix encoded path of a leaf or an extension, returning whether the node is
+/// a leaf and the nibbles of the path.
+fn decode_path(item: &Item) -> Option<(bool, Vec<u8>)> {
+    let (&first, rest) = item.payload.split_first().filter(|_| !item.is_list)?;
+    let flags = first >> 4;
+    if flags > 3 || (flags & 1 == 0 && first & 0xf != 0) {
+        return None;
+    }
+    let mut path = Vec::with_capacity(2 * rest.len() + 1);
+    if flags & 1 == 1 {
+        path.push(first & 0xf);
+    }
+    path.extend(rest.iter().flat_map(|byte| [byte >> 4, byte & 0xf]));
+    Some((flags & 2 == 2, path))
+}
+
+/// Decodes a legacy receipt, or a typed receipt of one of the [`TYPED_RECEIPTS`] types.
+fn decode_receipt(encoded: &[u8]) -> Option<Receipt> {
+    let (tx_type, body) = match encoded.split_first()? {
+        (&tx_type, body) if TYPED_RECEIPTS.contains(&tx_type) => (tx_type, body),
+        // Legacy receipts are RLP lists, whose first byte is at least 0xc0.
+        (&first, _) if first >= 0xc0 => (0, encoded),
+        _ => return None,
+    };
+
+    let fields = rlp::decode_list(body)?;
+    let [outcome, cumulative_gas_used, logs_bloom, logs] = &fields[..] else {
+        return None;
+    };
+    if outcome.is_list || cumulative_gas_used.is_list || logs_bloom.is_list || !logs.is_list {
+        return None;
+    }
+
+    let outcome = match outcome.payload {
+        [] => ReceiptOutcome::Status(false),
+        [1] => ReceiptOutcome::Status(true),
+        root if root.len() == 32 => ReceiptOutcome::PostState(root.try_into().unwrap()),
+        _ => return None,
+    };
+    let logs = rlp::list_items(logs.payload)?
+        .iter()
+        .map(decode_log)
+        .collect::<Option<Vec<_>>>()?;
+    Some(Receipt {
+        tx_type,
+        outcome,
+        cumulative_gas_used: rlp::decode_u64(cumulative_gas_used.payload)?,
+        logs_bloom: logs_bloom.payload.try_into().ok()?,
+        logs,
+    })
+}
+
+/// Decodes a log, the list `[address, [topic, ...], data]`.
+fn decode_log(log: &Item) -> Option<Log> {
+    if !log.is_list {
+        return None;
+    }
+    let fields = rlp::list_items(log.payload)?;
+    let [address, topics, data] = &fields[..] else {
+        return None;
+    };
+    if address.is_list || !topics.is_list || data.is_list {
+        return None;
+    }
+    let topics = rlp::list_items(topics.payload)?
+        .iter()
+        .map(|topic| {
+            if topic.is_list {
+                None
+            } else {
+                topic.payload.try_into().ok()
+            }
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(Log {
+        address: address.payload.try_into().ok()?,
+        topics,
+        data: data.payload.to_vec(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_receipt_proof() {
+        fn put_len(out: &mut Vec<u8>, offset: u8, len: usize) {
+            if len < 56 {
+                out.push(offset + len as u8);
+            } else {
+                let len_bytes = (len as u64).to_be_bytes();
+                let len_bytes = &len_bytes[len_bytes.iter().position(|&b| b != 0).unwrap()..];
+                out.push(offset + 55 + len_bytes.len() as u8);
+                out.extend_from_slice(len_bytes);
+            }
+        }
+        fn string(bytes: &[u8]) -> Vec<u8> {
+            match bytes {
+                [byte] if *byte < 0x80 => vec![*byte],
+                _ => {
+                    let mut out = Vec::new();
+                    put_len(&mut out, 0x80, bytes.len());
+                    out.extend_from_slice(bytes);
+                    out
+                }
+            }
+        }
+        fn list(items: &[Vec<u8>]) -> Vec<u8> {
+            let payload = items.concat();
+            let mut out = Vec::new();
+            put_len(&mut out, 0xc0, payload.len());
+            out.extend_from_slice(&payload);
+            out
+        }
+        fn encode_log(log: &Log) -> Vec<u8> {
+            let topics = log
+                .topics
+                .iter()
+                .map(|topic| string(topic))
+                .collect::<Vec<_>>();
+            list(&[string(&log.address), list(&topics), string(&log.data)])
+        }
+        fn encode_receipt(receipt: &Receipt) -> Vec<u8> {
+            let outcome = match receipt.outcome {
+                ReceiptOutcome::PostState(root) => string(&root),
+                ReceiptOutcome::Status(status) => string(if status { &[1] } else { &[] }),
+            };
+            let gas = receipt.cumulative_gas_used.to_be_bytes();
+            let logs = receipt.logs.iter().map(encode_log).collect::<Vec<_>>();
+            let body = list(&[
+                outcome,
+                string(&gas[gas.iter().position(|&b| b != 0).unwrap()..]),
+                string(&receipt.logs_bloom),
+                list(&logs),
+            ]);
+            match receipt.tx_type {
+                0 => body,
+                tx_type => [vec![tx_type], body].concat(),
+            }
+        }
+        fn branch(children: &[(usize, Vec<u8>)]) -> Vec<u8> {
+            let mut items = vec![string(&[]); 17];
+            for (nibble, child) in children {
+                items[*nibble].clone_from(child);
+            }
+            list(&items)
+        }
+
+        let transfer = [0x77; 32];
+        let deposit = [0x88; 32];
+        let token = [0xaa; 20];
+        let status_receipt = Receipt {
+            tx_type: 2,
+            outcome: ReceiptOutcome::Status(true),
+            cumulative_gas_used: 52_000,
+            logs_bloom: [0x0f; 256],
+            logs: vec![
+                Log {
+                    address: token,
+                    topics: vec![transfer, [0x01; 32]],
+                    data: vec![0x05; 32],
+                },
+                Log {
+                    address: [0xbb; 20],
+                    topics: vec![transfer],
+                    data: Vec::new(),
+                },
+                Log {
+                    address: token,
+                    topics: vec![deposit],
+                    data: vec![0x09],
+                },
+                Log {
+                    address: token,
+                    topics: Vec::new(),
+                    data: Vec::new(),
+                },
+            ],
+        };
+        let legacy_receipt = Receipt {
+            tx_type: 0,
+            outcome: ReceiptOutcome::PostState([0x33; 32]),
+            cumulative_gas_used: 21_000,
+            logs_bloom: [0; 256],
+            logs: Vec::new(),
+        };
+
+        // The keys of transactions 0 and 1 are 0x80 and 0x01, which branch on their first nibble.
+        // Each leaf holds the rest of its key, a single nibble, with an odd leaf prefix.
+        let status_leaf = list(&[string(&[0x30]), string(&encode_receipt(&status_receipt))]);
+        let legacy_leaf = list(&[string(&[0x31]), string(&encode_receipt(&legacy_receipt))]);
+        let root_node = branch(&[
+            (0, string(&keccak256(&legacy_leaf))),
+            (8, string(&keccak256(&status_leaf))),
+        ]);
+        let root = keccak256(&root_node);
+
+        assert_eq!(
+            verify_receipt_proof(&root, 0, &[&root_node, &status_leaf]),
+            Ok(status_receipt.clone())
+        );
+        assert_eq!(
+            verify_receipt_proof(&root, 1, &[&root_node, &legacy_leaf]),
+            Ok(legacy_receipt)
+        );
+        // The key of transaction 2, 0x02, diverges from the leaf of transaction 1.
+        assert_eq!(
+            verify_receipt_proof(&root, 2, &[&root_node, &legacy_leaf]),
+            Err(Error::MptKeyNotFound)
+        );
+        // The key of transaction 48, 0x30, has no child in the branch.
+        assert_eq!(
+            verify_receipt_proof(&root, 0x30, &[&root_node]),
+            Err(Error::MptKeyNotFound)
+        );
+
+        let mut tampered_leaf = status_leaf.clone();
+        *tampered_leaf.last_mut().unwrap() ^= 1;
+        for proof in [
+            vec![&root_node[..]],
+            vec![&status_leaf[..]],
+            vec![&root_node[..], &legacy_leaf[..]],
+            vec![&root_node[..], &tampered_leaf[..]],
+            vec![&root_node[..], &status_leaf[..], &legacy_leaf[..]],
+            Vec::new(),
+        ] {
+            assert_eq!(
+                verify_receipt_proof(&root, 0, &proof),
+                Err(Error::MptProofInvalid)
+            );
+        }
+        assert_eq!(
+            verify_receipt_proof(&[0; 32], 0, &[&root_node, &status_leaf]),
+            Err(Error::MptProofInvalid)
+        );
+
+        // A trie whose only key is 0x80 is a single leaf holding the whole key, with an even prefix.
+        let unknown_type = list(&[string(&[0x20, 0x80]), string(&[0x04, 0xc0])]);
+        assert_eq!(
+            verify_receipt_proof(&keccak256(&unknown_type), 0, &[&unknown_type]),
+            Err(Error::ReceiptMalformed)
+        );
+
+        // The keys of transactions 128 and 129, 0x8180 and 0x8181, share an extension of three
+        // nibbles. Their leaves are short enough to be embedded in the branch.
+        let branch_node = branch(&[
+            (0, list(&[string(&[0x20]), string(b"transaction 128")])),
+            (1, list(&[string(&[0x20]), string(b"transaction 129")])),
+        ]);
+        let extension = list(&[string(&[0x18, 0x18]), string(&keccak256(&branch_node))]);
+        let root = keccak256(&extension);
+        assert_eq!(
+            verify_transaction_proof(&root, 128, &[&extension, &branch_node]),
+            Ok(&b"transaction 128"[..])
+        );
+        assert_eq!(
+            verify_transaction_proof(&root, 129, &[&extension, &branch_node]),
+            Ok(&b"transaction 129"[..])
+        );
+        assert_eq!(
+            verify_transaction_proof(&root, 130, &[&extension, &branch_node]),
+            Err(Error::MptKeyNotFound)
+        );
+        // The key 0x81 ends inside the extension.
+        assert_eq!(verify_proof(&root, &[0x81], &[&extension]), Ok(None));
+        assert_eq!(
+            verify_proof(&root, &[0x81, 0x80], &[&extension]),
+            Err(Error::MptProofInvalid)
+        );
+
+        assert_eq!(
+            extract_logs(&status_receipt, None, None),
+            status_receipt.logs
+        );
+        assert_eq!(
+            extract_logs(&status_receipt, Some(token), None),
+            [0, 2, 3].map(|i| status_receipt.logs[i].clone())
+        );
+        assert_eq!(
+            extract_logs(&status_receipt, None, Some(transfer)),
+            status_receipt.logs[..2]
+        );
+        assert_eq!(
+            extract_logs(&status_receipt, Some(token), Some(transfer)),
+            status_receipt.logs[..1]
+        );
+        assert!(extract_logs(&status_receipt, Some([0xcc; 20]), None).is_empty());
+    }
+}
@@ -0,0 +1,502 @@
+//! Verification of Nostr events, as defined in NIP-01.
+//!
+//! An event's id is the SHA-256 of the canonical serialization
+//! `[0, pubkey, created_at, kind, tags, content]`, and its signature is a BIP-340 signature of the
+//! id by the x-only pubkey.
+
+use crate::error::Error;
+use crate::secp256k1::schnorr;
+use crate::sha256::Sha256;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// A Nostr event whose id and signature have been verified.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifiedEvent {
+    /// The SHA-256 of the canonical serialization of the event.
+    pub id: [u8; 32],
+    /// The x-only public key of the author.
+    pub pubkey: [u8; 32],
+    /// The Unix timestamp at which the event was created, as claimed by the author.
+    pub created_at: u64,
+    pub kind: u64,
+    pub tags: Vec<Vec<String>>,
+    pub content: String,
+}
+
+/// Parses a Nostr event in JSON, checks that its id is the hash of its canonical serialization,
+/// and verifies its signature.
+///
+/// Returns [`Error::NostrEventMalformed`] if the event is not valid JSON or lacks a field,
+/// [`Error::NostrIdMismatch`] if the id does not match, which is checked before the signature,
+/// and [`Error::VerificationFailed`] if the signature does not verify. Fields other than the
+/// ones of NIP-01 are ignored.
+pub fn verify_nostr_event(event_json: &str) -> Result<VerifiedEvent, Error> {
+    let event = parse_event(event_json).ok_or(Error::NostrEventMalformed)?;
+
+    if event_id(&event) != event.id {
+        return Err(Error::NostrIdMismatch);
+    }
+    if !schnorr::verify(&event.pubkey, &event.id, &event.sig)? {
+        return Err(Error::VerificationFailed);
+    }
+
+    Ok(VerifiedEvent {
+        id: event.id,
+        pubkey: event.pubkey,
+        created_at: event.created_at,
+        kind: event.kind,
+        tags: event.tags,
+        content: event.content,
+    })
+}
+
+struct Event {
+    id: [u8; 32],
+    pubkey: [u8; 32],
+    created_at: u64,
+    kind: u64,
+    tags: Vec<Vec<String>>,
+    content: String,
+    sig: [u8; 64],
+}
+
+/// Hashes the canonical serialization of an event: no whitespace, and only the escapes required
+/// by NIP-01.
+fn event_id(event: &Event) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"[0,\"");
+    hasher.update(&encode_hex(&event.pubkey));
+    hasher.update(b"\",");
+    hash_u64(&mut hasher, event.created_at);
+    hasher.update(b",");
+    hash_u64(&mut hasher, event.kind);
+    hasher.update(b",[");
+    for (i, tag) in event.tags.iter().enumerate() {
+        if i > 0 {
+            hasher.update(b",");
+        }
+        hasher.update(b"[");
+        for (j, value) in tag.iter().enumerate() {
+            if j > 0 {
+                hasher.update(b",");
+            }
+            hash_string(&mut hasher, value);
+        }
+        hasher.update(b"]");
+    }
+    hasher.update(b"],");
+    hash_string(&mut hasher, &event.content);
+    hasher.update(b"]");
+    hasher.finalize()
+}
+
+fn hash_u64(hasher: &mut Sha256, value: u64) {
+    let mut buf = [0u8; 20];
+    let mut start = buf.len();
+    let mut value = value;
+    loop {
+        start -= 1;
+        buf[start] = b'0' + (value % 10) as u8;
+        value /= 10;
+        if value == 0 {
+            break;
+        }
+    }
+    hasher.update(&buf[start..]);
+}
+
+/// Hashes a JSON string. The escapes of NIP-01 are used where they exist, the other control
+/// characters are escaped as `\u00xx`, like `JSON.stringify` does, and everything else is
+/// included verbatim.
+fn hash_string(hasher: &mut Sha256, value: &str) {
+    hasher.update(b"\"");
+    let bytes = value.as_bytes();
+    let mut run_start = 0;
+    for (i, &byte) in bytes.iter().enumerate() {
+        let unicode_escape;
+        let escape: &[u8] = match byte {
+            b'\n' => b"\\n",
+            b'"' => b"\\\"",
+            b'\\' => b"\\\\",
+            b'\r' => b"\\r",
+            b'\t' => b"\\t",
+            0x08 => b"\\b",
+            0x0c => b"\\f",
+            0x00..=0x1f => {
+                unicode_escape = [
+                    b'\\',
+                    b'u',
+                    b'0',
+                    b'0',
+                    HEX_DIGITS[(byte >> 4) as usize],
+                    HEX_DIGITS[(byte & 0xf) as usize],
+                ];
+                &unicode_escape
+            }
+            _ => continue,
+        };
+        hasher.update(&bytes[run_start..i]);
+        hasher.update(escape);
+        run_start = i + 1;
+    }
+    hasher.update(&bytes[run_start..]);
+    hasher.update(b"\"");
+}
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+fn encode_hex<const N: usize>(bytes: &[u8; N]) -> Vec<u8> {
+    bytes
+        .iter()
+        .flat_map(|byte| {
+            [
+                HEX_DIGITS[(byte >> 4) as usize],
+                HEX_DIGITS[(byte & 0xf) as usize],
+            ]
+        })
+        .collect()
+}
+
+/// Decodes lowercase hex, the only form that NIP-01 allows.
+fn decode_hex<const N: usize>(hex: &str) -> Option<[u8; N]> {
+    let hex = hex.as_bytes();
+    if hex.len() != 2 * N {
+        return None;
+    }
+    let digit = |c: u8| match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        _ => None,
+    };
+    let mut bytes = [0u8; N];
+    for (byte, pair) in bytes.iter_mut().zip(hex.chunks_exact(2)) {
+        *byte = (digit(pair[0])? << 4) | digit(pair[1])?;
+    }
+    Some(bytes)
+}
+
+fn parse_event(json: &str) -> Option<Event> {
+    let mut parser = Parser { json, pos: 0 };
+    let (mut id, mut pubkey, mut created_at, mut kind, mut tags, mut content, mut sig) =
+        (None, None, None, None, None, None, None);
+
+    parser.expect(b'{')?;
+    if !parser.eat(b'}') {
+        loop {
+            let key = parser.string()?;
+            parser.expect(b':')?;
+            // Duplicate fields are rejected, as parsers disagree on which one wins.
+            match key.as_str() {
+                "id" if id.is_none() => id = Some(decode_hex(&parser.string()?)?),
+                "pubkey" if pubkey.is_none() => pubkey = Some(decode_hex(&parser.string()?)?),
+                "created_at" if created_at.is_none() => created_at = Some(parser.number()?),
+                "kind" if kind.is_none() => kind = Some(parser.number()?),
+                "tags" if tags.is_none() => {
+                    tags = Some(parser.array(|parser| parser.array(Parser::string))?)
+                }
+                "content" if content.is_none() => content = Some(parser.string()?),
+                "sig" if sig.is_none() => sig = Some(decode_hex(&parser.string()?)?),
+                "id" | "pubkey" | "created_at" | "kind" | "tags" | "content" | "sig" => {
+                    return None
+                }
+                _ => parser.skip_value()?,
+            }
+            if !parser.eat(b',') {
+                break;
+            }
+        }
+        parser.expect(b'}')?;
+    }
+    parser.skip_whitespace();
+    if parser.pos != json.len() {
+        return None;
+    }
+
+    Some(Event {
+        id: id?,
+        pubkey: pubkey?,
+        created_at: created_at?,
+        kind: kind?,
+        tags: tags?,
+        content: content?,
+        sig: sig?,
+    })
+}
+
+/// A minimal JSON parser, for the values that appear in events.
+struct Parser<'a> {
+    json: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        let bytes = self.json.as_bytes();
+        while self.pos < bytes.len() && matches!(bytes[self.pos], b' ' | b'\t' | b'\n' | b'\r') {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.json.as_bytes().get(self.pos).copied()
+    }
+
+    /// Skips the given byte if it is next.
+    fn eat(&mut self, byte: u8) -> bool {
+        let found = self.peek() == Some(byte);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        self.eat(byte).then_some(())
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.expect(b'"')?;
+        let bytes = self.json.as_bytes();
+        let mut value = String::new();
+        let mut run_start = self.pos;
+        loop {
+            match *bytes.get(self.pos)? {
+                b'"' => {
+                    value.push_str(&self.json[run_start..self.pos]);
+                    self.pos += 1;
+                    return Some(value);
+                }
+                b'\\' => {
+                    value.push_str(&self.json[run_start..self.pos]);
+                    let escape = *bytes.get(self.pos + 1)?;
+                    self.pos += 2;
+                    let c = match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.unicode_escape()?,
+                        _ => return None,
+                    };
+                    value.push(c);
+                    run_start = self.pos;
+                }
+                // Control characters must be escaped.
+                0x00..=0x1f => return None,
+                _ => self.pos += 1,
+            }
+        }
+    }
+
+    /// Parses the digits of a `\u` escape, and of the low surrogate that follows a high one.
+    fn unicode_escape(&mut self) -> Option<char> {
+        let high = self.hex4()?;
+        if !(0xd800..0xdc00).contains(&high) {
+            return char::from_u32(high);
+        }
+        if self.json.as_bytes().get(self.pos..self.pos + 2)? != b"\\u" {
+            return None;
+        }
+        self.pos += 2;
+        let low = self.hex4()?;
+        if !(0xdc00..0xe000).contains(&low) {
+            return None;
+        }
+        char::from_u32(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00))
+    }
+
+    fn hex4(&mut self) -> Option<u32> {
+        let digits = self.json.get(self.pos..self.pos + 4)?;
+        if !digits.bytes().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        self.pos += 4;
+        u32::from_str_radix(digits, 16).ok()
+    }
+
+    /// Parses a nonnegative integer.
+    fn number(&mut self) -> Option<u64> {
+        self.skip_whitespace();
+        let bytes = self.json.as_bytes();
+        let start = self.pos;
+        while self.pos < bytes.len() && bytes[self.pos].is_ascii_digit() {
+            self.pos += 1;
+        }
+        let digits = &self.json[start..self.pos];
+        // JSON does not allow leading zeros.
+        if digits.is_empty() || (digits.len() > 1 && digits.starts_with('0')) {
+            return None;
+        }
+        digits.parse().ok()
+    }
+
+    fn array<T>(&mut self, mut item: impl FnMut(&mut Self) -> Option<T>) -> Option<Vec<T>> {
+        self.expect(b'[')?;
+        let mut items = Vec::new();
+        if self.eat(b']') {
+            return Some(items);
+        }
+        loop {
+            items.push(item(self)?);
+            if !self.eat(b',') {
+                break;
+            }
+        }
+        self.expect(b']')?;
+        Some(items)
+    }
+
+    /// Skips a value of any type.
+    fn skip_value(&mut self) -> Option<()> {
+        match self.peek()? {
+            b'"' => {
+                self.string()?;
+            }
+            b'[' => {
+                self.array(Self::skip_value)?;
+            }
+            b'{' => {
+                self.pos += 1;
+                if !self.eat(b'}') {
+                    loop {
+                        self.string()?;
+                        self.expect(b':')?;
+                        self.skip_value()?;
+                        if !self.eat(b',') {
+                            break;
+                        }
+                    }
+                    self.expect(b'}')?;
+                }
+            }
+            _ => {
+                // Numbers and literals, which end at a delimiter.
+                let bytes = self.json.as_bytes();
+                let start = self.pos;
+                while self.pos < bytes.len()
+                    && !matches!(
+                        bytes[self.pos],
+                        b',' | b']' | b'}' | b' ' | b'\t' | b'\n' | b'\r'
+                    )
+                {
+                    self.pos += 1;
+                }
+                let token = &self.json[start..self.pos];
+                let is_number = token
+                    .bytes()
+                    .all(|c| c.is_ascii_digit() || matches!(c, b'-' | b'+' | b'.' | b'e' | b'E'));
+                if token.is_empty() || !(is_number || matches!(token, "true" | "false" | "null")) {
+                    return None;
+                }
+            }
+        }
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::schnorr::SigningKey;
+    use sha2::{Digest, Sha256 as Sha256Reference};
+    use std::string::ToString;
+    use std::vec;
+
+    const CREATED_AT: u64 = 1_700_000_000;
+    /// The content as written by a client, with escapes that a canonical serialization drops.
+    const CONTENT_JSON: &str = r#"hello \"nostr\"\n\\\t\u0001é😀\/"#;
+    /// The same content in the canonical serialization of NIP-01.
+    const CONTENT_CANONICAL: &str = "hello \\\"nostr\\\"\\n\\\\\\t\\u0001\u{e9}\u{1f600}/";
+
+    fn key() -> SigningKey {
+        SigningKey::from_bytes(&[0x11; 32]).unwrap()
+    }
+
+    /// Returns an event signed by [`key`], with its id computed from the canonical serialization
+    /// written out by hand.
+    fn event_json(content_json: &str, content_canonical: &str) -> String {
+        let key = key();
+        let pubkey = hex::encode(key.verifying_key().to_bytes());
+        let tags = r#"[["e","5c83da77af1dec6d7289834998ad7aafbd9e2191396d75ec3cc27f5a77226f36"],["t","test"]]"#;
+        let serialized = format!(r#"[0,"{pubkey}",{CREATED_AT},1,{tags},"{content_canonical}"]"#);
+        let id: [u8; 32] = Sha256Reference::digest(serialized.as_bytes()).into();
+        let sig = key.sign_prehash_with_aux_rand(&id, &[0; 32]).unwrap();
+        format!(
+            "{{\n  \"id\": \"{}\",\n  \"pubkey\": \"{pubkey}\",\n  \"created_at\": {CREATED_AT},\n  \
+             \"kind\": 1,\n  \"tags\": {tags},\n  \"content\": \"{content_json}\",\n  \
+             \"sig\": \"{}\"\n}}",
+            hex::encode(id),
+            hex::encode(sig.to_bytes())
+        )
+    }
+
+    #[test]
+    fn test_verify_nostr_event() {
+        let json = event_json(CONTENT_JSON, CONTENT_CANONICAL);
+        let event = verify_nostr_event(&json).unwrap();
+        assert_eq!(
+            event.pubkey,
+            <[u8; 32]>::from(key().verifying_key().to_bytes())
+        );
+        assert_eq!(event.created_at, CREATED_AT);
+        assert_eq!(event.kind, 1);
+        assert_eq!(event.tags[1], vec!["t".to_string(), "test".to_string()]);
+        assert_eq!(event.content, "hello \"nostr\"\n\\\t\u{1}\u{e9}\u{1f600}/");
+
+        // Fields outside of NIP-01 are ignored.
+        let extended = json.replacen(
+            '{',
+            r#"{"relays": [{"url": "wss://relay"}, 1.5e3, null],"#,
+            1,
+        );
+        assert_eq!(verify_nostr_event(&extended), Ok(event));
+    }
+
+    #[test]
+    fn test_verify_nostr_event_rejects_forgeries() {
+        let json = event_json(CONTENT_JSON, CONTENT_CANONICAL);
+        assert_eq!(
+            verify_nostr_event(&json.replace("\"kind\": 1", "\"kind\": 2")),
+            Err(Error::NostrIdMismatch)
+        );
+        assert_eq!(
+            verify_nostr_event(&json.replace("hello", "hullo")),
+            Err(Error::NostrIdMismatch)
+        );
+
+        // A valid id with the signature of another event.
+        let other = event_json("other", "other");
+        let sig = |json: &str| json[json.find("\"sig\"").unwrap()..].to_string();
+        let forged = json.replace(&sig(&json), &sig(&other));
+        assert_eq!(verify_nostr_event(&forged), Err(Error::VerificationFailed));
+    }
+
+    #[test]
+    fn test_verify_nostr_event_rejects_malformed_events() {
+        let json = event_json(CONTENT_JSON, CONTENT_CANONICAL);
+        let id = &json[json.find("\"id\": \"").unwrap() + 7..][..64];
+        let malformed = [
+            json.replacen('{', "{\"kind\": 1,", 1),
+            json.replace("\"kind\": 1,", ""),
+            json.replace(id, &id.to_uppercase()),
+            json.replace(&format!("{CREATED_AT}"), &format!("0{CREATED_AT}")),
+            json.replace(r"\u0001", "\u{1}"),
+            json.replace('😀', r"\ud83d"),
+            json.clone() + "}",
+            json[..json.len() - 1].to_string(),
+        ];
+        for json in &malformed {
+            assert_eq!(
+                verify_nostr_event(json),
+                Err(Error::NostrEventMalformed),
+                "{json}"
+            );
+        }
+    }
+}
@@ -0,0 +1,524 @@
+//! ECDSA verification over P-384, for which there is no precompile.
+//!
+//! The arithmetic modulo `p` and modulo the order `n` runs on [`BigMod`]: the products are
+//! computed with the uint256 precompile inside the zkVM, and reduced with a quotient and a
+//! remainder hinted by the bigmod hook and checked with the division identity. The inversions of
+//! `s` and of the `z` coordinates are hinted as well, and each is checked with one product. A hint
+//! that fails its check is ignored and the value computed in the VM, so the hook can slow a
+//! verification down but not change its result.
+//!
+//! `u1 * G + u2 * Q` is computed in Jacobian coordinates with a single chain of doublings, adding
+//! `G`, `Q` or `G + Q` for each pair of bits of the scalars.
+//!
+//! # Cycle cost
+//!
+//! A verification takes 384 doublings of 8 products and about 288 additions of 11 products, so
+//! about 6,200 products modulo `p`, each with a request to the bigmod hook. Inside the zkVM, the
+//! 768-bit product of two coordinates takes nine calls to the uint256 precompile and its check nine
+//! more, about 110,000 calls for a verification, on top of the limb arithmetic around them. A
+//! verification is therefore expected to take tens of millions of cycles, which is tractable but
+//! far from the cost of a precompiled curve. The SHA-384 digest is computed in software, with the
+//! `Sha384` of the `sha2` crate.
+
+use crate::bigmod::BigMod;
+use crate::error::Error;
+use crate::utils::hex;
+use alloc::vec::Vec;
+use core::convert::TryInto;
+
+/// A big endian element of the base field or of the scalar field.
+type Element = [u8; 48];
+
+/// The modulus `p = 2^384 - 2^128 - 2^96 + 2^32 - 1` of the base field.
+const P: Element = hex(concat!(
+    "ffffffffffffffffffffffffffffffffffffffffffffffff",
+    "fffffffffffffffeffffffff0000000000000000ffffffff"
+));
+
+/// `(p + 1) / 4`, the exponent of square roots.
+const SQRT_EXPONENT: Element = hex(concat!(
+    "3fffffffffffffffffffffffffffffffffffffffffffffff",
+    "ffffffffffffffffbfffffffc00000000000000040000000"
+));
+
+/// The order `n` of the base point.
+const N: Element = hex(concat!(
+    "ffffffffffffffffffffffffffffffffffffffffffffffff",
+    "c7634d81f4372ddf581a0db248b0a77aecec196accc52973"
+));
+
+/// The coefficient `b` of the curve `y^2 = x^3 - 3 * x + b`.
+const B: Element = hex(concat!(
+    "b3312fa7e23ee7e4988e056be3f82d19181d9c6efe814112",
+    "0314088f5013875ac656398d8a2ed19d2a85c8edd3ec2aef"
+));
+
+/// The base point.
+const G: (Element, Element) = (
+    hex(concat!(
+        "aa87ca22be8b05378eb1c71ef320ad746e1d3b628ba79b98",
+        "59f741e082542a385502f25dbf55296c3a545e3872760ab7"
+    )),
+    hex(concat!(
+        "3617de4a96262c6f5d9e98bf9292dc29f8f41dbd289a147c",
+        "e9da3113b5f0b8c00a60b1ce1d7e819d7a431d7c90ea0e5f"
+    )),
+);
+
+const ZERO: Element = [0; 48];
+
+const ONE: Element = {
+    let mut one = [0; 48];
+    one[47] = 1;
+    one
+};
+
+/// Verifies the ECDSA signature `r || s` of a SHA-384 digest over P-384.
+///
+/// The public key is a SEC1 encoding, compressed or not. As the digest is as long as the order,
+/// it is only reduced modulo the order, and `s` may be above half the order, as in the `p384`
+/// crate.
+///
+/// Returns [`Error::InvalidPublicKey`] if the key is not a point of the curve, and
+/// [`Error::InvalidSignature`] if `r` or `s` is zero or not below the order.
+pub fn verify_signature(
+    pubkey_sec1: &[u8],
+    msg_hash: &[u8; 48],
+    sig: &[u8; 96],
+) -> Result<bool, Error> {
+    Curve::new().verify(pubkey_sec1, msg_hash, sig)
+}
+
+/// The arithmetic of the curve, with the base field and the scalar field.
+struct Curve<'a> {
+    field: Field<'a>,
+    scalars: BigMod<'a>,
+}
+
+impl Curve<'static> {
+    fn new() -> Self {
+        Self {
+            field: Field(BigMod::new(&P).unwrap()),
+            scalars: BigMod::new(&N).unwrap(),
+        }
+    }
+}
+
+impl Curve<'_> {
+    fn verify(
+        &self,
+        pubkey_sec1: &[u8],
+        msg_hash: &[u8; 48],
+        sig: &[u8; 96],
+    ) -> Result<bool, Error> {
+        let q = self.decode_point(pubkey_sec1)?;
+        let r: Element = sig[..48].try_into().unwrap();
+        let s: Element = sig[48..].try_into().unwrap();
+        if [r, s].iter().any(|x| *x == ZERO || *x >= N) {
+            return Err(Error::InvalidSignature);
+        }
+
+        // The order is prime, so any s below it is invertible.
+        let w = self.scalars.inv(&s).ok_or(Error::InvalidSignature)?;
+        let u1 = element(self.scalars.mul(msg_hash, &w));
+        let u2 = element(self.scalars.mul(&r, &w));
+        let Some((x, _)) = self.lincomb(&u1, &u2, &q).to_affine(&self.field) else {
+            return Ok(false);
+        };
+        // x is below p, which is less than twice n.
+        Ok(self.scalars.add(&x, &ZERO) == r)
+    }
+
+    /// Decodes an uncompressed or compressed SEC1 encoding of a point of the curve.
+    fn decode_point(&self, sec1: &[u8]) -> Result<(Element, Element), Error> {
+        let field = &self.field;
+        let coordinate = |bytes: &[u8]| -> Result<Element, Error> {
+            let x: Element = bytes.try_into().map_err(|_| Error::InvalidPublicKey)?;
+            (x < P).then_some(x).ok_or(Error::InvalidPublicKey)
+        };
+        let (&tag, rest) = sec1.split_first().ok_or(Error::InvalidPublicKey)?;
+        let (x, y) = match (tag, rest.len()) {
+            (0x04, 96) => (coordinate(&rest[..48])?, Some(coordinate(&rest[48..])?)),
+            (0x02 | 0x03, 48) => (coordinate(rest)?, None),
+            _ => return Err(Error::InvalidPublicKey),
+        };
+
+        let x3 = field.mul(&field.sqr(&x), &x);
+        let rhs = field.add(&field.sub(&x3, &field.add(&field.add(&x, &x), &x)), &B);
+        let y = match y {
+            Some(y) => y,
+            None => {
+                let y = element(field.0.pow(&rhs, &SQRT_EXPONENT));
+                if y[47] & 1 == tag & 1 {
+                    y
+                } else {
+                    field.sub(&ZERO, &y)
+                }
+            }
+        };
+        // The curve has a prime order, so every point of it is in the group of the base point.
+        if field.sqr(&y) != rhs {
+            return Err(Error::InvalidPublicKey);
+        }
+        Ok((x, y))
+    }
+
+    /// Returns `u1 * G + u2 * Q`, with Shamir's trick.
+    fn lincomb(&self, u1: &Element, u2: &Element, q: &(Element, Element)) -> Point {
+        let field = &self.field;
+        let g = Point::from_affine(&G);
+        let gq = g.add_affine(field, q).to_affine(field);
+
+        let bits = |k: &Element, i: usize| (k[i / 8] >> (7 - i % 8)) & 1 == 1;
+        (0..384).fold(Point::INFINITY, |acc, i| {
+            let acc = acc.double(field);
+            match (bits(u1, i), bits(u2, i)) {
+                (true, true) => gq.as_ref().map_or(acc, |gq| acc.add_affine(field, gq)),
+                (true, false) => acc.add_affine(field, &G),
+                (false, true) => acc.add_affine(field, q),
+                (false, false) => acc,
+            }
+        })
+    }
+}
+
+/// The base field, on top of [`BigMod`].
+struct Field<'a>(BigMod<'a>);
+
+impl Field<'_> {
+    fn add(&self, a: &Element, b: &Element) -> Element {
+        element(self.0.add(a, b))
+    }
+
+    fn sub(&self, a: &Element, b: &Element) -> Element {
+        element(self.0.sub(a, b))
+    }
+
+    fn mul(&self, a: &Element, b: &Element) -> Element {
+        element(self.0.mul(a, b))
+    }
+
+    fn sqr(&self, a: &Element) -> Element {
+        self.mul(a, a)
+    }
+
+    /// Returns `2^k * a`.
+    fn shl(&self, a: &Element, k: u32) -> Element {
+        (0..k).fold(*a, |acc, _| self.add(&acc, &acc))
+    }
+}
+
+/// A point in Jacobian coordinates, `(x / z^2, y / z^3)`, or the point at infinity if `z` is zero.
+#[derive(Clone, Copy)]
+struct Point {
+    x: Element,
+    y: Element,
+    z: Element,
+}
+
+impl Point {
+    const INFINITY: Self = Point {
+        x: ONE,
+        y: ONE,
+        z: ZERO,
+    };
+
+    fn from_affine((x, y): &(Element, Element)) -> Self {
+        Point {
+            x: *x,
+            y: *y,
+            z: ONE,
+        }
+    }
+
+    fn is_infinity(&self) -> bool {
+        self.z == ZERO
+    }
+
+    /// Doubles the point, with the `dbl-2001-b` formulas for curves with `a = -3`.
+    fn double(&self, field: &Field) -> Self {
+        if self.is_infinity() {
+            return Self::INFINITY;
+        }
+        let delta = field.sqr(&self.z);
+        let gamma = field.sqr(&self.y);
+        let beta = field.mul(&self.x, &gamma);
+        let alpha = field.mul(&field.sub(&self.x, &delta), &field.add(&self.x, &delta));
+        let alpha = field.add(&field.add(&alpha, &alpha), &alpha);
+        let x = field.sub(&field.sqr(&alpha), &field.shl(&beta, 3));
+        let z = field.sub(
+            &field.sub(&field.sqr(&field.add(&self.y, &self.z)), &gamma),
+            &delta,
+        );
+        let y = field.sub(
+            &field.mul(&alpha, &field.sub(&field.shl(&beta, 2), &x)),
+            &field.shl(&field.sqr(&gamma), 3),
+        );
+        Point { x, y, z }
+    }
+
+    /// Adds an affine point, with the `madd-2007-bl` formulas.
+    fn add_affine(&self, field: &Field, (x2, y2): &(Element, Element)) -> Self {
+        if self.is_infinity() {
+            return Self::from_affine(&(*x2, *y2));
+        }
+        let z1z1 = field.sqr(&self.z);
+        let u2 = field.mul(x2, &z1z1);
+        let s2 = field.mul(&field.mul(y2, &self.z), &z1z1);
+        let h = field.sub(&u2, &self.x);
+        let r = field.sub(&s2, &self.y);
+        if h == ZERO {
+            return if r == ZERO {
+                self.double(field)
+            } else {
+                Self::INFINITY
+            };
+        }
+        let hh = field.sqr(&h);
+        let i = field.shl(&hh, 2);
+        let j = field.mul(&h, &i);
+        let r = field.add(&r, &r);
+        let v = field.mul(&self.x, &i);
+        let x = field.sub(&field.sub(&field.sqr(&r), &j), &field.add(&v, &v));
+        let y1j = field.mul(&self.y, &j);
+        let y = field.sub(&field.mul(&r, &field.sub(&v, &x)), &field.add(&y1j, &y1j));
+        let z = field.sub(&field.sub(&field.sqr(&field.add(&self.z, &h)), &z1z1), &hh);
+        Point { x, y, z }
+    }
+
+    /// Returns the affine coordinates of the point, or `None` for the point at infinity.
+    fn to_affine(self, field: &Field) -> Option<(Element, Element)> {
+        // p is prime, so only the point at infinity has no inverse of z.
+        let z_inv = element(field.0.inv(&self.z)?);
+        let z_inv2 = field.sqr(&z_inv);
+        Some((
+            field.mul(&self.x, &z_inv2),
+            field.mul(&self.y, &field.mul(&z_inv2, &z_inv)),
+        ))
+    }
+}
+
+fn element(bytes: Vec<u8>) -> Element {
+    bytes.try_into().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha2::{Digest, Sha384};
+
+    fn bytes<const N: usize>(hex_str: &str) -> [u8; N] {
+        hex::decode(hex_str).unwrap().try_into().unwrap()
+    }
+
+    /// The public key of RFC 6979, A.2.6, uncompressed.
+    fn rfc6979_key() -> Vec<u8> {
+        hex::decode(concat!(
+            "04",
+            "ec3a4e415b4e19a4568618029f427fa5da9a8bc4ae92e02e06aae5286b300c64",
+            "def8f0ea9055866064a254515480bc13",
+            "8015d9b72d7d57244ea8ef9ac0c621896708a59367f9dfb9f54ca84b3f1c9db1",
+            "288b231c3ae0d4fe7344fd2533264720",
+        ))
+        .unwrap()
+    }
+
+    /// The SHA-384 signatures of RFC 6979, A.2.6, as `(message, r || s)`.
+    fn rfc6979_signatures() -> [(&'static [u8], [u8; 96]); 2] {
+        [
+            (
+                b"sample",
+                bytes(concat!(
+                    "94edbb92a5ecb8aad4736e56c691916b3f88140666ce9fa73d64c4ea95ad133c",
+                    "81a648152e44acf96e36dd1e80fabe46",
+                    "99ef4aeb15f178cea1fe40db2603138f130e740a19624526203b6351d0a3a94f",
+                    "a329c145786e679e7b82c71a38628ac8",
+                )),
+            ),
+            (
+                b"test",
+                bytes(concat!(
+                    "8203b63d3c853e8d77227fb377bcf7b7b772e97892a80f36ab775d509d7a5feb",
+                    "0542a7f0812998da8f1dd3ca3cf023db",
+                    "ddd0760448d42d8a43af45af836fce4de8be06b485e9b61b827c2f13173923e0",
+                    "6a739f040649a667bf3b828246baa5a5",
+                )),
+            ),
+        ]
+    }
+
+    fn digest(message: &[u8]) -> [u8; 48] {
+        Sha384::digest(message).into()
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let key = rfc6979_key();
+        let mut compressed = vec![0x02 | (key[96] & 1)];
+        compressed.extend_from_slice(&key[1..49]);
+        for (message, sig) in rfc6979_signatures() {
+            let msg_hash = digest(message);
+            assert_eq!(verify_signature(&key, &msg_hash, &sig), Ok(true));
+            assert_eq!(verify_signature(&compressed, &msg_hash, &sig), Ok(true));
+
+            // s and n - s are both accepted.
+            let mut negated = sig;
+            let minus_s = BigMod::new(&N).unwrap().sub(&ZERO, &sig[48..]);
+            negated[48..].copy_from_slice(&minus_s);
+            assert_eq!(verify_signature(&key, &msg_hash, &negated), Ok(true));
+
+            let mut other_hash = msg_hash;
+            other_hash[47] ^= 1;
+            assert_eq!(verify_signature(&key, &other_hash, &sig), Ok(false));
+            let mut other_r = sig;
+            other_r[0] ^= 1;
+            assert_eq!(verify_signature(&key, &msg_hash, &other_r), Ok(false));
+        }
+
+        // The signature of "sample" does not verify "test".
+        let [(_, sample), _] = rfc6979_signatures();
+        assert_eq!(verify_signature(&key, &digest(b"test"), &sample), Ok(false));
+    }
+
+    #[test]
+    fn test_verify_signature_rejects_malformed_inputs() {
+        let key = rfc6979_key();
+        let [(message, sig), _] = rfc6979_signatures();
+        let msg_hash = digest(message);
+
+        let mut wrong_parity = vec![0x02 | (!key[96] & 1)];
+        wrong_parity.extend_from_slice(&key[1..49]);
+        assert_eq!(verify_signature(&wrong_parity, &msg_hash, &sig), Ok(false));
+
+        let mut off_curve = key.clone();
+        off_curve[96] ^= 1;
+        let mut unreduced = key.clone();
+        unreduced[1..49].copy_from_slice(&P);
+        let mut hybrid = key.clone();
+        hybrid[0] = 0x06;
+        for key in [
+            off_curve,
+            unreduced,
+            hybrid,
+            key[..96].to_vec(),
+            vec![0x00],
+            vec![],
+        ] {
+            assert_eq!(
+                verify_signature(&key, &msg_hash, &sig),
+                Err(Error::InvalidPublicKey)
+            );
+        }
+        // 1 is not the x-coordinate of a point of the curve.
+        let mut no_point = vec![0x02];
+        no_point.extend_from_slice(&ONE);
+        assert_eq!(
+            verify_signature(&no_point, &msg_hash, &sig),
+            Err(Error::InvalidPublicKey)
+        );
+
+        for (start, value) in [(0, ZERO), (0, N), (48, ZERO), (48, N), (48, [0xff; 48])] {
+            let mut bad = sig;
+            bad[start..start + 48].copy_from_slice(&value);
+            assert_eq!(
+                verify_signature(&key, &msg_hash, &bad),
+                Err(Error::InvalidSignature)
+            );
+        }
+    }
+
+    /// Alters the responses of the bigmod hook to a request.
+    #[cfg(not(feature = "no-hints"))]
+    type Tamper = fn(&[u8], Vec<Vec<u8>>) -> Vec<Vec<u8>>;
+
+    /// Runs `f` with the curve arithmetic hinted by the bigmod hook of [`crate::io::mock`], which
+    /// `tamper` may alter, and returns its result with the numbers of requests for a division and
+    /// for an inversion.
+    #[cfg(not(feature = "no-hints"))]
+    fn with_mock_hook<T>(
+        tamper: Tamper,
+        f: impl FnOnce(&Curve) -> T,
+    ) -> (T, [usize; 2]) {
+        use crate::bigmod::tests::hook_responses;
+        use crate::bigmod::{hook_hints, OP_DIVREM};
+        use crate::io::{mock, FD_BIGMOD_HOOK};
+        use core::cell::Cell;
+        use std::rc::Rc;
+
+        let requests = Rc::new(Cell::new([0, 0]));
+        let hook = {
+            let requests = requests.clone();
+            move |fd, request: &[u8]| {
+                assert_eq!(fd, FD_BIGMOD_HOOK);
+                let mut counts = requests.get();
+                counts[(request[0] != OP_DIVREM) as usize] += 1;
+                requests.set(counts);
+                tamper(request, hook_responses(request))
+            }
+        };
+        let (result, unread) = mock::with_hook(hook, || {
+            f(&Curve {
+                field: Field(BigMod::with_hints(&P, hook_hints).unwrap()),
+                scalars: BigMod::with_hints(&N, hook_hints).unwrap(),
+            })
+        });
+        assert_eq!(unread, 0);
+        (result, requests.get())
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-hints"))]
+    fn test_verify_signature_with_mock_hook() {
+        use crate::bigmod::tests::hook_responses;
+        use crate::bigmod::OP_INVERSE;
+
+        let key = rfc6979_key();
+        let [(message, sig), _] = rfc6979_signatures();
+        let msg_hash = digest(message);
+        let mut forged = sig;
+        forged[95] ^= 1;
+
+        let (valid, [divisions, inversions]) =
+            with_mock_hook(|_, res| res, |curve| curve.verify(&key, &msg_hash, &sig));
+        assert_eq!(valid, Ok(true));
+        // The inverses of s, of the z coordinate of G + Q and of that of the result.
+        assert_eq!(inversions, 3);
+        // The cost documented above: every product is reduced with a hint.
+        assert_eq!(divisions, 6_166);
+
+        let tampers: [Tamper; 4] = [
+            // A remainder off by one.
+            |_, mut res| {
+                if let Some(last) = res[1].last_mut() {
+                    *last ^= 1;
+                }
+                res
+            },
+            // An inverse of another value.
+            |request, res| {
+                let mut other = request.to_vec();
+                *other.last_mut().unwrap() ^= 1;
+                match request[0] {
+                    OP_INVERSE => hook_responses(&other),
+                    _ => res,
+                }
+            },
+            // The claim that a value has no inverse.
+            |request, res| match request[0] {
+                OP_INVERSE => vec![vec![], ONE.to_vec()],
+                _ => res,
+            },
+            // Missing responses.
+            |_, _| vec![vec![], vec![]],
+        ];
+        for tamper in tampers {
+            let (results, _) = with_mock_hook(tamper, |curve| {
+                [
+                    curve.verify(&key, &msg_hash, &sig),
+                    curve.verify(&key, &msg_hash, &forged),
+                ]
+            });
+            assert_eq!(results, [Ok(true), Ok(false)]);
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! Decoding and encoding of the RLP items of Ethereum data structures.
+//!
+//! Decoding is strict, like the decoder of geth: lengths must have no leading zeros and use the
+//! short form when possible, and single bytes below 0x80 must be their own encoding. As the
+//! encoding of canonical items is unique, decoded items can be copied rather than encoded again.
+//! The decoder is only built for the modules that read RLP, as EIP-7702 only encodes it.
+
+use alloc::vec::Vec;
+
+/// An RLP item, with its whole encoding.
+#[cfg(any(feature = "clique", feature = "enr", feature = "mpt"))]
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Item<'a> {
+    pub(crate) encoding: &'a [u8],
+    pub(crate) payload: &'a [u8],
+    pub(crate) is_list: bool,
+}
+
+/// Reads an item from the start of `input`. Returns `None` if the input is truncated or the item
+/// is not canonical.
+#[cfg(any(feature = "clique", feature = "enr", feature = "mpt"))]
+pub(crate) fn read_item<'a>(input: &mut &'a [u8]) -> Option<Item<'a>> {
+    let prefix = *input.first()?;
+    let (header_len, payload_len, is_list) = match prefix {
+        0x00..=0x7f => (0, 1, false),
+        0x80..=0xb7 => (1, (prefix - 0x80) as usize, false),
+        0xb8..=0xbf => {
+            let len_len = (prefix - 0xb7) as usize;
+            (1 + len_len, read_length(input.get(1..1 + len_len)?)?, false)
+        }
+        0xc0..=0xf7 => (1, (prefix - 0xc0) as usize, true),
+        0xf8..=0xff => {
+            let len_len = (prefix - 0xf7) as usize;
+            (1 + len_len, read_length(input.get(1..1 + len_len)?)?, true)
+        }
+    };
+
+    let encoding = input.get(..header_len.checked_add(payload_len)?)?;
+    let payload = &encoding[header_len..];
+    // Single bytes below 0x80 are their own encoding.
+    if prefix == 0x81 && payload[0] < 0x80 {
+        return None;
+    }
+    *input = &input[encoding.len()..];
+    Some(Item {
+        encoding,
+        payload,
+        is_list,
+    })
+}
+
+/// Decodes `bytes` as a single list, returning its items.
+#[cfg(any(feature = "clique", feature = "enr", feature = "mpt"))]
+pub(crate) fn decode_list(bytes: &[u8]) -> Option<Vec<Item<'_>>> {
+    let mut input = bytes;
+    let list = read_item(&mut input)?;
+    if !list.is_list || !input.is_empty() {
+        return None;
+    }
+    list_items(list.payload)
+}
+
+/// Splits the payload of a list into its items.
+#[cfg(any(feature = "clique", feature = "enr", feature = "mpt"))]
+pub(crate) fn list_items(mut payload: &[u8]) -> Option<Vec<Item<'_>>> {
+    let mut items = Vec::new();
+    while !payload.is_empty() {
+        items.push(read_item(&mut payload)?);
+    }
+    Some(items)
+}
+
+/// Decodes the payload of a string as an integer of at most 64 bits, which must have no leading
+/// zeros.
+#[cfg(any(feature = "clique", feature = "enr", feature = "mpt"))]
+pub(crate) fn decode_u64(payload: &[u8]) -> Option<u64> {
+    if payload.len() > 8 || payload.first() == Some(&0) {
+        return None;
+    }
+    Some(
+        payload
+            .iter()
+            .fold(0, |value, &byte| (value << 8) | byte as u64),
+    )
+}
+
+/// Encodes an integer as a string, without leading zeros.
+#[cfg(any(feature = "eip7702", feature = "mpt"))]
+pub(crate) fn put_u64(buf: &mut Vec<u8>, value: u64) {
+    let bytes = value.to_be_bytes();
+    let bytes = &bytes[value.leading_zeros() as usize / 8..];
+    match bytes {
+        [byte] if *byte < 0x80 => buf.push(*byte),
+        _ => {
+            put_header(buf, 0x80, bytes.len());
+            buf.extend_from_slice(bytes);
+        }
+    }
+}
+
+/// Encodes the header of a string, with `offset` 0x80, or of a list, with `offset` 0xc0.
+pub(crate) fn put_header(buf: &mut Vec<u8>, offset: u8, len: usize) {
+    if len < 56 {
+        buf.push(offset + len as u8);
+    } else {
+        let len_bytes = len.to_be_bytes();
+        let skip = len.leading_zeros() as usize / 8;
+        buf.push(offset + 55 + (len_bytes.len() - skip) as u8);
+        buf.extend_from_slice(&len_bytes[skip..]);
+    }
+}
+
+/// Decodes the big endian length of a long item, which must not fit in a short one.
+#[cfg(any(feature = "clique", feature = "enr", feature = "mpt"))]
+fn read_length(bytes: &[u8]) -> Option<usize> {
+    if bytes[0] == 0 || bytes.len() > core::mem::size_of::<usize>() {
+        return None;
+    }
+    let len = bytes
+        .iter()
+        .fold(0usize, |len, &byte| (len << 8) | byte as usize);
+    (len >= 56).then_some(len)
+}
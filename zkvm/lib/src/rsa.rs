@@ -0,0 +1,552 @@
+//! RSA signature verification, with the PKCS#1 v1.5 and PSS encodings of RFC 8017, for moduli of
+//! up to 4096 bits.
+//!
+//! The signature is raised to the public exponent with [`BigMod::pow`], whose products use the
+//! uint256 precompile inside the zkVM and are reduced with a quotient and a remainder hinted by
+//! the bigmod hook and checked with the division identity. A hint that fails its check is ignored
+//! and the division computed in the VM, so the hook can slow a verification down but not change
+//! its result. The encoded message is then checked in software.
+//!
+//! # Cycle cost
+//!
+//! With the usual exponent 65537, a verification takes 17 products modulo `n`, each with a request
+//! to the bigmod hook. Inside the zkVM, the product of two 2048-bit numbers takes 256 calls to the
+//! uint256 precompile and its check 256 more, so a verification makes about 9,000 calls for a
+//! 2048-bit modulus, and four times as many for a 4096-bit one. The hashes of the message and of
+//! PSS run in software, or on the sha256 precompiles with the patched `sha2` crate.
+
+use crate::bigmod::{BigMod, MAX_MODULUS_LEN};
+use crate::error::Error;
+use alloc::vec;
+use alloc::vec::Vec;
+use sha2::Digest;
+
+/// The DER prefix of the `DigestInfo` of a SHA-256 digest, for [`verify_pkcs1v15`].
+pub const SHA256_PREFIX: [u8; 19] = [
+    0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05,
+    0x00, 0x04, 0x20,
+];
+
+/// The DER prefix of the `DigestInfo` of a SHA-384 digest, for [`verify_pkcs1v15`].
+pub const SHA384_PREFIX: [u8; 19] = [
+    0x30, 0x41, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x02, 0x05,
+    0x00, 0x04, 0x30,
+];
+
+/// The DER prefix of the `DigestInfo` of a SHA-512 digest, for [`verify_pkcs1v15`].
+pub const SHA512_PREFIX: [u8; 19] = [
+    0x30, 0x51, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03, 0x05,
+    0x00, 0x04, 0x40,
+];
+
+/// The largest public exponent, as in the `rsa` crate.
+const MAX_EXPONENT: u64 = (1 << 33) - 1;
+
+/// Verifies the RSASSA-PKCS1-v1_5 signature of a digest, whose `DigestInfo` is `prefix`
+/// followed by `hashed`. An empty prefix verifies a signature of raw data, as the `rsa` crate
+/// does with an unprefixed scheme.
+///
+/// The modulus and the exponent are big endian, and the signature must be as long as the
+/// modulus.
+///
+/// Returns `Ok(false)` if the signature does not verify. Returns [`Error::InvalidPublicKey`] if
+/// the modulus is even or longer than 4096 bits, or the exponent is below 2 or above `2^33 - 1`.
+pub fn verify_pkcs1v15(
+    modulus: &[u8],
+    exponent: &[u8],
+    prefix: &[u8],
+    hashed: &[u8],
+    sig: &[u8],
+) -> Result<bool, Error> {
+    Ok(PublicKey::new(modulus, exponent)?.verify_pkcs1v15(prefix, hashed, sig))
+}
+
+/// Verifies the RSASSA-PSS signature of a digest `hashed`, with `D` as the hash and with MGF1
+/// over `D` as the mask generation function.
+///
+/// The salt must be `salt_len` bytes long if it is given, and may have any length otherwise, as
+/// with the verifying keys of the `rsa` crate.
+///
+/// Returns `Ok(false)` if the signature does not verify, and the errors of [`verify_pkcs1v15`]
+/// for an invalid key.
+pub fn verify_pss<D: Digest>(
+    modulus: &[u8],
+    exponent: &[u8],
+    hashed: &[u8],
+    sig: &[u8],
+    salt_len: Option<usize>,
+) -> Result<bool, Error> {
+    Ok(PublicKey::new(modulus, exponent)?.verify_pss::<D>(hashed, sig, salt_len))
+}
+
+/// A validated public key.
+struct PublicKey<'a> {
+    arith: BigMod<'static>,
+    /// The modulus and the exponent, without leading zeros.
+    modulus: &'a [u8],
+    exponent: &'a [u8],
+    /// The length of the modulus in bits.
+    bits: usize,
+}
+
+impl<'a> PublicKey<'a> {
+    fn new(modulus: &'a [u8], exponent: &'a [u8]) -> Result<Self, Error> {
+        let modulus = strip_leading_zeros(modulus);
+        let exponent = strip_leading_zeros(exponent);
+        let exponent_value = (exponent.len() <= 8).then(|| {
+            exponent
+                .iter()
+                .fold(0u64, |acc, &byte| acc << 8 | byte as u64)
+        });
+        if modulus.is_empty()
+            || modulus.len() > MAX_MODULUS_LEN
+            || modulus[modulus.len() - 1] & 1 == 0
+            || !matches!(exponent_value, Some(2..=MAX_EXPONENT))
+        {
+            return Err(Error::InvalidPublicKey);
+        }
+        Ok(Self {
+            arith: BigMod::new(modulus).map_err(|_| Error::InvalidPublicKey)?,
+            modulus,
+            exponent,
+            bits: 8 * modulus.len() - modulus[0].leading_zeros() as usize,
+        })
+    }
+
+    /// Returns `sig^e` modulo `n` as many bytes as the modulus, or `None` if the signature does
+    /// not have the length of the modulus or is not below it.
+    fn encoded_message(&self, sig: &[u8]) -> Option<Vec<u8>> {
+        // Numbers of the same length compare as their bytes.
+        if sig.len() != self.modulus.len() || sig >= self.modulus {
+            return None;
+        }
+        Some(self.arith.pow(sig, self.exponent))
+    }
+
+    fn verify_pkcs1v15(&self, prefix: &[u8], hashed: &[u8], sig: &[u8]) -> bool {
+        let Some(em) = self.encoded_message(sig) else {
+            return false;
+        };
+
+        // EM = 0x00 || 0x01 || PS || 0x00 || T, with at least 8 bytes of 0xff in PS.
+        let t_len = prefix.len() + hashed.len();
+        if em.len() < t_len + 11 {
+            return false;
+        }
+        let ps_len = em.len() - t_len - 3;
+        let mut expected = vec![0xff; em.len()];
+        expected[0] = 0x00;
+        expected[1] = 0x01;
+        expected[2 + ps_len] = 0x00;
+        expected[3 + ps_len..3 + ps_len + prefix.len()].copy_from_slice(prefix);
+        expected[3 + ps_len + prefix.len()..].copy_from_slice(hashed);
+        em == expected
+    }
+
+    fn verify_pss<D: Digest>(&self, hashed: &[u8], sig: &[u8], salt_len: Option<usize>) -> bool {
+        let Some(em) = self.encoded_message(sig) else {
+            return false;
+        };
+
+        // The encoded message has one bit less than the modulus, and no byte at all if that bit is
+        // the last of the leading byte.
+        let em_bits = self.bits - 1;
+        let (top, em) = em.split_at(em.len() - (em_bits + 7) / 8);
+        if top.iter().any(|&byte| byte != 0) {
+            return false;
+        }
+        let h_len = <D as Digest>::output_size();
+        let unused_bits = 8 * em.len() - em_bits;
+        if hashed.len() != h_len
+            || em.len() < h_len + salt_len.unwrap_or(0) + 2
+            || em.last() != Some(&0xbc)
+            || em[0] & !(0xff >> unused_bits) != 0
+        {
+            return false;
+        }
+
+        // EM = maskedDB || H || 0xbc, where DB = PS || 0x01 || salt with PS made of zeros.
+        let (masked_db, h) = em[..em.len() - 1].split_at(em.len() - h_len - 1);
+        let mut db = mgf1::<D>(h, masked_db.len());
+        for (byte, masked) in db.iter_mut().zip(masked_db) {
+            *byte ^= masked;
+        }
+        db[0] &= 0xff >> unused_bits;
+        let ps_len = match salt_len {
+            Some(salt_len) => db.len() - salt_len - 1,
+            None => match db.iter().position(|&byte| byte != 0) {
+                Some(ps_len) => ps_len,
+                None => return false,
+            },
+        };
+        if db[..ps_len].iter().any(|&byte| byte != 0) || db[ps_len] != 0x01 {
+            return false;
+        }
+
+        let expected = D::new()
+            .chain_update([0u8; 8])
+            .chain_update(hashed)
+            .chain_update(&db[ps_len + 1..])
+            .finalize();
+        expected.as_slice() == h
+    }
+}
+
+/// MGF1 of RFC 8017, with `D` as the hash.
+fn mgf1<D: Digest>(seed: &[u8], len: usize) -> Vec<u8> {
+    let mut mask = Vec::with_capacity(len + <D as Digest>::output_size());
+    let mut counter = 0u32;
+    while mask.len() < len {
+        mask.extend_from_slice(
+            &D::new()
+                .chain_update(seed)
+                .chain_update(counter.to_be_bytes())
+                .finalize(),
+        );
+        counter += 1;
+    }
+    mask.truncate(len);
+    mask
+}
+
+fn strip_leading_zeros(bytes: &[u8]) -> &[u8] {
+    let start = bytes
+        .iter()
+        .position(|&byte| byte != 0)
+        .unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha2::{Sha256, Sha384, Sha512};
+
+    // Keys generated with OpenSSL, with the exponent 65537, and their signatures of `MESSAGE`.
+    const MESSAGE: &[u8] = b"SP1 RSA verification";
+    const EXPONENT: [u8; 3] = [0x01, 0x00, 0x01];
+
+    const MODULUS_2048: &str = concat!(
+        "96761dc3c9e2d9b948fd2703840baf31aaaad9b18ba9d12b86dc7361d8c05dc3",
+        "11dd21123d1af125be17b5ed53ac172c223989b120a954c6ad334ceebf176664",
+        "fc6588f7551eda82cc20492dd8fa6dbe9f28149b90d5987ce53e8d23ccc82c42",
+        "8071e49460b66b83b2778e7bcad67264ef0c7f12b326f22827dfa531c412e31b",
+        "678d84409e4af24248a1631af3a6faa4508469fd66c44557c04bb8b866cf3a3b",
+        "bf935342b7236ecd0bfa974f5f44d9f3baec3e1f80b9c7cbb7cefc85d4f01736",
+        "4ac11f304c0ba8232f562be90e8a1a33fefa943185a2326335963bd6eb9fd12e",
+        "ef8cff4bab1ef9f622d27b71b5842ccd35e3f13b235554c90e9069fa865d8d17",
+    );
+
+    const MODULUS_4096: &str = concat!(
+        "b81e880363b0b07cfc784843df09e351f48c3bf131955f95156db541adfff085",
+        "82cde9e603b1a4b9a2d3450f579a33bc1cd4b9194445194f1a41b8061e3c75d5",
+        "449834ad9a5931b0be0aad836dc9f06fa2131af454745695ab74b6090b0f1c73",
+        "02bc9e6824aa280129da7c70b79a67713d6115c9fd6d97722fbc49f9c5139291",
+        "4bde1b13df766a32e34fbec931c81ba5c302ef5eb9c10903bc8be69bdc1be684",
+        "8e3253b89af9161dc15215492f4431626b88e3ce880272375b257ac19927e7b4",
+        "e17f0f6d4162987728ad5add6c75f0b678958d8f15ac37ce118c6a69e00431d8",
+        "4e4b7eda5f9e6b57502792dca3f0d01278ce93753944e6a84cbbc6f63963a023",
+        "7a334ee34c54954093b8595bf8d1477cd786ced754823d04bd5832cd5087748d",
+        "09e462d745c58b710ab3b9ba431f8923bcecffd9c776ab2cb181fa1877757f6a",
+        "4d67bf486755e98b8643eb29325d3c27c22bd930d7922de14e735d46757fb29b",
+        "068615bfd053641ac4becf4fbf9d1000727b28b633ec26bce5638acb919b745b",
+        "a86f894999fe5777c70ebbd1da80ec4e6f63ba8aca3b5b763faa385447bd450e",
+        "5d3ba5752c3eac8ac0592776b95df71164f3cb8b7f167df7d20be5e22e1f46c9",
+        "c500769a3989f54d1d5b309283ee9e4d7287f4a6a32a5b2c1011562db2ee402b",
+        "2424be3845d4d90ad7e1bf2ac953166e812b849524dac77fd505727003ca1569",
+    );
+
+    const MODULUS_1025: &str = concat!(
+        "0182a4bac8c6eb2209bed9d601b86d8d28a75ebe222f16ce612ab97f75a750ee",
+        "5c196fae79d536e60c211db331d3b77dd325f3a774a7ab3bd9e99e81cf9f4471",
+        "21dbcbef9d150d45b46063510f4c2cbfa7dedd302deb78640fbb224bb35c0e63",
+        "4c0df99366aefd895476dfab5659f39cf98718f9001b5527b78456c0b3c80141",
+        "41",
+    );
+
+    const PKCS1V15_SHA256_2048: &str = concat!(
+        "8298367e3a8c4f261d8db8ad2e2c7200e6832b349acd4dd4614f5d6e94eecb6f",
+        "08677b1db0b581e8f9e7e3f306768d4ed8b28ad01d5334e99bcf1ea79a415c36",
+        "c78b25505873eaa4196342f2be69508b58c93ffdd06d3cf00f94a66ff376f1c4",
+        "08898d75be6e10478f1516df03ef3ca8178ca3432c6e9ca3b7c2e63eddf470ac",
+        "0617cc5badd204523bfc21122ad9e4ca0abb38ed22ee039e896e7ba6d514de0d",
+        "cc77b91bc1a282390c034c52db5bb55b86da474dc4213dd315eb4c3d53985fa1",
+        "1a00f89292ae8c88a355dafc1af7333507513d057aa89122ade1c709fbf58a62",
+        "3683b6dd551663989109bbec529ee7ce3b491bcd9f85d6e6981baffd1dd3024b",
+    );
+
+    const PSS_SHA256_2048: &str = concat!(
+        "60307fe372864928e01a231269163d750e54578de63ab37b2afeabd32961b5dd",
+        "79c6bd29fbc5528aa6382ed4181e242e256992536b9f2c2e572aca4b048e32a7",
+        "6bd9425c365f65c1321d0ae4d132fa99369273a3df3e2e7fdab3b8149f6ced90",
+        "66335e6a3bd82dfad92fc7854e91fa016435be56ead70ac1f1e5c279f12a95b4",
+        "082db910df1f3166c872a994b2148dba0b72e3c270f8a140755517cc56096e05",
+        "b1db8231a40b86d14172e75be41c1a703a9e2bb19268879adb6ec89675663d55",
+        "4aec75c1390f58dc051a92ec93cc4ef734bb98ed0f2b8a32b85e8ac743a65289",
+        "cdf4d7905219a23bf26f5efd4983f83987abce1ab54b035b1be8d26f4a0e6032",
+    );
+
+    const PKCS1V15_SHA512_4096: &str = concat!(
+        "9ef4bfcd8cc21ddd4c80e706a432b025539914eb4ab613a0051a7c27b823ca33",
+        "f1e0e6ae3b6c71840277aaf9bfe970b976940d7586c2416f8ee9eb216af88825",
+        "08c2373bdc0b331a5cd38f2690114aa0c8f6303b33ac90a5b6d264483c456b51",
+        "3f036ca64afdcca459ee96724d5639b542e609a59610fd6dc0e0681b3e0f337d",
+        "3a4007409d675ce9177415b24fe7c8fdd37dfd0df125efc0e0ce1d9de3859dd7",
+        "3d0f23594465e57fd3c8366cfa3e5b6f5e8b59df937b0602a0dad498afeb6980",
+        "1759265bc46f4b5085f9d01754c403ad9d17fe3e4d829936d3894271cb08f30b",
+        "c6f800b681275cfa572b8472bb25a75536c4803211485cbb361e52c7180fe06c",
+        "80de21afacf6734d706b71958ced60f1faa2eeaab764b6e3d188a2495e8a03c4",
+        "8bdbeab258069dbf8ee3d9df745ec9e9def4efedf6d53b3323dd04807ec6f5c1",
+        "2cf55661b4d45d794d07666f721001f17d4e5d55369f24720d3670e57189440a",
+        "538c0acca14400125188b59fad7179ae9a48603095f8c70ef335384f7c1d7195",
+        "0df0a817dc8e1decf02091d4c49608490ab07130083fd6b8f78868fd1c2034e8",
+        "3e89a11767f4f6792dc9a276aba6a6502f37d985ae15dfb168f407e1d42e2d7a",
+        "ba013bb0827eba2bb2ad339e1e7b2f478cef77c6bf81e29aef7594aa37857277",
+        "04080e25f6179708d9121ce153dc1ed94eeea824b599559aae87fdcfc02c4b20",
+    );
+
+    const PSS_SHA384_4096: &str = concat!(
+        "81899f27c917092fb761b32a0c7f41534857d06e034a40e85cdfb6f016cc6970",
+        "6b4d5908273b9343bb8c935b4401e52b84ea6a5f0b0d5afb041b2d42c0f365a7",
+        "a355e8b7cc691b9132b8821d7ea73758862780b92ac1ec80c9441a343cd58fe6",
+        "68ea7f367c4557bf0202ea8bf2f34b03daf39aa7b2501e24ed38481fbaac42b2",
+        "783841c9af250b667961d62d4bafd8714ff6fde32693ca2fd3161cf7b7c70ce4",
+        "a605efafe9920e2dc075c103ca27158fd7868d9ff7f155c69cfa970b8e34989b",
+        "a4c9018df60af64b9f6e705fc254c4dca30b2864bb77471714f9f813fe7f8bd3",
+        "a702b86b8ee0666e14e4f679f0c22a237ab839eb724fecec8954e9ca673cbec7",
+        "36d9e52931116be5e29fa7b48ba7bfd57b0db6c2120874677e61034c56149ab1",
+        "2e04bef81085f58397eb397907a7ba5b70501c584d80cdaf52211322cc2fde72",
+        "fb603f3e36fb6c7eae5f869a2aaebbf8d5b0668c5aa0e15d7ff86c253a53bbb5",
+        "409a92bdbc1077dd87b082600a21377cdaf14558c965abdbd807e159725c756f",
+        "ba2eb9955bcf9325cc9770adcac0ccf3cff297e3a2142d761d076e42080e91d4",
+        "279f5100082f66c7794a62cd00db1c0ad3801b44c6a30f399ea0c1fe3df6298e",
+        "253b954deaf885019bcd16abbfcd30b79bb402988f86a8a4e2f1c124eb17893e",
+        "2be132db1dbff9750b7e8324e8c59a1582bf166e5eb42cdafcdcd95dab840569",
+    );
+
+    const PSS_SHA256_1025: &str = concat!(
+        "014a40f68b6323dce4145ea414f0d98ec3c0c66d6ac68f5d597c932952aa0883",
+        "1f54a26697631e2bc54511e516eb6adcd565eeb86ca44681d715c6ddf63ba137",
+        "0ae2f69bdc8a38bbb7ad1bbe9fde0ec3a38e69619ddc72623c97b65684d403bf",
+        "e40f755fb347226cd42fce231672b7d83e23380c4c397b10c50582f956c69188",
+        "19",
+    );
+
+    fn bytes(hex_str: &str) -> Vec<u8> {
+        hex::decode(hex_str).unwrap()
+    }
+
+    #[test]
+    fn test_verify_pkcs1v15() {
+        let modulus = bytes(MODULUS_2048);
+        let sig = bytes(PKCS1V15_SHA256_2048);
+        let hashed = Sha256::digest(MESSAGE);
+        let verify = |prefix: &[u8], hashed: &[u8], sig: &[u8]| {
+            verify_pkcs1v15(&modulus, &EXPONENT, prefix, hashed, sig)
+        };
+        assert_eq!(verify(&SHA256_PREFIX, &hashed, &sig), Ok(true));
+
+        let mut other_hash = hashed;
+        other_hash[0] ^= 1;
+        let mut other_sig = sig.clone();
+        other_sig[255] ^= 1;
+        let mut unprefixed = SHA256_PREFIX.to_vec();
+        unprefixed.extend_from_slice(&hashed);
+        assert_eq!(verify(&SHA256_PREFIX, &other_hash, &sig), Ok(false));
+        assert_eq!(verify(&SHA256_PREFIX, &hashed, &other_sig), Ok(false));
+        assert_eq!(verify(&SHA384_PREFIX, &hashed, &sig), Ok(false));
+        assert_eq!(verify(&[], &hashed, &sig), Ok(false));
+        // The same encoding, with the DigestInfo passed as raw data.
+        assert_eq!(verify(&[], &unprefixed, &sig), Ok(true));
+        // Signatures of another length than the modulus, or not below it.
+        assert_eq!(verify(&SHA256_PREFIX, &hashed, &sig[1..]), Ok(false));
+        assert_eq!(
+            verify(&SHA256_PREFIX, &hashed, &[&[0][..], &sig].concat()),
+            Ok(false)
+        );
+        assert_eq!(verify(&SHA256_PREFIX, &hashed, &modulus), Ok(false));
+        // A digest too long for the modulus.
+        assert_eq!(verify(&SHA256_PREFIX, &[0; 250], &sig), Ok(false));
+
+        // Leading zeros of the key are ignored.
+        assert_eq!(
+            verify_pkcs1v15(
+                &[&[0][..], &modulus].concat(),
+                &[0, 0, 1, 0, 1],
+                &SHA256_PREFIX,
+                &hashed,
+                &sig
+            ),
+            Ok(true)
+        );
+
+        assert_eq!(
+            verify_pkcs1v15(
+                &bytes(MODULUS_4096),
+                &EXPONENT,
+                &SHA512_PREFIX,
+                &Sha512::digest(MESSAGE),
+                &bytes(PKCS1V15_SHA512_4096)
+            ),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn test_verify_pss() {
+        let modulus = bytes(MODULUS_2048);
+        let sig = bytes(PSS_SHA256_2048);
+        let hashed = Sha256::digest(MESSAGE);
+        for salt_len in [None, Some(32)] {
+            assert_eq!(
+                verify_pss::<Sha256>(&modulus, &EXPONENT, &hashed, &sig, salt_len),
+                Ok(true)
+            );
+        }
+        let mut other_hash = hashed;
+        other_hash[31] ^= 1;
+        let mut other_sig = sig.clone();
+        other_sig[0] ^= 1;
+        for (hashed, sig, salt_len) in [
+            (&hashed[..], &sig[..], Some(20)),
+            (&hashed[..], &sig[..], Some(0)),
+            (&other_hash[..], &sig[..], None),
+            (&hashed[..], &other_sig[..], None),
+            (&hashed[..31], &sig[..], None),
+        ] {
+            assert_eq!(
+                verify_pss::<Sha256>(&modulus, &EXPONENT, hashed, sig, salt_len),
+                Ok(false)
+            );
+        }
+        // The PKCS#1 v1.5 signature is not a PSS one.
+        let sig = bytes(PKCS1V15_SHA256_2048);
+        assert_eq!(
+            verify_pss::<Sha256>(&modulus, &EXPONENT, &hashed, &sig, None),
+            Ok(false)
+        );
+
+        // An empty salt, with SHA-384.
+        let (modulus, sig) = (bytes(MODULUS_4096), bytes(PSS_SHA384_4096));
+        let hashed = Sha384::digest(MESSAGE);
+        for salt_len in [None, Some(0)] {
+            assert_eq!(
+                verify_pss::<Sha384>(&modulus, &EXPONENT, &hashed, &sig, salt_len),
+                Ok(true)
+            );
+        }
+        assert_eq!(
+            verify_pss::<Sha256>(&modulus, &EXPONENT, &Sha256::digest(MESSAGE), &sig, None),
+            Ok(false)
+        );
+
+        // With a 1025-bit modulus, the encoded message is a byte shorter than the modulus.
+        let (modulus, sig) = (bytes(MODULUS_1025), bytes(PSS_SHA256_1025));
+        let hashed = Sha256::digest(MESSAGE);
+        for salt_len in [None, Some(20)] {
+            assert_eq!(
+                verify_pss::<Sha256>(&modulus, &EXPONENT, &hashed, &sig, salt_len),
+                Ok(true)
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_rejects_invalid_keys() {
+        let modulus = bytes(MODULUS_2048);
+        let mut even = modulus.clone();
+        even[255] ^= 1;
+        let sig = bytes(PKCS1V15_SHA256_2048);
+        let hashed = Sha256::digest(MESSAGE);
+        for (modulus, exponent) in [
+            (&even[..], &EXPONENT[..]),
+            (&[], &EXPONENT[..]),
+            (&[0; 4], &EXPONENT[..]),
+            (&[0xff; 513], &EXPONENT[..]),
+            (&modulus[..], &[1]),
+            (&modulus[..], &[]),
+            (&modulus[..], &[2, 0, 0, 0, 0]),
+            (&modulus[..], &[1, 0, 0, 0, 0, 0, 0, 0, 1]),
+        ] {
+            assert_eq!(
+                verify_pkcs1v15(modulus, exponent, &SHA256_PREFIX, &hashed, &sig),
+                Err(Error::InvalidPublicKey)
+            );
+            assert_eq!(
+                verify_pss::<Sha256>(modulus, exponent, &hashed, &sig, None),
+                Err(Error::InvalidPublicKey)
+            );
+        }
+        // The smallest and the largest exponents are accepted.
+        for exponent in [&[2][..], &[1, 0xff, 0xff, 0xff, 0xff]] {
+            assert_eq!(
+                verify_pkcs1v15(&modulus, exponent, &SHA256_PREFIX, &hashed, &sig),
+                Ok(false)
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-hints"))]
+    fn test_verify_with_mock_hook() {
+        use crate::bigmod::hook_hints;
+        use crate::bigmod::tests::hook_responses;
+        use crate::io::{mock, FD_BIGMOD_HOOK};
+        use core::cell::Cell;
+        use std::rc::Rc;
+
+        type Tamper = fn(&[u8]) -> Vec<Vec<u8>>;
+        let modulus = bytes(MODULUS_2048);
+        let hashed = Sha256::digest(MESSAGE);
+        let sig = bytes(PKCS1V15_SHA256_2048);
+        let pss_sig = bytes(PSS_SHA256_2048);
+        let mut forged = sig.clone();
+        forged[255] ^= 1;
+
+        let run = |tamper: Tamper| {
+            let requests = Rc::new(Cell::new(0));
+            let hook = {
+                let requests = requests.clone();
+                move |fd, request: &[u8]| {
+                    assert_eq!(fd, FD_BIGMOD_HOOK);
+                    requests.set(requests.get() + 1);
+                    tamper(request)
+                }
+            };
+            let (results, unread) = mock::with_hook(hook, || {
+                let mut key = PublicKey::new(&modulus, &EXPONENT).unwrap();
+                key.arith = BigMod::with_hints(&modulus, hook_hints).unwrap();
+                [
+                    key.verify_pkcs1v15(&SHA256_PREFIX, &hashed, &sig),
+                    key.verify_pss::<Sha256>(&hashed, &pss_sig, None),
+                    key.verify_pkcs1v15(&SHA256_PREFIX, &hashed, &forged),
+                ]
+            });
+            assert_eq!(unread, 0);
+            (results, requests.get())
+        };
+
+        // The cost documented above: 16 squarings and a multiplication for each signature, as the
+        // first squaring and multiplication by the signature need no reduction.
+        assert_eq!(run(hook_responses), ([true, true, false], 3 * 17));
+
+        // The responses for another value, a remainder off by one, and missing responses do not
+        // change the results.
+        let tampers: [Tamper; 3] = [
+            |request| {
+                let mut other = request.to_vec();
+                *other.last_mut().unwrap() ^= 1;
+                hook_responses(&other)
+            },
+            |request| {
+                let mut res = hook_responses(request);
+                *res[1].last_mut().unwrap() ^= 1;
+                res
+            },
+            |_| vec![vec![], vec![]],
+        ];
+        for tamper in tampers {
+            assert_eq!(run(tamper).0, [true, true, false]);
+        }
+    }
+}
@@ -1,21 +1,33 @@
 #![allow(unused)]
 
+use crate::error::Error;
 use crate::io::{self, FD_ECRECOVER_HOOK};
+use crate::keccak::{keccak256, Keccak256};
+use crate::sha256::Sha256;
 use crate::unconstrained;
 use crate::utils::{AffinePoint, CurveOperations};
 use crate::{syscall_secp256k1_add, syscall_secp256k1_decompress, syscall_secp256k1_double};
 
-use anyhow::Context;
-use anyhow::{anyhow, Result};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 use core::convert::TryInto;
 use k256::ecdsa::hazmat::bits2field;
 use k256::ecdsa::signature::hazmat::PrehashVerifier;
 use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
 use k256::elliptic_curve::ff::PrimeFieldBits;
-use k256::elliptic_curve::ops::Invert;
-use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::elliptic_curve::ops::{Invert, Reduce};
+use k256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
 use k256::elliptic_curve::PrimeField;
-use k256::{PublicKey, Scalar, Secp256k1};
+use k256::{FieldBytes, FieldElement, PublicKey, Scalar, Secp256k1, U256};
+use serde::{Deserialize, Serialize};
+
+pub mod adaptor;
+pub mod consts;
+pub mod pedersen;
+pub mod recoverable;
+pub mod schnorr;
+pub mod stealth;
+pub mod xonly;
 
 const NUM_WORDS: usize = 16;
 
@@ -23,12 +35,7 @@ const NUM_WORDS: usize = 16;
 pub struct Secp256k1Operations;
 
 impl CurveOperations<NUM_WORDS> for Secp256k1Operations {
-    // The values are taken from https://en.bitcoin.it/wiki/Secp256k1.
-    const GENERATOR: [u32; NUM_WORDS] = [
-        385357720, 1509065051, 768485593, 43777243, 3464956679, 1436574357, 4191992748, 2042521214,
-        4212184248, 2621952143, 2793755673, 4246189128, 235997352, 1571093500, 648266853,
-        1211816567,
-    ];
+    const GENERATOR: [u32; NUM_WORDS] = consts::GENERATOR_LIMBS;
     fn add_assign(limbs: &mut [u32; NUM_WORDS], other: &[u32; NUM_WORDS]) {
         unsafe {
             syscall_secp256k1_add(limbs.as_mut_ptr(), other.as_ptr());
@@ -42,17 +49,130 @@ impl CurveOperations<NUM_WORDS> for Secp256k1Operations {
     }
 }
 
+/// An affine point on the secp256k1 curve, stored as the little endian limbs used by the
+/// precompiles.
+pub type Secp256k1AffinePoint = AffinePoint<Secp256k1Operations, NUM_WORDS>;
+
+impl Secp256k1AffinePoint {
+    /// Creates a point from its big endian coordinates, converting them to limbs in one pass.
+    pub fn from_be_coordinates(x: &[u8; 32], y: &[u8; 32]) -> Self {
+        let mut limbs = [0u32; NUM_WORDS];
+        limbs[..NUM_WORDS / 2].copy_from_slice(&consts::be_to_limbs(x));
+        limbs[NUM_WORDS / 2..].copy_from_slice(&consts::be_to_limbs(y));
+        Self::new(limbs)
+    }
+
+    /// Returns the big endian encoding of the x-coordinate.
+    pub fn x_be(&self) -> [u8; 32] {
+        limbs_to_be(&self.limbs[..NUM_WORDS / 2])
+    }
+
+    /// Returns the big endian encoding of the y-coordinate.
+    pub fn y_be(&self) -> [u8; 32] {
+        limbs_to_be(&self.limbs[NUM_WORDS / 2..])
+    }
+}
+
+/// Converts a k256 point, returning [`Error::IdentityPoint`] for the point at infinity.
+impl TryFrom<k256::AffinePoint> for Secp256k1AffinePoint {
+    type Error = Error;
+
+    fn try_from(point: k256::AffinePoint) -> Result<Self, Error> {
+        let encoded = point.to_encoded_point(false);
+        match (encoded.x(), encoded.y()) {
+            (Some(x), Some(y)) => Ok(Self::from_be_coordinates(&(*x).into(), &(*y).into())),
+            _ => Err(Error::IdentityPoint),
+        }
+    }
+}
+
+/// Converts the point to a k256 point, returning [`Error::InvalidPublicKey`] if it is not on the
+/// curve.
+impl TryFrom<Secp256k1AffinePoint> for k256::AffinePoint {
+    type Error = Error;
+
+    fn try_from(point: Secp256k1AffinePoint) -> Result<Self, Error> {
+        let encoded = k256::EncodedPoint::from(point);
+        Option::from(k256::AffinePoint::from_encoded_point(&encoded)).ok_or(Error::InvalidPublicKey)
+    }
+}
+
+/// Parses a SEC1 encoded point with [`parse_sec1_pubkey`], returning [`Error::IdentityPoint`] for
+/// the encoding of the point at infinity.
+impl TryFrom<&k256::EncodedPoint> for Secp256k1AffinePoint {
+    type Error = Error;
+
+    fn try_from(encoded: &k256::EncodedPoint) -> Result<Self, Error> {
+        if encoded.is_identity() {
+            return Err(Error::IdentityPoint);
+        }
+        let uncompressed = parse_sec1_pubkey(encoded.as_bytes())?;
+        Ok(Self::from_be_coordinates(
+            uncompressed[1..33].try_into().unwrap(),
+            uncompressed[33..].try_into().unwrap(),
+        ))
+    }
+}
+
+/// Encodes the point as an uncompressed SEC1 point, without checking that it is on the curve.
+impl From<Secp256k1AffinePoint> for k256::EncodedPoint {
+    fn from(point: Secp256k1AffinePoint) -> Self {
+        Self::from_affine_coordinates(&point.x_be().into(), &point.y_be().into(), false)
+    }
+}
+
+/// Converts the little endian limbs of a coordinate to its big endian encoding.
+fn limbs_to_be(limbs: &[u32]) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    for (i, limb) in limbs.iter().enumerate() {
+        let end = 32 - 4 * i;
+        bytes[end - 4..end].copy_from_slice(&limb.to_be_bytes());
+    }
+    bytes
+}
+
 /// Decompresses a compressed public key using secp256k1_decompress precompile.
-pub fn decompress_pubkey(compressed_key: &[u8; 33]) -> Result<[u8; 65]> {
+///
+/// Use [`parse_sec1_pubkey`] to also accept uncompressed and hybrid encodings.
+pub fn decompress_pubkey(compressed_key: &[u8; 33]) -> Result<[u8; 65], Error> {
+    parse_sec1_pubkey(compressed_key)
+}
+
+/// Parses a public key in any SEC1 encoding, and returns its uncompressed encoding.
+///
+/// The accepted encodings are the compressed ones, with prefix 2 or 3, the uncompressed one, with
+/// prefix 4, and the hybrid ones, uncompressed keys with prefix 6 or 7 whose prefix also encodes
+/// the parity of the y-coordinate. The identity, encoded as a single zero byte, has no public key.
+///
+/// Returns [`Error::InvalidPublicKey`] for other prefixes and lengths, for points that are not on
+/// the curve, and for hybrid keys whose prefix does not match the parity of y. Inside the zkVM,
+/// compressed keys are decompressed with the precompile, and an x-coordinate that has no point
+/// makes the execution fail.
+pub fn parse_sec1_pubkey(bytes: &[u8]) -> Result<[u8; 65], Error> {
+    match (bytes.first(), bytes.len()) {
+        (Some(&prefix @ (2 | 3)), 33) => {
+            decompress_point(bytes[1..].try_into().unwrap(), prefix == 3)
+        }
+        (Some(&prefix @ (4 | 6 | 7)), 65) => {
+            let mut uncompressed = [0u8; 65];
+            uncompressed.copy_from_slice(bytes);
+            uncompressed[0] = 4;
+            validate_uncompressed_pubkey(&uncompressed)?;
+            if prefix != 4 && prefix & 1 != bytes[64] & 1 {
+                return Err(Error::InvalidPublicKey);
+            }
+            Ok(uncompressed)
+        }
+        _ => Err(Error::InvalidPublicKey),
+    }
+}
+
+/// Computes the point with the given x-coordinate and y parity, in its uncompressed encoding.
+fn decompress_point(x: &[u8; 32], is_odd: bool) -> Result<[u8; 65], Error> {
     cfg_if::cfg_if! {
         if #[cfg(all(target_os = "zkvm", target_vendor = "succinct"))] {
             let mut decompressed_key: [u8; 64] = [0; 64];
-            decompressed_key[..32].copy_from_slice(&compressed_key[1..]);
-            let is_odd = match compressed_key[0] {
-                2 => false,
-                3 => true,
-                _ => return Err(anyhow!("Invalid compressed key")),
-            };
+            decompressed_key[..32].copy_from_slice(x);
             unsafe {
                 syscall_secp256k1_decompress(&mut decompressed_key, is_odd);
             }
@@ -62,10 +182,13 @@ pub fn decompress_pubkey(compressed_key: &[u8; 33]) -> Result<[u8; 65]> {
             result[1..].copy_from_slice(&decompressed_key);
             Ok(result)
         } else {
-            let public_key = PublicKey::from_sec1_bytes(compressed_key).context("invalid pubkey")?;
-            let bytes = public_key.to_encoded_point(false).to_bytes();
+            let mut compressed_key = [0u8; 33];
+            compressed_key[0] = 2 + is_odd as u8;
+            compressed_key[1..].copy_from_slice(x);
+            let public_key =
+                PublicKey::from_sec1_bytes(&compressed_key).map_err(|_| Error::InvalidPublicKey)?;
             let mut result: [u8; 65] = [0; 65];
-            result.copy_from_slice(&bytes);
+            result.copy_from_slice(public_key.to_encoded_point(false).as_bytes());
             Ok(result)
         }
     }
@@ -75,87 +198,171 @@ pub fn decompress_pubkey(compressed_key: &[u8; 33]) -> Result<[u8; 65]> {
 /// provided, it will be validated and used to verify the signature. Otherwise, the inverse of s
 /// will be computed and used.
 ///
-/// Warning: this function does not check if the key is actually on the curve.
+/// Returns `false` both for invalid inputs and for signatures that do not verify, use
+/// [`try_verify_signature`] to distinguish the two.
 pub fn verify_signature(
     pubkey: &[u8; 65],
     msg_hash: &[u8; 32],
     signature: &Signature,
     s_inverse: Option<&Scalar>,
 ) -> bool {
+    try_verify_signature(pubkey, msg_hash, signature, s_inverse).unwrap_or(false)
+}
+
+/// Verifies a secp256k1 signature using the public key and the message hash, like
+/// [`verify_signature`].
+///
+/// The public key may be uncompressed or hybrid, as parsed by [`parse_sec1_pubkey`].
+///
+/// Returns an error if the public key is not a point on the curve, if the message hash cannot be
+/// converted to a scalar or if the provided s_inverse is not the inverse of s, and `Ok(false)` if
+/// the signature does not verify.
+pub fn try_verify_signature(
+    pubkey: &[u8; 65],
+    msg_hash: &[u8; 32],
+    signature: &Signature,
+    s_inverse: Option<&Scalar>,
+) -> Result<bool, Error> {
+    if let Some(s_inv) = s_inverse {
+        check_s_inverse(signature, s_inv)?;
+    }
+
+    let pubkey = parse_sec1_pubkey(pubkey)?;
     cfg_if::cfg_if! {
         if #[cfg(all(target_os = "zkvm", target_vendor = "succinct"))] {
-            let pubkey_x = Scalar::from_repr(bits2field::<Secp256k1>(&pubkey[1..33]).unwrap()).unwrap();
-            let pubkey_y = Scalar::from_repr(bits2field::<Secp256k1>(&pubkey[33..]).unwrap()).unwrap();
+            // Convert the public key to an affine point
+            let affine = Secp256k1AffinePoint::from_be_coordinates(
+                pubkey[1..33].try_into().unwrap(),
+                pubkey[33..].try_into().unwrap(),
+            );
 
-            let mut pubkey_x_le_bytes = pubkey_x.to_bytes();
-            pubkey_x_le_bytes.reverse();
-            let mut pubkey_y_le_bytes = pubkey_y.to_bytes();
-            pubkey_y_le_bytes.reverse();
+            let Some(res) = nonce_point(&affine, msg_hash, signature, s_inverse)? else {
+                return Ok(false);
+            };
 
-            // Convert the public key to an affine point
-            let affine = AffinePoint::<Secp256k1Operations, NUM_WORDS>::from(&pubkey_x_le_bytes, &pubkey_y_le_bytes);
+            // The signature must satisfy r = x mod n.
+            let (r, _) = signature.split_scalars();
+            let x = <Scalar as Reduce<U256>>::reduce_bytes(&FieldBytes::from(res.x_be()));
+            Ok(*r == x)
+        } else {
+            let public_key =
+                PublicKey::from_sec1_bytes(&pubkey).map_err(|_| Error::InvalidPublicKey)?;
 
-            const GENERATOR: AffinePoint<Secp256k1Operations, NUM_WORDS> = AffinePoint::<Secp256k1Operations, NUM_WORDS>::generator_in_affine();
+            let verify_key = VerifyingKey::from(&public_key);
+            Ok(verify_key.verify_prehash(msg_hash, signature).is_ok())
+        }
+    }
+}
 
-            let field = bits2field::<Secp256k1>(msg_hash);
-            if field.is_err() {
-                return false;
-            }
-            let field = Scalar::from_repr(field.unwrap()).unwrap();
-            let z = field;
-            let (r, s) = signature.split_scalars();
-            let computed_s_inv;
-            let s_inv = match s_inverse {
-                Some(s_inv) => {
-                    assert_eq!(s_inv * s.as_ref(), Scalar::ONE);
-                    s_inv
-                }
-                None => {
-                    computed_s_inv = s.invert();
-                    &computed_s_inv
-                }
-            };
+/// Checks that `s_inv` is the inverse of the `s` scalar of `signature`.
+fn check_s_inverse(signature: &Signature, s_inv: &Scalar) -> Result<(), Error> {
+    let (_, s) = signature.split_scalars();
+    if s_inv * s.as_ref() != Scalar::ONE {
+        return Err(Error::InvalidSInverse);
+    }
+    Ok(())
+}
 
-            let u1 = z * s_inv;
-            let u2 = *r * s_inv;
+/// Computes `z * s^-1 * G + r * s^-1 * Q`, whose x-coordinate is `r` modulo n for a valid
+/// signature. Returns `None` if the result is the point at infinity.
+///
+/// The s_inverse value, if provided, must have been checked with [`check_s_inverse`].
+fn nonce_point(
+    pubkey: &Secp256k1AffinePoint,
+    msg_hash: &[u8; 32],
+    signature: &Signature,
+    s_inverse: Option<&Scalar>,
+) -> Result<Option<Secp256k1AffinePoint>, Error> {
+    // Per SEC1, digests that are not below the curve order are reduced modulo n.
+    let field = bits2field::<Secp256k1>(msg_hash).map_err(|_| Error::InvalidMessageHash)?;
+    let z = <Scalar as Reduce<U256>>::reduce_bytes(&field);
+    let (r, s) = signature.split_scalars();
+    let computed_s_inv;
+    let s_inv = match s_inverse {
+        Some(s_inv) => s_inv,
+        None => {
+            // `s` is not zero, so it always has an inverse.
+            computed_s_inv = scalar_invert(s.as_ref()).unwrap();
+            &computed_s_inv
+        }
+    };
 
-            let res = double_and_add_base(&u1, &GENERATOR, &u2, &affine).unwrap();
-            let mut x_bytes_be = [0u8; 32];
-            for i in 0..8 {
-                x_bytes_be[i * 4..(i * 4) + 4].copy_from_slice(&res.limbs[i].to_le_bytes());
-            }
-            x_bytes_be.reverse();
+    let u1 = z * s_inv;
+    let u2 = *r * s_inv;
 
-            let x_field = bits2field::<Secp256k1>(&x_bytes_be);
-            if x_field.is_err() {
-                return false;
-            }
-            *r == Scalar::from_repr(x_field.unwrap()).unwrap()
-        } else {
-            let public_key = PublicKey::from_sec1_bytes(pubkey);
-            if public_key.is_err() {
-                return false;
-            }
-            let public_key = public_key.unwrap();
+    Ok(double_and_add_base(&u1, &consts::GENERATOR, &u2, pubkey))
+}
 
-            let verify_key = VerifyingKey::from(&public_key);
-            let res = verify_key
-                .verify_prehash(msg_hash, signature)
-                .context("invalid signature");
+/// Returns the inverse of `s` modulo the curve order, or `None` if `s` is zero.
+///
+/// Inside the zkVM, the inverse is requested from the modular inverse hook and checked with a
+/// single multiplication, instead of being computed with a few hundred of them. A hint that fails
+/// the check is ignored, and the inverse is computed in the VM. Without the `alloc` feature, or
+/// with the `no-hints` feature, the inverse is always computed in the VM.
+pub fn scalar_invert(s: &Scalar) -> Option<Scalar> {
+    if *s == Scalar::ZERO {
+        return None;
+    }
 
-            res.is_ok()
+    #[cfg(all(
+        target_os = "zkvm",
+        target_vendor = "succinct",
+        feature = "alloc",
+        not(feature = "no-hints")
+    ))]
+    {
+        let s_bytes = s.to_bytes();
+        unconstrained! {
+            let mut buf = [0u8; 64];
+            buf[..32].copy_from_slice(&consts::N);
+            buf[32..].copy_from_slice(&s_bytes);
+            io::write(io::FD_MODINV_HOOK, &buf);
+        }
+
+        let hint = io::read_vec();
+        if let Ok(hint) = <[u8; 32]>::try_from(hint.as_slice()) {
+            if let Some(inverse) = Option::<Scalar>::from(Scalar::from_repr(hint.into())) {
+                if inverse * s == Scalar::ONE {
+                    return Some(inverse);
+                }
+            }
         }
     }
+
+    Option::from(s.invert())
+}
+
+/// Checks that `pubkey` is the uncompressed SEC1 encoding of a point on the curve.
+pub fn validate_uncompressed_pubkey(pubkey: &[u8; 65]) -> Result<(), Error> {
+    if pubkey[0] != 4 {
+        return Err(Error::InvalidPublicKey);
+    }
+
+    // `from_repr` rejects coordinates that are not below the field modulus.
+    let x = Option::<FieldElement>::from(FieldElement::from_repr(FieldBytes::clone_from_slice(
+        &pubkey[1..33],
+    )))
+    .ok_or(Error::InvalidPublicKey)?;
+    let y = Option::<FieldElement>::from(FieldElement::from_repr(FieldBytes::clone_from_slice(
+        &pubkey[33..],
+    )))
+    .ok_or(Error::InvalidPublicKey)?;
+
+    // Check the curve equation y^2 = x^3 + 7, on normalized representations.
+    if y.square().normalize() != (x.square() * x + FieldElement::from(7u64)).normalize() {
+        return Err(Error::InvalidPublicKey);
+    }
+    Ok(())
 }
 
 #[allow(non_snake_case)]
 fn double_and_add_base(
     a: &Scalar,
-    A: &AffinePoint<Secp256k1Operations, NUM_WORDS>,
+    A: &Secp256k1AffinePoint,
     b: &Scalar,
-    B: &AffinePoint<Secp256k1Operations, NUM_WORDS>,
-) -> Option<AffinePoint<Secp256k1Operations, NUM_WORDS>> {
-    let mut res: Option<AffinePoint<Secp256k1Operations, NUM_WORDS>> = None;
+    B: &Secp256k1AffinePoint,
+) -> Option<Secp256k1AffinePoint> {
+    let mut res: Option<Secp256k1AffinePoint> = None;
     let mut temp_A = *A;
     let mut temp_B = *B;
 
@@ -188,39 +395,1759 @@ fn double_and_add_base(
 /// WARNING: The values are read from outside of the VM and are not constrained to be correct.
 /// Either use `decompress_pubkey` and `verify_signature` to verify the results of this function, or
 /// use `ecrecover`.
-pub fn unconstrained_ecrecover(sig: &[u8; 65], msg_hash: &[u8; 32]) -> ([u8; 33], Scalar) {
+///
+/// Returns an error if the hook reports that the signature is invalid, if the hook responses do not
+/// have the expected lengths, or if the s_inverse value is not a valid scalar.
+///
+/// Not available with the `no-hints` feature, or without the `alloc` feature, since hook responses
+/// are read into freshly allocated buffers.
+#[cfg(all(feature = "alloc", not(feature = "no-hints")))]
+pub fn unconstrained_ecrecover(
+    sig: &[u8; 65],
+    msg_hash: &[u8; 32],
+) -> Result<([u8; 33], Scalar), Error> {
     // The `unconstrained!` wrapper is used since none of these computations directly affect
     // the output values of the VM. The remainder of the function sets the constraints on the values
     // instead. Removing the `unconstrained!` wrapper slightly increases the cycle count.
     unconstrained! {
-        let mut buf = [0; 65 + 32];
-        let (buf_sig, buf_msg_hash) = buf.split_at_mut(sig.len());
-        buf_sig.copy_from_slice(sig);
-        buf_msg_hash.copy_from_slice(msg_hash);
-        io::write(FD_ECRECOVER_HOOK, &buf);
+        write_ecrecover_request(sig, msg_hash);
     }
+    read_ecrecover_response(sig)
+}
 
-    let recovered_bytes: [u8; 33] = io::read_vec().try_into().unwrap();
+#[cfg(all(feature = "alloc", not(feature = "no-hints")))]
+fn write_ecrecover_request(sig: &[u8; 65], msg_hash: &[u8; 32]) {
+    io::write_ecrecover_request(FD_ECRECOVER_HOOK, sig, msg_hash);
+}
 
-    let s_inv_bytes: [u8; 32] = io::read_vec().try_into().unwrap();
-    let s_inverse = Scalar::from_repr(bits2field::<Secp256k1>(&s_inv_bytes).unwrap()).unwrap();
+/// Reads the response of the ecrecover hook to the request of `sig`.
+#[cfg(all(feature = "alloc", not(feature = "no-hints")))]
+fn read_ecrecover_response(sig: &[u8; 65]) -> Result<([u8; 33], Scalar), Error> {
+    let (recovered_bytes, s_inv_bytes) = io::read_ecrecover_response(FD_ECRECOVER_HOOK, sig)?;
+    let s_inverse =
+        Option::<Scalar>::from(Scalar::from_repr(s_inv_bytes.into())).ok_or(Error::BadHint {
+            fd: FD_ECRECOVER_HOOK,
+        })?;
 
-    (recovered_bytes, s_inverse)
+    Ok((recovered_bytes, s_inverse))
 }
 
 /// Given a signature and a message hash, returns the public key that signed the message.
-pub fn ecrecover(sig: &[u8; 65], msg_hash: &[u8; 32]) -> Result<[u8; 65]> {
-    let (pubkey, s_inv) = unconstrained_ecrecover(sig, msg_hash);
-    let pubkey = decompress_pubkey(&pubkey).context("decompress pubkey failed")?;
-    let verified = verify_signature(
-        &pubkey,
-        msg_hash,
-        &Signature::from_slice(&sig[..64]).unwrap(),
-        Some(&s_inv),
+///
+/// The last byte of the signature may use any of the encodings accepted by
+/// [`RecoveryMeta::from_v`]. Signatures held as a [`recoverable::RecoverableSignature`] are
+/// recovered with its `recover` method.
+///
+/// Inside the zkVM, the key and the inverse of `s` are requested from the ecrecover hook. Hook
+/// responses can only be read with the `alloc` feature, so without it, or with the `no-hints`
+/// feature, the key is recovered in the VM instead, with the same result at a higher cost.
+pub fn ecrecover(sig: &[u8; 65], msg_hash: &[u8; 32]) -> Result<[u8; 65], Error> {
+    let meta = RecoveryMeta::from_v(sig[64] as u64)?;
+    let mut sig64 = [0u8; 64];
+    sig64.copy_from_slice(&sig[..64]);
+    ecrecover_with_meta(&sig64, meta, msg_hash)
+}
+
+/// Given the `r` and `s` scalars of a signature and its `v` value, returns the public key that
+/// signed the message.
+///
+/// Unlike [`ecrecover`], `v` may be any EIP-155 value, including those of chain ids that do not
+/// fit in a byte.
+pub fn ecrecover_from_scalars(
+    r: &[u8; 32],
+    s: &[u8; 32],
+    v: u64,
+    msg_hash: &[u8; 32],
+) -> Result<[u8; 65], Error> {
+    let meta = RecoveryMeta::from_v(v)?;
+    let mut sig64 = [0u8; 64];
+    sig64[..32].copy_from_slice(r);
+    sig64[32..].copy_from_slice(s);
+    ecrecover_with_meta(&sig64, meta, msg_hash)
+}
+
+/// Checks that the signature `r || s || v` over `msg_hash` was made by `expected_pubkey`, with the
+/// same result as comparing the key returned by [`ecrecover`] with it, but at the cost of a
+/// signature verification.
+///
+/// Inside the zkVM, no key is queried from the `ecrecover` hook: the nonce point is computed from
+/// the expected key, with only the inverse of s requested from the modular inverse hook, and its
+/// x-coordinate and parity are checked against `r` and `v`. The expected key may be uncompressed or
+/// hybrid, as parsed by [`parse_sec1_pubkey`].
+///
+/// Returns `Ok(false)` if the signature was not made by the expected key, or does not recover.
+/// Returns [`Error::InvalidRecoveryId`] if `v` is invalid, [`Error::InvalidSignature`] if r or s
+/// is zero or not below the curve order, and [`Error::InvalidPublicKey`] if the expected key is
+/// not a point on the curve.
+pub fn verify_with_known_signer(
+    expected_pubkey: &[u8; 65],
+    sig: &[u8; 65],
+    msg_hash: &[u8; 32],
+) -> Result<bool, Error> {
+    let meta = RecoveryMeta::from_v(sig[64] as u64)?;
+    let signature = Signature::from_slice(&sig[..64]).map_err(|_| Error::InvalidSignature)?;
+    let pubkey = parse_sec1_pubkey(expected_pubkey)?;
+
+    cfg_if::cfg_if! {
+        if #[cfg(all(target_os = "zkvm", target_vendor = "succinct"))] {
+            let affine = Secp256k1AffinePoint::from_be_coordinates(
+                pubkey[1..33].try_into().unwrap(),
+                pubkey[33..].try_into().unwrap(),
+            );
+            let Some(nonce) = nonce_point(&affine, msg_hash, &signature, None)? else {
+                return Ok(false);
+            };
+
+            // Like in `verify_recovered`, the x-coordinate must be r itself, as recovery ids for
+            // x >= n are not supported.
+            let (r, _) = signature.split_scalars();
+            Ok(nonce.x_be() == <[u8; 32]>::from(r.to_bytes())
+                && (nonce.y_be()[31] & 1 == 1) == meta.parity())
+        } else {
+            let verifying_key =
+                VerifyingKey::from_sec1_bytes(&pubkey).map_err(|_| Error::InvalidPublicKey)?;
+            let (signature, recovery_id) = host_recovery_params(&signature, meta);
+            Ok(VerifyingKey::recover_from_prehash(msg_hash, &signature, recovery_id)
+                .is_ok_and(|recovered| recovered == verifying_key))
+        }
+    }
+}
+
+/// Recovers the public keys for both nonce point parities of a signature, returning each one
+/// with whether it was recovered and verified. Unrecovered candidates are all zeros.
+///
+/// This is a diagnostic API: when [`ecrecover`] unexpectedly fails or returns the wrong key,
+/// comparing the candidates tells whether the `v` value is wrong or the signature is over another
+/// digest. It costs two recoveries, and should not be used in production code paths.
+///
+/// Returns an error if the signature scalars are invalid.
+pub fn ecrecover_candidates(
+    sig64: &[u8; 64],
+    msg_hash: &[u8; 32],
+) -> Result<[([u8; 65], bool); 2], Error> {
+    Signature::from_slice(sig64).map_err(|_| Error::InvalidSignature)?;
+
+    let candidate = |parity| {
+        // A chain id of `None` cannot overflow.
+        let meta = RecoveryMeta::new(parity, None).unwrap();
+        match ecrecover_with_meta(sig64, meta, msg_hash) {
+            Ok(pubkey) => (pubkey, true),
+            Err(_) => ([0u8; 65], false),
+        }
+    };
+    Ok([candidate(false), candidate(true)])
+}
+
+/// Recovers the public key that signed each message with the signature at the same index, like
+/// calling [`ecrecover`] on every pair, and returns the result of each recovery.
+///
+/// Inside the zkVM, the responses to each request to the ecrecover hook are read before the next
+/// request is sent. The executor inserts the responses of a hook in front of the unread entries of
+/// the input stream, so the responses to requests sent together would be read in reverse order,
+/// and execution would fail with `HintOutOfOrder`.
+///
+/// Returns [`Error::SignatureCountMismatch`] if the numbers of messages and signatures differ.
+#[cfg(feature = "alloc")]
+pub fn ecrecover_batch(
+    msg_hashes: &[[u8; 32]],
+    sigs: &[[u8; 65]],
+) -> Result<Vec<Result<[u8; 65], Error>>, Error> {
+    if msg_hashes.len() != sigs.len() {
+        return Err(Error::SignatureCountMismatch {
+            messages: msg_hashes.len(),
+            signatures: sigs.len(),
+        });
+    }
+
+    Ok(msg_hashes
+        .iter()
+        .zip(sigs)
+        .map(|(msg_hash, sig)| ecrecover(sig, msg_hash))
+        .collect())
+}
+
+fn ecrecover_with_meta(
+    sig64: &[u8; 64],
+    meta: RecoveryMeta,
+    msg_hash: &[u8; 32],
+) -> Result<[u8; 65], Error> {
+    // Parse the signature before querying the hook, so that out of range scalars are rejected.
+    let signature = Signature::from_slice(sig64).map_err(|_| Error::InvalidSignature)?;
+
+    cfg_if::cfg_if! {
+        if #[cfg(not(all(
+            target_os = "zkvm",
+            target_vendor = "succinct",
+            feature = "alloc",
+            not(feature = "no-hints")
+        )))] {
+            recover_constrained(&signature, meta, msg_hash)
+        } else {
+            // The hook expects the raw recovery id as the last byte.
+            let mut sig = [0u8; 65];
+            sig[..64].copy_from_slice(sig64);
+            sig[64] = meta.recovery_id();
+
+            let (pubkey, s_inv) = unconstrained_ecrecover(&sig, msg_hash)?;
+            verify_recovered(&pubkey, &signature, meta, s_inv, msg_hash)
+        }
+    }
+}
+
+/// Checks that the compressed public key and the inverse of s returned by the hook recover to the
+/// signature's nonce point, and returns the uncompressed encoding of the key.
+///
+/// Both public keys whose nonce points share the x-coordinate `r` verify the signature, so the
+/// y-parity of the nonce point is also checked against the one encoded in `v`.
+#[cfg(all(
+    target_os = "zkvm",
+    target_vendor = "succinct",
+    feature = "alloc",
+    not(feature = "no-hints")
+))]
+fn verify_recovered(
+    compressed: &[u8; 33],
+    signature: &Signature,
+    meta: RecoveryMeta,
+    s_inv: Scalar,
+    msg_hash: &[u8; 32],
+) -> Result<[u8; 65], Error> {
+    // The hook normalizes high s values, in which case it returns the inverse of -s.
+    let s_inv = if signature.normalize_s().is_some() {
+        -s_inv
+    } else {
+        s_inv
+    };
+    check_s_inverse(signature, &s_inv).map_err(|_| Error::BadHint {
+        fd: FD_ECRECOVER_HOOK,
+    })?;
+
+    // The prefix has been checked by `unconstrained_ecrecover`. The precompile constrains
+    // the result to be on the curve, so the coordinates are used as is.
+    let is_odd = compressed[0] == 3;
+    let mut coordinates = [0u8; 64];
+    coordinates[..32].copy_from_slice(&compressed[1..]);
+    unsafe {
+        syscall_secp256k1_decompress(&mut coordinates, is_odd);
+    }
+    if (coordinates[63] & 1 == 1) != is_odd {
+        return Err(Error::BadHint {
+            fd: FD_ECRECOVER_HOOK,
+        });
+    }
+
+    let affine = Secp256k1AffinePoint::from_be_coordinates(
+        coordinates[..32].try_into().unwrap(),
+        coordinates[32..].try_into().unwrap(),
     );
-    if verified {
-        Ok(pubkey)
+    let nonce = nonce_point(&affine, msg_hash, signature, Some(&s_inv))?
+        .ok_or(Error::VerificationFailed)?;
+
+    // Only recovery ids without the overflow bit are supported, so the x-coordinate of the
+    // nonce point is r itself rather than r + n.
+    let (r, _) = signature.split_scalars();
+    let nonce_y = nonce.y_be();
+    if nonce.x_be() != <[u8; 32]>::from(r.to_bytes()) || (nonce_y[31] & 1 == 1) != meta.parity() {
+        return Err(Error::VerificationFailed);
+    }
+
+    let mut pubkey = [0u8; 65];
+    pubkey[0] = 4;
+    pubkey[1..].copy_from_slice(&coordinates);
+    Ok(pubkey)
+}
+
+/// Recovers the public key without consuming any hook data, by computing `r^-1 * (s * R - z * G)`
+/// in the VM, where `R` is the nonce point. Outside of the VM, the key is recovered with k256.
+#[cfg(not(all(
+    target_os = "zkvm",
+    target_vendor = "succinct",
+    feature = "alloc",
+    not(feature = "no-hints")
+)))]
+fn recover_constrained(
+    signature: &Signature,
+    meta: RecoveryMeta,
+    msg_hash: &[u8; 32],
+) -> Result<[u8; 65], Error> {
+    cfg_if::cfg_if! {
+        if #[cfg(all(target_os = "zkvm", target_vendor = "succinct"))] {
+            let (r, s) = signature.split_scalars();
+            let nonce_point = lift_r(&r, meta.parity())?;
+
+            let z = <Scalar as Reduce<U256>>::reduce_bytes(FieldBytes::from_slice(msg_hash));
+            let r_inv = r.invert();
+            let u1 = -(z * r_inv.as_ref());
+            let u2 = *s * r_inv.as_ref();
+
+            let pubkey = double_and_add_base(&u1, &consts::GENERATOR, &u2, &nonce_point)
+                .ok_or(Error::VerificationFailed)?;
+
+            let mut result = [0u8; 65];
+            result[0] = 4;
+            result[1..33].copy_from_slice(&pubkey.x_be());
+            result[33..].copy_from_slice(&pubkey.y_be());
+            Ok(result)
+        } else {
+            let (signature, recovery_id) = host_recovery_params(signature, meta);
+            let verifying_key = VerifyingKey::recover_from_prehash(msg_hash, &signature, recovery_id)
+                .map_err(|_| Error::VerificationFailed)?;
+            let mut result = [0u8; 65];
+            result.copy_from_slice(verifying_key.to_encoded_point(false).as_bytes());
+            Ok(result)
+        }
+    }
+}
+
+/// Returns the nonce point of a signature, whose x-coordinate is r, as recovery ids for x >= n are
+/// not supported, and whose y-coordinate has the given parity.
+///
+/// Returns [`Error::VerificationFailed`] if no point has the x-coordinate r. That is proven with
+/// the square root hook, like in [`xonly::XOnlyPublicKey::lift_x`], as the decompression
+/// precompile would make the execution fail instead.
+fn lift_r(r: &Scalar, parity: bool) -> Result<Secp256k1AffinePoint, Error> {
+    // r is below n, and so below p.
+    let x = FieldElement::from_repr(r.to_bytes()).unwrap();
+    let y = xonly::sqrt(&(x.square() * x + FieldElement::from(7u64)))
+        .ok_or(Error::VerificationFailed)?;
+    let y = if bool::from(y.is_odd()) == parity {
+        y
     } else {
-        Err(anyhow!("failed to verify signature"))
+        (-y).normalize()
+    };
+    Ok(Secp256k1AffinePoint::from_be_coordinates(
+        r.to_bytes().as_slice().try_into().unwrap(),
+        y.to_bytes().as_slice().try_into().unwrap(),
+    ))
+}
+
+/// Returns the signature and recovery id to recover with k256, which rejects high s values.
+/// Negating s negates the nonce point, which flips the parity of its y-coordinate.
+#[cfg(not(all(target_os = "zkvm", target_vendor = "succinct")))]
+fn host_recovery_params(signature: &Signature, meta: RecoveryMeta) -> (Signature, RecoveryId) {
+    let recovery_id = RecoveryId::from_byte(meta.recovery_id()).unwrap();
+    match signature.normalize_s() {
+        Some(normalized) => (
+            normalized,
+            RecoveryId::from_byte(recovery_id.to_byte() ^ 1).unwrap(),
+        ),
+        None => (*signature, recovery_id),
+    }
+}
+
+/// The information carried by the `v` value of a signature: the parity of the y-coordinate of
+/// the nonce point, and the chain id for EIP-155 signatures.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RecoveryMeta {
+    parity: bool,
+    chain_id: Option<u64>,
+}
+
+impl RecoveryMeta {
+    /// The largest chain id whose EIP-155 `v` value fits in a `u64`.
+    pub const MAX_CHAIN_ID: u64 = (u64::MAX - 36) / 2;
+
+    /// Creates a new [`RecoveryMeta`], checking that the chain id can be encoded in a `v` value.
+    pub fn new(parity: bool, chain_id: Option<u64>) -> Result<Self, Error> {
+        if let Some(chain_id) = chain_id.filter(|&chain_id| chain_id > Self::MAX_CHAIN_ID) {
+            return Err(Error::ChainIdOverflow(chain_id));
+        }
+        Ok(Self { parity, chain_id })
+    }
+
+    /// Parses a `v` value, which is either a typed-transaction `yParity` (0 or 1), a pre-EIP-155
+    /// value (27 or 28), or an EIP-155 value (`chain_id * 2 + 35` or `chain_id * 2 + 36`).
+    ///
+    /// Returns [`Error::ChainIdOverflow`] like [`RecoveryMeta::new`] if the chain id is above
+    /// [`RecoveryMeta::MAX_CHAIN_ID`], so that every parsed value can be encoded again.
+    pub fn from_v(v: u64) -> Result<Self, Error> {
+        match v {
+            0 | 1 => Ok(Self {
+                parity: v == 1,
+                chain_id: None,
+            }),
+            27 | 28 => Ok(Self {
+                parity: v == 28,
+                chain_id: None,
+            }),
+            35.. => Self::new((v - 35) % 2 == 1, Some((v - 35) / 2)),
+            _ => Err(Error::InvalidRecoveryId(v)),
+        }
+    }
+
+    /// Encodes the `v` value. If `legacy` is set, returns the EIP-155 value if there is a chain id
+    /// and the pre-EIP-155 value otherwise. If not, returns the `yParity` value.
+    pub fn to_v(&self, legacy: bool) -> u64 {
+        let parity = self.parity as u64;
+        match (legacy, self.chain_id) {
+            (false, _) => parity,
+            (true, None) => 27 + parity,
+            (true, Some(chain_id)) => chain_id * 2 + 35 + parity,
+        }
+    }
+
+    /// Whether the y-coordinate of the nonce point is odd.
+    pub fn parity(&self) -> bool {
+        self.parity
+    }
+
+    /// The chain id, for EIP-155 signatures.
+    pub fn chain_id(&self) -> Option<u64> {
+        self.chain_id
+    }
+
+    /// The recovery id, i.e. the `yParity` value as a byte.
+    pub fn recovery_id(&self) -> u8 {
+        self.parity as u8
+    }
+}
+
+/// Normalizes a signature `r || s || v` to a low s value, as required by EIP-2: if `s > n / 2`,
+/// replaces s with `n - s` and flips the parity encoded in `v`, which recovers the same key.
+///
+/// `v` keeps its encoding, which may be any of those accepted by [`RecoveryMeta::from_v`]. Returns
+/// whether the signature was changed.
+///
+/// Returns [`Error::InvalidSignature`] if r or s is zero or not below the curve order, and
+/// [`Error::InvalidRecoveryId`] if `v` is invalid or if the flipped value does not fit in a byte,
+/// which only happens for the EIP-155 value 255. The signature is unchanged on error.
+pub fn normalize_signature(sig: &mut [u8; 65]) -> Result<bool, Error> {
+    let v = sig[64];
+    let meta = RecoveryMeta::from_v(v as u64)?;
+    let signature = Signature::from_slice(&sig[..64]).map_err(|_| Error::InvalidSignature)?;
+    let Some(normalized) = signature.normalize_s() else {
+        return Ok(false);
+    };
+
+    // The chain id of a `v` value that fits in a byte cannot overflow.
+    let flipped = RecoveryMeta::new(!meta.parity(), meta.chain_id()).unwrap();
+    let flipped_v =
+        u8::try_from(flipped.to_v(v >= 27)).map_err(|_| Error::InvalidRecoveryId(v as u64))?;
+    sig[..64].copy_from_slice(&normalized.to_bytes());
+    sig[64] = flipped_v;
+    Ok(true)
+}
+
+/// Returns the signature normalized to a low s value, like [`normalize_signature`].
+pub fn normalized_signature(sig: &[u8; 65]) -> Result<[u8; 65], Error> {
+    let mut normalized = *sig;
+    normalize_signature(&mut normalized)?;
+    Ok(normalized)
+}
+
+/// Returns the Ethereum address of an uncompressed public key, i.e. the last 20 bytes of the
+/// Keccak-256 hash of its coordinates.
+pub fn pubkey_to_address(pubkey: &[u8; 65]) -> [u8; 20] {
+    let hash = keccak256(&pubkey[1..]);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    address
+}
+
+/// Given a signature and a message hash, returns the Ethereum address of the signer.
+pub fn ecrecover_address(sig: &[u8; 65], msg_hash: &[u8; 32]) -> Result<[u8; 20], Error> {
+    let pubkey = ecrecover(sig, msg_hash)?;
+    Ok(pubkey_to_address(&pubkey))
+}
+
+/// Returns the address of a contract deployed with `CREATE`, i.e. the last 20 bytes of the
+/// Keccak-256 hash of the RLP encoding of `[sender, nonce]`.
+pub fn create_address(sender: [u8; 20], nonce: u64) -> [u8; 20] {
+    // The list is at most 1 + 21 + 9 bytes long, so its length fits in the prefix byte.
+    let mut rlp = [0u8; 31];
+    rlp[1] = 0x80 + 20;
+    rlp[2..22].copy_from_slice(&sender);
+    let mut len = 22;
+
+    // Integers are encoded as their minimal big endian bytes, and zero as the empty string. Bytes
+    // below 0x80 are their own encoding.
+    let nonce_bytes = nonce.to_be_bytes();
+    let nonce_bytes = &nonce_bytes[nonce.leading_zeros() as usize / 8..];
+    if !matches!(nonce_bytes, [byte] if *byte < 0x80) {
+        rlp[len] = 0x80 + nonce_bytes.len() as u8;
+        len += 1;
+    }
+    rlp[len..len + nonce_bytes.len()].copy_from_slice(nonce_bytes);
+    len += nonce_bytes.len();
+    rlp[0] = 0xc0 + (len - 1) as u8;
+
+    let hash = keccak256(&rlp[..len]);
+    hash[12..].try_into().unwrap()
+}
+
+/// Returns the address of a contract deployed with `CREATE2`, i.e. the last 20 bytes of
+/// `keccak256(0xff || sender || salt || init_code_hash)`.
+pub fn create2_address(sender: [u8; 20], salt: [u8; 32], init_code_hash: [u8; 32]) -> [u8; 20] {
+    let mut preimage = [0u8; 1 + 20 + 32 + 32];
+    preimage[0] = 0xff;
+    preimage[1..21].copy_from_slice(&sender);
+    preimage[21..53].copy_from_slice(&salt);
+    preimage[53..].copy_from_slice(&init_code_hash);
+
+    let hash = keccak256(&preimage);
+    hash[12..].try_into().unwrap()
+}
+
+/// Returns the address of a contract deployed with `CREATE2`, like [`create2_address`], hashing
+/// the init code.
+pub fn create2_address_from_code(sender: [u8; 20], salt: [u8; 32], init_code: &[u8]) -> [u8; 20] {
+    create2_address(sender, salt, keccak256(init_code))
+}
+
+/// Implements the semantics of the Ethereum `ecrecover` precompile at address `0x01`.
+///
+/// The input is `hash || v || r || s`, each 32 bytes. Like the EVM, shorter inputs are padded
+/// with zeros and extra bytes are ignored. `v` must be 27 or 28, and `r` and `s` must be nonzero
+/// and below the curve order; high `s` values are accepted.
+///
+/// Returns the signer's address left-padded to 32 bytes, or `None` where the precompile returns
+/// empty output.
+pub fn evm_ecrecover(input: &[u8]) -> Option<[u8; 32]> {
+    let mut padded = [0u8; 128];
+    let len = input.len().min(128);
+    padded[..len].copy_from_slice(&input[..len]);
+
+    let (msg_hash, rest) = padded.split_at(32);
+    let (v, sig64) = rest.split_at(32);
+    if v[..31].iter().any(|&byte| byte != 0) || !matches!(v[31], 27 | 28) {
+        return None;
+    }
+
+    let meta = RecoveryMeta::from_v(v[31] as u64).ok()?;
+    let pubkey = ecrecover_with_meta(
+        sig64.try_into().unwrap(),
+        meta,
+        msg_hash.try_into().unwrap(),
+    )
+    .ok()?;
+
+    let mut output = [0u8; 32];
+    output[12..].copy_from_slice(&pubkey_to_address(&pubkey));
+    Some(output)
+}
+
+/// The hash function used to compute the digest of a signed message.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HashAlg {
+    /// SHA-256, as used by most ECDSA signatures outside of Ethereum.
+    Sha256,
+    /// Keccak-256, as used by Ethereum.
+    Keccak256,
+}
+
+/// Hashes a signed message incrementally, so that large messages can be verified without holding
+/// them in memory at once.
+///
+/// The digest does not depend on how the message is split into chunks. Requires the `alloc`
+/// feature, as the Keccak-256 state is boxed.
+#[cfg(feature = "alloc")]
+#[derive(Clone)]
+pub struct SigningMessageHasher {
+    hasher: MessageHasher,
+}
+
+/// The Keccak-256 state is boxed, as it is three times as large as the SHA-256 one.
+#[cfg(feature = "alloc")]
+#[derive(Clone)]
+enum MessageHasher {
+    Sha256(Sha256),
+    Keccak256(alloc::boxed::Box<Keccak256>),
+}
+
+#[cfg(feature = "alloc")]
+impl SigningMessageHasher {
+    /// Creates a hasher for a message hashed with `alg`.
+    pub fn new(alg: HashAlg) -> Self {
+        let hasher = match alg {
+            HashAlg::Sha256 => MessageHasher::Sha256(Sha256::new()),
+            HashAlg::Keccak256 => MessageHasher::Keccak256(Keccak256::new().into()),
+        };
+        Self { hasher }
+    }
+
+    /// Absorbs the next chunk of the message.
+    pub fn update(&mut self, chunk: &[u8]) {
+        match &mut self.hasher {
+            MessageHasher::Sha256(hasher) => hasher.update(chunk),
+            MessageHasher::Keccak256(hasher) => hasher.update(chunk),
+        }
+    }
+
+    /// Returns the digest of the message.
+    pub fn finalize(self) -> [u8; 32] {
+        match self.hasher {
+            MessageHasher::Sha256(hasher) => hasher.finalize(),
+            MessageHasher::Keccak256(hasher) => hasher.finalize(),
+        }
+    }
+
+    /// Verifies `signature` over the message against `pubkey`, like [`verify_signature`].
+    pub fn finalize_and_verify(self, pubkey: &[u8; 65], signature: &Signature) -> bool {
+        verify_signature(pubkey, &self.finalize(), signature, None)
+    }
+
+    /// Recovers the public key that signed the message, like [`ecrecover`].
+    pub fn finalize_and_ecrecover(self, sig: &[u8; 65]) -> Result<[u8; 65], Error> {
+        ecrecover(sig, &self.finalize())
+    }
+}
+
+/// Options for the threshold signature verification functions.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ThresholdOptions {
+    /// Return an error for a malformed signature, or one from an address outside of the authorized
+    /// set, instead of ignoring it.
+    pub reject_unauthorized: bool,
+    /// The authorized set is sorted in ascending order, so membership is checked with a binary
+    /// search instead of a linear scan. Debug builds check that it is.
+    pub authorized_sorted: bool,
+}
+
+/// Verifies that at least `threshold` distinct addresses from `authorized` signed `msg_hash`.
+///
+/// A signer appearing several times in `signatures` is only counted once, and malformed
+/// signatures and signatures from addresses outside of `authorized` are ignored. All the
+/// signatures are recovered with [`ecrecover_batch`], even those after the threshold is reached.
+/// Returns an error if the ecrecover hook misbehaves.
+#[cfg(feature = "alloc")]
+pub fn verify_threshold_signatures(
+    msg_hash: &[u8; 32],
+    signatures: &[[u8; 65]],
+    authorized: &[[u8; 20]],
+    threshold: usize,
+) -> Result<bool, Error> {
+    verify_threshold_signatures_with(
+        msg_hash,
+        signatures,
+        authorized,
+        threshold,
+        ThresholdOptions::default(),
+    )
+}
+
+/// Same as [`verify_threshold_signatures`], with the given options.
+#[cfg(feature = "alloc")]
+pub fn verify_threshold_signatures_with(
+    msg_hash: &[u8; 32],
+    signatures: &[[u8; 65]],
+    authorized: &[[u8; 20]],
+    threshold: usize,
+    options: ThresholdOptions,
+) -> Result<bool, Error> {
+    let signers = collect_signers(msg_hash, signatures, authorized, Some(threshold), options)?;
+    Ok(signers.len() >= threshold)
+}
+
+/// Returns the distinct authorized addresses that signed `msg_hash`, in order of first
+/// appearance in `signatures`.
+#[cfg(feature = "alloc")]
+pub fn recover_threshold_signers(
+    msg_hash: &[u8; 32],
+    signatures: &[[u8; 65]],
+    authorized: &[[u8; 20]],
+    options: ThresholdOptions,
+) -> Result<Vec<[u8; 20]>, Error> {
+    collect_signers(msg_hash, signatures, authorized, None, options)
+}
+
+#[cfg(feature = "alloc")]
+fn collect_signers(
+    msg_hash: &[u8; 32],
+    signatures: &[[u8; 65]],
+    authorized: &[[u8; 20]],
+    stop_at: Option<usize>,
+    options: ThresholdOptions,
+) -> Result<Vec<[u8; 20]>, Error> {
+    if options.authorized_sorted {
+        debug_assert!(
+            authorized.windows(2).all(|pair| pair[0] < pair[1]),
+            "the authorized set is not sorted in ascending order"
+        );
+    }
+
+    let msg_hashes = alloc::vec![*msg_hash; signatures.len()];
+    let recovered = ecrecover_batch(&msg_hashes, signatures)?;
+
+    let mut signers = Vec::new();
+    // The signers seen so far, kept sorted to detect duplicates.
+    let mut seen: Vec<[u8; 20]> = Vec::new();
+
+    for (i, pubkey) in recovered.into_iter().enumerate() {
+        if stop_at.map_or(false, |threshold| signers.len() >= threshold) {
+            break;
+        }
+
+        let address = match pubkey {
+            Ok(pubkey) => pubkey_to_address(&pubkey),
+            Err(
+                Error::InvalidSignature
+                | Error::InvalidRecoveryId(_)
+                | Error::ChainIdOverflow(_)
+                | Error::VerificationFailed,
+            ) if !options.reject_unauthorized => continue,
+            Err(err) => return Err(err),
+        };
+
+        let is_authorized = if options.authorized_sorted {
+            authorized.binary_search(&address).is_ok()
+        } else {
+            authorized.contains(&address)
+        };
+        if !is_authorized {
+            if options.reject_unauthorized {
+                return Err(Error::UnauthorizedSigner { index: i });
+            }
+            continue;
+        }
+
+        if let Err(pos) = seen.binary_search(&address) {
+            seen.insert(pos, address);
+            signers.push(address);
+        }
+    }
+
+    Ok(signers)
+}
+
+/// Returns the root of a Merkle tree over the distinct addresses that signed each message with
+/// the signature at the same index, so that a guest can commit to a large signer set at once.
+///
+/// The addresses are sorted in ascending order, and the leaves are `keccak256(address)`, the
+/// hash of `abi.encodePacked(address)`. Each parent is the hash of its sorted children, as in the
+/// `MerkleProof` library of OpenZeppelin and in merkletreejs with `sortPairs`, and the last node
+/// of a level with an odd number of nodes is carried up to the next level unchanged. The root of
+/// a single signer is its leaf, and the root of no signers is all zeros, which no proof opens.
+///
+/// Returns [`Error::SignatureCountMismatch`] if the numbers of messages and signatures differ,
+/// and the recovery error of the first malformed signature.
+#[cfg(feature = "alloc")]
+pub fn commit_signers_merkle_root(
+    msg_hashes: &[[u8; 32]],
+    sigs: &[[u8; 65]],
+) -> Result<[u8; 32], Error> {
+    let mut signers = ecrecover_batch(msg_hashes, sigs)?
+        .into_iter()
+        .map(|pubkey| pubkey.map(|pubkey| pubkey_to_address(&pubkey)))
+        .collect::<Result<Vec<_>, _>>()?;
+    signers.sort_unstable();
+    signers.dedup();
+    Ok(signers_merkle_root(&signers))
+}
+
+/// Returns the root of the Merkle tree of [`commit_signers_merkle_root`] over `signers`, which
+/// must be sorted and distinct.
+#[cfg(feature = "alloc")]
+pub fn signers_merkle_root(signers: &[[u8; 20]]) -> [u8; 32] {
+    let mut level = signers
+        .iter()
+        .map(|signer| keccak256(signer))
+        .collect::<Vec<_>>();
+    if level.is_empty() {
+        return [0; 32];
+    }
+    while level.len() > 1 {
+        level = level.chunks(2).map(merkle_parent).collect();
+    }
+    level[0]
+}
+
+/// Returns the proof that `signer` is a leaf of the Merkle tree of [`signers_merkle_root`], as
+/// the siblings from the leaf to the root, or `None` if it is not one of the signers.
+#[cfg(feature = "alloc")]
+pub fn signers_merkle_proof(signers: &[[u8; 20]], signer: &[u8; 20]) -> Option<Vec<[u8; 32]>> {
+    let mut index = signers.iter().position(|s| s == signer)?;
+    let mut level = signers
+        .iter()
+        .map(|signer| keccak256(signer))
+        .collect::<Vec<_>>();
+    let mut proof = Vec::new();
+    while level.len() > 1 {
+        // A node carried up unchanged has no sibling on its level.
+        if let Some(sibling) = level.get(index ^ 1) {
+            proof.push(*sibling);
+        }
+        level = level.chunks(2).map(merkle_parent).collect();
+        index /= 2;
+    }
+    Some(proof)
+}
+
+/// Checks that `signer` is a leaf of the Merkle tree with the given root, like `MerkleProof.verify`
+/// in OpenZeppelin.
+pub fn verify_signer_merkle_proof(root: &[u8; 32], signer: &[u8; 20], proof: &[[u8; 32]]) -> bool {
+    let leaf = proof.iter().fold(keccak256(signer), |node, sibling| {
+        merkle_parent(&[node, *sibling])
+    });
+    leaf == *root
+}
+
+/// Hashes two sibling nodes in ascending order, or returns a node without a sibling as is.
+fn merkle_parent(nodes: &[[u8; 32]]) -> [u8; 32] {
+    match nodes {
+        [a, b] => {
+            let mut preimage = [0u8; 64];
+            let (low, high) = if a <= b { (a, b) } else { (b, a) };
+            preimage[..32].copy_from_slice(low);
+            preimage[32..].copy_from_slice(high);
+            keccak256(&preimage)
+        }
+        [node] => *node,
+        _ => unreachable!("nodes are hashed in pairs"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recovery_meta_round_trips() {
+        let max_v = RecoveryMeta::MAX_CHAIN_ID * 2 + 36;
+        for (v, parity, chain_id, legacy) in [
+            (0, false, None, false),
+            (1, true, None, false),
+            (27, false, None, true),
+            (28, true, None, true),
+            (35, false, Some(0), true),
+            (36, true, Some(0), true),
+            (37, false, Some(1), true),
+            (max_v - 1, false, Some(RecoveryMeta::MAX_CHAIN_ID), true),
+            (max_v, true, Some(RecoveryMeta::MAX_CHAIN_ID), true),
+        ] {
+            let meta = RecoveryMeta::from_v(v).unwrap();
+            assert_eq!(
+                meta,
+                RecoveryMeta::new(parity, chain_id).unwrap(),
+                "v = {v}"
+            );
+            assert_eq!(meta.to_v(legacy), v);
+        }
+        assert_eq!(max_v, u64::MAX - 1);
+
+        for v in [2, 26, 29, 34] {
+            assert_eq!(RecoveryMeta::from_v(v), Err(Error::InvalidRecoveryId(v)));
+        }
+        assert_eq!(
+            RecoveryMeta::from_v(u64::MAX),
+            Err(Error::ChainIdOverflow(RecoveryMeta::MAX_CHAIN_ID + 1))
+        );
+        assert_eq!(
+            RecoveryMeta::new(false, Some(RecoveryMeta::MAX_CHAIN_ID + 1)),
+            Err(Error::ChainIdOverflow(RecoveryMeta::MAX_CHAIN_ID + 1))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_contract_addresses() {
+        use crate::secp256k1::{create2_address, create2_address_from_code, create_address};
+        use alloy_primitives::{Address, B256};
+
+        let bytes = |hex_str: &str| hex::decode(hex_str).unwrap();
+        let address = |hex_str: &str| -> [u8; 20] { bytes(hex_str).try_into().unwrap() };
+        let word = |hex_str: &str| -> [u8; 32] { bytes(hex_str).try_into().unwrap() };
+
+        let sender = address("6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0");
+        let created = [
+            "cd234a471b72ba2f1ccf0a70fcaba648a5eecd8d",
+            "343c43a37d37dff08ae8c4a11544c718abb4fcf8",
+            "f778b86fa74e846c4f0a1fbd1335fe81c00a0c91",
+            "fffd933a0bc612844eaf0c6fe3e5b8e9b6c1d19c",
+        ];
+        for (nonce, expected) in created.iter().enumerate() {
+            assert_eq!(create_address(sender, nonce as u64), address(expected));
+        }
+        let nonces = [
+            0x7f,
+            0x80,
+            0xff,
+            0x100,
+            0x1234567,
+            u32::MAX as u64,
+            u64::MAX,
+        ];
+        for nonce in (0..300).chain(nonces) {
+            let expected = Address::from(sender).create(nonce);
+            assert_eq!(
+                create_address(sender, nonce),
+                expected.0 .0,
+                "nonce {nonce}"
+            );
+        }
+
+        // The examples of EIP-1014, as (sender, salt, init code, address).
+        let zero_salt = [0u8; 32];
+        let create2_examples = [
+            (
+                [0u8; 20],
+                zero_salt,
+                bytes("00"),
+                "4d1a2e2bb4f88f0250f26ffff098b0b30b26bf38",
+            ),
+            (
+                address("deadbeef00000000000000000000000000000000"),
+                zero_salt,
+                bytes("00"),
+                "b928f69bb1d91cd65274e3c79d8986362984fda3",
+            ),
+            (
+                address("00000000000000000000000000000000deadbeef"),
+                word("00000000000000000000000000000000000000000000000000000000cafebabe"),
+                bytes("deadbeef"),
+                "60f3f640a8508fc6a86d45df051962668e1e8ac7",
+            ),
+            (
+                [0u8; 20],
+                zero_salt,
+                Vec::new(),
+                "e33c0c7f7df4809055c3eba6c09cfe4baf1bd9e0",
+            ),
+        ];
+        for (sender, salt, init_code, expected) in create2_examples {
+            assert_eq!(
+                create2_address_from_code(sender, salt, &init_code),
+                address(expected)
+            );
+            let expected = Address::from(sender).create2_from_code(B256::from(salt), &init_code);
+            assert_eq!(
+                create2_address_from_code(sender, salt, &init_code),
+                expected.0 .0
+            );
+        }
+
+        // The Uniswap V2 USDC/WETH pair, whose salt is the hash of its two tokens.
+        let factory = address("5c69bee701ef814a2b6a3edd4b1652cb9cc5aa6f");
+        let tokens = bytes(concat!(
+            "a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+            "c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"
+        ));
+        let salt = crate::keccak::keccak256(&tokens);
+        let init_code_hash =
+            word("96e8ac4277198ff8b6f785478aa9a39f403cb768dd02cbee326c3e7da348845f");
+        assert_eq!(
+            create2_address(factory, salt, init_code_hash),
+            address("b4e16d0168e52d35cacd2c6185b44281ec28c9dc")
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_commit_signers_merkle_root() {
+        use crate::error::Error;
+        use crate::keccak::keccak256;
+        use crate::secp256k1::{
+            commit_signers_merkle_root, pubkey_to_address, signers_merkle_proof,
+            signers_merkle_root, verify_signer_merkle_proof,
+        };
+        use alloc::vec::Vec;
+        use k256::ecdsa::SigningKey;
+
+        // The roots built by merkletreejs with `sortPairs` over the keccak256 hashes of the
+        // addresses, where the third leaf is carried up to the root.
+        let addresses = [[0x11; 20], [0x22; 20], [0x33; 20]];
+        for (count, root) in [
+            (
+                1,
+                "e2c07404b8c1df4c46226425cac68c28d27a766bbddce62309f36724839b22c0",
+            ),
+            (
+                2,
+                "4beda981c9d34f2dd099131be6049a1d87676d227e63f4a409ee629043314b4f",
+            ),
+            (
+                3,
+                "cbf843e9efe7be41ca4d3a03347d27e7bb96d83ae75b3b36983ad907d2109c65",
+            ),
+        ] {
+            assert_eq!(hex::encode(signers_merkle_root(&addresses[..count])), root);
+        }
+        assert_eq!(signers_merkle_root(&[]), [0; 32]);
+
+        let keys = (1..=5u8)
+            .map(|i| SigningKey::from_slice(&[i; 32]).unwrap())
+            .collect::<Vec<_>>();
+        let mut signers = keys
+            .iter()
+            .map(|key| {
+                pubkey_to_address(
+                    &key.verifying_key()
+                        .to_encoded_point(false)
+                        .as_bytes()
+                        .try_into()
+                        .unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        signers.sort_unstable();
+
+        // Every key signs its own message, and the first two sign a second one.
+        let mut msg_hashes = Vec::new();
+        let mut sigs = Vec::new();
+        for (i, key) in keys.iter().chain(&keys[..2]).enumerate() {
+            let msg_hash = keccak256(&[i as u8]);
+            let (sig, recid) = key.sign_prehash_recoverable(&msg_hash).unwrap();
+            let mut sig65 = [0u8; 65];
+            sig65[..64].copy_from_slice(&sig.to_bytes());
+            sig65[64] = recid.to_byte();
+            msg_hashes.push(msg_hash);
+            sigs.push(sig65);
+        }
+
+        let root = commit_signers_merkle_root(&msg_hashes, &sigs).unwrap();
+        assert_eq!(root, signers_merkle_root(&signers));
+        for signer in &signers {
+            let proof = signers_merkle_proof(&signers, signer).unwrap();
+            assert!(verify_signer_merkle_proof(&root, signer, &proof));
+            assert!(!verify_signer_merkle_proof(&root, &[0x11; 20], &proof));
+        }
+        assert_eq!(signers_merkle_proof(&signers, &[0x11; 20]), None);
+        assert_eq!(
+            signers_merkle_proof(&signers[..1], &signers[0]),
+            Some(Vec::new())
+        );
+
+        assert_eq!(commit_signers_merkle_root(&[], &[]), Ok([0; 32]));
+        assert_eq!(
+            commit_signers_merkle_root(&msg_hashes[1..], &sigs),
+            Err(Error::SignatureCountMismatch {
+                messages: 6,
+                signatures: 7,
+            })
+        );
+        sigs[3][64] = 4;
+        assert_eq!(
+            commit_signers_merkle_root(&msg_hashes, &sigs),
+            Err(Error::InvalidRecoveryId(4))
+        );
+    }
+
+    #[test]
+    fn test_lift_r_rejects_non_residues() {
+        use k256::elliptic_curve::point::{AffineCoordinates, DecompressPoint};
+        use k256::elliptic_curve::subtle::Choice;
+
+        // r^3 + 7 is not a square for these values of r, so no point has them as x-coordinate.
+        for r in [5u64, 7, 9] {
+            for parity in [false, true] {
+                assert_eq!(
+                    lift_r(&Scalar::from(r), parity),
+                    Err(Error::VerificationFailed),
+                    "r = {r}"
+                );
+            }
+            let mut sig = [0u8; 65];
+            sig[31] = r as u8;
+            sig[63] = 1;
+            for v in [27, 28] {
+                sig[64] = v;
+                assert_eq!(ecrecover(&sig, &[0x42; 32]), Err(Error::VerificationFailed));
+            }
+        }
+
+        // The x-coordinates of multiples of the generator are below n.
+        for k in [1u64, 2, 3, 0xdead_beef] {
+            let point = (k256::ProjectivePoint::GENERATOR * Scalar::from(k)).to_affine();
+            let r = Scalar::from_repr(point.x()).unwrap();
+            for parity in [false, true] {
+                let expected =
+                    k256::AffinePoint::decompress(&r.to_bytes(), Choice::from(parity as u8))
+                        .unwrap();
+                assert_eq!(lift_r(&r, parity), Secp256k1AffinePoint::try_from(expected));
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "alloc", not(feature = "no-hints")))]
+    fn test_unconstrained_ecrecover_rejects_malformed_responses() {
+        use crate::io::{mock, HOOK_STATUS_INVALID_SIGNATURE, HOOK_STATUS_UNRECOVERABLE};
+        use alloc::vec::Vec;
+        use k256::ecdsa::SigningKey;
+
+        let key = SigningKey::from_slice(&[0x33; 32]).unwrap();
+        let msg_hash = [0x44; 32];
+        let (signature, recid) = key.sign_prehash_recoverable(&msg_hash).unwrap();
+        let mut sig = [0u8; 65];
+        sig[..64].copy_from_slice(&signature.to_bytes());
+        sig[64] = recid.to_byte();
+        let compressed: [u8; 33] = key
+            .verifying_key()
+            .to_encoded_point(true)
+            .as_bytes()
+            .try_into()
+            .unwrap();
+        let s_inverse = *signature.s().invert();
+
+        let recover = |responses: Vec<Vec<u8>>| {
+            mock::with_hook(
+                move |fd, request| {
+                    assert_eq!(fd, FD_ECRECOVER_HOOK);
+                    assert_eq!(request, [&sig[..], &msg_hash].concat());
+                    responses.clone()
+                },
+                || unconstrained_ecrecover(&sig, &msg_hash),
+            )
+        };
+        let malformed = |expected, actual| Error::HookResponseMalformed {
+            fd: FD_ECRECOVER_HOOK,
+            expected,
+            actual,
+        };
+
+        let key_bytes = compressed.to_vec();
+        let s_inv_bytes = s_inverse.to_bytes().to_vec();
+        assert_eq!(
+            recover(vec![key_bytes.clone(), s_inv_bytes.clone()]),
+            (Ok((compressed, s_inverse)), 0)
+        );
+
+        // Keys of the wrong length, whose inverse of s is still read, and no key at all.
+        assert_eq!(
+            recover(vec![key_bytes[..32].to_vec(), s_inv_bytes.clone()]),
+            (Err(malformed(33, 32)), 0)
+        );
+        assert_eq!(
+            recover(vec![[&key_bytes[..], &[0]].concat(), s_inv_bytes.clone()]),
+            (Err(malformed(33, 34)), 0)
+        );
+        assert_eq!(recover(vec![Vec::new()]), (Err(malformed(33, 0)), 0));
+        assert_eq!(recover(Vec::new()), (Err(malformed(33, 0)), 0));
+
+        // A key with another prefix, or an unknown status.
+        let mut uncompressed_prefix = key_bytes.clone();
+        uncompressed_prefix[0] = 4;
+        assert_eq!(
+            recover(vec![uncompressed_prefix, s_inv_bytes.clone()]).0,
+            Err(Error::HookFailed {
+                fd: FD_ECRECOVER_HOOK,
+                status: 4,
+            })
+        );
+
+        // An inverse of s of the wrong length, or out of range.
+        assert_eq!(
+            recover(vec![key_bytes.clone(), s_inv_bytes[..31].to_vec()]),
+            (Err(malformed(32, 31)), 0)
+        );
+        assert_eq!(recover(vec![key_bytes.clone()]), (Err(malformed(32, 0)), 0));
+        assert_eq!(
+            recover(vec![key_bytes.clone(), vec![0xff; 32]]),
+            (
+                Err(Error::BadHint {
+                    fd: FD_ECRECOVER_HOOK,
+                }),
+                0
+            )
+        );
+
+        // The statuses of failed requests.
+        assert_eq!(
+            recover(vec![vec![HOOK_STATUS_INVALID_SIGNATURE]]),
+            (Err(Error::InvalidSignature), 0)
+        );
+        assert_eq!(
+            recover(vec![vec![HOOK_STATUS_UNRECOVERABLE]]),
+            (Err(Error::VerificationFailed), 0)
+        );
+    }
+
+    /// A signature, its message hash, and the responses of the ecrecover hook to it.
+    #[cfg(all(feature = "alloc", not(feature = "no-hints")))]
+    type HookedSignature = ([u8; 65], [u8; 32], Vec<Vec<u8>>);
+
+    /// Signatures over distinct messages by distinct keys.
+    #[cfg(all(feature = "alloc", not(feature = "no-hints")))]
+    fn hooked_signatures(count: u8) -> Vec<HookedSignature> {
+        use k256::ecdsa::SigningKey;
+
+        (1..=count)
+            .map(|seed| {
+                let key = SigningKey::from_slice(&[seed; 32]).unwrap();
+                let msg_hash = [seed.wrapping_mul(5); 32];
+                let (signature, recid) = key.sign_prehash_recoverable(&msg_hash).unwrap();
+                let mut sig = [0u8; 65];
+                sig[..64].copy_from_slice(&signature.to_bytes());
+                sig[64] = recid.to_byte();
+                let responses = vec![
+                    key.verifying_key()
+                        .to_encoded_point(true)
+                        .as_bytes()
+                        .to_vec(),
+                    signature.s().invert().to_bytes().to_vec(),
+                ];
+                (sig, msg_hash, responses)
+            })
+            .collect()
+    }
+
+    /// Answers each request of `signatures` like the ecrecover hook, or with `responses` for the
+    /// one at `index`.
+    #[cfg(all(feature = "alloc", not(feature = "no-hints")))]
+    fn mock_ecrecover_hook(
+        signatures: Vec<HookedSignature>,
+        malformed: Option<(usize, Vec<Vec<u8>>)>,
+    ) -> impl FnMut(u32, &[u8]) -> Vec<Vec<u8>> {
+        move |fd, request| {
+            assert_eq!(fd, FD_ECRECOVER_HOOK);
+            let index = signatures
+                .iter()
+                .position(|(sig, msg_hash, _)| request == [&sig[..], msg_hash].concat())
+                .expect("unknown request");
+            match &malformed {
+                Some((malformed, responses)) if *malformed == index => responses.clone(),
+                _ => signatures[index].2.clone(),
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "alloc", not(feature = "no-hints")))]
+    fn test_unconstrained_ecrecover_reads_each_response_in_turn() {
+        use crate::io::mock;
+
+        let signatures = hooked_signatures(4);
+        let recover_all = || {
+            signatures
+                .iter()
+                .map(|(sig, msg_hash, _)| {
+                    unconstrained_ecrecover(sig, msg_hash).map(|(key, _)| key)
+                })
+                .collect::<Vec<_>>()
+        };
+        let keys = signatures
+            .iter()
+            .map(|(_, _, responses)| Ok(responses[0].as_slice().try_into().unwrap()))
+            .collect::<Vec<_>>();
+
+        let (recovered, unread) =
+            mock::with_hook(mock_ecrecover_hook(signatures.clone(), None), recover_all);
+        assert_eq!((recovered, unread), (keys.clone(), 0));
+
+        // A key of the wrong length does not shift the responses to the next requests.
+        let mut expected = keys;
+        expected[1] = Err(Error::HookResponseMalformed {
+            fd: FD_ECRECOVER_HOOK,
+            expected: 33,
+            actual: 32,
+        });
+        let short_key = vec![
+            signatures[1].2[0][..32].to_vec(),
+            signatures[1].2[1].clone(),
+        ];
+        let (recovered, unread) = mock::with_hook(
+            mock_ecrecover_hook(signatures.clone(), Some((1, short_key))),
+            recover_all,
+        );
+        assert_eq!((recovered, unread), (expected, 0));
+    }
+
+    #[test]
+    #[cfg(all(feature = "alloc", not(feature = "no-hints")))]
+    #[should_panic(expected = "read a response to hook invocation 2 before the unread ones of 0")]
+    fn test_ecrecover_requests_sent_together_are_read_out_of_order() {
+        use crate::io::mock;
+
+        // The executor inserts the responses to the last request in front of the others.
+        let signatures = hooked_signatures(3);
+        mock::with_hook(mock_ecrecover_hook(signatures.clone(), None), || {
+            for (sig, msg_hash, _) in &signatures {
+                write_ecrecover_request(sig, msg_hash);
+            }
+            signatures
+                .iter()
+                .map(|(sig, _, _)| read_ecrecover_response(sig))
+                .collect::<Vec<_>>()
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_evm_ecrecover() {
+        use k256::ecdsa::SigningKey;
+
+        let input = |hash: &[u8], v: &[u8], r: &[u8], s: &[u8]| [hash, v, r, s].concat();
+        let word = |hex_str: &str| hex::decode(hex_str).unwrap();
+        let v = |v: u8| {
+            let mut word = [0u8; 32];
+            word[31] = v;
+            word
+        };
+
+        // The vector of the `CallEcrecover0` state tests, which go-ethereum also runs.
+        let hash = word("456e9aea5e197a1f1af7a3e85a3212fa4049a3ba34c2289b4c860fc0b0c64ef3");
+        let r = word("9242685bf161793cc25603c231bc2f568eb630ea16aa137d2664ac8038825608");
+        let s = word("4f8ae3bd7535248d0bd448298cc2e2071e56992d0774dc340c368ae950852ada");
+        let expected: [u8; 32] =
+            word("0000000000000000000000007156526fbd7a3c72969b54f64e42c10fbb768c8a")
+                .try_into()
+                .unwrap();
+        let valid = input(&hash, &v(28), &r, &s);
+        assert_eq!(evm_ecrecover(&valid), Some(expected));
+
+        // Bytes past the 128 of the input are ignored, and missing ones are zeros.
+        assert_eq!(
+            evm_ecrecover(&[&valid[..], &[0xff; 32]].concat()),
+            Some(expected)
+        );
+        assert_eq!(evm_ecrecover(&valid[..96]), None);
+        assert_eq!(evm_ecrecover(&[]), None);
+
+        // Only 27 and 28 are valid values of v, with all other bytes of the word zero.
+        let n = word("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141");
+        let mut high_v = v(28);
+        high_v[0] = 1;
+        for v in [v(0), v(1), v(26), v(29), v(37), high_v] {
+            assert_eq!(evm_ecrecover(&input(&hash, &v, &r, &s)), None);
+        }
+        // r and s must be in [1, n), and high values of s are accepted.
+        let zero = [0u8; 32];
+        let mut n_minus_s = [0u8; 32];
+        n_minus_s.copy_from_slice(
+            &(-*Signature::from_slice(&[&r[..], &s].concat())
+                .unwrap()
+                .s()
+                .as_ref())
+            .to_bytes(),
+        );
+        for (r, s) in [
+            (&zero[..], &s[..]),
+            (&n, &s),
+            (&[0xff; 32], &s),
+            (&r, &zero),
+            (&r, &n),
+            (&r, &[0xff; 32]),
+        ] {
+            assert_eq!(evm_ecrecover(&input(&hash, &v(28), r, s)), None);
+        }
+        assert_eq!(
+            evm_ecrecover(&input(&hash, &v(27), &r, &n_minus_s)),
+            Some(expected)
+        );
+
+        // A zero digest is a valid one.
+        let key = SigningKey::from_slice(&[0x55; 32]).unwrap();
+        let (signature, recid) = key.sign_prehash_recoverable(&zero).unwrap();
+        let mut address = [0u8; 32];
+        address[12..].copy_from_slice(&pubkey_to_address(
+            &key.verifying_key()
+                .to_encoded_point(false)
+                .as_bytes()
+                .try_into()
+                .unwrap(),
+        ));
+        let signature = signature.to_bytes().to_vec();
+        assert_eq!(
+            evm_ecrecover(&input(
+                &zero,
+                &v(27 + recid.to_byte()),
+                &signature[..32],
+                &signature[32..]
+            )),
+            Some(address)
+        );
+    }
+
+    #[test]
+    fn test_values_above_the_order() {
+        use k256::ecdsa::SigningKey;
+        use k256::elliptic_curve::sec1::ToEncodedPoint;
+
+        /// The order of secp256k1.
+        const N: [u8; 32] = [
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xfe, 0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c,
+            0xd0, 0x36, 0x41, 0x41,
+        ];
+
+        let key = SigningKey::from_slice(&[0x77; 32]).unwrap();
+        let pubkey: [u8; 65] = key
+            .verifying_key()
+            .to_encoded_point(false)
+            .as_bytes()
+            .try_into()
+            .unwrap();
+
+        // A digest of n + 5 is reduced to 5, so a signature over either one verifies for both.
+        // Grinding such a digest from a message would take about 2^128 hashes.
+        let mut reduced = [0u8; 32];
+        reduced[31] = 5;
+        let mut digest = N;
+        digest[31] += 5;
+        let (signature, recid) = key.sign_prehash_recoverable(&reduced).unwrap();
+        let mut sig = [0u8; 65];
+        sig[..64].copy_from_slice(&signature.to_bytes());
+        sig[64] = recid.to_byte() + 27;
+        for msg_hash in [reduced, digest] {
+            assert_eq!(
+                try_verify_signature(&pubkey, &msg_hash, &signature, None),
+                Ok(true)
+            );
+            assert_eq!(ecrecover(&sig, &msg_hash), Ok(pubkey));
+            assert_eq!(verify_with_known_signer(&pubkey, &sig, &msg_hash), Ok(true));
+        }
+        let (signature, _) = key.sign_prehash_recoverable(&[0xff; 32]).unwrap();
+        assert!(verify_signature(&pubkey, &[0xff; 32], &signature, None));
+
+        // Scalars of n and above are not reduced, but rejected.
+        let mut above = N;
+        above[31] += 1;
+        for r in [N, above, [0xff; 32]] {
+            let mut sig_r = sig;
+            sig_r[..32].copy_from_slice(&r);
+            assert_eq!(ecrecover(&sig_r, &reduced), Err(Error::InvalidSignature));
+            assert_eq!(
+                verify_with_known_signer(&pubkey, &sig_r, &reduced),
+                Err(Error::InvalidSignature)
+            );
+            assert!(Signature::from_slice(&sig_r[..64]).is_err());
+
+            let mut input = [0u8; 128];
+            input[31] = 5;
+            input[63] = sig[64];
+            input[64..].copy_from_slice(&sig_r[..64]);
+            assert_eq!(evm_ecrecover(&input), None);
+            input[64..].copy_from_slice(&sig[..64]);
+            assert!(evm_ecrecover(&input).is_some());
+        }
+    }
+
+    #[test]
+    fn test_parse_sec1_pubkey_prefixes() {
+        use k256::elliptic_curve::sec1::ToEncodedPoint;
+
+        // The generator has an even y-coordinate, and its negation an odd one.
+        let generator = k256::ProjectivePoint::GENERATOR;
+        let even: [u8; 65] = generator
+            .to_affine()
+            .to_encoded_point(false)
+            .as_bytes()
+            .try_into()
+            .unwrap();
+        let odd: [u8; 65] = (-generator)
+            .to_affine()
+            .to_encoded_point(false)
+            .as_bytes()
+            .try_into()
+            .unwrap();
+        assert_eq!((even[64] & 1, odd[64] & 1), (0, 1));
+        let mut off_curve = even;
+        off_curve[64] ^= 1;
+
+        let encode = |prefix: u8, body: &[u8]| [&[prefix][..], body].concat();
+        for prefix in 0..=7u8 {
+            assert_eq!(parse_sec1_pubkey(&[prefix]).ok(), None, "prefix {prefix}");
+
+            let expected = match prefix {
+                2 => Some(even),
+                3 => Some(odd),
+                _ => None,
+            };
+            let compressed = encode(prefix, &even[1..33]);
+            assert_eq!(
+                parse_sec1_pubkey(&compressed).ok(),
+                expected,
+                "prefix {prefix}"
+            );
+            let x_too_large = encode(prefix, &[0xff; 32]);
+            assert_eq!(
+                parse_sec1_pubkey(&x_too_large).ok(),
+                None,
+                "prefix {prefix}"
+            );
+
+            let expected_even = matches!(prefix, 4 | 6).then_some(even);
+            let expected_odd = matches!(prefix, 4 | 7).then_some(odd);
+            assert_eq!(
+                parse_sec1_pubkey(&encode(prefix, &even[1..])).ok(),
+                expected_even
+            );
+            assert_eq!(
+                parse_sec1_pubkey(&encode(prefix, &odd[1..])).ok(),
+                expected_odd
+            );
+            assert_eq!(
+                parse_sec1_pubkey(&encode(prefix, &off_curve[1..])).ok(),
+                None
+            );
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    mod threshold_signatures {
+        use super::*;
+        use alloc::vec::Vec;
+        use k256::ecdsa::SigningKey;
+        use proptest::prelude::*;
+
+        const MSG_HASH: [u8; 32] = [0x5a; 32];
+        /// The keys that sign, of which the first `AUTHORIZED` are authorized.
+        const KEYS: usize = 6;
+        const AUTHORIZED: usize = 4;
+
+        /// The signature of each key, followed by a signature with an invalid `v` and one with
+        /// `r = 0`.
+        fn signatures() -> Vec<[u8; 65]> {
+            let mut sigs = (1..=KEYS as u8)
+                .map(|i| {
+                    let key = SigningKey::from_slice(&[i; 32]).unwrap();
+                    let (sig, recid) = key.sign_prehash_recoverable(&MSG_HASH).unwrap();
+                    let mut sig65 = [0u8; 65];
+                    sig65[..64].copy_from_slice(&sig.to_bytes());
+                    sig65[64] = recid.to_byte() + 27;
+                    sig65
+                })
+                .collect::<Vec<_>>();
+            let mut bad_v = sigs[0];
+            bad_v[64] = 5;
+            let mut zero_r = sigs[1];
+            zero_r[..32].fill(0);
+            sigs.extend([bad_v, zero_r]);
+            sigs
+        }
+
+        fn addresses(sigs: &[[u8; 65]]) -> Vec<[u8; 20]> {
+            sigs[..KEYS]
+                .iter()
+                .map(|sig| ecrecover_address(sig, &MSG_HASH).unwrap())
+                .collect()
+        }
+
+        /// Lists of indices into [`signatures`], with duplicates, in any order.
+        fn picks() -> impl Strategy<Value = Vec<usize>> {
+            prop::collection::vec(0..KEYS + 2, 0..16).prop_shuffle()
+        }
+
+        proptest! {
+            // Each case recovers up to 16 signatures on the host, several times over.
+            #![proptest_config(ProptestConfig::with_cases(64))]
+
+            #[test]
+            fn distinct_authorized_signers_are_counted(picks in picks()) {
+                let all = signatures();
+                let addresses = addresses(&all);
+                let authorized = &addresses[..AUTHORIZED];
+                let sigs = picks.iter().map(|&i| all[i]).collect::<Vec<_>>();
+
+                let mut expected = Vec::new();
+                for &i in &picks {
+                    if i < AUTHORIZED && !expected.contains(&addresses[i]) {
+                        expected.push(addresses[i]);
+                    }
+                }
+
+                let mut sorted = authorized.to_vec();
+                sorted.sort_unstable();
+                let sorted_options = ThresholdOptions {
+                    authorized_sorted: true,
+                    ..Default::default()
+                };
+                prop_assert_eq!(
+                    recover_threshold_signers(&MSG_HASH, &sigs, authorized, Default::default()),
+                    Ok(expected.clone())
+                );
+                prop_assert_eq!(
+                    recover_threshold_signers(&MSG_HASH, &sigs, &sorted, sorted_options),
+                    Ok(expected.clone())
+                );
+                for threshold in 0..=AUTHORIZED + 1 {
+                    let met = Ok(expected.len() >= threshold);
+                    prop_assert_eq!(
+                        verify_threshold_signatures(&MSG_HASH, &sigs, authorized, threshold),
+                        met.clone()
+                    );
+                    prop_assert_eq!(
+                        verify_threshold_signatures_with(
+                            &MSG_HASH,
+                            &sigs,
+                            &sorted,
+                            threshold,
+                            sorted_options
+                        ),
+                        met
+                    );
+                }
+            }
+
+            #[test]
+            fn rejected_signatures_are_reported(picks in picks()) {
+                let all = signatures();
+                let addresses = addresses(&all);
+                let sigs = picks.iter().map(|&i| all[i]).collect::<Vec<_>>();
+
+                let options = ThresholdOptions {
+                    reject_unauthorized: true,
+                    ..Default::default()
+                };
+                let expected = match picks.iter().position(|&i| i >= AUTHORIZED) {
+                    None => Ok(()),
+                    Some(index) => Err(match picks[index] {
+                        KEYS => Error::InvalidRecoveryId(5),
+                        i if i > KEYS => Error::InvalidSignature,
+                        _ => Error::UnauthorizedSigner { index },
+                    }),
+                };
+                prop_assert_eq!(
+                    recover_threshold_signers(&MSG_HASH, &sigs, &addresses[..AUTHORIZED], options)
+                        .map(|_| ()),
+                    expected
+                );
+            }
+
+            #[test]
+            fn batch_recovery_matches_ecrecover(picks in picks()) {
+                let all = signatures();
+                let sigs = picks.iter().map(|&i| all[i]).collect::<Vec<_>>();
+                let msg_hashes = picks.iter().map(|&i| [i as u8; 32]).collect::<Vec<_>>();
+
+                let expected = msg_hashes
+                    .iter()
+                    .zip(&sigs)
+                    .map(|(msg_hash, sig)| ecrecover(sig, msg_hash))
+                    .collect::<Vec<_>>();
+                prop_assert_eq!(ecrecover_batch(&msg_hashes, &sigs), Ok(expected));
+            }
+        }
+
+        #[test]
+        #[should_panic(expected = "not sorted")]
+        fn unsorted_authorized_set_is_caught() {
+            let all = signatures();
+            let mut authorized = addresses(&all);
+            authorized.sort_unstable_by(|a, b| b.cmp(a));
+            let options = ThresholdOptions {
+                authorized_sorted: true,
+                ..Default::default()
+            };
+            let _ = verify_threshold_signatures_with(&MSG_HASH, &all, &authorized, 1, options);
+        }
+
+        #[test]
+        fn batch_recovery_checks_lengths() {
+            assert_eq!(
+                ecrecover_batch(&[MSG_HASH; 2], &signatures()[..1]),
+                Err(Error::SignatureCountMismatch {
+                    messages: 2,
+                    signatures: 1,
+                })
+            );
+        }
+    }
+
+    mod point_conversions {
+        use crate::error::Error;
+        use crate::secp256k1::consts;
+        use crate::secp256k1::xonly::{Parity, XOnlyPublicKey};
+        use crate::secp256k1::Secp256k1AffinePoint;
+        use k256::elliptic_curve::ops::Reduce;
+        use k256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
+        use k256::{AffinePoint, EncodedPoint, ProjectivePoint, PublicKey, Scalar, U256};
+        use proptest::prelude::*;
+
+        /// Random multiples of the generator, the generator itself and the identity.
+        fn point() -> impl Strategy<Value = AffinePoint> {
+            let random = any::<[u8; 32]>().prop_map(|bytes| {
+                let scalar = <Scalar as Reduce<U256>>::reduce_bytes(&bytes.into());
+                (ProjectivePoint::GENERATOR * scalar).to_affine()
+            });
+            prop_oneof![
+                Just(AffinePoint::GENERATOR),
+                Just(AffinePoint::IDENTITY),
+                random
+            ]
+        }
+
+        proptest! {
+            #[test]
+            fn k256_points_round_trip(point in point()) {
+                let converted = Secp256k1AffinePoint::try_from(point);
+                if point == AffinePoint::IDENTITY {
+                    prop_assert_eq!(converted, Err(Error::IdentityPoint));
+                    let identity = EncodedPoint::identity();
+                    prop_assert_eq!(
+                        Secp256k1AffinePoint::try_from(&identity),
+                        Err(Error::IdentityPoint)
+                    );
+                    return Ok(());
+                }
+                let converted = converted.unwrap();
+                prop_assert_eq!(AffinePoint::try_from(converted), Ok(point));
+
+                let uncompressed = point.to_encoded_point(false);
+                prop_assert_eq!(EncodedPoint::from(converted), uncompressed);
+                for compress in [false, true] {
+                    let encoded = point.to_encoded_point(compress);
+                    prop_assert_eq!(Secp256k1AffinePoint::try_from(&encoded), Ok(converted));
+                }
+                if point == AffinePoint::GENERATOR {
+                    prop_assert_eq!(converted, Secp256k1AffinePoint::generator_in_affine());
+                }
+            }
+
+            #[test]
+            fn points_off_the_curve_are_rejected(point in point()) {
+                let Ok(converted) = Secp256k1AffinePoint::try_from(point) else {
+                    return Ok(());
+                };
+                let mut y = converted.y_be();
+                y[31] ^= 1;
+                let off_curve = Secp256k1AffinePoint::from_be_coordinates(&converted.x_be(), &y);
+                prop_assert_eq!(AffinePoint::try_from(off_curve), Err(Error::InvalidPublicKey));
+                let encoded = EncodedPoint::from(off_curve);
+                prop_assert!(bool::from(AffinePoint::from_encoded_point(&encoded).is_none()));
+                prop_assert_eq!(
+                    Secp256k1AffinePoint::try_from(&encoded),
+                    Err(Error::InvalidPublicKey)
+                );
+            }
+
+            #[test]
+            fn xonly_keys_lift_to_even_points(point in point()) {
+                let Ok(pubkey) = PublicKey::from_affine(point) else {
+                    return Ok(());
+                };
+                let (key, parity) = XOnlyPublicKey::from_pubkey(&pubkey);
+                let even = match parity {
+                    Parity::Even => point,
+                    Parity::Odd => -point,
+                };
+                prop_assert_eq!(key.lift_x(), Secp256k1AffinePoint::try_from(even));
+                let compressed = point.to_encoded_point(true);
+                prop_assert_eq!(&key.as_bytes()[..], &compressed.as_bytes()[1..]);
+                prop_assert_eq!(parity == Parity::Odd, compressed.as_bytes()[0] == 3);
+
+                let bytes = bincode::serialize(&key).unwrap();
+                prop_assert_eq!(&bytes[..], &key.as_bytes()[..]);
+                prop_assert_eq!(bincode::deserialize::<XOnlyPublicKey>(&bytes).unwrap(), key);
+            }
+        }
+
+        #[test]
+        fn test_lift_x_rejects_invalid_coordinates() {
+            // x^3 + 7 is not a square for these x-coordinates.
+            for x in [0u8, 5, 7, 9] {
+                let mut bytes = [0u8; 32];
+                bytes[31] = x;
+                let key = XOnlyPublicKey::from_bytes(bytes);
+                assert_eq!(key.lift_x(), Err(Error::InvalidPublicKey), "x = {x}");
+            }
+
+            // x = p + 1 would be valid modulo p, as 1^3 + 7 is a square.
+            let mut one = [0u8; 32];
+            one[31] = 1;
+            assert!(XOnlyPublicKey::from_bytes(one).lift_x().is_ok());
+            let mut p_plus_one = consts::P;
+            p_plus_one[31] += 1;
+            for x in [consts::P, p_plus_one, [0xff; 32]] {
+                let key = XOnlyPublicKey::from_bytes(x);
+                assert_eq!(key.lift_x(), Err(Error::InvalidPublicKey), "x = {x:02x?}");
+            }
+        }
+
+        #[test]
+        fn test_xonly_keys_are_ordered_by_bytes() {
+            let mut keys = [[3u8; 32], [1; 32], [2; 32]].map(XOnlyPublicKey::from);
+            keys.sort();
+            assert_eq!(keys.map(<[u8; 32]>::from), [[1; 32], [2; 32], [3; 32]]);
+        }
     }
 }
@@ -0,0 +1,319 @@
+//! BIP-340 compatible Schnorr adaptor signatures, as in the `schnorr_adaptor` module of
+//! secp256k1-zkp, for discreet log contracts and atomic swaps.
+//!
+//! A pre-signature of a message for the adaptor point `T` is the compressed nonce
+//! `R = R' + T = k * G + T`, followed by `s' = k' + e * x`, where `e` is the BIP-340 challenge of
+//! `x(R)`, and `k'` is `k` if `R` has an even y-coordinate and `-k` otherwise. Whoever knows the
+//! discrete logarithm `t` of `T` completes it into the BIP-340 signature `(x(R), s' + t)`, or
+//! `(x(R), s' - t)` if `R` has an odd y-coordinate, as the nonce of the signature is then `-R`.
+//! Publishing the signature reveals `t` to the signer, as the difference of the two.
+
+use super::schnorr::tagged_hash;
+use super::xonly::XOnlyPublicKey;
+use super::Secp256k1AffinePoint;
+use crate::error::Error;
+use core::convert::TryInto;
+use k256::elliptic_curve::ops::Reduce;
+use k256::elliptic_curve::PrimeField;
+use k256::{FieldBytes, FieldElement, Scalar, U256};
+
+/// Verifies that `pre_sig` is a pre-signature of `msg` by the x-only public key, which the
+/// discrete logarithm of `adaptor_point`, a compressed point, completes into a BIP-340
+/// signature.
+///
+/// Returns `false` if the public key, the adaptor point or the nonce is not on the curve, or if
+/// `s'` is not below the curve order.
+pub fn verify_adaptor_signature(
+    pubkey_x: &[u8; 32],
+    msg: &[u8; 32],
+    adaptor_point: &[u8; 33],
+    pre_sig: &[u8; 65],
+) -> bool {
+    try_verify(pubkey_x, msg, adaptor_point, pre_sig).unwrap_or(false)
+}
+
+/// Returns the discrete logarithm of the adaptor point, from a pre-signature and the signature
+/// completed from it.
+///
+/// The signature is not verified, so the secret is only correct if the signature is valid and
+/// the pre-signature verifies with [`verify_adaptor_signature`]. Returns
+/// [`Error::InvalidSignature`] if the nonce of the pre-signature is not compressed or a scalar is
+/// not below the curve order, and [`Error::AdaptorSignatureMismatch`] if the signature has
+/// another nonce.
+pub fn extract_adaptor_secret(pre_sig: &[u8; 65], final_sig: &[u8; 64]) -> Result<[u8; 32], Error> {
+    let odd = is_odd(pre_sig[0]).ok_or(Error::InvalidSignature)?;
+    if final_sig[..32] != pre_sig[1..33] {
+        return Err(Error::AdaptorSignatureMismatch);
+    }
+    let pre_s = scalar(&pre_sig[33..]).ok_or(Error::InvalidSignature)?;
+    let s = scalar(&final_sig[32..]).ok_or(Error::InvalidSignature)?;
+    let secret = if odd { pre_s - s } else { s - pre_s };
+    Ok(secret.to_bytes().into())
+}
+
+fn try_verify(
+    pubkey_x: &[u8; 32],
+    msg: &[u8; 32],
+    adaptor_point: &[u8; 33],
+    pre_sig: &[u8; 65],
+) -> Result<bool, Error> {
+    let public_key = XOnlyPublicKey::from_bytes(*pubkey_x).lift_x()?;
+    let adaptor = lift_compressed(adaptor_point)?;
+    let nonce = lift_compressed(pre_sig[..33].try_into().unwrap())?;
+    let Some(s) = scalar(&pre_sig[33..]) else {
+        return Ok(false);
+    };
+    let e = <Scalar as Reduce<U256>>::reduce_bytes(&FieldBytes::from(tagged_hash(
+        "BIP0340/challenge",
+        &[&pre_sig[1..33], &pubkey_x[..], &msg[..]],
+    )));
+    let odd = pre_sig[0] == 3;
+
+    // s' * G - e * P is k' * G, that is R' if R has an even y-coordinate and -R' otherwise, and
+    // R' + T must be R. R' is never the point at infinity, as the nonce k is not zero.
+    cfg_if::cfg_if! {
+        if #[cfg(all(target_os = "zkvm", target_vendor = "succinct"))] {
+            use super::{consts, double_and_add_base};
+
+            let Some(point) = double_and_add_base(&s, &consts::GENERATOR, &-e, &public_key) else {
+                return Ok(false);
+            };
+            let point = if odd { negate(&point) } else { point };
+            Ok(add(point, &adaptor) == Some(nonce))
+        } else {
+            use k256::{AffinePoint, ProjectivePoint};
+
+            let public_key = ProjectivePoint::from(AffinePoint::try_from(public_key)?);
+            let point = ProjectivePoint::GENERATOR * s - public_key * e;
+            if point == ProjectivePoint::IDENTITY {
+                return Ok(false);
+            }
+            let point = if odd { -point } else { point };
+            let adaptor = AffinePoint::try_from(adaptor)?;
+            let nonce = ProjectivePoint::from(AffinePoint::try_from(nonce)?);
+            Ok(point + adaptor == nonce)
+        }
+    }
+}
+
+/// Decompresses a point with [`XOnlyPublicKey::lift_x`], so that points from untrusted sources
+/// that are not on the curve are rejected rather than make the execution fail.
+fn lift_compressed(bytes: &[u8; 33]) -> Result<Secp256k1AffinePoint, Error> {
+    let odd = is_odd(bytes[0]).ok_or(Error::InvalidPublicKey)?;
+    let point = XOnlyPublicKey::from_bytes(bytes[1..].try_into().unwrap()).lift_x()?;
+    Ok(if odd { negate(&point) } else { point })
+}
+
+/// Returns the point with the same x-coordinate and the opposite y-coordinate.
+fn negate(point: &Secp256k1AffinePoint) -> Secp256k1AffinePoint {
+    // The y-coordinate of a point on the curve is below the field modulus.
+    let y = FieldElement::from_repr(point.y_be().into()).unwrap();
+    Secp256k1AffinePoint::from_be_coordinates(&point.x_be(), &(-y).normalize().to_bytes().into())
+}
+
+/// Returns `a + b`, or `None` for the point at infinity. The addition precompile does not handle
+/// equal or opposite points.
+#[cfg(all(target_os = "zkvm", target_vendor = "succinct"))]
+fn add(mut a: Secp256k1AffinePoint, b: &Secp256k1AffinePoint) -> Option<Secp256k1AffinePoint> {
+    use super::NUM_WORDS;
+
+    if a.limbs[..NUM_WORDS / 2] != b.limbs[..NUM_WORDS / 2] {
+        a.add_assign(b);
+    } else if a == *b {
+        a.double();
+    } else {
+        return None;
+    }
+    Some(a)
+}
+
+/// Returns whether a SEC1 prefix is the one of a compressed point with an odd y-coordinate, or
+/// `None` if it is not the prefix of a compressed point.
+fn is_odd(prefix: u8) -> Option<bool> {
+    match prefix {
+        2 => Some(false),
+        3 => Some(true),
+        _ => None,
+    }
+}
+
+fn scalar(bytes: &[u8]) -> Option<Scalar> {
+    Option::from(Scalar::from_repr(FieldBytes::clone_from_slice(bytes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secp256k1::schnorr;
+    use k256::ecdsa::signature::hazmat::PrehashVerifier;
+    use k256::elliptic_curve::sec1::ToEncodedPoint;
+    use k256::ProjectivePoint;
+
+    #[test]
+    fn test_schnorr_adaptor_signatures() {
+        let compress = |point: ProjectivePoint| -> [u8; 33] {
+            point
+                .to_affine()
+                .to_encoded_point(true)
+                .as_bytes()
+                .try_into()
+                .unwrap()
+        };
+        let scalar = |byte: u8| Scalar::from_repr([byte; 32].into()).unwrap();
+
+        // Pre-signatures are built as by `secp256k1_schnorr_adaptor_presign` of secp256k1-zkp:
+        // the nonce is negated when R = k * G + T has an odd y-coordinate, and so is the secret
+        // key when the public key has one, as in BIP-340.
+        let presign = |secret_key: Scalar, msg: &[u8; 32], adaptor: ProjectivePoint, k: Scalar| {
+            let public_key = compress(ProjectivePoint::GENERATOR * secret_key);
+            let secret_key = if public_key[0] == 3 {
+                -secret_key
+            } else {
+                secret_key
+            };
+            let nonce = compress(ProjectivePoint::GENERATOR * k + adaptor);
+            let k = if nonce[0] == 3 { -k } else { k };
+            let e = <Scalar as Reduce<U256>>::reduce_bytes(
+                &tagged_hash("BIP0340/challenge", &[&nonce[1..], &public_key[1..], msg]).into(),
+            );
+            let mut pre_sig = [0u8; 65];
+            pre_sig[..33].copy_from_slice(&nonce);
+            pre_sig[33..].copy_from_slice(&(k + e * secret_key).to_bytes());
+            let pubkey_x: [u8; 32] = public_key[1..].try_into().unwrap();
+            (pubkey_x, pre_sig)
+        };
+
+        let mut parities = [false; 2];
+        for i in 1..=32u8 {
+            let msg = [i.wrapping_mul(7); 32];
+            let secret = scalar(0x40 + i);
+            let adaptor = ProjectivePoint::GENERATOR * secret;
+            let adaptor_point = compress(adaptor);
+            let (pubkey_x, pre_sig) = presign(scalar(i), &msg, adaptor, scalar(0x80 + i));
+            parities[(pre_sig[0] - 2) as usize] = true;
+            assert!(verify_adaptor_signature(
+                &pubkey_x,
+                &msg,
+                &adaptor_point,
+                &pre_sig
+            ));
+
+            // The pre-signature is not a signature, and only verifies for its adaptor point.
+            let mut as_sig = [0u8; 64];
+            as_sig.copy_from_slice(&pre_sig[1..]);
+            assert_eq!(schnorr::verify(&pubkey_x, &msg, &as_sig), Ok(false));
+            let other = compress(ProjectivePoint::GENERATOR * scalar(0xc0 + i));
+            let mut negated = adaptor_point;
+            negated[0] ^= 1;
+            let mut other_msg = msg;
+            other_msg[0] ^= 1;
+            assert!(!verify_adaptor_signature(&pubkey_x, &msg, &other, &pre_sig));
+            assert!(!verify_adaptor_signature(
+                &pubkey_x, &msg, &negated, &pre_sig
+            ));
+            assert!(!verify_adaptor_signature(
+                &pubkey_x,
+                &other_msg,
+                &adaptor_point,
+                &pre_sig
+            ));
+
+            // The signature completed with the secret verifies with k256, which implements
+            // BIP-340 independently, and reveals the secret.
+            let s = Scalar::from_repr(<[u8; 32]>::try_from(&pre_sig[33..]).unwrap().into());
+            let s = if pre_sig[0] == 2 {
+                s.unwrap() + secret
+            } else {
+                s.unwrap() - secret
+            };
+            let mut final_sig = as_sig;
+            final_sig[32..].copy_from_slice(&s.to_bytes());
+            let verifying_key = k256::schnorr::VerifyingKey::from_bytes(&pubkey_x).unwrap();
+            let signature = k256::schnorr::Signature::try_from(&final_sig[..]).unwrap();
+            assert!(verifying_key.verify_prehash(&msg, &signature).is_ok());
+            assert_eq!(schnorr::verify(&pubkey_x, &msg, &final_sig), Ok(true));
+            assert_eq!(
+                extract_adaptor_secret(&pre_sig, &final_sig),
+                Ok(secret.to_bytes().into())
+            );
+        }
+        assert_eq!(parities, [true, true]);
+
+        let secret = scalar(0x55);
+        let adaptor_point = compress(ProjectivePoint::GENERATOR * secret);
+        let msg = [0x42; 32];
+        let (pubkey_x, pre_sig) = presign(
+            scalar(0x11),
+            &msg,
+            ProjectivePoint::GENERATOR * secret,
+            scalar(0x22),
+        );
+        assert!(verify_adaptor_signature(
+            &pubkey_x,
+            &msg,
+            &adaptor_point,
+            &pre_sig
+        ));
+        let (other_key, _) = presign(
+            scalar(0x12),
+            &msg,
+            ProjectivePoint::GENERATOR * secret,
+            scalar(0x22),
+        );
+        assert!(!verify_adaptor_signature(
+            &other_key,
+            &msg,
+            &adaptor_point,
+            &pre_sig
+        ));
+
+        // Malformed nonces, scalars and points are rejected.
+        let mut bad_prefix = pre_sig;
+        bad_prefix[0] = 4;
+        let mut off_curve = pre_sig;
+        off_curve[1..33].copy_from_slice(&[0; 32]);
+        off_curve[0] = 2;
+        let mut high_s = pre_sig;
+        high_s[33..].copy_from_slice(&[0xff; 32]);
+        let mut tweaked_s = pre_sig;
+        tweaked_s[64] ^= 1;
+        for pre_sig in [bad_prefix, off_curve, high_s, tweaked_s] {
+            assert!(!verify_adaptor_signature(
+                &pubkey_x,
+                &msg,
+                &adaptor_point,
+                &pre_sig
+            ));
+        }
+        assert!(!verify_adaptor_signature(
+            &[0; 32],
+            &msg,
+            &adaptor_point,
+            &pre_sig
+        ));
+        let mut off_curve_adaptor = [0u8; 33];
+        off_curve_adaptor[0] = 2;
+        assert!(!verify_adaptor_signature(
+            &pubkey_x,
+            &msg,
+            &off_curve_adaptor,
+            &pre_sig
+        ));
+
+        let mut final_sig = [0u8; 64];
+        final_sig[..32].copy_from_slice(&pre_sig[1..33]);
+        assert_eq!(
+            extract_adaptor_secret(&bad_prefix, &final_sig),
+            Err(Error::InvalidSignature)
+        );
+        assert_eq!(
+            extract_adaptor_secret(&high_s, &final_sig),
+            Err(Error::InvalidSignature)
+        );
+        final_sig[0] ^= 1;
+        assert_eq!(
+            extract_adaptor_secret(&pre_sig, &final_sig),
+            Err(Error::AdaptorSignatureMismatch)
+        );
+    }
+}
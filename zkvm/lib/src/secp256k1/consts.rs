@@ -0,0 +1,138 @@
+//! The secp256k1 curve parameters.
+//!
+//! Each parameter is given as a big endian `[u8; 32]`, and as little endian `u32` limbs in the
+//! layout used by [`Secp256k1AffinePoint`]. The limb forms are derived from the byte forms at
+//! compile time.
+
+use super::Secp256k1AffinePoint;
+
+/// The field modulus p = 2^256 - 2^32 - 977.
+pub const P: [u8; 32] = hex32(b"fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f");
+
+/// The curve order n.
+pub const N: [u8; 32] = hex32(b"fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141");
+
+/// Half of the curve order, rounded down. Signatures with `s <= HALF_N` have a low `s`.
+pub const HALF_N: [u8; 32] =
+    hex32(b"7fffffffffffffffffffffffffffffff5d576e7357a4501ddfe92f46681b20a0");
+
+/// The constant b of the curve equation y^2 = x^3 + b.
+pub const B: [u8; 32] = hex32(b"0000000000000000000000000000000000000000000000000000000000000007");
+
+/// The x-coordinate of the generator.
+pub const GX: [u8; 32] = hex32(b"79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798");
+
+/// The y-coordinate of the generator.
+pub const GY: [u8; 32] = hex32(b"483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8");
+
+/// The GLV endomorphism scalar lambda, a cube root of unity modulo n, such that
+/// `lambda * (x, y) = (beta * x, y)`.
+pub const LAMBDA: [u8; 32] =
+    hex32(b"5363ad4cc05c30e0a5261c028812645a122e22ea20816678df02967c1b23bd72");
+
+/// The GLV endomorphism constant beta, a cube root of unity modulo p.
+pub const BETA: [u8; 32] =
+    hex32(b"7ae96a2b657c07106e64479eac3434e99cf0497512f58995c1396c28719501ee");
+
+/// [`P`] as little endian limbs.
+pub const P_LIMBS: [u32; 8] = be_to_limbs(&P);
+
+/// [`N`] as little endian limbs.
+pub const N_LIMBS: [u32; 8] = be_to_limbs(&N);
+
+/// [`HALF_N`] as little endian limbs.
+pub const HALF_N_LIMBS: [u32; 8] = be_to_limbs(&HALF_N);
+
+/// [`LAMBDA`] as little endian limbs.
+pub const LAMBDA_LIMBS: [u32; 8] = be_to_limbs(&LAMBDA);
+
+/// [`BETA`] as little endian limbs.
+pub const BETA_LIMBS: [u32; 8] = be_to_limbs(&BETA);
+
+/// The limbs of the generator, x followed by y.
+pub const GENERATOR_LIMBS: [u32; 16] = concat_limbs(be_to_limbs(&GX), be_to_limbs(&GY));
+
+/// The generator of the curve.
+pub const GENERATOR: Secp256k1AffinePoint = Secp256k1AffinePoint::new(GENERATOR_LIMBS);
+
+// Self-checks, evaluated at compile time.
+const _: () = {
+    // n = 2 * (n / 2) + 1.
+    let mut i = 0;
+    while i < 8 {
+        let shifted = (HALF_N_LIMBS[i] << 1) | if i == 0 { 1 } else { HALF_N_LIMBS[i - 1] >> 31 };
+        assert!(shifted == N_LIMBS[i], "HALF_N is not half of N");
+        i += 1;
+    }
+    assert!(HALF_N_LIMBS[7] >> 31 == 0, "HALF_N is not half of N");
+
+    // The parameters that are field elements are below p, and the scalars below n.
+    assert!(is_below(&GENERATOR_LIMBS_X, &P_LIMBS), "GX is not below P");
+    assert!(is_below(&GENERATOR_LIMBS_Y, &P_LIMBS), "GY is not below P");
+    assert!(is_below(&BETA_LIMBS, &P_LIMBS), "BETA is not below P");
+    assert!(is_below(&LAMBDA_LIMBS, &N_LIMBS), "LAMBDA is not below N");
+    assert!(is_below(&N_LIMBS, &P_LIMBS), "N is not below P");
+};
+
+const GENERATOR_LIMBS_X: [u32; 8] = be_to_limbs(&GX);
+const GENERATOR_LIMBS_Y: [u32; 8] = be_to_limbs(&GY);
+
+/// Converts a big endian 256-bit integer to little endian limbs.
+pub(crate) const fn be_to_limbs(bytes: &[u8; 32]) -> [u32; 8] {
+    let mut limbs = [0u32; 8];
+    let mut i = 0;
+    while i < 8 {
+        // The i-th little endian limb is the i-th big endian word from the end.
+        let end = 32 - 4 * i;
+        limbs[i] = u32::from_be_bytes([
+            bytes[end - 4],
+            bytes[end - 3],
+            bytes[end - 2],
+            bytes[end - 1],
+        ]);
+        i += 1;
+    }
+    limbs
+}
+
+pub(crate) const fn concat_limbs(x: [u32; 8], y: [u32; 8]) -> [u32; 16] {
+    let mut limbs = [0u32; 16];
+    let mut i = 0;
+    while i < 8 {
+        limbs[i] = x[i];
+        limbs[8 + i] = y[i];
+        i += 1;
+    }
+    limbs
+}
+
+/// Returns whether `a < b`, for little endian limbs.
+const fn is_below(a: &[u32; 8], b: &[u32; 8]) -> bool {
+    let mut i = 8;
+    while i > 0 {
+        i -= 1;
+        if a[i] != b[i] {
+            return a[i] < b[i];
+        }
+    }
+    false
+}
+
+/// Parses 64 hex digits at compile time.
+pub(crate) const fn hex32(digits: &[u8; 64]) -> [u8; 32] {
+    const fn nibble(digit: u8) -> u8 {
+        match digit {
+            b'0'..=b'9' => digit - b'0',
+            b'a'..=b'f' => digit - b'a' + 10,
+            _ => panic!("invalid hex digit"),
+        }
+    }
+
+    let mut bytes = [0u8; 32];
+    let mut i = 0;
+    while i < 32 {
+        bytes[i] = (nibble(digits[2 * i]) << 4) | nibble(digits[2 * i + 1]);
+        i += 1;
+    }
+    bytes
+}
@@ -0,0 +1,206 @@
+//! Pedersen commitments `C = v * H + r * G` to 64-bit amounts `v`, with blinding factors `r`.
+//!
+//! The second generator `H` must have no known discrete logarithm with respect to `G`. Its
+//! x-coordinate is the SHA-256 hash of the uncompressed SEC1 encoding of `G`, which is on the
+//! curve, and its y-coordinate is the even one. This is the generator `H` of the confidential
+//! transactions of Elements, and the unspendable key of BIP-341.
+//!
+//! The commitment of a zero amount with a zero blinding factor is the point at infinity, which
+//! has no affine coordinates: [`commit`] rejects it, and [`verify_balance`] takes the fee
+//! commitment as an option so that a zero fee can be left out.
+
+use super::consts::{be_to_limbs, concat_limbs, hex32};
+use super::{validate_uncompressed_pubkey, Secp256k1AffinePoint};
+use crate::error::Error;
+use k256::elliptic_curve::PrimeField;
+use k256::{FieldBytes, Scalar};
+
+/// The x-coordinate of the generator `H`, `sha256(0x04 || GX || GY)`.
+pub const HX: [u8; 32] = hex32(b"50929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0");
+
+/// The even y-coordinate of the generator `H`.
+pub const HY: [u8; 32] = hex32(b"31d3c6863973926e049e637cb1b5f40a36dac28af1766968c30c2313f3a38904");
+
+/// The generator `H` that amounts are committed with.
+pub const H: Secp256k1AffinePoint =
+    Secp256k1AffinePoint::new(concat_limbs(be_to_limbs(&HX), be_to_limbs(&HY)));
+
+/// Returns the commitment `value * H + blinding * G`, where `blinding` is a big endian scalar.
+///
+/// Either the value or the blinding factor may be zero, but not both. Returns
+/// [`Error::InvalidBlinding`] if the blinding factor is not below the curve order, and
+/// [`Error::IdentityPoint`] if both are zero, as the commitment is then the point at infinity.
+pub fn commit(value: u64, blinding: &[u8; 32]) -> Result<Secp256k1AffinePoint, Error> {
+    let blinding =
+        Option::<Scalar>::from(Scalar::from_repr(FieldBytes::clone_from_slice(blinding)))
+            .ok_or(Error::InvalidBlinding)?;
+    let value = Scalar::from(value);
+
+    cfg_if::cfg_if! {
+        if #[cfg(all(target_os = "zkvm", target_vendor = "succinct"))] {
+            super::double_and_add_base(&value, &H, &blinding, &super::consts::GENERATOR)
+                .ok_or(Error::IdentityPoint)
+        } else {
+            use k256::ProjectivePoint;
+
+            let h = ProjectivePoint::from(k256::AffinePoint::try_from(H)?);
+            let res = h * value + ProjectivePoint::GENERATOR * blinding;
+            Secp256k1AffinePoint::try_from(res.to_affine())
+        }
+    }
+}
+
+/// Checks that `commitment` commits to `value` with the given blinding factor.
+///
+/// Returns `false` if the blinding factor is not below the curve order, and if the value and the
+/// blinding factor are both zero, as no affine point commits to them.
+pub fn verify_commitment(
+    commitment: &Secp256k1AffinePoint,
+    value: u64,
+    blinding: &[u8; 32],
+) -> bool {
+    commit(value, blinding).map_or(false, |expected| expected == *commitment)
+}
+
+/// Checks that the commitments of the inputs of a transaction add up to those of its outputs and
+/// of its fee.
+///
+/// As the generators are independent, the amounts balance if and only if the blinding factors do
+/// as well, which the creator of the transaction chooses them to. A public fee `f` is committed
+/// as `commit(f, &[0; 32])`, and a zero fee is passed as `None`.
+///
+/// Returns `false` if a commitment is not on the curve. The sums may be the point at infinity, so
+/// that a transaction with no inputs and no outputs balances.
+pub fn verify_balance(
+    inputs: &[Secp256k1AffinePoint],
+    outputs: &[Secp256k1AffinePoint],
+    fee_commitment: Option<&Secp256k1AffinePoint>,
+) -> bool {
+    let mut commitments = inputs.iter().chain(outputs).chain(fee_commitment);
+    if !commitments.all(is_on_curve) {
+        return false;
+    }
+    sum(inputs.iter()) == sum(outputs.iter().chain(fee_commitment))
+}
+
+fn is_on_curve(point: &Secp256k1AffinePoint) -> bool {
+    let mut uncompressed = [4u8; 65];
+    uncompressed[1..33].copy_from_slice(&point.x_be());
+    uncompressed[33..].copy_from_slice(&point.y_be());
+    validate_uncompressed_pubkey(&uncompressed).is_ok()
+}
+
+/// Returns the sum of points on the curve, or `None` for the point at infinity.
+fn sum<'a>(points: impl Iterator<Item = &'a Secp256k1AffinePoint>) -> Option<Secp256k1AffinePoint> {
+    cfg_if::cfg_if! {
+        if #[cfg(all(target_os = "zkvm", target_vendor = "succinct"))] {
+            use super::NUM_WORDS;
+
+            // The addition precompile does not handle equal or opposite points, nor the point at
+            // infinity, which a commitment and its negation add up to.
+            let mut res: Option<Secp256k1AffinePoint> = None;
+            for point in points {
+                match res.as_mut() {
+                    None => res = Some(*point),
+                    Some(acc) if acc.limbs[..NUM_WORDS / 2] == point.limbs[..NUM_WORDS / 2] => {
+                        if acc == point {
+                            acc.double();
+                        } else {
+                            res = None;
+                        }
+                    }
+                    Some(acc) => acc.add_assign(point),
+                }
+            }
+            res
+        } else {
+            use k256::ProjectivePoint;
+
+            // The points were checked to be on the curve.
+            let res = points
+                .map(|point| k256::AffinePoint::try_from(*point).unwrap())
+                .fold(ProjectivePoint::IDENTITY, |acc, point| acc + point);
+            Secp256k1AffinePoint::try_from(res.to_affine()).ok()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha256::sha256;
+    use k256::elliptic_curve::sec1::ToEncodedPoint;
+    use k256::{ProjectivePoint, PublicKey};
+
+    #[test]
+    fn test_pedersen_commitments() {
+        // H is the point with an even y-coordinate whose x-coordinate is the hash of the
+        // uncompressed generator.
+        let generator = ProjectivePoint::GENERATOR
+            .to_affine()
+            .to_encoded_point(false);
+        let mut compressed_h = [2u8; 33];
+        compressed_h[1..].copy_from_slice(&sha256(generator.as_bytes()));
+        let h = PublicKey::from_sec1_bytes(&compressed_h)
+            .unwrap()
+            .to_projective();
+        assert_eq!(Secp256k1AffinePoint::try_from(h.to_affine()).unwrap(), H);
+
+        let scalar = |bytes: [u8; 32]| Scalar::from_repr(bytes.into()).unwrap();
+        let host_commit = |value: u64, blinding: [u8; 32]| {
+            let point = h * Scalar::from(value) + ProjectivePoint::GENERATOR * scalar(blinding);
+            Secp256k1AffinePoint::try_from(point.to_affine())
+        };
+
+        for (value, blinding) in [
+            (0, [0x11; 32]),
+            (1_000, [0; 32]),
+            (42, [0x33; 32]),
+            (u64::MAX, [0x22; 32]),
+        ] {
+            let commitment = commit(value, &blinding).unwrap();
+            assert_eq!(Ok(commitment), host_commit(value, blinding));
+            assert!(verify_commitment(&commitment, value, &blinding));
+            assert!(!verify_commitment(
+                &commitment,
+                value.wrapping_add(1),
+                &blinding
+            ));
+        }
+        assert_eq!(commit(0, &[0; 32]), Err(Error::IdentityPoint));
+        assert_eq!(commit(1, &[0xff; 32]), Err(Error::InvalidBlinding));
+        assert!(!verify_commitment(&H, 1, &[0xff; 32]));
+
+        // Inputs of 70 and 30 balance outputs of 60 and 35 with a fee of 5, when the blinding
+        // factors of the outputs add up to those of the inputs.
+        let (r1, r2, r3) = ([0x01; 32], [0x02; 32], [0x04; 32]);
+        let r4: [u8; 32] = (scalar(r1) + scalar(r2) - scalar(r3)).to_bytes().into();
+        let inputs = [commit(70, &r1).unwrap(), commit(30, &r2).unwrap()];
+        let outputs = [commit(60, &r3).unwrap(), commit(35, &r4).unwrap()];
+        let fee = commit(5, &[0; 32]).unwrap();
+        assert!(verify_balance(&inputs, &outputs, Some(&fee)));
+        assert!(!verify_balance(&inputs, &outputs, None));
+        assert!(!verify_balance(
+            &inputs,
+            &outputs,
+            Some(&commit(6, &[0; 32]).unwrap())
+        ));
+
+        // Sums that are the point at infinity, or that add a commitment to itself.
+        let negated = Secp256k1AffinePoint::try_from(
+            (-(h * Scalar::from(70u64) + ProjectivePoint::GENERATOR * scalar(r1))).to_affine(),
+        )
+        .unwrap();
+        assert!(verify_balance(&[], &[], None));
+        assert!(verify_balance(&[inputs[0], negated], &[], None));
+        assert!(verify_balance(
+            &[inputs[0], inputs[0]],
+            &[commit(140, &(scalar(r1) + scalar(r1)).to_bytes().into()).unwrap()],
+            None
+        ));
+
+        // Commitments must be on the curve.
+        let off_curve = Secp256k1AffinePoint::from_be_coordinates(&[1; 32], &[2; 32]);
+        assert!(!verify_balance(&[off_curve], &[off_curve], None));
+    }
+}
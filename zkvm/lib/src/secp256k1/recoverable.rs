@@ -0,0 +1,231 @@
+//! A recoverable signature type, with conversions between the encodings that signatures are
+//! handled in.
+
+use super::{ecrecover_with_meta, pubkey_to_address, RecoveryMeta};
+use crate::error::Error;
+use core::convert::{TryFrom, TryInto};
+use core::fmt;
+use k256::ecdsa::{RecoveryId, Signature};
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::ser::{SerializeTuple, Serializer};
+use serde::{Deserialize, Serialize};
+
+/// A signature `(r, s)` with the metadata of its `v` value.
+///
+/// The scalars are checked to be nonzero and below the curve order; high s values are accepted.
+/// The canonical encoding is `r || s || v`, where `v` is the pre-EIP-155 value 27 or 28 for
+/// signatures without a chain id, and the EIP-155 value otherwise. It is used for serde, as a
+/// sequence of 65 bytes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RecoverableSignature {
+    r: [u8; 32],
+    s: [u8; 32],
+    meta: RecoveryMeta,
+}
+
+impl RecoverableSignature {
+    /// Creates a signature, returning [`Error::InvalidSignature`] if `r` or `s` is zero or not
+    /// below the curve order.
+    pub fn new(r: [u8; 32], s: [u8; 32], meta: RecoveryMeta) -> Result<Self, Error> {
+        Signature::from_scalars(r, s).map_err(|_| Error::InvalidSignature)?;
+        Ok(Self { r, s, meta })
+    }
+
+    /// The big endian `r` scalar.
+    pub fn r(&self) -> &[u8; 32] {
+        &self.r
+    }
+
+    /// The big endian `s` scalar.
+    pub fn s(&self) -> &[u8; 32] {
+        &self.s
+    }
+
+    /// The metadata of the `v` value.
+    pub fn meta(&self) -> RecoveryMeta {
+        self.meta
+    }
+
+    /// Returns the `r || s` bytes of the signature.
+    pub fn to_rs_bytes(&self) -> [u8; 64] {
+        let mut sig64 = [0u8; 64];
+        sig64[..32].copy_from_slice(&self.r);
+        sig64[32..].copy_from_slice(&self.s);
+        sig64
+    }
+
+    /// Returns the public key that signed the message, like [`super::ecrecover`].
+    pub fn recover(&self, msg_hash: &[u8; 32]) -> Result<[u8; 65], Error> {
+        ecrecover_with_meta(&self.to_rs_bytes(), self.meta, msg_hash)
+    }
+
+    /// Returns the Ethereum address of the signer, like [`super::ecrecover_address`].
+    pub fn recover_address(&self, msg_hash: &[u8; 32]) -> Result<[u8; 20], Error> {
+        Ok(pubkey_to_address(&self.recover(msg_hash)?))
+    }
+
+    fn from_rs_bytes(sig64: &[u8; 64], meta: RecoveryMeta) -> Result<Self, Error> {
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+        r.copy_from_slice(&sig64[..32]);
+        s.copy_from_slice(&sig64[32..]);
+        Self::new(r, s, meta)
+    }
+}
+
+/// Parses `r || s || v`, where `v` may use any of the encodings accepted by
+/// [`RecoveryMeta::from_v`].
+impl TryFrom<[u8; 65]> for RecoverableSignature {
+    type Error = Error;
+
+    fn try_from(sig: [u8; 65]) -> Result<Self, Error> {
+        let meta = RecoveryMeta::from_v(sig[64] as u64)?;
+        Self::from_rs_bytes(sig[..64].try_into().unwrap(), meta)
+    }
+}
+
+/// Encodes the signature canonically, returning [`Error::ChainIdOverflow`] if the EIP-155 `v`
+/// value does not fit in a byte.
+impl TryFrom<RecoverableSignature> for [u8; 65] {
+    type Error = Error;
+
+    fn try_from(sig: RecoverableSignature) -> Result<Self, Error> {
+        let v = sig.meta.to_v(true);
+        let v =
+            u8::try_from(v).map_err(|_| Error::ChainIdOverflow(sig.meta.chain_id().unwrap()))?;
+        let mut bytes = [0u8; 65];
+        bytes[..64].copy_from_slice(&sig.to_rs_bytes());
+        bytes[64] = v;
+        Ok(bytes)
+    }
+}
+
+/// Parses the EIP-2098 compact form `r || (yParity << 255 | s)`, which has no chain id.
+impl TryFrom<[u8; 64]> for RecoverableSignature {
+    type Error = Error;
+
+    fn try_from(compact: [u8; 64]) -> Result<Self, Error> {
+        let mut sig64 = compact;
+        let parity = sig64[32] >> 7 == 1;
+        sig64[32] &= 0x7f;
+        // A chain id of `None` cannot overflow.
+        Self::from_rs_bytes(&sig64, RecoveryMeta::new(parity, None).unwrap())
+    }
+}
+
+/// Encodes the signature in the EIP-2098 compact form, dropping the chain id.
+///
+/// The form is only defined for low s values, so [`Error::InvalidSignature`] is returned for high
+/// ones; use [`super::normalize_signature`] first for signatures that are not normalized.
+impl TryFrom<RecoverableSignature> for [u8; 64] {
+    type Error = Error;
+
+    fn try_from(sig: RecoverableSignature) -> Result<Self, Error> {
+        let mut compact = sig.to_rs_bytes();
+        let signature = Signature::from_slice(&compact).unwrap();
+        if signature.normalize_s().is_some() {
+            return Err(Error::InvalidSignature);
+        }
+        compact[32] |= (sig.meta.parity() as u8) << 7;
+        Ok(compact)
+    }
+}
+
+/// Converts a k256 signature and recovery id, returning [`Error::InvalidRecoveryId`] for
+/// recovery ids of nonce points whose x-coordinate is not below the curve order, which are not
+/// supported by [`super::ecrecover`].
+impl TryFrom<(Signature, RecoveryId)> for RecoverableSignature {
+    type Error = Error;
+
+    fn try_from((signature, recovery_id): (Signature, RecoveryId)) -> Result<Self, Error> {
+        if recovery_id.is_x_reduced() {
+            return Err(Error::InvalidRecoveryId(recovery_id.to_byte() as u64));
+        }
+        let meta = RecoveryMeta::new(recovery_id.is_y_odd(), None).unwrap();
+        Self::from_rs_bytes(signature.to_bytes().as_slice().try_into().unwrap(), meta)
+    }
+}
+
+/// Converts the signature to a k256 signature and recovery id, dropping the chain id.
+impl From<RecoverableSignature> for (Signature, RecoveryId) {
+    fn from(sig: RecoverableSignature) -> Self {
+        // The scalars have been checked when the signature was created.
+        let signature = Signature::from_scalars(sig.r, sig.s).unwrap();
+        (signature, RecoveryId::new(sig.meta.parity(), false))
+    }
+}
+
+/// Converts an alloy signature, whose parity may use any of the `v` encodings.
+#[cfg(feature = "alloy")]
+impl TryFrom<alloy_primitives::Signature> for RecoverableSignature {
+    type Error = Error;
+
+    fn try_from(sig: alloy_primitives::Signature) -> Result<Self, Error> {
+        let meta = RecoveryMeta::from_v(sig.v().to_u64())?;
+        Self::new(
+            sig.r().to_be_bytes::<32>(),
+            sig.s().to_be_bytes::<32>(),
+            meta,
+        )
+    }
+}
+
+/// Converts the signature to an alloy signature, with an EIP-155 parity if there is a chain id
+/// and a `yParity` otherwise.
+#[cfg(feature = "alloy")]
+impl From<RecoverableSignature> for alloy_primitives::Signature {
+    fn from(sig: RecoverableSignature) -> Self {
+        use alloy_primitives::{Parity, U256};
+
+        let parity = match sig.meta.chain_id() {
+            Some(_) => Parity::Eip155(sig.meta.to_v(true)),
+            None => Parity::Parity(sig.meta.parity()),
+        };
+        // The scalars have been checked when the signature was created, and the parity is
+        // already a `Parity`, so the conversion cannot fail.
+        Self::from_rs_and_parity(
+            U256::from_be_bytes(sig.r),
+            U256::from_be_bytes(sig.s),
+            parity,
+        )
+        .unwrap()
+    }
+}
+
+impl Serialize for RecoverableSignature {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let bytes = <[u8; 65]>::try_from(*self).map_err(serde::ser::Error::custom)?;
+        let mut tuple = serializer.serialize_tuple(bytes.len())?;
+        for byte in &bytes {
+            tuple.serialize_element(byte)?;
+        }
+        tuple.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for RecoverableSignature {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BytesVisitor;
+
+        impl<'de> Visitor<'de> for BytesVisitor {
+            type Value = [u8; 65];
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a 65-byte signature")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<[u8; 65], A::Error> {
+                let mut bytes = [0u8; 65];
+                for (i, byte) in bytes.iter_mut().enumerate() {
+                    *byte = seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+                }
+                Ok(bytes)
+            }
+        }
+
+        let bytes = deserializer.deserialize_tuple(65, BytesVisitor)?;
+        Self::try_from(bytes).map_err(de::Error::custom)
+    }
+}
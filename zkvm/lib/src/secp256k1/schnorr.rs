@@ -0,0 +1,69 @@
+//! BIP-340 Schnorr signatures, with x-only public keys.
+
+use super::xonly::XOnlyPublicKey;
+use crate::error::Error;
+use crate::sha256::{sha256, Sha256};
+use k256::elliptic_curve::ops::Reduce;
+use k256::elliptic_curve::PrimeField;
+use k256::{FieldBytes, FieldElement, Scalar, U256};
+
+/// Computes the BIP-340 tagged hash `SHA-256(SHA-256(tag) || SHA-256(tag) || data)`, where `data`
+/// is the concatenation of the given parts.
+pub fn tagged_hash(tag: &str, data: &[&[u8]]) -> [u8; 32] {
+    let tag_hash = sha256(tag.as_bytes());
+    let mut hasher = Sha256::new();
+    hasher.update(&tag_hash);
+    hasher.update(&tag_hash);
+    for part in data {
+        hasher.update(part);
+    }
+    hasher.finalize()
+}
+
+/// Verifies a BIP-340 signature of `message` with an x-only public key.
+///
+/// Returns an error if the public key is not the x-coordinate of a point on the curve, as
+/// reported by [`XOnlyPublicKey::lift_x`], and `Ok(false)` if the signature is out of range or
+/// does not verify.
+pub fn verify(public_key: &[u8; 32], message: &[u8], signature: &[u8; 64]) -> Result<bool, Error> {
+    let point = XOnlyPublicKey::from_bytes(*public_key).lift_x()?;
+
+    let (r, s) = signature.split_at(32);
+    if Option::<FieldElement>::from(FieldElement::from_repr(FieldBytes::clone_from_slice(r)))
+        .is_none()
+    {
+        return Ok(false);
+    }
+    let Some(s) = Option::<Scalar>::from(Scalar::from_repr(FieldBytes::clone_from_slice(s))) else {
+        return Ok(false);
+    };
+    let e = <Scalar as Reduce<U256>>::reduce_bytes(&FieldBytes::from(tagged_hash(
+        "BIP0340/challenge",
+        &[r, &public_key[..], message],
+    )));
+
+    // The nonce point s * G - e * P must have the x-coordinate r and an even y-coordinate.
+    cfg_if::cfg_if! {
+        if #[cfg(all(target_os = "zkvm", target_vendor = "succinct"))] {
+            use super::{consts, double_and_add_base};
+
+            let Some(nonce) = double_and_add_base(&s, &consts::GENERATOR, &-e, &point) else {
+                return Ok(false);
+            };
+            Ok(nonce.x_be()[..] == *r && nonce.y_be()[31] & 1 == 0)
+        } else {
+            use core::convert::TryFrom;
+            use k256::elliptic_curve::sec1::ToEncodedPoint;
+            use k256::{AffinePoint, ProjectivePoint};
+
+            let point = ProjectivePoint::from(AffinePoint::try_from(point)?);
+            let nonce = (ProjectivePoint::GENERATOR * s - point * e).to_affine();
+            let nonce = nonce.to_encoded_point(false);
+            match (nonce.x(), nonce.y()) {
+                (Some(x), Some(y)) => Ok(x[..] == *r && y[31] & 1 == 0),
+                // The point at infinity.
+                _ => Ok(false),
+            }
+        }
+    }
+}
@@ -0,0 +1,229 @@
+//! Stealth addresses of ERC-5564, with the secp256k1 scheme (scheme id 1) of ERC-6538
+//! registries.
+
+use super::pubkey_to_address;
+use crate::error::Error;
+use crate::keccak::keccak256;
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::elliptic_curve::PrimeField;
+use k256::{FieldBytes, PublicKey, Scalar};
+
+/// Checks that `expected_address` is the stealth address derived from an announcement, as seen
+/// by the recipient with the given spending public key and viewing private key.
+///
+/// The shared secret is the compressed point `viewing_key * ephemeral_pubkey`, and its keccak256
+/// hash is both the view tag, in its first byte, and the tweak of the stealth public key
+/// `spend_pubkey + hash * G`. The view tag is checked first, so that most announcements for
+/// other recipients are discarded without computing the stealth public key.
+///
+/// Returns `Ok(false)` if the view tag or the address does not match. Returns
+/// [`Error::InvalidPublicKey`] for public keys that are not on the curve,
+/// [`Error::InvalidPrivateKey`] if the viewing key is zero or not below the curve order, and
+/// [`Error::InvalidTweak`] if the hash is not below the curve order.
+pub fn check_stealth_address(
+    spend_pubkey: &[u8; 33],
+    viewing_key: &[u8; 32],
+    ephemeral_pubkey: &[u8; 33],
+    expected_address: [u8; 20],
+    view_tag: u8,
+) -> Result<bool, Error> {
+    let viewing_key =
+        Option::<Scalar>::from(Scalar::from_repr(FieldBytes::clone_from_slice(viewing_key)))
+            .filter(|key| *key != Scalar::ZERO)
+            .ok_or(Error::InvalidPrivateKey)?;
+    let ephemeral_pubkey = decompress_checked(ephemeral_pubkey)?;
+
+    let shared_secret = mul_add(&ephemeral_pubkey, &viewing_key, None)?;
+    let hashed_secret = keccak256(shared_secret.to_encoded_point(true).as_bytes());
+    if hashed_secret[0] != view_tag {
+        return Ok(false);
+    }
+
+    // The tweak is rejected rather than reduced, like in the reference implementation.
+    let tweak = Option::<Scalar>::from(Scalar::from_repr(hashed_secret.into()))
+        .ok_or(Error::InvalidTweak)?;
+    let spend_pubkey = decompress_checked(spend_pubkey)?;
+    let stealth_pubkey =
+        mul_add(&spend_pubkey, &Scalar::ONE, Some(&tweak)).map_err(|_| Error::InvalidTweak)?;
+
+    let mut uncompressed = [0u8; 65];
+    uncompressed.copy_from_slice(stealth_pubkey.to_encoded_point(false).as_bytes());
+    Ok(pubkey_to_address(&uncompressed) == expected_address)
+}
+
+/// Decompresses a public key, checking that it is on the curve.
+///
+/// Unlike [`super::decompress_pubkey`], this does not use the precompile inside the zkVM, which
+/// cannot prove that an x-coordinate has no point. Ephemeral keys come from untrusted
+/// announcements, so invalid ones must be rejected rather than make the execution fail.
+fn decompress_checked(compressed: &[u8; 33]) -> Result<PublicKey, Error> {
+    PublicKey::from_sec1_bytes(compressed).map_err(|_| Error::InvalidPublicKey)
+}
+
+/// Computes `a * point + b * G`, returning an error for the point at infinity.
+fn mul_add(point: &PublicKey, a: &Scalar, b: Option<&Scalar>) -> Result<PublicKey, Error> {
+    cfg_if::cfg_if! {
+        if #[cfg(all(target_os = "zkvm", target_vendor = "succinct"))] {
+            use super::{consts, double_and_add_base, Secp256k1AffinePoint};
+            use core::convert::TryInto;
+
+            let encoded = point.to_encoded_point(false);
+            let point = Secp256k1AffinePoint::from_be_coordinates(
+                encoded.as_bytes()[1..33].try_into().unwrap(),
+                encoded.as_bytes()[33..].try_into().unwrap(),
+            );
+            let res = double_and_add_base(a, &point, b.unwrap_or(&Scalar::ZERO), &consts::GENERATOR)
+                .ok_or(Error::InvalidPublicKey)?;
+
+            let mut uncompressed = [4u8; 65];
+            uncompressed[1..33].copy_from_slice(&res.x_be());
+            uncompressed[33..].copy_from_slice(&res.y_be());
+            // The precompiles keep the result on the curve.
+            PublicKey::from_sec1_bytes(&uncompressed).map_err(|_| Error::InvalidPublicKey)
+        } else {
+            use k256::ProjectivePoint;
+
+            let mut res = point.to_projective() * a;
+            if let Some(b) = b {
+                res += ProjectivePoint::GENERATOR * b;
+            }
+            PublicKey::from_affine(res.to_affine()).map_err(|_| Error::InvalidPublicKey)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::{NonZeroScalar, ProjectivePoint};
+
+    fn scalar(byte: u8) -> Scalar {
+        *NonZeroScalar::try_from(&[byte; 32][..]).unwrap()
+    }
+
+    fn compressed(point: ProjectivePoint) -> [u8; 33] {
+        point
+            .to_affine()
+            .to_encoded_point(true)
+            .as_bytes()
+            .try_into()
+            .unwrap()
+    }
+
+    fn address(point: ProjectivePoint) -> [u8; 20] {
+        let encoded = point.to_affine().to_encoded_point(false);
+        alloy_primitives::keccak256(&encoded.as_bytes()[1..])[12..]
+            .try_into()
+            .unwrap()
+    }
+
+    /// An announcement as a sender computes it, from the ephemeral private key and the
+    /// recipient's viewing public key, with the stealth private key that the recipient derives.
+    struct Announcement {
+        ephemeral_pubkey: [u8; 33],
+        address: [u8; 20],
+        view_tag: u8,
+        stealth_key: Scalar,
+    }
+
+    fn announce(spend_key: &Scalar, viewing_key: &Scalar, ephemeral_key: &Scalar) -> Announcement {
+        let shared_secret = compressed(ProjectivePoint::GENERATOR * viewing_key * ephemeral_key);
+        let hashed_secret = alloy_primitives::keccak256(shared_secret);
+        let tweak = Option::<Scalar>::from(Scalar::from_repr(hashed_secret.0.into())).unwrap();
+        let spend_pubkey = ProjectivePoint::GENERATOR * spend_key;
+        Announcement {
+            ephemeral_pubkey: compressed(ProjectivePoint::GENERATOR * ephemeral_key),
+            address: address(spend_pubkey + ProjectivePoint::GENERATOR * tweak),
+            view_tag: hashed_secret[0],
+            stealth_key: *spend_key + tweak,
+        }
+    }
+
+    #[test]
+    fn test_check_stealth_address() {
+        let (spend_key, viewing_key) = (scalar(0x21), scalar(0x22));
+        let spend_pubkey = compressed(ProjectivePoint::GENERATOR * spend_key);
+        let viewing_key_bytes: [u8; 32] = viewing_key.to_bytes().into();
+        for ephemeral in 0x30..0x34 {
+            let announcement = announce(&spend_key, &viewing_key, &scalar(ephemeral));
+            assert_eq!(
+                announcement.address,
+                address(ProjectivePoint::GENERATOR * announcement.stealth_key)
+            );
+            let check = |address: [u8; 20], view_tag: u8| {
+                check_stealth_address(
+                    &spend_pubkey,
+                    &viewing_key_bytes,
+                    &announcement.ephemeral_pubkey,
+                    address,
+                    view_tag,
+                )
+            };
+            assert_eq!(check(announcement.address, announcement.view_tag), Ok(true));
+            assert_eq!(
+                check(announcement.address, announcement.view_tag ^ 1),
+                Ok(false)
+            );
+            let mut other_address = announcement.address;
+            other_address[19] ^= 1;
+            assert_eq!(check(other_address, announcement.view_tag), Ok(false));
+        }
+
+        // An announcement for another recipient.
+        let other = announce(&scalar(0x23), &scalar(0x24), &scalar(0x30));
+        let result = check_stealth_address(
+            &spend_pubkey,
+            &viewing_key_bytes,
+            &other.ephemeral_pubkey,
+            other.address,
+            other.view_tag,
+        );
+        assert_eq!(result, Ok(false));
+    }
+
+    #[test]
+    fn test_check_stealth_address_rejects_invalid_keys() {
+        let (spend_key, viewing_key) = (scalar(0x21), scalar(0x22));
+        let spend_pubkey = compressed(ProjectivePoint::GENERATOR * spend_key);
+        let viewing_key: [u8; 32] = viewing_key.to_bytes().into();
+        let announcement = announce(&spend_key, &scalar(0x22), &scalar(0x30));
+        let check = |spend_pubkey: &[u8; 33], viewing_key: &[u8; 32], ephemeral: &[u8; 33]| {
+            check_stealth_address(
+                spend_pubkey,
+                viewing_key,
+                ephemeral,
+                announcement.address,
+                announcement.view_tag,
+            )
+        };
+
+        let order: [u8; 32] =
+            hex::decode("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        for invalid in [[0; 32], order, [0xff; 32]] {
+            assert_eq!(
+                check(&spend_pubkey, &invalid, &announcement.ephemeral_pubkey),
+                Err(Error::InvalidPrivateKey)
+            );
+        }
+
+        let mut bad_prefix = announcement.ephemeral_pubkey;
+        bad_prefix[0] = 4;
+        // x = 5 is not the x-coordinate of a point, as 5^3 + 7 is not a square.
+        let mut off_curve = [0u8; 33];
+        off_curve[0] = 2;
+        off_curve[32] = 5;
+        for invalid in [bad_prefix, off_curve] {
+            assert_eq!(
+                check(&spend_pubkey, &viewing_key, &invalid),
+                Err(Error::InvalidPublicKey)
+            );
+            assert_eq!(
+                check(&invalid, &viewing_key, &announcement.ephemeral_pubkey),
+                Err(Error::InvalidPublicKey)
+            );
+        }
+    }
+}
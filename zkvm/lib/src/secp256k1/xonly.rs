@@ -0,0 +1,132 @@
+//! x-only public keys, which stand for the point with the given x-coordinate and an even
+//! y-coordinate, as in BIP-340 and Taproot.
+
+use super::Secp256k1AffinePoint;
+use crate::error::Error;
+use core::convert::TryInto;
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::elliptic_curve::PrimeField;
+use k256::{FieldBytes, FieldElement, PublicKey};
+use serde::{Deserialize, Serialize};
+
+/// The parity of the y-coordinate of a point.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Parity {
+    Even,
+    Odd,
+}
+
+/// The big endian x-coordinate of a point with an even y-coordinate.
+///
+/// The bytes are only checked by [`XOnlyPublicKey::lift_x`], so that keys from untrusted sources
+/// can be deserialized and rejected later. Keys are ordered by their bytes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct XOnlyPublicKey([u8; 32]);
+
+impl XOnlyPublicKey {
+    /// Creates a key from its bytes, without checking that they are the x-coordinate of a point.
+    pub const fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// The big endian x-coordinate.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Returns the x-only key of a public key, and the parity of its y-coordinate. The x-only key
+    /// stands for the public key itself if the parity is even, and for its negation otherwise.
+    pub fn from_pubkey(pubkey: &PublicKey) -> (Self, Parity) {
+        let encoded = pubkey.to_encoded_point(false);
+        // The encoding is uncompressed, and public keys are never the point at infinity.
+        let x = encoded.x().unwrap().as_slice().try_into().unwrap();
+        let parity = match encoded.y().unwrap()[31] & 1 {
+            0 => Parity::Even,
+            _ => Parity::Odd,
+        };
+        (Self(x), parity)
+    }
+
+    /// Returns the point with the x-coordinate of the key and an even y-coordinate, the `lift_x`
+    /// function of BIP-340.
+    ///
+    /// Returns [`Error::InvalidPublicKey`] if the x-coordinate is not below the field modulus, or
+    /// if no point has it. Unlike [`super::decompress_pubkey`], both cases are proven inside the
+    /// zkVM rather than making the execution fail, so keys from untrusted sources can be lifted.
+    pub fn lift_x(&self) -> Result<Secp256k1AffinePoint, Error> {
+        let x = Option::<FieldElement>::from(FieldElement::from_repr(
+            FieldBytes::clone_from_slice(&self.0),
+        ))
+        .ok_or(Error::InvalidPublicKey)?;
+        let y =
+            sqrt(&(x.square() * x + FieldElement::from(7u64))).ok_or(Error::InvalidPublicKey)?;
+        let y = if bool::from(y.is_odd()) {
+            (-y).normalize()
+        } else {
+            y
+        };
+        Ok(Secp256k1AffinePoint::from_be_coordinates(
+            &self.0,
+            y.to_bytes().as_slice().try_into().unwrap(),
+        ))
+    }
+}
+
+impl From<[u8; 32]> for XOnlyPublicKey {
+    fn from(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<XOnlyPublicKey> for [u8; 32] {
+    fn from(key: XOnlyPublicKey) -> Self {
+        key.0
+    }
+}
+
+/// Returns a normalized square root of `value`, or `None` if it is not a quadratic residue.
+///
+/// Inside the zkVM, the root is requested from the square root hook and checked by squaring it.
+/// As -1 is not a quadratic residue modulo p, a hint whose square is `-value` proves that `value`
+/// has no root. A hint that fails both checks is ignored, and the root is computed in the VM.
+/// Without the `alloc` feature, or with the `no-hints` feature, the root is always computed in
+/// the VM.
+pub(super) fn sqrt(value: &FieldElement) -> Option<FieldElement> {
+    let value = value.normalize();
+
+    #[cfg(all(
+        target_os = "zkvm",
+        target_vendor = "succinct",
+        feature = "alloc",
+        not(feature = "no-hints")
+    ))]
+    {
+        use super::consts;
+        use crate::io;
+        use crate::unconstrained;
+        use core::convert::TryFrom;
+
+        let value_bytes = value.to_bytes();
+        unconstrained! {
+            let mut buf = [0u8; 64];
+            buf[..32].copy_from_slice(&consts::P);
+            buf[32..].copy_from_slice(&value_bytes);
+            io::write(io::FD_SQRT_HOOK, &buf);
+        }
+
+        let hint = io::read_vec();
+        if let Ok(hint) = <[u8; 32]>::try_from(hint.as_slice()) {
+            if let Some(root) = Option::<FieldElement>::from(FieldElement::from_repr(hint.into())) {
+                let square = root.square().normalize();
+                if square == value {
+                    return Some(root);
+                }
+                if square == (-value).normalize() {
+                    return None;
+                }
+            }
+        }
+    }
+
+    Option::<FieldElement>::from(value.sqrt()).map(|root| root.normalize())
+}
@@ -0,0 +1,366 @@
+//! ECDSA public key recovery on secp256r1 (NIST P-256), the curve of WebAuthn passkeys.
+//!
+//! There is no precompile for P-256, so the point arithmetic is the one of the p256 crate. Inside
+//! the zkVM, [`ecrecover`] reads the key and the inverse of s from the `ecrecover_r1` hook, and
+//! checks them with [`verify_recovered`]: the guest pays for a double scalar multiplication, while
+//! the square root of the nonce point and the inversions of r and s are left to the host.
+
+use crate::error::Error;
+#[cfg(all(feature = "alloc", not(feature = "no-hints")))]
+use crate::io;
+use crate::io::FD_ECRECOVER_R1_HOOK;
+use crate::sha256::{sha256, Sha256};
+#[cfg(all(feature = "alloc", not(feature = "no-hints")))]
+use crate::unconstrained;
+use p256::ecdsa::Signature;
+use p256::elliptic_curve::ops::{LinearCombination, Reduce};
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+#[cfg(all(feature = "alloc", not(feature = "no-hints")))]
+use p256::elliptic_curve::PrimeField;
+use p256::{FieldBytes, ProjectivePoint, PublicKey, Scalar, U256};
+
+/// Returns the hash signed by a WebAuthn assertion, the SHA-256 of the authenticator data
+/// followed by the SHA-256 of the client data JSON.
+pub fn webauthn_message_hash(authenticator_data: &[u8], client_data_json: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(authenticator_data);
+    hasher.update(&sha256(client_data_json));
+    hasher.finalize()
+}
+
+/// Given a signature and a message hash, returns the uncompressed public key that signed the
+/// message.
+///
+/// The last byte of the signature is the recovery id, the parity of the y-coordinate of the nonce
+/// point. Ids of nonce points whose x-coordinate is not below the curve order are not supported.
+/// High s values are accepted, as WebAuthn authenticators are not required to normalize them.
+///
+/// Inside the zkVM, the key and the inverse of s are requested from the `ecrecover_r1` hook and
+/// checked with [`verify_recovered`]. Hook responses can only be read with the `alloc` feature,
+/// so without it, or with the `no-hints` feature, the key is recovered in the VM instead.
+///
+/// Returns [`Error::InvalidSignature`] if r or s is zero or not below the curve order,
+/// [`Error::InvalidRecoveryId`] if the id is not 0 or 1, and [`Error::VerificationFailed`] if no
+/// key recovers.
+pub fn ecrecover(sig: &[u8; 65], msg_hash: &[u8; 32]) -> Result<[u8; 65], Error> {
+    // Parse the signature before querying the hook, so that out of range scalars are rejected.
+    let signature = Signature::from_slice(&sig[..64]).map_err(|_| Error::InvalidSignature)?;
+    let y_odd = match sig[64] {
+        0 => false,
+        1 => true,
+        id => return Err(Error::InvalidRecoveryId(id as u64)),
+    };
+
+    cfg_if::cfg_if! {
+        if #[cfg(all(
+            target_os = "zkvm",
+            target_vendor = "succinct",
+            feature = "alloc",
+            not(feature = "no-hints")
+        ))] {
+            let (pubkey, s_inv) = unconstrained_ecrecover(sig, msg_hash)?;
+            verify_recovered(&pubkey, &s_inv, &signature, y_odd, msg_hash)
+        } else {
+            recover_constrained(&signature, y_odd, msg_hash)
+        }
+    }
+}
+
+/// Outside of the VM, computes the compressed public key and the inverse of s of a signature over
+/// a message hash with the `ecrecover_r1` hook.
+///
+/// WARNING: The values are read from outside of the VM and are not constrained to be correct.
+/// Check them with [`verify_recovered`], or use [`ecrecover`].
+///
+/// Returns an error if the hook reports that the signature is invalid, if the hook responses do not
+/// have the expected lengths, or if the s_inverse value is not a valid scalar.
+///
+/// Not available with the `no-hints` feature, or without the `alloc` feature, since hook responses
+/// are read into freshly allocated buffers.
+#[cfg(all(feature = "alloc", not(feature = "no-hints")))]
+pub fn unconstrained_ecrecover(
+    sig: &[u8; 65],
+    msg_hash: &[u8; 32],
+) -> Result<([u8; 33], Scalar), Error> {
+    unconstrained! {
+        io::write_ecrecover_request(FD_ECRECOVER_R1_HOOK, sig, msg_hash);
+    }
+    let (pubkey, s_inv_bytes) = io::read_ecrecover_response(FD_ECRECOVER_R1_HOOK, sig)?;
+    let s_inverse =
+        Option::<Scalar>::from(Scalar::from_repr(s_inv_bytes.into())).ok_or(Error::BadHint {
+            fd: FD_ECRECOVER_R1_HOOK,
+        })?;
+
+    Ok((pubkey, s_inverse))
+}
+
+/// Checks that the compressed public key and the inverse of s returned by the `ecrecover_r1` hook
+/// recover to the nonce point of the signature, whose y-coordinate has the parity `y_odd`, and
+/// returns the uncompressed encoding of the key.
+///
+/// The hook normalizes high s values, in which case it returns the inverse of -s. Both public keys
+/// whose nonce points share the x-coordinate `r` verify the signature, so the parity of the nonce
+/// point is checked as well.
+///
+/// Returns [`Error::BadHint`] if `s_inv` is not the inverse of s or the key is not a point on the
+/// curve, and [`Error::VerificationFailed`] if the key did not make the signature.
+pub fn verify_recovered(
+    compressed: &[u8; 33],
+    s_inv: &Scalar,
+    signature: &Signature,
+    y_odd: bool,
+    msg_hash: &[u8; 32],
+) -> Result<[u8; 65], Error> {
+    let bad_hint = Error::BadHint {
+        fd: FD_ECRECOVER_R1_HOOK,
+    };
+    let (r, s) = signature.split_scalars();
+    let s_inv = if signature.normalize_s().is_some() {
+        -*s_inv
+    } else {
+        *s_inv
+    };
+    if s_inv * s.as_ref() != Scalar::ONE {
+        return Err(bad_hint);
+    }
+    let pubkey = PublicKey::from_sec1_bytes(compressed).map_err(|_| bad_hint)?;
+
+    let z = message_scalar(msg_hash);
+    let nonce = ProjectivePoint::lincomb(
+        &ProjectivePoint::GENERATOR,
+        &(z * s_inv),
+        &pubkey.to_projective(),
+        &(*r * s_inv),
+    )
+    .to_encoded_point(false);
+
+    // The identity has no coordinates. As recovery ids for x >= n are not supported, the
+    // x-coordinate of the nonce point is r itself rather than r + n.
+    match (nonce.x(), nonce.y()) {
+        (Some(x), Some(y)) if *x == r.to_bytes() && (y[31] & 1 == 1) == y_odd => {}
+        _ => return Err(Error::VerificationFailed),
+    }
+    Ok(uncompressed(&pubkey))
+}
+
+/// Recovers the public key without consuming any hook data, by computing `r^-1 * (s * R - z * G)`,
+/// where `R` is the nonce point.
+#[cfg(not(all(
+    target_os = "zkvm",
+    target_vendor = "succinct",
+    feature = "alloc",
+    not(feature = "no-hints")
+)))]
+fn recover_constrained(
+    signature: &Signature,
+    y_odd: bool,
+    msg_hash: &[u8; 32],
+) -> Result<[u8; 65], Error> {
+    use p256::elliptic_curve::point::DecompressPoint;
+    use p256::elliptic_curve::subtle::Choice;
+    use p256::AffinePoint;
+
+    let (r, s) = signature.split_scalars();
+    let nonce = Option::<AffinePoint>::from(AffinePoint::decompress(
+        &r.to_bytes(),
+        Choice::from(y_odd as u8),
+    ))
+    .ok_or(Error::VerificationFailed)?;
+
+    // `r` is not zero, so it always has an inverse.
+    let r_inv = Option::<Scalar>::from(r.as_ref().invert()).unwrap();
+    let z = message_scalar(msg_hash);
+    let pubkey = ProjectivePoint::lincomb(
+        &ProjectivePoint::from(nonce),
+        &(*s * r_inv),
+        &ProjectivePoint::GENERATOR,
+        &(-z * r_inv),
+    );
+    let pubkey =
+        PublicKey::from_affine(pubkey.to_affine()).map_err(|_| Error::VerificationFailed)?;
+    Ok(uncompressed(&pubkey))
+}
+
+/// Returns the message hash as a scalar, reduced modulo the curve order as in SEC1.
+fn message_scalar(msg_hash: &[u8; 32]) -> Scalar {
+    <Scalar as Reduce<U256>>::reduce_bytes(FieldBytes::from_slice(msg_hash))
+}
+
+fn uncompressed(pubkey: &PublicKey) -> [u8; 65] {
+    let mut bytes = [0u8; 65];
+    bytes.copy_from_slice(pubkey.to_encoded_point(false).as_bytes());
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p256::ecdsa::SigningKey;
+    use p256::elliptic_curve::PrimeField;
+    use std::vec::Vec;
+
+    /// A signature with its recovery id, the message hash, and the responses of the hook to it.
+    type HookedSignature = ([u8; 65], [u8; 32], Vec<Vec<u8>>);
+
+    /// A WebAuthn assertion by the passkey with the secret key `[seed; 32]`, with its signature
+    /// negated to a high s value if `high_s`.
+    fn webauthn_signature(seed: u8, high_s: bool) -> (SigningKey, HookedSignature) {
+        let key = SigningKey::from_slice(&[seed; 32]).unwrap();
+        // The flags and sign count of a user present and verified assertion, after the hash of
+        // the relying party id.
+        let mut authenticator_data = sha256(b"example.com").to_vec();
+        authenticator_data.extend_from_slice(&[0x05, 0, 0, 0, seed]);
+        let client_data_json =
+            br#"{"type":"webauthn.get","challenge":"c3Ax","origin":"https://example.com"}"#;
+        let msg_hash = webauthn_message_hash(&authenticator_data, client_data_json);
+
+        let (signature, recid) = key.sign_prehash_recoverable(&msg_hash).unwrap();
+        let (r, s) = signature.split_scalars();
+        let (s, y_odd) = if high_s {
+            (-*s, !recid.is_y_odd())
+        } else {
+            (*s, recid.is_y_odd())
+        };
+        let mut sig = [0u8; 65];
+        sig[..32].copy_from_slice(&r.to_bytes());
+        sig[32..64].copy_from_slice(&s.to_bytes());
+        sig[64] = y_odd as u8;
+
+        // Like the hook, answer with the inverse of the normalized s.
+        let low_s = if high_s { -s } else { s };
+        let responses = vec![
+            key.verifying_key()
+                .to_encoded_point(true)
+                .as_bytes()
+                .to_vec(),
+            low_s.invert().unwrap().to_bytes().to_vec(),
+        ];
+        (key, (sig, msg_hash, responses))
+    }
+
+    #[test]
+    fn test_webauthn_message_hash() {
+        let mut preimage = b"authenticator data".to_vec();
+        preimage.extend_from_slice(&sha256(b"{}"));
+        assert_eq!(
+            webauthn_message_hash(b"authenticator data", b"{}"),
+            sha256(&preimage)
+        );
+    }
+
+    #[test]
+    fn test_ecrecover() {
+        for high_s in [false, true] {
+            let (key, (sig, msg_hash, _)) = webauthn_signature(7, high_s);
+            let expected = key.verifying_key().to_encoded_point(false);
+            assert_eq!(ecrecover(&sig, &msg_hash).unwrap(), expected.as_bytes());
+
+            // The other parity recovers another key, or none.
+            let mut other = sig;
+            other[64] ^= 1;
+            assert_ne!(
+                ecrecover(&other, &msg_hash).ok().as_ref().map(|k| &k[..]),
+                Some(expected.as_bytes())
+            );
+        }
+
+        let (_, (mut sig, msg_hash, _)) = webauthn_signature(7, false);
+        sig[64] = 2;
+        assert_eq!(ecrecover(&sig, &msg_hash), Err(Error::InvalidRecoveryId(2)));
+        sig[32..64].fill(0);
+        assert_eq!(ecrecover(&sig, &msg_hash), Err(Error::InvalidSignature));
+    }
+
+    #[test]
+    fn test_verify_recovered() {
+        for high_s in [false, true] {
+            let (key, (sig, msg_hash, responses)) = webauthn_signature(9, high_s);
+            let signature = Signature::from_slice(&sig[..64]).unwrap();
+            let compressed: [u8; 33] = responses[0].as_slice().try_into().unwrap();
+            let s_inv = Scalar::from_repr(*FieldBytes::from_slice(&responses[1])).unwrap();
+            let y_odd = sig[64] == 1;
+            let verify = |compressed: &[u8; 33], s_inv: &Scalar, y_odd: bool| {
+                verify_recovered(compressed, s_inv, &signature, y_odd, &msg_hash)
+            };
+
+            assert_eq!(
+                verify(&compressed, &s_inv, y_odd).unwrap(),
+                key.verifying_key().to_encoded_point(false).as_bytes()
+            );
+            let bad_hint = Err(Error::BadHint {
+                fd: FD_ECRECOVER_R1_HOOK,
+            });
+            assert_eq!(verify(&compressed, &(s_inv + Scalar::ONE), y_odd), bad_hint);
+            assert_eq!(verify(&compressed, &-s_inv, y_odd), bad_hint);
+            let mut not_a_point = compressed;
+            not_a_point[1..].fill(0xff);
+            assert_eq!(verify(&not_a_point, &s_inv, y_odd), bad_hint);
+
+            // The key of another passkey, or the right one with the wrong parity, does not verify.
+            let (_, (_, _, other)) = webauthn_signature(10, high_s);
+            let other: [u8; 33] = other[0].as_slice().try_into().unwrap();
+            assert_eq!(
+                verify(&other, &s_inv, y_odd),
+                Err(Error::VerificationFailed)
+            );
+            assert_eq!(
+                verify(&compressed, &s_inv, !y_odd),
+                Err(Error::VerificationFailed)
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "alloc", not(feature = "no-hints")))]
+    fn test_unconstrained_ecrecover() {
+        use crate::io::mock;
+
+        let signatures = [false, true].map(|high_s| webauthn_signature(11, high_s).1);
+        let expected = signatures
+            .iter()
+            .map(|(_, _, responses)| {
+                let s_inv = Scalar::from_repr(*FieldBytes::from_slice(&responses[1])).unwrap();
+                Ok((responses[0].as_slice().try_into().unwrap(), s_inv))
+            })
+            .collect::<Vec<_>>();
+        let hooked = signatures.clone();
+        let hook = move |fd, request: &[u8]| {
+            assert_eq!(fd, FD_ECRECOVER_R1_HOOK);
+            hooked
+                .iter()
+                .find(|(sig, msg_hash, _)| request == [&sig[..], msg_hash].concat())
+                .expect("unknown request")
+                .2
+                .clone()
+        };
+        let (recovered, unread) = mock::with_hook(hook, || {
+            signatures
+                .iter()
+                .map(|(sig, msg_hash, _)| unconstrained_ecrecover(sig, msg_hash))
+                .collect::<Vec<_>>()
+        });
+        assert_eq!((recovered, unread), (expected, 0));
+
+        // A failed request, and an inverse of s that is not below the curve order.
+        let (sig, msg_hash, _) = signatures[0];
+        let (result, unread) = mock::with_hook(
+            |_, _| vec![vec![io::HOOK_STATUS_UNRECOVERABLE]],
+            || unconstrained_ecrecover(&sig, &msg_hash),
+        );
+        assert_eq!((result, unread), (Err(Error::VerificationFailed), 0));
+        let key = signatures[0].2[0].clone();
+        let (result, unread) = mock::with_hook(
+            move |_, _| vec![key.clone(), vec![0xff; 32]],
+            || unconstrained_ecrecover(&sig, &msg_hash),
+        );
+        assert_eq!(
+            (result, unread),
+            (
+                Err(Error::BadHint {
+                    fd: FD_ECRECOVER_R1_HOOK
+                }),
+                0
+            )
+        );
+    }
+}
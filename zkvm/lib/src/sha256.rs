@@ -0,0 +1,104 @@
+//! SHA-256 hashing on top of the sha256_extend and sha256_compress precompiles.
+
+#[cfg(all(target_os = "zkvm", target_vendor = "succinct"))]
+use crate::{syscall_sha256_compress, syscall_sha256_extend};
+
+/// The block size of SHA-256 in bytes.
+const BLOCK_SIZE: usize = 64;
+
+/// The initial hash value of SHA-256.
+const H0: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// An incremental SHA-256 hasher.
+///
+/// Inside the zkVM, each block is processed with the `sha256_extend` and `sha256_compress`
+/// precompiles.
+#[derive(Clone)]
+pub struct Sha256 {
+    state: [u32; 8],
+    buf: [u8; BLOCK_SIZE],
+    buf_len: usize,
+    len: u64,
+}
+
+impl Sha256 {
+    /// Creates a new hasher with the initial state.
+    pub const fn new() -> Self {
+        Self {
+            state: H0,
+            buf: [0; BLOCK_SIZE],
+            buf_len: 0,
+            len: 0,
+        }
+    }
+
+    /// Absorbs `data` into the hasher.
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.len += data.len() as u64;
+        while !data.is_empty() {
+            let take = (BLOCK_SIZE - self.buf_len).min(data.len());
+            self.buf[self.buf_len..self.buf_len + take].copy_from_slice(&data[..take]);
+            self.buf_len += take;
+            data = &data[take..];
+            if self.buf_len == BLOCK_SIZE {
+                compress(&mut self.state, &self.buf);
+                self.buf_len = 0;
+            }
+        }
+    }
+
+    /// Pads the remaining input and returns the digest.
+    pub fn finalize(mut self) -> [u8; 32] {
+        let bit_len = self.len.wrapping_mul(8);
+
+        self.buf[self.buf_len..].fill(0);
+        self.buf[self.buf_len] = 0x80;
+        // The length takes the last 8 bytes, which may need an extra block.
+        if self.buf_len >= BLOCK_SIZE - 8 {
+            compress(&mut self.state, &self.buf);
+            self.buf.fill(0);
+        }
+        self.buf[BLOCK_SIZE - 8..].copy_from_slice(&bit_len.to_be_bytes());
+        compress(&mut self.state, &self.buf);
+
+        let mut out = [0u8; 32];
+        for (chunk, word) in out.chunks_exact_mut(4).zip(self.state.iter()) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        out
+    }
+}
+
+impl Default for Sha256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Computes the SHA-256 digest of `data`.
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    hasher.finalize()
+}
+
+fn compress(state: &mut [u32; 8], block: &[u8; BLOCK_SIZE]) {
+    cfg_if::cfg_if! {
+        if #[cfg(all(target_os = "zkvm", target_vendor = "succinct"))] {
+            let mut w = [0u32; 64];
+            for (word, chunk) in w.iter_mut().zip(block.chunks_exact(4)) {
+                *word = u32::from_be_bytes(chunk.try_into().unwrap());
+            }
+            unsafe {
+                syscall_sha256_extend(w.as_mut_ptr());
+                syscall_sha256_compress(w.as_mut_ptr(), state.as_mut_ptr());
+            }
+        } else {
+            use sha2::digest::generic_array::GenericArray;
+
+            sha2::compress256(state, &[*GenericArray::from_slice(block)]);
+        }
+    }
+}
@@ -0,0 +1,293 @@
+//! Verification of signatures whose algorithm is only known at runtime, for guests that process
+//! signatures from several sources.
+//!
+//! The enums serialize with serde as externally tagged values, a variant index followed by the
+//! fields, so that they only take four bytes more than their fields with bincode.
+
+use crate::ed25519;
+use crate::error::Error;
+use crate::keccak::keccak256;
+use crate::secp256k1::{schnorr, try_verify_signature, HashAlg};
+use crate::sha256::sha256;
+use p256::ecdsa::signature::hazmat::PrehashVerifier;
+use serde::{Deserialize, Serialize};
+
+/// A public key of one of the supported signature algorithms.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnyPublicKey {
+    /// An uncompressed secp256k1 key.
+    EcdsaK256(#[serde(with = "byte_array")] [u8; 65]),
+    /// An uncompressed P-256 key.
+    EcdsaP256(#[serde(with = "byte_array")] [u8; 65]),
+    /// An Ed25519 key, which must be a valid encoding.
+    Ed25519([u8; 32]),
+    /// A BIP-340 x-only secp256k1 key.
+    SchnorrK256([u8; 32]),
+}
+
+/// A signature of one of the supported signature algorithms.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnySignature {
+    /// An ECDSA `r || s` signature over secp256k1, of a message hashed with `hash`.
+    EcdsaK256 {
+        #[serde(with = "byte_array")]
+        sig: [u8; 64],
+        hash: HashAlg,
+    },
+    /// An ECDSA `r || s` signature over P-256, of a message hashed with SHA-256.
+    EcdsaP256 {
+        #[serde(with = "byte_array")]
+        sig: [u8; 64],
+    },
+    /// An Ed25519 signature, as defined in RFC 8032.
+    Ed25519 {
+        #[serde(with = "byte_array")]
+        sig: [u8; 64],
+    },
+    /// A BIP-340 signature.
+    SchnorrK256 {
+        #[serde(with = "byte_array")]
+        sig: [u8; 64],
+    },
+}
+
+/// The signed data, as given to [`verify`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MessageKind<'a> {
+    /// The message itself.
+    Raw(&'a [u8]),
+    /// The digest of the message, for ECDSA signatures. BIP-340 signatures sign the digest as their
+    /// message, and Ed25519 signatures cannot be verified from a digest.
+    Prehashed([u8; 32]),
+}
+
+/// Verifies a signature with the module of its algorithm.
+///
+/// ECDSA signatures of raw messages are verified against the digest of the message, computed with
+/// the hash function of the signature. BIP-340 signatures are verified against the message bytes,
+/// or the digest for prehashed messages, as BIP-340 does not hash the message itself.
+///
+/// Returns `Ok(false)` if the signature does not verify. Returns [`Error::SignatureKeyMismatch`]
+/// if the key and the signature are for different algorithms, [`Error::UnsupportedMessageKind`]
+/// for Ed25519 signatures of a prehashed message, and the errors of the algorithm's module for
+/// invalid keys.
+pub fn verify(
+    pubkey: &AnyPublicKey,
+    msg_or_hash: &MessageKind,
+    sig: &AnySignature,
+) -> Result<bool, Error> {
+    match (pubkey, sig) {
+        (AnyPublicKey::EcdsaK256(key), AnySignature::EcdsaK256 { sig, hash }) => {
+            let digest = match (msg_or_hash, hash) {
+                (MessageKind::Raw(message), HashAlg::Sha256) => sha256(message),
+                (MessageKind::Raw(message), HashAlg::Keccak256) => keccak256(message),
+                (MessageKind::Prehashed(digest), _) => *digest,
+            };
+            let Ok(signature) = k256::ecdsa::Signature::from_slice(sig) else {
+                return Ok(false);
+            };
+            try_verify_signature(key, &digest, &signature, None)
+        }
+        (AnyPublicKey::EcdsaP256(key), AnySignature::EcdsaP256 { sig }) => {
+            let digest = match msg_or_hash {
+                MessageKind::Raw(message) => sha256(message),
+                MessageKind::Prehashed(digest) => *digest,
+            };
+            let key = p256::ecdsa::VerifyingKey::from_sec1_bytes(key)
+                .map_err(|_| Error::InvalidPublicKey)?;
+            let Ok(signature) = p256::ecdsa::Signature::from_slice(sig) else {
+                return Ok(false);
+            };
+            Ok(key.verify_prehash(&digest, &signature).is_ok())
+        }
+        (AnyPublicKey::Ed25519(key), AnySignature::Ed25519 { sig }) => match msg_or_hash {
+            MessageKind::Raw(message) => Ok(ed25519::verify(key, message, sig)),
+            MessageKind::Prehashed(_) => Err(Error::UnsupportedMessageKind),
+        },
+        (AnyPublicKey::SchnorrK256(key), AnySignature::SchnorrK256 { sig }) => {
+            let message = match msg_or_hash {
+                MessageKind::Raw(message) => *message,
+                MessageKind::Prehashed(digest) => &digest[..],
+            };
+            schnorr::verify(key, message, sig)
+        }
+        _ => Err(Error::SignatureKeyMismatch),
+    }
+}
+
+/// Serializes byte arrays as tuples, as serde only implements its traits for arrays of up to 32
+/// elements.
+mod byte_array {
+    use core::fmt;
+    use serde::de::{self, Deserializer, SeqAccess, Visitor};
+    use serde::ser::{SerializeTuple, Serializer};
+
+    pub fn serialize<S: Serializer, const N: usize>(
+        bytes: &[u8; N],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut tuple = serializer.serialize_tuple(N)?;
+        for byte in bytes {
+            tuple.serialize_element(byte)?;
+        }
+        tuple.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<[u8; N], D::Error> {
+        struct BytesVisitor<const N: usize>;
+
+        impl<'de, const N: usize> Visitor<'de> for BytesVisitor<N> {
+            type Value = [u8; N];
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{N} bytes")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<[u8; N], A::Error> {
+                let mut bytes = [0u8; N];
+                for (i, byte) in bytes.iter_mut().enumerate() {
+                    *byte = seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+                }
+                Ok(bytes)
+            }
+        }
+
+        deserializer.deserialize_tuple(N, BytesVisitor::<N>)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p256::ecdsa::signature::hazmat::PrehashSigner;
+
+    fn to_array<const N: usize>(bytes: &[u8]) -> [u8; N] {
+        bytes.try_into().unwrap()
+    }
+
+    /// Verifies a batch of signatures of every algorithm through the single entry point, with
+    /// their keys and signatures round-tripped through bincode as guest inputs would be.
+    #[test]
+    fn test_verify_any_signature_batch() {
+        let message = b"a batch of signatures from several chains";
+        let sha256_digest = sha256(message);
+        let keccak256_digest = keccak256(message);
+
+        // An Ethereum signature, of the Keccak-256 digest.
+        let k256_key = k256::ecdsa::SigningKey::from_slice(&[0x11; 32]).unwrap();
+        let k256_sig: k256::ecdsa::Signature = k256_key.sign_prehash(&keccak256_digest).unwrap();
+        let k256_pubkey = k256_key.verifying_key().to_encoded_point(false);
+        let k256_pubkey = AnyPublicKey::EcdsaK256(to_array(k256_pubkey.as_bytes()));
+        let k256_sig = |hash| AnySignature::EcdsaK256 {
+            sig: to_array(&k256_sig.to_bytes()),
+            hash,
+        };
+
+        // A passkey signature, of the SHA-256 digest.
+        let p256_key = p256::ecdsa::SigningKey::from_slice(&[0x22; 32]).unwrap();
+        let p256_sig: p256::ecdsa::Signature = p256_key.sign_prehash(&sha256_digest).unwrap();
+        let p256_pubkey = p256_key.verifying_key().to_encoded_point(false);
+        let p256_pubkey = AnyPublicKey::EcdsaP256(to_array(p256_pubkey.as_bytes()));
+        let p256_sig = AnySignature::EcdsaP256 {
+            sig: to_array(&p256_sig.to_bytes()),
+        };
+
+        // A BIP-340 signature, whose message is the SHA-256 digest.
+        let schnorr_key = k256::schnorr::SigningKey::from_bytes(&[0x33; 32]).unwrap();
+        let schnorr_sig = schnorr_key
+            .sign_prehash_with_aux_rand(&sha256_digest, &[0; 32])
+            .unwrap();
+        let schnorr_pubkey =
+            AnyPublicKey::SchnorrK256(to_array(&schnorr_key.verifying_key().to_bytes()));
+        let schnorr_sig = AnySignature::SchnorrK256 {
+            sig: to_array(&schnorr_sig.to_bytes()),
+        };
+
+        // Test 1 of RFC 8032, the signature of the empty message.
+        let ed25519_pubkey = AnyPublicKey::Ed25519(to_array(
+            &hex::decode("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a")
+                .unwrap(),
+        ));
+        let ed25519_sig = AnySignature::Ed25519 {
+            sig: to_array(
+                &hex::decode(concat!(
+                    "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555",
+                    "fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b"
+                ))
+                .unwrap(),
+            ),
+        };
+
+        let raw = MessageKind::Raw(message);
+        let batch = [
+            (k256_pubkey, raw, k256_sig(HashAlg::Keccak256), Ok(true)),
+            (
+                k256_pubkey,
+                MessageKind::Prehashed(keccak256_digest),
+                k256_sig(HashAlg::Keccak256),
+                Ok(true),
+            ),
+            (k256_pubkey, raw, k256_sig(HashAlg::Sha256), Ok(false)),
+            (p256_pubkey, raw, p256_sig, Ok(true)),
+            (
+                p256_pubkey,
+                MessageKind::Prehashed(sha256_digest),
+                p256_sig,
+                Ok(true),
+            ),
+            (
+                p256_pubkey,
+                MessageKind::Prehashed(keccak256_digest),
+                p256_sig,
+                Ok(false),
+            ),
+            (
+                schnorr_pubkey,
+                MessageKind::Raw(&sha256_digest),
+                schnorr_sig,
+                Ok(true),
+            ),
+            (
+                schnorr_pubkey,
+                MessageKind::Prehashed(sha256_digest),
+                schnorr_sig,
+                Ok(true),
+            ),
+            (schnorr_pubkey, raw, schnorr_sig, Ok(false)),
+            // The Ed25519 module has no host fallback for its precompiles, so only the checks made
+            // before the signature is verified run here.
+            (
+                ed25519_pubkey,
+                MessageKind::Prehashed(sha256_digest),
+                ed25519_sig,
+                Err(Error::UnsupportedMessageKind),
+            ),
+            // Keys and signatures of different algorithms.
+            (k256_pubkey, raw, p256_sig, Err(Error::SignatureKeyMismatch)),
+            (
+                schnorr_pubkey,
+                raw,
+                k256_sig(HashAlg::Sha256),
+                Err(Error::SignatureKeyMismatch),
+            ),
+            (
+                ed25519_pubkey,
+                MessageKind::Raw(b""),
+                schnorr_sig,
+                Err(Error::SignatureKeyMismatch),
+            ),
+        ];
+
+        for (i, (pubkey, msg, sig, expected)) in batch.into_iter().enumerate() {
+            let pubkey: AnyPublicKey =
+                bincode::deserialize(&bincode::serialize(&pubkey).unwrap()).unwrap();
+            let sig: AnySignature =
+                bincode::deserialize(&bincode::serialize(&sig).unwrap()).unwrap();
+            assert_eq!(verify(&pubkey, &msg, &sig), expected, "entry {i}");
+        }
+    }
+}
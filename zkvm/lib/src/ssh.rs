@@ -0,0 +1,295 @@
+//! Verification of the signatures made by `ssh-keygen -Y sign`, in the SSHSIG format described in
+//! OpenSSH's `PROTOCOL.sshsig`.
+//!
+//! Only ed25519 keys are supported.
+
+use crate::ed25519;
+use crate::error::Error;
+use crate::sha256::sha256;
+use crate::utils::decode_base64;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryInto;
+use sha2::{Digest, Sha512};
+
+const MAGIC_PREAMBLE: &[u8] = b"SSHSIG";
+const SIG_VERSION: u32 = 1;
+const ARMOR_BEGIN: &str = "-----BEGIN SSH SIGNATURE-----";
+const ARMOR_END: &str = "-----END SSH SIGNATURE-----";
+const KEY_TYPE_ED25519: &str = "ssh-ed25519";
+
+/// Verifies an armored SSHSIG signature of `message` against a public key in the OpenSSH format,
+/// such as a line of an `authorized_keys` or `allowed_signers` file without its principals.
+///
+/// Returns `Ok(false)` if the signature is by another key, for another namespace, or does not
+/// verify. Returns [`Error::UnsupportedSshAlgorithm`] for keys other than ed25519 and hash
+/// algorithms other than SHA-256 and SHA-512, and [`Error::SshSignatureMalformed`] if the key or
+/// the signature cannot be decoded.
+pub fn verify_sshsig(
+    public_key_openssh: &str,
+    namespace: &str,
+    message: &[u8],
+    sshsig_pem: &str,
+) -> Result<bool, Error> {
+    let public_key = parse_openssh_public_key(public_key_openssh)?;
+    let blob = dearmor(sshsig_pem).ok_or(Error::SshSignatureMalformed)?;
+
+    let mut reader = Reader(&blob);
+    if reader.bytes(MAGIC_PREAMBLE.len()) != Some(MAGIC_PREAMBLE)
+        || reader.u32() != Some(SIG_VERSION)
+    {
+        return Err(Error::SshSignatureMalformed);
+    }
+    let signer_key = reader.string().ok_or(Error::SshSignatureMalformed)?;
+    let sig_namespace = reader.string().ok_or(Error::SshSignatureMalformed)?;
+    let reserved = reader.string().ok_or(Error::SshSignatureMalformed)?;
+    let hash_algorithm = reader.string().ok_or(Error::SshSignatureMalformed)?;
+    let signature = reader.string().ok_or(Error::SshSignatureMalformed)?;
+    if !reader.0.is_empty() {
+        return Err(Error::SshSignatureMalformed);
+    }
+
+    let signer_key = parse_public_key_blob(signer_key)?;
+    let mut signature = Reader(signature);
+    let sig_type = signature.string().ok_or(Error::SshSignatureMalformed)?;
+    if sig_type != KEY_TYPE_ED25519.as_bytes() {
+        return Err(Error::UnsupportedSshAlgorithm);
+    }
+    let signature: &[u8; 64] = signature
+        .string()
+        .filter(|_| signature.0.is_empty())
+        .and_then(|signature| signature.try_into().ok())
+        .ok_or(Error::SshSignatureMalformed)?;
+
+    let digest = match hash_algorithm {
+        b"sha512" => Sha512::digest(message).to_vec(),
+        b"sha256" => sha256(message).to_vec(),
+        _ => return Err(Error::UnsupportedSshAlgorithm),
+    };
+
+    if signer_key != public_key || sig_namespace != namespace.as_bytes() {
+        return Ok(false);
+    }
+
+    // The signature is over the preamble and the fields, with the hash of the message in place of
+    // the public key and the signature.
+    let mut signed_data = Vec::new();
+    signed_data.extend_from_slice(MAGIC_PREAMBLE);
+    for field in [sig_namespace, reserved, hash_algorithm, &digest[..]] {
+        put_string(&mut signed_data, field);
+    }
+    Ok(ed25519::verify(&public_key, &signed_data, signature))
+}
+
+/// Parses a public key in the OpenSSH format, `ssh-ed25519 <base64 blob> [comment]`.
+fn parse_openssh_public_key(public_key: &str) -> Result<[u8; 32], Error> {
+    let mut fields = public_key.split_whitespace();
+    let key_type = fields.next().ok_or(Error::SshSignatureMalformed)?;
+    if key_type != KEY_TYPE_ED25519 {
+        return Err(Error::UnsupportedSshAlgorithm);
+    }
+    let blob = fields
+        .next()
+        .and_then(decode_base64)
+        .ok_or(Error::SshSignatureMalformed)?;
+    parse_public_key_blob(&blob)
+}
+
+/// Parses the wire encoding of a public key, the strings of its type and of its key bytes.
+fn parse_public_key_blob(blob: &[u8]) -> Result<[u8; 32], Error> {
+    let mut reader = Reader(blob);
+    let key_type = reader.string().ok_or(Error::SshSignatureMalformed)?;
+    if key_type != KEY_TYPE_ED25519.as_bytes() {
+        return Err(Error::UnsupportedSshAlgorithm);
+    }
+    reader
+        .string()
+        .filter(|_| reader.0.is_empty())
+        .and_then(|key| key.try_into().ok())
+        .ok_or(Error::SshSignatureMalformed)
+}
+
+/// Removes the armor of a signature and decodes its base64 body.
+fn dearmor(armored: &str) -> Option<Vec<u8>> {
+    let body = armored
+        .trim()
+        .strip_prefix(ARMOR_BEGIN)?
+        .strip_suffix(ARMOR_END)?;
+    decode_base64(&body.split_whitespace().collect::<String>())
+}
+
+/// Appends an SSH string, its big endian 32-bit length followed by its bytes.
+fn put_string(buf: &mut Vec<u8>, bytes: &[u8]) {
+    buf.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+    buf.extend_from_slice(bytes);
+}
+
+/// A reader over SSH wire encoded data.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.0.len() < len {
+            return None;
+        }
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Some(bytes)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_be_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn string(&mut self) -> Option<&'a [u8]> {
+        let len = self.u32()?;
+        self.bytes(len as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ed25519::host;
+    use crate::utils::encode_base64;
+
+    const NAMESPACE: &str = "file";
+    const MESSAGE: &[u8] = b"This is a test message.\n";
+
+    fn string(bytes: &[u8]) -> Vec<u8> {
+        let mut buf = Vec::new();
+        put_string(&mut buf, bytes);
+        buf
+    }
+
+    fn public_key_blob(public_key: &[u8; 32]) -> Vec<u8> {
+        [string(KEY_TYPE_ED25519.as_bytes()), string(public_key)].concat()
+    }
+
+    fn openssh_public_key(public_key: &[u8; 32]) -> String {
+        format!(
+            "{KEY_TYPE_ED25519} {} user@host",
+            encode_base64(&public_key_blob(public_key))
+        )
+    }
+
+    /// Wraps a blob in the armor of `ssh-keygen`, with lines of 70 characters.
+    fn armor(blob: &[u8]) -> String {
+        let body = encode_base64(blob);
+        let lines: Vec<&str> = body
+            .as_bytes()
+            .chunks(70)
+            .map(|line| core::str::from_utf8(line).unwrap())
+            .collect();
+        format!("{ARMOR_BEGIN}\n{}\n{ARMOR_END}\n", lines.join("\n"))
+    }
+
+    /// Returns the fields of an SSHSIG blob, signed as `ssh-keygen -Y sign` does.
+    fn sshsig_fields(seed: &[u8; 32], namespace: &str, hash_algorithm: &str) -> Vec<Vec<u8>> {
+        let digest = match hash_algorithm {
+            "sha256" => sha256(MESSAGE).to_vec(),
+            _ => Sha512::digest(MESSAGE).to_vec(),
+        };
+        let mut signed_data = MAGIC_PREAMBLE.to_vec();
+        for field in [
+            namespace.as_bytes(),
+            b"",
+            hash_algorithm.as_bytes(),
+            &digest,
+        ] {
+            put_string(&mut signed_data, field);
+        }
+        let (public_key, signature) = host::sign(seed, &signed_data);
+        let signature_blob = [string(KEY_TYPE_ED25519.as_bytes()), string(&signature)].concat();
+        vec![
+            MAGIC_PREAMBLE.to_vec(),
+            SIG_VERSION.to_be_bytes().to_vec(),
+            string(&public_key_blob(&public_key)),
+            string(namespace.as_bytes()),
+            string(b""),
+            string(hash_algorithm.as_bytes()),
+            string(&signature_blob),
+        ]
+    }
+
+    #[test]
+    fn test_verify_sshsig() {
+        let seed = [7; 32];
+        let (public_key, _) = host::sign(&seed, b"");
+        let openssh = openssh_public_key(&public_key);
+        for hash_algorithm in ["sha512", "sha256"] {
+            let signature = armor(&sshsig_fields(&seed, NAMESPACE, hash_algorithm).concat());
+            assert_eq!(
+                verify_sshsig(&openssh, NAMESPACE, MESSAGE, &signature),
+                Ok(true)
+            );
+            assert_eq!(
+                verify_sshsig(&openssh, "git", MESSAGE, &signature),
+                Ok(false)
+            );
+            assert_eq!(
+                verify_sshsig(&openssh, NAMESPACE, b"another message", &signature),
+                Ok(false)
+            );
+        }
+
+        let signature = armor(&sshsig_fields(&seed, NAMESPACE, "sha512").concat());
+        let (other_key, _) = host::sign(&[8; 32], b"");
+        assert_eq!(
+            verify_sshsig(
+                &openssh_public_key(&other_key),
+                NAMESPACE,
+                MESSAGE,
+                &signature
+            ),
+            Ok(false)
+        );
+
+        // The last byte of the blob is in S.
+        let mut blob = sshsig_fields(&seed, NAMESPACE, "sha512").concat();
+        *blob.last_mut().unwrap() ^= 1;
+        assert_eq!(
+            verify_sshsig(&openssh, NAMESPACE, MESSAGE, &armor(&blob)),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn test_verify_sshsig_rejects_malformed_signatures() {
+        let seed = [7; 32];
+        let (public_key, _) = host::sign(&seed, b"");
+        let openssh = openssh_public_key(&public_key);
+        let verify = |blob: &[u8]| verify_sshsig(&openssh, NAMESPACE, MESSAGE, &armor(blob));
+
+        let mut fields = sshsig_fields(&seed, NAMESPACE, "sha512");
+        fields[5] = string(b"sha1");
+        assert_eq!(
+            verify(&fields.concat()),
+            Err(Error::UnsupportedSshAlgorithm)
+        );
+
+        let mut fields = sshsig_fields(&seed, NAMESPACE, "sha512");
+        fields[1] = 2u32.to_be_bytes().to_vec();
+        assert_eq!(verify(&fields.concat()), Err(Error::SshSignatureMalformed));
+
+        let mut blob = sshsig_fields(&seed, NAMESPACE, "sha512").concat();
+        blob.push(0);
+        assert_eq!(verify(&blob), Err(Error::SshSignatureMalformed));
+        blob.truncate(blob.len() - 2);
+        assert_eq!(verify(&blob), Err(Error::SshSignatureMalformed));
+
+        let signature = armor(&sshsig_fields(&seed, NAMESPACE, "sha512").concat());
+        assert_eq!(
+            verify_sshsig(&openssh, NAMESPACE, MESSAGE, &signature[1..]),
+            Err(Error::SshSignatureMalformed)
+        );
+        assert_eq!(
+            verify_sshsig("ssh-rsa AAAAB3NzaC1yc2E=", NAMESPACE, MESSAGE, &signature),
+            Err(Error::UnsupportedSshAlgorithm)
+        );
+        assert_eq!(
+            verify_sshsig("ssh-ed25519 AAAA!", NAMESPACE, MESSAGE, &signature),
+            Err(Error::SshSignatureMalformed)
+        );
+    }
+}
@@ -0,0 +1,402 @@
+//! Verification of Tendermint and CometBFT commits, as done by light clients.
+//!
+//! Validators sign the protobuf encoding of a `CanonicalVote`, prefixed with its length. Each
+//! signature of a commit has its own timestamp, which is part of the signed vote.
+
+use crate::ed25519;
+use crate::error::Error;
+use crate::sha256::sha256;
+use alloc::vec::Vec;
+use core::convert::TryInto;
+
+/// The `SignedMsgType` of precommits, the votes that make up a commit.
+const PRECOMMIT_TYPE: u64 = 2;
+
+const WIRE_VARINT: u8 = 0;
+const WIRE_FIXED64: u8 = 1;
+const WIRE_LEN: u8 = 2;
+
+/// The header of the parts that a block is split into for gossiping.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PartSetHeader {
+    pub total: u32,
+    pub hash: [u8; 32],
+}
+
+/// The identifier of a block, made of the header hash and the part set header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockId {
+    pub hash: [u8; 32],
+    pub part_set_header: PartSetHeader,
+}
+
+/// A protobuf `Timestamp`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Timestamp {
+    pub seconds: i64,
+    pub nanos: i32,
+}
+
+/// What a validator voted for in a commit.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BlockIdFlag {
+    /// The validator's vote was not received.
+    Absent,
+    /// The validator voted for the block of the commit.
+    Commit,
+    /// The validator voted for no block.
+    Nil,
+}
+
+/// The signature of a validator in a commit, ordered like the validator set.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitSig {
+    pub block_id_flag: BlockIdFlag,
+    pub validator_address: [u8; 20],
+    pub timestamp: Timestamp,
+    /// The Ed25519 signature, which is ignored for absent votes.
+    pub signature: [u8; 64],
+}
+
+/// The precommits for a block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Commit {
+    pub height: i64,
+    pub round: i32,
+    pub block_id: BlockId,
+    pub signatures: Vec<CommitSig>,
+}
+
+/// A validator with an Ed25519 key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Validator {
+    pub pub_key: [u8; 32],
+    pub voting_power: i64,
+}
+
+impl Validator {
+    /// Returns the address of the validator, the first 20 bytes of the SHA-256 of its key.
+    pub fn address(&self) -> [u8; 20] {
+        sha256(&self.pub_key)[..20].try_into().unwrap()
+    }
+}
+
+/// Verifies that more than two thirds of the voting power of `validators` signed a commit for the
+/// block with the given header hash, on the chain `chain_id`.
+///
+/// Only the votes for the block count: absent and nil votes are skipped, and their signatures
+/// are not checked. The signatures are checked one at a time, as a batched check would not
+/// accept exactly the same signatures as [`ed25519::verify`]. The height of the commit must be
+/// checked against the header by the caller, and the validator keys must be valid encodings.
+///
+/// Returns `Ok(false)` if the commit is for another block, if a signature does not verify, or if
+/// the signed voting power is two thirds or less. Returns [`Error::TendermintMalformed`] if the
+/// signatures do not match the validator set, or if a voting power is negative or the total
+/// overflows.
+pub fn verify_commit(
+    header_hash: &[u8; 32],
+    commit: &Commit,
+    validators: &[Validator],
+    chain_id: &str,
+) -> Result<bool, Error> {
+    if commit.signatures.len() != validators.len() {
+        return Err(Error::TendermintMalformed);
+    }
+    if commit.block_id.hash != *header_hash {
+        return Ok(false);
+    }
+
+    let mut total_power: i64 = 0;
+    let mut signed_power: i64 = 0;
+    for (sig, validator) in commit.signatures.iter().zip(validators) {
+        if validator.voting_power < 0 {
+            return Err(Error::TendermintMalformed);
+        }
+        total_power = total_power
+            .checked_add(validator.voting_power)
+            .ok_or(Error::TendermintMalformed)?;
+
+        if sig.block_id_flag == BlockIdFlag::Absent {
+            continue;
+        }
+        if sig.validator_address != validator.address() {
+            return Err(Error::TendermintMalformed);
+        }
+        if sig.block_id_flag == BlockIdFlag::Nil {
+            continue;
+        }
+
+        let sign_bytes = vote_sign_bytes(commit, &sig.timestamp, chain_id);
+        if !ed25519::verify(&validator.pub_key, &sign_bytes, &sig.signature) {
+            return Ok(false);
+        }
+        // The sum of the powers is at most the total, which did not overflow.
+        signed_power += validator.voting_power;
+    }
+
+    Ok(signed_power as i128 * 3 > total_power as i128 * 2)
+}
+
+/// Encodes the length-prefixed `CanonicalVote` for the block of a commit, with the timestamp of
+/// one of its signatures.
+///
+/// As in proto3, fields with default values are omitted, except for the non-nullable part set
+/// header and timestamp.
+fn vote_sign_bytes(commit: &Commit, timestamp: &Timestamp, chain_id: &str) -> Vec<u8> {
+    let mut part_set_header = Vec::new();
+    if commit.block_id.part_set_header.total != 0 {
+        put_varint_field(
+            &mut part_set_header,
+            1,
+            commit.block_id.part_set_header.total as u64,
+        );
+    }
+    put_bytes_field(
+        &mut part_set_header,
+        2,
+        &commit.block_id.part_set_header.hash,
+    );
+
+    let mut block_id = Vec::new();
+    put_bytes_field(&mut block_id, 1, &commit.block_id.hash);
+    put_bytes_field(&mut block_id, 2, &part_set_header);
+
+    let mut time = Vec::new();
+    if timestamp.seconds != 0 {
+        put_varint_field(&mut time, 1, timestamp.seconds as u64);
+    }
+    if timestamp.nanos != 0 {
+        // Negative int32 values are sign extended to 64 bits.
+        put_varint_field(&mut time, 2, timestamp.nanos as i64 as u64);
+    }
+
+    let mut vote = Vec::new();
+    put_varint_field(&mut vote, 1, PRECOMMIT_TYPE);
+    // The height and round are sfixed64.
+    if commit.height != 0 {
+        put_key(&mut vote, 2, WIRE_FIXED64);
+        vote.extend_from_slice(&commit.height.to_le_bytes());
+    }
+    if commit.round != 0 {
+        put_key(&mut vote, 3, WIRE_FIXED64);
+        vote.extend_from_slice(&(commit.round as i64).to_le_bytes());
+    }
+    put_bytes_field(&mut vote, 4, &block_id);
+    put_bytes_field(&mut vote, 5, &time);
+    if !chain_id.is_empty() {
+        put_bytes_field(&mut vote, 6, chain_id.as_bytes());
+    }
+
+    let mut sign_bytes = Vec::with_capacity(vote.len() + 2);
+    put_varint(&mut sign_bytes, vote.len() as u64);
+    sign_bytes.extend_from_slice(&vote);
+    sign_bytes
+}
+
+fn put_key(buf: &mut Vec<u8>, field: u8, wire_type: u8) {
+    buf.push((field << 3) | wire_type);
+}
+
+fn put_varint_field(buf: &mut Vec<u8>, field: u8, value: u64) {
+    put_key(buf, field, WIRE_VARINT);
+    put_varint(buf, value);
+}
+
+fn put_bytes_field(buf: &mut Vec<u8>, field: u8, bytes: &[u8]) {
+    put_key(buf, field, WIRE_LEN);
+    put_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+fn put_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ed25519::host;
+    use std::vec;
+
+    const CHAIN_ID: &str = "test-chain-1";
+    const HEADER_HASH: [u8; 32] = [0xaa; 32];
+    const TIME: Timestamp = Timestamp {
+        seconds: 1_717_200_000,
+        nanos: 123_456_789,
+    };
+
+    fn commit(height: i64, round: i32) -> Commit {
+        Commit {
+            height,
+            round,
+            block_id: BlockId {
+                hash: HEADER_HASH,
+                part_set_header: PartSetHeader {
+                    total: 1,
+                    hash: [0xbb; 32],
+                },
+            },
+            signatures: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_vote_sign_bytes() {
+        // The timestamp of the zero `time.Time` of Go, as in the sign bytes test vectors of
+        // CometBFT.
+        let zero_time = Timestamp {
+            seconds: -62135596800,
+            nanos: 0,
+        };
+        let mut expected = vec![0x6b, 0x08, 0x02];
+        expected.extend_from_slice(&[0x11, 1, 0, 0, 0, 0, 0, 0, 0]);
+        expected.extend_from_slice(&[0x19, 1, 0, 0, 0, 0, 0, 0, 0]);
+        expected.extend_from_slice(&[0x22, 0x48, 0x0a, 0x20]);
+        expected.extend_from_slice(&[0xaa; 32]);
+        expected.extend_from_slice(&[0x12, 0x24, 0x08, 0x01, 0x12, 0x20]);
+        expected.extend_from_slice(&[0xbb; 32]);
+        expected.extend_from_slice(&[
+            0x2a, 0x0b, 0x08, 0x80, 0x92, 0xb8, 0xc3, 0x98, 0xfe, 0xff, 0xff, 0xff, 0x01,
+        ]);
+        assert_eq!(vote_sign_bytes(&commit(1, 1), &zero_time, ""), expected);
+
+        // Fields with default values are omitted, but not the part set header and timestamp.
+        let mut empty = commit(0, 0);
+        empty.block_id.part_set_header.total = 0;
+        let mut expected = vec![0x4f, 0x08, 0x02, 0x22, 0x46, 0x0a, 0x20];
+        expected.extend_from_slice(&[0xaa; 32]);
+        expected.extend_from_slice(&[0x12, 0x22, 0x12, 0x20]);
+        expected.extend_from_slice(&[0xbb; 32]);
+        expected.extend_from_slice(&[0x2a, 0x00, 0x32, 0x01, b'c']);
+        let epoch = Timestamp {
+            seconds: 0,
+            nanos: 0,
+        };
+        assert_eq!(vote_sign_bytes(&empty, &epoch, "c"), expected);
+    }
+
+    /// The validators with the seeds 1 to `powers.len()`, and their keys.
+    fn validators(powers: &[i64]) -> Vec<([u8; 32], Validator)> {
+        powers
+            .iter()
+            .enumerate()
+            .map(|(i, &voting_power)| {
+                let seed = [i as u8 + 1; 32];
+                let (pub_key, _) = host::sign(&seed, b"");
+                (
+                    seed,
+                    Validator {
+                        pub_key,
+                        voting_power,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Signs `commit` with the validators, voting as given by `flags`.
+    fn sign_commit(
+        mut commit: Commit,
+        validators: &[([u8; 32], Validator)],
+        flags: &[BlockIdFlag],
+    ) -> (Commit, Vec<Validator>) {
+        for ((seed, validator), &block_id_flag) in validators.iter().zip(flags) {
+            let signature = match block_id_flag {
+                BlockIdFlag::Commit => {
+                    host::sign(seed, &vote_sign_bytes(&commit, &TIME, CHAIN_ID)).1
+                }
+                _ => [0; 64],
+            };
+            let validator_address = match block_id_flag {
+                BlockIdFlag::Absent => [0; 20],
+                _ => validator.address(),
+            };
+            commit.signatures.push(CommitSig {
+                block_id_flag,
+                validator_address,
+                timestamp: TIME,
+                signature,
+            });
+        }
+        let validators = validators.iter().map(|(_, v)| v.clone()).collect();
+        (commit, validators)
+    }
+
+    #[test]
+    fn test_verify_commit() {
+        use BlockIdFlag::{Absent, Commit, Nil};
+        let cases: [(&[i64], &[BlockIdFlag], bool); 5] = [
+            (&[10, 10, 10, 10], &[Commit, Commit, Commit, Commit], true),
+            (&[10, 10, 10, 10], &[Commit, Absent, Commit, Commit], true),
+            // Half of the power, then exactly two thirds of it.
+            (&[10, 10, 10, 10], &[Commit, Nil, Absent, Commit], false),
+            (&[10, 10, 10], &[Nil, Commit, Commit], false),
+            (&[41, 10, 10], &[Commit, Absent, Nil], true),
+        ];
+        for (powers, flags, expected) in cases {
+            let (commit, validators) = sign_commit(commit(100, 0), &validators(powers), flags);
+            assert_eq!(
+                verify_commit(&HEADER_HASH, &commit, &validators, CHAIN_ID),
+                Ok(expected),
+                "{powers:?} {flags:?}"
+            );
+        }
+
+        let set = validators(&[10, 10, 10, 10]);
+        let (signed, validators) =
+            sign_commit(commit(100, 0), &set, &[Commit, Commit, Commit, Absent]);
+        assert_eq!(
+            verify_commit(&[0xab; 32], &signed, &validators, CHAIN_ID),
+            Ok(false)
+        );
+        assert_eq!(
+            verify_commit(&HEADER_HASH, &signed, &validators, "test-chain-2"),
+            Ok(false)
+        );
+        let mut later = signed.clone();
+        later.signatures[1].timestamp.nanos += 1;
+        assert_eq!(
+            verify_commit(&HEADER_HASH, &later, &validators, CHAIN_ID),
+            Ok(false)
+        );
+        let mut other_round = signed;
+        other_round.round = 1;
+        assert_eq!(
+            verify_commit(&HEADER_HASH, &other_round, &validators, CHAIN_ID),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn test_verify_commit_rejects_malformed_commits() {
+        use BlockIdFlag::{Absent, Commit};
+        let set = validators(&[10, 10, 10, 10]);
+        let (signed, validators) =
+            sign_commit(commit(7, 0), &set, &[Commit, Commit, Commit, Absent]);
+        let verify = |commit: &super::Commit, validators: &[Validator]| {
+            verify_commit(&HEADER_HASH, commit, validators, CHAIN_ID)
+        };
+        assert_eq!(verify(&signed, &validators), Ok(true));
+
+        assert_eq!(
+            verify(&signed, &validators[..3]),
+            Err(Error::TendermintMalformed)
+        );
+        let mut swapped = validators.clone();
+        swapped.swap(0, 1);
+        assert_eq!(verify(&signed, &swapped), Err(Error::TendermintMalformed));
+        let mut negative = validators.clone();
+        negative[3].voting_power = -1;
+        assert_eq!(verify(&signed, &negative), Err(Error::TendermintMalformed));
+        let mut overflowing = validators;
+        overflowing[1].voting_power = i64::MAX;
+        assert_eq!(
+            verify(&signed, &overflowing),
+            Err(Error::TendermintMalformed)
+        );
+    }
+}
@@ -0,0 +1,191 @@
+//! Verification of the TLS 1.3 CertificateVerify message of a server, as in RFC 8446 section
+//! 4.4.3.
+
+use crate::error::Error;
+use crate::sha256::Sha256;
+use crate::x509::{parse_ec_spki, parse_ecdsa_signature, Curve};
+use p256::ecdsa::signature::hazmat::PrehashVerifier;
+
+/// The context string of the signatures made by servers.
+const SERVER_CONTEXT: &[u8] = b"TLS 1.3, server CertificateVerify";
+
+/// A TLS 1.3 signature scheme.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SignatureScheme {
+    EcdsaSecp256r1Sha256,
+    EcdsaSecp384r1Sha384,
+    RsaPssRsaeSha256,
+    RsaPssRsaeSha384,
+    RsaPssRsaeSha512,
+    Ed25519,
+    /// Any other scheme, with its code point, which is never the one of the other variants.
+    Other(u16),
+}
+
+impl From<u16> for SignatureScheme {
+    fn from(code: u16) -> Self {
+        match code {
+            0x0403 => Self::EcdsaSecp256r1Sha256,
+            0x0503 => Self::EcdsaSecp384r1Sha384,
+            0x0804 => Self::RsaPssRsaeSha256,
+            0x0805 => Self::RsaPssRsaeSha384,
+            0x0806 => Self::RsaPssRsaeSha512,
+            0x0807 => Self::Ed25519,
+            _ => Self::Other(code),
+        }
+    }
+}
+
+impl From<SignatureScheme> for u16 {
+    fn from(scheme: SignatureScheme) -> Self {
+        match scheme {
+            SignatureScheme::EcdsaSecp256r1Sha256 => 0x0403,
+            SignatureScheme::EcdsaSecp384r1Sha384 => 0x0503,
+            SignatureScheme::RsaPssRsaeSha256 => 0x0804,
+            SignatureScheme::RsaPssRsaeSha384 => 0x0805,
+            SignatureScheme::RsaPssRsaeSha512 => 0x0806,
+            SignatureScheme::Ed25519 => 0x0807,
+            SignatureScheme::Other(code) => code,
+        }
+    }
+}
+
+/// Verifies the signature of a server's CertificateVerify message with the DER
+/// SubjectPublicKeyInfo of its certificate, where `transcript_hash` is the SHA-256 transcript
+/// hash up to and including the Certificate message.
+///
+/// Only `ecdsa_secp256r1_sha256` is supported, and the other schemes are reported as
+/// [`Error::UnsupportedSignatureScheme`] so that callers can fall back to another verifier.
+/// Returns `Ok(false)` if the signature does not verify.
+pub fn verify_certificate_verify(
+    spki_der: &[u8],
+    transcript_hash: &[u8; 32],
+    scheme: SignatureScheme,
+    signature: &[u8],
+) -> Result<bool, Error> {
+    if scheme != SignatureScheme::EcdsaSecp256r1Sha256 {
+        return Err(Error::UnsupportedSignatureScheme(scheme.into()));
+    }
+    let key = match parse_ec_spki(spki_der, 0) {
+        Ok((Curve::P256, key)) => key,
+        Ok(_) | Err(Error::UnsupportedAlgorithm { .. }) => {
+            return Err(Error::UnsupportedSignatureScheme(scheme.into()))
+        }
+        Err(err) => return Err(err),
+    };
+    let key =
+        p256::ecdsa::VerifyingKey::from_sec1_bytes(key).map_err(|_| Error::InvalidPublicKey)?;
+
+    let Some((r, s)) = parse_ecdsa_signature(signature) else {
+        return Ok(false);
+    };
+    let Ok(signature) = p256::ecdsa::Signature::from_scalars(r, s) else {
+        return Ok(false);
+    };
+
+    // The signed content is 64 spaces, the context string, a zero byte and the transcript hash.
+    let mut hasher = Sha256::new();
+    hasher.update(&[0x20; 64]);
+    hasher.update(SERVER_CONTEXT);
+    hasher.update(&[0]);
+    hasher.update(transcript_hash);
+    Ok(key.verify_prehash(&hasher.finalize(), &signature).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha256::sha256;
+    use crate::x509::tests::{ec_spki, ecdsa_der, p256_spki};
+    use p256::ecdsa::signature::hazmat::PrehashSigner;
+    use std::vec::Vec;
+
+    const TRANSCRIPT_HASH: [u8; 32] = [0x7e; 32];
+
+    /// Signs a transcript hash with the given context string, as a TLS 1.3 peer would.
+    fn sign(key: &p256::ecdsa::SigningKey, context: &[u8], transcript_hash: &[u8; 32]) -> Vec<u8> {
+        let content = [&[0x20; 64][..], context, &[0], transcript_hash].concat();
+        let signature: p256::ecdsa::Signature = key.sign_prehash(&sha256(&content)).unwrap();
+        let (r, s) = signature.split_bytes();
+        ecdsa_der(&r, &s)
+    }
+
+    #[test]
+    fn test_verify_certificate_verify() {
+        let key = p256::ecdsa::SigningKey::from_slice(&[9; 32]).unwrap();
+        let spki = p256_spki(&key);
+        let signature = sign(&key, SERVER_CONTEXT, &TRANSCRIPT_HASH);
+        let verify = |transcript_hash: &[u8; 32], signature: &[u8]| {
+            verify_certificate_verify(
+                &spki,
+                transcript_hash,
+                SignatureScheme::EcdsaSecp256r1Sha256,
+                signature,
+            )
+        };
+        assert_eq!(verify(&TRANSCRIPT_HASH, &signature), Ok(true));
+        assert_eq!(verify(&[0x7f; 32], &signature), Ok(false));
+
+        // A client's signature of the same transcript is not a server's.
+        let client = sign(&key, b"TLS 1.3, client CertificateVerify", &TRANSCRIPT_HASH);
+        assert_eq!(verify(&TRANSCRIPT_HASH, &client), Ok(false));
+
+        let other_key = p256::ecdsa::SigningKey::from_slice(&[10; 32]).unwrap();
+        let other = sign(&other_key, SERVER_CONTEXT, &TRANSCRIPT_HASH);
+        assert_eq!(verify(&TRANSCRIPT_HASH, &other), Ok(false));
+
+        // Signatures that are not DER, or whose scalars are out of range.
+        assert_eq!(verify(&TRANSCRIPT_HASH, &signature[1..]), Ok(false));
+        assert_eq!(
+            verify(&TRANSCRIPT_HASH, &ecdsa_der(&[0; 32], &[1; 32])),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn test_verify_certificate_verify_rejects_other_schemes() {
+        let key = p256::ecdsa::SigningKey::from_slice(&[9; 32]).unwrap();
+        let signature = sign(&key, SERVER_CONTEXT, &TRANSCRIPT_HASH);
+        for code in [0x0503, 0x0804, 0x0807, 0x0601] {
+            assert_eq!(
+                verify_certificate_verify(
+                    &p256_spki(&key),
+                    &TRANSCRIPT_HASH,
+                    code.into(),
+                    &signature
+                ),
+                Err(Error::UnsupportedSignatureScheme(code))
+            );
+        }
+
+        // A secp256k1 key cannot be used with ecdsa_secp256r1_sha256.
+        let secp256k1 = k256::ecdsa::SigningKey::from_slice(&[9; 32]).unwrap();
+        let spki = ec_spki(
+            &[0x2b, 0x81, 0x04, 0x00, 0x0a],
+            secp256k1.verifying_key().to_encoded_point(false).as_bytes(),
+        );
+        let scheme = SignatureScheme::EcdsaSecp256r1Sha256;
+        assert_eq!(
+            verify_certificate_verify(&spki, &TRANSCRIPT_HASH, scheme, &signature),
+            Err(Error::UnsupportedSignatureScheme(0x0403))
+        );
+        assert_eq!(
+            verify_certificate_verify(&[0x30, 0x00], &TRANSCRIPT_HASH, scheme, &signature),
+            Err(Error::InvalidPublicKey)
+        );
+    }
+
+    #[test]
+    fn test_signature_scheme_code_points() {
+        for code in [
+            0x0403, 0x0503, 0x0804, 0x0805, 0x0806, 0x0807, 0x0601, 0xfe00,
+        ] {
+            assert_eq!(u16::from(SignatureScheme::from(code)), code);
+        }
+        assert_eq!(SignatureScheme::from(0x0807), SignatureScheme::Ed25519);
+        assert_eq!(
+            SignatureScheme::from(0x0601),
+            SignatureScheme::Other(0x0601)
+        );
+    }
+}
@@ -1,3 +1,6 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
 pub trait CurveOperations<const NUM_WORDS: usize> {
     const GENERATOR: [u32; NUM_WORDS];
 
@@ -5,10 +8,40 @@ pub trait CurveOperations<const NUM_WORDS: usize> {
     fn double(limbs: &mut [u32; NUM_WORDS]);
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone)]
 pub struct AffinePoint<C: CurveOperations<NUM_WORDS>, const NUM_WORDS: usize> {
     pub(crate) limbs: [u32; NUM_WORDS],
-    _marker: std::marker::PhantomData<C>,
+    _marker: core::marker::PhantomData<C>,
+}
+
+// The comparison and formatting only depend on the coordinates, so they are implemented without
+// requiring the curve operations to implement the traits as well.
+impl<C: CurveOperations<NUM_WORDS>, const NUM_WORDS: usize> PartialEq
+    for AffinePoint<C, NUM_WORDS>
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.limbs == other.limbs
+    }
+}
+
+impl<C: CurveOperations<NUM_WORDS>, const NUM_WORDS: usize> Eq for AffinePoint<C, NUM_WORDS> {}
+
+/// Formats the coordinates as big endian hexadecimal integers.
+impl<C: CurveOperations<NUM_WORDS>, const NUM_WORDS: usize> core::fmt::Debug
+    for AffinePoint<C, NUM_WORDS>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let (x, y) = self.limbs.split_at(NUM_WORDS / 2);
+        f.write_str("AffinePoint { x: 0x")?;
+        for limb in x.iter().rev() {
+            write!(f, "{limb:08x}")?;
+        }
+        f.write_str(", y: 0x")?;
+        for limb in y.iter().rev() {
+            write!(f, "{limb:08x}")?;
+        }
+        f.write_str(" }")
+    }
 }
 
 #[derive(Debug)]
@@ -22,14 +55,14 @@ impl<C: CurveOperations<NUM_WORDS> + Copy, const NUM_WORDS: usize> AffinePoint<C
     pub const fn generator_in_affine() -> Self {
         Self {
             limbs: Self::GENERATOR,
-            _marker: std::marker::PhantomData,
+            _marker: core::marker::PhantomData,
         }
     }
 
     pub const fn new(limbs: [u32; NUM_WORDS]) -> Self {
         Self {
             limbs,
-            _marker: std::marker::PhantomData,
+            _marker: core::marker::PhantomData,
         }
     }
 
@@ -40,13 +73,9 @@ impl<C: CurveOperations<NUM_WORDS> + Copy, const NUM_WORDS: usize> AffinePoint<C
         debug_assert!(y_bytes.len() == NUM_WORDS * 2);
 
         let mut limbs = [0u32; NUM_WORDS];
-        let x = bytes_to_words_le(x_bytes);
-        let y = bytes_to_words_le(y_bytes);
-        debug_assert!(x.len() == NUM_WORDS / 2);
-        debug_assert!(y.len() == NUM_WORDS / 2);
-
-        limbs[..(NUM_WORDS / 2)].copy_from_slice(&x);
-        limbs[(NUM_WORDS / 2)..].copy_from_slice(&y);
+        let (x_limbs, y_limbs) = limbs.split_at_mut(NUM_WORDS / 2);
+        bytes_to_words_le_into(x_bytes, x_limbs);
+        bytes_to_words_le_into(y_bytes, y_limbs);
         Self::new(limbs)
     }
 
@@ -87,15 +116,14 @@ impl<C: CurveOperations<NUM_WORDS> + Copy, const NUM_WORDS: usize> AffinePoint<C
     }
 
     pub fn from_le_bytes(limbs: &[u8]) -> Self {
-        let u32_limbs = bytes_to_words_le(limbs);
-        debug_assert!(u32_limbs.len() == NUM_WORDS);
+        debug_assert!(limbs.len() == NUM_WORDS * 4);
 
-        Self {
-            limbs: u32_limbs.try_into().unwrap(),
-            _marker: std::marker::PhantomData,
-        }
+        let mut u32_limbs = [0u32; NUM_WORDS];
+        bytes_to_words_le_into(limbs, &mut u32_limbs);
+        Self::new(u32_limbs)
     }
 
+    #[cfg(feature = "alloc")]
     pub fn to_le_bytes(&self) -> Vec<u8> {
         let le_bytes = words_to_bytes_le(&self.limbs);
         debug_assert!(le_bytes.len() == NUM_WORDS * 4);
@@ -104,6 +132,7 @@ impl<C: CurveOperations<NUM_WORDS> + Copy, const NUM_WORDS: usize> AffinePoint<C
 }
 
 /// Converts a slice of words to a byte array in little endian.
+#[cfg(feature = "alloc")]
 pub fn words_to_bytes_le(words: &[u32]) -> Vec<u8> {
     words
         .iter()
@@ -112,9 +141,108 @@ pub fn words_to_bytes_le(words: &[u32]) -> Vec<u8> {
 }
 
 /// Converts a byte array in little endian to a slice of words.
+#[cfg(feature = "alloc")]
 pub fn bytes_to_words_le(bytes: &[u8]) -> Vec<u32> {
     bytes
         .chunks_exact(4)
         .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
         .collect::<Vec<_>>()
 }
+
+/// Converts a byte array in little endian into words, writing them to `words` without allocating.
+pub fn bytes_to_words_le_into(bytes: &[u8], words: &mut [u32]) {
+    debug_assert!(bytes.len() == words.len() * 4);
+    for (word, chunk) in words.iter_mut().zip(bytes.chunks_exact(4)) {
+        *word = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+}
+
+/// Decodes padded base64 with the standard alphabet of RFC 4648. Returns `None` if the input is
+/// not valid base64.
+#[cfg(feature = "alloc")]
+pub fn decode_base64(input: &str) -> Option<Vec<u8>> {
+    let input = input.as_bytes();
+    if input.len() % 4 != 0 {
+        return None;
+    }
+
+    let num_chunks = input.len() / 4;
+    let mut output = Vec::with_capacity(num_chunks * 3);
+    for (i, chunk) in input.chunks_exact(4).enumerate() {
+        // Padding is only allowed at the end of the last chunk.
+        let padding = chunk.iter().rev().take_while(|&&c| c == b'=').count();
+        if padding > 2 || (padding > 0 && i + 1 != num_chunks) {
+            return None;
+        }
+
+        let mut acc = 0u32;
+        for &c in &chunk[..4 - padding] {
+            let value = match c {
+                b'A'..=b'Z' => c - b'A',
+                b'a'..=b'z' => c - b'a' + 26,
+                b'0'..=b'9' => c - b'0' + 52,
+                b'+' => 62,
+                b'/' => 63,
+                _ => return None,
+            };
+            acc = (acc << 6) | value as u32;
+        }
+        acc <<= 6 * padding as u32;
+        output.extend_from_slice(&acc.to_be_bytes()[1..4 - padding]);
+    }
+    Some(output)
+}
+
+/// Encodes bytes as padded base64 with the standard alphabet, for the tests of the decoders.
+#[cfg(all(test, any(feature = "ssh", feature = "minisign")))]
+pub(crate) fn encode_base64(bytes: &[u8]) -> alloc::string::String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut output = alloc::string::String::new();
+    for chunk in bytes.chunks(3) {
+        let mut acc = [0u8; 4];
+        acc[1..1 + chunk.len()].copy_from_slice(chunk);
+        let acc = u32::from_be_bytes(acc);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                output.push(ALPHABET[(acc >> (18 - 6 * i)) as usize & 63] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+    output
+}
+
+/// Decodes unpadded base64 with the URL and filename safe alphabet of RFC 4648, as in the text
+/// form of Ethereum Node Records. Returns `None` if the input is not valid base64url.
+#[cfg(feature = "alloc")]
+pub fn decode_base64url(input: &str) -> Option<Vec<u8>> {
+    if input.len() % 4 == 1 || input.contains(|c| matches!(c, '+' | '/' | '=')) {
+        return None;
+    }
+    let mut padded = input.replace('-', "+").replace('_', "/");
+    padded.push_str(&"=="[..(4 - input.len() % 4) % 4]);
+    decode_base64(&padded)
+}
+
+/// Decodes lowercase hex digits at compile time.
+#[cfg(any(feature = "bls12381", feature = "kzg", feature = "p384"))]
+pub(crate) const fn hex<const N: usize>(digits: &str) -> [u8; N] {
+    const fn nibble(digit: u8) -> u8 {
+        match digit {
+            b'0'..=b'9' => digit - b'0',
+            b'a'..=b'f' => digit - b'a' + 10,
+            _ => panic!("invalid hex digit"),
+        }
+    }
+
+    let digits = digits.as_bytes();
+    assert!(digits.len() == 2 * N, "wrong number of hex digits");
+    let mut bytes = [0u8; N];
+    let mut i = 0;
+    while i < N {
+        bytes[i] = (nibble(digits[2 * i]) << 4) | nibble(digits[2 * i + 1]);
+        i += 1;
+    }
+    bytes
+}
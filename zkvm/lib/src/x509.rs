@@ -0,0 +1,743 @@
+//! Verification of X.509 certificate chains, such as the ones handed to attestation flows.
+//!
+//! Only the fields needed to check the signatures and the validity periods are parsed. This is
+//! not a full X.509 library: extensions such as basic constraints and key usage are not checked,
+//! and names are compared byte for byte.
+//!
+//! Certificates signed with ecdsa-with-SHA256 over P-256 or secp256k1 are supported. RSA
+//! signatures are reported as [`Error::UnsupportedAlgorithm`], as there is no RSA precompile yet.
+
+use crate::error::Error;
+use crate::secp256k1::{decompress_pubkey, try_verify_signature};
+use crate::sha256::sha256;
+use core::convert::TryInto;
+use p256::ecdsa::signature::hazmat::PrehashVerifier;
+
+const TAG_INTEGER: u8 = 0x02;
+const TAG_BIT_STRING: u8 = 0x03;
+const TAG_OID: u8 = 0x06;
+const TAG_UTC_TIME: u8 = 0x17;
+const TAG_GENERALIZED_TIME: u8 = 0x18;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_VERSION: u8 = 0xa0;
+
+/// 1.2.840.10045.4.3.2
+const OID_ECDSA_WITH_SHA256: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+/// 1.2.840.10045.2.1
+const OID_EC_PUBLIC_KEY: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+/// 1.2.840.10045.3.1.7
+const OID_PRIME256V1: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+/// 1.3.132.0.10
+const OID_SECP256K1: &[u8] = &[0x2b, 0x81, 0x04, 0x00, 0x0a];
+
+/// A certificate chain whose signatures have been verified up to the trusted root.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct VerifiedChain<'a> {
+    /// The DER encoding of the leaf certificate's SubjectPublicKeyInfo.
+    pub leaf_spki: &'a [u8],
+    /// The DER encoding of the leaf certificate's subject name.
+    pub leaf_subject: &'a [u8],
+    /// The Unix timestamp from which all the certificates of the chain are valid.
+    pub not_before: i64,
+    /// The Unix timestamp until which all the certificates of the chain are valid.
+    pub not_after: i64,
+}
+
+/// Verifies a chain of DER certificates, ordered from the leaf to the one signed by the trusted
+/// root, whose DER SubjectPublicKeyInfo is given. The chain may end with the root certificate
+/// itself, in which case its self-signature is checked against the trusted key.
+///
+/// Each certificate's issuer must be the subject of the next one. The validity periods are not
+/// checked, use [`verify_cert_chain_at`] to do so.
+pub fn verify_cert_chain<'a>(
+    chain_der: &[&'a [u8]],
+    trusted_root_spki: &[u8],
+) -> Result<VerifiedChain<'a>, Error> {
+    verify_chain(chain_der, trusted_root_spki, None)
+}
+
+/// Verifies a chain of DER certificates like [`verify_cert_chain`], also checking that every
+/// certificate is valid at the given Unix timestamp.
+pub fn verify_cert_chain_at<'a>(
+    chain_der: &[&'a [u8]],
+    trusted_root_spki: &[u8],
+    unix_time: i64,
+) -> Result<VerifiedChain<'a>, Error> {
+    verify_chain(chain_der, trusted_root_spki, Some(unix_time))
+}
+
+fn verify_chain<'a>(
+    chain_der: &[&'a [u8]],
+    trusted_root_spki: &[u8],
+    unix_time: Option<i64>,
+) -> Result<VerifiedChain<'a>, Error> {
+    let parse = |index: usize| {
+        Certificate::parse(chain_der[index]).ok_or(Error::CertificateMalformed { index })
+    };
+    if chain_der.is_empty() {
+        return Err(Error::EmptyCertificateChain);
+    }
+
+    let mut certificate = parse(0)?;
+    let mut chain = VerifiedChain {
+        leaf_spki: certificate.spki,
+        leaf_subject: certificate.subject,
+        not_before: i64::MIN,
+        not_after: i64::MAX,
+    };
+    let mut index = 0;
+    loop {
+        if let Some(unix_time) = unix_time {
+            if unix_time < certificate.not_before {
+                return Err(Error::CertificateNotYetValid { index });
+            }
+            if unix_time > certificate.not_after {
+                return Err(Error::CertificateExpired { index });
+            }
+        }
+        chain.not_before = chain.not_before.max(certificate.not_before);
+        chain.not_after = chain.not_after.min(certificate.not_after);
+
+        let issuer = if index + 1 < chain_der.len() {
+            Some(parse(index + 1)?)
+        } else {
+            None
+        };
+        let issuer_spki = match &issuer {
+            Some(issuer) if issuer.subject != certificate.issuer => {
+                return Err(Error::IssuerMismatch { index });
+            }
+            Some(issuer) => issuer.spki,
+            None => trusted_root_spki,
+        };
+        certificate.verify(issuer_spki, index)?;
+
+        match issuer {
+            Some(issuer) => {
+                certificate = issuer;
+                index += 1;
+            }
+            None => return Ok(chain),
+        }
+    }
+}
+
+/// The fields of a certificate that are needed to verify it.
+struct Certificate<'a> {
+    /// The full encoding of the TBSCertificate, which is what the signature is over.
+    tbs: &'a [u8],
+    issuer: &'a [u8],
+    subject: &'a [u8],
+    not_before: i64,
+    not_after: i64,
+    spki: &'a [u8],
+    /// The content of the signatureAlgorithm AlgorithmIdentifier.
+    signature_algorithm: &'a [u8],
+    signature: &'a [u8],
+}
+
+impl<'a> Certificate<'a> {
+    fn parse(der: &'a [u8]) -> Option<Self> {
+        let (certificate, rest) = Der(der).expect(TAG_SEQUENCE)?;
+        if !rest.0.is_empty() {
+            return None;
+        }
+
+        let mut certificate = Der(certificate);
+        let (tbs_content, tbs) = certificate.expect_full(TAG_SEQUENCE)?;
+        let (signature_algorithm, _) = certificate.expect_full(TAG_SEQUENCE)?;
+        let (signature, _) = certificate.expect_full(TAG_BIT_STRING)?;
+        if !certificate.0.is_empty() {
+            return None;
+        }
+        // Signatures are whole bytes, so there are never unused bits.
+        let (&0, signature) = signature.split_first()? else {
+            return None;
+        };
+
+        let mut tbs_fields = Der(tbs_content);
+        if tbs_fields.peek_tag() == Some(TAG_VERSION) {
+            tbs_fields.next()?;
+        }
+        tbs_fields.expect_full(TAG_INTEGER)?;
+        // The signature algorithm is repeated in the signed part, and the two must match.
+        let (inner_algorithm, _) = tbs_fields.expect_full(TAG_SEQUENCE)?;
+        if inner_algorithm != signature_algorithm {
+            return None;
+        }
+        let (_, issuer) = tbs_fields.expect_full(TAG_SEQUENCE)?;
+        let (validity, _) = tbs_fields.expect_full(TAG_SEQUENCE)?;
+        let (_, subject) = tbs_fields.expect_full(TAG_SEQUENCE)?;
+        let (_, spki) = tbs_fields.expect_full(TAG_SEQUENCE)?;
+
+        let mut validity = Der(validity);
+        let (tag, not_before, _) = validity.next()?;
+        let not_before = parse_time(tag, not_before)?;
+        let (tag, not_after, _) = validity.next()?;
+        let not_after = parse_time(tag, not_after)?;
+
+        Some(Self {
+            tbs,
+            issuer,
+            subject,
+            not_before,
+            not_after,
+            spki,
+            signature_algorithm,
+            signature,
+        })
+    }
+
+    /// Verifies the certificate's signature with the issuer's SubjectPublicKeyInfo.
+    fn verify(&self, issuer_spki: &[u8], index: usize) -> Result<(), Error> {
+        let mut algorithm = Der(self.signature_algorithm);
+        let (oid, _) = algorithm
+            .expect_full(TAG_OID)
+            .ok_or(Error::CertificateMalformed { index })?;
+        if oid != OID_ECDSA_WITH_SHA256 {
+            return Err(Error::UnsupportedAlgorithm { index });
+        }
+
+        let (curve, key) = parse_ec_spki(issuer_spki, index)?;
+        let (r, s) = parse_ecdsa_signature(self.signature)
+            .ok_or(Error::CertificateSignatureInvalid { index })?;
+        let hash = sha256(self.tbs);
+
+        let verified = match curve {
+            Curve::P256 => {
+                let key = p256::ecdsa::VerifyingKey::from_sec1_bytes(key)
+                    .map_err(|_| Error::InvalidPublicKey)?;
+                let signature = p256::ecdsa::Signature::from_scalars(r, s)
+                    .map_err(|_| Error::CertificateSignatureInvalid { index })?;
+                key.verify_prehash(&hash, &signature).is_ok()
+            }
+            Curve::Secp256k1 => {
+                let pubkey: [u8; 65] = match key.len() {
+                    33 => decompress_pubkey(key.try_into().unwrap())?,
+                    65 => key.try_into().unwrap(),
+                    _ => return Err(Error::InvalidPublicKey),
+                };
+                let signature = k256::ecdsa::Signature::from_scalars(r, s)
+                    .map_err(|_| Error::CertificateSignatureInvalid { index })?;
+                // Certificate signatures are not required to have a low s, unlike the ones that
+                // k256 accepts, and negating s does not change whether an ECDSA signature verifies.
+                let signature = signature.normalize_s().unwrap_or(signature);
+                match try_verify_signature(&pubkey, &hash, &signature, None) {
+                    Ok(verified) => verified,
+                    Err(Error::InvalidPublicKey) => return Err(Error::InvalidPublicKey),
+                    Err(_) => false,
+                }
+            }
+        };
+        if !verified {
+            return Err(Error::CertificateSignatureInvalid { index });
+        }
+        Ok(())
+    }
+}
+
+/// The curves of the supported public keys.
+pub(crate) enum Curve {
+    P256,
+    Secp256k1,
+}
+
+/// Parses an elliptic curve SubjectPublicKeyInfo, returning the curve and the SEC1 encoding of
+/// the key.
+pub(crate) fn parse_ec_spki(spki: &[u8], index: usize) -> Result<(Curve, &[u8]), Error> {
+    let parse = || {
+        let (spki, _) = Der(spki).expect(TAG_SEQUENCE)?;
+        let mut spki = Der(spki);
+        let (algorithm, _) = spki.expect_full(TAG_SEQUENCE)?;
+        let (key, _) = spki.expect_full(TAG_BIT_STRING)?;
+        let (&0, key) = key.split_first()? else {
+            return None;
+        };
+
+        let mut algorithm = Der(algorithm);
+        let (key_type, _) = algorithm.expect_full(TAG_OID)?;
+        // Only elliptic curve keys have a curve parameter.
+        let curve = match algorithm.expect_full(TAG_OID) {
+            Some((curve, _)) if key_type == OID_EC_PUBLIC_KEY => Some(curve),
+            _ => None,
+        };
+        Some((curve, key))
+    };
+
+    let (curve, key) = parse().ok_or(Error::InvalidPublicKey)?;
+    let curve = match curve {
+        Some(OID_PRIME256V1) => Curve::P256,
+        Some(OID_SECP256K1) => Curve::Secp256k1,
+        _ => return Err(Error::UnsupportedAlgorithm { index }),
+    };
+    Ok((curve, key))
+}
+
+/// Parses the DER `SEQUENCE { r INTEGER, s INTEGER }` of an ECDSA signature into big endian
+/// 32-byte scalars.
+pub(crate) fn parse_ecdsa_signature(signature: &[u8]) -> Option<([u8; 32], [u8; 32])> {
+    let (signature, rest) = Der(signature).expect(TAG_SEQUENCE)?;
+    if !rest.0.is_empty() {
+        return None;
+    }
+    let mut signature = Der(signature);
+    let (r, _) = signature.expect_full(TAG_INTEGER)?;
+    let (s, _) = signature.expect_full(TAG_INTEGER)?;
+    if !signature.0.is_empty() {
+        return None;
+    }
+    Some((integer_to_scalar(r)?, integer_to_scalar(s)?))
+}
+
+/// Left-pads the content of a nonnegative DER INTEGER to 32 bytes.
+fn integer_to_scalar(integer: &[u8]) -> Option<[u8; 32]> {
+    // The leading zero byte only keeps the sign bit of positive integers clear.
+    let integer = match integer {
+        [0, rest @ ..] => rest,
+        [byte, ..] if byte & 0x80 != 0 => return None,
+        _ => integer,
+    };
+    if integer.len() > 32 {
+        return None;
+    }
+    let mut scalar = [0u8; 32];
+    scalar[32 - integer.len()..].copy_from_slice(integer);
+    Some(scalar)
+}
+
+/// Parses a UTCTime or a GeneralizedTime in the `Z` form required by RFC 5280 into a Unix
+/// timestamp.
+fn parse_time(tag: u8, time: &[u8]) -> Option<i64> {
+    let (year, rest) = match tag {
+        // Two-digit years below 50 are in the 21st century.
+        TAG_UTC_TIME if time.len() == 13 => {
+            let year = digits(&time[..2])?;
+            (
+                if year < 50 { 2000 + year } else { 1900 + year },
+                &time[2..],
+            )
+        }
+        TAG_GENERALIZED_TIME if time.len() == 15 => (digits(&time[..4])?, &time[4..]),
+        _ => return None,
+    };
+    if rest[10] != b'Z' {
+        return None;
+    }
+
+    let month = digits(&rest[0..2])?;
+    let day = digits(&rest[2..4])?;
+    let hour = digits(&rest[4..6])?;
+    let minute = digits(&rest[6..8])?;
+    let second = digits(&rest[8..10])?;
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return None;
+    }
+    Some(days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second)
+}
+
+/// Parses ASCII decimal digits.
+fn digits(bytes: &[u8]) -> Option<i64> {
+    bytes.iter().try_fold(0, |acc, &byte| {
+        byte.is_ascii_digit()
+            .then(|| acc * 10 + (byte - b'0') as i64)
+    })
+}
+
+/// Returns the number of days between the Unix epoch and a date of the proleptic Gregorian
+/// calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    // Years start in March, so that leap days are at the end of the year.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// A reader over a sequence of DER elements.
+struct Der<'a>(&'a [u8]);
+
+impl<'a> Der<'a> {
+    /// Reads the next element, returning its tag, its content and its full encoding.
+    fn next(&mut self) -> Option<(u8, &'a [u8], &'a [u8])> {
+        let (&tag, rest) = self.0.split_first()?;
+        let (&first, mut rest) = rest.split_first()?;
+        let len = if first < 0x80 {
+            first as usize
+        } else {
+            let len_bytes = (first & 0x7f) as usize;
+            if len_bytes == 0 || len_bytes > 4 || rest.len() < len_bytes {
+                return None;
+            }
+            let (len, tail) = rest.split_at(len_bytes);
+            rest = tail;
+            len.iter().fold(0, |acc, &byte| (acc << 8) | byte as usize)
+        };
+        if rest.len() < len {
+            return None;
+        }
+
+        let header_len = self.0.len() - rest.len();
+        let full = &self.0[..header_len + len];
+        self.0 = &rest[len..];
+        Some((tag, &rest[..len], full))
+    }
+
+    /// Returns the tag of the next element without reading it.
+    fn peek_tag(&self) -> Option<u8> {
+        self.0.first().copied()
+    }
+
+    /// Reads the next element, which must have the given tag, returning its content and its full
+    /// encoding.
+    fn expect_full(&mut self, tag: u8) -> Option<(&'a [u8], &'a [u8])> {
+        match self.next()? {
+            (actual, content, full) if actual == tag => Some((content, full)),
+            _ => None,
+        }
+    }
+
+    /// Reads the first element, which must have the given tag, returning its content and a
+    /// reader over the remaining elements.
+    fn expect(mut self, tag: u8) -> Option<(&'a [u8], Self)> {
+        let (content, _) = self.expect_full(tag)?;
+        Some((content, self))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use p256::ecdsa::signature::hazmat::PrehashSigner;
+    use std::vec::Vec;
+
+    const TAG_UTF8_STRING: u8 = 0x0c;
+    const TAG_SET: u8 = 0x31;
+    /// 2.5.4.3, the common name.
+    const OID_COMMON_NAME: &[u8] = &[0x55, 0x04, 0x03];
+    /// 1.2.840.113549.1.1.11
+    const OID_SHA256_WITH_RSA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0b];
+
+    pub(crate) fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+        let mut der = std::vec![tag];
+        match content.len() {
+            len @ 0..=0x7f => der.push(len as u8),
+            len @ 0x80..=0xff => der.extend_from_slice(&[0x81, len as u8]),
+            len => der.extend_from_slice(&[0x82, (len >> 8) as u8, len as u8]),
+        }
+        der.extend_from_slice(content);
+        der
+    }
+
+    /// Encodes a big endian unsigned integer as a DER INTEGER.
+    fn integer(bytes: &[u8]) -> Vec<u8> {
+        let start = bytes
+            .iter()
+            .position(|&byte| byte != 0)
+            .unwrap_or(bytes.len() - 1);
+        let mut content = Vec::new();
+        if bytes[start] & 0x80 != 0 {
+            content.push(0);
+        }
+        content.extend_from_slice(&bytes[start..]);
+        tlv(TAG_INTEGER, &content)
+    }
+
+    /// Encodes an ECDSA signature as the DER `SEQUENCE { r INTEGER, s INTEGER }`.
+    pub(crate) fn ecdsa_der(r: &[u8], s: &[u8]) -> Vec<u8> {
+        tlv(TAG_SEQUENCE, &[integer(r), integer(s)].concat())
+    }
+
+    /// Encodes a SubjectPublicKeyInfo of an elliptic curve key.
+    pub(crate) fn ec_spki(curve: &[u8], sec1: &[u8]) -> Vec<u8> {
+        let algorithm = [tlv(TAG_OID, OID_EC_PUBLIC_KEY), tlv(TAG_OID, curve)].concat();
+        let key = [&[0], sec1].concat();
+        tlv(
+            TAG_SEQUENCE,
+            &[tlv(TAG_SEQUENCE, &algorithm), tlv(TAG_BIT_STRING, &key)].concat(),
+        )
+    }
+
+    pub(crate) fn p256_spki(key: &p256::ecdsa::SigningKey) -> Vec<u8> {
+        ec_spki(
+            OID_PRIME256V1,
+            key.verifying_key().to_encoded_point(false).as_bytes(),
+        )
+    }
+
+    /// A name with a single common name.
+    fn name(common_name: &str) -> Vec<u8> {
+        let attribute = [
+            tlv(TAG_OID, OID_COMMON_NAME),
+            tlv(TAG_UTF8_STRING, common_name.as_bytes()),
+        ]
+        .concat();
+        tlv(TAG_SEQUENCE, &tlv(TAG_SET, &tlv(TAG_SEQUENCE, &attribute)))
+    }
+
+    enum Signer {
+        P256(p256::ecdsa::SigningKey),
+        Secp256k1(k256::ecdsa::SigningKey),
+    }
+
+    impl Signer {
+        fn spki(&self) -> Vec<u8> {
+            match self {
+                Self::P256(key) => p256_spki(key),
+                Self::Secp256k1(key) => ec_spki(
+                    OID_SECP256K1,
+                    key.verifying_key().to_encoded_point(true).as_bytes(),
+                ),
+            }
+        }
+
+        fn sign(&self, tbs: &[u8]) -> ([u8; 32], [u8; 32]) {
+            let hash = sha256(tbs);
+            let bytes: [u8; 64] = match self {
+                Self::P256(key) => {
+                    let signature: p256::ecdsa::Signature = key.sign_prehash(&hash).unwrap();
+                    signature.to_bytes().into()
+                }
+                Self::Secp256k1(key) => {
+                    let signature: k256::ecdsa::Signature = key.sign_prehash(&hash).unwrap();
+                    signature.to_bytes().into()
+                }
+            };
+            (
+                bytes[..32].try_into().unwrap(),
+                bytes[32..].try_into().unwrap(),
+            )
+        }
+    }
+
+    /// A certificate with what [`Certificate::parse`] reads.
+    struct CertificateBuilder<'a> {
+        issuer: &'a str,
+        subject: &'a str,
+        /// The validity period, as UTCTime or GeneralizedTime strings.
+        validity: [(u8, &'a str); 2],
+        spki: Vec<u8>,
+        signature_algorithm: &'a [u8],
+    }
+
+    impl CertificateBuilder<'_> {
+        fn tbs(&self) -> Vec<u8> {
+            let validity: Vec<u8> = self
+                .validity
+                .iter()
+                .flat_map(|(tag, time)| tlv(*tag, time.as_bytes()))
+                .collect();
+            let fields = [
+                tlv(TAG_VERSION, &integer(&[2])),
+                integer(&[0x1f, 0x42]),
+                self.algorithm(),
+                name(self.issuer),
+                tlv(TAG_SEQUENCE, &validity),
+                name(self.subject),
+                self.spki.clone(),
+            ];
+            tlv(TAG_SEQUENCE, &fields.concat())
+        }
+
+        fn algorithm(&self) -> Vec<u8> {
+            tlv(TAG_SEQUENCE, &tlv(TAG_OID, self.signature_algorithm))
+        }
+
+        fn assemble(&self, tbs: &[u8], signature: &[u8]) -> Vec<u8> {
+            let signature = [&[0], signature].concat();
+            let fields = [
+                tbs.to_vec(),
+                self.algorithm(),
+                tlv(TAG_BIT_STRING, &signature),
+            ];
+            tlv(TAG_SEQUENCE, &fields.concat())
+        }
+
+        fn sign(&self, signer: &Signer) -> Vec<u8> {
+            let tbs = self.tbs();
+            let (r, s) = signer.sign(&tbs);
+            self.assemble(&tbs, &ecdsa_der(&r, &s))
+        }
+    }
+
+    struct Pki {
+        root: Signer,
+        intermediate: Signer,
+        /// The root certificate, self-signed.
+        root_certificate: Vec<u8>,
+        /// The intermediate certificate, signed by the root with P-256.
+        intermediate_certificate: Vec<u8>,
+        /// The leaf certificate, signed by the intermediate with secp256k1.
+        leaf_certificate: Vec<u8>,
+        leaf: CertificateBuilder<'static>,
+    }
+
+    fn pki() -> Pki {
+        let root = Signer::P256(p256::ecdsa::SigningKey::from_slice(&[1; 32]).unwrap());
+        let intermediate =
+            Signer::Secp256k1(k256::ecdsa::SigningKey::from_slice(&[2; 32]).unwrap());
+        let leaf_key = p256::ecdsa::SigningKey::from_slice(&[3; 32]).unwrap();
+
+        let root_certificate = CertificateBuilder {
+            issuer: "Root CA",
+            subject: "Root CA",
+            validity: [
+                (TAG_UTC_TIME, "200101000000Z"),
+                (TAG_UTC_TIME, "491231235959Z"),
+            ],
+            spki: root.spki(),
+            signature_algorithm: OID_ECDSA_WITH_SHA256,
+        }
+        .sign(&root);
+        let intermediate_certificate = CertificateBuilder {
+            issuer: "Root CA",
+            subject: "Intermediate CA",
+            validity: [
+                (TAG_GENERALIZED_TIME, "20240101000000Z"),
+                (TAG_GENERALIZED_TIME, "20340101000000Z"),
+            ],
+            spki: intermediate.spki(),
+            signature_algorithm: OID_ECDSA_WITH_SHA256,
+        }
+        .sign(&root);
+        let leaf = CertificateBuilder {
+            issuer: "Intermediate CA",
+            subject: "enclave.example.com",
+            validity: [
+                (TAG_UTC_TIME, "240601000000Z"),
+                (TAG_UTC_TIME, "250601000000Z"),
+            ],
+            spki: p256_spki(&leaf_key),
+            signature_algorithm: OID_ECDSA_WITH_SHA256,
+        };
+        Pki {
+            leaf_certificate: leaf.sign(&intermediate),
+            root,
+            intermediate,
+            root_certificate,
+            intermediate_certificate,
+            leaf,
+        }
+    }
+
+    #[test]
+    fn test_verify_cert_chain() {
+        let pki = pki();
+        let chain = [&pki.leaf_certificate[..], &pki.intermediate_certificate[..]];
+        let verified = verify_cert_chain(&chain, &pki.root.spki()).unwrap();
+        assert_eq!(verified.leaf_spki, pki.leaf.spki);
+        assert_eq!(verified.leaf_subject, name("enclave.example.com"));
+        // From the leaf's 2024-06-01 to its 2025-06-01.
+        assert_eq!(verified.not_before, 1717200000);
+        assert_eq!(verified.not_after, 1748736000);
+
+        // The chain may end with the root, whose self-signature is checked.
+        let with_root = [
+            &pki.leaf_certificate[..],
+            &pki.intermediate_certificate[..],
+            &pki.root_certificate[..],
+        ];
+        assert_eq!(
+            verify_cert_chain(&with_root, &pki.root.spki()),
+            Ok(verified)
+        );
+        assert_eq!(
+            verify_cert_chain(&chain[..1], &pki.intermediate.spki()).map(|chain| chain.leaf_spki),
+            Ok(&pki.leaf.spki[..])
+        );
+    }
+
+    #[test]
+    fn test_verify_cert_chain_at() {
+        let pki = pki();
+        let chain = [&pki.leaf_certificate[..], &pki.intermediate_certificate[..]];
+        let root = pki.root.spki();
+        assert!(verify_cert_chain_at(&chain, &root, 1717200000).is_ok());
+        assert!(verify_cert_chain_at(&chain, &root, 1748736000).is_ok());
+        assert_eq!(
+            verify_cert_chain_at(&chain, &root, 1717199999),
+            Err(Error::CertificateNotYetValid { index: 0 })
+        );
+        assert_eq!(
+            verify_cert_chain_at(&chain, &root, 1748736001),
+            Err(Error::CertificateExpired { index: 0 })
+        );
+        // The intermediate is only valid from 2024-01-01.
+        assert_eq!(
+            verify_cert_chain_at(&chain[1..], &root, 1704067199),
+            Err(Error::CertificateNotYetValid { index: 0 })
+        );
+    }
+
+    #[test]
+    fn test_verify_cert_chain_rejects_bad_chains() {
+        let pki = pki();
+        let root = pki.root.spki();
+        let chain = [&pki.leaf_certificate[..], &pki.intermediate_certificate[..]];
+
+        assert_eq!(
+            verify_cert_chain(&[], &root),
+            Err(Error::EmptyCertificateChain)
+        );
+        assert_eq!(
+            verify_cert_chain(&chain, &pki.intermediate.spki()),
+            Err(Error::CertificateSignatureInvalid { index: 1 })
+        );
+        assert_eq!(
+            verify_cert_chain(&[chain[1], chain[0]], &root),
+            Err(Error::IssuerMismatch { index: 0 })
+        );
+
+        // A signature of other contents.
+        let mut tbs = pki.leaf.tbs();
+        let (r, s) = pki.intermediate.sign(&tbs);
+        *tbs.last_mut().unwrap() ^= 1;
+        let tampered = pki.leaf.assemble(&tbs, &ecdsa_der(&r, &s));
+        assert_eq!(
+            verify_cert_chain(&[&tampered[..], chain[1]], &root),
+            Err(Error::CertificateSignatureInvalid { index: 0 })
+        );
+
+        let mut trailing = pki.leaf_certificate.clone();
+        trailing.push(0);
+        let mut truncated = pki.intermediate_certificate.clone();
+        truncated.pop();
+        assert_eq!(
+            verify_cert_chain(&[&trailing[..], chain[1]], &root),
+            Err(Error::CertificateMalformed { index: 0 })
+        );
+        assert_eq!(
+            verify_cert_chain(&[chain[0], &truncated[..]], &root),
+            Err(Error::CertificateMalformed { index: 1 })
+        );
+
+        let rsa = CertificateBuilder {
+            signature_algorithm: OID_SHA256_WITH_RSA,
+            ..pki.leaf
+        }
+        .sign(&pki.intermediate);
+        assert_eq!(
+            verify_cert_chain(&[&rsa[..], chain[1]], &root),
+            Err(Error::UnsupportedAlgorithm { index: 0 })
+        );
+    }
+
+    /// Certificates may carry a high s, which k256 only accepts once normalized.
+    #[test]
+    fn test_verify_cert_chain_accepts_high_s() {
+        let pki = pki();
+        let tbs = pki.leaf.tbs();
+        let (r, s) = pki.intermediate.sign(&tbs);
+        let s = k256::ecdsa::Signature::from_scalars(r, s).unwrap().s();
+        let high_s: [u8; 32] = (-*s).to_bytes().into();
+        let certificate = pki.leaf.assemble(&tbs, &ecdsa_der(&r, &high_s));
+        let chain = [&certificate[..], &pki.intermediate_certificate[..]];
+        assert!(verify_cert_chain(&chain, &pki.root.spki()).is_ok());
+    }
+}
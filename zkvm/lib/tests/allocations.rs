@@ -0,0 +1,81 @@
+#![cfg(feature = "secp256k1")]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use k256::ecdsa::SigningKey;
+use k256::elliptic_curve::ops::Invert;
+use sp1_lib::secp256k1::{ecrecover, try_verify_signature, verify_signature};
+
+/// The system allocator, counting the allocations of each thread, so that the tests running in
+/// parallel do not count each other's.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Returns the result of `f` and the number of allocations it made.
+fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+#[test]
+fn test_verify_signature_does_not_allocate() {
+    let key = SigningKey::from_slice(&[0x24; 32]).unwrap();
+    let pubkey: [u8; 65] = key
+        .verifying_key()
+        .to_encoded_point(false)
+        .as_bytes()
+        .try_into()
+        .unwrap();
+    let msg_hash = [0x42; 32];
+    let (signature, recid) = key.sign_prehash_recoverable(&msg_hash).unwrap();
+    let mut sig = [0u8; 65];
+    sig[..64].copy_from_slice(&signature.to_bytes());
+    sig[64] = recid.to_byte();
+    let s_inverse = *signature.s().invert();
+
+    // The first calls may initialize the precomputed tables of k256.
+    assert!(verify_signature(&pubkey, &msg_hash, &signature, None));
+    assert_eq!(ecrecover(&sig, &msg_hash), Ok(pubkey));
+
+    let cases: [(&str, &dyn Fn() -> bool); 5] = [
+        ("verify_signature", &|| {
+            verify_signature(&pubkey, &msg_hash, &signature, None)
+        }),
+        ("verify_signature with s_inverse", &|| {
+            verify_signature(&pubkey, &msg_hash, &signature, Some(&s_inverse))
+        }),
+        ("verify_signature of another digest", &|| {
+            !verify_signature(&pubkey, &[0x43; 32], &signature, None)
+        }),
+        ("try_verify_signature of a key off the curve", &|| {
+            let mut off_curve = pubkey;
+            off_curve[64] ^= 1;
+            try_verify_signature(&off_curve, &msg_hash, &signature, None).is_err()
+        }),
+        ("ecrecover", &|| ecrecover(&sig, &msg_hash) == Ok(pubkey)),
+    ];
+    for (name, case) in cases {
+        let (ok, allocations) = count_allocations(case);
+        assert!(ok, "{name}");
+        assert_eq!(allocations, 0, "{name} allocated");
+    }
+}
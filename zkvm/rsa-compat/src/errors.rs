@@ -0,0 +1,53 @@
+//! The errors of the crate.
+
+use core::fmt;
+
+/// An alias for results with the errors of the crate.
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// An error of the crate. The variants are those of `rsa` which verification can return.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The signature does not verify.
+    Verification,
+    /// The length of the digest is not the output size of the hash.
+    InputNotHashed,
+    /// The modulus is even, and cannot be used with the bigmod arithmetic.
+    InvalidModulus,
+    /// The modulus has more bits than allowed.
+    ModulusTooLarge,
+    /// The public exponent is below 2.
+    PublicExponentTooSmall,
+    /// The public exponent is above `2^33 - 1`.
+    PublicExponentTooLarge,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Error::Verification => "verification error",
+            Error::InputNotHashed => "input must be hashed",
+            Error::InvalidModulus => "invalid modulus",
+            Error::ModulusTooLarge => "modulus too large",
+            Error::PublicExponentTooSmall => "public exponent too small",
+            Error::PublicExponentTooLarge => "public exponent too large",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl From<Error> for signature::Error {
+    fn from(err: Error) -> Self {
+        #[cfg(feature = "std")]
+        {
+            signature::Error::from_source(err)
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            let _ = err;
+            signature::Error::new()
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! RSA public keys.
+
+use num_bigint::BigUint;
+use pkcs8::der::asn1::{AnyRef, UintRef};
+use pkcs8::der::{Decode, Reader, SliceReader};
+use pkcs8::{spki, ObjectIdentifier, SubjectPublicKeyInfoRef};
+
+use crate::errors::{Error, Result};
+use crate::traits::{PublicKeyParts, SignatureScheme};
+
+/// The OID of `rsaEncryption`, the algorithm of RSA keys in a `SubjectPublicKeyInfo`.
+const RSA_ENCRYPTION: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.1");
+
+/// An RSA public key.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct RsaPublicKey {
+    n: BigUint,
+    e: BigUint,
+}
+
+impl RsaPublicKey {
+    /// The largest modulus, in bits, which is also the largest the bigmod arithmetic supports.
+    pub const MAX_SIZE: usize = 4096;
+
+    /// The smallest public exponent.
+    pub const MIN_PUB_EXPONENT: u64 = 2;
+
+    /// The largest public exponent.
+    pub const MAX_PUB_EXPONENT: u64 = (1 << 33) - 1;
+
+    /// Creates a key from its modulus and public exponent, with a modulus of up to
+    /// [`RsaPublicKey::MAX_SIZE`] bits.
+    ///
+    /// Unlike in `rsa`, an even modulus is rejected with [`Error::InvalidModulus`], as no
+    /// signature could be verified with it.
+    pub fn new(n: BigUint, e: BigUint) -> Result<Self> {
+        Self::new_with_max_size(n, e, Self::MAX_SIZE)
+    }
+
+    /// Creates a key from its modulus and public exponent, with a modulus of up to `max_size`
+    /// bits. Moduli longer than [`RsaPublicKey::MAX_SIZE`] bits are rejected whatever `max_size`.
+    pub fn new_with_max_size(n: BigUint, e: BigUint, max_size: usize) -> Result<Self> {
+        if n.bits() as usize > max_size.min(Self::MAX_SIZE) {
+            return Err(Error::ModulusTooLarge);
+        }
+        if !n.bit(0) {
+            return Err(Error::InvalidModulus);
+        }
+        if e < BigUint::from(Self::MIN_PUB_EXPONENT) {
+            return Err(Error::PublicExponentTooSmall);
+        }
+        if e > BigUint::from(Self::MAX_PUB_EXPONENT) {
+            return Err(Error::PublicExponentTooLarge);
+        }
+        Ok(Self { n, e })
+    }
+
+    /// Creates a key without checking it. Signatures never verify with a key which
+    /// [`RsaPublicKey::new`] would reject.
+    pub fn new_unchecked(n: BigUint, e: BigUint) -> Self {
+        Self { n, e }
+    }
+
+    /// Verifies the signature `sig` of the digest `hashed` with the given scheme.
+    pub fn verify<S: SignatureScheme>(&self, scheme: S, hashed: &[u8], sig: &[u8]) -> Result<()> {
+        scheme.verify(self, hashed, sig)
+    }
+}
+
+impl PublicKeyParts for RsaPublicKey {
+    fn n(&self) -> &BigUint {
+        &self.n
+    }
+
+    fn e(&self) -> &BigUint {
+        &self.e
+    }
+}
+
+/// Decodes the `RSAPublicKey` of RFC 8017 in a `SubjectPublicKeyInfo` of `rsaEncryption`, whose
+/// parameters must be NULL.
+impl TryFrom<SubjectPublicKeyInfoRef<'_>> for RsaPublicKey {
+    type Error = spki::Error;
+
+    fn try_from(spki: SubjectPublicKeyInfoRef<'_>) -> spki::Result<Self> {
+        spki.algorithm.assert_algorithm_oid(RSA_ENCRYPTION)?;
+        if spki.algorithm.parameters_any()? != AnyRef::NULL {
+            return Err(spki::Error::KeyMalformed);
+        }
+
+        let bytes = spki
+            .subject_public_key
+            .as_bytes()
+            .ok_or(spki::Error::KeyMalformed)?;
+        let mut reader = SliceReader::new(bytes)?;
+        let (n, e) =
+            reader.sequence(|reader| Ok((UintRef::decode(reader)?, UintRef::decode(reader)?)))?;
+        reader.finish(())?;
+
+        Self::new(
+            BigUint::from_bytes_be(n.as_bytes()),
+            BigUint::from_bytes_be(e.as_bytes()),
+        )
+        .map_err(|_| spki::Error::KeyMalformed)
+    }
+}
+
+/// Turns the result of a verification with [`sp1_lib::rsa`] into that of the crate. `sp1-lib`
+/// only returns an error for a key that [`RsaPublicKey::new`] rejects.
+pub(crate) fn to_result(verified: core::result::Result<bool, sp1_lib::error::Error>) -> Result<()> {
+    match verified {
+        Ok(true) => Ok(()),
+        _ => Err(Error::Verification),
+    }
+}
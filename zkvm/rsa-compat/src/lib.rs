@@ -0,0 +1,241 @@
+//! The public key API of the [`rsa`](https://docs.rs/rsa/0.9) crate, backed by the bigmod
+//! arithmetic of `sp1-lib`.
+//!
+//! Code written against `rsa` can use this crate instead with a `[patch]` entry:
+//!
+//! ```toml
+//! [patch.crates-io]
+//! rsa = { git = "https://github.com/succinctlabs/sp1", package = "rsa" }
+//! ```
+//!
+//! Only verification is provided, with the PKCS#1 v1.5 and PSS encodings and for moduli of up to
+//! 4096 bits: [`RsaPublicKey::verify`], and the [`pkcs1v15::VerifyingKey`] and
+//! [`pss::VerifyingKey`] implementations of the [`signature`] traits. They accept the same
+//! signatures as in `rsa`. Keys are decoded from the `SubjectPublicKeyInfo` of X.509 with
+//! [`pkcs8::DecodePublicKey`].
+//!
+//! Every verification goes through [`sp1_lib::rsa`]. Inside the zkVM, the products of the
+//! exponentiation run on the uint256 precompile and are reduced with the quotients hinted by the
+//! bigmod hook; elsewhere, they are computed in software. Private keys, signing, encryption and
+//! the PKCS#1 encodings of keys are not provided.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod errors;
+pub mod pkcs1v15;
+pub mod pss;
+pub mod traits;
+
+mod key;
+
+pub use key::RsaPublicKey;
+pub use num_bigint::BigUint;
+pub use pkcs1v15::Pkcs1v15Sign;
+pub use pkcs8;
+pub use pss::Pss;
+#[cfg(feature = "sha2")]
+pub use sha2;
+pub use signature;
+pub use traits::{PublicKeyParts, SignatureScheme};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use pkcs8::DecodePublicKey;
+    use sha2::{Digest, Sha256, Sha384, Sha512};
+    use signature::hazmat::PrehashVerifier;
+    use signature::{DigestVerifier, Verifier};
+
+    // The key of the RSA example, and signatures of `MESSAGE` made with OpenSSL from its private
+    // key. The PKCS#1 v1.5 one is the signature checked by the example.
+    const PUBLIC_KEY_DER: &[u8] = include_bytes!("../tests/data/rsa2048-pub.der");
+    const MESSAGE: &[u8] = b"Hello world!";
+
+    const PKCS1V15_SHA256: [&str; 8] = [
+        "2079f76d6bf9d2b2ea9588f22287fa7f96e12b89f1278b4eb331a96fc860b7e3",
+        "460f2ee37267a9aa396bd666de0d13d8f1861a7c60ca1db94504cc4edf3d7c29",
+        "b3ff543a2f89f266a1252d1427814337d2a46952d6dfc2c98f7263ed9d2a4932",
+        "afa0915f8af29d5a64aace2750314137cad61113b7f4b8116cab36b2f289d743",
+        "b9c67aea84f0492a7b2ec913c5f8097a105643faedf52bc7413e99a02c6c157d",
+        "c59ae773e126eee58fcb9f41931209e00e2b3a10079402bb615f46ae4495074f",
+        "df7ccf39d6f27e020703c6ca1a88ed6acd0be378a26816a7c07cef27c99d2d55",
+        "93f701f0d9dcda4fee8764162c585f0940e0653936abda06a08961725a202fb8",
+    ];
+
+    // With a salt as long as the digest.
+    const PSS_SHA256: [&str; 8] = [
+        "2162a4405a860e05e3c5e47eec38d615c8462c1264d622f9d56499c10b5f6599",
+        "c79ca82c5fa3f6589fd1ab4581762e5d49f565a3173a55581b077a2150df28a9",
+        "7c355414effcbf20db47915f69ac8a901b6248aef1c43330b91f39a3f288657e",
+        "9c30a1f31804c85c5e2edf5cca48df890f35991582f59705d1355493d9818239",
+        "ce1933d7dca9175a899eda2421365d256eb1aafc5ad4b728dc6193d6a16f4861",
+        "c480a07cf37025821295e715a5c99cca3b6874fbe25398c8999a27c3d2776dd2",
+        "9d1d999c848171ebd43aac12b03ec94212817d97b891a6e6986ead8bf1494322",
+        "fa2c1f26e9ba39965eef973aa25b4c54561c62b359115f3b5d9c94098f454016",
+    ];
+
+    // With a salt of 20 bytes.
+    const PSS_SHA512: [&str; 8] = [
+        "0e521c5fc0f03226f7b64447eb41e86f7b7eeec14f677082739268e8a0c24a59",
+        "8642db2aae1a2cc35ce7e86c98446e22463bc1e346ee568f0ebd7730960dcc8e",
+        "db24cbc7ddfa12585f21c050a9cd1e31dbdf0f8073f4421f4e49d926c45bdc3b",
+        "299af1133f52cee5a54efb86a57f2558ff08f89605ae00421fd50d802a0b88a4",
+        "5e0b5a0a22289c7ad7e85c31006fe2f46d5cee31e2e17318fc67ddb1e2ee1690",
+        "1e77beb3f00c3f2c7a833317db61a19ecda47c6d90729b06eb602232159b1706",
+        "ab4b2e30865d1749d320b483447374622d80b8ffb811c794cb06526af521de39",
+        "f473a9b36ea40946e604c4c3b6f6dc2b3a9b3628508fedf3ac29a230157532d7",
+    ];
+
+    fn public_key() -> RsaPublicKey {
+        RsaPublicKey::from_public_key_der(PUBLIC_KEY_DER).unwrap()
+    }
+
+    fn signature_bytes(chunks: [&str; 8]) -> Vec<u8> {
+        hex::decode(chunks.concat()).unwrap()
+    }
+
+    #[test]
+    fn test_public_key() {
+        let key = public_key();
+        assert_eq!(key.size(), 256);
+        assert_eq!(key.n().bits(), 2048);
+        assert_eq!(*key.e(), BigUint::from(65537u32));
+
+        let n = key.n().clone();
+        let e = key.e().clone();
+        assert_eq!(RsaPublicKey::new(n.clone(), e.clone()), Ok(key));
+        assert_eq!(
+            RsaPublicKey::new_with_max_size(n.clone(), e.clone(), 1024),
+            Err(errors::Error::ModulusTooLarge)
+        );
+        assert_eq!(
+            RsaPublicKey::new(
+                BigUint::from(1u32) << 4096u32 | BigUint::from(1u32),
+                e.clone()
+            ),
+            Err(errors::Error::ModulusTooLarge)
+        );
+        assert_eq!(
+            RsaPublicKey::new(&n + 1u32, e.clone()),
+            Err(errors::Error::InvalidModulus)
+        );
+        assert_eq!(
+            RsaPublicKey::new(n.clone(), BigUint::from(1u32)),
+            Err(errors::Error::PublicExponentTooSmall)
+        );
+        assert_eq!(
+            RsaPublicKey::new(n, BigUint::from(1u64 << 33)),
+            Err(errors::Error::PublicExponentTooLarge)
+        );
+    }
+
+    #[test]
+    fn test_public_key_der_rejects_other_keys() {
+        // The DER is SEQUENCE { SEQUENCE { OID, NULL }, BIT STRING }, and the last arc of the
+        // rsaEncryption OID is its 17th byte.
+        let mut der = PUBLIC_KEY_DER.to_vec();
+        assert_eq!(der[16], 0x01);
+        der[16] = 0x0a;
+        assert!(RsaPublicKey::from_public_key_der(&der).is_err());
+        assert!(RsaPublicKey::from_public_key_der(&PUBLIC_KEY_DER[1..]).is_err());
+    }
+
+    #[test]
+    fn test_verify_pkcs1v15() {
+        let key = pkcs1v15::VerifyingKey::<Sha256>::new(public_key());
+        let bytes = signature_bytes(PKCS1V15_SHA256);
+        let sig = pkcs1v15::Signature::try_from(bytes.as_slice()).unwrap();
+        assert!(key.verify(MESSAGE, &sig).is_ok());
+        assert!(key
+            .verify_digest(Sha256::new_with_prefix(MESSAGE), &sig)
+            .is_ok());
+        assert!(key.verify_prehash(&Sha256::digest(MESSAGE), &sig).is_ok());
+        assert!(public_key()
+            .verify(
+                Pkcs1v15Sign::new::<Sha256>(),
+                &Sha256::digest(MESSAGE),
+                &bytes
+            )
+            .is_ok());
+
+        assert!(key.verify(b"Hello world?", &sig).is_err());
+        assert!(pkcs1v15::VerifyingKey::<Sha512>::new(public_key())
+            .verify(MESSAGE, &sig)
+            .is_err());
+        assert!(
+            pkcs1v15::VerifyingKey::<Sha256>::new_unprefixed(public_key())
+                .verify(MESSAGE, &sig)
+                .is_err()
+        );
+        for tampered in [&bytes[1..], &[bytes.as_slice(), &[0]].concat()] {
+            let sig = pkcs1v15::Signature::try_from(tampered).unwrap();
+            assert!(key.verify(MESSAGE, &sig).is_err());
+        }
+        assert_eq!(
+            public_key().verify(Pkcs1v15Sign::new::<Sha256>(), &[0; 31], &bytes),
+            Err(errors::Error::InputNotHashed)
+        );
+    }
+
+    #[test]
+    fn test_pkcs1v15_prefixes() {
+        assert_eq!(
+            *Pkcs1v15Sign::new::<Sha256>().prefix,
+            sp1_lib::rsa::SHA256_PREFIX
+        );
+        assert_eq!(
+            *Pkcs1v15Sign::new::<Sha384>().prefix,
+            sp1_lib::rsa::SHA384_PREFIX
+        );
+        assert_eq!(
+            *Pkcs1v15Sign::new::<Sha512>().prefix,
+            sp1_lib::rsa::SHA512_PREFIX
+        );
+    }
+
+    #[test]
+    fn test_verify_pss() {
+        let sha256_bytes = signature_bytes(PSS_SHA256);
+        let sha256_sig = pss::Signature::try_from(sha256_bytes.as_slice()).unwrap();
+        let key = pss::VerifyingKey::<Sha256>::new(public_key());
+        assert!(key.verify(MESSAGE, &sha256_sig).is_ok());
+        assert!(key
+            .verify_digest(Sha256::new_with_prefix(MESSAGE), &sha256_sig)
+            .is_ok());
+        assert!(key
+            .verify_prehash(&Sha256::digest(MESSAGE), &sha256_sig)
+            .is_ok());
+        assert!(key.verify(b"Hello world?", &sha256_sig).is_err());
+
+        let sha512_bytes = signature_bytes(PSS_SHA512);
+        let sha512_sig = pss::Signature::try_from(sha512_bytes.as_slice()).unwrap();
+        assert!(
+            pss::VerifyingKey::<Sha512>::new_with_salt_len(public_key(), 20)
+                .verify(MESSAGE, &sha512_sig)
+                .is_ok()
+        );
+        assert!(public_key()
+            .verify(
+                Pss::new_with_salt::<Sha512>(20),
+                &Sha512::digest(MESSAGE),
+                &sha512_bytes
+            )
+            .is_ok());
+
+        // As in `rsa`, the salt must have exactly the length the key expects.
+        assert!(pss::VerifyingKey::<Sha512>::new(public_key())
+            .verify(MESSAGE, &sha512_sig)
+            .is_err());
+        assert!(
+            pss::VerifyingKey::<Sha256>::new_with_salt_len(public_key(), 20)
+                .verify(MESSAGE, &sha256_sig)
+                .is_err()
+        );
+        assert!(pss::VerifyingKey::<Sha512>::new(public_key())
+            .verify(MESSAGE, &sha256_sig)
+            .is_err());
+    }
+}
@@ -0,0 +1,204 @@
+//! RSASSA-PKCS1-v1_5 signatures, of RFC 8017.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use digest::Digest;
+use pkcs8::{spki, AssociatedOid, SubjectPublicKeyInfoRef};
+use signature::hazmat::PrehashVerifier;
+use signature::{DigestVerifier, SignatureEncoding, Verifier};
+
+use crate::errors::{Error, Result};
+use crate::key::to_result;
+use crate::traits::{PublicKeyParts, SignatureScheme};
+use crate::RsaPublicKey;
+
+/// The PKCS#1 v1.5 signature scheme, for [`RsaPublicKey::verify`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pkcs1v15Sign {
+    /// The length of the digest, or `None` to accept digests of any length.
+    pub hash_len: Option<usize>,
+    /// The DER prefix of the `DigestInfo`, which is followed by the digest.
+    pub prefix: Box<[u8]>,
+}
+
+impl Pkcs1v15Sign {
+    /// Creates the scheme for digests of `D`.
+    pub fn new<D: Digest + AssociatedOid>() -> Self {
+        Self {
+            hash_len: Some(<D as Digest>::output_size()),
+            prefix: digest_info_prefix::<D>(),
+        }
+    }
+
+    /// Creates the scheme for raw data, which is signed without a `DigestInfo`.
+    pub fn new_unprefixed() -> Self {
+        Self {
+            hash_len: None,
+            prefix: Box::new([]),
+        }
+    }
+}
+
+impl SignatureScheme for Pkcs1v15Sign {
+    fn verify(self, pub_key: &RsaPublicKey, hashed: &[u8], sig: &[u8]) -> Result<()> {
+        if self.hash_len.is_some_and(|len| len != hashed.len()) {
+            return Err(Error::InputNotHashed);
+        }
+        verify(pub_key, &self.prefix, hashed, sig)
+    }
+}
+
+/// A PKCS#1 v1.5 signature. Its length is only checked when it is verified, and must be that of
+/// the modulus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    bytes: Box<[u8]>,
+}
+
+impl TryFrom<&[u8]> for Signature {
+    type Error = signature::Error;
+
+    fn try_from(bytes: &[u8]) -> signature::Result<Self> {
+        Ok(Self {
+            bytes: bytes.into(),
+        })
+    }
+}
+
+impl From<Signature> for Box<[u8]> {
+    fn from(signature: Signature) -> Self {
+        signature.bytes
+    }
+}
+
+impl SignatureEncoding for Signature {
+    type Repr = Box<[u8]>;
+}
+
+/// A key that verifies PKCS#1 v1.5 signatures of messages hashed with `D`.
+#[derive(Debug)]
+pub struct VerifyingKey<D: Digest> {
+    inner: RsaPublicKey,
+    prefix: Box<[u8]>,
+    phantom: PhantomData<D>,
+}
+
+impl<D: Digest> VerifyingKey<D> {
+    /// Creates a key whose signatures are of a `DigestInfo` of `D`.
+    pub fn new(key: RsaPublicKey) -> Self
+    where
+        D: AssociatedOid,
+    {
+        Self {
+            inner: key,
+            prefix: digest_info_prefix::<D>(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Creates a key whose signatures are of the digest alone, without a `DigestInfo`.
+    pub fn new_unprefixed(key: RsaPublicKey) -> Self {
+        Self {
+            inner: key,
+            prefix: Box::new([]),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<D: Digest> Verifier<Signature> for VerifyingKey<D> {
+    fn verify(&self, msg: &[u8], signature: &Signature) -> signature::Result<()> {
+        Ok(verify(
+            &self.inner,
+            &self.prefix,
+            &D::digest(msg),
+            &signature.bytes,
+        )?)
+    }
+}
+
+impl<D: Digest> DigestVerifier<D, Signature> for VerifyingKey<D> {
+    fn verify_digest(&self, digest: D, signature: &Signature) -> signature::Result<()> {
+        Ok(verify(
+            &self.inner,
+            &self.prefix,
+            &digest.finalize(),
+            &signature.bytes,
+        )?)
+    }
+}
+
+impl<D: Digest> PrehashVerifier<Signature> for VerifyingKey<D> {
+    fn verify_prehash(&self, prehash: &[u8], signature: &Signature) -> signature::Result<()> {
+        Ok(verify(
+            &self.inner,
+            &self.prefix,
+            prehash,
+            &signature.bytes,
+        )?)
+    }
+}
+
+// Implemented by hand, as a derive would require `D: Clone`.
+impl<D: Digest> Clone for VerifyingKey<D> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            prefix: self.prefix.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<D: Digest> AsRef<RsaPublicKey> for VerifyingKey<D> {
+    fn as_ref(&self) -> &RsaPublicKey {
+        &self.inner
+    }
+}
+
+impl<D: Digest> From<VerifyingKey<D>> for RsaPublicKey {
+    fn from(key: VerifyingKey<D>) -> Self {
+        key.inner
+    }
+}
+
+impl<D: Digest + AssociatedOid> TryFrom<SubjectPublicKeyInfoRef<'_>> for VerifyingKey<D> {
+    type Error = spki::Error;
+
+    fn try_from(spki: SubjectPublicKeyInfoRef<'_>) -> spki::Result<Self> {
+        RsaPublicKey::try_from(spki).map(Self::new)
+    }
+}
+
+fn verify(pub_key: &RsaPublicKey, prefix: &[u8], hashed: &[u8], sig: &[u8]) -> Result<()> {
+    to_result(sp1_lib::rsa::verify_pkcs1v15(
+        &pub_key.n().to_bytes_be(),
+        &pub_key.e().to_bytes_be(),
+        prefix,
+        hashed,
+        sig,
+    ))
+}
+
+/// Returns the DER prefix of the `DigestInfo` of a digest of `D`, which is
+/// `SEQUENCE { SEQUENCE { OID, NULL }, OCTET STRING }` without the contents of the octet string.
+fn digest_info_prefix<D: Digest + AssociatedOid>() -> Box<[u8]> {
+    let oid = D::OID.as_bytes();
+    let oid_len = oid.len() as u8;
+    let hash_len = <D as Digest>::output_size() as u8;
+
+    let mut prefix = Vec::with_capacity(oid.len() + 10);
+    prefix.extend_from_slice(&[
+        0x30,
+        oid_len + 8 + hash_len,
+        0x30,
+        oid_len + 4,
+        0x06,
+        oid_len,
+    ]);
+    prefix.extend_from_slice(oid);
+    prefix.extend_from_slice(&[0x05, 0x00, 0x04, hash_len]);
+    prefix.into_boxed_slice()
+}
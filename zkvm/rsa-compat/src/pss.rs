@@ -0,0 +1,171 @@
+//! RSASSA-PSS signatures, of RFC 8017, with MGF1 over the hash of the message.
+
+use alloc::boxed::Box;
+use core::fmt;
+use core::marker::PhantomData;
+
+use digest::Digest;
+use pkcs8::{spki, SubjectPublicKeyInfoRef};
+use signature::hazmat::PrehashVerifier;
+use signature::{DigestVerifier, SignatureEncoding, Verifier};
+
+use crate::errors::Result;
+use crate::key::to_result;
+use crate::traits::{PublicKeyParts, SignatureScheme};
+use crate::RsaPublicKey;
+
+/// The verification function of `sp1-lib` for a given hash.
+type VerifyFn = fn(
+    &[u8],
+    &[u8],
+    &[u8],
+    &[u8],
+    Option<usize>,
+) -> core::result::Result<bool, sp1_lib::error::Error>;
+
+/// The PSS signature scheme, for [`RsaPublicKey::verify`].
+#[derive(Clone, Copy)]
+pub struct Pss {
+    /// The length of the salt, which signatures must have.
+    pub salt_len: usize,
+    verify: VerifyFn,
+}
+
+impl Pss {
+    /// Creates the scheme for digests of `D`, with a salt as long as the digest.
+    pub fn new<D: Digest>() -> Self {
+        Self::new_with_salt::<D>(<D as Digest>::output_size())
+    }
+
+    /// Creates the scheme for digests of `D`, with a salt of `len` bytes.
+    pub fn new_with_salt<D: Digest>(len: usize) -> Self {
+        Self {
+            salt_len: len,
+            verify: sp1_lib::rsa::verify_pss::<D>,
+        }
+    }
+}
+
+impl fmt::Debug for Pss {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pss")
+            .field("salt_len", &self.salt_len)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SignatureScheme for Pss {
+    fn verify(self, pub_key: &RsaPublicKey, hashed: &[u8], sig: &[u8]) -> Result<()> {
+        to_result((self.verify)(
+            &pub_key.n().to_bytes_be(),
+            &pub_key.e().to_bytes_be(),
+            hashed,
+            sig,
+            Some(self.salt_len),
+        ))
+    }
+}
+
+/// A PSS signature. Its length is only checked when it is verified, and must be that of the
+/// modulus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    bytes: Box<[u8]>,
+}
+
+impl TryFrom<&[u8]> for Signature {
+    type Error = signature::Error;
+
+    fn try_from(bytes: &[u8]) -> signature::Result<Self> {
+        Ok(Self {
+            bytes: bytes.into(),
+        })
+    }
+}
+
+impl From<Signature> for Box<[u8]> {
+    fn from(signature: Signature) -> Self {
+        signature.bytes
+    }
+}
+
+impl SignatureEncoding for Signature {
+    type Repr = Box<[u8]>;
+}
+
+/// A key that verifies PSS signatures of messages hashed with `D`.
+#[derive(Debug)]
+pub struct VerifyingKey<D: Digest> {
+    inner: RsaPublicKey,
+    salt_len: usize,
+    phantom: PhantomData<D>,
+}
+
+impl<D: Digest> VerifyingKey<D> {
+    /// Creates a key whose signatures have a salt as long as a digest of `D`.
+    pub fn new(key: RsaPublicKey) -> Self {
+        Self::new_with_salt_len(key, <D as Digest>::output_size())
+    }
+
+    /// Creates a key whose signatures have a salt of `salt_len` bytes.
+    pub fn new_with_salt_len(key: RsaPublicKey, salt_len: usize) -> Self {
+        Self {
+            inner: key,
+            salt_len,
+            phantom: PhantomData,
+        }
+    }
+
+    fn verify_hashed(&self, hashed: &[u8], signature: &Signature) -> Result<()> {
+        Pss::new_with_salt::<D>(self.salt_len).verify(&self.inner, hashed, &signature.bytes)
+    }
+}
+
+impl<D: Digest> Verifier<Signature> for VerifyingKey<D> {
+    fn verify(&self, msg: &[u8], signature: &Signature) -> signature::Result<()> {
+        Ok(self.verify_hashed(&D::digest(msg), signature)?)
+    }
+}
+
+impl<D: Digest> DigestVerifier<D, Signature> for VerifyingKey<D> {
+    fn verify_digest(&self, digest: D, signature: &Signature) -> signature::Result<()> {
+        Ok(self.verify_hashed(&digest.finalize(), signature)?)
+    }
+}
+
+impl<D: Digest> PrehashVerifier<Signature> for VerifyingKey<D> {
+    fn verify_prehash(&self, prehash: &[u8], signature: &Signature) -> signature::Result<()> {
+        Ok(self.verify_hashed(prehash, signature)?)
+    }
+}
+
+// Implemented by hand, as a derive would require `D: Clone`.
+impl<D: Digest> Clone for VerifyingKey<D> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            salt_len: self.salt_len,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<D: Digest> AsRef<RsaPublicKey> for VerifyingKey<D> {
+    fn as_ref(&self) -> &RsaPublicKey {
+        &self.inner
+    }
+}
+
+impl<D: Digest> From<VerifyingKey<D>> for RsaPublicKey {
+    fn from(key: VerifyingKey<D>) -> Self {
+        key.inner
+    }
+}
+
+impl<D: Digest> TryFrom<SubjectPublicKeyInfoRef<'_>> for VerifyingKey<D> {
+    type Error = spki::Error;
+
+    fn try_from(spki: SubjectPublicKeyInfoRef<'_>) -> spki::Result<Self> {
+        RsaPublicKey::try_from(spki).map(Self::new)
+    }
+}
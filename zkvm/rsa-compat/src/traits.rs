@@ -0,0 +1,26 @@
+//! The traits of public keys and of signature schemes.
+
+use num_bigint::BigUint;
+
+use crate::errors::Result;
+use crate::RsaPublicKey;
+
+/// The components of a public key.
+pub trait PublicKeyParts {
+    /// Returns the modulus.
+    fn n(&self) -> &BigUint;
+
+    /// Returns the public exponent.
+    fn e(&self) -> &BigUint;
+
+    /// Returns the length of the modulus in bytes, which is the length of its signatures.
+    fn size(&self) -> usize {
+        (self.n().bits() as usize + 7) / 8
+    }
+}
+
+/// A signature scheme, for [`RsaPublicKey::verify`]. Unlike in `rsa`, signing is not part of it.
+pub trait SignatureScheme {
+    /// Verifies the signature `sig` of the digest `hashed` with `pub_key`.
+    fn verify(self, pub_key: &RsaPublicKey, hashed: &[u8], sig: &[u8]) -> Result<()>;
+}
@@ -0,0 +1,118 @@
+//! Verifies the self-signatures of real root certificates, as an X.509 stack does: the key is
+//! decoded from the `SubjectPublicKeyInfo`, and the signature of the `tbsCertificate` is checked
+//! with the hash that the signature algorithm names.
+//!
+//! The certificates are ISRG Root X1 and GlobalSign Root R6, which have 4096-bit keys and are
+//! signed with SHA-256 and SHA-384 respectively.
+
+use rsa::pkcs1v15::{Signature, VerifyingKey};
+use rsa::pkcs8::der::asn1::BitStringRef;
+use rsa::pkcs8::der::{Decode, Reader, SliceReader};
+use rsa::pkcs8::{AlgorithmIdentifierRef, DecodePublicKey, ObjectIdentifier};
+use rsa::signature::Verifier;
+use rsa::{Pkcs1v15Sign, PublicKeyParts, RsaPublicKey};
+use sha2::{Digest, Sha256, Sha384};
+
+const ISRG_ROOT_X1: &[u8] = include_bytes!("data/isrg-root-x1.der");
+const GLOBALSIGN_ROOT_R6: &[u8] = include_bytes!("data/globalsign-root-r6.der");
+
+const SHA256_WITH_RSA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.11");
+const SHA384_WITH_RSA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.12");
+
+/// The parts of a certificate which its signature covers and is checked with.
+struct Certificate<'a> {
+    tbs: &'a [u8],
+    spki: &'a [u8],
+    algorithm: ObjectIdentifier,
+    signature: &'a [u8],
+}
+
+impl<'a> Certificate<'a> {
+    /// Splits a v3 certificate, `SEQUENCE { tbsCertificate, signatureAlgorithm, signature }`.
+    fn parse(der: &'a [u8]) -> Self {
+        let mut reader = SliceReader::new(der).unwrap();
+        let (tbs, algorithm, signature) = reader
+            .sequence(|cert| {
+                let tbs = cert.tlv_bytes()?;
+                let algorithm = AlgorithmIdentifierRef::decode(cert)?.oid;
+                let signature = BitStringRef::decode(cert)?.raw_bytes();
+                Ok((tbs, algorithm, signature))
+            })
+            .unwrap();
+        reader.finish(()).unwrap();
+
+        // The key follows the version, serial number, signature algorithm, issuer, validity and
+        // subject, and is followed by the extensions.
+        let spki = SliceReader::new(tbs)
+            .unwrap()
+            .sequence(|tbs| {
+                let fields = (0..7)
+                    .map(|_| tbs.tlv_bytes())
+                    .collect::<Result<Vec<_>, _>>()?;
+                while !tbs.is_finished() {
+                    tbs.tlv_bytes()?;
+                }
+                Ok(fields[6])
+            })
+            .unwrap();
+
+        Self {
+            tbs,
+            spki,
+            algorithm,
+            signature,
+        }
+    }
+}
+
+#[test]
+fn test_verify_isrg_root_x1() {
+    let cert = Certificate::parse(ISRG_ROOT_X1);
+    assert_eq!(cert.algorithm, SHA256_WITH_RSA);
+
+    let key = VerifyingKey::<Sha256>::from_public_key_der(cert.spki).unwrap();
+    assert_eq!(key.as_ref().size(), 512);
+    let signature = Signature::try_from(cert.signature).unwrap();
+    assert!(key.verify(cert.tbs, &signature).is_ok());
+
+    let key = RsaPublicKey::from_public_key_der(cert.spki).unwrap();
+    let hashed = Sha256::digest(cert.tbs);
+    assert!(key
+        .verify(Pkcs1v15Sign::new::<Sha256>(), &hashed, cert.signature)
+        .is_ok());
+}
+
+#[test]
+fn test_verify_globalsign_root_r6() {
+    let cert = Certificate::parse(GLOBALSIGN_ROOT_R6);
+    assert_eq!(cert.algorithm, SHA384_WITH_RSA);
+
+    let key = VerifyingKey::<Sha384>::from_public_key_der(cert.spki).unwrap();
+    assert_eq!(key.as_ref().size(), 512);
+    let signature = Signature::try_from(cert.signature).unwrap();
+    assert!(key.verify(cert.tbs, &signature).is_ok());
+}
+
+#[test]
+fn test_reject_mismatched_certificates() {
+    let isrg = Certificate::parse(ISRG_ROOT_X1);
+    let globalsign = Certificate::parse(GLOBALSIGN_ROOT_R6);
+    let isrg_key = VerifyingKey::<Sha256>::from_public_key_der(isrg.spki).unwrap();
+    let isrg_signature = Signature::try_from(isrg.signature).unwrap();
+
+    // A modified tbsCertificate.
+    let mut tbs = isrg.tbs.to_vec();
+    let last = tbs.len() - 1;
+    tbs[last] ^= 1;
+    assert!(isrg_key.verify(&tbs, &isrg_signature).is_err());
+
+    // The signature of another certificate, and the key of another issuer.
+    let globalsign_signature = Signature::try_from(globalsign.signature).unwrap();
+    assert!(isrg_key.verify(isrg.tbs, &globalsign_signature).is_err());
+    let globalsign_key = VerifyingKey::<Sha256>::from_public_key_der(globalsign.spki).unwrap();
+    assert!(globalsign_key.verify(isrg.tbs, &isrg_signature).is_err());
+
+    // Another hash than the one of the signature algorithm.
+    let sha384_key = VerifyingKey::<Sha384>::from_public_key_der(isrg.spki).unwrap();
+    assert!(sha384_key.verify(isrg.tbs, &isrg_signature).is_err());
+}
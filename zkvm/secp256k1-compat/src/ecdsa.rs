@@ -0,0 +1,108 @@
+//! ECDSA signatures, with and without a recovery id.
+
+use sp1_lib::error::Error as Sp1Error;
+use sp1_lib::secp256k1::{ecrecover, try_verify_signature};
+
+use crate::{Error, Message, PublicKey, COMPACT_SIGNATURE_SIZE};
+
+/// An ECDSA signature.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Signature(k256::ecdsa::Signature);
+
+impl Signature {
+    /// Parses a 64-byte `r || s` signature.
+    pub fn from_compact(data: &[u8]) -> Result<Signature, Error> {
+        if data.len() != COMPACT_SIGNATURE_SIZE {
+            return Err(Error::InvalidSignature);
+        }
+        k256::ecdsa::Signature::from_slice(data)
+            .map(Signature)
+            .map_err(|_| Error::InvalidSignature)
+    }
+
+    /// Returns the 64-byte `r || s` encoding of the signature.
+    pub fn serialize_compact(&self) -> [u8; COMPACT_SIGNATURE_SIZE] {
+        self.0.to_bytes().into()
+    }
+
+    /// Replaces a high `s` by its negation, so that the signature is accepted by
+    /// [`Secp256k1::verify_ecdsa`](crate::Secp256k1::verify_ecdsa).
+    pub fn normalize_s(&mut self) {
+        if let Some(normalized) = self.0.normalize_s() {
+            self.0 = normalized;
+        }
+    }
+}
+
+/// The recovery id of a signature, from 0 to 3.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RecoveryId(i32);
+
+impl RecoveryId {
+    /// Creates a recovery id, which must be in the range 0 to 3.
+    pub fn from_i32(id: i32) -> Result<RecoveryId, Error> {
+        match id {
+            0..=3 => Ok(RecoveryId(id)),
+            _ => Err(Error::InvalidRecoveryId),
+        }
+    }
+
+    /// Returns the recovery id as an integer.
+    pub fn to_i32(self) -> i32 {
+        self.0
+    }
+}
+
+/// An ECDSA signature with the recovery id of its public key.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RecoverableSignature {
+    signature: Signature,
+    recovery_id: RecoveryId,
+}
+
+impl RecoverableSignature {
+    /// Parses a 64-byte `r || s` signature with its recovery id.
+    pub fn from_compact(data: &[u8], recid: RecoveryId) -> Result<RecoverableSignature, Error> {
+        Ok(RecoverableSignature {
+            signature: Signature::from_compact(data)?,
+            recovery_id: recid,
+        })
+    }
+
+    /// Returns the recovery id and the 64-byte `r || s` encoding of the signature.
+    pub fn serialize_compact(&self) -> (RecoveryId, [u8; COMPACT_SIGNATURE_SIZE]) {
+        (self.recovery_id, self.signature.serialize_compact())
+    }
+
+    /// Drops the recovery id.
+    pub fn to_standard(&self) -> Signature {
+        self.signature
+    }
+}
+
+pub(crate) fn verify(msg: &Message, sig: &Signature, pk: &PublicKey) -> Result<(), Error> {
+    // libsecp256k1 rejects malleable signatures.
+    if sig.0.normalize_s().is_some() {
+        return Err(Error::IncorrectSignature);
+    }
+
+    match try_verify_signature(pk.as_uncompressed(), &msg.0, &sig.0, None) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(Error::IncorrectSignature),
+        Err(Sp1Error::InvalidPublicKey) => Err(Error::InvalidPublicKey),
+        Err(_) => Err(Error::IncorrectSignature),
+    }
+}
+
+pub(crate) fn recover(msg: &Message, sig: &RecoverableSignature) -> Result<PublicKey, Error> {
+    let mut sig65 = [0u8; 65];
+    sig65[..64].copy_from_slice(&sig.signature.serialize_compact());
+    sig65[64] = sig.recovery_id.0 as u8;
+
+    match ecrecover(&sig65, &msg.0) {
+        Ok(pubkey) => Ok(PublicKey::from_uncompressed_unchecked(pubkey)),
+        // Recovery ids 2 and 3, for nonce points with x >= n, are not supported by `ecrecover`,
+        // and such signatures are rejected like unrecoverable ones.
+        Err(_) => Err(Error::InvalidSignature),
+    }
+}
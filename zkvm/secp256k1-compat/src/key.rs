@@ -0,0 +1,39 @@
+use sp1_lib::secp256k1::parse_sec1_pubkey;
+
+use crate::{Error, PUBLIC_KEY_SIZE, UNCOMPRESSED_PUBLIC_KEY_SIZE};
+
+/// A secp256k1 public key, kept in its uncompressed SEC1 encoding.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PublicKey([u8; UNCOMPRESSED_PUBLIC_KEY_SIZE]);
+
+impl PublicKey {
+    /// Parses a compressed, uncompressed or hybrid SEC1 encoded public key.
+    pub fn from_slice(data: &[u8]) -> Result<PublicKey, Error> {
+        parse_sec1_pubkey(data)
+            .map(PublicKey)
+            .map_err(|_| Error::InvalidPublicKey)
+    }
+
+    /// Returns the compressed SEC1 encoding of the public key.
+    pub fn serialize(&self) -> [u8; PUBLIC_KEY_SIZE] {
+        let mut compressed = [0u8; PUBLIC_KEY_SIZE];
+        compressed[0] = 2 | (self.0[64] & 1);
+        compressed[1..].copy_from_slice(&self.0[1..33]);
+        compressed
+    }
+
+    /// Returns the uncompressed SEC1 encoding of the public key.
+    pub fn serialize_uncompressed(&self) -> [u8; UNCOMPRESSED_PUBLIC_KEY_SIZE] {
+        self.0
+    }
+
+    pub(crate) fn from_uncompressed_unchecked(
+        uncompressed: [u8; UNCOMPRESSED_PUBLIC_KEY_SIZE],
+    ) -> Self {
+        PublicKey(uncompressed)
+    }
+
+    pub(crate) fn as_uncompressed(&self) -> &[u8; UNCOMPRESSED_PUBLIC_KEY_SIZE] {
+        &self.0
+    }
+}
@@ -0,0 +1,196 @@
+//! A subset of the [`secp256k1`](https://docs.rs/secp256k1/0.29) crate API, backed by the
+//! precompiles of `sp1-lib`.
+//!
+//! Code written against the `secp256k1` crate can use this crate instead with a `[patch]` entry:
+//!
+//! ```toml
+//! [patch.crates-io]
+//! secp256k1 = { git = "https://github.com/succinctlabs/sp1", package = "secp256k1" }
+//! ```
+//!
+//! Only verification and recovery of ECDSA signatures is provided. Signing, key tweaking, ECDH,
+//! DER encoded signatures and Schnorr signatures are not.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use core::fmt;
+use core::marker::PhantomData;
+
+pub mod ecdsa;
+mod key;
+
+pub use key::PublicKey;
+
+/// The size of a compressed public key, in bytes.
+pub const PUBLIC_KEY_SIZE: usize = 33;
+
+/// The size of an uncompressed public key, in bytes.
+pub const UNCOMPRESSED_PUBLIC_KEY_SIZE: usize = 65;
+
+/// The size of a compact signature, in bytes.
+pub const COMPACT_SIGNATURE_SIZE: usize = 64;
+
+/// The size of a message digest, in bytes.
+pub const MESSAGE_SIZE: usize = 32;
+
+/// An error returned by the `secp256k1` API.
+///
+/// The variants are those of `secp256k1::Error`, including the ones this crate never returns.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Error {
+    /// The signature does not verify against the message and public key.
+    IncorrectSignature,
+    /// The message is not 32 bytes long.
+    InvalidMessage,
+    /// The public key is not a valid encoding of a point on the curve.
+    InvalidPublicKey,
+    /// The signature is not a valid encoding, or no public key can be recovered from it.
+    InvalidSignature,
+    /// The secret key is not a valid scalar.
+    InvalidSecretKey,
+    /// The shared secret is not valid.
+    InvalidSharedSecret,
+    /// The recovery id is not in the range 0 to 3.
+    InvalidRecoveryId,
+    /// The tweak is not a valid scalar.
+    InvalidTweak,
+    /// Not enough memory to create a context.
+    NotEnoughMemory,
+    /// The public keys sum to the point at infinity.
+    InvalidPublicKeySum,
+    /// The parity value is not 0 or 1.
+    InvalidParityValue(InvalidParityValue),
+    /// The ElligatorSwift encoding is not valid.
+    InvalidEllSwift,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::IncorrectSignature => f.write_str("signature failed verification"),
+            Error::InvalidMessage => f.write_str("message was not 32 bytes (do you need to hash?)"),
+            Error::InvalidPublicKey => f.write_str("malformed public key"),
+            Error::InvalidSignature => f.write_str("malformed signature"),
+            Error::InvalidSecretKey => f.write_str("malformed or out-of-range secret key"),
+            Error::InvalidSharedSecret => f.write_str("malformed or out-of-range shared secret"),
+            Error::InvalidRecoveryId => f.write_str("bad recovery id"),
+            Error::InvalidTweak => f.write_str("bad tweak"),
+            Error::NotEnoughMemory => f.write_str("not enough memory allocated"),
+            Error::InvalidPublicKeySum => f.write_str(
+                "the sum of public keys was invalid or the input vector lengths was less than 1",
+            ),
+            Error::InvalidParityValue(err) => write!(f, "{err}"),
+            Error::InvalidEllSwift => f.write_str("malformed EllSwift value"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// The error of a parity value that is not 0 or 1.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InvalidParityValue(i32);
+
+impl fmt::Display for InvalidParityValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid value {} for Parity - must be 0 or 1", self.0)
+    }
+}
+
+/// A 32-byte message digest to be verified.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Message([u8; MESSAGE_SIZE]);
+
+impl Message {
+    /// Creates a message from a 32-byte digest.
+    pub fn from_digest(digest: [u8; MESSAGE_SIZE]) -> Message {
+        Message(digest)
+    }
+
+    /// Creates a message from a digest, which must be 32 bytes long.
+    pub fn from_digest_slice(digest: &[u8]) -> Result<Message, Error> {
+        digest
+            .try_into()
+            .map(Message)
+            .map_err(|_| Error::InvalidMessage)
+    }
+}
+
+impl AsRef<[u8]> for Message {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// The capabilities of a context.
+pub trait Context {}
+
+/// A context that can verify signatures.
+pub trait Verification: Context {}
+
+/// A context that can only verify signatures.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VerifyOnly {}
+
+/// A context with all capabilities. Only verification is supported by this crate.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum All {}
+
+impl Context for VerifyOnly {}
+impl Verification for VerifyOnly {}
+impl Context for All {}
+impl Verification for All {}
+
+/// The entry point of the API. No state is kept, since the precompiles need no precomputation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Secp256k1<C: Context> {
+    _marker: PhantomData<C>,
+}
+
+impl Secp256k1<All> {
+    /// Creates a context with all capabilities.
+    pub fn new() -> Self {
+        Self {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl Default for Secp256k1<All> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Secp256k1<VerifyOnly> {
+    /// Creates a context that can only verify signatures.
+    pub fn verification_only() -> Self {
+        Self {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<C: Verification> Secp256k1<C> {
+    /// Checks that `sig` is a valid signature of `msg` by `pk`.
+    ///
+    /// As in libsecp256k1, only signatures with a low `s` are accepted.
+    pub fn verify_ecdsa(
+        &self,
+        msg: &Message,
+        sig: &ecdsa::Signature,
+        pk: &PublicKey,
+    ) -> Result<(), Error> {
+        ecdsa::verify(msg, sig, pk)
+    }
+
+    /// Recovers the public key that produced `sig` over `msg`.
+    pub fn recover_ecdsa(
+        &self,
+        msg: &Message,
+        sig: &ecdsa::RecoverableSignature,
+    ) -> Result<PublicKey, Error> {
+        ecdsa::recover(msg, sig)
+    }
+}